@@ -282,6 +282,55 @@ fn deleting_songs_in_batches_default(c: &mut Criterion) {
     });
 }
 
+fn looking_up_songs_external_ids(c: &mut Criterion) {
+    let primary_key = "id";
+    let searchable_fields = ["title", "album", "artist"];
+    let filterable_fields = [];
+    let sortable_fields = [];
+
+    let index = setup_index_with_settings(
+        &primary_key,
+        &searchable_fields,
+        &filterable_fields,
+        &sortable_fields,
+    );
+
+    let config = IndexerConfig::default();
+    let mut wtxn = index.write_txn().unwrap();
+    let indexing_config = IndexDocumentsConfig::default();
+    let builder =
+        IndexDocuments::new(&mut wtxn, &index, &config, indexing_config, |_| (), || false).unwrap();
+    let documents = utils::documents_from(datasets_paths::SMOL_SONGS, "csv");
+    let (builder, user_error) = builder.add_documents(documents).unwrap();
+    user_error.unwrap();
+    builder.execute().unwrap();
+    wtxn.commit().unwrap();
+
+    let rtxn = index.read_txn().unwrap();
+    let external_documents_ids = index.external_documents_ids(&rtxn).unwrap();
+    // the transform looks the external ids of the documents up, sorted by chunks.
+    let external_ids: Vec<_> = external_documents_ids.to_hash_map().into_keys().collect();
+    let mut sorted_external_ids = external_ids.clone();
+    sorted_external_ids.sort_unstable();
+
+    let mut group = c.benchmark_group("indexing");
+    group.bench_function("Looking up the songs external ids one by one", |b| {
+        b.iter(|| external_ids.iter().map(|id| external_documents_ids.get(id)).collect::<Vec<_>>())
+    });
+    group.bench_function("Looking up the songs external ids by sorted chunks", |b| {
+        b.iter(|| {
+            sorted_external_ids
+                .chunks(100_000)
+                .map(|chunk| external_documents_ids.get_many(chunk).unwrap())
+                .collect::<Vec<_>>()
+        })
+    });
+    group.finish();
+
+    drop(rtxn);
+    index.prepare_for_closing().wait();
+}
+
 fn indexing_songs_in_three_batches_default(c: &mut Criterion) {
     let mut group = c.benchmark_group("indexing");
     group.sample_size(BENCHMARK_ITERATION);
@@ -499,6 +548,61 @@ fn indexing_wiki(c: &mut Criterion) {
     });
 }
 
+fn indexing_wiki_with_detected_languages(c: &mut Criterion) {
+    let mut group = c.benchmark_group("indexing");
+    group.sample_size(BENCHMARK_ITERATION);
+    group.bench_function("Indexing wiki with a detected languages allow list", |b| {
+        b.iter_with_setup(
+            move || {
+                let primary_key = "id";
+                let searchable_fields = ["title", "body"];
+                let filterable_fields = [];
+                let sortable_fields = [];
+
+                let index = setup_index_with_settings(
+                    &primary_key,
+                    &searchable_fields,
+                    &filterable_fields,
+                    &sortable_fields,
+                );
+
+                let config = IndexerConfig::default();
+                let mut wtxn = index.write_txn().unwrap();
+                let mut builder = Settings::new(&mut wtxn, &index, &config);
+                let languages = vec!["eng".to_string(), "fra".to_string()];
+                builder.set_detected_languages_allow_list(languages);
+                builder.execute(|_| (), || false).unwrap();
+                wtxn.commit().unwrap();
+
+                index
+            },
+            move |index| {
+                let config = IndexerConfig::default();
+                let indexing_config =
+                    IndexDocumentsConfig { autogenerate_docids: true, ..Default::default() };
+                let mut wtxn = index.write_txn().unwrap();
+                let builder = IndexDocuments::new(
+                    &mut wtxn,
+                    &index,
+                    &config,
+                    indexing_config,
+                    |_| (),
+                    || false,
+                )
+                .unwrap();
+
+                let documents = utils::documents_from(datasets_paths::SMOL_WIKI_ARTICLES, "csv");
+                let (builder, user_error) = builder.add_documents(documents).unwrap();
+                user_error.unwrap();
+                builder.execute().unwrap();
+                wtxn.commit().unwrap();
+
+                index.prepare_for_closing().wait();
+            },
+        )
+    });
+}
+
 fn reindexing_wiki(c: &mut Criterion) {
     let mut group = c.benchmark_group("indexing");
     group.sample_size(BENCHMARK_ITERATION);
@@ -1359,10 +1463,12 @@ criterion_group!(
     indexing_songs_default,
     reindexing_songs_default,
     deleting_songs_in_batches_default,
+    looking_up_songs_external_ids,
     indexing_songs_without_faceted_numbers,
     indexing_songs_without_faceted_fields,
     indexing_songs_in_three_batches_default,
     indexing_wiki,
+    indexing_wiki_with_detected_languages,
     reindexing_wiki,
     deleting_wiki_in_batches_default,
     indexing_wiki_in_three_batches,
@@ -187,6 +187,20 @@ fn bench_songs(c: &mut criterion::Criterion) {
             ],
             ..BASE_CONF
         },
+
+        /* the same queries as the "prefix search" group, with a bounded number of ranked candidates */
+        utils::Conf {
+            group_name: "prefix search with max candidates",
+            queries: &[
+                "s", // 500k+ results
+                "a", //
+                "b", //
+                "i", //
+                "x", // only 7k results
+            ],
+            max_candidates: Some(1000),
+            ..BASE_CONF
+        },
     ];
 
     utils::run_benches(c, confs);
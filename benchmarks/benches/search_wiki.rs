@@ -87,6 +87,16 @@ fn bench_songs(c: &mut criterion::Criterion) {
             criterion: Some(&["words"]),
             ..BASE_CONF
         },
+        utils::Conf {
+            group_name: "exactness",
+            queries: &[
+                "the black saint and the sinner lady and the ",         // nine words
+                "american football player born in the new york city ", // nine words
+                "history of the roman empire in the third century ",   // nine words
+            ],
+            criterion: Some(&["exactness"]),
+            ..BASE_CONF
+        },
         /* the we bench some global / normal search with all the default criterion in the default
          * order */
         utils::Conf {
@@ -37,6 +37,8 @@ pub struct Conf<'a> {
     pub optional_words: bool,
     /// primary key, if there is None we'll auto-generate docids for every documents
     pub primary_key: Option<&'a str>,
+    /// bound the number of candidates ranked by the costly criteria
+    pub max_candidates: Option<usize>,
 }
 
 impl Conf<'_> {
@@ -52,6 +54,7 @@ impl Conf<'_> {
         sort: None,
         optional_words: true,
         primary_key: None,
+        max_candidates: None,
     };
 }
 
@@ -130,6 +133,9 @@ pub fn run_benches(c: &mut criterion::Criterion, confs: &[Conf]) {
                         let sort = sort.iter().map(|sort| sort.parse().unwrap()).collect();
                         search.sort_criteria(sort);
                     }
+                    if let Some(max_candidates) = conf.max_candidates {
+                        search.set_max_candidates(max_candidates);
+                    }
                     let _ids = search.execute().unwrap();
                 });
             });
@@ -1,4 +1,5 @@
 use std::fmt::Display;
+use std::ops::Range;
 
 use nom::error::{self, ParseError};
 use nom::Parser;
@@ -84,6 +85,35 @@ impl<'a> Error<'a> {
         &self.context
     }
 
+    /// The byte range of the incriminated part of the filter.
+    pub fn span(&self) -> Range<usize> {
+        let start = self.context.location_offset();
+        start..start + self.context.fragment().len()
+    }
+
+    /// The line, starting at 1, where the incriminated part of the filter begins.
+    pub fn line(&self) -> u32 {
+        self.context.location_line()
+    }
+
+    /// The column, counted in characters and starting at 1, where the incriminated
+    /// part of the filter begins.
+    pub fn column(&self) -> usize {
+        self.context.get_utf8_column()
+    }
+
+    /// The line and column where the incriminated part of the filter ends.
+    fn end_position(&self) -> (u32, usize) {
+        let fragment = self.context.fragment();
+        match fragment.rsplit_once('\n') {
+            Some((before, last_line)) => {
+                let lines = before.matches('\n').count() as u32 + 1;
+                (self.line() + lines, last_line.chars().count() + 1)
+            }
+            None => (self.line(), self.column() + fragment.chars().count()),
+        }
+    }
+
     pub fn new_from_kind(context: Span<'a>, kind: ErrorKind<'a>) -> Self {
         Self { context, kind }
     }
@@ -190,9 +220,22 @@ impl<'a> Display for Error<'a> {
             )?,
             ErrorKind::External(ref error) => writeln!(f, "{}", error)?,
         }
-        let base_column = self.context.get_utf8_column();
-        let size = self.context.fragment().chars().count();
+        let (end_line, end_column) = self.end_position();
 
-        write!(f, "{}:{} {}", base_column, base_column + size, self.context.extra)
+        // A multi-line filter would break our two lines format, we escape it and give
+        // the lines along with the columns to locate the incriminated part.
+        if self.context.extra.contains('\n') {
+            write!(
+                f,
+                "{}:{}-{}:{} {}",
+                self.line(),
+                self.column(),
+                end_line,
+                end_column,
+                self.context.extra.escape_debug()
+            )
+        } else {
+            write!(f, "{}:{} {}", self.column(), end_column, self.context.extra)
+        }
     }
 }
@@ -95,13 +95,22 @@ impl<'a> Token<'a> {
         self.value.as_ref().map_or(&self.span, |value| value)
     }
 
+    /// Returns `true` if the token was written between quotes in the filter, e.g. `"true"`.
+    pub fn is_quoted(&self) -> bool {
+        let offset = self.span.location_offset();
+        offset > 0 && matches!(self.span.extra.as_bytes().get(offset - 1), Some(b'\'' | b'"'))
+    }
+
     pub fn as_external_error(&self, error: impl std::error::Error) -> Error<'a> {
         Error::new_from_external(self.span, error)
     }
 
     pub fn parse_finite_float(&self) -> Result<f64, Error> {
         let value: f64 = self.span.parse().map_err(|e| self.as_external_error(e))?;
-        if value.is_finite() {
+        if value == 0.0 {
+            // `-0.0` is the same number as `0.0`.
+            Ok(0.0)
+        } else if value.is_finite() {
             Ok(value)
         } else {
             Err(Error::new_from_kind(self.span, ErrorKind::NonFiniteFloat))
@@ -404,6 +413,22 @@ pub mod tests {
         unsafe { Span::new_from_raw_offset(offset, lines as u32, value, "") }.into()
     }
 
+    #[test]
+    fn quoted_values() {
+        fn value(s: &str) -> Token {
+            match FilterCondition::parse(s).unwrap().unwrap() {
+                FilterCondition::Condition { op: Condition::Equal(value), .. } => value,
+                condition => panic!("unexpected condition {}", condition),
+            }
+        }
+
+        assert!(!value("in_stock = true").is_quoted());
+        assert!(value("in_stock = \"true\"").is_quoted());
+        assert!(value("in_stock = 'true'").is_quoted());
+        assert!(value("in_stock = 'tr\\'ue'").is_quoted());
+        assert!(!Token::from("true").is_quoted());
+    }
+
     #[test]
     fn parse() {
         use FilterCondition as Fc;
@@ -659,6 +684,30 @@ pub mod tests {
         "###);
     }
 
+    #[test]
+    fn multiline_error() {
+        use FilterCondition as Fc;
+
+        fn p(s: &str) -> impl std::fmt::Display + '_ {
+            Fc::parse(s).unwrap_err().to_string()
+        }
+
+        insta::assert_display_snapshot!(p("channel = Ponce\nOR dog = 12 = 13"), @r###"
+        Found unexpected characters at the end of the filter: `= 13`. You probably forgot an `OR` or an `AND` rule.
+        2:13-2:17 channel = Ponce\nOR dog = 12 = 13
+        "###);
+
+        insta::assert_display_snapshot!(p("channel = mv OR (followers >= 1000\nAND subscribers < 10"), @r###"
+        Expression `(followers >= 1000\nAND subscribers < 10` is missing the following closing delimiter: `)`.
+        1:17-2:21 channel = mv OR (followers >= 1000\nAND subscribers < 10
+        "###);
+
+        let error = Fc::parse("channel = Ponce\nOR dog = 12 = 13").unwrap_err();
+        assert_eq!(error.span(), 28..32);
+        assert_eq!(error.line(), 2);
+        assert_eq!(error.column(), 13);
+    }
+
     #[test]
     fn depth() {
         let filter = FilterCondition::parse("account_ids=1 OR account_ids=2 OR account_ids=3 OR account_ids=4 OR account_ids=5 OR account_ids=6").unwrap().unwrap();
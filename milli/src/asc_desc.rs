@@ -6,7 +6,7 @@ use std::str::FromStr;
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
-use crate::error::is_reserved_keyword;
+use crate::error::{closest_match, is_reserved_keyword};
 use crate::{CriterionError, Error, UserError};
 
 /// This error type is never supposed to be shown to the end user.
@@ -29,7 +29,7 @@ impl fmt::Display for AscDescError {
                 write!(f, "Longitude must be contained between -180 and 180 degrees.",)
             }
             Self::InvalidSyntax { name } => {
-                write!(f, "Invalid syntax for the asc/desc parameter: expected expression ending by `:asc` or `:desc`, found `{}`.", name)
+                write!(f, "Invalid syntax for the asc/desc parameter: expected expression ending by `:asc` or `:desc`, found `{}`.{}", name, did_you_mean(name))
             }
             Self::ReservedKeyword { name } => {
                 write!(
@@ -42,6 +42,18 @@ impl fmt::Display for AscDescError {
     }
 }
 
+/// Returns a ` Did you mean ...?` hint when the order of an asc/desc expression
+/// looks like a typo of `asc` or `desc`, e.g. `price:asec`.
+pub(crate) fn did_you_mean(text: &str) -> String {
+    match text.rsplit_once(':') {
+        Some((field, order)) => match closest_match(order, ["asc", "desc"]) {
+            Some(order) => format!(" Did you mean `{}:{}`?", field, order),
+            None => String::new(),
+        },
+        None => String::new(),
+    }
+}
+
 impl From<AscDescError> for CriterionError {
     fn from(error: AscDescError) -> Self {
         match error {
@@ -163,7 +175,7 @@ pub enum SortError {
     #[error("Invalid syntax for the geo parameter: expected expression formated like \
                     `_geoPoint(latitude, longitude)` and ending by `:asc` or `:desc`, found `{name}`.")]
     BadGeoPointUsage { name: String },
-    #[error("Invalid syntax for the sort parameter: expected expression ending by `:asc` or `:desc`, found `{name}`.")]
+    #[error("Invalid syntax for the sort parameter: expected expression ending by `:asc` or `:desc`, found `{name}`.{}", did_you_mean(name))]
     InvalidName { name: String },
     #[error("`{name}` is a reserved keyword and thus can't be used as a sort expression.")]
     ReservedName { name: String },
@@ -294,4 +306,19 @@ mod tests {
             );
         }
     }
+
+    #[test]
+    fn sort_error_suggestion() {
+        let error = SortError::from("price:asec".parse::<AscDesc>().unwrap_err());
+        insta::assert_display_snapshot!(error, @"Invalid syntax for the sort parameter: expected expression ending by `:asc` or `:desc`, found `price:asec`. Did you mean `price:asc`?");
+
+        let error = SortError::from("release_date:dsec".parse::<AscDesc>().unwrap_err());
+        insta::assert_display_snapshot!(error, @"Invalid syntax for the sort parameter: expected expression ending by `:asc` or `:desc`, found `release_date:dsec`. Did you mean `release_date:desc`?");
+
+        let error = SortError::from("price:machin".parse::<AscDesc>().unwrap_err());
+        insta::assert_display_snapshot!(error, @"Invalid syntax for the sort parameter: expected expression ending by `:asc` or `:desc`, found `price:machin`.");
+
+        let error = SortError::from("price".parse::<AscDesc>().unwrap_err());
+        insta::assert_display_snapshot!(error, @"Invalid syntax for the sort parameter: expected expression ending by `:asc` or `:desc`, found `price`.");
+    }
 }
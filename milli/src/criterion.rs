@@ -4,11 +4,15 @@ use std::str::FromStr;
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
-use crate::{AscDesc, Member};
+use crate::update::MAX_FIELD_ID_WORD_COUNT;
+use crate::{asc_desc, error, AscDesc, Member};
+
+/// The names of the built-in ranking rules.
+const RANKING_RULES: [&str; 6] = ["words", "typo", "proximity", "attribute", "sort", "exactness"];
 
 #[derive(Error, Debug)]
 pub enum CriterionError {
-    #[error("`{name}` ranking rule is invalid. Valid ranking rules are words, typo, sort, proximity, attribute, exactness and custom ranking rules.")]
+    #[error("`{name}` ranking rule is invalid. Valid ranking rules are `words`, `typo`, `proximity`, `attribute`, `sort`, `exactness`, `field:asc`, `field:desc`, `boost:field` and `length:words`.{}", did_you_mean(name))]
     InvalidName { name: String },
     #[error("`{name}` is a reserved keyword and thus can't be used as a ranking rule")]
     ReservedName { name: String },
@@ -24,6 +28,15 @@ pub enum CriterionError {
     ReservedNameForFilter { name: String },
 }
 
+/// Returns a ` Did you mean ...?` hint for an invalid ranking rule, with the closest
+/// built-in ranking rule, e.g. for `proxmity`, or the closest asc/desc rule, e.g. for `price:dsec`.
+fn did_you_mean(name: &str) -> String {
+    match name.rsplit_once(':') {
+        Some(_) => asc_desc::did_you_mean(name),
+        None => error::did_you_mean(name, RANKING_RULES),
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
 pub enum Criterion {
     /// Sorted by decreasing number of matched query terms.
@@ -45,13 +58,24 @@ pub enum Criterion {
     Asc(String),
     /// Sorted by the decreasing value of the field specified.
     Desc(String),
+    /// Sorted by the decreasing numeric value of the field specified, the documents without
+    /// a value being placed in a neutral bucket, between the positive and negative values.
+    Boost(String),
+    /// Demotes the documents matching the query in short fields only.
+    ///
+    /// The length of a match is the word count of the longest searchable field containing
+    /// a word of the query. The documents whose match has at least the specified number of
+    /// words come first, the others follow by decreasing match length, the documents
+    /// only matching in one-word fields coming last. The word count of a field is read from
+    /// the `field_id_word_count_docids` database, so the number of words can't exceed 10.
+    Length(u8),
 }
 
 impl Criterion {
     /// Returns the field name parameter of this criterion.
     pub fn field_name(&self) -> Option<&str> {
         match self {
-            Criterion::Asc(name) | Criterion::Desc(name) => Some(name),
+            Criterion::Asc(name) | Criterion::Desc(name) | Criterion::Boost(name) => Some(name),
             _otherwise => None,
         }
     }
@@ -68,6 +92,18 @@ impl FromStr for Criterion {
             "attribute" => Ok(Criterion::Attribute),
             "sort" => Ok(Criterion::Sort),
             "exactness" => Ok(Criterion::Exactness),
+            text if !text.ends_with(":asc") && !text.ends_with(":desc") => {
+                if let Some(words) = text.strip_prefix("length:") {
+                    return match words.parse() {
+                        Ok(words @ 1..=MAX_FIELD_ID_WORD_COUNT) => Ok(Criterion::Length(words)),
+                        _ => Err(CriterionError::InvalidName { name: text.to_string() }),
+                    };
+                }
+                match text.strip_prefix("boost:") {
+                    Some(field) if !field.is_empty() => Ok(Criterion::Boost(field.to_string())),
+                    _ => Err(CriterionError::InvalidName { name: text.to_string() }),
+                }
+            }
             text => match AscDesc::from_str(text)? {
                 AscDesc::Asc(Member::Field(field)) => Ok(Criterion::Asc(field)),
                 AscDesc::Desc(Member::Field(field)) => Ok(Criterion::Desc(field)),
@@ -103,6 +139,8 @@ impl fmt::Display for Criterion {
             Exactness => f.write_str("exactness"),
             Asc(attr) => write!(f, "{}:asc", attr),
             Desc(attr) => write!(f, "{}:desc", attr),
+            Boost(attr) => write!(f, "boost:{}", attr),
+            Length(words) => write!(f, "length:{}", words),
         }
     }
 }
@@ -129,6 +167,13 @@ mod tests {
             ("truc:machin:desc", Criterion::Desc(S("truc:machin"))),
             ("hello-world!:desc", Criterion::Desc(S("hello-world!"))),
             ("it's spacy over there:asc", Criterion::Asc(S("it's spacy over there"))),
+            ("boost:_boost", Criterion::Boost(S("_boost"))),
+            ("boost:boost", Criterion::Boost(S("boost"))),
+            ("boost:asc", Criterion::Asc(S("boost"))),
+            ("boost:price:desc", Criterion::Desc(S("boost:price"))),
+            ("length:3", Criterion::Length(3)),
+            ("length:10", Criterion::Length(10)),
+            ("length:asc", Criterion::Asc(S("length"))),
         ];
 
         for (input, expected) in valid_criteria {
@@ -153,6 +198,12 @@ mod tests {
             ("price:aasc", InvalidName { name: S("price:aasc") }),
             ("price:asc and desc", InvalidName { name: S("price:asc and desc") }),
             ("price:asc:truc", InvalidName { name: S("price:asc:truc") }),
+            ("boost:", InvalidName { name: S("boost:") }),
+            ("boost", InvalidName { name: S("boost") }),
+            ("length", InvalidName { name: S("length") }),
+            ("length:0", InvalidName { name: S("length:0") }),
+            ("length:11", InvalidName { name: S("length:11") }),
+            ("length:title", InvalidName { name: S("length:title") }),
             ("_geo:asc", ReservedName { name: S("_geo") }),
             ("_geoDistance:asc", ReservedName { name: S("_geoDistance") }),
             ("_geoPoint:asc", ReservedNameForSort { name: S("_geoPoint") }),
@@ -180,4 +231,20 @@ mod tests {
             );
         }
     }
+
+    #[test]
+    fn suggest_closest_ranking_rule() {
+        let error = |input: &str| input.parse::<Criterion>().unwrap_err().to_string();
+        let hint = |input: &str| error(input).rsplit_once(". ").map(|(_, hint)| hint.to_string());
+
+        assert_eq!(hint("proxmity").unwrap(), "Did you mean `proximity`?");
+        assert_eq!(hint("word").unwrap(), "Did you mean `words`?");
+        assert_eq!(hint("typos").unwrap(), "Did you mean `typo`?");
+        assert_eq!(hint("atribute").unwrap(), "Did you mean `attribute`?");
+        assert_eq!(hint("exactnes").unwrap(), "Did you mean `exactness`?");
+        assert_eq!(hint("price:dsec").unwrap(), "Did you mean `price:desc`?");
+
+        insta::assert_display_snapshot!(error("proxmity"), @"`proxmity` ranking rule is invalid. Valid ranking rules are `words`, `typo`, `proximity`, `attribute`, `sort`, `exactness`, `field:asc`, `field:desc`, `boost:field` and `length:words`. Did you mean `proximity`?");
+        insta::assert_display_snapshot!(error("popularity"), @"`popularity` ranking rule is invalid. Valid ranking rules are `words`, `typo`, `proximity`, `attribute`, `sort`, `exactness`, `field:asc`, `field:desc`, `boost:field` and `length:words`.");
+    }
 }
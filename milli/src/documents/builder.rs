@@ -1,13 +1,17 @@
-use std::io::{self, Write};
+use std::io::{self, BufRead, Write};
 
 use grenad::{CompressionType, WriterBuilder};
 use serde::de::Deserializer;
-use serde_json::{to_writer, Value};
+use serde_json::to_writer;
 
 use super::{DocumentsBatchIndex, Error, DOCUMENTS_BATCH_INDEX_KEY};
 use crate::documents::serde_impl::DocumentVisitor;
 use crate::Object;
 
+/// The default cell value of the CSV typed columns that is considered as a missing value,
+/// see [`DocumentsBatchBuilder::csv_null_literal`].
+pub const DEFAULT_CSV_NULL_LITERAL: &str = "null";
+
 /// The `DocumentsBatchBuilder` provides a way to build a documents batch in the intermediary
 /// format used by milli.
 ///
@@ -39,6 +43,12 @@ pub struct DocumentsBatchBuilder<W> {
     /// A buffer to serialize the values and avoid reallocating,
     /// serialized values are stored in an obkv.
     value_buffer: Vec<u8>,
+
+    /// Whether the empty cells of the CSV string columns are kept as empty strings
+    /// instead of being omitted from the documents.
+    keep_empty_strings: bool,
+    /// The cell value of the CSV typed columns that is considered as a missing value.
+    csv_null_literal: Option<String>,
 }
 
 impl<W: Write> DocumentsBatchBuilder<W> {
@@ -49,9 +59,27 @@ impl<W: Write> DocumentsBatchBuilder<W> {
             documents_count: 0,
             obkv_buffer: Vec::new(),
             value_buffer: Vec::new(),
+            keep_empty_strings: false,
+            csv_null_literal: Some(String::from(DEFAULT_CSV_NULL_LITERAL)),
         }
     }
 
+    /// Keeps the empty cells of the CSV string columns as empty strings, by default they are
+    /// considered as missing values and the field is omitted from the document.
+    ///
+    /// The empty cells of the `:number` columns are always considered as missing values.
+    pub fn keep_empty_strings(&mut self, keep: bool) {
+        self.keep_empty_strings = keep;
+    }
+
+    /// Sets the cell value of the CSV typed columns, i.e. the `:number` and `:string` columns,
+    /// that is considered as a missing value, [`DEFAULT_CSV_NULL_LITERAL`] by default.
+    ///
+    /// `None` disables it, the untyped columns never use it.
+    pub fn csv_null_literal(&mut self, literal: Option<String>) {
+        self.csv_null_literal = literal;
+    }
+
     /// Returns the number of documents inserted into this builder.
     pub fn documents_count(&self) -> u32 {
         self.documents_count
@@ -87,6 +115,31 @@ impl<W: Write> DocumentsBatchBuilder<W> {
         de.deserialize_any(&mut visitor)?
     }
 
+    /// Appends a new NDJSON stream, one JSON object per line, into the batch and updates the
+    /// `DocumentsBatchIndex` accordingly.
+    ///
+    /// The lines are parsed and appended one at a time, the blank ones are skipped. On error,
+    /// the documents of the previous lines stay appended to the batch.
+    pub fn append_ndjson<R: BufRead>(&mut self, mut reader: R) -> Result<(), Error> {
+        let mut line_buffer = String::new();
+        let mut line = 0;
+        loop {
+            line_buffer.clear();
+            if reader.read_line(&mut line_buffer)? == 0 {
+                return Ok(());
+            }
+            line += 1;
+            if line_buffer.trim().is_empty() {
+                continue;
+            }
+
+            let object: Object = serde_json::from_str(&line_buffer).map_err(|error| {
+                Error::Ndjson { error, line, documents_appended: self.documents_count }
+            })?;
+            self.append_json_object(&object)?;
+        }
+    }
+
     /// Appends a new CSV file into the batch and updates the `DocumentsBatchIndex` accordingly.
     pub fn append_csv<R: io::Read>(&mut self, mut reader: csv::Reader<R>) -> Result<(), Error> {
         // Make sure that we insert the fields ids in order as the obkv writer has this requirement.
@@ -94,11 +147,11 @@ impl<W: Write> DocumentsBatchBuilder<W> {
             .headers()?
             .into_iter()
             .map(parse_csv_header)
-            .map(|(k, t)| (self.fields_index.insert(k), t))
+            .map(|(k, t, typed)| (self.fields_index.insert(k), t, typed))
             .enumerate()
             .collect();
         // Make sure that we insert the fields ids in order as the obkv writer has this requirement.
-        typed_fields_ids.sort_unstable_by_key(|(_, (fid, _))| *fid);
+        typed_fields_ids.sort_unstable_by_key(|(_, (fid, _, _))| *fid);
 
         let mut record = csv::StringRecord::new();
         let mut line = 0;
@@ -110,15 +163,19 @@ impl<W: Write> DocumentsBatchBuilder<W> {
             self.obkv_buffer.clear();
             let mut writer = obkv::KvWriter::new(&mut self.obkv_buffer);
 
-            for (i, (field_id, type_)) in typed_fields_ids.iter() {
+            for (i, (field_id, type_, typed)) in typed_fields_ids.iter() {
                 self.value_buffer.clear();
 
                 let value = &record[*i];
+                let is_null_literal = *typed
+                    && self.csv_null_literal.as_deref().map_or(false, |null| value.trim() == null);
                 match type_ {
+                    // The missing values are omitted from the document.
+                    AllowedType::Number if value.trim().is_empty() || is_null_literal => continue,
+                    AllowedType::String if is_null_literal => continue,
+                    AllowedType::String if value.is_empty() && !self.keep_empty_strings => continue,
                     AllowedType::Number => {
-                        if value.trim().is_empty() {
-                            to_writer(&mut self.value_buffer, &Value::Null)?;
-                        } else if let Ok(integer) = value.trim().parse::<i64>() {
+                        if let Ok(integer) = value.trim().parse::<i64>() {
                             to_writer(&mut self.value_buffer, &integer)?;
                         } else {
                             match value.trim().parse::<f64>() {
@@ -135,13 +192,7 @@ impl<W: Write> DocumentsBatchBuilder<W> {
                             }
                         }
                     }
-                    AllowedType::String => {
-                        if value.is_empty() {
-                            to_writer(&mut self.value_buffer, &Value::Null)?;
-                        } else {
-                            to_writer(&mut self.value_buffer, value)?;
-                        }
-                    }
+                    AllowedType::String => to_writer(&mut self.value_buffer, value)?,
                 }
 
                 // We insert into the obkv writer the value buffer that has been filled just above.
@@ -176,16 +227,17 @@ enum AllowedType {
     Number,
 }
 
-fn parse_csv_header(header: &str) -> (&str, AllowedType) {
+/// Returns the field name, its type and whether the type was explicitly given in the header.
+fn parse_csv_header(header: &str) -> (&str, AllowedType, bool) {
     // if there are several separators we only split on the last one.
     match header.rsplit_once(':') {
         Some((field_name, field_type)) => match field_type {
-            "string" => (field_name, AllowedType::String),
-            "number" => (field_name, AllowedType::Number),
+            "string" => (field_name, AllowedType::String, true),
+            "number" => (field_name, AllowedType::Number, true),
             // if the pattern isn't reconized, we keep the whole field.
-            _otherwise => (header, AllowedType::String),
+            _otherwise => (header, AllowedType::String, false),
         },
-        None => (header, AllowedType::String),
+        None => (header, AllowedType::String, false),
     }
 }
 
@@ -193,10 +245,12 @@ fn parse_csv_header(header: &str) -> (&str, AllowedType) {
 mod test {
     use std::io::Cursor;
 
-    use serde_json::json;
+    use big_s::S;
+    use serde_json::{json, Value};
 
     use super::*;
     use crate::documents::{obkv_to_object, DocumentsBatchReader};
+    use crate::index::tests::TempIndex;
 
     #[test]
     fn add_single_documents_json() {
@@ -257,6 +311,90 @@ mod test {
         assert!(cursor.next_document().unwrap().is_none());
     }
 
+    fn csv_to_values(
+        csv_content: &str,
+        builder: impl FnOnce(&mut DocumentsBatchBuilder<Vec<u8>>),
+    ) -> Vec<Value> {
+        let csv = csv::Reader::from_reader(Cursor::new(csv_content));
+        let mut documents = DocumentsBatchBuilder::new(Vec::new());
+        builder(&mut documents);
+        documents.append_csv(csv).unwrap();
+        let vector = documents.into_inner().unwrap();
+
+        let (mut cursor, index) = DocumentsBatchReader::from_reader(Cursor::new(vector))
+            .unwrap()
+            .into_cursor_and_fields_index();
+        let mut values = Vec::new();
+        while let Some(doc) = cursor.next_document().unwrap() {
+            values.push(obkv_to_object(&doc, &index).map(Value::from).unwrap());
+        }
+        values
+    }
+
+    #[test]
+    fn csv_missing_values() {
+        let csv_content = "id:number,name,city:string,pop:number\n\
+                           1,,Paris,\n\
+                           2,null,null,null\n\
+                           3,\"\",,  ";
+
+        // the empty cells and the null literal of the typed columns are omitted
+        let values = csv_to_values(csv_content, |_| ());
+        assert_eq!(
+            values,
+            vec![
+                json!({ "id": 1, "city": "Paris" }),
+                json!({ "id": 2, "name": "null" }),
+                json!({ "id": 3 }),
+            ]
+        );
+
+        // the empty strings can be kept, the empty numbers are still omitted
+        let values = csv_to_values(csv_content, |builder| {
+            builder.keep_empty_strings(true);
+        });
+        assert_eq!(
+            values,
+            vec![
+                json!({ "id": 1, "name": "", "city": "Paris" }),
+                json!({ "id": 2, "name": "null" }),
+                json!({ "id": 3, "name": "", "city": "" }),
+            ]
+        );
+
+        // the null literal of the number columns can't be parsed without it
+        let csv = csv::Reader::from_reader(Cursor::new(csv_content));
+        let mut builder = DocumentsBatchBuilder::new(Vec::new());
+        builder.csv_null_literal(None);
+        assert!(builder.append_csv(csv).is_err());
+
+        let values = csv_to_values("id:number,pop:number\n1,NA", |builder| {
+            builder.csv_null_literal(Some(S("NA")));
+        });
+        assert_eq!(values, vec![json!({ "id": 1 })]);
+    }
+
+    #[test]
+    fn csv_missing_values_field_distribution() {
+        let csv_content = "id:number,name,pop:number\n1,kevin,\n2,,20\n3,null,null";
+        let csv = csv::Reader::from_reader(Cursor::new(csv_content));
+        let mut builder = DocumentsBatchBuilder::new(Vec::new());
+        builder.append_csv(csv).unwrap();
+        let vector = builder.into_inner().unwrap();
+
+        let index = TempIndex::new();
+        index
+            .add_documents(DocumentsBatchReader::from_reader(Cursor::new(vector)).unwrap())
+            .unwrap();
+
+        let rtxn = index.read_txn().unwrap();
+        let field_distribution = index.field_distribution(&rtxn).unwrap();
+        assert_eq!(
+            field_distribution.into_iter().collect::<Vec<_>>(),
+            vec![(S("id"), 3), (S("name"), 2), (S("pop"), 1)]
+        );
+    }
+
     #[test]
     fn simple_csv_document() {
         let csv_content = r#"city,country,pop
@@ -576,4 +714,49 @@ mod test {
         let mut builder = DocumentsBatchBuilder::new(Vec::new());
         assert!(builder.append_csv(csv).is_err());
     }
+
+    #[test]
+    fn ndjson_streaming() {
+        let lines: Vec<String> = (0..100_000)
+            .map(|i| json!({ "id": i, "title": format!("document {i}"), "even": i % 2 == 0 }))
+            .map(|document| document.to_string())
+            .collect();
+
+        // appending the lines is the same as appending each object
+        let ndjson = lines.join("\n") + "\n";
+        let mut builder = DocumentsBatchBuilder::new(Vec::new());
+        builder.append_ndjson(Cursor::new(ndjson)).unwrap();
+        assert_eq!(builder.documents_count(), 100_000);
+        let streamed = builder.into_inner().unwrap();
+
+        let mut builder = DocumentsBatchBuilder::new(Vec::new());
+        for line in &lines {
+            let object: Object = serde_json::from_str(line).unwrap();
+            builder.append_json_object(&object).unwrap();
+        }
+        assert_eq!(streamed, builder.into_inner().unwrap());
+
+        // the first broken line is reported along with the documents appended before it
+        let mut broken_lines = lines;
+        broken_lines[50_000] = S(r#"{ "id": 50000, "title": "#);
+        broken_lines[70_000] = S("not json");
+        let ndjson = broken_lines.join("\n");
+        let mut builder = DocumentsBatchBuilder::new(Vec::new());
+        match builder.append_ndjson(Cursor::new(ndjson)) {
+            Err(Error::Ndjson { line, documents_appended, .. }) => {
+                assert_eq!(line, 50_001);
+                assert_eq!(documents_appended, 50_000);
+            }
+            otherwise => panic!("unexpected result {:?}", otherwise),
+        }
+        assert_eq!(builder.documents_count(), 50_000);
+
+        // the blank lines are skipped but counted
+        let ndjson = "{ \"id\": 0 }\n\n  \n{ \"id\": 1 }\n[]\n";
+        let mut builder = DocumentsBatchBuilder::new(Vec::new());
+        match builder.append_ndjson(Cursor::new(ndjson)) {
+            Err(Error::Ndjson { line: 5, documents_appended: 2, .. }) => (),
+            otherwise => panic!("unexpected result {:?}", otherwise),
+        }
+    }
 }
@@ -87,6 +87,17 @@ impl<R> EnrichedDocumentsBatchCursor<R> {
         self.documents.reset();
         self.external_ids.reset();
     }
+
+    /// Returns the id of the next document without reading the document itself.
+    ///
+    /// It doesn't advance the documents, the cursor must be reset before reading
+    /// the documents with `next_enriched_document`.
+    pub fn next_document_id(&mut self) -> Result<Option<DocumentId>, DocumentsBatchCursorError> {
+        match self.external_ids.move_on_next()? {
+            Some((_, bytes)) => serde_json::from_slice(bytes).map(Some).map_err(Into::into),
+            None => Ok(None),
+        }
+    }
 }
 
 impl<R: io::Read + io::Seek> EnrichedDocumentsBatchCursor<R> {
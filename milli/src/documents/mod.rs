@@ -8,7 +8,7 @@ use std::io;
 use std::str::Utf8Error;
 
 use bimap::BiHashMap;
-pub use builder::DocumentsBatchBuilder;
+pub use builder::{DocumentsBatchBuilder, DEFAULT_CSV_NULL_LITERAL};
 pub use enriched::{EnrichedDocument, EnrichedDocumentsBatchCursor, EnrichedDocumentsBatchReader};
 use obkv::KvReader;
 pub use reader::{DocumentsBatchCursor, DocumentsBatchCursorError, DocumentsBatchReader};
@@ -90,6 +90,7 @@ impl DocumentsBatchIndex {
 #[derive(Debug)]
 pub enum Error {
     ParseFloat { error: std::num::ParseFloatError, line: usize, value: String },
+    Ndjson { error: serde_json::Error, line: usize, documents_appended: u32 },
     InvalidDocumentFormat,
     InvalidEnrichedData,
     InvalidUtf8(Utf8Error),
@@ -136,6 +137,11 @@ impl fmt::Display for Error {
             Error::ParseFloat { error, line, value } => {
                 write!(f, "Error parsing number {:?} at line {}: {}", value, line, error)
             }
+            Error::Ndjson { error, line, documents_appended } => write!(
+                f,
+                "Error parsing the document at line {} after appending {} documents: {}",
+                line, documents_appended, error
+            ),
             Error::InvalidDocumentFormat => {
                 f.write_str("Invalid document addition format, missing the documents batch index.")
             }
@@ -152,7 +158,7 @@ impl fmt::Display for Error {
 
 impl std::error::Error for Error {}
 
-#[cfg(test)]
+#[cfg(any(test, feature = "test-utils"))]
 pub fn objects_from_json_value(json: serde_json::Value) -> Vec<crate::Object> {
     let documents = match json {
         object @ serde_json::Value::Object(_) => vec![object],
@@ -182,7 +188,7 @@ macro_rules! documents {
     }};
 }
 
-#[cfg(test)]
+#[cfg(any(test, feature = "test-utils"))]
 pub fn documents_batch_reader_from_objects(
     objects: impl IntoIterator<Item = Object>,
 ) -> DocumentsBatchReader<std::io::Cursor<Vec<u8>>> {
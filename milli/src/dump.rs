@@ -0,0 +1,416 @@
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+use std::io::{self, BufRead, Seek, SeekFrom, Write};
+
+use heed::{RoTxn, RwTxn};
+use serde::{Deserialize, Serialize};
+
+use crate::documents::{DocumentsBatchBuilder, DocumentsBatchReader};
+use crate::error::{InternalError, UserError};
+use crate::facet::MixedFacetTypes;
+use crate::update::{IndexDocuments, IndexDocumentsConfig, IndexerConfig, Settings};
+use crate::{
+    all_obkv_to_json, Criterion, Index, LongWordsPolicy, MaxFieldsPolicy, Object, Result,
+    StopWordsMode, TypoOnDiacritics,
+};
+
+/// The name of the format written in the header of the dumps.
+pub const DUMP_FORMAT: &str = "milli-dump";
+
+/// The version of the dump format, increased whenever the format changes in a way that
+/// prevents the previous versions from reading it. [`import_dump`] only reads the dumps
+/// written in this version.
+pub const DUMP_FORMAT_VERSION: u32 = 1;
+
+/// The first line of a dump, describing the index and how to rebuild it.
+///
+/// A dump is a stream of JSON lines: the header followed by one line for each document of
+/// the index, with all its fields as they were given. The dump is independent of the
+/// architecture and of the internal representation of the index, the documents are indexed
+/// again when it is imported.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DumpHeader {
+    /// Always [`DUMP_FORMAT`].
+    pub format: String,
+    /// The [`DUMP_FORMAT_VERSION`] the dump is written in.
+    pub version: u32,
+    pub primary_key: Option<String>,
+    /// The number of documents following the header.
+    pub documents_count: u64,
+    pub settings: DumpSettings,
+}
+
+/// The settings of an index, as they were set by the user.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DumpSettings {
+    pub searchable_fields: Option<Vec<String>>,
+    pub displayed_fields: Option<Vec<String>>,
+    pub filterable_fields: BTreeSet<String>,
+    pub sortable_fields: BTreeSet<String>,
+    pub criteria: Vec<Criterion>,
+    pub stop_words: BTreeSet<String>,
+    #[serde(default)]
+    pub stop_words_mode: StopWordsMode,
+    pub distinct_field: Option<String>,
+    pub synonyms: BTreeMap<String, Vec<String>>,
+    pub authorize_typos: bool,
+    pub min_word_len_one_typo: u8,
+    pub min_word_len_two_typos: u8,
+    pub exact_words: BTreeSet<String>,
+    pub exact_attributes: BTreeSet<String>,
+    #[serde(default)]
+    pub case_sensitive_attributes: BTreeSet<String>,
+    pub max_values_per_facet: Option<usize>,
+    pub pagination_max_total_hits: Option<usize>,
+    pub max_word_length: usize,
+    pub truncate_long_words: bool,
+    #[serde(default)]
+    pub min_indexed_word_length: usize,
+    pub max_positions_per_word: Option<usize>,
+    #[serde(default)]
+    pub max_fields: Option<usize>,
+    #[serde(default)]
+    pub max_fields_policy: MaxFieldsPolicy,
+    pub field_boosts: BTreeMap<String, f32>,
+    pub fields_languages: BTreeMap<String, String>,
+    #[serde(default)]
+    pub detected_languages_allow_list: Vec<String>,
+    #[serde(default)]
+    pub stemming: BTreeMap<String, bool>,
+    pub normalization_rules: BTreeMap<String, String>,
+    pub mixed_facet_types: BTreeMap<String, MixedFacetTypes>,
+    pub proximity_databases: bool,
+    #[serde(default)]
+    pub cross_field_proximity: bool,
+    #[serde(default)]
+    pub typo_on_diacritics: TypoOnDiacritics,
+}
+
+impl DumpSettings {
+    fn from_index(index: &Index, rtxn: &RoTxn) -> Result<DumpSettings> {
+        let strings = |fields: Vec<&str>| fields.into_iter().map(String::from).collect();
+        let stop_words = match index.stop_words(rtxn)? {
+            Some(stop_words) => stop_words.stream().into_strs()?.into_iter().collect(),
+            None => BTreeSet::new(),
+        };
+        let exact_words = match index.exact_words(rtxn)? {
+            Some(exact_words) => exact_words.stream().into_strs()?.into_iter().collect(),
+            None => BTreeSet::new(),
+        };
+        // the synonyms are stored as the normalized words of the synonymous expressions.
+        let synonyms = index
+            .synonyms(rtxn)?
+            .into_iter()
+            .map(|(words, synonyms)| {
+                (words.join(" "), synonyms.iter().map(|synonym| synonym.join(" ")).collect())
+            })
+            .collect();
+
+        Ok(DumpSettings {
+            searchable_fields: index.user_defined_searchable_fields(rtxn)?.map(strings),
+            displayed_fields: index.displayed_fields(rtxn)?.map(strings),
+            filterable_fields: index.filterable_fields(rtxn)?.into_iter().collect(),
+            sortable_fields: index.sortable_fields(rtxn)?.into_iter().collect(),
+            criteria: index.criteria(rtxn)?,
+            stop_words,
+            stop_words_mode: index.stop_words_mode(rtxn)?,
+            distinct_field: index.distinct_field(rtxn)?.map(String::from),
+            synonyms,
+            authorize_typos: index.authorize_typos(rtxn)?,
+            min_word_len_one_typo: index.min_word_len_one_typo(rtxn)?,
+            min_word_len_two_typos: index.min_word_len_two_typos(rtxn)?,
+            exact_words,
+            exact_attributes: index.exact_attributes(rtxn)?.into_iter().map(String::from).collect(),
+            case_sensitive_attributes: index
+                .case_sensitive_attributes(rtxn)?
+                .into_iter()
+                .map(String::from)
+                .collect(),
+            max_values_per_facet: index.max_values_per_facet(rtxn)?,
+            pagination_max_total_hits: index.pagination_max_total_hits(rtxn)?,
+            max_word_length: index.max_word_length(rtxn)?,
+            truncate_long_words: index.long_words_policy(rtxn)? == LongWordsPolicy::Truncate,
+            min_indexed_word_length: index.min_indexed_word_length(rtxn)?,
+            max_positions_per_word: index.max_positions_per_word(rtxn)?,
+            max_fields: index.max_fields(rtxn)?,
+            max_fields_policy: index.max_fields_policy(rtxn)?,
+            field_boosts: index.field_boosts(rtxn)?,
+            fields_languages: index.fields_languages(rtxn)?,
+            detected_languages_allow_list: index.detected_languages_allow_list(rtxn)?,
+            stemming: index.stemming(rtxn)?,
+            normalization_rules: index.normalization_rules(rtxn)?,
+            mixed_facet_types: index.mixed_facet_types(rtxn)?,
+            proximity_databases: index.proximity_databases(rtxn)?,
+            cross_field_proximity: index.cross_field_proximity(rtxn)?,
+            typo_on_diacritics: index.typo_on_diacritics(rtxn)?,
+        })
+    }
+
+    fn apply(self, settings: &mut Settings) {
+        if let Some(fields) = self.searchable_fields {
+            settings.set_searchable_fields(fields);
+        }
+        if let Some(fields) = self.displayed_fields {
+            settings.set_displayed_fields(fields);
+        }
+        settings.set_filterable_fields(self.filterable_fields.into_iter().collect());
+        settings.set_sortable_fields(self.sortable_fields.into_iter().collect());
+        settings.set_criteria(self.criteria);
+        settings.set_stop_words(self.stop_words);
+        settings.set_stop_words_mode(self.stop_words_mode);
+        if let Some(field) = self.distinct_field {
+            settings.set_distinct_field(field);
+        }
+        settings.set_synonyms(self.synonyms.into_iter().collect());
+        settings.set_autorize_typos(self.authorize_typos);
+        settings.set_min_word_len_one_typo(self.min_word_len_one_typo);
+        settings.set_min_word_len_two_typos(self.min_word_len_two_typos);
+        settings.set_exact_words(self.exact_words);
+        settings.set_exact_attributes(self.exact_attributes.into_iter().collect::<HashSet<_>>());
+        let case_sensitive_attributes = self.case_sensitive_attributes.into_iter().collect();
+        settings.set_case_sensitive_attributes(case_sensitive_attributes);
+        if let Some(value) = self.max_values_per_facet {
+            settings.set_max_values_per_facet(value);
+        }
+        if let Some(value) = self.pagination_max_total_hits {
+            settings.set_pagination_max_total_hits(value);
+        }
+        settings.set_max_word_length(self.max_word_length);
+        settings.set_long_words_policy(if self.truncate_long_words {
+            LongWordsPolicy::Truncate
+        } else {
+            LongWordsPolicy::Drop
+        });
+        settings.set_min_indexed_word_length(self.min_indexed_word_length);
+        if let Some(value) = self.max_positions_per_word {
+            settings.set_max_positions_per_word(value);
+        }
+        if let Some(value) = self.max_fields {
+            settings.set_max_fields(value);
+        }
+        settings.set_max_fields_policy(self.max_fields_policy);
+        settings.set_field_boosts(self.field_boosts);
+        settings.set_fields_languages(self.fields_languages);
+        settings.set_detected_languages_allow_list(self.detected_languages_allow_list);
+        settings.set_stemming(self.stemming);
+        settings.set_normalization_rules(self.normalization_rules);
+        settings.set_mixed_facet_types(self.mixed_facet_types);
+        settings.set_proximity_databases(self.proximity_databases);
+        settings.set_cross_field_proximity(self.cross_field_proximity);
+        settings.set_typo_on_diacritics(self.typo_on_diacritics);
+    }
+}
+
+/// Writes the settings and the documents of the index into the writer, see [`DumpHeader`]
+/// for the format. The soft-deleted documents are not written.
+pub(crate) fn export_dump<W: Write>(rtxn: &RoTxn, index: &Index, mut writer: W) -> Result<()> {
+    let header = DumpHeader {
+        format: DUMP_FORMAT.to_string(),
+        version: DUMP_FORMAT_VERSION,
+        primary_key: index.primary_key(rtxn)?.map(String::from),
+        documents_count: index.number_of_documents(rtxn)?,
+        settings: DumpSettings::from_index(index, rtxn)?,
+    };
+    serde_json::to_writer(&mut writer, &header).map_err(InternalError::SerdeJson)?;
+    writer.write_all(b"\n")?;
+
+    let fields_ids_map = index.fields_ids_map(rtxn)?;
+    for result in index.all_documents(rtxn)? {
+        let (_docid, obkv) = result?;
+        let document = all_obkv_to_json(obkv, &fields_ids_map)?;
+        serde_json::to_writer(&mut writer, &document).map_err(InternalError::SerdeJson)?;
+        writer.write_all(b"\n")?;
+    }
+
+    writer.flush()?;
+    Ok(())
+}
+
+/// Rebuilds an index from a dump written by [`Index::export_dump`], the settings are applied
+/// and the documents are indexed again in the given, usually empty, index.
+///
+/// Returns the header of the dump.
+pub fn import_dump<'i, R: BufRead>(
+    wtxn: &mut RwTxn<'i, '_>,
+    index: &'i Index,
+    indexer_config: &IndexerConfig,
+    reader: R,
+) -> Result<DumpHeader> {
+    let invalid_dump = |reason: String| UserError::InvalidDump { reason };
+    let mut lines = reader.lines();
+
+    let header = match lines.next() {
+        Some(line) => serde_json::from_str::<DumpHeader>(&line?)
+            .map_err(|e| invalid_dump(format!("its header can't be read: {}", e)))?,
+        None => return Err(invalid_dump("it is empty".to_string()).into()),
+    };
+    if header.format != DUMP_FORMAT || header.version != DUMP_FORMAT_VERSION {
+        return Err(invalid_dump(format!(
+            "it is a `{}` dump of version {}, only the `{}` dumps of version {} can be imported",
+            header.format, header.version, DUMP_FORMAT, DUMP_FORMAT_VERSION
+        ))
+        .into());
+    }
+
+    let mut settings = Settings::new(wtxn, index, indexer_config);
+    if let Some(primary_key) = header.primary_key.clone() {
+        settings.set_primary_key(primary_key);
+    }
+    header.settings.clone().apply(&mut settings);
+    settings.execute(|_| (), || false)?;
+
+    let mut builder = DocumentsBatchBuilder::new(io::BufWriter::new(tempfile::tempfile()?));
+    for (nth, line) in lines.enumerate() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let document: Object = serde_json::from_str(&line)
+            .map_err(|e| invalid_dump(format!("its document {} can't be read: {}", nth, e)))?;
+        builder.append_json_object(&document)?;
+    }
+
+    if builder.documents_count() as u64 != header.documents_count {
+        return Err(invalid_dump(format!(
+            "it contains {} documents instead of the {} announced by its header",
+            builder.documents_count(),
+            header.documents_count
+        ))
+        .into());
+    }
+
+    if header.documents_count != 0 {
+        let mut file = builder.into_inner()?.into_inner().map_err(|e| e.into_error())?;
+        file.seek(SeekFrom::Start(0))?;
+        let documents = DocumentsBatchReader::from_reader(file)?;
+        let config = IndexDocumentsConfig::default();
+        let indexing = IndexDocuments::new(wtxn, index, indexer_config, config, |_| (), || false)?;
+        let (indexing, user_error) = indexing.add_documents(documents)?;
+        user_error?;
+        indexing.execute()?;
+    }
+
+    Ok(header)
+}
+
+#[cfg(test)]
+mod tests {
+    use big_s::S;
+    use maplit::{btreemap, hashset};
+
+    use super::*;
+    use crate::index::tests::TempIndex;
+    use crate::{AscDesc, Filter, Search, SearchResult};
+
+    #[test]
+    fn export_and_import_a_dump() {
+        let index = TempIndex::new();
+        index
+            .update_settings(|settings| {
+                settings.set_primary_key(S("id"));
+                settings.set_searchable_fields(vec![S("title"), S("description")]);
+                settings.set_filterable_fields(hashset! { S("genre"), S("_geo") });
+                settings.set_sortable_fields(hashset! { S("year") });
+                settings.set_stop_words(maplit::btreeset! { S("the") });
+                settings.set_synonyms(
+                    btreemap! {
+                        S("sci fi") => vec![S("science fiction")],
+                    }
+                    .into_iter()
+                    .collect(),
+                );
+                settings.set_distinct_field(S("saga"));
+                settings.set_min_word_len_one_typo(3);
+            })
+            .unwrap();
+        index
+            .add_documents(documents!([
+                { "id": 1, "title": "The Empire Strikes Back", "description": "sci fi space opera", "genre": "sci-fi", "year": 1980, "saga": "star wars" },
+                { "id": 2, "title": "A New Hope", "description": "science fiction in space", "genre": "sci-fi", "year": 1977, "saga": "star wars" },
+                { "id": 3, "title": "Alien", "description": "science fiction horror in space", "genre": "horror", "year": 1979, "saga": "alien", "_geo": { "lat": 45.5, "lng": 9.5 } },
+                { "id": 4, "title": "The Shining", "description": "horror in a hotel", "genre": "horror", "year": 1980, "saga": "shining" },
+                { "id": 5, "title": "Space Odyssey", "description": "sci fi", "genre": "sci-fi", "year": 1968, "saga": "odyssey" },
+            ]))
+            .unwrap();
+        index.delete_document("4");
+
+        let mut dump = Vec::new();
+        let rtxn = index.read_txn().unwrap();
+        index.export_dump(&rtxn, &mut dump).unwrap();
+
+        let imported = TempIndex::new();
+        let mut wtxn = imported.write_txn().unwrap();
+        let header =
+            import_dump(&mut wtxn, &imported, &imported.indexer_config, dump.as_slice()).unwrap();
+        wtxn.commit().unwrap();
+        assert_eq!(header.version, DUMP_FORMAT_VERSION);
+        assert_eq!(header.documents_count, 4);
+
+        let imported_rtxn = imported.read_txn().unwrap();
+        assert_eq!(
+            DumpSettings::from_index(&imported, &imported_rtxn).unwrap(),
+            DumpSettings::from_index(&index, &rtxn).unwrap()
+        );
+
+        let external_ids = |index: &Index, rtxn: &RoTxn, search: &Search| {
+            let SearchResult { documents_ids, candidates, .. } = search.execute().unwrap();
+            let fields_ids_map = index.fields_ids_map(rtxn).unwrap();
+            let ids: Vec<_> = index
+                .documents(rtxn, documents_ids)
+                .unwrap()
+                .into_iter()
+                .map(|(_, obkv)| all_obkv_to_json(obkv, &fields_ids_map).unwrap()["id"].clone())
+                .collect();
+            (ids, candidates.len())
+        };
+
+        let searches: Vec<(&str, Option<&str>, Option<&str>)> = vec![
+            ("space", None, None),
+            ("sci fi", None, None),
+            ("science fiction", Some("genre = horror"), None),
+            ("", Some("_geoRadius(45.5, 9.5, 1000)"), None),
+            ("", None, Some("year:asc")),
+        ];
+        for (query, filter, sort) in searches {
+            let mut search = Search::new(&rtxn, &index);
+            let mut imported_search = Search::new(&imported_rtxn, &imported);
+            for search in [&mut search, &mut imported_search] {
+                search.query(query);
+                if let Some(filter) = filter {
+                    search.filter(Filter::from_str(filter).unwrap().unwrap());
+                }
+                if let Some(sort) = sort {
+                    search.sort_criteria(vec![sort.parse::<AscDesc>().unwrap()]);
+                }
+            }
+            assert_eq!(
+                external_ids(&imported, &imported_rtxn, &imported_search),
+                external_ids(&index, &rtxn, &search),
+                "query: {:?}, filter: {:?}, sort: {:?}",
+                query,
+                filter,
+                sort
+            );
+        }
+    }
+
+    #[test]
+    fn import_a_dump_of_another_version() {
+        let index = TempIndex::new();
+        let mut dump = Vec::new();
+        let rtxn = index.read_txn().unwrap();
+        index.export_dump(&rtxn, &mut dump).unwrap();
+        drop(rtxn);
+
+        let dump = String::from_utf8(dump).unwrap().replacen(
+            &format!("\"version\":{}", DUMP_FORMAT_VERSION),
+            "\"version\":0",
+            1,
+        );
+        let mut wtxn = index.write_txn().unwrap();
+        let error =
+            import_dump(&mut wtxn, &index, &index.indexer_config, dump.as_bytes()).unwrap_err();
+        assert!(matches!(error, crate::Error::UserError(UserError::InvalidDump { .. })));
+    }
+}
@@ -1,7 +1,7 @@
 use std::collections::BTreeSet;
 use std::convert::Infallible;
 use std::fmt::Write;
-use std::{io, str};
+use std::{fmt, io, str};
 
 use heed::{Error as HeedError, MdbError};
 use rayon::ThreadPoolBuildError;
@@ -15,6 +15,58 @@ pub fn is_reserved_keyword(keyword: &str) -> bool {
     ["_geo", "_geoDistance", "_geoPoint", "_geoRadius"].contains(&keyword)
 }
 
+/// Returns the candidate the closest to `word`, if it is close enough to be a typo of it.
+pub(crate) fn closest_match<'a>(
+    word: &str,
+    candidates: impl IntoIterator<Item = &'a str>,
+) -> Option<&'a str> {
+    let max_distance = (word.chars().count() / 3).max(1);
+    candidates
+        .into_iter()
+        .map(|candidate| (edit_distance(word, candidate), candidate))
+        .filter(|(distance, _)| (1..=max_distance).contains(distance))
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, candidate)| candidate)
+}
+
+/// Returns a ` Did you mean ...?` hint to append to an error message about `word`,
+/// or an empty string when none of the candidates looks like what was meant.
+pub(crate) fn did_you_mean<'a>(
+    word: &str,
+    candidates: impl IntoIterator<Item = &'a str>,
+) -> String {
+    match closest_match(word, candidates) {
+        Some(candidate) => format!(" Did you mean `{}`?", candidate),
+        None => String::new(),
+    }
+}
+
+/// The edit distance between two strings, counted in characters, where swapping two
+/// adjacent characters counts as a single edit.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let mut before_previous = vec![0; b.len() + 1];
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    let mut current = vec![0; b.len() + 1];
+
+    for (i, &ca) in a.iter().enumerate() {
+        current[0] = i + 1;
+        for (j, &cb) in b.iter().enumerate() {
+            let substitution = previous[j] + (ca != cb) as usize;
+            let mut distance = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+            if i > 0 && j > 0 && ca == b[j - 1] && a[i - 1] == cb {
+                distance = distance.min(before_previous[j - 1] + 1);
+            }
+            current[j + 1] = distance;
+        }
+        std::mem::swap(&mut before_previous, &mut previous);
+        std::mem::swap(&mut previous, &mut current);
+    }
+
+    previous[b.len()]
+}
+
 #[derive(Error, Debug)]
 pub enum Error {
     #[error("internal: {0}.")]
@@ -29,6 +81,8 @@ pub enum Error {
 pub enum InternalError {
     #[error("{}", HeedError::DatabaseClosing)]
     DatabaseClosing,
+    #[error("Missing the {db_name} database.")]
+    DatabaseMissing { db_name: &'static str },
     #[error("Missing {} in the {db_name} database.", key.unwrap_or("key"))]
     DatabaseMissingEntry { db_name: &'static str, key: Option<&'static str> },
     #[error(transparent)]
@@ -89,18 +143,32 @@ pub enum FieldIdMapMissingEntry {
 pub enum UserError {
     #[error("A soft deleted internal document id have been used: `{document_id}`.")]
     AccessingSoftDeletedDocument { document_id: DocumentId },
-    #[error("A document cannot contain more than 65,535 fields.")]
-    AttributeLimitReached,
+    #[error("{}", match .external_id {
+        Some(external_id) => format!("The document with the id: `{external_id}` brings the number of fields of the index over the limit of {limit} fields."),
+        None => format!("An index cannot contain more than {limit} fields."),
+    })]
+    AttributeLimitReached { external_id: Option<String>, limit: usize },
     #[error(transparent)]
     CriterionError(#[from] CriterionError),
+    /// The index already uses all the internal document ids when documents are added, the ids
+    /// exhausted while the documents are added are reported by [`UserError::NoMoreDocumentIds`].
     #[error("Maximum number of documents reached.")]
     DocumentLimitReached,
+    #[error("The document with the id: `{external_id}` is {size} bytes long, a document can't exceed {limit} bytes.")]
+    DocumentTooLarge { external_id: String, size: usize, limit: usize },
     #[error(
         "Document identifier `{}` is invalid. \
 A document identifier can be of type integer or string, \
-only composed of alphanumeric characters (a-z A-Z 0-9), hyphens (-) and underscores (_).", .document_id.to_string()
+only composed of alphanumeric characters (a-z A-Z 0-9), hyphens (-) and underscores (_). {}{}", .document_id.to_string(),
+        .reason,
+        .document_nth.map_or(String::new(), |nth| format!(" The document is at position {} of the payload.", nth))
     )]
-    InvalidDocumentId { document_id: Value },
+    InvalidDocumentId {
+        document_id: Value,
+        /// The position of the document in the added documents, `None` for a deletion.
+        document_nth: Option<u32>,
+        reason: InvalidDocumentIdReason,
+    },
     #[error("Invalid facet distribution, {}", format_invalid_filter_distribution(.invalid_facets_name, .valid_facets_name))]
     InvalidFacetsDistribution {
         invalid_facets_name: BTreeSet<String>,
@@ -110,16 +178,19 @@ only composed of alphanumeric characters (a-z A-Z 0-9), hyphens (-) and undersco
     InvalidGeoField(#[from] GeoError),
     #[error("{0}")]
     InvalidFilter(String),
-    #[error("Attribute `{}` is not sortable. {}",
+    #[error("Attribute `{}` is not sortable. {}{}",
         .field,
         match .valid_fields.is_empty() {
             true => "This index does not have configured sortable attributes.".to_string(),
             false => format!("Available sortable attributes are: `{}`.",
                     valid_fields.iter().map(AsRef::as_ref).collect::<Vec<&str>>().join(", ")
                 ),
-        }
+        },
+        did_you_mean(field, valid_fields.iter().map(AsRef::as_ref)),
     )]
     InvalidSortableAttribute { field: String, valid_fields: BTreeSet<String> },
+    #[error("Attribute `{field}` is not searchable, the query term `{field}:{term}` can't be scoped to it.")]
+    UnsearchableScopedTerm { field: String, term: String },
     #[error("{}", HeedError::BadOpenOptions)]
     InvalidLmdbOpenOptions,
     #[error("The sort ranking rule must be specified in the ranking rules settings to use the sort parameter at search time.")]
@@ -138,6 +209,8 @@ only composed of alphanumeric characters (a-z A-Z 0-9), hyphens (-) and undersco
     MultiplePrimaryKeyCandidatesFound { candidates: Vec<String> },
     #[error("There is no more space left on the device. Consider increasing the size of the disk/partition.")]
     NoSpaceLeftOnDevice,
+    #[error("There is no more internal document id available, an index can't contain more than 4294967296 documents including the soft-deleted ones. Consider hard-deleting the documents that are not needed anymore, including the soft-deleted ones, or splitting the documents across several indexes.")]
+    NoMoreDocumentIds,
     #[error("Index already has a primary key: `{0}`.")]
     PrimaryKeyCannotBeChanged(String),
     #[error(transparent)]
@@ -148,6 +221,230 @@ only composed of alphanumeric characters (a-z A-Z 0-9), hyphens (-) and undersco
     UnknownInternalDocumentId { document_id: DocumentId },
     #[error("`minWordSizeForTypos` setting is invalid. `oneTypo` and `twoTypos` fields should be between `0` and `255`, and `twoTypos` should be greater or equals to `oneTypo` but found `oneTypo: {0}` and twoTypos: {1}`.")]
     InvalidMinTypoWordLenSetting(u8, u8),
+    #[error("The language `{language}` of the `{field}` attribute is not supported. Languages must be given as ISO 639-3 codes, e.g. `eng` or `jpn`.")]
+    InvalidFieldLanguage { field: String, language: String },
+    #[error("The language `{language}` of the detected languages allow list is not supported. Languages must be given as ISO 639-3 codes, e.g. `eng` or `jpn`.")]
+    InvalidDetectedLanguage { language: String },
+    #[error("The language `{language}` can't be stemmed. Languages must be given as ISO 639-3 codes, e.g. `eng` or `fra`, of a language with a stemmer.")]
+    InvalidStemmingLanguage { language: String },
+    #[error("Attribute `{field}` only accepts numbers as facet values but the string `{value}` was found.")]
+    InvalidNumericFacetValue { field: String, value: String },
+    #[error("The normalization rule replacing `{pattern}` by `{replacement}` is invalid. The pattern of a normalization rule can't be empty.")]
+    InvalidNormalizationRule { pattern: String, replacement: String },
+    #[error("The boost `{boost}` of the `{field}` field is invalid. A field boost must be a finite number greater than zero.")]
+    InvalidFieldBoost { field: String, boost: f32 },
+    #[error("Attribute `{}` of the `boost:{}` ranking rule is not filterable. {}",
+        .field,
+        .field,
+        match .valid_fields.is_empty() {
+            true => "This index does not have configured filterable attributes.".to_string(),
+            false => format!("Available filterable attributes are: `{}`.",
+                    valid_fields.iter().map(AsRef::as_ref).collect::<Vec<&str>>().join(", ")
+                ),
+        }
+    )]
+    InvalidBoostAttribute { field: String, valid_fields: BTreeSet<String> },
+    #[error("The `{criterion}` ranking rule can't be disabled at search time, {reason}.")]
+    CriterionCannotBeDisabled { criterion: String, reason: &'static str },
+    #[error("The dump can't be imported, {reason}.")]
+    InvalidDump { reason: String },
+}
+
+/// A stable code identifying the kind of a [`UserError`], see [`UserError::code`].
+///
+/// The codes are never renamed nor reused, they can be used to map the errors to the
+/// errors of an API without depending on their messages.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ErrorCode {
+    /// A soft-deleted document was accessed by its internal id.
+    AccessingSoftDeletedDocument,
+    /// The documents contain more fields than an index can.
+    AttributeLimitReached,
+    /// A ranking rule is invalid.
+    InvalidCriterion,
+    /// A document is larger than the maximum document size.
+    DocumentTooLarge,
+    /// A document identifier is invalid.
+    InvalidDocumentId,
+    /// The facet distribution is requested for non-filterable attributes.
+    InvalidFacetsDistribution,
+    /// The `_geo` field of a document is invalid.
+    InvalidGeoField,
+    /// A filter is invalid.
+    InvalidFilter,
+    /// A search is sorted by a non-sortable attribute.
+    InvalidSortableAttribute,
+    /// A query term is scoped to a non-searchable attribute.
+    UnsearchableScopedTerm,
+    /// The index is opened with options different from the ones it is already opened with.
+    InvalidLmdbOpenOptions,
+    /// A search is sorted while the sort ranking rule is missing.
+    SortRankingRuleMissing,
+    /// The database file is in an invalid state.
+    InvalidStoreFile,
+    /// The maximum size of the database is reached.
+    MaxDatabaseSizeReached,
+    /// A document does not have the primary key attribute.
+    MissingDocumentId,
+    /// A document has several values for the primary key attribute.
+    TooManyDocumentIds,
+    /// The primary key can't be inferred as no attribute ends with `id`.
+    MissingPrimaryKey,
+    /// The primary key can't be inferred as several attributes end with `id`.
+    MultiplePrimaryKeyCandidates,
+    /// There is no space left on the device.
+    NoSpaceLeftOnDevice,
+    /// There is no internal document id left.
+    NoMoreDocumentIds,
+    /// The primary key of an index already containing documents is changed.
+    PrimaryKeyCannotBeChanged,
+    /// The documents payload is not valid JSON.
+    MalformedPayload,
+    /// A sort criterion of a search is invalid.
+    InvalidSort,
+    /// An unknown document was accessed by its internal id.
+    UnknownInternalDocumentId,
+    /// The minimum word lengths for typos are invalid.
+    InvalidMinTypoWordLenSetting,
+    /// The language of an attribute is not supported.
+    InvalidFieldLanguage,
+    /// A language of the detected languages allow list is not supported.
+    InvalidDetectedLanguage,
+    /// A language of the stemming setting can't be stemmed.
+    InvalidStemmingLanguage,
+    /// A string is given to a numeric-only facet.
+    InvalidNumericFacetValue,
+    /// A normalization rule is invalid.
+    InvalidNormalizationRule,
+    /// A field boost isn't a finite number greater than zero.
+    InvalidFieldBoost,
+    /// The attribute of a `boost` ranking rule is not filterable.
+    InvalidBoostAttribute,
+    /// A ranking rule that is required is disabled at search time.
+    CriterionCannotBeDisabled,
+    /// A dump can't be imported.
+    InvalidDump,
+}
+
+impl ErrorCode {
+    /// The code as a `snake_case` string, e.g. `invalid_filter`.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ErrorCode::AccessingSoftDeletedDocument => "accessing_soft_deleted_document",
+            ErrorCode::AttributeLimitReached => "attribute_limit_reached",
+            ErrorCode::InvalidCriterion => "invalid_criterion",
+            ErrorCode::DocumentTooLarge => "document_too_large",
+            ErrorCode::InvalidDocumentId => "invalid_document_id",
+            ErrorCode::InvalidFacetsDistribution => "invalid_facets_distribution",
+            ErrorCode::InvalidGeoField => "invalid_geo_field",
+            ErrorCode::InvalidFilter => "invalid_filter",
+            ErrorCode::InvalidSortableAttribute => "invalid_sortable_attribute",
+            ErrorCode::UnsearchableScopedTerm => "unsearchable_scoped_term",
+            ErrorCode::InvalidLmdbOpenOptions => "invalid_lmdb_open_options",
+            ErrorCode::SortRankingRuleMissing => "sort_ranking_rule_missing",
+            ErrorCode::InvalidStoreFile => "invalid_store_file",
+            ErrorCode::MaxDatabaseSizeReached => "max_database_size_reached",
+            ErrorCode::MissingDocumentId => "missing_document_id",
+            ErrorCode::TooManyDocumentIds => "too_many_document_ids",
+            ErrorCode::MissingPrimaryKey => "missing_primary_key",
+            ErrorCode::MultiplePrimaryKeyCandidates => "multiple_primary_key_candidates",
+            ErrorCode::NoSpaceLeftOnDevice => "no_space_left_on_device",
+            ErrorCode::NoMoreDocumentIds => "no_more_document_ids",
+            ErrorCode::PrimaryKeyCannotBeChanged => "primary_key_cannot_be_changed",
+            ErrorCode::MalformedPayload => "malformed_payload",
+            ErrorCode::InvalidSort => "invalid_sort",
+            ErrorCode::UnknownInternalDocumentId => "unknown_internal_document_id",
+            ErrorCode::InvalidMinTypoWordLenSetting => "invalid_min_typo_word_len_setting",
+            ErrorCode::InvalidFieldLanguage => "invalid_field_language",
+            ErrorCode::InvalidDetectedLanguage => "invalid_detected_language",
+            ErrorCode::InvalidStemmingLanguage => "invalid_stemming_language",
+            ErrorCode::InvalidNumericFacetValue => "invalid_numeric_facet_value",
+            ErrorCode::InvalidNormalizationRule => "invalid_normalization_rule",
+            ErrorCode::InvalidFieldBoost => "invalid_field_boost",
+            ErrorCode::InvalidBoostAttribute => "invalid_boost_attribute",
+            ErrorCode::CriterionCannotBeDisabled => "criterion_cannot_be_disabled",
+            ErrorCode::InvalidDump => "invalid_dump",
+        }
+    }
+}
+
+impl fmt::Display for ErrorCode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl UserError {
+    /// Returns the stable code of the kind of the error.
+    pub fn code(&self) -> ErrorCode {
+        use UserError::*;
+
+        match self {
+            AccessingSoftDeletedDocument { .. } => ErrorCode::AccessingSoftDeletedDocument,
+            AttributeLimitReached { .. } => ErrorCode::AttributeLimitReached,
+            CriterionError(_) => ErrorCode::InvalidCriterion,
+            DocumentTooLarge { .. } => ErrorCode::DocumentTooLarge,
+            InvalidDocumentId { .. } => ErrorCode::InvalidDocumentId,
+            InvalidFacetsDistribution { .. } => ErrorCode::InvalidFacetsDistribution,
+            InvalidGeoField(_) => ErrorCode::InvalidGeoField,
+            InvalidFilter(_) => ErrorCode::InvalidFilter,
+            InvalidSortableAttribute { .. } => ErrorCode::InvalidSortableAttribute,
+            UnsearchableScopedTerm { .. } => ErrorCode::UnsearchableScopedTerm,
+            InvalidLmdbOpenOptions => ErrorCode::InvalidLmdbOpenOptions,
+            SortRankingRuleMissing => ErrorCode::SortRankingRuleMissing,
+            InvalidStoreFile => ErrorCode::InvalidStoreFile,
+            MaxDatabaseSizeReached => ErrorCode::MaxDatabaseSizeReached,
+            MissingDocumentId { .. } => ErrorCode::MissingDocumentId,
+            TooManyDocumentIds { .. } => ErrorCode::TooManyDocumentIds,
+            NoPrimaryKeyCandidateFound => ErrorCode::MissingPrimaryKey,
+            MultiplePrimaryKeyCandidatesFound { .. } => ErrorCode::MultiplePrimaryKeyCandidates,
+            NoSpaceLeftOnDevice => ErrorCode::NoSpaceLeftOnDevice,
+            NoMoreDocumentIds => ErrorCode::NoMoreDocumentIds,
+            PrimaryKeyCannotBeChanged(_) => ErrorCode::PrimaryKeyCannotBeChanged,
+            SerdeJson(_) => ErrorCode::MalformedPayload,
+            SortError(_) => ErrorCode::InvalidSort,
+            UnknownInternalDocumentId { .. } => ErrorCode::UnknownInternalDocumentId,
+            InvalidMinTypoWordLenSetting(..) => ErrorCode::InvalidMinTypoWordLenSetting,
+            InvalidFieldLanguage { .. } => ErrorCode::InvalidFieldLanguage,
+            InvalidDetectedLanguage { .. } => ErrorCode::InvalidDetectedLanguage,
+            InvalidStemmingLanguage { .. } => ErrorCode::InvalidStemmingLanguage,
+            InvalidNumericFacetValue { .. } => ErrorCode::InvalidNumericFacetValue,
+            InvalidNormalizationRule { .. } => ErrorCode::InvalidNormalizationRule,
+            InvalidFieldBoost { .. } => ErrorCode::InvalidFieldBoost,
+            InvalidBoostAttribute { .. } => ErrorCode::InvalidBoostAttribute,
+            CriterionCannotBeDisabled { .. } => ErrorCode::CriterionCannotBeDisabled,
+            InvalidDump { .. } => ErrorCode::InvalidDump,
+        }
+    }
+}
+
+impl Error {
+    /// Returns `true` if the error is caused by the user, e.g. an invalid document or filter,
+    /// rather than by the engine or the system.
+    pub fn is_user_error(&self) -> bool {
+        matches!(self, Error::UserError(_))
+    }
+
+    /// Returns the stable code of the error if it is caused by the user.
+    pub fn user_error_code(&self) -> Option<ErrorCode> {
+        match self {
+            Error::UserError(error) => Some(error.code()),
+            _ => None,
+        }
+    }
+}
+
+/// Why a document identifier is rejected, see [`DocumentIdRules`](crate::update::DocumentIdRules).
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum InvalidDocumentIdReason {
+    #[error("It is neither an integer nor a string.")]
+    InvalidType,
+    #[error("It is empty.")]
+    Empty,
+    #[error("It contains the invalid characters {}.", .0.iter().map(|c| format!("`{}`", c.escape_debug())).collect::<Vec<_>>().join(", "))]
+    InvalidCharacters(Vec<char>),
+    #[error("It is {length} bytes long, more than the maximum of {max_length} bytes.")]
+    TooLong { length: usize, max_length: usize },
 }
 
 #[derive(Error, Debug)]
@@ -168,6 +465,8 @@ pub enum GeoError {
     BadLatitude { document_id: Value, value: Value },
     #[error("Could not parse longitude in the document with the id: `{document_id}`. Was expecting a finite number but instead got `{value}`.")]
     BadLongitude { document_id: Value, value: Value },
+    #[error("Could not parse the region of the `_geo` field in the document with the id: `{document_id}`. Was expecting a `boundingBox` with its south-west and north-east `[lat, lng]` corners or a `polygon` with at least three `[lat, lng]` vertices but instead got `{value}`.")]
+    BadShape { document_id: Value, value: Value },
 }
 
 fn format_invalid_filter_distribution(
@@ -318,3 +617,85 @@ fn conditionally_lookup_for_error_message() {
         assert_eq!(err.to_string(), format!("{} {}", prefix, suffix));
     }
 }
+
+#[test]
+fn suggest_closest_attribute() {
+    let fields = ["release_date", "price", "title"];
+    assert_eq!(closest_match("relase_date", fields), Some("release_date"));
+    assert_eq!(closest_match("pryce", fields), Some("price"));
+    assert_eq!(closest_match("tilte", fields), Some("title"));
+    assert_eq!(closest_match("genre", fields), None);
+    assert_eq!(closest_match("title", fields), None);
+
+    let err = UserError::InvalidSortableAttribute {
+        field: "relase_date".to_string(),
+        valid_fields: BTreeSet::from(["release_date".to_string(), "price".to_string()]),
+    };
+    insta::assert_display_snapshot!(err, @"Attribute `relase_date` is not sortable. Available sortable attributes are: `price, release_date`. Did you mean `release_date`?");
+}
+
+#[test]
+fn user_errors_have_unique_codes() {
+    use std::collections::HashSet;
+
+    use big_s::S;
+
+    let errors = vec![
+        UserError::AccessingSoftDeletedDocument { document_id: 0 },
+        UserError::AttributeLimitReached { external_id: None, limit: 0 },
+        UserError::CriterionError(CriterionError::ReservedName { name: S("_geo") }),
+        UserError::DocumentTooLarge { external_id: S("0"), size: 0, limit: 0 },
+        UserError::InvalidDocumentId {
+            document_id: Value::Null,
+            document_nth: None,
+            reason: InvalidDocumentIdReason::Empty,
+        },
+        UserError::InvalidFacetsDistribution {
+            invalid_facets_name: BTreeSet::new(),
+            valid_facets_name: BTreeSet::new(),
+        },
+        UserError::InvalidGeoField(GeoError::MissingLatitude { document_id: Value::Null }),
+        UserError::InvalidFilter(String::new()),
+        UserError::InvalidSortableAttribute { field: String::new(), valid_fields: BTreeSet::new() },
+        UserError::UnsearchableScopedTerm { field: String::new(), term: String::new() },
+        UserError::InvalidLmdbOpenOptions,
+        UserError::SortRankingRuleMissing,
+        UserError::InvalidStoreFile,
+        UserError::MaxDatabaseSizeReached,
+        UserError::MissingDocumentId { primary_key: S("id"), document: Object::new() },
+        UserError::TooManyDocumentIds { primary_key: S("id"), document: Object::new() },
+        UserError::NoPrimaryKeyCandidateFound,
+        UserError::MultiplePrimaryKeyCandidatesFound { candidates: vec![S("id"), S("uid")] },
+        UserError::NoSpaceLeftOnDevice,
+        UserError::NoMoreDocumentIds,
+        UserError::PrimaryKeyCannotBeChanged(S("id")),
+        UserError::SerdeJson(serde_json::from_str::<Value>("{").unwrap_err()),
+        UserError::SortError(SortError::InvalidLatitude),
+        UserError::UnknownInternalDocumentId { document_id: 0 },
+        UserError::InvalidMinTypoWordLenSetting(2, 1),
+        UserError::InvalidFieldLanguage { field: String::new(), language: String::new() },
+        UserError::InvalidDetectedLanguage { language: String::new() },
+        UserError::InvalidStemmingLanguage { language: String::new() },
+        UserError::InvalidNumericFacetValue { field: String::new(), value: String::new() },
+        UserError::InvalidNormalizationRule { pattern: String::new(), replacement: String::new() },
+        UserError::InvalidFieldBoost { field: String::new(), boost: 0.0 },
+        UserError::InvalidBoostAttribute { field: String::new(), valid_fields: BTreeSet::new() },
+        UserError::CriterionCannotBeDisabled { criterion: String::new(), reason: "" },
+        UserError::InvalidDump { reason: String::new() },
+    ];
+
+    let mut codes = HashSet::new();
+    for error in &errors {
+        let code = error.code();
+        assert!(!code.as_str().is_empty());
+        assert!(codes.insert(code.as_str()), "the code `{}` is used twice", code);
+    }
+    assert_eq!(codes.len(), errors.len());
+
+    let error = Error::from(UserError::MaxDatabaseSizeReached);
+    assert!(error.is_user_error());
+    assert_eq!(error.user_error_code(), Some(ErrorCode::MaxDatabaseSizeReached));
+    let error = Error::from(InternalError::AbortedIndexation);
+    assert!(!error.is_user_error());
+    assert_eq!(error.user_error_code(), None);
+}
@@ -4,7 +4,8 @@ use std::convert::TryInto;
 use std::{fmt, str};
 
 use fst::map::IndexedValue;
-use fst::{IntoStreamer, Streamer};
+use fst::raw::CompiledAddr;
+use fst::{Automaton, IntoStreamer, Streamer};
 use roaring::RoaringBitmap;
 
 const DELETED_ID: u64 = u64::MAX;
@@ -47,6 +48,55 @@ impl<'a> ExternalDocumentsIds<'a> {
         }
     }
 
+    /// Returns the internal ids of the given external ids in the same order, `None` for the ones
+    /// that [`Self::get`] wouldn't find.
+    ///
+    /// The external ids must be sorted and unique. The soft and hard maps are then only walked
+    /// along the paths they share with the external ids, instead of being searched for every
+    /// external id.
+    pub fn get_many<I, A>(&self, external_ids: I) -> fst::Result<Vec<Option<u32>>>
+    where
+        I: IntoIterator<Item = A>,
+        A: AsRef<[u8]>,
+    {
+        let mut builder = fst::MapBuilder::memory();
+        for (i, external_id) in external_ids.into_iter().enumerate() {
+            builder.insert(external_id, i as u64)?;
+        }
+        let batch = builder.into_map();
+
+        let mut ids = vec![None; batch.len()];
+        // the soft map is read after the hard one to prefer its ids, as in `get`.
+        for map in [&self.hard, &self.soft] {
+            let mut stream = map.search(FstKeys(batch.as_fst())).into_stream();
+            while let Some((external_id, id)) = stream.next() {
+                // only the keys of the batch are matched by the search.
+                if let Some(position) = batch.get(external_id) {
+                    ids[position as usize] = Some(id);
+                }
+            }
+        }
+
+        Ok(ids
+            .into_iter()
+            .map(|id| match id {
+                Some(id) if id != DELETED_ID && !self.soft_deleted_docids.contains(id as u32) => {
+                    Some(id.try_into().unwrap())
+                }
+                _otherwise => None,
+            })
+            .collect())
+    }
+
+    /// Returns an iterator over the external ids that [`Self::get`] would find,
+    /// in lexicographic order.
+    ///
+    /// The soft and hard maps are streamed, one lookup per map being done for every external
+    /// id, so that the external ids are never all loaded in memory.
+    pub fn into_external_ids(self) -> ExternalIds<'a> {
+        ExternalIds { ids: self, last: None }
+    }
+
     /// Rebuild the internal FSTs in the ExternalDocumentsIds structure such that they
     /// don't contain any soft deleted document id.
     pub fn delete_soft_deleted_documents_ids_from_fsts(&mut self) -> fst::Result<()> {
@@ -85,7 +135,11 @@ impl<'a> ExternalDocumentsIds<'a> {
 
         // We save the new map as the new soft map.
         self.soft = new_soft_builder.into_map().map_data(Cow::Owned)?;
-        self.merge_soft_into_hard()
+        if self.soft.len() >= self.hard.len() / 2 {
+            self.merge_soft_into_hard()?;
+        }
+
+        Ok(())
     }
 
     /// An helper function to debug this type, returns an `HashMap` of both,
@@ -106,24 +160,29 @@ impl<'a> ExternalDocumentsIds<'a> {
         map
     }
 
-    fn merge_soft_into_hard(&mut self) -> fst::Result<()> {
-        if self.soft.len() >= self.hard.len() / 2 {
-            let union_op = self.hard.op().add(&self.soft).r#union();
-
-            let mut iter = union_op.into_stream();
-            let mut new_hard_builder = fst::MapBuilder::memory();
-            while let Some((external_id, marked_docids)) = iter.next() {
-                let value = indexed_last_value(marked_docids).unwrap();
-                if value != DELETED_ID {
-                    new_hard_builder.insert(external_id, value)?;
-                }
+    /// Merges the soft map into the hard one, leaving an empty soft map.
+    ///
+    /// It is done automatically when inserting ids once the soft map is at least half
+    /// the size of the hard one, see [`Index::compact_external_documents_ids`] to do it
+    /// explicitly.
+    ///
+    /// [`Index::compact_external_documents_ids`]: crate::Index::compact_external_documents_ids
+    pub fn merge_soft_into_hard(&mut self) -> fst::Result<()> {
+        let union_op = self.hard.op().add(&self.soft).r#union();
+
+        let mut iter = union_op.into_stream();
+        let mut new_hard_builder = fst::MapBuilder::memory();
+        while let Some((external_id, marked_docids)) = iter.next() {
+            let value = indexed_last_value(marked_docids).unwrap();
+            if value != DELETED_ID {
+                new_hard_builder.insert(external_id, value)?;
             }
+        }
 
-            drop(iter);
+        drop(iter);
 
-            self.hard = new_hard_builder.into_map().map_data(Cow::Owned)?;
-            self.soft = fst::Map::default().map_data(Cow::Owned)?;
-        }
+        self.hard = new_hard_builder.into_map().map_data(Cow::Owned)?;
+        self.soft = fst::Map::default().map_data(Cow::Owned)?;
 
         Ok(())
     }
@@ -145,7 +204,118 @@ impl Default for ExternalDocumentsIds<'static> {
     }
 }
 
+/// An iterator over the external ids of an [`ExternalDocumentsIds`],
+/// see [`ExternalDocumentsIds::into_external_ids`].
+pub struct ExternalIds<'a> {
+    ids: ExternalDocumentsIds<'a>,
+    /// The last external id that was read from the maps.
+    last: Option<Vec<u8>>,
+}
+
+impl Iterator for ExternalIds<'_> {
+    type Item = String;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let hard = first_entry_after(&self.ids.hard, self.last.as_deref());
+            let soft = first_entry_after(&self.ids.soft, self.last.as_deref());
+            // the soft map is preferred over the hard one for the same external id, as in `get`.
+            let (external_id, id) = match (hard, soft) {
+                (Some(hard), Some(soft)) if hard.0 < soft.0 => hard,
+                (_, Some(soft)) => soft,
+                (Some(hard), None) => hard,
+                (None, None) => return None,
+            };
+
+            self.last = Some(external_id);
+            if id != DELETED_ID && !self.ids.soft_deleted_docids.contains(id as u32) {
+                let external_id = self.last.as_deref().unwrap();
+                return Some(String::from_utf8_lossy(external_id).into_owned());
+            }
+        }
+    }
+}
+
+/// Returns the first entry of the map whose key is greater than the given one,
+/// or the first entry of the map if no key is given.
+fn first_entry_after(map: &fst::Map<Cow<[u8]>>, key: Option<&[u8]>) -> Option<(Vec<u8>, u64)> {
+    let mut stream = match key {
+        Some(key) => map.range().gt(key).into_stream(),
+        None => map.stream(),
+    };
+    stream.next().map(|(key, value)| (key.to_vec(), value))
+}
+
 /// Returns the value of the `IndexedValue` with the highest _index_.
 fn indexed_last_value(indexed_values: &[IndexedValue]) -> Option<u64> {
     indexed_values.iter().copied().max_by_key(|iv| iv.index).map(|iv| iv.value)
 }
+
+/// An automaton matching the keys of an fst, to search another fst for these keys only.
+struct FstKeys<'a, D>(&'a fst::raw::Fst<D>);
+
+impl<D: AsRef<[u8]>> Automaton for FstKeys<'_, D> {
+    type State = Option<CompiledAddr>;
+
+    fn start(&self) -> Self::State {
+        Some(self.0.root().addr())
+    }
+
+    fn is_match(&self, state: &Self::State) -> bool {
+        state.map_or(false, |addr| self.0.node(addr).is_final())
+    }
+
+    fn can_match(&self, state: &Self::State) -> bool {
+        state.is_some()
+    }
+
+    fn accept(&self, state: &Self::State, byte: u8) -> Self::State {
+        let node = self.0.node((*state)?);
+        node.find_input(byte).map(|i| node.transition_addr(i))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::iter::FromIterator;
+
+    use super::*;
+
+    #[test]
+    fn get_many() {
+        let hard = fst::Map::from_iter([("a", 0), ("b", 1), ("c", 2), ("e", 4)]).unwrap();
+        let soft = fst::Map::from_iter([("b", 5), ("d", DELETED_ID), ("f", 6)]).unwrap();
+        let soft_deleted_docids = RoaringBitmap::from_iter([2]);
+        let ids = ExternalDocumentsIds::new(
+            hard.map_data(Cow::Owned).unwrap(),
+            soft.map_data(Cow::Owned).unwrap(),
+            soft_deleted_docids,
+        );
+
+        let external_ids = ["a", "b", "c", "d", "f", "g"];
+        let expected: Vec<_> = external_ids.iter().map(|id| ids.get(id)).collect();
+        assert_eq!(expected, vec![Some(0), Some(5), None, None, Some(6), None]);
+        assert_eq!(ids.get_many(external_ids).unwrap(), expected);
+
+        assert_eq!(ids.get_many(["e"]).unwrap(), vec![Some(4)]);
+        assert_eq!(ids.get_many(Vec::<String>::new()).unwrap(), vec![]);
+        // the external ids must be sorted.
+        assert!(ids.get_many(["b", "a"]).is_err());
+    }
+
+    #[test]
+    fn into_external_ids() {
+        let hard = fst::Map::from_iter([("a", 0), ("b", 1), ("c", 2), ("d", 3), ("e", 4)]).unwrap();
+        let soft = fst::Map::from_iter([("b", 5), ("d", DELETED_ID), ("f", 6)]).unwrap();
+        let soft_deleted_docids = RoaringBitmap::from_iter([2]);
+        let ids = ExternalDocumentsIds::new(
+            hard.map_data(Cow::Owned).unwrap(),
+            soft.map_data(Cow::Owned).unwrap(),
+            soft_deleted_docids,
+        );
+
+        let external_ids: Vec<_> = ids.into_external_ids().collect();
+        assert_eq!(external_ids, vec!["a", "b", "e", "f"]);
+        assert_eq!(ExternalDocumentsIds::default().into_external_ids().count(), 0);
+    }
+}
@@ -0,0 +1,27 @@
+use serde::{Deserialize, Serialize};
+
+/// How the string values of a faceted field that also contains numbers are indexed,
+/// see [`Settings::set_mixed_facet_types`](crate::update::Settings::set_mixed_facet_types).
+///
+/// Without a mode the numbers and the strings are indexed separately, e.g. `10` is only
+/// matched by the numeric filters like `price < 20` and `"10"` only by the string filters.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum MixedFacetTypes {
+    /// The field only accepts numbers, the documents addition fails if a document
+    /// contains a string value for this field.
+    StrictNumeric,
+    /// The strings that can be parsed as numbers, e.g. `"10"` or `"2.5"`, are indexed as
+    /// numbers only, the other strings are still indexed as strings.
+    Coerce,
+    /// The strings that can be parsed as numbers are indexed as both numbers and strings,
+    /// and so are the numbers, e.g. `10` and `"10"` are both matched by `price < 20` and
+    /// are the same value in the facet distribution of the strings.
+    DualIndex,
+}
+
+impl MixedFacetTypes {
+    /// Parses the string facet value as a number, ignoring the surrounding whitespaces.
+    pub(crate) fn parse_number(value: &str) -> Option<f64> {
+        value.trim().parse::<f64>().ok().filter(|number| number.is_finite())
+    }
+}
@@ -4,6 +4,9 @@ use serde::{Deserialize, Serialize};
 
 use crate::FieldId;
 
+/// The maximum number of fields of an index, the field ids are `u16`.
+pub const MAX_FIELDS: usize = FieldId::MAX as usize + 1;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FieldsIdsMap {
     names_ids: BTreeMap<String, FieldId>,
@@ -0,0 +1,237 @@
+//! Entry points to fuzz milli with `cargo fuzz`, enabled by the `fuzz` feature, the targets
+//! calling them are in the `fuzz` directory of the crate. The module is also compiled by the
+//! tests, which replay the regression entries of the corpus.
+//!
+//! The harnesses ignore the inputs that are too large to be processed quickly and panic when
+//! they find a bug, i.e. an internal error or a codec that doesn't round-trip. The user errors
+//! are the expected outcome of most of the inputs and are ignored.
+
+use std::borrow::Cow;
+use std::io::Cursor;
+
+use heed::{BytesDecode, BytesEncode, EnvOpenOptions};
+use once_cell::sync::Lazy;
+use tempfile::TempDir;
+
+use crate::documents::{DocumentsBatchBuilder, DocumentsBatchReader};
+use crate::update::{IndexDocuments, IndexDocumentsConfig, IndexerConfig, Settings};
+use crate::{
+    BoRoaringBitmapCodec, BoRoaringBitmapLenCodec, CboRoaringBitmapCodec, CboRoaringBitmapLenCodec,
+    Error, Filter, Index, Result, RoaringBitmapCodec, RoaringBitmapLenCodec,
+};
+
+/// The maximum size of the inputs, the larger ones are ignored.
+const MAX_INPUT_LEN: usize = 64 * 1024;
+
+/// The maximum length of the filters, the recursive descent of the parser can overflow
+/// the stack beyond, which doesn't happen with the filters of a normal build.
+const MAX_FILTER_LEN: usize = 500;
+
+/// The size of the indexes of the harnesses, the inputs are small.
+const MAP_SIZE: usize = 10 * 1024 * 1024;
+
+/// The documents of the index the filters are evaluated against, they contain every
+/// kind of filterable value.
+const FILTER_DOCUMENTS: &str = r#"[
+    { "id": 0, "price": 10, "color": "red", "tags": ["a", "b"], "available": true,
+      "_geo": { "lat": 45.0, "lng": 3.0 } },
+    { "id": 1, "price": -3.5, "color": "Blue", "tags": [], "available": false,
+      "_geo": { "lat": -10.5, "lng": 170.0 } },
+    { "id": 2, "color": null, "nested": { "size": 3, "name": "small" } }
+]"#;
+
+/// An index stored in a temporary directory, kept for the whole fuzzing session.
+struct FuzzIndex {
+    index: Index,
+    _tempdir: TempDir,
+}
+
+impl FuzzIndex {
+    fn new() -> Result<FuzzIndex> {
+        let mut options = EnvOpenOptions::new();
+        options.map_size(MAP_SIZE);
+        let tempdir = TempDir::new()?;
+        let index = Index::new(options, tempdir.path())?;
+        Ok(FuzzIndex { index, _tempdir: tempdir })
+    }
+}
+
+/// The empty index the documents of [`fuzz_transform`] are transformed against.
+static TRANSFORM_INDEX: Lazy<FuzzIndex> =
+    Lazy::new(|| FuzzIndex::new().expect("the fuzzing index couldn't be created"));
+
+/// The index the filters of [`fuzz_filter`] are evaluated against.
+static FILTER_INDEX: Lazy<FuzzIndex> = Lazy::new(|| {
+    let index = FuzzIndex::new().expect("the fuzzing index couldn't be created");
+    fill_filter_index(&index.index).expect("the fuzzing index couldn't be filled");
+    index
+});
+
+fn fill_filter_index(index: &Index) -> Result<()> {
+    let config = IndexerConfig::default();
+    let mut wtxn = index.write_txn()?;
+
+    let mut settings = Settings::new(&mut wtxn, index, &config);
+    settings.set_primary_key("id".to_string());
+    let filterable = ["id", "price", "color", "tags", "available", "nested", "_geo"];
+    settings.set_filterable_fields(filterable.iter().map(|f| f.to_string()).collect());
+    settings.execute(|_| (), || false)?;
+
+    let mut builder = DocumentsBatchBuilder::new(Vec::new());
+    builder.append_json_array(FILTER_DOCUMENTS.as_bytes())?;
+    let documents = DocumentsBatchReader::from_reader(Cursor::new(builder.into_inner()?))?;
+    let indexing = IndexDocuments::new(
+        &mut wtxn,
+        index,
+        &config,
+        IndexDocumentsConfig::default(),
+        |_| (),
+        || false,
+    )?;
+    let (indexing, user_result) = indexing.add_documents(documents)?;
+    user_result?;
+    indexing.execute()?;
+
+    wtxn.commit()?;
+    Ok(())
+}
+
+/// Panics if the error is not a user error, it is a bug in milli.
+#[track_caller]
+fn expect_user_error<T>(result: Result<T>) -> Option<T> {
+    match result {
+        Ok(value) => Some(value),
+        Err(Error::UserError(_)) => None,
+        Err(error) => panic!("found a non-user error: {error:?}"),
+    }
+}
+
+/// Reads the bytes as a JSON array of documents, or as NDJSON, and transforms them like
+/// an addition of documents to an empty index, i.e. their primary key is validated and
+/// they are flattened. The documents are never written in the index.
+pub fn fuzz_transform(bytes: &[u8]) {
+    if bytes.len() > MAX_INPUT_LEN {
+        return;
+    }
+
+    let mut builder = DocumentsBatchBuilder::new(Vec::new());
+    let appended = match bytes.iter().find(|b| !b.is_ascii_whitespace()) {
+        Some(b'[') => builder.append_json_array(bytes),
+        _ => builder.append_ndjson(bytes),
+    };
+    if appended.is_err() {
+        // the input is not a valid batch of JSON documents.
+        return;
+    }
+    let documents = builder.into_inner().expect("the documents batch couldn't be written");
+    let documents = DocumentsBatchReader::from_reader(Cursor::new(documents))
+        .expect("the documents batch couldn't be read back");
+
+    let index = &TRANSFORM_INDEX.index;
+    let config = IndexerConfig::default();
+    let mut wtxn = index.write_txn().expect("the write transaction couldn't be opened");
+    let indexing = IndexDocuments::new(
+        &mut wtxn,
+        index,
+        &config,
+        IndexDocumentsConfig::default(),
+        |_| (),
+        || false,
+    );
+    if let Some(indexing) = expect_user_error(indexing) {
+        if let Some((indexing, user_result)) = expect_user_error(indexing.add_documents(documents))
+        {
+            // the documents are transformed, not indexed.
+            let _ = user_result;
+            drop(indexing);
+        }
+    }
+    wtxn.abort().expect("the write transaction couldn't be aborted");
+}
+
+/// Parses the bytes as a filter and evaluates it against a tiny index.
+pub fn fuzz_filter(bytes: &[u8]) {
+    let expression = match std::str::from_utf8(bytes) {
+        Ok(expression) if expression.len() <= MAX_FILTER_LEN => expression,
+        _ => return,
+    };
+
+    let filter = match expect_user_error(Filter::from_str(expression)) {
+        Some(Some(filter)) => filter,
+        _ => return,
+    };
+
+    let index = &FILTER_INDEX.index;
+    let rtxn = index.read_txn().expect("the read transaction couldn't be opened");
+    if let Some(candidates) = expect_user_error(filter.evaluate(&rtxn, index)) {
+        let documents_ids = index.documents_ids(&rtxn).expect("the documents ids are missing");
+        assert!(candidates.is_subset(&documents_ids), "the filter matched unknown documents");
+    }
+}
+
+/// Decodes the bytes with the bitmap codecs and checks that the bitmaps they decode
+/// are encoded back to the same bitmaps, and that their lengths are decoded correctly.
+pub fn fuzz_cbo_codec(bytes: &[u8]) {
+    if bytes.len() > MAX_INPUT_LEN {
+        return;
+    }
+
+    if let Some(bitmap) = CboRoaringBitmapCodec::bytes_decode(bytes) {
+        let encoded = CboRoaringBitmapCodec::bytes_encode(&bitmap).unwrap();
+        assert_eq!(CboRoaringBitmapCodec::bytes_decode(&encoded).as_ref(), Some(&bitmap));
+        assert_eq!(CboRoaringBitmapLenCodec::bytes_decode(&encoded), Some(bitmap.len()));
+
+        // merging a bitmap with itself gives the same bitmap.
+        let mut merged = Vec::new();
+        CboRoaringBitmapCodec::merge_into(&[Cow::Borrowed(bytes), encoded], &mut merged)
+            .expect("a decodable bitmap couldn't be merged");
+        assert_eq!(CboRoaringBitmapCodec::bytes_decode(&merged), Some(bitmap));
+    }
+
+    if let Some(bitmap) = RoaringBitmapCodec::bytes_decode(bytes) {
+        let encoded = RoaringBitmapCodec::bytes_encode(&bitmap).unwrap();
+        assert_eq!(RoaringBitmapCodec::bytes_decode(&encoded).as_ref(), Some(&bitmap));
+        assert_eq!(RoaringBitmapLenCodec::bytes_decode(&encoded), Some(bitmap.len()));
+    }
+    // the length is decoded without decoding the bitmap, it must not panic either.
+    let _ = RoaringBitmapLenCodec::bytes_decode(bytes);
+
+    if let Some(bitmap) = BoRoaringBitmapCodec::bytes_decode(bytes) {
+        let encoded = BoRoaringBitmapCodec::bytes_encode(&bitmap).unwrap();
+        assert_eq!(BoRoaringBitmapCodec::bytes_decode(&encoded).as_ref(), Some(&bitmap));
+        assert_eq!(BoRoaringBitmapLenCodec::bytes_decode(&encoded), Some(bitmap.len()));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::path::Path;
+
+    use super::*;
+
+    #[test]
+    fn regression_corpus() {
+        // the regression entries of the corpus of the fuzz targets, the other entries
+        // are the local corpus of `cargo fuzz` and are not versioned.
+        let targets: [(&str, fn(&[u8])); 3] =
+            [("transform", fuzz_transform), ("filter", fuzz_filter), ("cbo_codec", fuzz_cbo_codec)];
+
+        let corpus = Path::new(env!("CARGO_MANIFEST_DIR")).join("fuzz").join("corpus");
+        let mut replayed = 0;
+        for (target, fuzz) in targets {
+            for entry in fs::read_dir(corpus.join(target)).unwrap() {
+                let path = entry.unwrap().path();
+                let is_regression = path
+                    .file_name()
+                    .and_then(|name| name.to_str())
+                    .map_or(false, |name| name.starts_with("regression-"));
+                if is_regression {
+                    fuzz(&fs::read(&path).unwrap());
+                    replayed += 1;
+                }
+            }
+        }
+        assert!(replayed > 0, "no regression entry found in {}", corpus.display());
+    }
+}
@@ -12,6 +12,7 @@ use roaring::RoaringBitmap;
 pub use self::field_doc_id_facet_codec::FieldDocIdFacetCodec;
 pub use self::ordered_f64_codec::OrderedF64Codec;
 use super::StrRefCodec;
+use crate::facet::FacetType;
 use crate::{CboRoaringBitmapCodec, BEU16};
 
 pub type FieldDocIdFacetF64Codec = FieldDocIdFacetCodec<OrderedF64Codec>;
@@ -76,8 +77,8 @@ where
     type DItem = FacetGroupKey<T::DItem>;
 
     fn bytes_decode(bytes: &'a [u8]) -> Option<Self::DItem> {
-        let fid = u16::from_be_bytes(<[u8; 2]>::try_from(&bytes[0..=1]).ok()?);
-        let level = bytes[2];
+        let fid = u16::from_be_bytes(<[u8; 2]>::try_from(bytes.get(0..=1)?).ok()?);
+        let level = *bytes.get(2)?;
         let bound = T::bytes_decode(&bytes[3..])?;
         Some(FacetGroupKey { field_id: fid, level, left_bound: bound })
     }
@@ -96,8 +97,90 @@ impl<'a> heed::BytesEncode<'a> for FacetGroupValueCodec {
 impl<'a> heed::BytesDecode<'a> for FacetGroupValueCodec {
     type DItem = FacetGroupValue;
     fn bytes_decode(bytes: &'a [u8]) -> Option<Self::DItem> {
-        let size = bytes[0];
-        let bitmap = CboRoaringBitmapCodec::deserialize_from(&bytes[1..]).ok()?;
+        let (&size, bitmap) = bytes.split_first()?;
+        let bitmap = CboRoaringBitmapCodec::deserialize_from(bitmap).ok()?;
         Some(FacetGroupValue { size, bitmap })
     }
 }
+
+/// A key of the [`facet_id_string_docids`][`Index::facet_id_string_docids`] or
+/// [`facet_id_f64_docids`][`Index::facet_id_f64_docids`] database, see [`decode_key`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FacetKey<'a> {
+    String(FacetGroupKey<&'a str>),
+    Number(FacetGroupKey<f64>),
+}
+
+impl FacetKey<'_> {
+    pub fn field_id(&self) -> u16 {
+        match self {
+            FacetKey::String(key) => key.field_id,
+            FacetKey::Number(key) => key.field_id,
+        }
+    }
+
+    pub fn level(&self) -> u8 {
+        match self {
+            FacetKey::String(key) => key.level,
+            FacetKey::Number(key) => key.level,
+        }
+    }
+
+    pub fn facet_type(&self) -> FacetType {
+        match self {
+            FacetKey::String(_) => FacetType::String,
+            FacetKey::Number(_) => FacetType::Number,
+        }
+    }
+}
+
+/// Decodes a key of the facet database of the given type, `None` if the key is malformed.
+pub fn decode_key(facet_type: FacetType, bytes: &[u8]) -> Option<FacetKey> {
+    match facet_type {
+        FacetType::String => {
+            FacetGroupKeyCodec::<StrRefCodec>::bytes_decode(bytes).map(FacetKey::String)
+        }
+        FacetType::Number => {
+            FacetGroupKeyCodec::<OrderedF64Codec>::bytes_decode(bytes).map(FacetKey::Number)
+        }
+    }
+}
+
+/// Encodes a key of a facet database, the inverse of [`decode_key`].
+pub fn encode_key<'a>(key: &'a FacetKey<'a>) -> Option<Cow<'a, [u8]>> {
+    match key {
+        FacetKey::String(key) => FacetGroupKeyCodec::<StrRefCodec>::bytes_encode(key),
+        FacetKey::Number(key) => FacetGroupKeyCodec::<OrderedF64Codec>::bytes_encode(key),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn facet_key_round_trip() {
+        let keys = [
+            FacetKey::String(FacetGroupKey { field_id: 0, level: 0, left_bound: "drama" }),
+            FacetKey::String(FacetGroupKey { field_id: 3, level: 2, left_bound: "" }),
+            FacetKey::Number(FacetGroupKey { field_id: 1, level: 0, left_bound: -12.5 }),
+            FacetKey::Number(FacetGroupKey { field_id: 258, level: 4, left_bound: 1e10 }),
+        ];
+
+        for key in keys {
+            let bytes = encode_key(&key).unwrap();
+            let decoded = decode_key(key.facet_type(), &bytes).unwrap();
+            assert_eq!(decoded, key);
+            assert_eq!(decoded.field_id(), key.field_id());
+            assert_eq!(decoded.level(), key.level());
+        }
+    }
+
+    #[test]
+    fn decode_malformed_key() {
+        assert_eq!(decode_key(FacetType::String, &[]), None);
+        assert_eq!(decode_key(FacetType::String, &[0, 1]), None);
+        assert_eq!(decode_key(FacetType::String, &[0, 1, 0, 0xff]), None);
+        assert_eq!(decode_key(FacetType::Number, &[0, 1, 0, 1, 2, 3]), None);
+    }
+}
@@ -0,0 +1,31 @@
+use std::borrow::Cow;
+
+use crate::{try_split_array_at, FieldId};
+
+pub struct FieldIdBoolCodec;
+
+impl<'a> heed::BytesDecode<'a> for FieldIdBoolCodec {
+    type DItem = (FieldId, bool);
+
+    fn bytes_decode(bytes: &'a [u8]) -> Option<Self::DItem> {
+        let (field_id_bytes, bytes) = try_split_array_at(bytes)?;
+        let field_id = u16::from_be_bytes(field_id_bytes);
+        let ([value], _nothing) = try_split_array_at(bytes)?;
+        match value {
+            0 => Some((field_id, false)),
+            1 => Some((field_id, true)),
+            _ => None,
+        }
+    }
+}
+
+impl<'a> heed::BytesEncode<'a> for FieldIdBoolCodec {
+    type EItem = (FieldId, bool);
+
+    fn bytes_encode((field_id, value): &Self::EItem) -> Option<Cow<[u8]>> {
+        let mut bytes = Vec::with_capacity(2 + 1);
+        bytes.extend_from_slice(&field_id.to_be_bytes());
+        bytes.push(*value as u8);
+        Some(Cow::Owned(bytes))
+    }
+}
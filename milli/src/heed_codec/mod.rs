@@ -1,6 +1,8 @@
 mod beu32_str_codec;
 mod byte_slice_ref;
 pub mod facet;
+mod field_id_bool_codec;
+mod field_id_str_codec;
 mod field_id_word_count_codec;
 mod obkv_codec;
 mod roaring_bitmap;
@@ -13,6 +15,8 @@ pub use byte_slice_ref::ByteSliceRefCodec;
 pub use str_ref::StrRefCodec;
 
 pub use self::beu32_str_codec::BEU32StrCodec;
+pub use self::field_id_bool_codec::FieldIdBoolCodec;
+pub use self::field_id_str_codec::FieldIdStrCodec;
 pub use self::field_id_word_count_codec::FieldIdWordCountCodec;
 pub use self::obkv_codec::ObkvCodec;
 pub use self::roaring_bitmap::{BoRoaringBitmapCodec, CboRoaringBitmapCodec, RoaringBitmapCodec};
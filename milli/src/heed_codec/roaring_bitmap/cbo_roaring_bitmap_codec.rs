@@ -41,6 +41,7 @@ impl CboRoaringBitmapCodec {
         if bytes.len() <= THRESHOLD * size_of::<u32>() {
             // If there is threshold or less than threshold integers that can fit into this array
             // of bytes it means that we used the ByteOrder codec serializer.
+            check_integers_len(bytes)?;
             let mut bitmap = RoaringBitmap::new();
             while let Ok(integer) = bytes.read_u32::<NativeEndian>() {
                 bitmap.insert(integer);
@@ -64,6 +65,7 @@ impl CboRoaringBitmapCodec {
 
         for bytes in slices {
             if bytes.len() <= THRESHOLD * size_of::<u32>() {
+                check_integers_len(bytes)?;
                 let mut reader = bytes.as_ref();
                 while let Ok(integer) = reader.read_u32::<NativeEndian>() {
                     vec.push(integer);
@@ -95,6 +97,16 @@ impl CboRoaringBitmapCodec {
     }
 }
 
+/// Returns an error if the bytes encoded with the ByteOrder method don't contain
+/// a whole number of integers, the trailing bytes would otherwise be ignored.
+fn check_integers_len(bytes: &[u8]) -> io::Result<()> {
+    if bytes.len() % size_of::<u32>() == 0 {
+        Ok(())
+    } else {
+        Err(io::Error::new(io::ErrorKind::InvalidData, "truncated integer in a CboRoaringBitmap"))
+    }
+}
+
 impl heed::BytesDecode<'_> for CboRoaringBitmapCodec {
     type DItem = RoaringBitmap;
 
@@ -129,6 +141,17 @@ mod tests {
         assert_eq!(input, output);
     }
 
+    #[test]
+    fn truncated_integers() {
+        let input = RoaringBitmap::from_iter(0..3);
+        let bytes = CboRoaringBitmapCodec::bytes_encode(&input).unwrap();
+        assert!(CboRoaringBitmapCodec::bytes_decode(&bytes[..bytes.len() - 1]).is_none());
+
+        let mut buffer = Vec::new();
+        let slices = [bytes.clone(), Cow::Borrowed(&bytes[..5])];
+        assert!(CboRoaringBitmapCodec::merge_into(&slices, &mut buffer).is_err());
+    }
+
     #[test]
     fn verify_threshold() {
         let input = RoaringBitmap::from_iter(0..THRESHOLD as u32);
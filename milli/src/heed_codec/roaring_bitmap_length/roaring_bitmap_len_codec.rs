@@ -1,4 +1,4 @@
-use std::io::{self, BufRead, Read};
+use std::io::{self, Read};
 use std::mem;
 
 use byteorder::{LittleEndian, ReadBytesExt};
@@ -31,7 +31,7 @@ impl RoaringBitmapLenCodec {
         let description_bytes = &mut &description_bytes[..];
 
         if has_offsets {
-            bytes.consume(size * 4);
+            skip_bytes(&mut bytes, size * 4)?;
         }
 
         let mut length = 0;
@@ -41,9 +41,9 @@ impl RoaringBitmapLenCodec {
             length += len;
 
             if len <= 4096 {
-                bytes.consume(len as usize * mem::size_of::<u16>());
+                skip_bytes(&mut bytes, len as usize * mem::size_of::<u16>())?;
             } else {
-                bytes.consume(1024 * mem::size_of::<u64>())
+                skip_bytes(&mut bytes, 1024 * mem::size_of::<u64>())?;
             }
         }
 
@@ -51,6 +51,18 @@ impl RoaringBitmapLenCodec {
     }
 }
 
+/// Skips the given number of bytes, returns an error instead of panicking
+/// if the bitmap is truncated.
+fn skip_bytes(bytes: &mut &[u8], count: usize) -> io::Result<()> {
+    match bytes.get(count..) {
+        Some(remaining) => {
+            *bytes = remaining;
+            Ok(())
+        }
+        None => Err(io::Error::new(io::ErrorKind::UnexpectedEof, "truncated roaring bitmap")),
+    }
+}
+
 impl heed::BytesDecode<'_> for RoaringBitmapLenCodec {
     type DItem = u64;
 
@@ -74,4 +86,13 @@ mod tests {
         let len = RoaringBitmapLenCodec::deserialize_from_slice(&bytes).unwrap();
         assert_eq!(bitmap.len(), len);
     }
+
+    #[test]
+    fn truncated_roaring_bitmap_length() {
+        let bitmap: RoaringBitmap = (0..500).chain(800..800_000).collect();
+        let bytes = RoaringBitmapCodec::bytes_encode(&bitmap).unwrap();
+        for len in [4, 8, 20, bytes.len() - 1] {
+            assert!(RoaringBitmapLenCodec::deserialize_from_slice(&bytes[..len]).is_err());
+        }
+    }
 }
@@ -1,9 +1,12 @@
 use std::borrow::Cow;
-use std::collections::{HashMap, HashSet};
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, BinaryHeap, HashMap, HashSet};
 use std::fs::File;
 use std::mem::size_of;
 use std::path::Path;
 
+use charabia::{Language, Script};
+use fst::Streamer;
 use heed::flags::Flags;
 use heed::types::*;
 use heed::{CompactionOption, Database, PolyDatabase, RoTxn, RwTxn};
@@ -11,19 +14,24 @@ use roaring::RoaringBitmap;
 use rstar::RTree;
 use time::OffsetDateTime;
 
-use crate::error::{InternalError, UserError};
-use crate::facet::FacetType;
+use crate::error::{FieldIdMapMissingEntry, InternalError, UserError};
+use crate::facet::{FacetType, MixedFacetTypes};
 use crate::fields_ids_map::FieldsIdsMap;
 use crate::heed_codec::facet::{
     FacetGroupKeyCodec, FacetGroupValueCodec, FieldDocIdFacetF64Codec, FieldDocIdFacetStringCodec,
     FieldIdCodec, OrderedF64Codec,
 };
 use crate::heed_codec::StrRefCodec;
+use crate::search::{word_derivations, WordDerivationsCache};
+use crate::stemming::Stemmers;
 use crate::{
-    default_criteria, BEU32StrCodec, BoRoaringBitmapCodec, CboRoaringBitmapCodec, Criterion,
-    DocumentId, ExternalDocumentsIds, FacetDistribution, FieldDistribution, FieldId,
-    FieldIdWordCountCodec, GeoPoint, ObkvCodec, Result, RoaringBitmapCodec, RoaringBitmapLenCodec,
-    Search, StrBEU32Codec, U8StrStrCodec, BEU16, BEU32,
+    default_criteria, obkv_to_flattened, script_language_from_code, BEU32StrCodec,
+    BoRoaringBitmapCodec, CboRoaringBitmapCodec, Criterion, DocumentId, ExternalDocumentsIds,
+    FacetDistribution, FieldDistribution, FieldId, FieldIdBoolCodec, FieldIdStrCodec,
+    FieldIdWordCountCodec, Filter, GeoPoint, GeoShape, IntegrityIssue, LongWordsPolicy,
+    MaxFieldsPolicy, NumericFacetStats, ObkvCodec, Result, RoaringBitmapCodec,
+    RoaringBitmapLenCodec, Search, StopWordsMode, StrBEU32Codec, TypoOnDiacritics, U8StrStrCodec,
+    BEU16, BEU32, BEU64, MAX_FIELDS, MAX_WORD_LENGTH,
 };
 
 pub const DEFAULT_MIN_WORD_LEN_ONE_TYPO: u8 = 5;
@@ -42,13 +50,16 @@ pub mod main_key {
     pub const FIELDS_IDS_MAP_KEY: &str = "fields-ids-map";
     pub const GEO_FACETED_DOCUMENTS_IDS_KEY: &str = "geo-faceted-documents-ids";
     pub const GEO_RTREE_KEY: &str = "geo-rtree";
+    pub const GEO_SHAPES_RTREE_KEY: &str = "geo-shapes-rtree";
     pub const HARD_EXTERNAL_DOCUMENTS_IDS_KEY: &str = "hard-external-documents-ids";
+    pub const INDEXING_SEQUENCE_KEY: &str = "indexing-sequence";
     pub const NUMBER_FACETED_DOCUMENTS_IDS_PREFIX: &str = "number-faceted-documents-ids";
     pub const PRIMARY_KEY_KEY: &str = "primary-key";
     pub const SEARCHABLE_FIELDS_KEY: &str = "searchable-fields";
     pub const USER_DEFINED_SEARCHABLE_FIELDS_KEY: &str = "user-defined-searchable-fields";
     pub const SOFT_EXTERNAL_DOCUMENTS_IDS_KEY: &str = "soft-external-documents-ids";
     pub const STOP_WORDS_KEY: &str = "stop-words";
+    pub const STOP_WORDS_MODE_KEY: &str = "stop-words-mode";
     pub const STRING_FACETED_DOCUMENTS_IDS_PREFIX: &str = "string-faceted-documents-ids";
     pub const SYNONYMS_KEY: &str = "synonyms";
     pub const WORDS_FST_KEY: &str = "words-fst";
@@ -60,8 +71,30 @@ pub mod main_key {
     pub const TWO_TYPOS_WORD_LEN: &str = "two-typos-word-len";
     pub const EXACT_WORDS: &str = "exact-words";
     pub const EXACT_ATTRIBUTES: &str = "exact-attributes";
+    pub const CASE_SENSITIVE_ATTRIBUTES: &str = "case-sensitive-attributes";
     pub const MAX_VALUES_PER_FACET: &str = "max-values-per-facet";
     pub const PAGINATION_MAX_TOTAL_HITS: &str = "pagination-max-total-hits";
+    pub const MAX_WORD_LENGTH: &str = "max-word-length";
+    pub const LONG_WORDS_POLICY: &str = "long-words-policy";
+    pub const MIN_INDEXED_WORD_LENGTH: &str = "min-indexed-word-length";
+    pub const MAX_POSITIONS_PER_WORD: &str = "max-positions-per-word";
+    pub const MAX_FIELDS: &str = "max-fields";
+    pub const MAX_FIELDS_POLICY: &str = "max-fields-policy";
+    pub const FIELD_BOOSTS_KEY: &str = "field-boosts";
+    pub const FIELDS_LANGUAGES_KEY: &str = "fields-languages";
+    pub const DETECTED_LANGUAGES_KEY: &str = "detected-languages";
+    pub const STEMMING_KEY: &str = "stemming";
+    pub const NORMALIZATION_RULES_KEY: &str = "normalization-rules";
+    pub const MIXED_FACET_TYPES_KEY: &str = "mixed-facet-types";
+    pub const PROXIMITY_DATABASES_KEY: &str = "proximity-databases";
+    pub const CROSS_FIELD_PROXIMITY_KEY: &str = "cross-field-proximity";
+    pub const TYPO_ON_DIACRITICS_KEY: &str = "typo-on-diacritics";
+    pub const BOOLEAN_FACETS_KEY: &str = "boolean-facets";
+    pub const CANONICAL_NUMBERS_KEY: &str = "canonical-numbers";
+    pub const FACET_STRING_ORIGINALS_KEY: &str = "facet-string-originals";
+    pub const BUCKETED_POSITIONS_KEY: &str = "bucketed-positions";
+    pub const NULL_VALUES_UNCOUNTED_KEY: &str = "null-values-uncounted";
+    pub const TOKENIZER_FINGERPRINT_KEY: &str = "tokenizer-fingerprint";
 }
 
 pub mod db_name {
@@ -80,9 +113,59 @@ pub mod db_name {
     pub const FACET_ID_F64_DOCIDS: &str = "facet-id-f64-docids";
     pub const FACET_ID_EXISTS_DOCIDS: &str = "facet-id-exists-docids";
     pub const FACET_ID_STRING_DOCIDS: &str = "facet-id-string-docids";
+    pub const FACET_ID_BOOL_DOCIDS: &str = "facet-id-bool-docids";
+    pub const FACET_ID_STRING_ORIGINALS: &str = "facet-id-string-originals";
     pub const FIELD_ID_DOCID_FACET_F64S: &str = "field-id-docid-facet-f64s";
     pub const FIELD_ID_DOCID_FACET_STRINGS: &str = "field-id-docid-facet-strings";
     pub const DOCUMENTS: &str = "documents";
+    pub const DOCUMENTS_INDEXED_AT: &str = "documents-indexed-at";
+}
+
+/// The number of databases of an index, the main one included.
+const NUMBER_OF_DATABASES: u32 = 21;
+
+/// The options used to open the LMDB environment of an [`Index`], on top of the [`heed::EnvOpenOptions`].
+///
+/// These options trade the durability of the index for the speed of the writes, they are meant
+/// for the initial imports of big datasets: a crash during such an import means that the index
+/// must be rebuilt from scratch. Call [`Index::force_sync`] at the end of the import, and then
+/// reopen the index without these options.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct IndexOpenOptions {
+    unsafe_no_sync: bool,
+    writemap: bool,
+}
+
+impl IndexOpenOptions {
+    pub fn new() -> IndexOpenOptions {
+        IndexOpenOptions::default()
+    }
+
+    /// Don't flush the system buffers to the disk when committing a transaction (`MDB_NOSYNC`).
+    ///
+    /// A crash of the system, or of the process when combined with [`writemap`](Self::writemap),
+    /// can corrupt the index, it must then be rebuilt.
+    pub fn unsafe_no_sync(&mut self, value: bool) -> &mut IndexOpenOptions {
+        self.unsafe_no_sync = value;
+        self
+    }
+
+    /// Use a writeable memory map instead of the `write` syscalls to write the pages (`MDB_WRITEMAP`).
+    ///
+    /// A bug in the process can then corrupt the index, it must then be rebuilt.
+    pub fn writemap(&mut self, value: bool) -> &mut IndexOpenOptions {
+        self.writemap = value;
+        self
+    }
+
+    fn apply(&self, options: &mut heed::EnvOpenOptions) {
+        if self.unsafe_no_sync {
+            unsafe { options.flag(Flags::MdbNoSync) };
+        }
+        if self.writemap {
+            unsafe { options.flag(Flags::MdbWriteMap) };
+        }
+    }
 }
 
 #[derive(Clone)]
@@ -129,6 +212,11 @@ pub struct Index {
     pub facet_id_f64_docids: Database<FacetGroupKeyCodec<OrderedF64Codec>, FacetGroupValueCodec>,
     /// Maps the facet field id and ranges of strings with the docids that corresponds to them.
     pub facet_id_string_docids: Database<FacetGroupKeyCodec<StrRefCodec>, FacetGroupValueCodec>,
+    /// Maps the facet field id and the booleans with the docids that corresponds to them.
+    pub facet_id_bool_docids: Database<FieldIdBoolCodec, CboRoaringBitmapCodec>,
+    /// Maps the facet field id and the normalized strings with the number of documents
+    /// that contain each of their original spellings.
+    pub facet_id_string_originals: Database<FieldIdStrCodec, SerdeJson<BTreeMap<String, u64>>>,
 
     /// Maps the document id, the facet field id and the numbers.
     pub field_id_docid_facet_f64s: Database<FieldDocIdFacetF64Codec, Unit>,
@@ -137,6 +225,8 @@ pub struct Index {
 
     /// Maps the document id to the document as an obkv store.
     pub(crate) documents: Database<OwnedType<BEU32>, ObkvCodec>,
+    /// Maps the document id to the indexing sequence number at which it was last indexed.
+    pub documents_indexed_at: Database<OwnedType<BEU32>, OwnedType<BEU64>>,
 }
 
 impl Index {
@@ -148,7 +238,7 @@ impl Index {
     ) -> Result<Index> {
         use db_name::*;
 
-        options.max_dbs(18);
+        options.max_dbs(NUMBER_OF_DATABASES);
         unsafe { options.flag(Flags::MdbAlwaysFreePages) };
 
         let env = options.open(path)?;
@@ -169,11 +259,14 @@ impl Index {
         let facet_id_f64_docids = env.create_database(Some(FACET_ID_F64_DOCIDS))?;
         let facet_id_string_docids = env.create_database(Some(FACET_ID_STRING_DOCIDS))?;
         let facet_id_exists_docids = env.create_database(Some(FACET_ID_EXISTS_DOCIDS))?;
+        let facet_id_bool_docids = env.create_database(Some(FACET_ID_BOOL_DOCIDS))?;
+        let facet_id_string_originals = env.create_database(Some(FACET_ID_STRING_ORIGINALS))?;
 
         let field_id_docid_facet_f64s = env.create_database(Some(FIELD_ID_DOCID_FACET_F64S))?;
         let field_id_docid_facet_strings =
             env.create_database(Some(FIELD_ID_DOCID_FACET_STRINGS))?;
         let documents = env.create_database(Some(DOCUMENTS))?;
+        let documents_indexed_at = env.create_database(Some(DOCUMENTS_INDEXED_AT))?;
 
         Index::set_creation_dates(&env, main, created_at, updated_at)?;
 
@@ -194,9 +287,12 @@ impl Index {
             facet_id_f64_docids,
             facet_id_string_docids,
             facet_id_exists_docids,
+            facet_id_bool_docids,
+            facet_id_string_originals,
             field_id_docid_facet_f64s,
             field_id_docid_facet_strings,
             documents,
+            documents_indexed_at,
         })
     }
 
@@ -205,6 +301,61 @@ impl Index {
         Self::new_with_creation_dates(options, path, now, now)
     }
 
+    /// Opens the index with the given [`IndexOpenOptions`], see their documentation
+    /// for the durability guarantees that are lost.
+    pub fn new_with_open_options<P: AsRef<Path>>(
+        mut options: heed::EnvOpenOptions,
+        path: P,
+        open_options: IndexOpenOptions,
+    ) -> Result<Index> {
+        open_options.apply(&mut options);
+        Self::new(options, path)
+    }
+
+    /// Opens the databases of an already existing environment without creating them,
+    /// returning an error if one of them is missing.
+    fn open_existing(env: heed::Env) -> Result<Index> {
+        use db_name::*;
+
+        let main = env
+            .open_poly_database(Some(MAIN))?
+            .ok_or(InternalError::DatabaseMissing { db_name: MAIN })?;
+
+        Ok(Index {
+            main,
+            word_docids: open_existing_database(&env, WORD_DOCIDS)?,
+            exact_word_docids: open_existing_database(&env, EXACT_WORD_DOCIDS)?,
+            word_prefix_docids: open_existing_database(&env, WORD_PREFIX_DOCIDS)?,
+            exact_word_prefix_docids: open_existing_database(&env, EXACT_WORD_PREFIX_DOCIDS)?,
+            docid_word_positions: open_existing_database(&env, DOCID_WORD_POSITIONS)?,
+            word_pair_proximity_docids: open_existing_database(&env, WORD_PAIR_PROXIMITY_DOCIDS)?,
+            word_prefix_pair_proximity_docids: open_existing_database(
+                &env,
+                WORD_PREFIX_PAIR_PROXIMITY_DOCIDS,
+            )?,
+            prefix_word_pair_proximity_docids: open_existing_database(
+                &env,
+                PREFIX_WORD_PAIR_PROXIMITY_DOCIDS,
+            )?,
+            word_position_docids: open_existing_database(&env, WORD_POSITION_DOCIDS)?,
+            word_prefix_position_docids: open_existing_database(&env, WORD_PREFIX_POSITION_DOCIDS)?,
+            field_id_word_count_docids: open_existing_database(&env, FIELD_ID_WORD_COUNT_DOCIDS)?,
+            facet_id_f64_docids: open_existing_database(&env, FACET_ID_F64_DOCIDS)?,
+            facet_id_string_docids: open_existing_database(&env, FACET_ID_STRING_DOCIDS)?,
+            facet_id_exists_docids: open_existing_database(&env, FACET_ID_EXISTS_DOCIDS)?,
+            facet_id_bool_docids: open_existing_database(&env, FACET_ID_BOOL_DOCIDS)?,
+            facet_id_string_originals: open_existing_database(&env, FACET_ID_STRING_ORIGINALS)?,
+            field_id_docid_facet_f64s: open_existing_database(&env, FIELD_ID_DOCID_FACET_F64S)?,
+            field_id_docid_facet_strings: open_existing_database(
+                &env,
+                FIELD_ID_DOCID_FACET_STRINGS,
+            )?,
+            documents: open_existing_database(&env, DOCUMENTS)?,
+            documents_indexed_at: open_existing_database(&env, DOCUMENTS_INDEXED_AT)?,
+            env,
+        })
+    }
+
     fn set_creation_dates(
         env: &heed::Env,
         main: PolyDatabase,
@@ -225,6 +376,12 @@ impl Index {
                 main_key::CREATED_AT_KEY,
                 &created_at,
             )?;
+            // A new index stores its booleans in the dedicated database from the start.
+            main.put::<_, Str, Unit>(&mut txn, main_key::BOOLEAN_FACETS_KEY, &())?;
+            main.put::<_, Str, Unit>(&mut txn, main_key::CANONICAL_NUMBERS_KEY, &())?;
+            main.put::<_, Str, Unit>(&mut txn, main_key::FACET_STRING_ORIGINALS_KEY, &())?;
+            main.put::<_, Str, Unit>(&mut txn, main_key::BUCKETED_POSITIONS_KEY, &())?;
+            main.put::<_, Str, Unit>(&mut txn, main_key::NULL_VALUES_UNCOUNTED_KEY, &())?;
             txn.commit()?;
         }
         Ok(())
@@ -245,6 +402,12 @@ impl Index {
         self.env.path()
     }
 
+    /// Flushes the data buffers to the disk, even if the index was opened with
+    /// [`IndexOpenOptions::unsafe_no_sync`]. Call it at the end of an import.
+    pub fn force_sync(&self) -> Result<()> {
+        Ok(self.env.force_sync()?)
+    }
+
     /// Returns the size used by the index without the cached pages.
     pub fn used_size(&self) -> Result<u64> {
         Ok(self.env.non_free_pages_size()?)
@@ -327,6 +490,42 @@ impl Index {
             .unwrap_or_default())
     }
 
+    /// Returns the number of internal documents ids that can still be given to new documents.
+    ///
+    /// The ids of the soft-deleted documents are only made available again once the documents
+    /// are hard-deleted.
+    pub fn available_ids_count(&self, rtxn: &RoTxn) -> Result<u64> {
+        let used = self.number_of_documents(rtxn)?
+            + self
+                .main
+                .get::<_, Str, RoaringBitmapLenCodec>(
+                    rtxn,
+                    main_key::SOFT_DELETED_DOCUMENTS_IDS_KEY,
+                )?
+                .unwrap_or_default();
+        Ok(u32::MAX as u64 + 1 - used)
+    }
+
+    /// Removes the soft-deleted documents from the candidates, when there are no candidates
+    /// returns all the non-deleted documents if some documents are soft-deleted.
+    ///
+    /// The soft-deleted documents are still part of the word and facet databases, the read
+    /// paths that don't start from the candidates of a search must use this method. `None`
+    /// is returned when there are no candidates and no soft-deleted documents, i.e. when
+    /// the databases can be read as is.
+    pub(crate) fn live_candidates(
+        &self,
+        rtxn: &RoTxn,
+        candidates: Option<&RoaringBitmap>,
+    ) -> heed::Result<Option<RoaringBitmap>> {
+        let soft_deleted = self.soft_deleted_documents_ids(rtxn)?;
+        match candidates {
+            Some(candidates) => Ok(Some(candidates - soft_deleted)),
+            None if soft_deleted.is_empty() => Ok(None),
+            None => self.documents_ids(rtxn).map(Some),
+        }
+    }
+
     /* primary key */
 
     /// Writes the documents primary key, this is the field name that is used to store the id.
@@ -388,6 +587,25 @@ impl Index {
         Ok(ExternalDocumentsIds::new(hard, soft, soft_deleted_docids))
     }
 
+    /// Returns an iterator over the external ids of the documents of the index,
+    /// in lexicographic order, the soft-deleted documents being excluded.
+    ///
+    /// The ids are streamed from the external documents ids maps and never all loaded in memory.
+    pub fn external_ids<'t>(&self, rtxn: &'t RoTxn) -> Result<impl Iterator<Item = String> + 't> {
+        Ok(self.external_documents_ids(rtxn)?.into_external_ids())
+    }
+
+    /// Merges the soft external documents ids into the hard ones.
+    ///
+    /// This merge otherwise happens during a documents addition, once there are enough soft
+    /// external documents ids, this allows to schedule it at a more convenient time.
+    pub fn compact_external_documents_ids(&self, wtxn: &mut RwTxn) -> Result<()> {
+        let mut external_documents_ids = self.external_documents_ids(wtxn)?.into_static();
+        external_documents_ids.merge_soft_into_hard()?;
+        self.put_external_documents_ids(wtxn, &external_documents_ids)?;
+        Ok(())
+    }
+
     /* fields ids map */
 
     /// Writes the fields ids map which associate the documents keys with an internal field id
@@ -409,6 +627,12 @@ impl Index {
             .unwrap_or_default())
     }
 
+    /// Returns the number of fields of the index, including the flattened ones, an index
+    /// can't contain more than [`MAX_FIELDS`](crate::MAX_FIELDS) fields.
+    pub fn fields_count(&self, rtxn: &RoTxn) -> heed::Result<usize> {
+        Ok(self.fields_ids_map(rtxn)?.len())
+    }
+
     /* geo rtree */
 
     /// Writes the provided `rtree` which associates coordinates to documents ids.
@@ -436,6 +660,31 @@ impl Index {
         }
     }
 
+    /// Writes the provided `rtree` which associates the bounding boxes of regions to documents ids.
+    pub(crate) fn put_geo_shapes_rtree(
+        &self,
+        wtxn: &mut RwTxn,
+        rtree: &RTree<GeoShape>,
+    ) -> heed::Result<()> {
+        self.main.put::<_, Str, SerdeBincode<RTree<GeoShape>>>(
+            wtxn,
+            main_key::GEO_SHAPES_RTREE_KEY,
+            rtree,
+        )
+    }
+
+    /// Delete the `rtree` which associates the bounding boxes of regions to documents ids.
+    pub(crate) fn delete_geo_shapes_rtree(&self, wtxn: &mut RwTxn) -> heed::Result<bool> {
+        self.main.delete::<_, Str>(wtxn, main_key::GEO_SHAPES_RTREE_KEY)
+    }
+
+    /// Returns the `rtree` which associates the bounding boxes of regions to documents ids.
+    pub fn geo_shapes_rtree(&self, rtxn: &RoTxn) -> Result<Option<RTree<GeoShape>>> {
+        Ok(self
+            .main
+            .get::<_, Str, SerdeBincode<RTree<GeoShape>>>(rtxn, main_key::GEO_SHAPES_RTREE_KEY)?)
+    }
+
     /* geo faceted */
 
     /// Writes the documents ids that are faceted with a _geo field.
@@ -806,6 +1055,23 @@ impl Index {
         self.main.put::<_, ByteSlice, RoaringBitmapCodec>(wtxn, &buffer, docids)
     }
 
+    /// Deletes the documents ids that are faceted under this field id for the given facet type.
+    pub(crate) fn delete_faceted_documents_ids(
+        &self,
+        wtxn: &mut RwTxn,
+        field_id: FieldId,
+        facet_type: FacetType,
+    ) -> heed::Result<bool> {
+        let key = match facet_type {
+            FacetType::String => main_key::STRING_FACETED_DOCUMENTS_IDS_PREFIX,
+            FacetType::Number => main_key::NUMBER_FACETED_DOCUMENTS_IDS_PREFIX,
+        };
+        let mut buffer = vec![0u8; key.len() + size_of::<FieldId>()];
+        buffer[..key.len()].copy_from_slice(key.as_bytes());
+        buffer[key.len()..].copy_from_slice(&field_id.to_be_bytes());
+        self.main.delete::<_, ByteSlice>(wtxn, &buffer)
+    }
+
     /// Retrieve all the documents ids that are faceted under this field id for the given facet type.
     pub fn faceted_documents_ids(
         &self,
@@ -838,6 +1104,204 @@ impl Index {
         }
     }
 
+    /// Retrieve all the documents which contain this boolean value for the given field id.
+    pub fn facet_bool_docids(
+        &self,
+        rtxn: &RoTxn,
+        field_id: FieldId,
+        value: bool,
+    ) -> heed::Result<RoaringBitmap> {
+        match self.facet_id_bool_docids.get(rtxn, &(field_id, value))? {
+            Some(docids) => Ok(docids),
+            None => Ok(RoaringBitmap::new()),
+        }
+    }
+
+    /// Computes the minimum, maximum and average of the number values of this field id,
+    /// restricted to the `candidates` when given, returns `None` if there is no such value.
+    ///
+    /// The whole level 0 of the [`facet_id_f64_docids`](Self::facet_id_f64_docids) database
+    /// of the field is read, see [`Self::facet_numeric_range`] for a cheaper min/max.
+    pub fn facet_numeric_stats(
+        &self,
+        rtxn: &RoTxn,
+        field_id: FieldId,
+        candidates: Option<&RoaringBitmap>,
+    ) -> Result<Option<NumericFacetStats>> {
+        crate::search::facet::facet_numeric_stats(rtxn, self, field_id, candidates)
+    }
+
+    /// Returns the smallest and biggest number values of this field id,
+    /// restricted to the `candidates` when given.
+    ///
+    /// Without candidates, the bounds are directly read at both ends of the sorted level 0.
+    pub fn facet_numeric_range(
+        &self,
+        rtxn: &RoTxn,
+        field_id: FieldId,
+        candidates: Option<&RoaringBitmap>,
+    ) -> Result<Option<(f64, f64)>> {
+        crate::search::facet::facet_numeric_range(rtxn, self, field_id, candidates)
+    }
+
+    /// Returns the number of `candidates` having each number, string and boolean value of this
+    /// field id, the values none of the candidates have are omitted.
+    ///
+    /// Only the cardinalities of the intersections of the values documents with the candidates
+    /// are computed, see [`FacetDistribution`] to count the values of several facets at once.
+    pub fn facet_counts_in(
+        &self,
+        rtxn: &RoTxn,
+        field_id: FieldId,
+        candidates: &RoaringBitmap,
+    ) -> Result<BTreeMap<String, u64>> {
+        crate::search::facet::facet_counts_in(rtxn, self, field_id, candidates)
+    }
+
+    /// Estimates the fraction, between 0 and 1, of the documents matching all the words
+    /// of the `query` and the `filter`, without running the search.
+    ///
+    /// It is computed from the number of documents containing each word of the query and
+    /// matching each condition of the filter, the words and conditions are assumed to be
+    /// independent from each other. It is therefore only an approximation, which can be far
+    /// from the real fraction when the words often appear together, e.g. in a name.
+    pub fn estimate_selectivity(
+        &self,
+        rtxn: &RoTxn,
+        query: &str,
+        filter: Option<&Filter>,
+    ) -> Result<f64> {
+        crate::search::estimate_selectivity(rtxn, self, query, filter)
+    }
+
+    /// Verifies the invariants between the databases of the index, e.g. that the words
+    /// databases only contain known documents or that the words FST matches their words,
+    /// and returns every violation found.
+    ///
+    /// It is a read-only diagnostic which reads most of the databases, it can take a while
+    /// on a big index.
+    pub fn check_integrity(&self, rtxn: &RoTxn) -> Result<Vec<IntegrityIssue>> {
+        crate::integrity::check_integrity(rtxn, self)
+    }
+
+    /// Writes a dump of the index, its settings and documents, into the writer, it can be
+    /// imported into another index with [`import_dump`](crate::import_dump).
+    ///
+    /// Unlike a copy of the database file, a dump doesn't depend on the architecture nor on
+    /// the version of the engine, the documents are indexed again when it is imported.
+    pub fn export_dump<W: std::io::Write>(&self, rtxn: &RoTxn, writer: W) -> Result<()> {
+        crate::dump::export_dump(rtxn, self, writer)
+    }
+
+    /* boolean facets */
+
+    pub(crate) fn put_boolean_facets_indexed(&self, wtxn: &mut RwTxn) -> heed::Result<()> {
+        self.main.put::<_, Str, Unit>(wtxn, main_key::BOOLEAN_FACETS_KEY, &())
+    }
+
+    /// Returns whether the booleans of the documents are stored in the
+    /// [`facet_id_bool_docids`](Self::facet_id_bool_docids) database.
+    ///
+    /// The indexes created by older versions stored them as the `"true"` and `"false"` strings,
+    /// they are moved to their own database by [`migrate_boolean_facets`].
+    ///
+    /// [`migrate_boolean_facets`]: crate::update::migrate_boolean_facets
+    pub fn boolean_facets_indexed(&self, rtxn: &RoTxn) -> heed::Result<bool> {
+        Ok(self.main.get::<_, Str, Unit>(rtxn, main_key::BOOLEAN_FACETS_KEY)?.is_some())
+    }
+
+    /* canonical numbers */
+
+    pub(crate) fn put_canonical_numbers(&self, wtxn: &mut RwTxn) -> heed::Result<()> {
+        self.main.put::<_, Str, Unit>(wtxn, main_key::CANONICAL_NUMBERS_KEY, &())
+    }
+
+    /// Returns whether the numbers of the documents are indexed in their canonical form,
+    /// see [`crate::canonical_number`].
+    ///
+    /// The indexes created by older versions indexed the numbers as they were written, e.g. `1.0`
+    /// isn't found by searching `1`. They must be reindexed, e.g. by changing their searchable
+    /// fields, for the numbers of their documents to be canonicalized.
+    pub fn canonical_numbers(&self, rtxn: &RoTxn) -> heed::Result<bool> {
+        Ok(self.main.get::<_, Str, Unit>(rtxn, main_key::CANONICAL_NUMBERS_KEY)?.is_some())
+    }
+
+    /* facet string originals */
+
+    pub(crate) fn put_facet_string_originals_indexed(&self, wtxn: &mut RwTxn) -> heed::Result<()> {
+        self.main.put::<_, Str, Unit>(wtxn, main_key::FACET_STRING_ORIGINALS_KEY, &())
+    }
+
+    /// Returns whether the original spellings of the facet strings are counted in the
+    /// [`facet_id_string_originals`](Self::facet_id_string_originals) database.
+    ///
+    /// The indexes created by older versions didn't count them, the facet distribution then
+    /// returns the spelling of the first document of every value until all the documents are
+    /// reindexed.
+    pub fn facet_string_originals_indexed(&self, rtxn: &RoTxn) -> heed::Result<bool> {
+        Ok(self.main.get::<_, Str, Unit>(rtxn, main_key::FACET_STRING_ORIGINALS_KEY)?.is_some())
+    }
+
+    /* null values uncounted */
+
+    pub(crate) fn put_null_values_uncounted(&self, wtxn: &mut RwTxn) -> heed::Result<()> {
+        self.main.put::<_, Str, Unit>(wtxn, main_key::NULL_VALUES_UNCOUNTED_KEY, &())
+    }
+
+    /// Returns whether the fields of the documents with a null value are left out of the
+    /// [`field_distribution`](Self::field_distribution).
+    ///
+    /// The indexes created by older versions counted them, their field distribution is counted
+    /// again by the next documents addition or deletion.
+    pub fn null_values_uncounted(&self, rtxn: &RoTxn) -> heed::Result<bool> {
+        Ok(self.main.get::<_, Str, Unit>(rtxn, main_key::NULL_VALUES_UNCOUNTED_KEY)?.is_some())
+    }
+
+    /* bucketed positions */
+
+    pub(crate) fn put_bucketed_positions(&self, wtxn: &mut RwTxn) -> heed::Result<()> {
+        self.main.put::<_, Str, Unit>(wtxn, main_key::BUCKETED_POSITIONS_KEY, &())
+    }
+
+    /// Returns whether the positions of the [`word_position_docids`](Self::word_position_docids)
+    /// and [`word_prefix_position_docids`](Self::word_prefix_position_docids) databases are
+    /// bucketed, see [`crate::bucketed_position`].
+    ///
+    /// The indexes created by older versions stored the exact positions, they keep storing them
+    /// until all their documents are reindexed.
+    pub fn bucketed_positions(&self, rtxn: &RoTxn) -> heed::Result<bool> {
+        Ok(self.main.get::<_, Str, Unit>(rtxn, main_key::BUCKETED_POSITIONS_KEY)?.is_some())
+    }
+
+    /* tokenizer fingerprint */
+
+    /// Writes the fingerprint of the tokenizer configuration the documents are tokenized with,
+    /// `None` being the default configuration.
+    pub(crate) fn put_tokenizer_fingerprint(
+        &self,
+        wtxn: &mut RwTxn,
+        fingerprint: Option<u64>,
+    ) -> heed::Result<()> {
+        match fingerprint {
+            Some(fingerprint) => self.main.put::<_, Str, OwnedType<u64>>(
+                wtxn,
+                main_key::TOKENIZER_FINGERPRINT_KEY,
+                &fingerprint,
+            ),
+            None => {
+                self.main.delete::<_, Str>(wtxn, main_key::TOKENIZER_FINGERPRINT_KEY)?;
+                Ok(())
+            }
+        }
+    }
+
+    /// Returns the [fingerprint](crate::update::TokenizerConfig::fingerprint) of the tokenizer
+    /// configuration the documents of the index were tokenized with, `None` if they were
+    /// tokenized with the default configuration.
+    pub fn tokenizer_fingerprint(&self, rtxn: &RoTxn) -> heed::Result<Option<u64>> {
+        self.main.get::<_, Str, OwnedType<u64>>(rtxn, main_key::TOKENIZER_FINGERPRINT_KEY)
+    }
+
     /* distinct field */
 
     pub(crate) fn put_distinct_field(
@@ -877,138 +1341,618 @@ impl Index {
         }
     }
 
-    /* words fst */
+    /* field boosts */
 
-    /// Writes the FST which is the words dictionary of the engine.
-    pub(crate) fn put_words_fst<A: AsRef<[u8]>>(
+    pub(crate) fn put_field_boosts(
         &self,
         wtxn: &mut RwTxn,
-        fst: &fst::Set<A>,
+        boosts: &BTreeMap<String, f32>,
     ) -> heed::Result<()> {
-        self.main.put::<_, Str, ByteSlice>(wtxn, main_key::WORDS_FST_KEY, fst.as_fst().as_bytes())
+        self.main.put::<_, Str, SerdeJson<_>>(wtxn, main_key::FIELD_BOOSTS_KEY, boosts)
     }
 
-    /// Returns the FST which is the words dictionary of the engine.
-    pub fn words_fst<'t>(&self, rtxn: &'t RoTxn) -> Result<fst::Set<Cow<'t, [u8]>>> {
-        match self.main.get::<_, Str, ByteSlice>(rtxn, main_key::WORDS_FST_KEY)? {
-            Some(bytes) => Ok(fst::Set::new(bytes)?.map_data(Cow::Borrowed)?),
-            None => Ok(fst::Set::default().map_data(Cow::Owned)?),
-        }
+    pub(crate) fn delete_field_boosts(&self, wtxn: &mut RwTxn) -> heed::Result<bool> {
+        self.main.delete::<_, Str>(wtxn, main_key::FIELD_BOOSTS_KEY)
     }
 
-    /* stop words */
+    /// Returns the boost factor of the searchable fields used by the attribute criterion,
+    /// the fields that are not part of it have a boost of `1.0`.
+    pub fn field_boosts(&self, rtxn: &RoTxn) -> heed::Result<BTreeMap<String, f32>> {
+        Ok(self
+            .main
+            .get::<_, Str, SerdeJson<_>>(rtxn, main_key::FIELD_BOOSTS_KEY)?
+            .unwrap_or_default())
+    }
 
-    pub(crate) fn put_stop_words<A: AsRef<[u8]>>(
+    /* fields languages */
+
+    pub(crate) fn put_fields_languages(
         &self,
         wtxn: &mut RwTxn,
-        fst: &fst::Set<A>,
+        languages: &BTreeMap<String, String>,
     ) -> heed::Result<()> {
-        self.main.put::<_, Str, ByteSlice>(wtxn, main_key::STOP_WORDS_KEY, fst.as_fst().as_bytes())
+        self.main.put::<_, Str, SerdeJson<_>>(wtxn, main_key::FIELDS_LANGUAGES_KEY, languages)
     }
 
-    pub(crate) fn delete_stop_words(&self, wtxn: &mut RwTxn) -> heed::Result<bool> {
-        self.main.delete::<_, Str>(wtxn, main_key::STOP_WORDS_KEY)
+    pub(crate) fn delete_fields_languages(&self, wtxn: &mut RwTxn) -> heed::Result<bool> {
+        self.main.delete::<_, Str>(wtxn, main_key::FIELDS_LANGUAGES_KEY)
     }
 
-    pub fn stop_words<'t>(&self, rtxn: &'t RoTxn) -> Result<Option<fst::Set<&'t [u8]>>> {
-        match self.main.get::<_, Str, ByteSlice>(rtxn, main_key::STOP_WORDS_KEY)? {
-            Some(bytes) => Ok(Some(fst::Set::new(bytes)?)),
-            None => Ok(None),
-        }
+    /// Returns the ISO 639-3 language code given as a hint to the tokenizer for each field,
+    /// the fields without a hint rely on the automatic language detection.
+    pub fn fields_languages(&self, rtxn: &RoTxn) -> heed::Result<BTreeMap<String, String>> {
+        Ok(self
+            .main
+            .get::<_, Str, SerdeJson<_>>(rtxn, main_key::FIELDS_LANGUAGES_KEY)?
+            .unwrap_or_default())
     }
 
-    /* synonyms */
+    /* detected languages allow list */
 
-    pub(crate) fn put_synonyms(
+    pub(crate) fn put_detected_languages_allow_list(
         &self,
         wtxn: &mut RwTxn,
-        synonyms: &HashMap<Vec<String>, Vec<Vec<String>>>,
+        languages: &[String],
     ) -> heed::Result<()> {
-        self.main.put::<_, Str, SerdeBincode<_>>(wtxn, main_key::SYNONYMS_KEY, synonyms)
+        self.main.put::<_, Str, SerdeJson<_>>(wtxn, main_key::DETECTED_LANGUAGES_KEY, languages)
     }
 
-    pub(crate) fn delete_synonyms(&self, wtxn: &mut RwTxn) -> heed::Result<bool> {
-        self.main.delete::<_, Str>(wtxn, main_key::SYNONYMS_KEY)
+    pub(crate) fn delete_detected_languages_allow_list(
+        &self,
+        wtxn: &mut RwTxn,
+    ) -> heed::Result<bool> {
+        self.main.delete::<_, Str>(wtxn, main_key::DETECTED_LANGUAGES_KEY)
     }
 
-    pub fn synonyms(&self, rtxn: &RoTxn) -> heed::Result<HashMap<Vec<String>, Vec<Vec<String>>>> {
+    /// Returns the ISO 639-3 codes of the languages the tokenizer is restricted to detect,
+    /// at both indexing and search time, all the languages are detected when it is empty.
+    pub fn detected_languages_allow_list(&self, rtxn: &RoTxn) -> heed::Result<Vec<String>> {
         Ok(self
             .main
-            .get::<_, Str, SerdeBincode<_>>(rtxn, main_key::SYNONYMS_KEY)?
+            .get::<_, Str, SerdeJson<_>>(rtxn, main_key::DETECTED_LANGUAGES_KEY)?
             .unwrap_or_default())
     }
 
-    pub fn words_synonyms<S: AsRef<str>>(
+    /// Returns the allow list given to the tokenizer for the fields without a language hint.
+    pub(crate) fn documents_allow_list(
         &self,
         rtxn: &RoTxn,
-        words: &[S],
-    ) -> heed::Result<Option<Vec<Vec<String>>>> {
-        let words: Vec<_> = words.iter().map(|s| s.as_ref().to_owned()).collect();
-        Ok(self.synonyms(rtxn)?.remove(&words))
+    ) -> Result<HashMap<Script, Vec<Language>>> {
+        Ok(crate::detected_languages_allow_list(&self.detected_languages_allow_list(rtxn)?))
     }
 
-    /* words prefixes fst */
+    /* stemming */
 
-    /// Writes the FST which is the words prefixes dictionnary of the engine.
-    pub(crate) fn put_words_prefixes_fst<A: AsRef<[u8]>>(
+    pub(crate) fn put_stemming(
         &self,
         wtxn: &mut RwTxn,
-        fst: &fst::Set<A>,
+        stemming: &BTreeMap<String, bool>,
     ) -> heed::Result<()> {
-        self.main.put::<_, Str, ByteSlice>(
-            wtxn,
-            main_key::WORDS_PREFIXES_FST_KEY,
-            fst.as_fst().as_bytes(),
-        )
+        self.main.put::<_, Str, SerdeJson<_>>(wtxn, main_key::STEMMING_KEY, stemming)
     }
 
-    /// Returns the FST which is the words prefixes dictionnary of the engine.
-    pub fn words_prefixes_fst<'t>(&self, rtxn: &'t RoTxn) -> Result<fst::Set<Cow<'t, [u8]>>> {
-        match self.main.get::<_, Str, ByteSlice>(rtxn, main_key::WORDS_PREFIXES_FST_KEY)? {
-            Some(bytes) => Ok(fst::Set::new(bytes)?.map_data(Cow::Borrowed)?),
-            None => Ok(fst::Set::default().map_data(Cow::Owned)?),
-        }
+    pub(crate) fn delete_stemming(&self, wtxn: &mut RwTxn) -> heed::Result<bool> {
+        self.main.delete::<_, Str>(wtxn, main_key::STEMMING_KEY)
     }
 
-    /* word documents count */
+    /// Returns whether the words of each language, given as an ISO 639-3 code, are stemmed.
+    /// The words of the languages that are not listed are not stemmed.
+    pub fn stemming(&self, rtxn: &RoTxn) -> heed::Result<BTreeMap<String, bool>> {
+        Ok(self.main.get::<_, Str, SerdeJson<_>>(rtxn, main_key::STEMMING_KEY)?.unwrap_or_default())
+    }
 
-    /// Returns the number of documents ids associated with the given word,
-    /// it is much faster than deserializing the bitmap and getting the length of it.
-    pub fn word_documents_count(&self, rtxn: &RoTxn, word: &str) -> heed::Result<Option<u64>> {
-        self.word_docids.remap_data_type::<RoaringBitmapLenCodec>().get(rtxn, word)
+    /// Returns the stemmers of the languages the stemming is enabled for.
+    pub(crate) fn stemmers(&self, rtxn: &RoTxn) -> heed::Result<Stemmers> {
+        let stemming = self.stemming(rtxn)?;
+        Ok(Stemmers::from_codes(
+            stemming.iter().filter(|(_, enabled)| **enabled).map(|(code, _)| code.as_str()),
+        ))
     }
 
-    /* documents */
+    /* normalization rules */
 
-    /// Returns a [`Vec`] of the requested documents. Returns an error if a document is missing.
-    pub fn documents<'t>(
+    pub(crate) fn put_normalization_rules(
         &self,
-        rtxn: &'t RoTxn,
-        ids: impl IntoIterator<Item = DocumentId>,
-    ) -> Result<Vec<(DocumentId, obkv::KvReaderU16<'t>)>> {
-        let soft_deleted_documents = self.soft_deleted_documents_ids(rtxn)?;
-        let mut documents = Vec::new();
+        wtxn: &mut RwTxn,
+        rules: &BTreeMap<String, String>,
+    ) -> heed::Result<()> {
+        self.main.put::<_, Str, SerdeJson<_>>(wtxn, main_key::NORMALIZATION_RULES_KEY, rules)
+    }
 
-        for id in ids {
-            if soft_deleted_documents.contains(id) {
-                return Err(UserError::AccessingSoftDeletedDocument { document_id: id })?;
-            }
-            let kv = self
-                .documents
-                .get(rtxn, &BEU32::new(id))?
-                .ok_or(UserError::UnknownInternalDocumentId { document_id: id })?;
-            documents.push((id, kv));
-        }
+    pub(crate) fn delete_normalization_rules(&self, wtxn: &mut RwTxn) -> heed::Result<bool> {
+        self.main.delete::<_, Str>(wtxn, main_key::NORMALIZATION_RULES_KEY)
+    }
 
-        Ok(documents)
+    /// Returns the replacements applied to the words after the default normalization,
+    /// at both indexing and search time, see [`crate::apply_normalization_rules`].
+    pub fn normalization_rules(&self, rtxn: &RoTxn) -> heed::Result<BTreeMap<String, String>> {
+        Ok(self
+            .main
+            .get::<_, Str, SerdeJson<_>>(rtxn, main_key::NORMALIZATION_RULES_KEY)?
+            .unwrap_or_default())
     }
 
-    /// Returns an iterator over all the documents in the index.
-    pub fn all_documents<'t>(
-        &self,
-        rtxn: &'t RoTxn,
-    ) -> Result<impl Iterator<Item = heed::Result<(DocumentId, obkv::KvReaderU16<'t>)>>> {
-        let soft_deleted_docids = self.soft_deleted_documents_ids(rtxn)?;
+    /* mixed facet types */
+
+    pub(crate) fn put_mixed_facet_types(
+        &self,
+        wtxn: &mut RwTxn,
+        modes: &BTreeMap<String, MixedFacetTypes>,
+    ) -> heed::Result<()> {
+        self.main.put::<_, Str, SerdeJson<_>>(wtxn, main_key::MIXED_FACET_TYPES_KEY, modes)
+    }
+
+    pub(crate) fn delete_mixed_facet_types(&self, wtxn: &mut RwTxn) -> heed::Result<bool> {
+        self.main.delete::<_, Str>(wtxn, main_key::MIXED_FACET_TYPES_KEY)
+    }
+
+    /// Returns how the string values of each faceted field containing both numbers and
+    /// strings are indexed, the fields without a mode index them separately.
+    pub fn mixed_facet_types(
+        &self,
+        rtxn: &RoTxn,
+    ) -> heed::Result<BTreeMap<String, MixedFacetTypes>> {
+        Ok(self
+            .main
+            .get::<_, Str, SerdeJson<_>>(rtxn, main_key::MIXED_FACET_TYPES_KEY)?
+            .unwrap_or_default())
+    }
+
+    /// Returns the mixed facet types mode and the name of the fields with a mode.
+    pub(crate) fn fields_ids_mixed_facet_types(
+        &self,
+        rtxn: &RoTxn,
+    ) -> Result<HashMap<FieldId, (MixedFacetTypes, String)>> {
+        let fields_ids_map = self.fields_ids_map(rtxn)?;
+        let mut modes = HashMap::new();
+        for (field, mode) in self.mixed_facet_types(rtxn)? {
+            if let Some(fid) = fields_ids_map.id(&field) {
+                modes.insert(fid, (mode, field));
+            }
+        }
+        Ok(modes)
+    }
+
+    /// Returns the allow list given to the tokenizer for each field with a language hint,
+    /// the hint replaces the detected languages of its script.
+    pub(crate) fn fields_ids_allow_lists(
+        &self,
+        rtxn: &RoTxn,
+    ) -> Result<HashMap<FieldId, HashMap<Script, Vec<Language>>>> {
+        let fields_ids_map = self.fields_ids_map(rtxn)?;
+        let documents_allow_list = self.documents_allow_list(rtxn)?;
+        let mut allow_lists = HashMap::new();
+        for (field, code) in self.fields_languages(rtxn)? {
+            let fid = fields_ids_map.id(&field);
+            if let (Some(fid), Some((script, language))) = (fid, script_language_from_code(&code)) {
+                let mut allow_list = documents_allow_list.clone();
+                allow_list.insert(script, vec![language]);
+                allow_lists.insert(fid, allow_list);
+            }
+        }
+        Ok(allow_lists)
+    }
+
+    /// Returns the allow list given to the tokenizer for the queries, which is the union
+    /// of the detected languages and of the language hints of all the fields.
+    pub(crate) fn query_allow_list(&self, rtxn: &RoTxn) -> Result<HashMap<Script, Vec<Language>>> {
+        let mut allow_list = self.documents_allow_list(rtxn)?;
+        for code in self.fields_languages(rtxn)?.values() {
+            if let Some((script, language)) = script_language_from_code(code) {
+                let languages = allow_list.entry(script).or_default();
+                if !languages.contains(&language) {
+                    languages.push(language);
+                }
+            }
+        }
+        Ok(allow_list)
+    }
+
+    /* words fst */
+
+    /// Writes the FST which is the words dictionary of the engine.
+    pub(crate) fn put_words_fst<A: AsRef<[u8]>>(
+        &self,
+        wtxn: &mut RwTxn,
+        fst: &fst::Set<A>,
+    ) -> heed::Result<()> {
+        self.main.put::<_, Str, ByteSlice>(wtxn, main_key::WORDS_FST_KEY, fst.as_fst().as_bytes())
+    }
+
+    /// Returns the FST which is the words dictionary of the engine.
+    pub fn words_fst<'t>(&self, rtxn: &'t RoTxn) -> Result<fst::Set<Cow<'t, [u8]>>> {
+        match self.main.get::<_, Str, ByteSlice>(rtxn, main_key::WORDS_FST_KEY)? {
+            Some(bytes) => Ok(fst::Set::new(bytes)?.map_data(Cow::Borrowed)?),
+            None => Ok(fst::Set::default().map_data(Cow::Owned)?),
+        }
+    }
+
+    /* stop words */
+
+    pub(crate) fn put_stop_words<A: AsRef<[u8]>>(
+        &self,
+        wtxn: &mut RwTxn,
+        fst: &fst::Set<A>,
+    ) -> heed::Result<()> {
+        self.main.put::<_, Str, ByteSlice>(wtxn, main_key::STOP_WORDS_KEY, fst.as_fst().as_bytes())
+    }
+
+    pub(crate) fn delete_stop_words(&self, wtxn: &mut RwTxn) -> heed::Result<bool> {
+        self.main.delete::<_, Str>(wtxn, main_key::STOP_WORDS_KEY)
+    }
+
+    pub fn stop_words<'t>(&self, rtxn: &'t RoTxn) -> Result<Option<fst::Set<&'t [u8]>>> {
+        match self.main.get::<_, Str, ByteSlice>(rtxn, main_key::STOP_WORDS_KEY)? {
+            Some(bytes) => Ok(Some(fst::Set::new(bytes)?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Returns the stop words removed from the words of the documents,
+    /// `None` when they are only ignored in the queries.
+    pub(crate) fn indexed_stop_words<'t>(
+        &self,
+        rtxn: &'t RoTxn,
+    ) -> Result<Option<fst::Set<&'t [u8]>>> {
+        match self.stop_words_mode(rtxn)? {
+            StopWordsMode::IndexAndSearch => self.stop_words(rtxn),
+            StopWordsMode::SearchOnly => Ok(None),
+        }
+    }
+
+    /// Returns when the stop words are ignored, both at indexing and search time by default.
+    pub fn stop_words_mode(&self, rtxn: &RoTxn) -> heed::Result<StopWordsMode> {
+        // The mode is stored as a u8, 1 means that the stop words are only ignored at
+        // search time. The absence of a value means that they are also not indexed.
+        match self.main.get::<_, Str, OwnedType<u8>>(rtxn, main_key::STOP_WORDS_MODE_KEY)? {
+            Some(1) => Ok(StopWordsMode::SearchOnly),
+            _ => Ok(StopWordsMode::IndexAndSearch),
+        }
+    }
+
+    pub(crate) fn put_stop_words_mode(
+        &self,
+        wtxn: &mut RwTxn,
+        mode: StopWordsMode,
+    ) -> heed::Result<()> {
+        let value = match mode {
+            StopWordsMode::IndexAndSearch => 0u8,
+            StopWordsMode::SearchOnly => 1u8,
+        };
+        self.main.put::<_, Str, OwnedType<u8>>(wtxn, main_key::STOP_WORDS_MODE_KEY, &value)
+    }
+
+    pub(crate) fn delete_stop_words_mode(&self, wtxn: &mut RwTxn) -> heed::Result<bool> {
+        self.main.delete::<_, Str>(wtxn, main_key::STOP_WORDS_MODE_KEY)
+    }
+
+    /* synonyms */
+
+    pub(crate) fn put_synonyms(
+        &self,
+        wtxn: &mut RwTxn,
+        synonyms: &HashMap<Vec<String>, Vec<Vec<String>>>,
+    ) -> heed::Result<()> {
+        self.main.put::<_, Str, SerdeBincode<_>>(wtxn, main_key::SYNONYMS_KEY, synonyms)
+    }
+
+    pub(crate) fn delete_synonyms(&self, wtxn: &mut RwTxn) -> heed::Result<bool> {
+        self.main.delete::<_, Str>(wtxn, main_key::SYNONYMS_KEY)
+    }
+
+    pub fn synonyms(&self, rtxn: &RoTxn) -> heed::Result<HashMap<Vec<String>, Vec<Vec<String>>>> {
+        Ok(self
+            .main
+            .get::<_, Str, SerdeBincode<_>>(rtxn, main_key::SYNONYMS_KEY)?
+            .unwrap_or_default())
+    }
+
+    pub fn words_synonyms<S: AsRef<str>>(
+        &self,
+        rtxn: &RoTxn,
+        words: &[S],
+    ) -> heed::Result<Option<Vec<Vec<String>>>> {
+        let words: Vec<_> = words.iter().map(|s| s.as_ref().to_owned()).collect();
+        Ok(self.synonyms(rtxn)?.remove(&words))
+    }
+
+    /* words prefixes fst */
+
+    /// Writes the FST which is the words prefixes dictionnary of the engine.
+    pub(crate) fn put_words_prefixes_fst<A: AsRef<[u8]>>(
+        &self,
+        wtxn: &mut RwTxn,
+        fst: &fst::Set<A>,
+    ) -> heed::Result<()> {
+        self.main.put::<_, Str, ByteSlice>(
+            wtxn,
+            main_key::WORDS_PREFIXES_FST_KEY,
+            fst.as_fst().as_bytes(),
+        )
+    }
+
+    /// Returns the FST which is the words prefixes dictionnary of the engine.
+    pub fn words_prefixes_fst<'t>(&self, rtxn: &'t RoTxn) -> Result<fst::Set<Cow<'t, [u8]>>> {
+        match self.main.get::<_, Str, ByteSlice>(rtxn, main_key::WORDS_PREFIXES_FST_KEY)? {
+            Some(bytes) => Ok(fst::Set::new(bytes)?.map_data(Cow::Borrowed)?),
+            None => Ok(fst::Set::default().map_data(Cow::Owned)?),
+        }
+    }
+
+    /* word documents count */
+
+    /// Returns the number of documents ids associated with the given word,
+    /// it is much faster than deserializing the bitmap and getting the length of it.
+    pub fn word_documents_count(&self, rtxn: &RoTxn, word: &str) -> heed::Result<Option<u64>> {
+        self.word_docids.remap_data_type::<RoaringBitmapLenCodec>().get(rtxn, word)
+    }
+
+    /* words statistics */
+
+    /// Returns the number of distinct words in the words dictionary of the engine.
+    ///
+    /// The words dictionary is not updated when documents are soft-deleted, the returned
+    /// number is therefore an upper bound of the vocabulary of the non-deleted documents.
+    pub fn word_count(&self, rtxn: &RoTxn) -> Result<u64> {
+        Ok(self.words_fst(rtxn)?.len() as u64)
+    }
+
+    /// Returns the number of non-deleted documents containing the given word,
+    /// in any searchable attribute, exact ones included.
+    pub fn word_document_frequency(&self, rtxn: &RoTxn, word: &str) -> Result<u64> {
+        let mut docids = self.word_docids.get(rtxn, word)?.unwrap_or_default();
+        if let Some(exact_docids) = self.exact_word_docids.get(rtxn, word)? {
+            docids |= exact_docids;
+        }
+        docids -= self.soft_deleted_documents_ids(rtxn)?;
+        Ok(docids.len())
+    }
+
+    /// Returns the `n` words of the words dictionary that are contained in the highest
+    /// number of documents, along with this number, sorted by decreasing number of
+    /// documents and then alphabetically.
+    ///
+    /// The numbers are read without deserializing the bitmaps and are upper bounds:
+    /// soft-deleted documents are counted and a document containing a word in both
+    /// an exact and a non-exact attribute is counted twice.
+    /// Use [`Self::word_document_frequency`] to get the exact number for a given word.
+    pub fn top_words(&self, rtxn: &RoTxn, n: usize) -> Result<Vec<(String, u64)>> {
+        if n == 0 {
+            return Ok(Vec::new());
+        }
+
+        // A min-heap of the best `n` words seen so far, the worst one being at the top.
+        let mut heap = BinaryHeap::with_capacity(n + 1);
+        let words_fst = self.words_fst(rtxn)?;
+        let mut stream = words_fst.stream();
+        while let Some(bytes) = stream.next() {
+            let word = std::str::from_utf8(bytes)?;
+            let count = self.word_documents_count_upper_bound(rtxn, word)?;
+            heap.push(Reverse((count, Reverse(word.to_string()))));
+            if heap.len() > n {
+                heap.pop();
+            }
+        }
+
+        Ok(heap
+            .into_sorted_vec()
+            .into_iter()
+            .map(|Reverse((count, Reverse(word)))| (word, count))
+            .collect())
+    }
+
+    /// Returns up to `limit` words contained in the highest number of documents that also
+    /// contain the given word, along with this number, sorted by decreasing number of
+    /// documents and then alphabetically. The given word itself is not returned.
+    ///
+    /// The words are read from the `docid_word_positions` database for each document
+    /// containing the given word, the cost is thus proportional to the number of words of
+    /// these documents rather than to the size of the words dictionary. It can still be high
+    /// for a frequent word, whose documents are almost all read.
+    pub fn co_occurring_words(
+        &self,
+        rtxn: &RoTxn,
+        word: &str,
+        limit: usize,
+    ) -> Result<Vec<(String, u64)>> {
+        if limit == 0 {
+            return Ok(Vec::new());
+        }
+
+        let mut docids = self.word_docids.get(rtxn, word)?.unwrap_or_default();
+        if let Some(exact_docids) = self.exact_word_docids.get(rtxn, word)? {
+            docids |= exact_docids;
+        }
+        docids -= self.soft_deleted_documents_ids(rtxn)?;
+
+        // The positions of a word in all the fields of a document are stored under the same
+        // key, every word is thus counted once per document.
+        let docid_words = self.docid_word_positions.remap_data_type::<DecodeIgnore>();
+        let mut counts: HashMap<&str, u64> = HashMap::new();
+        for docid in docids {
+            for result in docid_words.prefix_iter(rtxn, &(docid, ""))? {
+                let ((_, other_word), ()) = result?;
+                if other_word != word {
+                    *counts.entry(other_word).or_default() += 1;
+                }
+            }
+        }
+
+        let mut words: Vec<_> = counts.into_iter().collect();
+        words.sort_unstable_by_key(|&(word, count)| (Reverse(count), word));
+        words.truncate(limit);
+
+        Ok(words.into_iter().map(|(word, count)| (word.to_string(), count)).collect())
+    }
+
+    /// Returns up to `limit` words of the words dictionary starting with the given prefix,
+    /// along with the number of documents containing them, sorted by decreasing number
+    /// of documents and then alphabetically.
+    ///
+    /// As for [`Self::top_words`], the numbers of documents are upper bounds.
+    pub fn words_with_prefix(
+        &self,
+        rtxn: &RoTxn,
+        prefix: &str,
+        limit: usize,
+    ) -> Result<Vec<(String, u64)>> {
+        self.words_with_prefix_and_typos(rtxn, prefix, 0, limit)
+    }
+
+    /// Same as [`Self::words_with_prefix`] but also returns the words starting with a prefix
+    /// at one typo of the given one, the first letter being excluded from the typo tolerance.
+    ///
+    /// The words without typo are returned first, the words with one typo are then sorted
+    /// by decreasing number of documents and then alphabetically.
+    pub fn words_with_fuzzy_prefix(
+        &self,
+        rtxn: &RoTxn,
+        prefix: &str,
+        limit: usize,
+    ) -> Result<Vec<(String, u64)>> {
+        self.words_with_prefix_and_typos(rtxn, prefix, 1, limit)
+    }
+
+    fn words_with_prefix_and_typos(
+        &self,
+        rtxn: &RoTxn,
+        prefix: &str,
+        max_typo: u8,
+        limit: usize,
+    ) -> Result<Vec<(String, u64)>> {
+        if limit == 0 {
+            return Ok(Vec::new());
+        }
+
+        // An empty prefix can't contain any typo.
+        let max_typo = if prefix.is_empty() { 0 } else { max_typo };
+        let words_fst = self.words_fst(rtxn)?;
+        let mut cache = WordDerivationsCache::new();
+        let derivations = word_derivations(prefix, true, max_typo, &words_fst, &mut cache)?;
+
+        let mut words = Vec::with_capacity(derivations.len());
+        for (word, typos) in derivations {
+            let count = self.word_documents_count_upper_bound(rtxn, word)?;
+            words.push((*typos, count, word));
+        }
+
+        words.sort_unstable_by(|(ta, ca, wa), (tb, cb, wb)| {
+            ta.cmp(tb).then_with(|| cb.cmp(ca)).then_with(|| wa.cmp(wb))
+        });
+
+        Ok(words.into_iter().take(limit).map(|(_, count, word)| (word.clone(), count)).collect())
+    }
+
+    /// Returns the number of documents containing the given word in both the
+    /// exact and non-exact word docids databases, without deserializing the bitmaps.
+    pub(crate) fn word_documents_count_upper_bound(
+        &self,
+        rtxn: &RoTxn,
+        word: &str,
+    ) -> heed::Result<u64> {
+        let word_docids = self.word_docids.remap_data_type::<RoaringBitmapLenCodec>();
+        let exact_word_docids = self.exact_word_docids.remap_data_type::<RoaringBitmapLenCodec>();
+        Ok(word_docids.get(rtxn, word)?.unwrap_or_default()
+            + exact_word_docids.get(rtxn, word)?.unwrap_or_default())
+    }
+
+    /* documents */
+
+    /// Returns a [`Vec`] of the requested documents. Returns an error if a document is missing.
+    pub fn documents<'t>(
+        &self,
+        rtxn: &'t RoTxn,
+        ids: impl IntoIterator<Item = DocumentId>,
+    ) -> Result<Vec<(DocumentId, obkv::KvReaderU16<'t>)>> {
+        let soft_deleted_documents = self.soft_deleted_documents_ids(rtxn)?;
+        let mut documents = Vec::new();
+
+        for id in ids {
+            if soft_deleted_documents.contains(id) {
+                return Err(UserError::AccessingSoftDeletedDocument { document_id: id })?;
+            }
+            let kv = self
+                .documents
+                .get(rtxn, &BEU32::new(id))?
+                .ok_or(UserError::UnknownInternalDocumentId { document_id: id })?;
+            documents.push((id, kv));
+        }
+
+        Ok(documents)
+    }
+
+    /// Writes the displayed fields of the document as a JSON object at the end of `buf`,
+    /// in the same order as [`crate::obkv_to_json`] but without deserializing the values.
+    ///
+    /// The documents are stored as they were sent, before being flattened, the nested objects
+    /// are thus written back as is. Returns an error if the document is missing.
+    pub fn document_json(&self, rtxn: &RoTxn, id: DocumentId, buf: &mut String) -> Result<()> {
+        let (_, obkv) = self
+            .documents(rtxn, Some(id))?
+            .pop()
+            .ok_or(UserError::UnknownInternalDocumentId { document_id: id })?;
+        let fields_ids_map = self.fields_ids_map(rtxn)?;
+        let displayed_fields = match self.displayed_fields_ids(rtxn)? {
+            Some(fields) => fields,
+            None => obkv.iter().map(|(id, _)| id).collect(),
+        };
+
+        buf.push('{');
+        let mut first = true;
+        for (field_id, value) in
+            displayed_fields.into_iter().filter_map(|id| obkv.get(id).map(|value| (id, value)))
+        {
+            let name = fields_ids_map
+                .name(field_id)
+                .ok_or(FieldIdMapMissingEntry::FieldId { field_id, process: "document_json" })?;
+            if !first {
+                buf.push(',');
+            }
+            first = false;
+            push_json_string(buf, name);
+            buf.push(':');
+            // the values are stored serialized in JSON.
+            buf.push_str(std::str::from_utf8(value)?);
+        }
+        buf.push('}');
+
+        Ok(())
+    }
+
+    /// Returns the requested documents as their displayed fields flattened like during
+    /// indexing, e.g. to export them as the rows of a table, see [`crate::obkv_to_flattened`].
+    ///
+    /// The names of the fields are the ones of the fields ids map, the nested objects are
+    /// not rebuilt. Returns an error if a document is missing.
+    pub fn flattened_documents(
+        &self,
+        rtxn: &RoTxn,
+        ids: impl IntoIterator<Item = DocumentId>,
+    ) -> Result<Vec<(DocumentId, Vec<(String, serde_json::Value)>)>> {
+        let fields_ids_map = self.fields_ids_map(rtxn)?;
+        let displayed_fields = self.displayed_fields_ids(rtxn)?;
+
+        self.documents(rtxn, ids)?
+            .into_iter()
+            .map(|(id, obkv)| {
+                let fields = match &displayed_fields {
+                    Some(fields) => obkv_to_flattened(fields, &fields_ids_map, obkv)?,
+                    None => {
+                        let fields: Vec<_> = obkv.iter().map(|(id, _)| id).collect();
+                        obkv_to_flattened(&fields, &fields_ids_map, obkv)?
+                    }
+                };
+                Ok((id, fields))
+            })
+            .collect()
+    }
+
+    /// Returns an iterator over all the documents in the index.
+    pub fn all_documents<'t>(
+        &self,
+        rtxn: &'t RoTxn,
+    ) -> Result<impl Iterator<Item = heed::Result<(DocumentId, obkv::KvReaderU16<'t>)>>> {
+        let soft_deleted_docids = self.soft_deleted_documents_ids(rtxn)?;
 
         Ok(self
             .documents
@@ -1020,6 +1964,23 @@ impl Index {
             }))
     }
 
+    /// Returns an iterator over the stored content of the soft-deleted documents.
+    ///
+    /// The content of the soft-deleted documents is only kept until they are purged, by
+    /// a hard deletion or once enough of them have been soft-deleted, the iterator must
+    /// therefore be read before any further deletion.
+    pub fn soft_deleted_documents<'t>(
+        &self,
+        rtxn: &'t RoTxn,
+    ) -> Result<impl Iterator<Item = heed::Result<(DocumentId, obkv::KvReaderU16<'t>)>>> {
+        let soft_deleted_docids = self.soft_deleted_documents_ids(rtxn)?;
+        let documents = self.documents;
+
+        Ok(soft_deleted_docids.into_iter().filter_map(move |id| {
+            documents.get(rtxn, &BEU32::new(id)).transpose().map(|obkv| obkv.map(|obkv| (id, obkv)))
+        }))
+    }
+
     pub fn facets_distribution<'a>(&'a self, rtxn: &'a RoTxn) -> FacetDistribution<'a> {
         FacetDistribution::new(rtxn, self)
     }
@@ -1058,6 +2019,41 @@ impl Index {
         self.main.put::<_, Str, SerdeJson<OffsetDateTime>>(wtxn, main_key::UPDATED_AT_KEY, time)
     }
 
+    /* indexing sequence */
+
+    /// Returns the last indexing sequence number that was attributed to a batch of documents,
+    /// `0` if no batch was ever indexed with [`IndexerConfig::track_indexing_sequence`] enabled.
+    ///
+    /// [`IndexerConfig::track_indexing_sequence`]: crate::update::IndexerConfig::track_indexing_sequence
+    pub fn indexing_sequence(&self, rtxn: &RoTxn) -> heed::Result<u64> {
+        Ok(self
+            .main
+            .get::<_, Str, OwnedType<BEU64>>(rtxn, main_key::INDEXING_SEQUENCE_KEY)?
+            .map_or(0, |sequence| sequence.get()))
+    }
+
+    /// Increments the indexing sequence number and returns the new value.
+    pub(crate) fn next_indexing_sequence(&self, wtxn: &mut RwTxn) -> heed::Result<u64> {
+        let sequence = self.indexing_sequence(wtxn)? + 1;
+        self.main.put::<_, Str, OwnedType<BEU64>>(
+            wtxn,
+            main_key::INDEXING_SEQUENCE_KEY,
+            &BEU64::new(sequence),
+        )?;
+        Ok(sequence)
+    }
+
+    /// Returns the indexing sequence number at which the given document was last inserted,
+    /// updated or reindexed. The sequence numbers are monotonically increasing.
+    ///
+    /// Returns `None` if the document doesn't exist or if it was indexed while
+    /// [`IndexerConfig::track_indexing_sequence`] was disabled.
+    ///
+    /// [`IndexerConfig::track_indexing_sequence`]: crate::update::IndexerConfig::track_indexing_sequence
+    pub fn document_indexed_at(&self, rtxn: &RoTxn, id: DocumentId) -> heed::Result<Option<u64>> {
+        Ok(self.documents_indexed_at.get(rtxn, &BEU32::new(id))?.map(|sequence| sequence.get()))
+    }
+
     pub fn authorize_typos(&self, txn: &RoTxn) -> heed::Result<bool> {
         // It is not possible to put a bool in heed with OwnedType, so we put a u8 instead. We
         // identify 0 as being false, and anything else as true. The absence of a value is true,
@@ -1142,169 +2138,604 @@ impl Index {
             .unwrap_or_default())
     }
 
-    /// Returns the list of exact attributes field ids.
-    pub fn exact_attributes_ids(&self, txn: &RoTxn) -> Result<HashSet<FieldId>> {
-        let attrs = self.exact_attributes(txn)?;
-        let fid_map = self.fields_ids_map(txn)?;
-        Ok(attrs.iter().filter_map(|attr| fid_map.id(attr)).collect())
+    /// Returns the list of exact attributes field ids.
+    pub fn exact_attributes_ids(&self, txn: &RoTxn) -> Result<HashSet<FieldId>> {
+        let attrs = self.exact_attributes(txn)?;
+        let fid_map = self.fields_ids_map(txn)?;
+        Ok(attrs.iter().filter_map(|attr| fid_map.id(attr)).collect())
+    }
+
+    /// Writes the exact attributes to the database.
+    pub(crate) fn put_exact_attributes(&self, txn: &mut RwTxn, attrs: &[&str]) -> Result<()> {
+        self.main.put::<_, Str, SerdeBincode<&[&str]>>(txn, main_key::EXACT_ATTRIBUTES, &attrs)?;
+        Ok(())
+    }
+
+    /// Clears the exact attributes from the store.
+    pub(crate) fn delete_exact_attributes(&self, txn: &mut RwTxn) -> heed::Result<bool> {
+        self.main.delete::<_, Str>(txn, main_key::EXACT_ATTRIBUTES)
+    }
+
+    /// Returns the case-sensitive attributes: attributes whose words are also indexed
+    /// with their case, see [`crate::Search::case_sensitive`].
+    pub fn case_sensitive_attributes<'t>(&self, txn: &'t RoTxn) -> Result<Vec<&'t str>> {
+        Ok(self
+            .main
+            .get::<_, Str, SerdeBincode<Vec<&str>>>(txn, main_key::CASE_SENSITIVE_ATTRIBUTES)?
+            .unwrap_or_default())
+    }
+
+    /// Returns the list of case-sensitive attributes field ids.
+    pub fn case_sensitive_attributes_ids(&self, txn: &RoTxn) -> Result<HashSet<FieldId>> {
+        let attrs = self.case_sensitive_attributes(txn)?;
+        let fid_map = self.fields_ids_map(txn)?;
+        Ok(attrs.iter().filter_map(|attr| fid_map.id(attr)).collect())
+    }
+
+    /// Writes the case-sensitive attributes to the database.
+    pub(crate) fn put_case_sensitive_attributes(
+        &self,
+        txn: &mut RwTxn,
+        attrs: &[&str],
+    ) -> Result<()> {
+        self.main.put::<_, Str, SerdeBincode<&[&str]>>(
+            txn,
+            main_key::CASE_SENSITIVE_ATTRIBUTES,
+            &attrs,
+        )?;
+        Ok(())
+    }
+
+    /// Clears the case-sensitive attributes from the store.
+    pub(crate) fn delete_case_sensitive_attributes(&self, txn: &mut RwTxn) -> heed::Result<bool> {
+        self.main.delete::<_, Str>(txn, main_key::CASE_SENSITIVE_ATTRIBUTES)
+    }
+
+    pub fn max_values_per_facet(&self, txn: &RoTxn) -> heed::Result<Option<usize>> {
+        self.main.get::<_, Str, OwnedType<usize>>(txn, main_key::MAX_VALUES_PER_FACET)
+    }
+
+    pub(crate) fn put_max_values_per_facet(&self, txn: &mut RwTxn, val: usize) -> heed::Result<()> {
+        self.main.put::<_, Str, OwnedType<usize>>(txn, main_key::MAX_VALUES_PER_FACET, &val)
+    }
+
+    pub(crate) fn delete_max_values_per_facet(&self, txn: &mut RwTxn) -> heed::Result<bool> {
+        self.main.delete::<_, Str>(txn, main_key::MAX_VALUES_PER_FACET)
+    }
+
+    pub fn pagination_max_total_hits(&self, txn: &RoTxn) -> heed::Result<Option<usize>> {
+        self.main.get::<_, Str, OwnedType<usize>>(txn, main_key::PAGINATION_MAX_TOTAL_HITS)
+    }
+
+    pub(crate) fn put_pagination_max_total_hits(
+        &self,
+        txn: &mut RwTxn,
+        val: usize,
+    ) -> heed::Result<()> {
+        self.main.put::<_, Str, OwnedType<usize>>(txn, main_key::PAGINATION_MAX_TOTAL_HITS, &val)
+    }
+
+    pub(crate) fn delete_pagination_max_total_hits(&self, txn: &mut RwTxn) -> heed::Result<bool> {
+        self.main.delete::<_, Str>(txn, main_key::PAGINATION_MAX_TOTAL_HITS)
+    }
+
+    /// Returns the maximum length, in bytes, of the indexed and searched words.
+    /// It can't be greater than [`MAX_WORD_LENGTH`].
+    pub fn max_word_length(&self, txn: &RoTxn) -> heed::Result<usize> {
+        Ok(self
+            .main
+            .get::<_, Str, OwnedType<usize>>(txn, main_key::MAX_WORD_LENGTH)?
+            .map_or(MAX_WORD_LENGTH, |max| max.min(MAX_WORD_LENGTH)))
+    }
+
+    pub(crate) fn put_max_word_length(&self, txn: &mut RwTxn, val: usize) -> heed::Result<()> {
+        self.main.put::<_, Str, OwnedType<usize>>(txn, main_key::MAX_WORD_LENGTH, &val)
+    }
+
+    pub(crate) fn delete_max_word_length(&self, txn: &mut RwTxn) -> heed::Result<bool> {
+        self.main.delete::<_, Str>(txn, main_key::MAX_WORD_LENGTH)
+    }
+
+    /// Returns how the words longer than the [`max_word_length`](Self::max_word_length) are handled.
+    pub fn long_words_policy(&self, txn: &RoTxn) -> heed::Result<LongWordsPolicy> {
+        // The policy is stored as a u8, 1 means that the words are truncated.
+        // The absence of a value means that the long words are dropped.
+        match self.main.get::<_, Str, OwnedType<u8>>(txn, main_key::LONG_WORDS_POLICY)? {
+            Some(1) => Ok(LongWordsPolicy::Truncate),
+            _ => Ok(LongWordsPolicy::Drop),
+        }
+    }
+
+    pub(crate) fn put_long_words_policy(
+        &self,
+        txn: &mut RwTxn,
+        policy: LongWordsPolicy,
+    ) -> heed::Result<()> {
+        let value = match policy {
+            LongWordsPolicy::Drop => 0u8,
+            LongWordsPolicy::Truncate => 1u8,
+        };
+        self.main.put::<_, Str, OwnedType<u8>>(txn, main_key::LONG_WORDS_POLICY, &value)
+    }
+
+    pub(crate) fn delete_long_words_policy(&self, txn: &mut RwTxn) -> heed::Result<bool> {
+        self.main.delete::<_, Str>(txn, main_key::LONG_WORDS_POLICY)
+    }
+
+    /// Returns the minimum length, in characters, of the indexed and searched words,
+    /// the shorter ones are ignored. Every word is indexed by default.
+    pub fn min_indexed_word_length(&self, txn: &RoTxn) -> heed::Result<usize> {
+        Ok(self
+            .main
+            .get::<_, Str, OwnedType<usize>>(txn, main_key::MIN_INDEXED_WORD_LENGTH)?
+            .unwrap_or(0))
+    }
+
+    pub(crate) fn put_min_indexed_word_length(
+        &self,
+        txn: &mut RwTxn,
+        val: usize,
+    ) -> heed::Result<()> {
+        self.main.put::<_, Str, OwnedType<usize>>(txn, main_key::MIN_INDEXED_WORD_LENGTH, &val)
+    }
+
+    pub(crate) fn delete_min_indexed_word_length(&self, txn: &mut RwTxn) -> heed::Result<bool> {
+        self.main.delete::<_, Str>(txn, main_key::MIN_INDEXED_WORD_LENGTH)
+    }
+
+    /// Returns the maximum number of positions stored for each word of a document,
+    /// `None` if all the positions are stored. It is never lower than one.
+    pub fn max_positions_per_word(&self, txn: &RoTxn) -> heed::Result<Option<usize>> {
+        Ok(self
+            .main
+            .get::<_, Str, OwnedType<usize>>(txn, main_key::MAX_POSITIONS_PER_WORD)?
+            .map(|max| max.max(1)))
+    }
+
+    pub(crate) fn put_max_positions_per_word(
+        &self,
+        txn: &mut RwTxn,
+        val: usize,
+    ) -> heed::Result<()> {
+        self.main.put::<_, Str, OwnedType<usize>>(txn, main_key::MAX_POSITIONS_PER_WORD, &val)
+    }
+
+    pub(crate) fn delete_max_positions_per_word(&self, txn: &mut RwTxn) -> heed::Result<bool> {
+        self.main.delete::<_, Str>(txn, main_key::MAX_POSITIONS_PER_WORD)
+    }
+
+    /// Returns the maximum number of fields of the index, `None` if only the [`MAX_FIELDS`]
+    /// limit applies. It can't be greater than [`MAX_FIELDS`].
+    pub fn max_fields(&self, txn: &RoTxn) -> heed::Result<Option<usize>> {
+        Ok(self
+            .main
+            .get::<_, Str, OwnedType<usize>>(txn, main_key::MAX_FIELDS)?
+            .map(|max| max.min(MAX_FIELDS)))
+    }
+
+    pub(crate) fn put_max_fields(&self, txn: &mut RwTxn, val: usize) -> heed::Result<()> {
+        self.main.put::<_, Str, OwnedType<usize>>(txn, main_key::MAX_FIELDS, &val)
+    }
+
+    pub(crate) fn delete_max_fields(&self, txn: &mut RwTxn) -> heed::Result<bool> {
+        self.main.delete::<_, Str>(txn, main_key::MAX_FIELDS)
+    }
+
+    /// Returns how the documents bringing more new fields than the
+    /// [`max_fields`](Self::max_fields) allows are handled.
+    pub fn max_fields_policy(&self, txn: &RoTxn) -> heed::Result<MaxFieldsPolicy> {
+        // The policy is stored as a u8, 1 means that the documents are skipped and 2 that
+        // they are truncated. The absence of a value means that the documents are rejected.
+        match self.main.get::<_, Str, OwnedType<u8>>(txn, main_key::MAX_FIELDS_POLICY)? {
+            Some(1) => Ok(MaxFieldsPolicy::Skip),
+            Some(2) => Ok(MaxFieldsPolicy::Truncate),
+            _ => Ok(MaxFieldsPolicy::Reject),
+        }
+    }
+
+    pub(crate) fn put_max_fields_policy(
+        &self,
+        txn: &mut RwTxn,
+        policy: MaxFieldsPolicy,
+    ) -> heed::Result<()> {
+        let value = match policy {
+            MaxFieldsPolicy::Reject => 0u8,
+            MaxFieldsPolicy::Skip => 1u8,
+            MaxFieldsPolicy::Truncate => 2u8,
+        };
+        self.main.put::<_, Str, OwnedType<u8>>(txn, main_key::MAX_FIELDS_POLICY, &value)
+    }
+
+    pub(crate) fn delete_max_fields_policy(&self, txn: &mut RwTxn) -> heed::Result<bool> {
+        self.main.delete::<_, Str>(txn, main_key::MAX_FIELDS_POLICY)
+    }
+
+    /// Returns whether the word pair proximity databases are built, `true` by default.
+    ///
+    /// When they aren't, the proximity criterion is ignored and the phrases match the
+    /// documents containing all their words, wherever they are.
+    pub fn proximity_databases(&self, txn: &RoTxn) -> heed::Result<bool> {
+        // The absence of a value is true, because by default, we build the proximity databases.
+        match self.main.get::<_, Str, OwnedType<u8>>(txn, main_key::PROXIMITY_DATABASES_KEY)? {
+            Some(0) => Ok(false),
+            _ => Ok(true),
+        }
+    }
+
+    pub(crate) fn put_proximity_databases(&self, txn: &mut RwTxn, flag: bool) -> heed::Result<()> {
+        self.main.put::<_, Str, OwnedType<u8>>(
+            txn,
+            main_key::PROXIMITY_DATABASES_KEY,
+            &(flag as u8),
+        )
+    }
+
+    pub(crate) fn delete_proximity_databases(&self, txn: &mut RwTxn) -> heed::Result<bool> {
+        self.main.delete::<_, Str>(txn, main_key::PROXIMITY_DATABASES_KEY)
+    }
+
+    /// Returns whether the word pair proximities are computed across the fields of the
+    /// documents, `false` by default.
+    ///
+    /// When they are, the fields of a document are considered one after the other in the
+    /// order of their ids, the last word of a field being next to the first word of the
+    /// following field.
+    pub fn cross_field_proximity(&self, txn: &RoTxn) -> heed::Result<bool> {
+        match self.main.get::<_, Str, OwnedType<u8>>(txn, main_key::CROSS_FIELD_PROXIMITY_KEY)? {
+            Some(1) => Ok(true),
+            _ => Ok(false),
+        }
+    }
+
+    pub(crate) fn put_cross_field_proximity(
+        &self,
+        txn: &mut RwTxn,
+        flag: bool,
+    ) -> heed::Result<()> {
+        self.main.put::<_, Str, OwnedType<u8>>(
+            txn,
+            main_key::CROSS_FIELD_PROXIMITY_KEY,
+            &(flag as u8),
+        )
+    }
+
+    pub(crate) fn delete_cross_field_proximity(&self, txn: &mut RwTxn) -> heed::Result<bool> {
+        self.main.delete::<_, Str>(txn, main_key::CROSS_FIELD_PROXIMITY_KEY)
+    }
+
+    /// Returns how the accents of the words are taken into account by the typo tolerance,
+    /// see [`TypoOnDiacritics`].
+    pub fn typo_on_diacritics(&self, txn: &RoTxn) -> heed::Result<TypoOnDiacritics> {
+        // The mode is stored as a u8, the absence of a value means that the accents are ignored.
+        match self.main.get::<_, Str, OwnedType<u8>>(txn, main_key::TYPO_ON_DIACRITICS_KEY)? {
+            Some(1) => Ok(TypoOnDiacritics::CountsAsTypo),
+            Some(2) => Ok(TypoOnDiacritics::NoMatch),
+            _ => Ok(TypoOnDiacritics::Free),
+        }
+    }
+
+    pub(crate) fn put_typo_on_diacritics(
+        &self,
+        txn: &mut RwTxn,
+        mode: TypoOnDiacritics,
+    ) -> heed::Result<()> {
+        let value = match mode {
+            TypoOnDiacritics::Free => 0u8,
+            TypoOnDiacritics::CountsAsTypo => 1u8,
+            TypoOnDiacritics::NoMatch => 2u8,
+        };
+        self.main.put::<_, Str, OwnedType<u8>>(txn, main_key::TYPO_ON_DIACRITICS_KEY, &value)
+    }
+
+    pub(crate) fn delete_typo_on_diacritics(&self, txn: &mut RwTxn) -> heed::Result<bool> {
+        self.main.delete::<_, Str>(txn, main_key::TYPO_ON_DIACRITICS_KEY)
+    }
+}
+
+fn open_existing_database<KC, DC>(env: &heed::Env, name: &'static str) -> Result<Database<KC, DC>>
+where
+    KC: 'static,
+    DC: 'static,
+{
+    match env.open_database(Some(name))? {
+        Some(database) => Ok(database),
+        None => Err(InternalError::DatabaseMissing { db_name: name }.into()),
+    }
+}
+
+/// Writes the string as a JSON string, escaping the quotes, backslashes and control characters.
+fn push_json_string(buf: &mut String, s: &str) {
+    use std::fmt::Write;
+
+    buf.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => buf.push_str("\\\""),
+            '\\' => buf.push_str("\\\\"),
+            '\n' => buf.push_str("\\n"),
+            '\r' => buf.push_str("\\r"),
+            '\t' => buf.push_str("\\t"),
+            c if (c as u32) < 0x20 => {
+                let _ = write!(buf, "\\u{:04x}", c as u32);
+            }
+            c => buf.push(c),
+        }
+    }
+    buf.push('"');
+}
+
+/// Forwards the listed reading methods of the [`IndexReadOnly`] to the inner [`Index`].
+macro_rules! forward_to_index {
+    ($(
+        $(#[$attr:meta])*
+        pub fn $name:ident $(<$($lt:lifetime),*>)? (&self $(, $arg:ident: $ty:ty)* $(,)?) -> $ret:ty;
+    )*) => {
+        $(
+            $(#[$attr])*
+            pub fn $name $(<$($lt),*>)? (&self $(, $arg: $ty)*) -> $ret {
+                self.index.$name($($arg),*)
+            }
+        )*
+    };
+}
+
+/// An index opened in read-only mode, e.g. from a read-only file system or a snapshot.
+///
+/// It exposes the search, the facet distribution and the getters of the [`Index`], but never
+/// gives access to the inner [`Index`] nor to a write transaction. This way, none of the update
+/// builders can be constructed on it.
+#[derive(Clone)]
+pub struct IndexReadOnly {
+    index: Index,
+}
+
+impl IndexReadOnly {
+    /// Opens an existing index in read-only mode, no database is created and an error
+    /// is returned if one of the databases of the index is missing.
+    ///
+    /// The indexes created by older versions don't contain the databases added since,
+    /// they must be opened once with [`Index::new`] to create them.
+    pub fn new<P: AsRef<Path>>(mut options: heed::EnvOpenOptions, path: P) -> Result<Self> {
+        options.max_dbs(NUMBER_OF_DATABASES);
+        unsafe { options.flag(Flags::MdbRdOnly) };
+
+        let env = options.open(path)?;
+        Index::open_existing(env).map(|index| IndexReadOnly { index })
     }
 
-    /// Writes the exact attributes to the database.
-    pub(crate) fn put_exact_attributes(&self, txn: &mut RwTxn, attrs: &[&str]) -> Result<()> {
-        self.main.put::<_, Str, SerdeBincode<&[&str]>>(txn, main_key::EXACT_ATTRIBUTES, &attrs)?;
-        Ok(())
+    /// Create a read transaction to be able to read the index.
+    pub fn read_txn(&self) -> heed::Result<RoTxn> {
+        self.index.read_txn()
     }
 
-    /// Clears the exact attributes from the store.
-    pub(crate) fn delete_exact_attributes(&self, txn: &mut RwTxn) -> heed::Result<bool> {
-        self.main.delete::<_, Str>(txn, main_key::EXACT_ATTRIBUTES)
+    /// Returns the canonicalized path where the heed `Env` of this `Index` lives.
+    pub fn path(&self) -> &Path {
+        self.index.path()
     }
 
-    pub fn max_values_per_facet(&self, txn: &RoTxn) -> heed::Result<Option<usize>> {
-        self.main.get::<_, Str, OwnedType<usize>>(txn, main_key::MAX_VALUES_PER_FACET)
+    pub fn copy_to_path<P: AsRef<Path>>(&self, path: P, option: CompactionOption) -> Result<File> {
+        self.index.copy_to_path(path, option)
     }
 
-    pub(crate) fn put_max_values_per_facet(&self, txn: &mut RwTxn, val: usize) -> heed::Result<()> {
-        self.main.put::<_, Str, OwnedType<usize>>(txn, main_key::MAX_VALUES_PER_FACET, &val)
+    /// Returns an `EnvClosingEvent` that can be used to wait for the closing event.
+    pub fn prepare_for_closing(self) -> heed::EnvClosingEvent {
+        self.index.prepare_for_closing()
     }
 
-    pub(crate) fn delete_max_values_per_facet(&self, txn: &mut RwTxn) -> heed::Result<bool> {
-        self.main.delete::<_, Str>(txn, main_key::MAX_VALUES_PER_FACET)
+    pub fn facets_distribution<'a>(&'a self, rtxn: &'a RoTxn) -> FacetDistribution<'a> {
+        self.index.facets_distribution(rtxn)
     }
 
-    pub fn pagination_max_total_hits(&self, txn: &RoTxn) -> heed::Result<Option<usize>> {
-        self.main.get::<_, Str, OwnedType<usize>>(txn, main_key::PAGINATION_MAX_TOTAL_HITS)
+    pub fn search<'a>(&'a self, rtxn: &'a RoTxn) -> Search<'a> {
+        self.index.search(rtxn)
     }
 
-    pub(crate) fn put_pagination_max_total_hits(
+    pub fn words_synonyms<S: AsRef<str>>(
         &self,
-        txn: &mut RwTxn,
-        val: usize,
-    ) -> heed::Result<()> {
-        self.main.put::<_, Str, OwnedType<usize>>(txn, main_key::PAGINATION_MAX_TOTAL_HITS, &val)
+        rtxn: &RoTxn,
+        words: &[S],
+    ) -> heed::Result<Option<Vec<Vec<String>>>> {
+        self.index.words_synonyms(rtxn, words)
     }
 
-    pub(crate) fn delete_pagination_max_total_hits(&self, txn: &mut RwTxn) -> heed::Result<bool> {
-        self.main.delete::<_, Str>(txn, main_key::PAGINATION_MAX_TOTAL_HITS)
+    forward_to_index! {
+        pub fn used_size(&self) -> Result<u64>;
+        pub fn on_disk_size(&self) -> Result<u64>;
+        pub fn map_size(&self) -> Result<usize>;
+        pub fn documents_ids(&self, rtxn: &RoTxn) -> heed::Result<RoaringBitmap>;
+        pub fn number_of_documents(&self, rtxn: &RoTxn) -> Result<u64>;
+        pub fn available_ids_count(&self, rtxn: &RoTxn) -> Result<u64>;
+        pub fn primary_key<'t>(&self, rtxn: &'t RoTxn) -> heed::Result<Option<&'t str>>;
+        pub fn external_documents_ids<'t>(
+            &self,
+            rtxn: &'t RoTxn,
+        ) -> Result<ExternalDocumentsIds<'t>>;
+        pub fn external_ids<'t>(
+            &self,
+            rtxn: &'t RoTxn,
+        ) -> Result<impl Iterator<Item = String> + 't>;
+        pub fn fields_ids_map(&self, rtxn: &RoTxn) -> heed::Result<FieldsIdsMap>;
+        pub fn fields_count(&self, rtxn: &RoTxn) -> heed::Result<usize>;
+        pub fn geo_rtree(&self, rtxn: &RoTxn) -> Result<Option<RTree<GeoPoint>>>;
+        pub fn geo_shapes_rtree(&self, rtxn: &RoTxn) -> Result<Option<RTree<GeoShape>>>;
+        pub fn geo_faceted_documents_ids(&self, rtxn: &RoTxn) -> heed::Result<RoaringBitmap>;
+        pub fn field_distribution(&self, rtxn: &RoTxn) -> heed::Result<FieldDistribution>;
+        pub fn displayed_fields<'t>(&self, rtxn: &'t RoTxn) -> heed::Result<Option<Vec<&'t str>>>;
+        pub fn displayed_fields_ids(&self, rtxn: &RoTxn) -> Result<Option<Vec<FieldId>>>;
+        pub fn searchable_fields<'t>(&self, rtxn: &'t RoTxn) -> heed::Result<Option<Vec<&'t str>>>;
+        pub fn searchable_fields_ids(&self, rtxn: &RoTxn) -> Result<Option<Vec<FieldId>>>;
+        pub fn user_defined_searchable_fields<'t>(
+            &self,
+            rtxn: &'t RoTxn,
+        ) -> heed::Result<Option<Vec<&'t str>>>;
+        pub fn filterable_fields(&self, rtxn: &RoTxn) -> heed::Result<HashSet<String>>;
+        pub fn filterable_fields_ids(&self, rtxn: &RoTxn) -> Result<HashSet<FieldId>>;
+        pub fn sortable_fields(&self, rtxn: &RoTxn) -> heed::Result<HashSet<String>>;
+        pub fn sortable_fields_ids(&self, rtxn: &RoTxn) -> Result<HashSet<FieldId>>;
+        pub fn faceted_fields(&self, rtxn: &RoTxn) -> heed::Result<HashSet<String>>;
+        pub fn faceted_fields_ids(&self, rtxn: &RoTxn) -> Result<HashSet<FieldId>>;
+        pub fn user_defined_faceted_fields(&self, rtxn: &RoTxn) -> Result<HashSet<String>>;
+        pub fn user_defined_faceted_fields_ids(&self, rtxn: &RoTxn) -> Result<HashSet<FieldId>>;
+        pub fn faceted_documents_ids(
+            &self,
+            rtxn: &RoTxn,
+            field_id: FieldId,
+            facet_type: FacetType,
+        ) -> heed::Result<RoaringBitmap>;
+        pub fn exists_faceted_documents_ids(
+            &self,
+            rtxn: &RoTxn,
+            field_id: FieldId,
+        ) -> heed::Result<RoaringBitmap>;
+        pub fn facet_bool_docids(
+            &self,
+            rtxn: &RoTxn,
+            field_id: FieldId,
+            value: bool,
+        ) -> heed::Result<RoaringBitmap>;
+        pub fn boolean_facets_indexed(&self, rtxn: &RoTxn) -> heed::Result<bool>;
+        pub fn canonical_numbers(&self, rtxn: &RoTxn) -> heed::Result<bool>;
+        pub fn facet_string_originals_indexed(&self, rtxn: &RoTxn) -> heed::Result<bool>;
+        pub fn bucketed_positions(&self, rtxn: &RoTxn) -> heed::Result<bool>;
+        pub fn null_values_uncounted(&self, rtxn: &RoTxn) -> heed::Result<bool>;
+        pub fn tokenizer_fingerprint(&self, rtxn: &RoTxn) -> heed::Result<Option<u64>>;
+        pub fn facet_numeric_stats(
+            &self,
+            rtxn: &RoTxn,
+            field_id: FieldId,
+            candidates: Option<&RoaringBitmap>,
+        ) -> Result<Option<NumericFacetStats>>;
+        pub fn facet_numeric_range(
+            &self,
+            rtxn: &RoTxn,
+            field_id: FieldId,
+            candidates: Option<&RoaringBitmap>,
+        ) -> Result<Option<(f64, f64)>>;
+        pub fn facet_counts_in(
+            &self,
+            rtxn: &RoTxn,
+            field_id: FieldId,
+            candidates: &RoaringBitmap,
+        ) -> Result<BTreeMap<String, u64>>;
+        pub fn estimate_selectivity(
+            &self,
+            rtxn: &RoTxn,
+            query: &str,
+            filter: Option<&Filter>,
+        ) -> Result<f64>;
+        pub fn check_integrity(&self, rtxn: &RoTxn) -> Result<Vec<IntegrityIssue>>;
+        pub fn distinct_field<'a>(&self, rtxn: &'a RoTxn) -> heed::Result<Option<&'a str>>;
+        pub fn criteria(&self, rtxn: &RoTxn) -> heed::Result<Vec<Criterion>>;
+        pub fn field_boosts(&self, rtxn: &RoTxn) -> heed::Result<BTreeMap<String, f32>>;
+        pub fn mixed_facet_types(
+            &self,
+            rtxn: &RoTxn,
+        ) -> heed::Result<BTreeMap<String, MixedFacetTypes>>;
+        pub fn fields_languages(&self, rtxn: &RoTxn) -> heed::Result<BTreeMap<String, String>>;
+        pub fn stemming(&self, rtxn: &RoTxn) -> heed::Result<BTreeMap<String, bool>>;
+        pub fn detected_languages_allow_list(&self, rtxn: &RoTxn) -> heed::Result<Vec<String>>;
+        pub fn normalization_rules(&self, rtxn: &RoTxn) -> heed::Result<BTreeMap<String, String>>;
+        pub fn words_fst<'t>(&self, rtxn: &'t RoTxn) -> Result<fst::Set<Cow<'t, [u8]>>>;
+        pub fn stop_words<'t>(&self, rtxn: &'t RoTxn) -> Result<Option<fst::Set<&'t [u8]>>>;
+        pub fn stop_words_mode(&self, rtxn: &RoTxn) -> heed::Result<StopWordsMode>;
+        pub fn synonyms(
+            &self,
+            rtxn: &RoTxn,
+        ) -> heed::Result<HashMap<Vec<String>, Vec<Vec<String>>>>;
+        pub fn words_prefixes_fst<'t>(&self, rtxn: &'t RoTxn) -> Result<fst::Set<Cow<'t, [u8]>>>;
+        pub fn word_documents_count(&self, rtxn: &RoTxn, word: &str) -> heed::Result<Option<u64>>;
+        pub fn word_count(&self, rtxn: &RoTxn) -> Result<u64>;
+        pub fn word_document_frequency(&self, rtxn: &RoTxn, word: &str) -> Result<u64>;
+        pub fn top_words(&self, rtxn: &RoTxn, n: usize) -> Result<Vec<(String, u64)>>;
+        pub fn co_occurring_words(
+            &self,
+            rtxn: &RoTxn,
+            word: &str,
+            limit: usize,
+        ) -> Result<Vec<(String, u64)>>;
+        pub fn words_with_prefix(
+            &self,
+            rtxn: &RoTxn,
+            prefix: &str,
+            limit: usize,
+        ) -> Result<Vec<(String, u64)>>;
+        pub fn words_with_fuzzy_prefix(
+            &self,
+            rtxn: &RoTxn,
+            prefix: &str,
+            limit: usize,
+        ) -> Result<Vec<(String, u64)>>;
+        pub fn documents<'t>(
+            &self,
+            rtxn: &'t RoTxn,
+            ids: impl IntoIterator<Item = DocumentId>,
+        ) -> Result<Vec<(DocumentId, obkv::KvReaderU16<'t>)>>;
+        pub fn document_json(&self, rtxn: &RoTxn, id: DocumentId, buf: &mut String) -> Result<()>;
+        pub fn flattened_documents(
+            &self,
+            rtxn: &RoTxn,
+            ids: impl IntoIterator<Item = DocumentId>,
+        ) -> Result<Vec<(DocumentId, Vec<(String, serde_json::Value)>)>>;
+        pub fn all_documents<'t>(
+            &self,
+            rtxn: &'t RoTxn,
+        ) -> Result<impl Iterator<Item = heed::Result<(DocumentId, obkv::KvReaderU16<'t>)>>>;
+        pub fn soft_deleted_documents<'t>(
+            &self,
+            rtxn: &'t RoTxn,
+        ) -> Result<impl Iterator<Item = heed::Result<(DocumentId, obkv::KvReaderU16<'t>)>>>;
+        pub fn created_at(&self, rtxn: &RoTxn) -> Result<OffsetDateTime>;
+        pub fn updated_at(&self, rtxn: &RoTxn) -> Result<OffsetDateTime>;
+        pub fn indexing_sequence(&self, rtxn: &RoTxn) -> heed::Result<u64>;
+        pub fn document_indexed_at(
+            &self,
+            rtxn: &RoTxn,
+            id: DocumentId,
+        ) -> heed::Result<Option<u64>>;
+        pub fn authorize_typos(&self, rtxn: &RoTxn) -> heed::Result<bool>;
+        pub fn min_word_len_one_typo(&self, rtxn: &RoTxn) -> heed::Result<u8>;
+        pub fn min_word_len_two_typos(&self, rtxn: &RoTxn) -> heed::Result<u8>;
+        pub fn exact_words<'t>(&self, rtxn: &'t RoTxn) -> Result<Option<fst::Set<Cow<'t, [u8]>>>>;
+        pub fn exact_attributes<'t>(&self, rtxn: &'t RoTxn) -> Result<Vec<&'t str>>;
+        pub fn exact_attributes_ids(&self, rtxn: &RoTxn) -> Result<HashSet<FieldId>>;
+        pub fn case_sensitive_attributes<'t>(&self, rtxn: &'t RoTxn) -> Result<Vec<&'t str>>;
+        pub fn case_sensitive_attributes_ids(&self, rtxn: &RoTxn) -> Result<HashSet<FieldId>>;
+        pub fn max_values_per_facet(&self, rtxn: &RoTxn) -> heed::Result<Option<usize>>;
+        pub fn pagination_max_total_hits(&self, rtxn: &RoTxn) -> heed::Result<Option<usize>>;
+        pub fn max_word_length(&self, rtxn: &RoTxn) -> heed::Result<usize>;
+        pub fn long_words_policy(&self, rtxn: &RoTxn) -> heed::Result<LongWordsPolicy>;
+        pub fn min_indexed_word_length(&self, rtxn: &RoTxn) -> heed::Result<usize>;
+        pub fn max_positions_per_word(&self, rtxn: &RoTxn) -> heed::Result<Option<usize>>;
+        pub fn max_fields(&self, rtxn: &RoTxn) -> heed::Result<Option<usize>>;
+        pub fn max_fields_policy(&self, rtxn: &RoTxn) -> heed::Result<MaxFieldsPolicy>;
+        pub fn proximity_databases(&self, rtxn: &RoTxn) -> heed::Result<bool>;
+        pub fn cross_field_proximity(&self, rtxn: &RoTxn) -> heed::Result<bool>;
+        pub fn typo_on_diacritics(&self, rtxn: &RoTxn) -> heed::Result<TypoOnDiacritics>;
     }
 }
 
 #[cfg(test)]
 pub(crate) mod tests {
     use std::collections::HashSet;
-    use std::ops::Deref;
 
     use big_s::S;
-    use heed::{EnvOpenOptions, RwTxn};
+    use heed::types::Str;
+    use heed::EnvOpenOptions;
     use maplit::hashset;
+    use serde_json::json;
     use tempfile::TempDir;
 
-    use crate::documents::DocumentsBatchReader;
     use crate::error::{Error, InternalError};
-    use crate::index::{DEFAULT_MIN_WORD_LEN_ONE_TYPO, DEFAULT_MIN_WORD_LEN_TWO_TYPOS};
+    use crate::index::{main_key, DEFAULT_MIN_WORD_LEN_ONE_TYPO, DEFAULT_MIN_WORD_LEN_TWO_TYPOS};
+    pub(crate) use crate::test_utils::TempIndex;
     use crate::update::{
         self, DeleteDocuments, DeletionStrategy, IndexDocuments, IndexDocumentsConfig,
-        IndexDocumentsMethod, IndexerConfig, Settings,
+        IndexDocumentsMethod, IndexerConfig,
+    };
+    use crate::{
+        db_snap, obkv_to_json, snapshot_index, FieldDistribution, Index, IndexReadOnly, Search,
+        SearchResult,
     };
-    use crate::{db_snap, obkv_to_json, Index, Search, SearchResult};
-
-    pub(crate) struct TempIndex {
-        pub inner: Index,
-        pub indexer_config: IndexerConfig,
-        pub index_documents_config: IndexDocumentsConfig,
-        _tempdir: TempDir,
-    }
-
-    impl Deref for TempIndex {
-        type Target = Index;
-
-        fn deref(&self) -> &Self::Target {
-            &self.inner
-        }
-    }
-
-    impl TempIndex {
-        /// Creates a temporary index
-        pub fn new_with_map_size(size: usize) -> Self {
-            let mut options = EnvOpenOptions::new();
-            options.map_size(size);
-            let _tempdir = TempDir::new_in(".").unwrap();
-            let inner = Index::new(options, _tempdir.path()).unwrap();
-            let indexer_config = IndexerConfig::default();
-            let index_documents_config = IndexDocumentsConfig::default();
-            Self { inner, indexer_config, index_documents_config, _tempdir }
-        }
-        /// Creates a temporary index, with a default `4096 * 1000` size. This should be enough for
-        /// most tests.
-        pub fn new() -> Self {
-            Self::new_with_map_size(4096 * 1000)
-        }
-        pub fn add_documents_using_wtxn<'t, R>(
-            &'t self,
-            wtxn: &mut RwTxn<'t, '_>,
-            documents: DocumentsBatchReader<R>,
-        ) -> Result<(), crate::error::Error>
-        where
-            R: std::io::Read + std::io::Seek,
-        {
-            let builder = IndexDocuments::new(
-                wtxn,
-                self,
-                &self.indexer_config,
-                self.index_documents_config.clone(),
-                |_| (),
-                || false,
-            )
-            .unwrap();
-            let (builder, user_error) = builder.add_documents(documents).unwrap();
-            user_error?;
-            builder.execute()?;
-            Ok(())
-        }
-        pub fn add_documents<R>(
-            &self,
-            documents: DocumentsBatchReader<R>,
-        ) -> Result<(), crate::error::Error>
-        where
-            R: std::io::Read + std::io::Seek,
-        {
-            let mut wtxn = self.write_txn().unwrap();
-            self.add_documents_using_wtxn(&mut wtxn, documents)?;
-            wtxn.commit().unwrap();
-            Ok(())
-        }
-
-        pub fn update_settings(
-            &self,
-            update: impl Fn(&mut Settings),
-        ) -> Result<(), crate::error::Error> {
-            let mut wtxn = self.write_txn().unwrap();
-            self.update_settings_using_wtxn(&mut wtxn, update)?;
-            wtxn.commit().unwrap();
-            Ok(())
-        }
-        pub fn update_settings_using_wtxn<'t>(
-            &'t self,
-            wtxn: &mut RwTxn<'t, '_>,
-            update: impl Fn(&mut Settings),
-        ) -> Result<(), crate::error::Error> {
-            let mut builder = update::Settings::new(wtxn, &self.inner, &self.indexer_config);
-            update(&mut builder);
-            builder.execute(drop, || false)?;
-            Ok(())
-        }
-
-        pub fn delete_document(&self, external_document_id: &str) {
-            let mut wtxn = self.write_txn().unwrap();
-
-            let mut delete = DeleteDocuments::new(&mut wtxn, &self).unwrap();
-            delete.strategy(self.index_documents_config.deletion_strategy);
-
-            delete.delete_external_id(external_document_id);
-            delete.execute().unwrap();
-            wtxn.commit().unwrap();
-        }
-    }
 
     #[test]
     fn aborting_indexation() {
@@ -1343,13 +2774,11 @@ pub(crate) mod tests {
     #[test]
     fn initial_field_distribution() {
         let index = TempIndex::new();
-        index
-            .add_documents(documents!([
-                { "id": 1, "name": "kevin" },
-                { "id": 2, "name": "bob", "age": 20 },
-                { "id": 2, "name": "bob", "age": 20 },
-            ]))
-            .unwrap();
+        index.add_json_documents(serde_json::json!([
+            { "id": 1, "name": "kevin" },
+            { "id": 2, "name": "bob", "age": 20 },
+            { "id": 2, "name": "bob", "age": 20 },
+        ]));
 
         db_snap!(index, field_distribution, 1);
 
@@ -1373,7 +2802,7 @@ pub(crate) mod tests {
             "
         );
 
-        // snapshot_index!(&index, "1", include: "^field_distribution$");
+        snapshot_index!(&index, "1", include: "^field_distribution$");
 
         // we add all the documents a second time. we are supposed to get the same
         // field_distribution in the end
@@ -1410,6 +2839,113 @@ pub(crate) mod tests {
         );
     }
 
+    #[test]
+    fn field_distribution_nested_arrays_and_nulls() {
+        let mut index = TempIndex::new();
+
+        // the null values are not counted, the empty arrays and the nested objects are
+        // counted once under their top-level key, whatever their children are
+        index
+            .add_documents(documents!([
+                { "id": 1, "tags": [], "address": { "city": "Paris", "zip": null }, "note": null },
+                { "id": 2, "tags": ["rust"], "address": null },
+            ]))
+            .unwrap();
+
+        db_snap!(index, field_distribution,
+            @r###"
+            address          1     
+            id               2     
+            tags             2     
+            "###
+        );
+
+        // a replaced document no longer counts for the fields it doesn't contain anymore
+        index
+            .add_documents(documents!([
+                { "id": 1, "tags": null, "note": "hello" },
+            ]))
+            .unwrap();
+
+        db_snap!(index, field_distribution,
+            @r###"
+            id               2     
+            note             1     
+            tags             1     
+            "###
+        );
+
+        // an updated document counts once, with the fields of its merged version
+        index.index_documents_config.update_method = IndexDocumentsMethod::UpdateDocuments;
+        index
+            .add_documents(documents!([
+                { "id": 2, "address": { "city": "Lyon" } },
+            ]))
+            .unwrap();
+
+        db_snap!(index, field_distribution,
+            @r###"
+            address          1     
+            id               2     
+            note             1     
+            tags             1     
+            "###
+        );
+
+        index.delete_document("1");
+
+        db_snap!(index, field_distribution,
+            @r###"
+            address          1     
+            id               1     
+            tags             1     
+            "###
+        );
+
+        // a field set to null by an update isn't counted anymore
+        index
+            .add_documents(documents!([
+                { "id": 2, "tags": null },
+            ]))
+            .unwrap();
+
+        db_snap!(index, field_distribution,
+            @r###"
+            address          1     
+            id               1     
+            "###
+        );
+    }
+
+    #[test]
+    fn field_distribution_counted_again_without_nulls() {
+        let index = TempIndex::new();
+        index
+            .add_documents(documents!([
+                { "id": 1, "note": null },
+                { "id": 2, "note": "hello" },
+            ]))
+            .unwrap();
+
+        // an index created by an older version counted the null values
+        let mut wtxn = index.write_txn().unwrap();
+        let field_distribution = FieldDistribution::from([(S("id"), 2), (S("note"), 2)]);
+        index.put_field_distribution(&mut wtxn, &field_distribution).unwrap();
+        index.main.delete::<_, Str>(&mut wtxn, main_key::NULL_VALUES_UNCOUNTED_KEY).unwrap();
+        wtxn.commit().unwrap();
+
+        // the deletion counts the fields again before decrementing the fields of the document
+        index.delete_document("2");
+
+        db_snap!(index, field_distribution,
+            @r###"
+            id               1     
+            "###
+        );
+        let rtxn = index.read_txn().unwrap();
+        assert!(index.null_values_uncounted(&rtxn).unwrap());
+    }
+
     #[test]
     fn put_and_retrieve_disable_typo() {
         let index = TempIndex::new();
@@ -1504,6 +3040,67 @@ pub(crate) mod tests {
         assert_eq!(user_defined, &["doggo", "name"]);
     }
 
+    #[test]
+    fn soft_deleted_documents_content() {
+        let mut index = TempIndex::new();
+        index.index_documents_config.deletion_strategy = DeletionStrategy::AlwaysSoft;
+
+        index
+            .add_documents(documents!([
+                { "id": 0, "name": "kevin" },
+                { "id": 1, "name": "kevina" },
+                { "id": 2, "name": "benoit" },
+            ]))
+            .unwrap();
+        index.delete_document("1");
+
+        let rtxn = index.read_txn().unwrap();
+        let fields_ids_map = index.fields_ids_map(&rtxn).unwrap();
+        let soft_deleted: Vec<_> = index
+            .soft_deleted_documents(&rtxn)
+            .unwrap()
+            .map(|result| {
+                let (id, obkv) = result.unwrap();
+                (id, obkv_to_json(&[0, 1], &fields_ids_map, obkv).unwrap())
+            })
+            .collect();
+        assert_eq!(soft_deleted.len(), 1);
+        assert_eq!(soft_deleted[0].0, 1);
+        assert_eq!(soft_deleted[0].1["name"], "kevina");
+        assert_eq!(index.all_documents(&rtxn).unwrap().count(), 2);
+        drop(rtxn);
+
+        // a hard deletion purges the content of the soft-deleted documents
+        index.index_documents_config.deletion_strategy = DeletionStrategy::AlwaysHard;
+        index.delete_document("2");
+
+        let rtxn = index.read_txn().unwrap();
+        assert_eq!(index.soft_deleted_documents(&rtxn).unwrap().count(), 0);
+        assert_eq!(index.all_documents(&rtxn).unwrap().count(), 1);
+    }
+
+    #[test]
+    fn external_ids() {
+        let mut index = TempIndex::new();
+        index.index_documents_config.deletion_strategy = DeletionStrategy::AlwaysSoft;
+
+        index
+            .add_documents(documents!([
+                { "id": "kevin" },
+                { "id": "bob" },
+                { "id": "alice" },
+                { "id": "zoe" },
+            ]))
+            .unwrap();
+        // the replaced documents are soft-deleted and their ids soft-inserted again.
+        index.add_documents(documents!([{ "id": "bob", "age": 20 }, { "id": "carol" }])).unwrap();
+        index.delete_document("kevin");
+
+        let rtxn = index.read_txn().unwrap();
+        let external_ids: Vec<_> = index.external_ids(&rtxn).unwrap().collect();
+        assert_eq!(external_ids, vec!["alice", "bob", "carol", "zoe"]);
+    }
+
     #[test]
     fn replace_documents_external_ids_and_soft_deletion_check() {
         use big_s::S;
@@ -2279,17 +3876,144 @@ pub(crate) mod tests {
         "###);
         db_snap!(index, soft_deleted_documents_ids, @"[2, 3, ]");
 
-        let rtxn = index.read_txn().unwrap();
-        let search = Search::new(&rtxn, &index);
-        let SearchResult { matching_words: _, candidates: _, mut documents_ids } =
-            search.execute().unwrap();
-        let primary_key_id = index.fields_ids_map(&rtxn).unwrap().id("primary_key").unwrap();
-        documents_ids.sort_unstable();
-        let docs = index.documents(&rtxn, documents_ids).unwrap();
-        let mut all_ids = HashSet::new();
-        for (_docid, obkv) in docs {
-            let id = obkv.get(primary_key_id).unwrap();
-            assert!(all_ids.insert(id));
+        let rtxn = index.read_txn().unwrap();
+        let search = Search::new(&rtxn, &index);
+        let SearchResult { matching_words: _, candidates: _, mut documents_ids, .. } =
+            search.execute().unwrap();
+        let primary_key_id = index.fields_ids_map(&rtxn).unwrap().id("primary_key").unwrap();
+        documents_ids.sort_unstable();
+        let docs = index.documents(&rtxn, documents_ids).unwrap();
+        let mut all_ids = HashSet::new();
+        for (_docid, obkv) in docs {
+            let id = obkv.get(primary_key_id).unwrap();
+            assert!(all_ids.insert(id));
+        }
+    }
+
+    #[test]
+    fn compact_external_documents_ids() {
+        let mut index = TempIndex::new();
+        index.index_documents_config.deletion_strategy = DeletionStrategy::AlwaysSoft;
+
+        index
+            .update_settings(|settings| {
+                settings.set_primary_key("id".to_owned());
+            })
+            .unwrap();
+
+        let documents: Vec<_> = (0..10).map(|id| serde_json::json!({ "id": id })).collect();
+        index.add_documents(documents!(documents)).unwrap();
+        // too few new ids to be merged into the hard fst automatically.
+        index
+            .add_documents(documents!([
+                { "id": 3, "a": 0 },
+                { "id": 10 },
+                { "id": 11 },
+            ]))
+            .unwrap();
+
+        db_snap!(index, external_documents_ids, @r###"
+        soft:
+        10                       11
+        11                       12
+        3                        10
+        hard:
+        0                        0
+        1                        1
+        2                        2
+        3                        3
+        4                        4
+        5                        5
+        6                        6
+        7                        7
+        8                        8
+        9                        9
+        "###);
+
+        let rtxn = index.read_txn().unwrap();
+        let before = index.external_documents_ids(&rtxn).unwrap().to_hash_map();
+        drop(rtxn);
+
+        let mut wtxn = index.write_txn().unwrap();
+        index.compact_external_documents_ids(&mut wtxn).unwrap();
+        wtxn.commit().unwrap();
+
+        db_snap!(index, external_documents_ids, @r###"
+        soft:
+        hard:
+        0                        0
+        1                        1
+        10                       11
+        11                       12
+        2                        2
+        3                        10
+        4                        4
+        5                        5
+        6                        6
+        7                        7
+        8                        8
+        9                        9
+        "###);
+
+        let rtxn = index.read_txn().unwrap();
+        let external_documents_ids = index.external_documents_ids(&rtxn).unwrap();
+        assert_eq!(external_documents_ids.to_hash_map(), before);
+        // ids that were in the hard fst, in the soft fst and in both before the merge.
+        assert_eq!(external_documents_ids.get("0"), Some(0));
+        assert_eq!(external_documents_ids.get("11"), Some(12));
+        assert_eq!(external_documents_ids.get("3"), Some(10));
+        drop(rtxn);
+
+        // the ids keep being resolved when new documents are added.
+        index.add_documents(documents!([{ "id": 11, "a": 1 }, { "id": 12 }])).unwrap();
+        let rtxn = index.read_txn().unwrap();
+        let external_documents_ids = index.external_documents_ids(&rtxn).unwrap();
+        assert_eq!(external_documents_ids.get("0"), Some(0));
+        assert_eq!(external_documents_ids.get("3"), Some(10));
+        assert_eq!(external_documents_ids.get("11"), Some(13));
+        assert_eq!(external_documents_ids.get("12"), Some(14));
+    }
+
+    // indexes 500k documents, run it with `cargo test -- --ignored`
+    #[ignore]
+    #[test]
+    fn hard_deleted_ids_are_recycled() {
+        let mut index = TempIndex::new_with_map_size(4096 * 100_000); // 400 MB
+        index.index_documents_config.deletion_strategy = DeletionStrategy::AlwaysHard;
+        index
+            .update_settings(|settings| {
+                settings.set_primary_key("id".to_owned());
+            })
+            .unwrap();
+
+        // 100k documents are added and hard-deleted at every round.
+        let batch_size = 100_000;
+        for round in 0..5 {
+            let ids = round * batch_size..(round + 1) * batch_size;
+            let documents: Vec<_> = ids
+                .map(|id| serde_json::json!({ "id": id, "text": format!("word{id}") }))
+                .collect();
+            index.add_documents(documents!(documents)).unwrap();
+
+            // the documents of the previous round are deleted.
+            if round > 0 {
+                let mut wtxn = index.write_txn().unwrap();
+                let mut delete = DeleteDocuments::new(&mut wtxn, &index).unwrap();
+                delete.strategy(DeletionStrategy::AlwaysHard);
+                for id in (round - 1) * batch_size..round * batch_size {
+                    assert!(delete.delete_external_id(&id.to_string()).is_some());
+                }
+                delete.execute().unwrap();
+                wtxn.commit().unwrap();
+            }
+
+            let rtxn = index.read_txn().unwrap();
+            let max_docid = index.documents_ids(&rtxn).unwrap().max().unwrap();
+            assert!(max_docid < 2 * batch_size, "round {round}: max docid {max_docid}");
+            assert_eq!(
+                index.available_ids_count(&rtxn).unwrap(),
+                u32::MAX as u64 + 1 - batch_size as u64
+            );
         }
     }
 
@@ -2357,4 +4081,672 @@ pub(crate) mod tests {
 
         db_snap!(index, geo_faceted_documents_ids); // ensure that no documents were inserted
     }
+
+    #[test]
+    fn words_statistics() {
+        let mut index = TempIndex::new();
+        index.index_documents_config.deletion_strategy = DeletionStrategy::AlwaysSoft;
+
+        index
+            .add_documents(documents!([
+                { "id": 0, "text": "the quick brown fox" },
+                { "id": 1, "text": "the lazy dog" },
+                { "id": 2, "text": "the quick dog" },
+            ]))
+            .unwrap();
+
+        let rtxn = index.read_txn().unwrap();
+        // the ids are indexed too: 0, 1, 2, brown, dog, fox, lazy, quick, the
+        assert_eq!(index.word_count(&rtxn).unwrap(), 9);
+        assert_eq!(index.word_document_frequency(&rtxn, "the").unwrap(), 3);
+        assert_eq!(index.word_document_frequency(&rtxn, "dog").unwrap(), 2);
+        assert_eq!(index.word_document_frequency(&rtxn, "cat").unwrap(), 0);
+        assert_eq!(
+            index.top_words(&rtxn, 3).unwrap(),
+            vec![(S("the"), 3), (S("dog"), 2), (S("quick"), 2)]
+        );
+        assert!(index.top_words(&rtxn, 0).unwrap().is_empty());
+        assert_eq!(index.top_words(&rtxn, 100).unwrap().len(), 9);
+        drop(rtxn);
+
+        index.delete_document("1");
+
+        let rtxn = index.read_txn().unwrap();
+        // the soft-deleted document is ignored by the exact frequency...
+        assert_eq!(index.word_document_frequency(&rtxn, "the").unwrap(), 2);
+        assert_eq!(index.word_document_frequency(&rtxn, "dog").unwrap(), 1);
+        assert_eq!(index.word_document_frequency(&rtxn, "lazy").unwrap(), 0);
+        // ...but still part of the upper bounds
+        assert_eq!(index.word_count(&rtxn).unwrap(), 9);
+        assert_eq!(
+            index.top_words(&rtxn, 3).unwrap(),
+            vec![(S("the"), 3), (S("dog"), 2), (S("quick"), 2)]
+        );
+    }
+
+    #[test]
+    fn document_as_raw_json() {
+        let index = TempIndex::new();
+        index
+            .update_settings(|settings| {
+                // the nested fields are flattened to be filtered
+                settings.set_filterable_fields(hashset! { S("doggo.age") });
+            })
+            .unwrap();
+        index
+            .add_documents(documents!([
+                {
+                    "id": 0,
+                    "name": "ki\"fir\n",
+                    "doggo": { "name": "kefir", "age": 3.5, "toys": ["ball", null, { "kind": "rope" }] },
+                    "good": true,
+                },
+            ]))
+            .unwrap();
+
+        let rtxn = index.read_txn().unwrap();
+        let mut buf = String::new();
+        index.document_json(&rtxn, 0, &mut buf).unwrap();
+        let document: serde_json::Value = serde_json::from_str(&buf).unwrap();
+        assert_eq!(
+            document,
+            serde_json::json!({
+                "id": 0,
+                "name": "ki\"fir\n",
+                "doggo": { "name": "kefir", "age": 3.5, "toys": ["ball", null, { "kind": "rope" }] },
+                "good": true,
+            })
+        );
+
+        // the output is the same as the one of `obkv_to_json`
+        let (_, obkv) = index.documents(&rtxn, Some(0)).unwrap().pop().unwrap();
+        let fields_ids_map = index.fields_ids_map(&rtxn).unwrap();
+        let fields: Vec<_> = obkv.iter().map(|(id, _)| id).collect();
+        let object = obkv_to_json(&fields, &fields_ids_map, obkv).unwrap();
+        assert_eq!(buf, serde_json::to_string(&object).unwrap());
+        drop(rtxn);
+
+        // only the displayed fields are written, in their order
+        index
+            .update_settings(|settings| {
+                settings.set_displayed_fields(vec![S("good"), S("doggo"), S("id")]);
+            })
+            .unwrap();
+        let rtxn = index.read_txn().unwrap();
+        let mut buf = String::from("[");
+        index.document_json(&rtxn, 0, &mut buf).unwrap();
+        assert_eq!(
+            buf,
+            r#"[{"good":true,"doggo":{"name":"kefir","age":3.5,"toys":["ball",null,{"kind":"rope"}]},"id":0}"#
+        );
+
+        assert!(index.document_json(&rtxn, 42, &mut buf).is_err());
+    }
+
+    #[test]
+    fn document_as_flattened_fields() {
+        let index = TempIndex::new();
+        index
+            .add_documents(documents!([
+                {
+                    "id": 0,
+                    "name": "kefir",
+                    "doggo": { "name": "kefir", "age": 3.5, "toys": ["ball", null, { "kind": "rope" }] },
+                    "good": true,
+                },
+                {
+                    "id": 1,
+                    // an empty array is kept, even when a dotted key is nested under it
+                    "doggo": { "friends": [], "friends.best": "echo", "collar": { "color": "red" } },
+                },
+            ]))
+            .unwrap();
+
+        let rtxn = index.read_txn().unwrap();
+        let mut documents = index.flattened_documents(&rtxn, [0, 1]).unwrap();
+        let (id, fields) = documents.remove(0);
+        assert_eq!(id, 0);
+        assert_eq!(
+            fields,
+            vec![
+                (S("id"), json!(0)),
+                (S("name"), json!("kefir")),
+                (S("doggo.name"), json!("kefir")),
+                (S("doggo.age"), json!(3.5)),
+                (S("doggo.toys"), json!(["ball", null])),
+                (S("doggo.toys.kind"), json!("rope")),
+                (S("good"), json!(true)),
+            ]
+        );
+        let (id, other_fields) = documents.remove(0);
+        assert_eq!(id, 1);
+        assert_eq!(
+            other_fields,
+            vec![
+                (S("id"), json!(1)),
+                (S("doggo.friends.best"), json!("echo")),
+                (S("doggo.collar.color"), json!("red")),
+                (S("doggo.friends"), json!([])),
+            ]
+        );
+
+        // the fields are named like the ones created when the document was flattened
+        let fields_ids_map = index.fields_ids_map(&rtxn).unwrap();
+        for (name, _) in &fields {
+            assert!(fields_ids_map.id(name).is_some(), "{name} is not a field of the index");
+        }
+        drop(rtxn);
+
+        // only the displayed fields are returned, in their order
+        index
+            .update_settings(|settings| {
+                settings.set_displayed_fields(vec![S("good"), S("doggo")]);
+            })
+            .unwrap();
+        let rtxn = index.read_txn().unwrap();
+        let (_, fields) = index.flattened_documents(&rtxn, [0]).unwrap().remove(0);
+        let names: Vec<_> = fields.iter().map(|(name, _)| name.as_str()).collect();
+        assert_eq!(names, ["good", "doggo.name", "doggo.age", "doggo.toys", "doggo.toys.kind"]);
+
+        assert!(index.flattened_documents(&rtxn, [0, 42]).is_err());
+    }
+
+    #[test]
+    fn documents_indexed_at() {
+        let mut index = TempIndex::new();
+
+        let indexed_at = |index: &TempIndex, external_id: &str| {
+            let rtxn = index.read_txn().unwrap();
+            let docid = index.external_documents_ids(&rtxn).unwrap().get(external_id).unwrap();
+            index.document_indexed_at(&rtxn, docid).unwrap()
+        };
+
+        // the sequence is not tracked by default
+        index.add_documents(documents!([{ "id": 0, "text": "kefir" }])).unwrap();
+        assert_eq!(indexed_at(&index, "0"), None);
+        assert_eq!(index.indexing_sequence(&index.read_txn().unwrap()).unwrap(), 0);
+
+        index.indexer_config.track_indexing_sequence = true;
+
+        index
+            .add_documents(documents!([
+                { "id": 0, "text": "kefir" },
+                { "id": 1, "text": "intel" },
+                { "id": 2, "text": "mimi" },
+            ]))
+            .unwrap();
+        assert_eq!(indexed_at(&index, "1"), Some(1));
+        assert_eq!(indexed_at(&index, "2"), Some(1));
+
+        // an update bumps the sequence of the updated document only
+        index.add_documents(documents!([{ "id": 1, "text": "intel the cat" }])).unwrap();
+        assert_eq!(indexed_at(&index, "1"), Some(2));
+        assert_eq!(indexed_at(&index, "2"), Some(1));
+        assert_eq!(index.indexing_sequence(&index.read_txn().unwrap()).unwrap(), 2);
+
+        // a settings update reindexing the documents bumps all of them
+        index
+            .update_settings(|settings| {
+                settings.set_searchable_fields(vec![S("text")]);
+            })
+            .unwrap();
+        assert_eq!(indexed_at(&index, "0"), Some(3));
+        assert_eq!(indexed_at(&index, "1"), Some(3));
+        assert_eq!(indexed_at(&index, "2"), Some(3));
+
+        // deleted documents lose their sequence number
+        let rtxn = index.read_txn().unwrap();
+        let docid = index.external_documents_ids(&rtxn).unwrap().get("2").unwrap();
+        drop(rtxn);
+        index.index_documents_config.deletion_strategy = DeletionStrategy::AlwaysHard;
+        index.delete_document("2");
+        let rtxn = index.read_txn().unwrap();
+        assert_eq!(index.document_indexed_at(&rtxn, docid).unwrap(), None);
+    }
+
+    #[test]
+    fn words_with_prefix() {
+        let index = TempIndex::new();
+
+        index
+            .add_documents(documents!([
+                { "id": 0, "text": "привет приветствие" },
+                { "id": 1, "text": "привет пример" },
+                { "id": 2, "text": "пример прием" },
+            ]))
+            .unwrap();
+
+        let rtxn = index.read_txn().unwrap();
+        assert_eq!(
+            index.words_with_prefix(&rtxn, "при", 10).unwrap(),
+            vec![(S("привет"), 2), (S("пример"), 2), (S("приветствие"), 1), (S("прием"), 1)]
+        );
+        assert_eq!(
+            index.words_with_prefix(&rtxn, "при", 2).unwrap(),
+            vec![(S("привет"), 2), (S("пример"), 2)]
+        );
+        assert_eq!(
+            index.words_with_prefix(&rtxn, "прив", 10).unwrap(),
+            vec![(S("привет"), 2), (S("приветствие"), 1)]
+        );
+        assert!(index.words_with_prefix(&rtxn, "при", 0).unwrap().is_empty());
+        assert!(index.words_with_prefix(&rtxn, "прем", 10).unwrap().is_empty());
+        assert!(index.words_with_prefix(&rtxn, "zz", 10).unwrap().is_empty());
+
+        // the words without typos are returned first
+        assert_eq!(
+            index.words_with_fuzzy_prefix(&rtxn, "прие", 10).unwrap(),
+            vec![(S("прием"), 1), (S("привет"), 2), (S("пример"), 2), (S("приветствие"), 1)]
+        );
+        assert_eq!(
+            index.words_with_fuzzy_prefix(&rtxn, "прем", 10).unwrap(),
+            vec![(S("пример"), 2), (S("прием"), 1)]
+        );
+        assert!(index.words_with_fuzzy_prefix(&rtxn, "zz", 10).unwrap().is_empty());
+        assert_eq!(index.words_with_fuzzy_prefix(&rtxn, "", 100).unwrap().len(), 7);
+    }
+
+    #[test]
+    fn co_occurring_words() {
+        let mut index = TempIndex::new();
+        index.index_documents_config.deletion_strategy = DeletionStrategy::AlwaysSoft;
+        index
+            .update_settings(|settings| {
+                settings.set_searchable_fields(vec![S("title"), S("description")]);
+            })
+            .unwrap();
+        index
+            .add_documents(documents!([
+                { "id": 0, "title": "peanut butter jelly" },
+                { "id": 1, "title": "peanut butter cookies" },
+                { "id": 2, "title": "peanut butter", "description": "salted butter" },
+                { "id": 3, "title": "jelly beans" },
+                { "id": 4, "title": "strawberry jelly" },
+            ]))
+            .unwrap();
+
+        let rtxn = index.read_txn().unwrap();
+        // butter is counted once for the document 2
+        assert_eq!(
+            index.co_occurring_words(&rtxn, "peanut", 10).unwrap(),
+            vec![(S("butter"), 3), (S("cookies"), 1), (S("jelly"), 1), (S("salted"), 1)]
+        );
+        assert_eq!(
+            index.co_occurring_words(&rtxn, "jelly", 2).unwrap(),
+            vec![(S("beans"), 1), (S("butter"), 1)]
+        );
+        assert!(index.co_occurring_words(&rtxn, "peanut", 0).unwrap().is_empty());
+        assert!(index.co_occurring_words(&rtxn, "unknown", 10).unwrap().is_empty());
+        drop(rtxn);
+
+        // the soft-deleted documents are ignored
+        index.delete_document("1");
+        let rtxn = index.read_txn().unwrap();
+        assert_eq!(
+            index.co_occurring_words(&rtxn, "peanut", 10).unwrap(),
+            vec![(S("butter"), 2), (S("jelly"), 1), (S("salted"), 1)]
+        );
+    }
+
+    #[test]
+    fn replace_documents_preserving_internal_ids() {
+        let mut index = TempIndex::new();
+        index.index_documents_config.preserve_documents_ids = true;
+        index.index_documents_config.deletion_strategy = DeletionStrategy::AlwaysSoft;
+
+        index
+            .add_documents(documents!([
+                { "id": 0, "doggo": "kefir" },
+                { "id": 1, "doggo": "intel" },
+                { "id": 2, "doggo": "mimi" },
+            ]))
+            .unwrap();
+        index.delete_document("2");
+
+        db_snap!(index, documents_ids, @"[0, 1, ]");
+        db_snap!(index, soft_deleted_documents_ids, @"[2, ]");
+
+        // the replaced document keeps its internal id and, as it is hard-deleted,
+        // the other soft-deleted documents are hard-deleted along with it
+        index
+            .add_documents(documents!([
+                { "id": 1, "doggo": "bernese" },
+                { "id": 3, "doggo": "moka" },
+            ]))
+            .unwrap();
+
+        db_snap!(index, documents_ids, @"[0, 1, 3, ]");
+        db_snap!(index, soft_deleted_documents_ids, @"[]");
+
+        let rtxn = index.read_txn().unwrap();
+        let external_documents_ids = index.external_documents_ids(&rtxn).unwrap();
+        assert_eq!(external_documents_ids.get("0"), Some(0));
+        assert_eq!(external_documents_ids.get("1"), Some(1));
+        assert_eq!(external_documents_ids.get("2"), None);
+        assert_eq!(external_documents_ids.get("3"), Some(3));
+
+        let SearchResult { documents_ids, .. } =
+            index.search(&rtxn).query("bernese").execute().unwrap();
+        assert_eq!(documents_ids, vec![1]);
+        let SearchResult { documents_ids, .. } =
+            index.search(&rtxn).query("intel").execute().unwrap();
+        assert!(documents_ids.is_empty());
+        drop(rtxn);
+
+        // the same goes for the updated documents
+        index.index_documents_config.update_method = IndexDocumentsMethod::UpdateDocuments;
+        index.add_documents(documents!([{ "id": 0, "age": 3 }])).unwrap();
+
+        db_snap!(index, documents_ids, @"[0, 1, 3, ]");
+
+        let rtxn = index.read_txn().unwrap();
+        let external_documents_ids = index.external_documents_ids(&rtxn).unwrap();
+        assert_eq!(external_documents_ids.get("0"), Some(0));
+        let SearchResult { documents_ids, .. } =
+            index.search(&rtxn).query("kefir").execute().unwrap();
+        assert_eq!(documents_ids, vec![0]);
+    }
+
+    #[test]
+    fn read_only_index() {
+        let index = TempIndex::new();
+        index
+            .update_settings(|settings| settings.set_filterable_fields(hashset! { S("doggo") }))
+            .unwrap();
+        index
+            .add_documents(documents!([
+                { "id": 0, "doggo": "kefir" },
+                { "id": 1, "doggo": "bernese" },
+            ]))
+            .unwrap();
+
+        let dir = TempDir::new_in(".").unwrap();
+        let path = dir.path().join("data.mdb");
+        let file = index.copy_to_path(&path, heed::CompactionOption::Disabled).unwrap();
+        let mut permissions = file.metadata().unwrap().permissions();
+        permissions.set_readonly(true);
+        std::fs::set_permissions(&path, permissions).unwrap();
+        drop(file);
+
+        let mut options = EnvOpenOptions::new();
+        options.map_size(4096 * 1000);
+        let read_only = IndexReadOnly::new(options, dir.path()).unwrap();
+
+        let rtxn = read_only.read_txn().unwrap();
+        assert_eq!(read_only.number_of_documents(&rtxn).unwrap(), 2);
+        assert_eq!(read_only.primary_key(&rtxn).unwrap(), Some("id"));
+
+        let SearchResult { documents_ids, .. } =
+            read_only.search(&rtxn).query("bernese").execute().unwrap();
+        assert_eq!(documents_ids, vec![1]);
+
+        let distribution =
+            read_only.facets_distribution(&rtxn).facets(Some("doggo")).execute().unwrap();
+        assert_eq!(distribution["doggo"].len(), 2);
+    }
+
+    #[test]
+    fn read_only_index_missing_databases() {
+        let dir = TempDir::new_in(".").unwrap();
+        let mut options = EnvOpenOptions::new();
+        options.map_size(4096 * 1000);
+        // an empty environment doesn't contain any of the databases of the index.
+        drop(options.open(dir.path()).unwrap());
+
+        let mut options = EnvOpenOptions::new();
+        options.map_size(4096 * 1000);
+        let error = IndexReadOnly::new(options, dir.path()).unwrap_err();
+        assert!(matches!(
+            error,
+            Error::InternalError(InternalError::DatabaseMissing { db_name: "main" })
+        ));
+    }
+
+    #[test]
+    fn read_only_older_index() {
+        use heed::types::ByteSlice;
+
+        use crate::index::db_name::*;
+
+        let dir = TempDir::new_in(".").unwrap();
+        let mut options = EnvOpenOptions::new();
+        options.map_size(4096 * 1000);
+        options.max_dbs(18);
+        // the indexes created by the older versions don't contain the databases added since.
+        let env = options.open(dir.path()).unwrap();
+        env.create_poly_database(Some(MAIN)).unwrap();
+        for name in [
+            WORD_DOCIDS,
+            EXACT_WORD_DOCIDS,
+            WORD_PREFIX_DOCIDS,
+            EXACT_WORD_PREFIX_DOCIDS,
+            DOCID_WORD_POSITIONS,
+            WORD_PAIR_PROXIMITY_DOCIDS,
+            WORD_PREFIX_PAIR_PROXIMITY_DOCIDS,
+            PREFIX_WORD_PAIR_PROXIMITY_DOCIDS,
+            WORD_POSITION_DOCIDS,
+            WORD_PREFIX_POSITION_DOCIDS,
+            FIELD_ID_WORD_COUNT_DOCIDS,
+            FACET_ID_F64_DOCIDS,
+            FACET_ID_EXISTS_DOCIDS,
+            FACET_ID_STRING_DOCIDS,
+            FIELD_ID_DOCID_FACET_F64S,
+            FIELD_ID_DOCID_FACET_STRINGS,
+            DOCUMENTS,
+        ] {
+            env.create_database::<ByteSlice, ByteSlice>(Some(name)).unwrap();
+        }
+        drop(env);
+
+        let mut options = EnvOpenOptions::new();
+        options.map_size(4096 * 1000);
+        let error = IndexReadOnly::new(options, dir.path()).unwrap_err();
+        assert!(matches!(
+            error,
+            Error::InternalError(InternalError::DatabaseMissing { db_name: FACET_ID_BOOL_DOCIDS })
+        ));
+
+        // opening the index once in read-write mode creates the missing databases.
+        let mut options = EnvOpenOptions::new();
+        options.map_size(4096 * 1000);
+        drop(Index::new(options, dir.path()).unwrap());
+
+        let mut options = EnvOpenOptions::new();
+        options.map_size(4096 * 1000);
+        let read_only = IndexReadOnly::new(options, dir.path()).unwrap();
+        let rtxn = read_only.read_txn().unwrap();
+        assert_eq!(read_only.number_of_documents(&rtxn).unwrap(), 0);
+        assert_eq!(read_only.document_indexed_at(&rtxn, 0).unwrap(), None);
+    }
+
+    #[test]
+    #[cfg(feature = "unsafe-bulk-load-tests")]
+    fn unsafe_open_options_produce_the_same_index() {
+        use crate::snapshot_tests::*;
+        use crate::IndexOpenOptions;
+
+        fn index_documents(index: &Index) {
+            let config = IndexerConfig::default();
+            let mut wtxn = index.write_txn().unwrap();
+            let mut builder = update::Settings::new(&mut wtxn, index, &config);
+            builder.set_filterable_fields(hashset! { S("doggo") });
+            builder.execute(|_| (), || false).unwrap();
+            let builder = IndexDocuments::new(
+                &mut wtxn,
+                index,
+                &config,
+                IndexDocumentsConfig::default(),
+                |_| (),
+                || false,
+            )
+            .unwrap();
+            let (builder, user_error) = builder
+                .add_documents(documents!([
+                    { "id": 0, "name": "kefir", "doggo": "bernese" },
+                    { "id": 1, "name": "echo", "doggo": "golden retriever" },
+                    { "id": 2, "name": "moka", "doggo": "bernese" },
+                ]))
+                .unwrap();
+            user_error.unwrap();
+            builder.execute().unwrap();
+            wtxn.commit().unwrap();
+        }
+
+        fn snap_index(index: &Index) -> Vec<String> {
+            vec![
+                snap_word_docids(index),
+                snap_exact_word_docids(index),
+                snap_word_prefix_docids(index),
+                snap_exact_word_prefix_docids(index),
+                snap_docid_word_positions(index),
+                snap_word_pair_proximity_docids(index),
+                snap_word_prefix_pair_proximity_docids(index),
+                snap_prefix_word_pair_proximity_docids(index),
+                snap_word_position_docids(index),
+                snap_field_id_word_count_docids(index),
+                snap_word_prefix_position_docids(index),
+                snap_facet_id_f64_docids(index),
+                snap_facet_id_exists_docids(index),
+                snap_facet_id_string_docids(index),
+                snap_field_id_docid_facet_strings(index),
+                snap_documents_ids(index),
+                snap_stop_words(index),
+                snap_soft_deleted_documents_ids(index),
+                snap_field_distributions(index),
+                snap_fields_ids_map(index),
+                snap_geo_faceted_documents_ids(index),
+                snap_external_documents_ids(index),
+                snap_number_faceted_documents_ids(index),
+                snap_string_faceted_documents_ids(index),
+                snap_words_fst(index),
+                snap_words_prefixes_fst(index),
+                snap_settings(index),
+            ]
+        }
+
+        let mut options = EnvOpenOptions::new();
+        options.map_size(4096 * 1000);
+        let safe_dir = TempDir::new_in(".").unwrap();
+        let safe_index = Index::new(options, safe_dir.path()).unwrap();
+        index_documents(&safe_index);
+
+        let mut options = EnvOpenOptions::new();
+        options.map_size(4096 * 1000);
+        let unsafe_dir = TempDir::new_in(".").unwrap();
+        let mut open_options = IndexOpenOptions::new();
+        open_options.unsafe_no_sync(true).writemap(true);
+        let unsafe_index =
+            Index::new_with_open_options(options, unsafe_dir.path(), open_options).unwrap();
+        index_documents(&unsafe_index);
+        unsafe_index.force_sync().unwrap();
+
+        assert_eq!(snap_index(&safe_index), snap_index(&unsafe_index));
+    }
+
+    #[test]
+    #[cfg(feature = "tracing")]
+    fn tracing_spans() {
+        use std::cell::RefCell;
+        use std::sync::{Arc, Mutex};
+
+        use tracing::span::{Attributes, Current, Id, Record};
+        use tracing::{Event, Metadata, Subscriber};
+
+        thread_local! {
+            static ENTERED: RefCell<Vec<Id>> = RefCell::new(Vec::new());
+        }
+
+        /// A subscriber that collects the created spans along with their parent,
+        /// the span entered on the thread that creates them.
+        #[derive(Default, Clone)]
+        struct SpanTree(Arc<Mutex<Vec<(&'static Metadata<'static>, Option<Id>)>>>);
+
+        impl Subscriber for SpanTree {
+            fn enabled(&self, _metadata: &Metadata) -> bool {
+                true
+            }
+
+            fn new_span(&self, span: &Attributes) -> Id {
+                let parent = match span.parent() {
+                    Some(parent) => Some(parent.clone()),
+                    None if span.is_contextual() => {
+                        ENTERED.with(|entered| entered.borrow().last().cloned())
+                    }
+                    None => None,
+                };
+                let mut spans = self.0.lock().unwrap();
+                spans.push((span.metadata(), parent));
+                Id::from_u64(spans.len() as u64)
+            }
+
+            fn current_span(&self) -> Current {
+                let spans = self.0.lock().unwrap();
+                ENTERED.with(|entered| match entered.borrow().last() {
+                    Some(id) => Current::new(id.clone(), spans[id.into_u64() as usize - 1].0),
+                    None => Current::none(),
+                })
+            }
+
+            fn record(&self, _span: &Id, _values: &Record) {}
+            fn record_follows_from(&self, _span: &Id, _follows: &Id) {}
+            fn event(&self, _event: &Event) {}
+
+            fn enter(&self, span: &Id) {
+                ENTERED.with(|entered| entered.borrow_mut().push(span.clone()));
+            }
+
+            fn exit(&self, _span: &Id) {
+                ENTERED.with(|entered| entered.borrow_mut().pop());
+            }
+        }
+
+        // the extraction runs on the threads of the indexing pool, the subscriber must be global.
+        let span_tree = SpanTree::default();
+        tracing::subscriber::set_global_default(span_tree.clone()).unwrap();
+
+        let index = TempIndex::new();
+        index
+            .add_documents(documents!([
+                { "id": 0, "doggo": "kefir" },
+                { "id": 1, "doggo": "bernese" },
+            ]))
+            .unwrap();
+
+        let rtxn = index.read_txn().unwrap();
+        let SearchResult { documents_ids, .. } =
+            index.search(&rtxn).query("kefir").execute().unwrap();
+        assert_eq!(documents_ids, vec![0]);
+
+        let spans = span_tree.0.lock().unwrap();
+        let is_descendant = |name: &str, ancestor: &str| {
+            spans.iter().filter(|(metadata, _)| metadata.name() == name).any(|(_, parent)| {
+                let mut parent = parent.clone();
+                while let Some(id) = parent {
+                    let (metadata, grand_parent) = &spans[id.into_u64() as usize - 1];
+                    if metadata.name() == ancestor {
+                        return true;
+                    }
+                    parent = grand_parent.clone();
+                }
+                false
+            })
+        };
+
+        // the spans of the tasks run on the other threads are children of the indexing span.
+        for (name, ancestor) in [
+            ("extract_docid_word_positions", "IndexDocuments::execute_raw"),
+            ("extract_fid_docid_facet_values", "IndexDocuments::execute_raw"),
+            ("extract_word_docids", "IndexDocuments::execute_raw"),
+            ("extract_word_position_docids", "IndexDocuments::execute_raw"),
+            ("write_typed_chunk_into_index", "IndexDocuments::execute_raw"),
+            ("Words::next", "Search::execute"),
+            ("Typo::next", "Search::execute"),
+            ("Final::next", "Search::execute"),
+        ] {
+            assert!(is_descendant(name, ancestor), "no {} span in the {} span", name, ancestor);
+        }
+        assert!(spans
+            .iter()
+            .any(|(metadata, _)| metadata.name() == "IndexDocuments::execute_prefix_databases"));
+    }
 }
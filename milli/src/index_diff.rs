@@ -0,0 +1,327 @@
+use std::fmt;
+
+use heed::types::{ByteSlice, OwnedType};
+use heed::{BytesDecode, Database};
+
+use crate::facet::FacetType;
+use crate::snapshot_tests::{
+    display_bytes, display_docid_word_positions_entry, display_document,
+    display_documents_indexed_at_entry, display_facet_entry, display_facet_id_bool_docids_entry,
+    display_facet_id_exists_docids_entry, display_facet_id_string_originals_entry,
+    display_field_id_docid_facet_f64s_entry, display_field_id_docid_facet_strings_entry,
+    display_field_id_word_count_docids_entry, display_main_key,
+    display_prefix_word_pair_proximity_docids_entry, display_word_docids_entry,
+    display_word_pair_proximity_docids_entry, display_word_position_docids_entry,
+    display_word_prefix_pair_proximity_docids_entry, display_word_prefix_position_docids_entry,
+};
+use crate::{FieldsIdsMap, Index, Result, BEU32};
+
+/// The maximum number of differences displayed for each database, the others are only counted.
+const MAX_DISPLAYED_DIFFERENCES: u64 = 10;
+
+/// The differences between two indexes, see [`index_diff`].
+///
+/// Its `Display` implementation writes, for every database that differs, the number of
+/// differences followed by the first ones, formatted like the snapshots of the database.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct IndexDiffReport {
+    /// The databases that differ, in the order of the fields of the [`Index`].
+    pub databases: Vec<DatabaseDiff>,
+}
+
+impl IndexDiffReport {
+    /// Returns `true` if the two indexes contain exactly the same entries.
+    pub fn is_empty(&self) -> bool {
+        self.databases.is_empty()
+    }
+}
+
+/// The differences between the entries of a database of two indexes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DatabaseDiff {
+    pub name: &'static str,
+    /// The number of keys only present in the left index.
+    pub only_in_left: u64,
+    /// The number of keys only present in the right index.
+    pub only_in_right: u64,
+    /// The number of keys present in both indexes but with different values.
+    pub different_values: u64,
+    /// The first differences, the entries of the left index are prefixed by `-` and the ones
+    /// of the right index by `+`, a key with different values is displayed once on each side.
+    /// Only the keys of the main database are displayed, not its values.
+    pub entries: Vec<String>,
+}
+
+impl DatabaseDiff {
+    fn new(name: &'static str) -> DatabaseDiff {
+        DatabaseDiff {
+            name,
+            only_in_left: 0,
+            only_in_right: 0,
+            different_values: 0,
+            entries: Vec::new(),
+        }
+    }
+
+    /// Returns the total number of differences, displayed or not.
+    pub fn differences(&self) -> u64 {
+        self.only_in_left + self.only_in_right + self.different_values
+    }
+
+    fn push_left(&mut self, entry: String) {
+        self.only_in_left += 1;
+        if self.differences() <= MAX_DISPLAYED_DIFFERENCES {
+            self.entries.push(format!("- {entry}"));
+        }
+    }
+
+    fn push_right(&mut self, entry: String) {
+        self.only_in_right += 1;
+        if self.differences() <= MAX_DISPLAYED_DIFFERENCES {
+            self.entries.push(format!("+ {entry}"));
+        }
+    }
+
+    fn push_different(&mut self, left: String, right: String) {
+        self.different_values += 1;
+        if self.differences() <= MAX_DISPLAYED_DIFFERENCES {
+            self.entries.push(format!("- {left}"));
+            self.entries.push(format!("+ {right}"));
+        }
+    }
+}
+
+impl fmt::Display for IndexDiffReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for database in &self.databases {
+            writeln!(
+                f,
+                "{}: {} only in left, {} only in right, {} different",
+                database.name,
+                database.only_in_left,
+                database.only_in_right,
+                database.different_values
+            )?;
+            for entry in &database.entries {
+                writeln!(f, "{entry}")?;
+            }
+            let hidden = database.differences().saturating_sub(MAX_DISPLAYED_DIFFERENCES);
+            if hidden > 0 {
+                writeln!(f, "... and {hidden} more differences")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Compares two indexes database by database and reports the keys present in only one of
+/// them and the keys whose values differ, e.g. to check that a migration rebuilt an index
+/// identically.
+///
+/// The databases are read in a single pass, in the order of their keys, without being loaded
+/// in memory. The entries are compared as raw bytes, the settings or the timestamps stored in
+/// the main database are therefore reported like any other entry.
+pub fn index_diff(left: &Index, right: &Index) -> Result<IndexDiffReport> {
+    let left_rtxn = left.read_txn()?;
+    let right_rtxn = right.read_txn()?;
+    let mut databases = Vec::new();
+
+    databases.push(diff_entries(
+        "main",
+        left.main.iter::<_, ByteSlice, ByteSlice>(&left_rtxn)?,
+        right.main.iter::<_, ByteSlice, ByteSlice>(&right_rtxn)?,
+        &|key, _| display_main_key(key),
+        &|key, _| display_main_key(key),
+    )?);
+
+    // The databases whose entries are displayed from their decoded keys and values.
+    macro_rules! diff_database {
+        ($name:ident, $display:expr) => {{
+            let display =
+                |key: &[u8], value: &[u8]| display_entry(&left.$name, key, value, $display);
+            databases.push(diff_entries(
+                stringify!($name),
+                left.$name.remap_types::<ByteSlice, ByteSlice>().iter(&left_rtxn)?,
+                right.$name.remap_types::<ByteSlice, ByteSlice>().iter(&right_rtxn)?,
+                &display,
+                &display,
+            )?);
+        }};
+    }
+
+    diff_database!(word_docids, display_word_docids_entry);
+    diff_database!(exact_word_docids, display_word_docids_entry);
+    diff_database!(word_prefix_docids, display_word_docids_entry);
+    diff_database!(exact_word_prefix_docids, display_word_docids_entry);
+    diff_database!(docid_word_positions, display_docid_word_positions_entry);
+    diff_database!(word_pair_proximity_docids, display_word_pair_proximity_docids_entry);
+    diff_database!(
+        word_prefix_pair_proximity_docids,
+        display_word_prefix_pair_proximity_docids_entry
+    );
+    diff_database!(
+        prefix_word_pair_proximity_docids,
+        display_prefix_word_pair_proximity_docids_entry
+    );
+    diff_database!(word_position_docids, display_word_position_docids_entry);
+    diff_database!(field_id_word_count_docids, display_field_id_word_count_docids_entry);
+    diff_database!(word_prefix_position_docids, display_word_prefix_position_docids_entry);
+    diff_database!(facet_id_exists_docids, display_facet_id_exists_docids_entry);
+
+    databases.push(diff_entries(
+        "facet_id_f64_docids",
+        left.facet_id_f64_docids.remap_types::<ByteSlice, ByteSlice>().iter(&left_rtxn)?,
+        right.facet_id_f64_docids.remap_types::<ByteSlice, ByteSlice>().iter(&right_rtxn)?,
+        &|key, value| display_facet_entry(FacetType::Number, key, value),
+        &|key, value| display_facet_entry(FacetType::Number, key, value),
+    )?);
+    databases.push(diff_entries(
+        "facet_id_string_docids",
+        left.facet_id_string_docids.remap_types::<ByteSlice, ByteSlice>().iter(&left_rtxn)?,
+        right.facet_id_string_docids.remap_types::<ByteSlice, ByteSlice>().iter(&right_rtxn)?,
+        &|key, value| display_facet_entry(FacetType::String, key, value),
+        &|key, value| display_facet_entry(FacetType::String, key, value),
+    )?);
+
+    diff_database!(facet_id_bool_docids, display_facet_id_bool_docids_entry);
+    diff_database!(facet_id_string_originals, display_facet_id_string_originals_entry);
+    diff_database!(field_id_docid_facet_f64s, display_field_id_docid_facet_f64s_entry);
+    diff_database!(field_id_docid_facet_strings, display_field_id_docid_facet_strings_entry);
+
+    // The documents are displayed with the fields ids map of their own index.
+    let left_fields_ids_map = left.fields_ids_map(&left_rtxn)?;
+    let right_fields_ids_map = right.fields_ids_map(&right_rtxn)?;
+    databases.push(diff_entries(
+        "documents",
+        left.documents.remap_types::<ByteSlice, ByteSlice>().iter(&left_rtxn)?,
+        right.documents.remap_types::<ByteSlice, ByteSlice>().iter(&right_rtxn)?,
+        &|key, value| display_document_entry(key, value, &left_fields_ids_map),
+        &|key, value| display_document_entry(key, value, &right_fields_ids_map),
+    )?);
+
+    diff_database!(documents_indexed_at, display_documents_indexed_at_entry);
+
+    databases.retain(|database| database.differences() != 0);
+    Ok(IndexDiffReport { databases })
+}
+
+/// Compares the entries of a database of two indexes, both sorted by key, in a single pass.
+fn diff_entries<'t>(
+    name: &'static str,
+    mut left: impl Iterator<Item = heed::Result<(&'t [u8], &'t [u8])>>,
+    mut right: impl Iterator<Item = heed::Result<(&'t [u8], &'t [u8])>>,
+    display_left: &dyn Fn(&[u8], &[u8]) -> String,
+    display_right: &dyn Fn(&[u8], &[u8]) -> String,
+) -> heed::Result<DatabaseDiff> {
+    let mut diff = DatabaseDiff::new(name);
+    let mut left_entry = left.next().transpose()?;
+    let mut right_entry = right.next().transpose()?;
+
+    loop {
+        match (left_entry, right_entry) {
+            (None, None) => break,
+            (Some((left_key, left_value)), Some((right_key, right_value)))
+                if left_key == right_key =>
+            {
+                if left_value != right_value {
+                    diff.push_different(
+                        display_left(left_key, left_value),
+                        display_right(right_key, right_value),
+                    );
+                }
+                left_entry = left.next().transpose()?;
+                right_entry = right.next().transpose()?;
+            }
+            (Some((left_key, left_value)), Some((right_key, _))) if left_key < right_key => {
+                diff.push_left(display_left(left_key, left_value));
+                left_entry = left.next().transpose()?;
+            }
+            (Some((left_key, left_value)), None) => {
+                diff.push_left(display_left(left_key, left_value));
+                left_entry = left.next().transpose()?;
+            }
+            (_, Some((right_key, right_value))) => {
+                diff.push_right(display_right(right_key, right_value));
+                right_entry = right.next().transpose()?;
+            }
+        }
+    }
+
+    Ok(diff)
+}
+
+/// Decodes an entry with the codecs of the database to display it like its snapshot,
+/// the entries that can't be decoded are displayed with their raw bytes.
+fn display_entry<'a, KC, DC>(
+    _database: &Database<KC, DC>,
+    key: &'a [u8],
+    value: &'a [u8],
+    display: fn((KC::DItem, DC::DItem)) -> String,
+) -> String
+where
+    KC: BytesDecode<'a>,
+    DC: BytesDecode<'a>,
+{
+    match (KC::bytes_decode(key), DC::bytes_decode(value)) {
+        (Some(key), Some(value)) => display((key, value)),
+        _ => format!("<<undecodable entry {}: {}>>", display_bytes(key), display_bytes(value)),
+    }
+}
+
+fn display_document_entry(key: &[u8], value: &[u8], fields_ids_map: &FieldsIdsMap) -> String {
+    match OwnedType::<BEU32>::bytes_decode(key) {
+        Some(docid) => {
+            let document = display_document(obkv::KvReaderU16::new(value), fields_ids_map);
+            format!("{:<4} {document}", docid.get())
+        }
+        None => format!("<<undecodable entry {}: {}>>", display_bytes(key), display_bytes(value)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::index_diff;
+    use crate::test_utils::TempIndex;
+
+    #[test]
+    fn one_different_document() {
+        let left = TempIndex::new();
+        left.add_json_documents(json!([
+            { "id": 0, "name": "kevin" },
+            { "id": 1, "name": "bob" },
+        ]));
+        let right = TempIndex::new();
+        right.add_json_documents(json!([
+            { "id": 0, "name": "kevina" },
+            { "id": 1, "name": "bob" },
+        ]));
+
+        assert!(index_diff(&left, &left).unwrap().is_empty());
+
+        // the timestamps of the indexes always differ
+        let report = index_diff(&left, &right).unwrap();
+        insta::assert_snapshot!(report, @r###"
+        main: 0 only in left, 0 only in right, 3 different
+        - created-at
+        + created-at
+        - updated-at
+        + updated-at
+        - words-fst
+        + words-fst
+        word_docids: 1 only in left, 1 only in right, 0 different
+        - kevin            [0, ]
+        + kevina           [0, ]
+        docid_word_positions: 1 only in left, 1 only in right, 0 different
+        - 0      kevin            [65536, ]
+        + 0      kevina           [65536, ]
+        word_position_docids: 1 only in left, 1 only in right, 0 different
+        - kevin            65536  [0, ]
+        + kevina           65536  [0, ]
+        documents: 0 only in left, 0 only in right, 1 different
+        - 0    id:0 name:"kevin"
+        + 0    id:0 name:"kevina"
+        "###);
+    }
+}
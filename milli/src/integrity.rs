@@ -0,0 +1,251 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+
+use fst::Streamer;
+use heed::RoTxn;
+use roaring::RoaringBitmap;
+
+use crate::facet::FacetType;
+use crate::heed_codec::facet::FacetGroupKeyCodec;
+use crate::heed_codec::ByteSliceRefCodec;
+use crate::index::db_name;
+use crate::{DocumentId, FieldId, Index, Result};
+
+/// A violation of one of the invariants of an [`Index`], see [`Index::check_integrity`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum IntegrityIssue {
+    /// The word is associated to documents that are neither indexed nor soft-deleted.
+    UnknownDocumentsInWordDocids { database: &'static str, word: String, docids: RoaringBitmap },
+    /// The documents ids faceted under this field differ from the documents found in the
+    /// level 0 of its facet database.
+    FacetedDocumentsMismatch {
+        field_id: FieldId,
+        facet_type: FacetType,
+        /// The documents only present in the faceted documents ids.
+        missing_from_database: RoaringBitmap,
+        /// The documents only present in the facet database.
+        missing_from_faceted_documents_ids: RoaringBitmap,
+    },
+    /// The external id resolves to an internal id that is neither indexed nor soft-deleted.
+    DanglingExternalId { external_id: String, internal_id: DocumentId },
+    /// The word is in the words FST but in none of the word docids databases.
+    WordMissingFromWordDocids { word: String },
+    /// The word is in one of the word docids databases but not in the words FST.
+    WordMissingFromWordsFst { word: String },
+}
+
+impl fmt::Display for IntegrityIssue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::UnknownDocumentsInWordDocids { database, word, docids } => write!(
+                f,
+                "The word `{}` of the `{}` database contains the unknown documents {:?}.",
+                word,
+                database,
+                docids.iter().collect::<Vec<_>>()
+            ),
+            Self::FacetedDocumentsMismatch {
+                field_id,
+                facet_type,
+                missing_from_database,
+                missing_from_faceted_documents_ids,
+            } => write!(
+                f,
+                "The {} faceted documents of the field {} don't match its facet database, \
+                 {:?} are missing from the database and {:?} from the faceted documents ids.",
+                facet_type,
+                field_id,
+                missing_from_database.iter().collect::<Vec<_>>(),
+                missing_from_faceted_documents_ids.iter().collect::<Vec<_>>()
+            ),
+            Self::DanglingExternalId { external_id, internal_id } => write!(
+                f,
+                "The external id `{}` resolves to the unknown document {}.",
+                external_id, internal_id
+            ),
+            Self::WordMissingFromWordDocids { word } => write!(
+                f,
+                "The word `{}` of the words FST is missing from the word docids databases.",
+                word
+            ),
+            Self::WordMissingFromWordsFst { word } => {
+                write!(f, "The word `{}` is missing from the words FST.", word)
+            }
+        }
+    }
+}
+
+/// Verifies the invariants between the databases of the index and returns every violation found.
+pub(crate) fn check_integrity(rtxn: &RoTxn, index: &Index) -> Result<Vec<IntegrityIssue>> {
+    let mut issues = Vec::new();
+
+    let known_documents = index.documents_ids(rtxn)? | index.soft_deleted_documents_ids(rtxn)?;
+
+    // the documents of the word docids databases, and the words they contain
+    let mut words = BTreeSet::new();
+    for (database, db) in [
+        (db_name::WORD_DOCIDS, index.word_docids),
+        (db_name::EXACT_WORD_DOCIDS, index.exact_word_docids),
+    ] {
+        for result in db.iter(rtxn)? {
+            let (word, docids) = result?;
+            let unknown = docids - &known_documents;
+            if !unknown.is_empty() {
+                issues.push(IntegrityIssue::UnknownDocumentsInWordDocids {
+                    database,
+                    word: word.to_string(),
+                    docids: unknown,
+                });
+            }
+            words.insert(word.to_string());
+        }
+    }
+
+    // the words FST must contain exactly the words of both word docids databases
+    let words_fst = index.words_fst(rtxn)?;
+    let mut stream = words_fst.stream();
+    let mut words = words.into_iter().peekable();
+    while let Some(fst_word) = stream.next() {
+        let fst_word = String::from_utf8_lossy(fst_word);
+        while let Some(word) = words.next_if(|word| word.as_str() < fst_word.as_ref()) {
+            issues.push(IntegrityIssue::WordMissingFromWordsFst { word });
+        }
+        if words.next_if(|word| word.as_str() == fst_word.as_ref()).is_none() {
+            issues.push(IntegrityIssue::WordMissingFromWordDocids { word: fst_word.into_owned() });
+        }
+    }
+    issues.extend(words.map(|word| IntegrityIssue::WordMissingFromWordsFst { word }));
+
+    // the faceted documents ids must be the union of the level 0 of the facet databases
+    let faceted_fields_ids = index.faceted_fields_ids(rtxn)?;
+    for facet_type in [FacetType::Number, FacetType::String] {
+        let db = match facet_type {
+            FacetType::Number => {
+                index.facet_id_f64_docids.remap_key_type::<FacetGroupKeyCodec<ByteSliceRefCodec>>()
+            }
+            FacetType::String => index
+                .facet_id_string_docids
+                .remap_key_type::<FacetGroupKeyCodec<ByteSliceRefCodec>>(),
+        };
+
+        let mut level0_docids: BTreeMap<FieldId, RoaringBitmap> =
+            faceted_fields_ids.iter().map(|&field_id| (field_id, RoaringBitmap::new())).collect();
+        for result in db.iter(rtxn)? {
+            let (key, value) = result?;
+            if key.level == 0 {
+                *level0_docids.entry(key.field_id).or_default() |= value.bitmap;
+            }
+        }
+
+        for (field_id, database_docids) in level0_docids {
+            let faceted_docids = index.faceted_documents_ids(rtxn, field_id, facet_type)?;
+            if faceted_docids != database_docids {
+                issues.push(IntegrityIssue::FacetedDocumentsMismatch {
+                    field_id,
+                    facet_type,
+                    missing_from_database: &faceted_docids - &database_docids,
+                    missing_from_faceted_documents_ids: database_docids - faceted_docids,
+                });
+            }
+        }
+    }
+
+    // the external ids must resolve to known documents
+    let mut external_ids: Vec<_> =
+        index.external_documents_ids(rtxn)?.to_hash_map().into_iter().collect();
+    external_ids.sort_unstable();
+    for (external_id, internal_id) in external_ids {
+        if !known_documents.contains(internal_id) {
+            issues.push(IntegrityIssue::DanglingExternalId { external_id, internal_id });
+        }
+    }
+
+    Ok(issues)
+}
+
+#[cfg(test)]
+mod tests {
+    use big_s::S;
+    use maplit::hashset;
+    use roaring::RoaringBitmap;
+
+    use super::IntegrityIssue;
+    use crate::facet::FacetType;
+    use crate::index::tests::TempIndex;
+
+    #[test]
+    fn check_integrity() {
+        let index = TempIndex::new();
+        index
+            .update_settings(|settings| {
+                settings.set_primary_key(S("id"));
+                settings.set_filterable_fields(hashset! { S("price") });
+            })
+            .unwrap();
+        index
+            .add_documents(documents!([
+                { "id": 0, "title": "hello world", "price": 10 },
+                { "id": 1, "title": "hello kitty", "price": 20 },
+                { "id": 2, "title": "bonjour", "price": "cheap" },
+            ]))
+            .unwrap();
+
+        let rtxn = index.read_txn().unwrap();
+        assert_eq!(index.check_integrity(&rtxn).unwrap(), vec![]);
+        let price = index.fields_ids_map(&rtxn).unwrap().id("price").unwrap();
+        drop(rtxn);
+
+        // a word associated to an unknown document and missing from the words FST
+        let mut wtxn = index.write_txn().unwrap();
+        let mut docids = RoaringBitmap::new();
+        docids.insert(0);
+        docids.insert(42);
+        index.word_docids.put(&mut wtxn, "corrupted", &docids).unwrap();
+        wtxn.commit().unwrap();
+
+        let rtxn = index.read_txn().unwrap();
+        let mut unknown = RoaringBitmap::new();
+        unknown.insert(42);
+        assert_eq!(
+            index.check_integrity(&rtxn).unwrap(),
+            vec![
+                IntegrityIssue::UnknownDocumentsInWordDocids {
+                    database: "word-docids",
+                    word: S("corrupted"),
+                    docids: unknown,
+                },
+                IntegrityIssue::WordMissingFromWordsFst { word: S("corrupted") },
+            ]
+        );
+        drop(rtxn);
+
+        // a document missing from the faceted documents ids
+        let mut wtxn = index.write_txn().unwrap();
+        index.word_docids.delete(&mut wtxn, "corrupted").unwrap();
+        let mut docids = index.faceted_documents_ids(&wtxn, price, FacetType::Number).unwrap();
+        docids.remove(1);
+        index.put_faceted_documents_ids(&mut wtxn, price, FacetType::Number, &docids).unwrap();
+        wtxn.commit().unwrap();
+
+        let rtxn = index.read_txn().unwrap();
+        let mut missing = RoaringBitmap::new();
+        missing.insert(1);
+        let issues = index.check_integrity(&rtxn).unwrap();
+        assert_eq!(
+            issues,
+            vec![IntegrityIssue::FacetedDocumentsMismatch {
+                field_id: price,
+                facet_type: FacetType::Number,
+                missing_from_database: RoaringBitmap::new(),
+                missing_from_faceted_documents_ids: missing,
+            }]
+        );
+        assert_eq!(
+            issues[0].to_string(),
+            format!(
+                "The number faceted documents of the field {price} don't match its facet \
+                 database, [] are missing from the database and [1] from the faceted documents ids."
+            )
+        );
+    }
+}
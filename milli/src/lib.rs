@@ -4,47 +4,68 @@ pub mod documents;
 
 mod asc_desc;
 mod criterion;
+mod dump;
 mod error;
 mod external_documents_ids;
 pub mod facet;
 mod fields_ids_map;
 pub mod heed_codec;
 pub mod index;
+mod integrity;
 pub mod proximity;
 mod search;
+mod stemming;
 pub mod update;
 
-#[cfg(test)]
+#[cfg(any(test, feature = "snapshot-tests"))]
 #[macro_use]
 pub mod snapshot_tests;
-
-use std::collections::{BTreeMap, HashMap};
+#[cfg(any(test, feature = "fuzz"))]
+pub mod fuzz;
+#[cfg(any(test, feature = "test-utils"))]
+mod index_diff;
+#[cfg(any(test, feature = "test-utils"))]
+pub mod test_utils;
+
+use std::borrow::Cow;
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::convert::{TryFrom, TryInto};
 use std::hash::BuildHasherDefault;
 
+use charabia::{Language, Script};
 pub use filter_parser::{Condition, FilterCondition, Span, Token};
 use fxhash::{FxHasher32, FxHasher64};
 pub use grenad::CompressionType;
+use serde::{Deserialize, Serialize};
 use serde_json::Value;
 pub use {charabia as tokenizer, heed};
 
 pub use self::asc_desc::{AscDesc, AscDescError, Member, SortError};
 pub use self::criterion::{default_criteria, Criterion, CriterionError};
+pub use self::dump::{import_dump, DumpHeader, DumpSettings, DUMP_FORMAT, DUMP_FORMAT_VERSION};
 pub use self::error::{
-    Error, FieldIdMapMissingEntry, InternalError, SerializationError, UserError,
+    Error, ErrorCode, FieldIdMapMissingEntry, InternalError, InvalidDocumentIdReason,
+    SerializationError, UserError,
 };
 pub use self::external_documents_ids::ExternalDocumentsIds;
-pub use self::fields_ids_map::FieldsIdsMap;
+pub use self::fields_ids_map::{FieldsIdsMap, MAX_FIELDS};
 pub use self::heed_codec::{
     BEU32StrCodec, BoRoaringBitmapCodec, BoRoaringBitmapLenCodec, CboRoaringBitmapCodec,
-    CboRoaringBitmapLenCodec, FieldIdWordCountCodec, ObkvCodec, RoaringBitmapCodec,
-    RoaringBitmapLenCodec, StrBEU32Codec, U8StrStrCodec, UncheckedU8StrStrCodec,
+    CboRoaringBitmapLenCodec, FieldIdBoolCodec, FieldIdStrCodec, FieldIdWordCountCodec, ObkvCodec,
+    RoaringBitmapCodec, RoaringBitmapLenCodec, StrBEU32Codec, U8StrStrCodec,
+    UncheckedU8StrStrCodec,
 };
-pub use self::index::Index;
+pub use self::index::{Index, IndexOpenOptions, IndexReadOnly};
+#[cfg(any(test, feature = "test-utils"))]
+pub use self::index_diff::{index_diff, DatabaseDiff, IndexDiffReport};
+pub use self::integrity::IntegrityIssue;
 pub use self::search::{
-    CriterionImplementationStrategy, FacetDistribution, Filter, FormatOptions, MatchBounds,
-    MatcherBuilder, MatchingWord, MatchingWords, Search, SearchResult, TermsMatchingStrategy,
-    DEFAULT_VALUES_PER_FACET,
+    CandidateMetrics, CriterionImplementationStrategy, DocumentExplanation, EmptyQuery,
+    ExactnessConfig, FacetDistribution, FacetDistributionResult, Filter, FilterClauseExplanation,
+    FormatOptions, HighlightMerging, MatchBounds, MatcherBuilder, MatchingWord, MatchingWords,
+    MissingValues, NumericFacetStats, PhraseOrder, QueryCorrection, QueryTreeNode,
+    QueryWordExplanation, Search, SearchIter, SearchResult, SearchTimings, SortValueExplanation,
+    TermsMatchingStrategy, TieBreaker, WordMatchExplanation, DEFAULT_VALUES_PER_FACET,
 };
 
 pub type Result<T> = std::result::Result<T, error::Error>;
@@ -71,6 +92,14 @@ pub type SmallVec8<T> = smallvec::SmallVec<[T; 8]>;
 /// expressed in term of latitude and longitude.
 pub type GeoPoint = rstar::primitives::GeomWithData<[f64; 3], (DocumentId, [f64; 2])>;
 
+/// A GeoShape is the bounding box of a region expressed in terms of latitude and longitude.
+/// Its metadata is a tuple composed of 1. the DocumentId of the associated document and
+/// 2. the vertices of the polygon delimiting the region, in terms of latitude and longitude.
+pub type GeoShape = rstar::primitives::GeomWithData<
+    rstar::primitives::Rectangle<[f64; 2]>,
+    (DocumentId, Vec<[f64; 2]>),
+>;
+
 /// The maximum length a LMDB key can be.
 ///
 /// Note that the actual allowed length is a little bit higher, but
@@ -86,6 +115,367 @@ pub const MAX_FACET_VALUE_LENGTH: usize = MAX_LMDB_KEY_LENGTH - 20;
 /// The maximum length a word can be
 pub const MAX_WORD_LENGTH: usize = MAX_LMDB_KEY_LENGTH / 2;
 
+/// How the words longer than the maximum word length of the index are handled,
+/// both when indexing the documents and when building the query tree.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum LongWordsPolicy {
+    /// The long words are ignored.
+    #[default]
+    Drop,
+    /// The long words are truncated to the maximum word length.
+    Truncate,
+}
+
+impl LongWordsPolicy {
+    /// Returns the word if it is not longer than `max_word_length` bytes,
+    /// otherwise drops it or truncates it on a char boundary.
+    pub fn apply(self, word: &str, max_word_length: usize) -> Option<&str> {
+        if word.len() <= max_word_length {
+            return Some(word);
+        }
+
+        match self {
+            LongWordsPolicy::Drop => None,
+            LongWordsPolicy::Truncate => {
+                let end = (0..=max_word_length).rev().find(|i| word.is_char_boundary(*i))?;
+                Some(&word[..end]).filter(|word| !word.is_empty())
+            }
+        }
+    }
+}
+
+/// When the stop words of the index are ignored.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum StopWordsMode {
+    /// The stop words are neither indexed nor searched, the documents are reindexed
+    /// when the stop words change.
+    #[default]
+    IndexAndSearch,
+    /// The stop words are indexed but ignored in the queries, except in their phrases.
+    /// The documents are not reindexed when the stop words change.
+    SearchOnly,
+}
+
+/// How the accents of the words are taken into account by the typo tolerance.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum TypoOnDiacritics {
+    /// The accents are ignored, `cafe` matches `café` without any typo.
+    #[default]
+    Free,
+    /// An accent difference counts as a typo, `cafe` matches `café` with one typo when
+    /// the typo tolerance of the word allows it.
+    CountsAsTypo,
+    /// A word with an accent never matches the same word without it, and conversely.
+    NoMatch,
+}
+
+/// How the documents bringing more new fields than the
+/// [`max_fields`](crate::Index::max_fields) of the index allows are handled.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum MaxFieldsPolicy {
+    /// The whole documents addition fails.
+    #[default]
+    Reject,
+    /// The document is ignored and its external id is reported in the
+    /// [`skipped_documents`](crate::update::DocumentAdditionResult::skipped_documents).
+    Skip,
+    /// The document is indexed with the first new fields that fit, the top-level fields in
+    /// the order of the documents batch and then the fields generated by the flattening of
+    /// its nested objects. The dropped top-level fields are not stored, while the nested
+    /// objects are always stored in full, only their dropped fields are not indexed.
+    Truncate,
+}
+
+/// Applies the custom normalization rules of the index to a word already normalized by the
+/// tokenizer, i.e. lowercased and without diacritics. The rules are applied one after the other
+/// in the lexicographic order of their patterns, each of them replacing all its occurrences.
+pub fn apply_normalization_rules<'a>(
+    word: &'a str,
+    rules: &BTreeMap<String, String>,
+) -> Cow<'a, str> {
+    let mut word = Cow::Borrowed(word);
+    for (pattern, replacement) in rules {
+        if word.contains(pattern.as_str()) {
+            word = Cow::Owned(word.replace(pattern.as_str(), replacement));
+        }
+    }
+    word
+}
+
+/// Applies the custom normalization rules of the index to its stop words, the words normalized
+/// by the rules into one of them are stop words too, see [`apply_normalization_rules`].
+///
+/// Returns `None` when there are no stop words or no rules, the tokenizer already
+/// recognizes the stop words then.
+pub(crate) fn normalize_stop_words<A: AsRef<[u8]>>(
+    stop_words: Option<&fst::Set<A>>,
+    rules: &BTreeMap<String, String>,
+) -> Result<Option<fst::Set<Vec<u8>>>> {
+    match stop_words {
+        Some(stop_words) if !rules.is_empty() => {
+            let mut words: Vec<_> = stop_words
+                .stream()
+                .into_strs()?
+                .iter()
+                .map(|word| apply_normalization_rules(word, rules).into_owned())
+                .collect();
+            words.sort_unstable();
+            words.dedup();
+            Ok(Some(fst::Set::from_iter(words)?))
+        }
+        _ => Ok(None),
+    }
+}
+
+/// Formats a number in its canonical form, the shortest representation that parses back
+/// to the same `f64`, without exponent nor trailing zeros: `1`, `1.0` and `1e0` are
+/// all formatted as `1`, and `-0.0` as `0`.
+pub fn canonical_number(number: f64) -> String {
+    if number == 0.0 {
+        String::from("0")
+    } else {
+        number.to_string()
+    }
+}
+
+/// Formats a JSON number in its canonical form: the integers are written exactly and the
+/// other numbers as by [`canonical_number`], unless that form would have to be written with
+/// a lot of zeros (e.g. `1e300`), these are kept in their exponent form.
+pub fn canonical_json_number(number: &serde_json::Number) -> String {
+    if let Some(integer) = number.as_u64() {
+        integer.to_string()
+    } else if let Some(integer) = number.as_i64() {
+        integer.to_string()
+    } else {
+        match number.as_f64() {
+            Some(float) if has_compact_canonical_form(float) => canonical_number(float),
+            _ => number.to_string(),
+        }
+    }
+}
+
+/// Whether the canonical form of a number is written without a lot of leading or trailing zeros.
+fn has_compact_canonical_form(number: f64) -> bool {
+    number == 0.0 || (1e-7..1e21).contains(&number.abs())
+}
+
+/// Returns the [canonical form](canonical_number) of a word that is a finite number,
+/// e.g. `2.50` or `25e-1`, or the word unchanged otherwise.
+///
+/// The integers written with leading zeros, e.g. zip codes, are left unchanged, as are the
+/// words that can't be represented exactly: the integers that don't fit in 64 bits, the
+/// numbers with more significant digits than a `f64` holds and the numbers whose canonical
+/// form would be written with a lot of zeros, e.g. `1e300`.
+pub fn canonicalize_numeric_word(word: &str) -> Cow<str> {
+    let unsigned = word.strip_prefix(|c| c == '-' || c == '+').unwrap_or(word);
+    let bytes = unsigned.as_bytes();
+    let is_number_like = bytes.first().map_or(false, |b| b.is_ascii_digit() || *b == b'.')
+        && bytes.iter().all(|b| matches!(b, b'0'..=b'9' | b'.' | b'-' | b'+' | b'e' | b'E'));
+    let leading_zeros = bytes.len() > 1 && bytes[0] == b'0' && bytes[1].is_ascii_digit();
+    if !is_number_like || leading_zeros {
+        return Cow::Borrowed(word);
+    }
+
+    let canonical = if bytes.iter().all(u8::is_ascii_digit) {
+        match (word.parse::<u64>(), word.parse::<i64>()) {
+            (Ok(integer), _) => integer.to_string(),
+            (_, Ok(integer)) => integer.to_string(),
+            _ => return Cow::Borrowed(word),
+        }
+    } else {
+        // a f64 only holds the numbers with up to 15 significant digits exactly.
+        let mantissa = unsigned.split(|c| c == 'e' || c == 'E').next().unwrap_or_default();
+        let digits = mantissa.trim_matches(|c| c == '0' || c == '.').replace('.', "");
+        if digits.len() > 15 {
+            return Cow::Borrowed(word);
+        }
+        match word.parse::<f64>() {
+            Ok(number) if number.is_finite() && has_compact_canonical_form(number) => {
+                canonical_number(number)
+            }
+            _ => return Cow::Borrowed(word),
+        }
+    };
+
+    if canonical == word {
+        Cow::Borrowed(word)
+    } else {
+        Cow::Owned(canonical)
+    }
+}
+
+/// The first char of the case-preserved forms of the words, it keeps them
+/// apart from the normalized words in the word databases.
+pub const CASE_SENSITIVE_WORD_PREFIX: char = '\u{1}';
+
+/// Returns the case-preserved form of a word as written in the text, [canonicalized] if numeric.
+///
+/// This form is indexed along with the normalized word for the case-sensitive attributes
+/// and is the one searched by the case-sensitive searches.
+///
+/// [canonicalized]: canonicalize_numeric_word
+pub fn case_sensitive_word(original: &str) -> String {
+    let word = canonicalize_numeric_word(original.trim());
+    format!("{CASE_SENSITIVE_WORD_PREFIX}{word}")
+}
+
+/// The first char of the diacritics-preserved forms of the words, it keeps them apart from
+/// the normalized words in the word databases.
+pub const DIACRITICS_WORD_PREFIX: char = '\u{2}';
+
+/// Returns the diacritics-preserved form of a word as written in the text: lowercased,
+/// [canonicalized] if numeric and with the normalization `rules` applied, `None` in the
+/// [`TypoOnDiacritics::Free`] mode.
+///
+/// This form is indexed along with the normalized `lemma` of the word and is the one searched
+/// in the other modes. In the [`TypoOnDiacritics::NoMatch`] mode, each char differing from the
+/// lemma is followed by two [`DIACRITICS_WORD_PREFIX`], adding or removing an accent then
+/// costing three typos, more than ever allowed. The words whose lemma has another number of
+/// chars, e.g. `cœur` normalized as `coeur`, are followed by three of them instead.
+///
+/// [canonicalized]: canonicalize_numeric_word
+pub fn diacritics_word(
+    original: &str,
+    lemma: &str,
+    mode: TypoOnDiacritics,
+    rules: &BTreeMap<String, String>,
+) -> Option<String> {
+    let word = canonicalize_numeric_word(original.trim()).to_lowercase();
+    let word = apply_normalization_rules(&word, rules);
+    let lemma = canonicalize_numeric_word(lemma.trim());
+    let lemma = apply_normalization_rules(&lemma, rules);
+    let mut form = String::from(DIACRITICS_WORD_PREFIX);
+    match mode {
+        TypoOnDiacritics::Free => return None,
+        TypoOnDiacritics::NoMatch if word.chars().count() == lemma.chars().count() => {
+            for (c, l) in word.chars().zip(lemma.chars()) {
+                form.push(c);
+                if c != l {
+                    form.extend([DIACRITICS_WORD_PREFIX; 2]);
+                }
+            }
+        }
+        TypoOnDiacritics::NoMatch => {
+            form.push_str(&word);
+            form.extend([DIACRITICS_WORD_PREFIX; 3]);
+        }
+        TypoOnDiacritics::CountsAsTypo => form.push_str(&word),
+    }
+    Some(form)
+}
+
+/// The first char of the stems of the words, it keeps them apart from the words
+/// in the word databases: a word is not an exact match of the words it is the stem of.
+pub const STEM_WORD_PREFIX: char = '\u{3}';
+
+/// Returns the form of a stem indexed along with the words it is the stem
+/// of, and searched along with the words of the query it is the stem of.
+pub fn stem_word(stem: &str) -> String {
+    format!("{STEM_WORD_PREFIX}{stem}")
+}
+
+/// Returns the prefix of the case-preserved and diacritics-preserved forms and of the stems
+/// of the words.
+pub(crate) fn preserved_form_prefix(word: &str) -> Option<char> {
+    word.chars().next().filter(|c| {
+        matches!(*c, CASE_SENSITIVE_WORD_PREFIX | DIACRITICS_WORD_PREFIX | STEM_WORD_PREFIX)
+    })
+}
+
+/// Returns the word as written, without the prefix and the padding of its preserved forms.
+pub(crate) fn unprefixed_word(word: &str) -> Cow<str> {
+    match preserved_form_prefix(word) {
+        Some(_) => Cow::Owned(word[1..].chars().filter(|c| *c != DIACRITICS_WORD_PREFIX).collect()),
+        None => Cow::Borrowed(word),
+    }
+}
+
+/// Returns the normalized form of a facet string, the one stored in the level zero keys of
+/// the `facet_id_string_docids` database and looked up by the equality filters: trimmed,
+/// [canonicalized](canonicalize_numeric_word) if numeric, lowercased and truncated to fit
+/// in an LMDB key.
+pub fn normalize_facet_string(value: &str) -> String {
+    let normalized = canonicalize_numeric_word(value.trim()).to_lowercase();
+    if normalized.len() + 4 < MAX_FACET_VALUE_LENGTH {
+        normalized
+    } else {
+        normalized
+            .char_indices()
+            .take_while(|(idx, _)| idx + 4 < MAX_FACET_VALUE_LENGTH)
+            .map(|(_, c)| c)
+            .collect()
+    }
+}
+
+/// Returns the script and the tokenizer language corresponding to an
+/// ISO 639-3 language code, e.g. `jpn`, or `None` if it is not supported.
+pub fn script_language_from_code(code: &str) -> Option<(Script, Language)> {
+    let script_language = match code {
+        "ara" => (Script::Arabic, Language::Ara),
+        "cmn" => (Script::Cj, Language::Cmn),
+        "dan" => (Script::Latin, Language::Dan),
+        "deu" => (Script::Latin, Language::Deu),
+        "ell" => (Script::Greek, Language::Ell),
+        "eng" => (Script::Latin, Language::Eng),
+        "fra" => (Script::Latin, Language::Fra),
+        "heb" => (Script::Hebrew, Language::Heb),
+        "hin" => (Script::Devanagari, Language::Hin),
+        "ita" => (Script::Latin, Language::Ita),
+        "jpn" => (Script::Cj, Language::Jpn),
+        "kor" => (Script::Hangul, Language::Kor),
+        "nld" => (Script::Latin, Language::Nld),
+        "pol" => (Script::Latin, Language::Pol),
+        "por" => (Script::Latin, Language::Por),
+        "rus" => (Script::Cyrillic, Language::Rus),
+        "spa" => (Script::Latin, Language::Spa),
+        "swe" => (Script::Latin, Language::Swe),
+        "tha" => (Script::Thai, Language::Tha),
+        "tur" => (Script::Latin, Language::Tur),
+        "ukr" => (Script::Cyrillic, Language::Ukr),
+        "vie" => (Script::Latin, Language::Vie),
+        _ => return None,
+    };
+    Some(script_language)
+}
+
+/// The scripts of the languages supported by [`script_language_from_code`].
+const DETECTED_SCRIPTS: [Script; 9] = [
+    Script::Arabic,
+    Script::Cj,
+    Script::Cyrillic,
+    Script::Devanagari,
+    Script::Greek,
+    Script::Hangul,
+    Script::Hebrew,
+    Script::Latin,
+    Script::Thai,
+];
+
+/// Returns the allow list restricting the language detection of the tokenizer to the
+/// languages of the given ISO 639-3 codes, the unsupported codes are ignored.
+///
+/// No language is detected in the scripts of none of these languages, their text is
+/// segmented without any language specific segmenter. All the languages are detected
+/// when there are no codes, the allow list is then empty.
+pub(crate) fn detected_languages_allow_list(codes: &[String]) -> HashMap<Script, Vec<Language>> {
+    if codes.is_empty() {
+        return HashMap::new();
+    }
+
+    let mut allow_list: HashMap<_, _> =
+        DETECTED_SCRIPTS.iter().map(|script| (*script, Vec::new())).collect();
+    for (script, language) in codes.iter().filter_map(|code| script_language_from_code(code)) {
+        let languages = allow_list.entry(script).or_default();
+        if !languages.contains(&language) {
+            languages.push(language);
+        }
+    }
+    allow_list
+}
+
 pub const MAX_POSITION_PER_ATTRIBUTE: u32 = u16::MAX as u32 + 1;
 
 // Convert an absolute word position into a relative position.
@@ -100,6 +490,29 @@ pub fn absolute_from_relative_position(field_id: FieldId, relative: RelativePosi
     (field_id as u32) << 16 | (relative as u32)
 }
 
+/// The relative positions below this one are stored exactly in the position databases.
+pub const EXACT_POSITIONS_BUCKETS: RelativePosition = 16;
+
+/// Computes the bucket under which a relative position is stored in the `word_position_docids`
+/// and `word_prefix_position_docids` databases. The first positions are kept exact, the following
+/// ones are grouped in buckets twice as wide as the previous one, identified by their first position.
+///
+/// The indexes created by older versions store the exact positions, see
+/// [`Index::bucketed_positions`].
+pub fn bucketed_position(relative: RelativePosition) -> RelativePosition {
+    if relative < EXACT_POSITIONS_BUCKETS {
+        relative
+    } else {
+        1 << (15 - relative.leading_zeros())
+    }
+}
+
+/// Computes the bucket of an absolute word position, the field id of the attribute is kept.
+pub fn bucketed_absolute_position(absolute: Position) -> Position {
+    let (field_id, relative) = relative_from_absolute_position(absolute);
+    absolute_from_relative_position(field_id, bucketed_position(relative))
+}
+
 /// Transform a raw obkv store into a JSON Object.
 pub fn obkv_to_json(
     displayed_fields: &[FieldId],
@@ -121,6 +534,67 @@ pub fn obkv_to_json(
         .collect()
 }
 
+/// Transform a raw obkv store into its flattened fields, named like the fields created when
+/// the document was indexed, e.g. `{ "a": { "b": 1 } }` gives `[("a.b", 1)]`.
+///
+/// The fields are returned in the order of the displayed fields. The nested values are
+/// flattened like during indexing, the keys of the nested objects are skipped along with
+/// the flattened fields missing from the `fields_ids_map`, which were not indexed.
+pub fn obkv_to_flattened(
+    displayed_fields: &[FieldId],
+    fields_ids_map: &FieldsIdsMap,
+    obkv: obkv::KvReaderU16,
+) -> Result<Vec<(String, Value)>> {
+    let mut fields = Vec::new();
+    for (id, bytes) in displayed_fields.iter().copied().flat_map(|id| obkv.get(id).map(|v| (id, v)))
+    {
+        let name = fields_ids_map.name(id).ok_or(error::FieldIdMapMissingEntry::FieldId {
+            field_id: id,
+            process: "obkv_to_flattened",
+        })?;
+        let value: Value =
+            serde_json::from_slice(bytes).map_err(error::InternalError::SerdeJson)?;
+        if !json_depth_checker::should_flatten_from_unchecked_slice(bytes) {
+            fields.push((name.to_owned(), value));
+            continue;
+        }
+
+        // the flattening gives an empty array to the keys of the nested objects.
+        let mut objects_keys = HashSet::new();
+        insert_objects_keys(name, &value, &mut objects_keys);
+
+        let mut object = Object::new();
+        object.insert(name.to_owned(), value);
+        let flattened = flatten_serde_json::flatten(&object).into_iter().filter(|(key, value)| {
+            let is_object_key =
+                value.as_array().map_or(false, Vec::is_empty) && objects_keys.contains(key);
+            !is_object_key && fields_ids_map.id(key).is_some()
+        });
+        fields.extend(flattened);
+    }
+
+    Ok(fields)
+}
+
+/// Inserts the flattened keys of the objects found in the value, the objects nested
+/// in an array are flattened under the key of the array.
+fn insert_objects_keys(key: &str, value: &Value, objects_keys: &mut HashSet<String>) {
+    match value {
+        Value::Object(object) => {
+            objects_keys.insert(key.to_owned());
+            for (name, value) in object {
+                insert_objects_keys(&format!("{key}.{name}"), value, objects_keys);
+            }
+        }
+        Value::Array(values) => {
+            for value in values {
+                insert_objects_keys(key, value, objects_keys);
+            }
+        }
+        _ => (),
+    }
+}
+
 /// Transform every field of a raw obkv store into a JSON Object.
 pub fn all_obkv_to_json(obkv: obkv::KvReaderU16, fields_ids_map: &FieldsIdsMap) -> Result<Object> {
     let all_keys = obkv.iter().map(|(k, _v)| k).collect::<Vec<_>>();
@@ -134,7 +608,7 @@ pub fn json_to_string(value: &Value) -> Option<String> {
         match value {
             Value::Null => false,
             Value::Bool(boolean) => write!(output, "{}", boolean).is_ok(),
-            Value::Number(number) => write!(output, "{}", number).is_ok(),
+            Value::Number(number) => write!(output, "{}", canonical_json_number(number)).is_ok(),
             Value::String(string) => write!(output, "{}", string).is_ok(),
             Value::Array(array) => {
                 let mut count = 0;
@@ -204,6 +678,50 @@ pub fn distance_between_two_points(a: &[f64; 2], b: &[f64; 2]) -> f64 {
     a.haversine_distance_to(&b).meters()
 }
 
+/// Return the distance in meters between a point and the region delimited by a polygon,
+/// `0` when the point is inside it. The vertices are expressed in terms of latitude and
+/// longitude and the edges of the polygon are straight lines between them in this plane.
+pub fn distance_between_point_and_polygon(point: &[f64; 2], polygon: &[[f64; 2]]) -> f64 {
+    let edges = polygon.iter().zip(polygon.iter().cycle().skip(1));
+
+    // the ray casting algorithm counts the edges crossed by a ray going east from the point.
+    let mut inside = false;
+    for (a, b) in edges.clone() {
+        if (a[0] > point[0]) != (b[0] > point[0]) {
+            let crossing_lng = a[1] + (point[0] - a[0]) / (b[0] - a[0]) * (b[1] - a[1]);
+            if point[1] < crossing_lng {
+                inside = !inside;
+            }
+        }
+    }
+    if inside {
+        return 0.0;
+    }
+
+    edges
+        .map(|(a, b)| distance_between_point_and_segment(point, a, b))
+        .fold(f64::INFINITY, f64::min)
+}
+
+/// Return the distance in meters between a point and the closest point of a segment,
+/// found with a ternary search along the segment.
+fn distance_between_point_and_segment(point: &[f64; 2], a: &[f64; 2], b: &[f64; 2]) -> f64 {
+    let along = |t: f64| [a[0] + t * (b[0] - a[0]), a[1] + t * (b[1] - a[1])];
+    let (mut low, mut high) = (0.0, 1.0);
+    for _ in 0..64 {
+        let left = low + (high - low) / 3.0;
+        let right = high - (high - low) / 3.0;
+        if distance_between_two_points(point, &along(left))
+            <= distance_between_two_points(point, &along(right))
+        {
+            high = right;
+        } else {
+            low = left;
+        }
+    }
+    distance_between_two_points(point, &along((low + high) / 2.0))
+}
+
 /// Convert a point expressed in terms of latitude and longitude to a point in the
 /// cartesian coordinate expressed in terms of x, y and z.
 pub fn lat_lng_to_xyz(coord: &[f64; 2]) -> [f64; 3] {
@@ -222,6 +740,9 @@ pub fn is_faceted(field: &str, faceted_fields: impl IntoIterator<Item = impl AsR
 }
 
 /// Returns `true` if the field match the facet.
+///
+/// A `*` segment of the facet matches any segment of the field, e.g. `author.*` matches
+/// `author.age` and `author.name.first`, and `*.age` matches `author.age`.
 /// ```
 /// use milli::is_faceted_by;
 /// // -- the valid basics
@@ -246,10 +767,26 @@ pub fn is_faceted(field: &str, faceted_fields: impl IntoIterator<Item = impl AsR
 /// assert!(!is_faceted_by("animaux.chien", "animaux.ch"));
 /// assert!(!is_faceted_by("animaux.chien", "animaux.chi"));
 /// assert!(!is_faceted_by("animaux.chien", "animaux.chie"));
+///
+/// // -- the wildcards
+/// assert!(is_faceted_by("animaux.chien", "animaux.*"));
+/// assert!(is_faceted_by("animaux.chien.race", "animaux.*"));
+/// assert!(is_faceted_by("animaux.chien.race", "*.chien"));
+/// assert!(is_faceted_by("animaux.chien.race", "animaux.*.race"));
+/// assert!(!is_faceted_by("animaux", "animaux.*"));
+/// assert!(!is_faceted_by("animaux.chat.race", "*.chien"));
 /// ```
 pub fn is_faceted_by(field: &str, facet: &str) -> bool {
-    field.starts_with(facet)
-        && field[facet.len()..].chars().next().map(|c| c == '.').unwrap_or(true)
+    if !facet.contains('*') {
+        return field.starts_with(facet)
+            && field[facet.len()..].chars().next().map(|c| c == '.').unwrap_or(true);
+    }
+
+    let mut field_segments = field.split('.');
+    facet.split('.').all(|facet_segment| match field_segments.next() {
+        Some(field_segment) => facet_segment == "*" || facet_segment == field_segment,
+        None => false,
+    })
 }
 
 #[cfg(test)]
@@ -308,6 +845,19 @@ mod tests {
         assert_eq!(0xFFFFFFFF, absolute_from_relative_position(0xFFFF, 0xFFFF));
     }
 
+    #[test]
+    fn test_bucketed_position() {
+        assert_eq!(0, bucketed_position(0));
+        assert_eq!(15, bucketed_position(15));
+        assert_eq!(16, bucketed_position(16));
+        assert_eq!(16, bucketed_position(31));
+        assert_eq!(32, bucketed_position(32));
+        assert_eq!(256, bucketed_position(300));
+        assert_eq!(0x8000, bucketed_position(0xFFFF));
+        assert_eq!(0x00050010, bucketed_absolute_position(0x00050017));
+        assert_eq!(0x0005000F, bucketed_absolute_position(0x0005000F));
+    }
+
     #[test]
     fn test_all_obkv_to_json() {
         let mut fields_ids_map = FieldsIdsMap::new();
@@ -329,4 +879,54 @@ mod tests {
 
         assert_eq!(&actual, expected);
     }
+
+    #[test]
+    fn test_canonical_numbers() {
+        assert_eq!(canonical_number(1.0), "1");
+        assert_eq!(canonical_number(-0.0), "0");
+        assert_eq!(canonical_number(2.5), "2.5");
+        assert_eq!(canonical_number(0.1 + 0.2), "0.30000000000000004");
+        assert_eq!(canonical_number(1e21), "1000000000000000000000");
+
+        // integers, floats and exponent notations
+        assert_eq!(canonicalize_numeric_word("42"), "42");
+        assert_eq!(canonicalize_numeric_word("1.0"), "1");
+        assert_eq!(canonicalize_numeric_word("1e0"), "1");
+        assert_eq!(canonicalize_numeric_word("1E0"), "1");
+        assert_eq!(canonicalize_numeric_word("25e-1"), "2.5");
+        assert_eq!(canonicalize_numeric_word("2.50"), "2.5");
+        assert_eq!(canonicalize_numeric_word("0.0"), "0");
+        assert_eq!(canonicalize_numeric_word(".5"), "0.5");
+        assert_eq!(canonicalize_numeric_word("-0.0"), "0");
+        assert_eq!(canonicalize_numeric_word("-1.50"), "-1.5");
+
+        // the other words are left unchanged
+        assert_eq!(canonicalize_numeric_word("007"), "007");
+        assert_eq!(canonicalize_numeric_word("1e400"), "1e400");
+        assert_eq!(canonicalize_numeric_word("e10"), "e10");
+        assert_eq!(canonicalize_numeric_word("1.2.3"), "1.2.3");
+        assert_eq!(canonicalize_numeric_word("hello"), "hello");
+        assert!(matches!(canonicalize_numeric_word("42"), Cow::Borrowed(_)));
+
+        // the numbers that can't be represented exactly are left unchanged too
+        assert_eq!(canonicalize_numeric_word("18446744073709551615"), "18446744073709551615");
+        assert_eq!(canonicalize_numeric_word("-9223372036854775808"), "-9223372036854775808");
+        assert_eq!(
+            canonicalize_numeric_word("123456789012345678901234567890"),
+            "123456789012345678901234567890"
+        );
+        assert_eq!(canonicalize_numeric_word("9007199254740993.0"), "9007199254740993.0");
+        assert_eq!(canonicalize_numeric_word("1e300"), "1e300");
+        assert_eq!(canonicalize_numeric_word("1e-300"), "1e-300");
+
+        let value = json!([1, 1.0, -0.0, 1e3, 2.5]);
+        assert_eq!(json_to_string(&value).unwrap(), "1. 1. 0. 1000. 2.5. ");
+
+        // the integers are written exactly, even above 2^53
+        let value = json!([9007199254740993_u64, u64::MAX, i64::MIN, 1e300]);
+        assert_eq!(
+            json_to_string(&value).unwrap(),
+            "9007199254740993. 18446744073709551615. -9223372036854775808. 1e300. "
+        );
+    }
 }
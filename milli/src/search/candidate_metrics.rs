@@ -0,0 +1,243 @@
+use roaring::RoaringBitmap;
+use serde::Serialize;
+
+use super::criteria::{attribute_rank, CriteriaBuilder};
+use super::explain::{explain_operation, explain_query};
+use super::query_tree::{Operation, PrimitiveQueryPart, Query, QueryKind};
+use super::{Search, SearchResult};
+use crate::proximity::positions_proximity;
+use crate::{DocumentId, Result};
+
+/// The raw metrics computed for a candidate of the search, that can be combined to re-rank
+/// the candidates with a custom scorer, see [`Search::execute_with_metrics`].
+///
+/// The metrics are computed on the words of the query as written by the user, the synonyms
+/// and the words concatenations are not taken into account.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CandidateMetrics {
+    pub document_id: DocumentId,
+    /// The total number of typos of the query words found in the document,
+    /// each word being counted with its match with the fewest typos.
+    pub typos: u32,
+    /// The sum of the proximities between the consecutive query words found in
+    /// the document, a lower proximity meaning that the words are closer.
+    pub proximity: u32,
+    /// The number of query words found in the document.
+    pub matched_words: u32,
+    /// The rank given to the document by the attribute criterion, a lower rank being better,
+    /// or `None` if the document doesn't contain all the words of the query.
+    pub attribute_rank: Option<u64>,
+    /// The number of query words found in the document without any typo nor prefix.
+    pub exactness: u32,
+}
+
+impl<'a> Search<'a> {
+    /// Executes the search, fetching `overfetch` times more documents than the limit,
+    /// and returns the metrics of each of the candidates in the order they were ranked.
+    pub fn execute_with_metrics(&self, overfetch: usize) -> Result<Vec<CandidateMetrics>> {
+        let mut search = self.derive();
+        search.limit = self.limit.saturating_mul(overfetch.max(1));
+        let SearchResult { documents_ids, .. } = search.execute()?;
+
+        let (query_tree, primitive_query) = match self.build_query_tree()? {
+            Some((query_tree, primitive_query, _)) => (query_tree, primitive_query),
+            None => {
+                return Ok(documents_ids
+                    .into_iter()
+                    .map(|document_id| CandidateMetrics {
+                        document_id,
+                        typos: 0,
+                        proximity: 0,
+                        matched_words: 0,
+                        attribute_rank: None,
+                        exactness: 0,
+                    })
+                    .collect())
+            }
+        };
+
+        let ctx = CriteriaBuilder::new(self.rtxn, self.index)?;
+        let fields_ids_map = self.index.fields_ids_map(self.rtxn)?;
+        let exact_attributes = self.index.exact_attributes_ids(self.rtxn)?;
+
+        let mut metrics = Vec::with_capacity(documents_ids.len());
+        for document_id in documents_ids {
+            let document_words = self.document_words(document_id)?;
+
+            let mut typos = 0;
+            let mut matched_words = 0;
+            let mut exactness = 0;
+            // the positions of each query word found in the document, in the query order.
+            let mut matched_positions: Vec<RoaringBitmap> = Vec::new();
+            for part in &primitive_query {
+                match part {
+                    PrimitiveQueryPart::Word(word, is_prefix, _) => {
+                        let query = find_query(&query_tree, word).cloned().unwrap_or(Query {
+                            prefix: *is_prefix,
+                            kind: QueryKind::exact(word.clone()),
+                        });
+                        let matched = explain_query(
+                            &query,
+                            &document_words,
+                            &exact_attributes,
+                            &fields_ids_map,
+                        );
+                        let matches = &matched.explanation.matches;
+                        if let Some(min_typo) = matches.iter().map(|m| m.typo).min() {
+                            typos += min_typo as u32;
+                            matched_words += 1;
+                            if matches.iter().any(|m| m.typo == 0 && &m.word == word) {
+                                exactness += 1;
+                            }
+                            matched_positions.push(matched.positions);
+                        }
+                    }
+                    PrimitiveQueryPart::Phrase(words, slop) => {
+                        let mut phrase_words = Vec::new();
+                        let matches = explain_operation(
+                            &Operation::Phrase(words.clone(), *slop),
+                            &document_words,
+                            &exact_attributes,
+                            &fields_ids_map,
+                            &mut phrase_words,
+                        );
+                        if matches {
+                            let len = words.iter().flatten().count() as u32;
+                            matched_words += len;
+                            exactness += len;
+                            // the phrase is positioned by its first word.
+                            if let Some(first) = phrase_words.into_iter().next() {
+                                matched_positions.push(first.positions);
+                            }
+                        }
+                    }
+                }
+            }
+
+            let proximity = matched_positions
+                .windows(2)
+                .map(|pair| min_positions_proximity(&pair[0], &pair[1]))
+                .sum();
+
+            metrics.push(CandidateMetrics {
+                document_id,
+                typos,
+                proximity,
+                matched_words,
+                attribute_rank: attribute_rank(&ctx, &query_tree, document_id)?,
+                exactness,
+            });
+        }
+
+        Ok(metrics)
+    }
+}
+
+/// Returns the minimum proximity between the positions of two consecutive query words.
+///
+/// The positions are merged in a single pass, the closest positions of the two words
+/// are always next to each other once merged.
+fn min_positions_proximity(lhs: &RoaringBitmap, rhs: &RoaringBitmap) -> u32 {
+    let mut lhs = lhs.iter().peekable();
+    let mut rhs = rhs.iter().peekable();
+    let mut min_proximity = u32::max_value();
+    // the last merged position and whether it is a position of the left word.
+    let mut last = None;
+    loop {
+        let (position, is_lhs) = match (lhs.peek(), rhs.peek()) {
+            (Some(l), Some(r)) if l <= r => (*l, true),
+            (_, Some(r)) => (*r, false),
+            (Some(l), None) => (*l, true),
+            (None, None) => break,
+        };
+        if is_lhs {
+            lhs.next();
+        } else {
+            rhs.next();
+        }
+
+        if let Some((last_position, last_is_lhs)) = last {
+            if last_is_lhs != is_lhs {
+                let proximity = if is_lhs {
+                    positions_proximity(position, last_position)
+                } else {
+                    positions_proximity(last_position, position)
+                };
+                min_proximity = min_proximity.min(proximity);
+            }
+        }
+        last = Some((position, is_lhs));
+    }
+
+    min_proximity
+}
+
+/// Finds the query of the query tree that corresponds to this word of the user query.
+fn find_query<'t>(operation: &'t Operation, word: &str) -> Option<&'t Query> {
+    match operation {
+        Operation::And(ops) | Operation::Or(_, ops) => {
+            ops.iter().find_map(|op| find_query(op, word))
+        }
+        Operation::Query(query) if query.kind.word() == word => Some(query),
+        Operation::Query(_) | Operation::Phrase(..) => None,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::index::tests::TempIndex;
+
+    #[test]
+    fn candidates_metrics() {
+        let index = TempIndex::new();
+        index
+            .add_documents(documents!([
+                { "id": 0, "text": "hello world" },
+                { "id": 1, "text": "hello big world" },
+                { "id": 2, "text": "hallo world" },
+                { "id": 3, "text": "goodbye" },
+            ]))
+            .unwrap();
+
+        let rtxn = index.read_txn().unwrap();
+        let mut search = Search::new(&rtxn, &index);
+        search.query("hello world");
+        search.limit(1);
+        let metrics = search.execute_with_metrics(3).unwrap();
+
+        let documents_ids: Vec<_> = metrics.iter().map(|m| m.document_id).collect();
+        assert_eq!(documents_ids, vec![0, 1, 2]);
+
+        let summary: Vec<_> =
+            metrics.iter().map(|m| (m.typos, m.proximity, m.matched_words, m.exactness)).collect();
+        assert_eq!(summary, vec![(0, 1, 2, 2), (0, 2, 2, 2), (1, 1, 2, 1)]);
+
+        let ranks: Vec<_> = metrics.iter().map(|m| m.attribute_rank.unwrap()).collect();
+        assert!(ranks[0] < ranks[1]);
+    }
+
+    #[test]
+    fn merged_positions_proximity() {
+        let positions = |positions: &[u32]| positions.iter().copied().collect::<RoaringBitmap>();
+        let brute_force = |lhs: &RoaringBitmap, rhs: &RoaringBitmap| {
+            lhs.iter()
+                .flat_map(|l| rhs.iter().map(move |r| positions_proximity(l, r)))
+                .min()
+                .unwrap_or(u32::max_value())
+        };
+
+        let cases = [
+            (positions(&[0, 10, 20]), positions(&[5, 11, 30])),
+            (positions(&[4, 9]), positions(&[3, 8])),
+            (positions(&[7]), positions(&[7, 100])),
+            (positions(&[1, 2, 3]), positions(&[65536, 65537])),
+            (positions(&[2]), positions(&[])),
+        ];
+        for (lhs, rhs) in cases {
+            assert_eq!(min_positions_proximity(&lhs, &rhs), brute_force(&lhs, &rhs));
+        }
+        assert_eq!(min_positions_proximity(&positions(&[4, 9]), &positions(&[3, 8])), 2);
+    }
+}
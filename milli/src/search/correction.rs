@@ -0,0 +1,221 @@
+use std::cmp::Reverse;
+
+use charabia::{TokenKind, TokenizerBuilder};
+use fst::automaton::Str;
+use fst::{Automaton, IntoStreamer, Streamer};
+
+use super::query_tree::{typos, QueryKind, TypoConfig};
+use super::{word_derivations, Search, WordDerivationsCache};
+use crate::{apply_normalization_rules, canonicalize_numeric_word, Result};
+
+/// A corrected version of a search query, see [`Search::suggest_correction`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QueryCorrection {
+    /// The query in which the misspelled words have been replaced by their correction.
+    pub query: String,
+    /// Whether searching for the corrected query returns at least one document.
+    pub has_candidates: bool,
+}
+
+impl<'a> Search<'a> {
+    /// Suggests a corrected version of the query, in which every word that is not part of the
+    /// words dictionary is replaced by the nearest indexed word. The nearest word is the one with
+    /// the lowest number of typos and then the one contained in the highest number of documents.
+    ///
+    /// The words are corrected following the same rules as the search: the exact words and the
+    /// words shorter than the minimum word length for typos are never corrected, and nothing is
+    /// corrected if typos are not authorized. The last word of the query is considered as a
+    /// prefix and is only corrected if no indexed word starts with it.
+    ///
+    /// Returns `None` if there is no query or if none of its words could be corrected.
+    pub fn suggest_correction(&self) -> Result<Option<QueryCorrection>> {
+        let query = match self.query.as_ref() {
+            Some(query) => query,
+            None => return Ok(None),
+        };
+
+        if !self.is_typo_authorized()? {
+            return Ok(None);
+        }
+
+        let words_fst = self.index.words_fst(self.rtxn)?;
+        let exact_words = self.index.exact_words(self.rtxn)?;
+        let typo_config = TypoConfig {
+            max_typos: 2,
+            word_len_one_typo: self.index.min_word_len_one_typo(self.rtxn)?,
+            word_len_two_typo: self.index.min_word_len_two_typos(self.rtxn)?,
+            exact_words: exact_words.as_ref(),
+        };
+
+        let mut tokbuilder = TokenizerBuilder::new();
+        if let Some(tokenizer_config) = &self.tokenizer_config {
+            tokenizer_config.configure(&mut tokbuilder);
+        }
+        let stop_words = self.index.stop_words(self.rtxn)?;
+        if let Some(ref stop_words) = stop_words {
+            tokbuilder.stop_words(stop_words);
+        }
+        let allow_list = self.index.query_allow_list(self.rtxn)?;
+        if !allow_list.is_empty() {
+            tokbuilder.allow_list(&allow_list);
+        }
+        let tokenizer = tokbuilder.build();
+        let tokens: Vec<_> = tokenizer.tokenize(query).collect();
+        let normalization_rules = self.index.normalization_rules(self.rtxn)?;
+
+        let mut cache = WordDerivationsCache::new();
+        let mut corrected_query = String::with_capacity(query.len());
+        let mut last_byte_end = 0;
+        let mut corrected = false;
+        for (i, token) in tokens.iter().enumerate() {
+            if token.kind != TokenKind::Word {
+                continue;
+            }
+
+            let word = canonicalize_numeric_word(token.lemma());
+            let word = apply_normalization_rules(&word, &normalization_rules);
+            let word = word.as_ref();
+            let is_prefix = i + 1 == tokens.len();
+            if words_fst.contains(word)
+                || (is_prefix
+                    && words_fst
+                        .search(Str::new(word).starts_with())
+                        .into_stream()
+                        .next()
+                        .is_some())
+            {
+                continue;
+            }
+
+            let max_typo = match typos(word.to_string(), true, typo_config.clone()) {
+                QueryKind::Tolerant { typo, .. } => typo,
+                QueryKind::Exact { .. } => continue,
+            };
+
+            let mut best = None;
+            for (derived_word, typo) in
+                word_derivations(word, false, max_typo, &words_fst, &mut cache)?
+            {
+                let count = self.index.word_documents_count_upper_bound(self.rtxn, derived_word)?;
+                let key = (*typo, Reverse(count), derived_word);
+                if best.as_ref().map_or(true, |best| key < *best) {
+                    best = Some(key);
+                }
+            }
+
+            if let Some((_, _, correction)) = best {
+                corrected_query.push_str(&query[last_byte_end..token.byte_start]);
+                corrected_query.push_str(correction);
+                last_byte_end = token.byte_end;
+                corrected = true;
+            }
+        }
+
+        if !corrected {
+            return Ok(None);
+        }
+        corrected_query.push_str(&query[last_byte_end..]);
+
+        let mut search = self.derive();
+        search.query = Some(corrected_query.clone());
+        search.offset = 0;
+        search.limit = 1;
+        search.sort_criteria = None;
+        search.exhaustive_number_hits = false;
+        let has_candidates = !search.execute()?.documents_ids.is_empty();
+
+        Ok(Some(QueryCorrection { query: corrected_query, has_candidates }))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::BTreeSet;
+
+    use big_s::S;
+
+    use super::*;
+    use crate::index::tests::TempIndex;
+    use crate::TermsMatchingStrategy;
+
+    fn suggest(index: &TempIndex, query: &str) -> Option<QueryCorrection> {
+        let rtxn = index.read_txn().unwrap();
+        let mut search = Search::new(&rtxn, index);
+        search.query(query);
+        search.terms_matching_strategy(TermsMatchingStrategy::All);
+        search.suggest_correction().unwrap()
+    }
+
+    fn create_index() -> TempIndex {
+        let index = TempIndex::new();
+        index
+            .add_documents(documents!([
+                { "id": 0, "text": "the kitten sleeps" },
+                { "id": 1, "text": "the kitchen is clean" },
+                { "id": 2, "text": "a kitchen with a kitten" },
+                { "id": 3, "text": "clean kitchen" },
+            ]))
+            .unwrap();
+        index
+    }
+
+    #[test]
+    fn misspelled_word() {
+        let index = create_index();
+
+        let correction = suggest(&index, "kitchan").unwrap();
+        assert_eq!(correction, QueryCorrection { query: S("kitchen"), has_candidates: true });
+
+        // both kitchen and kitten are at one typo of kitcen but kitchen is more frequent
+        let correction = suggest(&index, "kitcen").unwrap();
+        assert_eq!(correction, QueryCorrection { query: S("kitchen"), has_candidates: true });
+    }
+
+    #[test]
+    fn misspelled_word_in_multi_words_query() {
+        let index = create_index();
+
+        let correction = suggest(&index, "kitcen clean").unwrap();
+        assert_eq!(correction, QueryCorrection { query: S("kitchen clean"), has_candidates: true });
+
+        let correction = suggest(&index, "kitcen sleeps").unwrap();
+        assert_eq!(
+            correction,
+            QueryCorrection { query: S("kitchen sleeps"), has_candidates: false }
+        );
+    }
+
+    #[test]
+    fn already_matching_query() {
+        let index = create_index();
+
+        assert_eq!(suggest(&index, "kitten sleeps"), None);
+        // the last word is a prefix
+        assert_eq!(suggest(&index, "clean kitch"), None);
+    }
+
+    #[test]
+    fn typo_settings_are_respected() {
+        let index = create_index();
+
+        index
+            .update_settings(|settings| {
+                settings.set_exact_words(BTreeSet::from([S("kitcen")]));
+            })
+            .unwrap();
+        assert_eq!(suggest(&index, "kitcen clean"), None);
+
+        index
+            .update_settings(|settings| {
+                settings.reset_exact_words();
+                settings.set_min_word_len_one_typo(7);
+                settings.set_min_word_len_two_typos(9);
+            })
+            .unwrap();
+        assert_eq!(suggest(&index, "kitcen clean"), None);
+        assert_eq!(suggest(&index, "kitchan clean").unwrap().query, S("kitchen clean"));
+
+        index.update_settings(|settings| settings.set_autorize_typos(false)).unwrap();
+        assert_eq!(suggest(&index, "kitchan clean"), None);
+    }
+}
@@ -1,4 +1,5 @@
 use std::mem::take;
+use std::ops::Bound::{Excluded, Unbounded};
 
 use itertools::Itertools;
 use log::debug;
@@ -10,9 +11,9 @@ use crate::facet::FacetType;
 use crate::heed_codec::facet::FacetGroupKeyCodec;
 use crate::heed_codec::ByteSliceRefCodec;
 use crate::search::criteria::{resolve_query_tree, CriteriaBuilder, InitialCandidates};
-use crate::search::facet::{ascending_facet_sort, descending_facet_sort};
+use crate::search::facet::{ascending_facet_sort, descending_facet_sort, Filter};
 use crate::search::query_tree::Operation;
-use crate::search::CriterionImplementationStrategy;
+use crate::search::{CriterionImplementationStrategy, MissingValues};
 use crate::{FieldId, Index, Result};
 
 /// Threshold on the number of candidates that will make
@@ -25,6 +26,12 @@ pub struct AscDesc<'t> {
     field_name: String,
     field_id: Option<FieldId>,
     is_ascending: bool,
+    /// Whether the candidates are ordered by decreasing boost, see [`Name::Boost`].
+    ///
+    /// [`Name::Boost`]: crate::Criterion::Boost
+    is_boost: bool,
+    /// Where the candidates without a value for the field are returned.
+    missing_values: MissingValues,
     query_tree: Option<Operation>,
     candidates: Box<dyn Iterator<Item = heed::Result<RoaringBitmap>> + 't>,
     allowed_candidates: RoaringBitmap,
@@ -40,9 +47,19 @@ impl<'t> AscDesc<'t> {
         rtxn: &'t heed::RoTxn,
         parent: Box<dyn Criterion + 't>,
         field_name: String,
+        missing_values: MissingValues,
         implementation_strategy: CriterionImplementationStrategy,
     ) -> Result<Self> {
-        Self::new(index, rtxn, parent, field_name, true, implementation_strategy)
+        Self::new(
+            index,
+            rtxn,
+            parent,
+            field_name,
+            true,
+            false,
+            missing_values,
+            implementation_strategy,
+        )
     }
 
     pub fn desc(
@@ -50,17 +67,49 @@ impl<'t> AscDesc<'t> {
         rtxn: &'t heed::RoTxn,
         parent: Box<dyn Criterion + 't>,
         field_name: String,
+        missing_values: MissingValues,
         implementation_strategy: CriterionImplementationStrategy,
     ) -> Result<Self> {
-        Self::new(index, rtxn, parent, field_name, false, implementation_strategy)
+        Self::new(
+            index,
+            rtxn,
+            parent,
+            field_name,
+            false,
+            false,
+            missing_values,
+            implementation_strategy,
+        )
     }
 
+    pub fn boost(
+        index: &'t Index,
+        rtxn: &'t heed::RoTxn,
+        parent: Box<dyn Criterion + 't>,
+        field_name: String,
+        implementation_strategy: CriterionImplementationStrategy,
+    ) -> Result<Self> {
+        Self::new(
+            index,
+            rtxn,
+            parent,
+            field_name,
+            false,
+            true,
+            MissingValues::Last,
+            implementation_strategy,
+        )
+    }
+
+    #[allow(clippy::too_many_arguments)]
     fn new(
         index: &'t Index,
         rtxn: &'t heed::RoTxn,
         parent: Box<dyn Criterion + 't>,
         field_name: String,
         is_ascending: bool,
+        is_boost: bool,
+        missing_values: MissingValues,
         implementation_strategy: CriterionImplementationStrategy,
     ) -> Result<Self> {
         let fields_ids_map = index.fields_ids_map(rtxn)?;
@@ -71,7 +120,9 @@ impl<'t> AscDesc<'t> {
                     index.faceted_documents_ids(rtxn, field_id, FacetType::Number)?;
                 let string_faceted =
                     index.faceted_documents_ids(rtxn, field_id, FacetType::String)?;
-                number_faceted | string_faceted
+                let bool_faceted = index.facet_bool_docids(rtxn, field_id, false)?
+                    | index.facet_bool_docids(rtxn, field_id, true)?;
+                number_faceted | string_faceted | bool_faceted
             }
             None => RoaringBitmap::default(),
         };
@@ -82,6 +133,8 @@ impl<'t> AscDesc<'t> {
             field_name,
             field_id,
             is_ascending,
+            is_boost,
+            missing_values,
             query_tree: None,
             candidates: Box::new(std::iter::empty()),
             allowed_candidates: RoaringBitmap::new(),
@@ -95,6 +148,15 @@ impl<'t> AscDesc<'t> {
 
 impl<'t> Criterion for AscDesc<'t> {
     #[logging_timer::time("AscDesc::{}")]
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            level = "trace",
+            name = "AscDesc::next",
+            skip_all,
+            fields(excluded_candidates = params.excluded_candidates.len())
+        )
+    )]
     fn next(&mut self, params: &mut CriterionParameters) -> Result<Option<CriterionResult>> {
         // remove excluded candidates when next is called, instead of doing it in the loop.
         self.allowed_candidates -= params.excluded_candidates;
@@ -102,7 +164,11 @@ impl<'t> Criterion for AscDesc<'t> {
         loop {
             debug!(
                 "Facet {}({}) iteration",
-                if self.is_ascending { "Asc" } else { "Desc" },
+                match (self.is_boost, self.is_ascending) {
+                    (true, _) => "Boost",
+                    (false, true) => "Asc",
+                    (false, false) => "Desc",
+                },
                 self.field_name
             );
 
@@ -148,7 +214,15 @@ impl<'t> Criterion for AscDesc<'t> {
                         }
 
                         self.allowed_candidates = &candidates - params.excluded_candidates;
+                        let missing = &self.allowed_candidates - &self.faceted_candidates;
                         self.candidates = match self.field_id {
+                            Some(field_id) if self.is_boost => boost_ordered(
+                                self.index,
+                                self.rtxn,
+                                field_id,
+                                candidates,
+                                self.implementation_strategy,
+                            )?,
                             Some(field_id) => facet_ordered(
                                 self.index,
                                 self.rtxn,
@@ -159,6 +233,18 @@ impl<'t> Criterion for AscDesc<'t> {
                             )?,
                             None => Box::new(std::iter::empty()),
                         };
+                        // the candidates without a value are otherwise returned
+                        // once the ordered ones are exhausted.
+                        if self.missing_values == MissingValues::First
+                            && !self.is_boost
+                            && !missing.is_empty()
+                        {
+                            let ordered = std::mem::replace(
+                                &mut self.candidates,
+                                Box::new(std::iter::empty()),
+                            );
+                            self.candidates = Box::new(std::iter::once(Ok(missing)).chain(ordered));
+                        }
                     }
                     None => return Ok(None),
                 },
@@ -191,9 +277,11 @@ fn facet_ordered_iterative<'t>(
         is_ascending,
         candidates.clone(),
     )?;
+    let bool_iter = facet_bool_ordered(index, rtxn, field_id, is_ascending, &candidates)?;
     let string_iter =
         iterative_facet_string_ordered_iter(index, rtxn, field_id, is_ascending, candidates)?;
-    Ok(Box::new(number_iter.chain(string_iter).map(Ok)) as Box<dyn Iterator<Item = _>>)
+    Ok(Box::new(number_iter.chain(bool_iter).chain(string_iter).map(Ok))
+        as Box<dyn Iterator<Item = _>>)
 }
 
 fn facet_ordered_set_based<'t>(
@@ -212,6 +300,8 @@ fn facet_ordered_set_based<'t>(
         candidates.clone(),
     )?;
 
+    let bool_iter = facet_bool_ordered(index, rtxn, field_id, is_ascending, &candidates)?;
+
     let string_iter = make_iter(
         rtxn,
         index.facet_id_string_docids.remap_key_type::<FacetGroupKeyCodec<ByteSliceRefCodec>>(),
@@ -219,7 +309,27 @@ fn facet_ordered_set_based<'t>(
         candidates,
     )?;
 
-    Ok(Box::new(number_iter.chain(string_iter)))
+    Ok(Box::new(number_iter.chain(bool_iter.map(Ok)).chain(string_iter)))
+}
+
+/// Returns the groups of the given candidates with a `false` and with a `true` value, `false`
+/// being the lowest, the booleans are ordered after the numbers and before the strings.
+fn facet_bool_ordered(
+    index: &Index,
+    rtxn: &heed::RoTxn,
+    field_id: FieldId,
+    is_ascending: bool,
+    candidates: &RoaringBitmap,
+) -> Result<impl Iterator<Item = RoaringBitmap>> {
+    let values = if is_ascending { [false, true] } else { [true, false] };
+    let mut groups = Vec::with_capacity(values.len());
+    for value in values {
+        let docids = index.facet_bool_docids(rtxn, field_id, value)? & candidates;
+        if !docids.is_empty() {
+            groups.push(docids);
+        }
+    }
+    Ok(groups.into_iter())
 }
 
 /// Returns an iterator over groups of the given candidates in ascending or descending order.
@@ -251,6 +361,74 @@ fn facet_ordered<'t>(
     }
 }
 
+/// Returns an iterator over groups of the given candidates in descending order of
+/// their number values, the candidates without number values are ignored.
+fn facet_number_descending<'t>(
+    index: &'t Index,
+    rtxn: &'t heed::RoTxn,
+    field_id: FieldId,
+    candidates: RoaringBitmap,
+    implementation_strategy: CriterionImplementationStrategy,
+) -> Result<Box<dyn Iterator<Item = heed::Result<RoaringBitmap>> + 't>> {
+    let is_iterative = match implementation_strategy {
+        CriterionImplementationStrategy::OnlyIterative => true,
+        CriterionImplementationStrategy::OnlySetBased => false,
+        CriterionImplementationStrategy::Dynamic => candidates.len() <= CANDIDATES_THRESHOLD,
+    };
+
+    if is_iterative {
+        let iter = iterative_facet_number_ordered_iter(index, rtxn, field_id, false, candidates)?;
+        Ok(Box::new(iter.map(Ok)))
+    } else {
+        let db =
+            index.facet_id_f64_docids.remap_key_type::<FacetGroupKeyCodec<ByteSliceRefCodec>>();
+        Ok(Box::new(descending_facet_sort(rtxn, db, field_id, candidates)?))
+    }
+}
+
+/// Returns an iterator over groups of the given candidates in decreasing order of their number
+/// value, the candidates without a positive nor a negative value being returned as a single
+/// neutral group between the positive and the negative ones.
+fn boost_ordered<'t>(
+    index: &'t Index,
+    rtxn: &'t heed::RoTxn,
+    field_id: FieldId,
+    candidates: RoaringBitmap,
+    implementation_strategy: CriterionImplementationStrategy,
+) -> Result<Box<dyn Iterator<Item = heed::Result<RoaringBitmap>> + 't>> {
+    let db = index.facet_id_f64_docids;
+    let mut positive = RoaringBitmap::new();
+    Filter::explore_facet_number_levels(
+        rtxn,
+        db,
+        field_id,
+        Excluded(0.0),
+        Unbounded,
+        &mut positive,
+    )?;
+    let mut negative = RoaringBitmap::new();
+    Filter::explore_facet_number_levels(
+        rtxn,
+        db,
+        field_id,
+        Unbounded,
+        Excluded(0.0),
+        &mut negative,
+    )?;
+
+    // a document with several values is ranked by its highest one.
+    let positive = positive & &candidates;
+    let negative = (negative & &candidates) - &positive;
+    let neutral = candidates - &positive - &negative;
+
+    let iter = facet_number_descending(index, rtxn, field_id, positive, implementation_strategy)?
+        .chain(std::iter::once(Ok(neutral)))
+        .chain(facet_number_descending(index, rtxn, field_id, negative, implementation_strategy)?)
+        .filter(|group| !matches!(group, Ok(docids) if docids.is_empty()));
+
+    Ok(Box::new(iter))
+}
+
 /// Fetch the whole list of candidates facet number values one by one and order them by it.
 ///
 /// This function is fast when the amount of candidates to rank is small.
@@ -343,7 +521,8 @@ mod tests {
     use maplit::hashset;
 
     use crate::index::tests::TempIndex;
-    use crate::{AscDesc, Criterion, Filter, Search, SearchResult};
+    use crate::search::{CriterionImplementationStrategy, MissingValues};
+    use crate::{AscDesc, Criterion, Error, Filter, Search, SearchResult, UserError};
 
     // Note that in this test, only the iterative sort algorithms are used. Set the CANDIDATES_THESHOLD
     // constant to 0 to ensure that the other sort algorithms are also correct.
@@ -532,4 +711,188 @@ mod tests {
         expected_ids.reverse();
         assert_eq!(expected_ids, documents_ids);
     }
+
+    #[test]
+    fn boost_criterion() {
+        let index = TempIndex::new();
+
+        index
+            .update_settings(|settings| {
+                settings.set_filterable_fields(hashset! { S("_boost") });
+                settings.set_criteria(vec![Criterion::Boost(S("_boost"))]);
+            })
+            .unwrap();
+        index
+            .add_documents(documents!([
+                { "id": 0, "_boost": 2 },
+                { "id": 1 },
+                { "id": 2, "_boost": -1 },
+                { "id": 3, "_boost": 5 },
+                { "id": 4, "_boost": 0 },
+                { "id": 5, "_boost": [-3, 1] },
+            ]))
+            .unwrap();
+
+        let rtxn = index.read_txn().unwrap();
+        assert!(
+            crate::snapshot_tests::snap_settings(&index).contains(r#"criteria: [Boost("_boost")]"#)
+        );
+
+        // the documents without a boost are in the neutral bucket,
+        // between the positive and the negative boosts.
+        for strategy in [
+            CriterionImplementationStrategy::OnlyIterative,
+            CriterionImplementationStrategy::OnlySetBased,
+        ] {
+            let mut search = Search::new(&rtxn, &index);
+            search.criterion_implementation_strategy(strategy);
+            let SearchResult { documents_ids, .. } = search.execute().unwrap();
+            assert_eq!(documents_ids, vec![3, 0, 5, 1, 4, 2]);
+        }
+    }
+
+    #[test]
+    fn sort_missing_values() {
+        let index = TempIndex::new();
+
+        index
+            .update_settings(|settings| {
+                settings.set_primary_key(S("id"));
+                settings.set_sortable_fields(hashset! { S("author.*") });
+                settings.set_criteria(vec![Criterion::Sort]);
+            })
+            .unwrap();
+
+        // only the even documents have an author
+        let mut docs = vec![];
+        for i in 0..10 {
+            if i % 2 == 0 {
+                docs.push(serde_json::json!({ "id": i, "author": { "age": 10 - i } }));
+            } else {
+                docs.push(serde_json::json!({ "id": i }));
+            }
+        }
+        index.add_documents(documents!(docs)).unwrap();
+
+        let rtxn = index.read_txn().unwrap();
+        for strategy in [
+            CriterionImplementationStrategy::OnlyIterative,
+            CriterionImplementationStrategy::OnlySetBased,
+        ] {
+            let search = |sort: &str, missing_values: Option<MissingValues>| {
+                let mut search = Search::new(&rtxn, &index);
+                search.sort_criteria(vec![AscDesc::from_str(sort).unwrap()]);
+                search.criterion_implementation_strategy(strategy);
+                if let Some(missing_values) = missing_values {
+                    search.sort_missing_values(missing_values);
+                }
+                let SearchResult { documents_ids, .. } = search.execute().unwrap();
+                documents_ids
+            };
+
+            // the documents without the field are returned last by default
+            assert_eq!(search("author.age:asc", None), vec![8, 6, 4, 2, 0, 1, 3, 5, 7, 9]);
+            assert_eq!(search("author.age:desc", None), vec![0, 2, 4, 6, 8, 1, 3, 5, 7, 9]);
+
+            let first = Some(MissingValues::First);
+            assert_eq!(search("author.age:asc", first), vec![1, 3, 5, 7, 9, 8, 6, 4, 2, 0]);
+            assert_eq!(search("author.age:desc", first), vec![1, 3, 5, 7, 9, 0, 2, 4, 6, 8]);
+
+            let last = Some(MissingValues::Last);
+            assert_eq!(search("author.age:asc", last), vec![8, 6, 4, 2, 0, 1, 3, 5, 7, 9]);
+        }
+
+        // the fields that don't match the sortable fields can't be sorted
+        let mut search = Search::new(&rtxn, &index);
+        search.sort_criteria(vec![AscDesc::from_str("id:asc").unwrap()]);
+        assert!(search.execute().is_err());
+    }
+
+    #[test]
+    fn sort_booleans() {
+        let index = TempIndex::new();
+
+        index
+            .update_settings(|settings| {
+                settings.set_primary_key(S("id"));
+                settings.set_sortable_fields(hashset! { S("value") });
+                settings.set_criteria(vec![Criterion::Sort]);
+            })
+            .unwrap();
+
+        // the booleans are ordered between the numbers and the strings
+        index
+            .add_documents(documents!([
+                { "id": 0, "value": true },
+                { "id": 1, "value": "abc" },
+                { "id": 2, "value": false },
+                { "id": 3, "value": 12 },
+                { "id": 4 },
+                { "id": 5, "value": true },
+            ]))
+            .unwrap();
+
+        let rtxn = index.read_txn().unwrap();
+        for strategy in [
+            CriterionImplementationStrategy::OnlyIterative,
+            CriterionImplementationStrategy::OnlySetBased,
+        ] {
+            let search = |sort: &str| {
+                let mut search = Search::new(&rtxn, &index);
+                search.sort_criteria(vec![AscDesc::from_str(sort).unwrap()]);
+                search.criterion_implementation_strategy(strategy);
+                let SearchResult { documents_ids, .. } = search.execute().unwrap();
+                documents_ids
+            };
+
+            assert_eq!(search("value:asc"), vec![3, 2, 0, 5, 1, 4]);
+            assert_eq!(search("value:desc"), vec![3, 0, 5, 2, 1, 4]);
+        }
+    }
+
+    #[test]
+    fn boost_criterion_requires_filterable_field() {
+        let index = TempIndex::new();
+
+        let error = index
+            .update_settings(|settings| {
+                settings.set_criteria(vec![Criterion::Boost(S("_boost"))]);
+            })
+            .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Attribute `_boost` of the `boost:_boost` ranking rule is not filterable. \
+This index does not have configured filterable attributes."
+        );
+
+        index
+            .update_settings(|settings| {
+                settings.set_filterable_fields(hashset! { S("_boost"), S("genre") });
+                settings.set_criteria(vec![Criterion::Boost(S("_boost"))]);
+            })
+            .unwrap();
+
+        // the field can't stop being filterable while the ranking rule uses it.
+        let error = index
+            .update_settings(|settings| {
+                settings.set_filterable_fields(hashset! { S("genre") });
+            })
+            .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Attribute `_boost` of the `boost:_boost` ranking rule is not filterable. \
+Available filterable attributes are: `genre`."
+        );
+        let error =
+            index.update_settings(|settings| settings.reset_filterable_fields()).unwrap_err();
+        assert!(matches!(error, Error::UserError(UserError::InvalidBoostAttribute { .. })));
+
+        // unless the ranking rule is removed at the same time.
+        index
+            .update_settings(|settings| {
+                settings.reset_filterable_fields();
+                settings.reset_criteria();
+            })
+            .unwrap();
+    }
 }
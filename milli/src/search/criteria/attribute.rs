@@ -12,7 +12,9 @@ use crate::search::query_tree::{Operation, QueryKind};
 use crate::search::{
     build_dfa, word_derivations, CriterionImplementationStrategy, WordDerivationsCache,
 };
-use crate::Result;
+use crate::{
+    bucketed_absolute_position, relative_from_absolute_position, DocumentId, FieldId, Result,
+};
 
 /// To be able to divide integers by the number of words in the query
 /// we want to find a multiplier that allow us to divide by any number between 1 and 10.
@@ -30,9 +32,12 @@ pub struct Attribute<'t> {
     state: Option<(Operation, FlattenedQueryTree, RoaringBitmap)>,
     initial_candidates: InitialCandidates,
     parent: Box<dyn Criterion + 't>,
-    linear_buckets: Option<btree_map::IntoIter<u64, RoaringBitmap>>,
+    linear_buckets: Option<btree_map::IntoIter<(u64, u64), RoaringBitmap>>,
     set_buckets: Option<BinaryHeap<Branch<'t>>>,
     implementation_strategy: CriterionImplementationStrategy,
+    /// The rank of each searchable field when boosts are defined, the candidates are
+    /// first bucketed by the rank of the field where their best match occurred.
+    field_ranks: Option<HashMap<FieldId, u64>>,
 }
 
 impl<'t> Attribute<'t> {
@@ -40,6 +45,7 @@ impl<'t> Attribute<'t> {
         ctx: &'t dyn Context<'t>,
         parent: Box<dyn Criterion + 't>,
         implementation_strategy: CriterionImplementationStrategy,
+        field_ranks: Option<HashMap<FieldId, u64>>,
     ) -> Self {
         Attribute {
             ctx,
@@ -49,12 +55,22 @@ impl<'t> Attribute<'t> {
             linear_buckets: None,
             set_buckets: None,
             implementation_strategy,
+            field_ranks,
         }
     }
 }
 
 impl<'t> Criterion for Attribute<'t> {
     #[logging_timer::time("Attribute::{}")]
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            level = "trace",
+            name = "Attribute::next",
+            skip_all,
+            fields(excluded_candidates = params.excluded_candidates.len())
+        )
+    )]
     fn next(&mut self, params: &mut CriterionParameters) -> Result<Option<CriterionResult>> {
         // remove excluded candidates when next is called, instead of doing it in the loop.
         if let Some((_, _, allowed_candidates)) = self.state.as_mut() {
@@ -72,14 +88,17 @@ impl<'t> Criterion for Attribute<'t> {
                     }));
                 }
                 Some((query_tree, flattened_query_tree, mut allowed_candidates)) => {
-                    let found_candidates = if matches!(
-                        self.implementation_strategy,
-                        CriterionImplementationStrategy::OnlyIterative
-                    ) || (matches!(
-                        self.implementation_strategy,
-                        CriterionImplementationStrategy::Dynamic
-                    ) && allowed_candidates.len()
-                        < CANDIDATES_THRESHOLD)
+                    // the field boosts are only supported by the linear buckets,
+                    // there are no field ranks when all the boosts are neutral.
+                    let found_candidates = if self.field_ranks.is_some()
+                        || matches!(
+                            self.implementation_strategy,
+                            CriterionImplementationStrategy::OnlyIterative
+                        )
+                        || (matches!(
+                            self.implementation_strategy,
+                            CriterionImplementationStrategy::Dynamic
+                        ) && allowed_candidates.len() < CANDIDATES_THRESHOLD)
                     {
                         let linear_buckets = match self.linear_buckets.as_mut() {
                             Some(linear_buckets) => linear_buckets,
@@ -88,6 +107,7 @@ impl<'t> Criterion for Attribute<'t> {
                                     self.ctx,
                                     &flattened_query_tree,
                                     &allowed_candidates,
+                                    self.field_ranks.as_ref(),
                                 )?;
                                 self.linear_buckets.get_or_insert(new_buckets.into_iter())
                             }
@@ -196,6 +216,8 @@ impl<'t> Criterion for Attribute<'t> {
 
 /// QueryPositionIterator is an Iterator over positions of a Query,
 /// It contains iterators over words positions.
+/// The positions beyond the exact ones are buckets, they are read as the first position
+/// of the bucket, see [`bucketed_position`](crate::bucketed_position).
 struct QueryPositionIterator<'t> {
     #[allow(clippy::type_complexity)]
     inner:
@@ -473,64 +495,104 @@ fn initialize_linear_buckets(
     ctx: &dyn Context,
     branches: &FlattenedQueryTree,
     allowed_candidates: &RoaringBitmap,
-) -> Result<BTreeMap<u64, RoaringBitmap>> {
+    field_ranks: Option<&HashMap<FieldId, u64>>,
+) -> Result<BTreeMap<(u64, u64), RoaringBitmap>> {
+    /// Returns the rank of the field of the best match of the branch, if there are
+    /// field ranks, followed by the rank of the positions of the branch in this field.
     fn compute_candidate_rank(
         branches: &FlattenedQueryTree,
         words_positions: HashMap<String, RoaringBitmap>,
-    ) -> u64 {
-        let mut min_rank = u64::max_value();
+        field_ranks: Option<&HashMap<FieldId, u64>>,
+    ) -> (u64, u64) {
+        let mut min_rank = (u64::max_value(), u64::max_value());
         for branch in branches {
             let branch_len = branch.len();
-            let mut branch_rank = Vec::with_capacity(branch_len);
+            // the best position of each word of the branch, in each field when the fields are ranked.
+            let mut branch_positions = Vec::with_capacity(branch_len);
             for derivates in branch {
-                let mut position = None;
+                let mut fields_positions = BTreeMap::new();
                 for Query { prefix, kind } in derivates {
-                    // find the best position of the current word in the document.
-                    let current_position = match kind {
+                    // find the best positions of the current word in the document.
+                    let derivations: Vec<_> = match kind {
                         QueryKind::Exact { word, .. } => {
                             if *prefix {
-                                word_derivations(word, true, 0, &words_positions)
-                                    .flat_map(|positions| positions.iter().next())
-                                    .min()
+                                word_derivations(word, true, 0, &words_positions).collect()
                             } else {
-                                words_positions
-                                    .get(word)
-                                    .and_then(|positions| positions.iter().next())
+                                words_positions.get(word).into_iter().collect()
                             }
                         }
                         QueryKind::Tolerant { typo, word } => {
-                            word_derivations(word, *prefix, *typo, &words_positions)
-                                .flat_map(|positions| positions.iter().next())
-                                .min()
+                            word_derivations(word, *prefix, *typo, &words_positions).collect()
                         }
                     };
 
-                    match (position, current_position) {
-                        (Some(p), Some(cp)) => position = Some(cmp::min(p, cp)),
-                        (None, Some(cp)) => position = Some(cp),
-                        _ => (),
+                    // without field ranks, only the best position of the word is needed.
+                    let take = if field_ranks.is_some() { usize::MAX } else { 1 };
+                    for positions in derivations {
+                        for position in positions.iter().take(take) {
+                            let (fid, _) = relative_from_absolute_position(position);
+                            let best = fields_positions.entry(fid).or_insert(position);
+                            *best = cmp::min(*best, position);
+                        }
                     }
                 }
 
                 // if a position is found, we add it to the branch score,
                 // otherwise the branch is considered as unfindable in this document and we break.
-                if let Some(position) = position {
-                    branch_rank.push(position as u64);
-                } else {
-                    branch_rank.clear();
+                if fields_positions.is_empty() {
+                    branch_positions.clear();
                     break;
                 }
+                branch_positions.push(fields_positions);
             }
 
-            if !branch_rank.is_empty() {
-                branch_rank.sort_unstable();
+            if branch_positions.is_empty() {
+                continue;
+            }
+
+            let positions_rank = |mut positions: Vec<u64>| -> u64 {
+                positions.sort_unstable();
                 // because several words in same query can't match all a the position 0,
                 // we substract the word index to the position.
-                let branch_rank: u64 =
-                    branch_rank.into_iter().enumerate().map(|(i, r)| r - i as u64).sum();
+                let rank: u64 = positions.into_iter().enumerate().map(|(i, r)| r - i as u64).sum();
                 // here we do the means of the words of the branch
-                min_rank =
-                    min_rank.min(branch_rank * LCM_10_FIRST_NUMBERS as u64 / branch_len as u64);
+                rank * LCM_10_FIRST_NUMBERS as u64 / branch_len as u64
+            };
+
+            // the best positions of the words, whatever their fields.
+            let positions: Vec<u64> = branch_positions
+                .iter()
+                .filter_map(|fields_positions| fields_positions.values().min())
+                .map(|position| *position as u64)
+                .collect();
+
+            match field_ranks {
+                Some(field_ranks) => {
+                    let field_rank =
+                        |fid| field_ranks.get(&fid).copied().unwrap_or(u64::max_value());
+                    // the words found in different fields are only as boosted as the least
+                    // boosted of these fields.
+                    let least_boosted = positions
+                        .iter()
+                        .map(|position| {
+                            field_rank(relative_from_absolute_position(*position as u32).0)
+                        })
+                        .max()
+                        .unwrap_or(u64::max_value());
+                    min_rank = min_rank.min((least_boosted, positions_rank(positions)));
+
+                    // the words all found in the same field are as boosted as this field.
+                    for fid in branch_positions[0].keys() {
+                        let positions: Option<Vec<u64>> = branch_positions
+                            .iter()
+                            .map(|fields_positions| fields_positions.get(fid).map(|p| *p as u64))
+                            .collect();
+                        if let Some(positions) = positions {
+                            min_rank = min_rank.min((field_rank(*fid), positions_rank(positions)));
+                        }
+                    }
+                }
+                None => min_rank = min_rank.min((0, positions_rank(positions))),
             }
         }
 
@@ -553,16 +615,38 @@ fn initialize_linear_buckets(
         })
     }
 
+    // the positions are bucketed like in the position databases read by the set based
+    // implementation, for both implementations to rank the documents the same way.
+    let bucketed_positions = ctx.bucketed_positions()?;
     let mut candidates = BTreeMap::new();
     for docid in allowed_candidates {
-        let words_positions = ctx.docid_words_positions(docid)?;
-        let rank = compute_candidate_rank(branches, words_positions);
+        let mut words_positions = ctx.docid_words_positions(docid)?;
+        if bucketed_positions {
+            for positions in words_positions.values_mut() {
+                *positions = positions.iter().map(bucketed_absolute_position).collect();
+            }
+        }
+        let rank = compute_candidate_rank(branches, words_positions, field_ranks);
         candidates.entry(rank).or_insert_with(RoaringBitmap::new).insert(docid);
     }
 
     Ok(candidates)
 }
 
+/// Returns the rank that the attribute criterion gives to this document, a lower rank being
+/// better, or `None` if the document doesn't contain all the words of a branch of the query.
+pub(crate) fn attribute_rank(
+    ctx: &dyn Context,
+    query_tree: &Operation,
+    docid: DocumentId,
+) -> Result<Option<u64>> {
+    let flattened_query_tree = flatten_query_tree(query_tree);
+    let mut candidates = RoaringBitmap::new();
+    candidates.insert(docid);
+    let buckets = initialize_linear_buckets(ctx, &flattened_query_tree, &candidates, None)?;
+    Ok(buckets.into_keys().next().map(|(_, rank)| rank).filter(|rank| *rank != u64::max_value()))
+}
+
 // TODO can we keep refs of Query
 fn flatten_query_tree(query_tree: &Operation) -> FlattenedQueryTree {
     use crate::search::criteria::Operation::{And, Or, Phrase};
@@ -595,7 +679,7 @@ fn flatten_query_tree(query_tree: &Operation) -> FlattenedQueryTree {
                     ops.iter().flat_map(recurse).collect()
                 }
             }
-            Phrase(words) => {
+            Phrase(words, _) => {
                 let queries = words
                     .iter()
                     .filter_map(|w| w.as_ref())
@@ -615,7 +699,9 @@ mod tests {
     use big_s::S;
 
     use super::*;
-    use crate::search::criteria::QueryKind;
+    use crate::index::tests::TempIndex;
+    use crate::search::criteria::{CriteriaBuilder, QueryKind};
+    use crate::{Search, SearchResult};
 
     #[test]
     fn simple_flatten_query_tree() {
@@ -706,4 +792,114 @@ mod tests {
         ]
         "###);
     }
+
+    #[test]
+    fn field_boosts() {
+        let index = TempIndex::new();
+        index
+            .update_settings(|settings| {
+                settings.set_searchable_fields(vec![S("title"), S("description")]);
+            })
+            .unwrap();
+        index
+            .add_documents(documents!([
+                { "id": 0, "title": "a kitten", "description": "a dog" },
+                { "id": 1, "title": "a dog", "description": "a kitten" },
+            ]))
+            .unwrap();
+
+        let search_ids = |index: &TempIndex| {
+            let rtxn = index.read_txn().unwrap();
+            let mut search = Search::new(&rtxn, index);
+            search.query("kitten");
+            search.execute().unwrap().documents_ids
+        };
+
+        // the title comes first in the searchable attributes.
+        assert_eq!(search_ids(&index), vec![0, 1]);
+
+        index
+            .update_settings(|settings| {
+                settings.set_field_boosts(BTreeMap::from([(S("description"), 10.0)]));
+            })
+            .unwrap();
+
+        // the description is now boosted above the title.
+        assert_eq!(search_ids(&index), vec![1, 0]);
+
+        index.update_settings(|settings| settings.reset_field_boosts()).unwrap();
+        assert_eq!(search_ids(&index), vec![0, 1]);
+    }
+
+    #[test]
+    fn field_boosts_rank_each_field() {
+        let index = TempIndex::new();
+        index
+            .update_settings(|settings| {
+                settings.set_searchable_fields(vec![S("title"), S("description")]);
+                settings.set_field_boosts(BTreeMap::from([(S("description"), 10.0)]));
+            })
+            .unwrap();
+        index
+            .add_documents(documents!([
+                { "id": 0, "title": "a kitten", "description": "a dog" },
+                { "id": 1, "title": "a kitten", "description": "a kitten" },
+            ]))
+            .unwrap();
+
+        let rtxn = index.read_txn().unwrap();
+        let mut search = Search::new(&rtxn, &index);
+        search.query("kitten");
+        // the best position of both documents is in the title, only the
+        // second one also contains the word in the boosted description.
+        assert_eq!(search.execute().unwrap().documents_ids, vec![1, 0]);
+        drop(rtxn);
+
+        // the neutral boosts keep the set based implementation.
+        index
+            .update_settings(|settings| {
+                settings.set_field_boosts(BTreeMap::from([(S("description"), 1.0)]));
+            })
+            .unwrap();
+        let rtxn = index.read_txn().unwrap();
+        let builder = CriteriaBuilder::new(&rtxn, &index).unwrap();
+        assert!(builder.field_ranks().unwrap().is_none());
+    }
+
+    #[test]
+    fn bucketed_positions() {
+        let index = TempIndex::new();
+        index
+            .update_settings(|settings| {
+                settings.set_primary_key(S("id"));
+                settings.set_criteria(vec![crate::Criterion::Attribute]);
+            })
+            .unwrap();
+
+        let text = |position: usize| format!("{}kitten", "lorem ".repeat(position));
+        index
+            .add_documents(documents!([
+                { "id": 0, "text": text(20) },
+                { "id": 1, "text": text(17) },
+                { "id": 2, "text": text(40) },
+                { "id": 3, "text": text(3) },
+            ]))
+            .unwrap();
+
+        let rtxn = index.read_txn().unwrap();
+        let search_ids = |strategy| {
+            let mut search = Search::new(&rtxn, &index);
+            search.query("kitten");
+            search.criterion_implementation_strategy(strategy);
+            let SearchResult { documents_ids, .. } = search.execute().unwrap();
+            documents_ids
+        };
+
+        // both implementations only see the buckets, the positions 17 and 20 are equal.
+        let documents_ids = search_ids(CriterionImplementationStrategy::OnlyIterative);
+        insta::assert_snapshot!(format!("{documents_ids:?}"), @"[3, 0, 1, 2]");
+
+        let documents_ids = search_ids(CriterionImplementationStrategy::OnlySetBased);
+        insta::assert_snapshot!(format!("{documents_ids:?}"), @"[3, 0, 1, 2]");
+    }
 }
@@ -1,17 +1,19 @@
-use std::collections::btree_map::Entry;
-use std::collections::BTreeMap;
 use std::convert::TryFrom;
 use std::mem::take;
 
 use log::debug;
-use roaring::{MultiOps, RoaringBitmap};
+use roaring::RoaringBitmap;
 
 use crate::search::criteria::{
     resolve_phrase, resolve_query_tree, Context, Criterion, CriterionParameters, CriterionResult,
     InitialCandidates,
 };
-use crate::search::query_tree::{Operation, PrimitiveQueryPart};
-use crate::{absolute_from_relative_position, FieldId, Result};
+use crate::search::query_tree::{Operation, PhraseSlop, PrimitiveQueryPart};
+use crate::search::ExactnessConfig;
+use crate::{
+    absolute_from_relative_position, bucketed_absolute_position, relative_from_absolute_position,
+    FieldId, Result, EXACT_POSITIONS_BUCKETS,
+};
 
 pub struct Exactness<'t> {
     ctx: &'t dyn Context<'t>,
@@ -21,6 +23,7 @@ pub struct Exactness<'t> {
     parent: Box<dyn Criterion + 't>,
     query: Vec<ExactQueryPart>,
     cache: Option<ExactWordsCombinationCache>,
+    config: ExactnessConfig,
 }
 
 impl<'t> Exactness<'t> {
@@ -28,6 +31,7 @@ impl<'t> Exactness<'t> {
         ctx: &'t dyn Context<'t>,
         parent: Box<dyn Criterion + 't>,
         primitive_query: &[PrimitiveQueryPart],
+        config: ExactnessConfig,
     ) -> heed::Result<Self> {
         let mut query: Vec<_> = Vec::with_capacity(primitive_query.len());
         for part in primitive_query {
@@ -42,12 +46,22 @@ impl<'t> Exactness<'t> {
             parent,
             query,
             cache: None,
+            config,
         })
     }
 }
 
 impl<'t> Criterion for Exactness<'t> {
     #[logging_timer::time("Exactness::{}")]
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            level = "trace",
+            name = "Exactness::next",
+            skip_all,
+            fields(excluded_candidates = params.excluded_candidates.len())
+        )
+    )]
     fn next(&mut self, params: &mut CriterionParameters) -> Result<Option<CriterionResult>> {
         // remove excluded candidates when next is called, instead of doing it in the loop.
         if let Some(state) = self.state.as_mut() {
@@ -65,8 +79,13 @@ impl<'t> Criterion for Exactness<'t> {
                     // the primitive query, which does not change
                 }
                 Some(state) => {
-                    let (candidates, state) =
-                        resolve_state(self.ctx, take(state), &self.query, &mut self.cache)?;
+                    let (candidates, state) = resolve_state(
+                        self.ctx,
+                        take(state),
+                        &self.query,
+                        &mut self.cache,
+                        &self.config,
+                    )?;
                     self.state = state;
 
                     return Ok(Some(CriterionResult {
@@ -102,7 +121,7 @@ impl<'t> Criterion for Exactness<'t> {
                             None => self.initial_candidates.map_inplace(|c| c | &candidates),
                         }
 
-                        self.state = Some(State::new(candidates));
+                        self.state = Some(State::new(candidates, &self.config));
                         self.query_tree = Some(query_tree);
                     }
                     Some(CriterionResult {
@@ -129,22 +148,48 @@ impl<'t> Criterion for Exactness<'t> {
 enum State {
     /// Extract the documents that have an attribute that contains exactly the query.
     ExactAttribute(RoaringBitmap),
-    /// Extract the documents that have an attribute that starts with exactly the query.
-    AttributeStartsWith(RoaringBitmap),
+    /// Extract the documents that have an attribute that starts with exactly the query,
+    /// along with these documents when they were already found by the previous state.
+    AttributeStartsWith(RoaringBitmap, Option<RoaringBitmap>),
+    /// Extract the documents that have an attribute that contains the query words consecutively.
+    ExactQuery(RoaringBitmap),
     /// Rank the remaining documents by the number of exact words contained.
     ExactWords(RoaringBitmap),
     Remainings(Vec<RoaringBitmap>),
 }
 
 impl State {
-    fn new(candidates: RoaringBitmap) -> Self {
-        Self::ExactAttribute(candidates)
+    fn new(candidates: RoaringBitmap, config: &ExactnessConfig) -> Self {
+        if config.exact_attribute {
+            Self::ExactAttribute(candidates)
+        } else {
+            Self::after_exact_attribute(candidates, config)
+        }
+    }
+
+    /// The state ranking the candidates left after the exact attribute levels.
+    fn after_exact_attribute(candidates: RoaringBitmap, config: &ExactnessConfig) -> Self {
+        if config.exact_query {
+            Self::ExactQuery(candidates)
+        } else {
+            Self::after_exact_query(candidates, config)
+        }
+    }
+
+    /// The state ranking the candidates left after the exact query levels.
+    fn after_exact_query(candidates: RoaringBitmap, config: &ExactnessConfig) -> Self {
+        if config.exact_words {
+            Self::ExactWords(candidates)
+        } else {
+            Self::Remainings(vec![candidates])
+        }
     }
 
     fn difference_with(&mut self, lhs: &RoaringBitmap) {
         match self {
             Self::ExactAttribute(candidates)
-            | Self::AttributeStartsWith(candidates)
+            | Self::AttributeStartsWith(candidates, _)
+            | Self::ExactQuery(candidates)
             | Self::ExactWords(candidates) => *candidates -= lhs,
             Self::Remainings(candidates_array) => {
                 candidates_array.iter_mut().for_each(|candidates| *candidates -= lhs);
@@ -156,7 +201,8 @@ impl State {
     fn is_empty(&self) -> bool {
         match self {
             Self::ExactAttribute(candidates)
-            | Self::AttributeStartsWith(candidates)
+            | Self::AttributeStartsWith(candidates, _)
+            | Self::ExactQuery(candidates)
             | Self::ExactWords(candidates) => candidates.is_empty(),
             Self::Remainings(candidates_array) => {
                 candidates_array.iter().all(RoaringBitmap::is_empty)
@@ -176,62 +222,89 @@ fn resolve_state(
     state: State,
     query: &[ExactQueryPart],
     cache: &mut Option<ExactWordsCombinationCache>,
+    config: &ExactnessConfig,
 ) -> Result<(RoaringBitmap, Option<State>)> {
     use State::*;
     match state {
         ExactAttribute(mut allowed_candidates) => {
             let mut candidates = RoaringBitmap::new();
+            let mut starts_with_candidates = None;
             if let Ok(query_len) = u8::try_from(query.len()) {
+                // Only the documents containing all the words of the query can start with it,
+                // the positions are therefore only looked up for them.
+                let all_words_candidates =
+                    exact_words_cache(ctx, query, cache)?.all_words_candidates(&allowed_candidates);
+
+                // The documents whose attribute starts with the query are kept for the next state,
+                // those whose attribute also has as many words as the query are exact matches.
+                let mut starts_with = RoaringBitmap::new();
                 let attributes_ids = ctx.searchable_fields_ids()?;
                 for id in attributes_ids {
+                    let attribute_starts_with =
+                        attribute_start_with_candidates(ctx, id, query, &all_words_candidates)?;
                     if let Some(attribute_allowed_docids) =
                         ctx.field_id_word_count_docids(id, query_len)?
                     {
-                        let mut attribute_candidates_array =
-                            attribute_start_with_docids(ctx, id, query)?;
-                        attribute_candidates_array.push(attribute_allowed_docids);
-
-                        candidates |= MultiOps::intersection(attribute_candidates_array);
+                        candidates |= match &attribute_starts_with {
+                            Some(docids) => docids & attribute_allowed_docids,
+                            None => attribute_allowed_docids & &allowed_candidates,
+                        };
                     }
+                    starts_with |= attribute_starts_with.unwrap_or_default();
                 }
 
-                // only keep allowed candidates
-                candidates &= &allowed_candidates;
                 // remove current candidates from allowed candidates
                 allowed_candidates -= &candidates;
+                starts_with_candidates = Some(starts_with - &candidates);
             }
 
-            Ok((candidates, Some(AttributeStartsWith(allowed_candidates))))
+            Ok((candidates, Some(AttributeStartsWith(allowed_candidates, starts_with_candidates))))
         }
-        AttributeStartsWith(mut allowed_candidates) => {
-            let mut candidates = RoaringBitmap::new();
-            let attributes_ids = ctx.searchable_fields_ids()?;
-            for id in attributes_ids {
-                let attribute_candidates_array = attribute_start_with_docids(ctx, id, query)?;
-                candidates |= MultiOps::intersection(attribute_candidates_array);
-            }
+        AttributeStartsWith(mut allowed_candidates, starts_with_candidates) => {
+            let mut candidates = match starts_with_candidates {
+                Some(starts_with_candidates) => starts_with_candidates,
+                None => {
+                    let all_words_candidates = exact_words_cache(ctx, query, cache)?
+                        .all_words_candidates(&allowed_candidates);
+                    let mut candidates = RoaringBitmap::new();
+                    let attributes_ids = ctx.searchable_fields_ids()?;
+                    for id in attributes_ids {
+                        candidates |=
+                            attribute_start_with_candidates(ctx, id, query, &all_words_candidates)?
+                                .unwrap_or_default();
+                    }
+                    candidates
+                }
+            };
 
             // only keep allowed candidates
             candidates &= &allowed_candidates;
             // remove current candidates from allowed candidates
             allowed_candidates -= &candidates;
-            Ok((candidates, Some(ExactWords(allowed_candidates))))
+            Ok((candidates, Some(State::after_exact_attribute(allowed_candidates, config))))
         }
-        ExactWords(allowed_candidates) => {
-            // Retrieve the cache if it already exist, otherwise create it.
-            let owned_cache = if let Some(cache) = cache.take() {
-                cache
-            } else {
-                compute_combinations(ctx, query)?
+        ExactQuery(mut allowed_candidates) => {
+            // The phrase is only resolved when some of the candidates contain all its words.
+            let all_words_candidates =
+                exact_words_cache(ctx, query, cache)?.all_words_candidates(&allowed_candidates);
+            let candidates = match exact_query_phrase(query) {
+                Some(phrase) if !all_words_candidates.is_empty() => {
+                    resolve_phrase(ctx, &phrase, PhraseSlop::EXACT)? & all_words_candidates
+                }
+                _ => RoaringBitmap::new(),
             };
+
+            // remove current candidates from allowed candidates
+            allowed_candidates -= &candidates;
+            Ok((candidates, Some(State::after_exact_query(allowed_candidates, config))))
+        }
+        ExactWords(allowed_candidates) => {
             // The cache contains the sets of documents which contain exactly 1,2,3,.. exact words
             // from the query. It cannot be empty. All the candidates in it are disjoint.
-
-            let mut candidates_array = owned_cache.combinations.clone();
+            let mut candidates_array = exact_words_cache(ctx, query, cache)?.combinations.clone();
             for candidates in candidates_array.iter_mut() {
                 *candidates &= &allowed_candidates;
             }
-            *cache = Some(owned_cache);
 
             let best_candidates = candidates_array.pop().unwrap();
 
@@ -250,34 +323,82 @@ fn resolve_state(
     }
 }
 
-fn attribute_start_with_docids(
+/// Returns the query as a phrase of its original words, or `None` if it only contains one word
+/// since the documents containing it are then ranked by the exact words level.
+fn exact_query_phrase(query: &[ExactQueryPart]) -> Option<Vec<Option<String>>> {
+    let mut phrase = Vec::new();
+    for part in query {
+        match part {
+            // the original word is the last of the synonyms.
+            ExactQueryPart::Synonyms(synonyms) => phrase.push(synonyms.last().cloned()),
+            ExactQueryPart::Phrase(words) => phrase.extend(words.iter().cloned()),
+        }
+    }
+
+    if phrase.iter().flatten().count() > 1 {
+        Some(phrase)
+    } else {
+        None
+    }
+}
+
+/// Returns the allowed candidates whose attribute starts with the query, or `None` if none of
+/// the words of the query is found at its position in the attribute of any document.
+///
+/// The words are looked up one after the other, starting from the allowed candidates, so that the
+/// lookups stop as soon as no candidate is left. The positions beyond the exact ones are looked
+/// up in their bucket, the remaining candidates are then checked against their exact positions.
+fn attribute_start_with_candidates(
     ctx: &dyn Context,
     attribute_id: FieldId,
     query: &[ExactQueryPart],
-) -> heed::Result<Vec<RoaringBitmap>> {
-    let mut attribute_candidates_array = Vec::new();
+    allowed_candidates: &RoaringBitmap,
+) -> heed::Result<Option<RoaringBitmap>> {
+    let bucketed_positions = ctx.bucketed_positions()?;
+    let position_key = |pos| if bucketed_positions { bucketed_absolute_position(pos) } else { pos };
+    let is_bucketed = |pos| {
+        let (_, relative) = relative_from_absolute_position(pos);
+        bucketed_positions && relative >= EXACT_POSITIONS_BUCKETS
+    };
+
+    let mut candidates = allowed_candidates.clone();
+    let mut intersected = false;
+    // the words looked up in a bucket, along with the position they must be found at.
+    let mut bucketed_words: Vec<(Vec<&str>, u32)> = Vec::new();
     // start from attribute first position
     let mut pos = absolute_from_relative_position(attribute_id, 0);
     for part in query {
+        if candidates.is_empty() {
+            break;
+        }
+
         use ExactQueryPart::*;
         match part {
             Synonyms(synonyms) => {
                 let mut synonyms_candidates = RoaringBitmap::new();
                 for word in synonyms {
-                    let wc = ctx.word_position_docids(word, pos)?;
+                    let wc = ctx.word_position_docids(word, position_key(pos))?;
                     if let Some(word_candidates) = wc {
                         synonyms_candidates |= word_candidates;
                     }
                 }
-                attribute_candidates_array.push(synonyms_candidates);
+                candidates &= synonyms_candidates;
+                intersected = true;
+                if is_bucketed(pos) {
+                    bucketed_words.push((synonyms.iter().map(String::as_str).collect(), pos));
+                }
                 pos += 1;
             }
             Phrase(phrase) => {
                 for word in phrase {
                     if let Some(word) = word {
-                        let wc = ctx.word_position_docids(word, pos)?;
+                        let wc = ctx.word_position_docids(word, position_key(pos))?;
                         if let Some(word_candidates) = wc {
-                            attribute_candidates_array.push(word_candidates);
+                            candidates &= word_candidates;
+                            intersected = true;
+                            if is_bucketed(pos) {
+                                bucketed_words.push((vec![word.as_str()], pos));
+                            }
                         }
                     }
                     pos += 1;
@@ -286,7 +407,24 @@ fn attribute_start_with_docids(
         }
     }
 
-    Ok(attribute_candidates_array)
+    // Only the queries longer than the exact positions read the positions of the candidates.
+    if !bucketed_words.is_empty() {
+        let mut exact_candidates = RoaringBitmap::new();
+        for docid in &candidates {
+            let words_positions = ctx.docid_words_positions(docid)?;
+            let starts_with = bucketed_words.iter().all(|(words, pos)| {
+                words.iter().any(|word| {
+                    words_positions.get(*word).map_or(false, |positions| positions.contains(*pos))
+                })
+            });
+            if starts_with {
+                exact_candidates.insert(docid);
+            }
+        }
+        candidates = exact_candidates;
+    }
+
+    Ok(if intersected { Some(candidates) } else { None })
 }
 
 #[derive(Debug, Clone)]
@@ -301,7 +439,9 @@ impl ExactQueryPart {
         part: &PrimitiveQueryPart,
     ) -> heed::Result<Self> {
         let part = match part {
-            PrimitiveQueryPart::Word(word, _) => {
+            // the stem of the word is not an exact match, unlike its synonyms. The stems are
+            // indexed apart from the words, `run` is not an exact match of `running`.
+            PrimitiveQueryPart::Word(word, ..) => {
                 match ctx.synonyms(word)? {
                     Some(synonyms) => {
                         let mut synonyms: Vec<_> = synonyms
@@ -320,7 +460,7 @@ impl ExactQueryPart {
                     None => ExactQueryPart::Synonyms(vec![word.clone()]),
                 }
             }
-            PrimitiveQueryPart::Phrase(phrase) => ExactQueryPart::Phrase(phrase.clone()),
+            PrimitiveQueryPart::Phrase(phrase, _) => ExactQueryPart::Phrase(phrase.clone()),
         };
 
         Ok(part)
@@ -332,6 +472,29 @@ struct ExactWordsCombinationCache {
     combinations: Vec<RoaringBitmap>,
 }
 
+impl ExactWordsCombinationCache {
+    /// The candidates containing all the words of the query.
+    fn all_words_candidates(&self, candidates: &RoaringBitmap) -> RoaringBitmap {
+        // the last combination contains the documents with as many words as the query.
+        match self.combinations.last() {
+            Some(all_words) => all_words & candidates,
+            None => candidates.clone(),
+        }
+    }
+}
+
+/// Retrieves the cache if it already exists, otherwise creates it.
+fn exact_words_cache<'c>(
+    ctx: &dyn Context,
+    query: &[ExactQueryPart],
+    cache: &'c mut Option<ExactWordsCombinationCache>,
+) -> Result<&'c ExactWordsCombinationCache> {
+    if cache.is_none() {
+        *cache = Some(compute_combinations(ctx, query)?);
+    }
+    Ok(cache.as_ref().unwrap())
+}
+
 fn compute_combinations(
     ctx: &dyn Context,
     query: &[ExactQueryPart],
@@ -342,16 +505,21 @@ fn compute_combinations(
         let mut candidates = RoaringBitmap::new();
         use ExactQueryPart::*;
         match part {
+            // the words of the exact attributes are only found in the exact word docids.
             Synonyms(synonyms) => {
                 for synonym in synonyms {
                     if let Some(synonym_candidates) = ctx.word_docids(synonym)? {
                         candidates |= synonym_candidates;
                     }
+                    if let Some(synonym_candidates) = ctx.exact_word_docids(synonym)? {
+                        candidates |= synonym_candidates;
+                    }
                 }
             }
-            // compute intersection on pair of words with a proximity of 0.
+            // compute intersection on pair of words with a proximity of 0,
+            // even for the phrases with a slop, to rank their exact matches first.
             Phrase(phrase) => {
-                candidates |= resolve_phrase(ctx, phrase)?;
+                candidates |= resolve_phrase(ctx, phrase, PhraseSlop::EXACT)?;
             }
         }
         parts_candidates_array.push(candidates);
@@ -368,105 +536,39 @@ fn compute_combinations(
 ///
 /// ## Implementation
 ///
-/// We do so by iteratively building a map containing the union of all the different ways to intersect `J` bitmaps among `b0,b1,...,bn`.
-/// - The key of the map is the index `i` of the last bitmap in the intersections
-/// - The value is the union of all the possible intersections of J bitmaps such that the last bitmap in the intersection is `bi`
-///
-/// For example, with the bitmaps `b0,b1,b2,b3`, this map should look like this
-/// ```text
-/// Map 0: (first iteration, contains all the combinations of 1 bitmap)
-///     // What follows are unions of intersection of bitmaps asscociated with the index of their last component
-///     0: [b0]
-///     1: [b1]
-///     2: [b2]
-///     3: [b3]
-/// Map 1: (second iteration, combinations of 2 bitmaps)
-///     1: [b0&b1]
-///     2: [b0&b2 | b1&b2]
-///     3: [b0&b3 | b1&b3 | b2&b3]
-/// Map 2: (third iteration, combinations of 3 bitmaps)
-///     2: [b0&b1&b2]
-///     3: [b0&b2&b3 | b1&b2&b3]
-/// Map 3: (fourth iteration, combinations of 4 bitmaps)
-///     3: [b0&b1&b2&b3]
-/// ```
+/// The bitmaps are counted one by one: once `b0,...,bk` have been seen, `Xi` contains the elements
+/// contained in at least `i+1` of them. Seeing `bk+1` adds to `Xi` the elements of `Xi-1` that are
+/// also in `bk+1`, the levels are thus updated from the last one to the first one so that `Xi-1`
+/// doesn't already contain `bk+1`.
 ///
-/// These maps are built one by one from the content of the preceding map.
-/// For example, to create Map 2, we look at each line of Map 1, for example:
 /// ```text
-/// 2: [b0&b2 | b1&b2]
+/// after b0: X0 = b0
+/// after b1: X1 = b0&b1,                      X0 = b0|b1
+/// after b2: X2 = b0&b1&b2, X1 = X1 | X0&b2,  X0 = b0|b1|b2
 /// ```
-/// And then for each i > 2, we compute `(b0&b2 | b1&b2) & bi = b0&b2&bi | b1&b2&bi`
-/// and then add it the new map (Map 3) under the key `i` (if it is not empty):
-/// ```text
-/// 3: [b0&b2&b3 | b1&b2&b3]
-/// 4: [b0&b2&b4 | b1&b2&b4]
-/// 5: [b0&b2&b5 | b1&b2&b5]
-/// etc.
-/// ```
-/// We only keep two maps in memory at any one point. As soon as Map J is built, we flatten Map J-1 into
-/// a single bitmap by taking the union of all of its values. This union gives us Xj-1.
-///
-/// ## Memory Usage
-/// This function is expected to be called on a maximum of 10 bitmaps. The worst case thus happens when
-/// 10 identical large bitmaps are given.
-///
-/// In the context of Meilisearch, let's imagine that we are given 10 bitmaps containing all
-/// the document ids. If the dataset contains 16 million documents, then each bitmap will take
-/// around 2MB of memory.
-///
-/// When creating Map 3, we will have, in memory:
-/// 1. The 10 original bitmaps (20MB)
-/// 2. X0 : 2MB
-/// 3. Map 1, containing 9 bitmaps: 18MB
-/// 4. Map 2, containing 8 bitmaps: 16MB
-/// 5. X1: 2MB
-/// for a total of around 60MB of memory. This roughly represents the maximum memory usage of this function.
 ///
 /// ## Time complexity
 /// Let N be the size of the given list of bitmaps and M the length of each individual bitmap.
 ///
-/// We need to create N new bitmaps. The most expensive one to create is the second one, where we need to
-/// iterate over the N keys of Map 1, and for each of those keys `k_i`, we perform `N-k_i` bitmap unions.
-/// Unioning two bitmaps is O(M), and we need to do it O(N^2) times.
-///
-/// Therefore the time complexity is O(N^3 * M).
+/// Each bitmap is intersected with and unioned into the N levels, the time complexity is
+/// therefore O(N^2 * M), instead of enumerating the ways to intersect J bitmaps among N.
+/// At most N + 1 bitmaps of size M are kept in memory besides the given ones.
 fn create_non_disjoint_combinations(bitmaps: Vec<RoaringBitmap>) -> Vec<RoaringBitmap> {
     let nbr_parts = bitmaps.len();
     if nbr_parts == 1 {
         return bitmaps;
     }
-    let mut flattened_levels = vec![];
-    let mut last_level: BTreeMap<usize, RoaringBitmap> =
-        bitmaps.clone().into_iter().enumerate().collect();
-
-    for _ in 2..=nbr_parts {
-        let mut new_level = BTreeMap::new();
-        for (last_part_index, base_combination) in last_level.iter() {
-            #[allow(clippy::needless_range_loop)]
-            for new_last_part_index in last_part_index + 1..nbr_parts {
-                let new_combination = base_combination & &bitmaps[new_last_part_index];
-                if !new_combination.is_empty() {
-                    match new_level.entry(new_last_part_index) {
-                        Entry::Occupied(mut b) => {
-                            *b.get_mut() |= new_combination;
-                        }
-                        Entry::Vacant(entry) => {
-                            entry.insert(new_combination);
-                        }
-                    }
-                }
-            }
+
+    let mut levels = vec![RoaringBitmap::new(); nbr_parts];
+    for (seen, bitmap) in bitmaps.iter().enumerate() {
+        // an element can't be contained in more bitmaps than the ones seen so far.
+        for i in (1..=seen).rev() {
+            let new_level = &levels[i - 1] & bitmap;
+            levels[i] |= new_level;
         }
-        // Now flatten the last level to save memory
-        let flattened_last_level = MultiOps::union(last_level.into_values());
-        flattened_levels.push(flattened_last_level);
-        last_level = new_level;
-    }
-    // Flatten the last level
-    let flattened_last_level = MultiOps::union(last_level.into_values());
-    flattened_levels.push(flattened_last_level);
-    flattened_levels
+        levels[0] |= bitmap;
+    }
+    levels
 }
 
 /// Given a list of bitmaps `b0,b1,...,bn` , compute the list of bitmaps `X0,X1,...,Xn`
@@ -490,6 +592,7 @@ fn create_disjoint_combinations(parts_candidates_array: Vec<RoaringBitmap>) -> V
 #[cfg(test)]
 mod tests {
     use big_s::S;
+    use maplit::hashset;
     use roaring::RoaringBitmap;
 
     use crate::index::tests::TempIndex;
@@ -497,7 +600,7 @@ mod tests {
         create_disjoint_combinations, create_non_disjoint_combinations,
     };
     use crate::snapshot_tests::display_bitmap;
-    use crate::{Criterion, SearchResult};
+    use crate::{Criterion, ExactnessConfig, SearchResult};
 
     #[test]
     fn test_exact_words_subcriterion() {
@@ -529,12 +632,143 @@ mod tests {
 
         let rtxn = index.read_txn().unwrap();
 
-        let SearchResult { matching_words: _, candidates: _, documents_ids } =
+        let SearchResult { matching_words: _, candidates: _, documents_ids, .. } =
             index.search(&rtxn).query("cats are better than dogs").execute().unwrap();
 
         insta::assert_snapshot!(format!("{documents_ids:?}"), @"[5, 4, 3, 2, 1]");
     }
 
+    #[test]
+    fn test_exact_query_subcriterion() {
+        let index = TempIndex::new();
+
+        index
+            .update_settings(|settings| {
+                settings.set_primary_key(S("id"));
+                settings.set_criteria(vec![Criterion::Exactness]);
+            })
+            .unwrap();
+
+        index
+            .add_documents(documents!([
+                // the exact words, scattered
+                { "id": "0", "text": "black is the cat and big is the dog" },
+                // the exact query, in the middle of the attribute
+                { "id": "1", "text": "my big black cat sleeps" },
+            ]))
+            .unwrap();
+
+        let rtxn = index.read_txn().unwrap();
+
+        // by default, both documents contain the 3 exact words
+        let SearchResult { documents_ids, .. } =
+            index.search(&rtxn).query("big black cat").execute().unwrap();
+        insta::assert_snapshot!(format!("{documents_ids:?}"), @"[0, 1]");
+
+        let SearchResult { documents_ids, .. } = index
+            .search(&rtxn)
+            .query("big black cat")
+            .exactness(ExactnessConfig { exact_query: true, ..Default::default() })
+            .execute()
+            .unwrap();
+        insta::assert_snapshot!(format!("{documents_ids:?}"), @"[1, 0]");
+    }
+
+    #[test]
+    fn test_long_query_levels() {
+        let index = TempIndex::new();
+
+        index
+            .update_settings(|settings| {
+                settings.set_primary_key(S("id"));
+                settings.set_criteria(vec![Criterion::Exactness]);
+            })
+            .unwrap();
+
+        index
+            .add_documents(documents!([
+                // 3 exact words
+                { "id": "0", "text": "alpha bravo charlie" },
+                // 9 exact words, scattered
+                { "id": "1", "text": "india hotel golf foxtrot echo delta charlie bravo alpha" },
+                // the exact query, in the middle of the attribute
+                { "id": "2", "text": "kilo alpha bravo charlie delta echo foxtrot golf hotel india" },
+                // attribute starts with the exact query
+                { "id": "3", "text": "alpha bravo charlie delta echo foxtrot golf hotel india juliett" },
+                // attribute equal to the exact query
+                { "id": "4", "text": "alpha bravo charlie delta echo foxtrot golf hotel india" },
+            ]))
+            .unwrap();
+
+        let rtxn = index.read_txn().unwrap();
+
+        let SearchResult { documents_ids, .. } = index
+            .search(&rtxn)
+            .query("alpha bravo charlie delta echo foxtrot golf hotel india")
+            .exactness(ExactnessConfig { exact_query: true, ..Default::default() })
+            .execute()
+            .unwrap();
+        insta::assert_snapshot!(format!("{documents_ids:?}"), @"[4, 3, 2, 1, 0]");
+    }
+
+    #[test]
+    fn test_attribute_start_beyond_exact_positions() {
+        let index = TempIndex::new();
+
+        index
+            .update_settings(|settings| {
+                settings.set_primary_key(S("id"));
+                settings.set_criteria(vec![Criterion::Exactness]);
+            })
+            .unwrap();
+
+        let query = "alpha bravo charlie delta echo foxtrot golf hotel india juliett kilo lima mike november oscar papa quebec romeo";
+        index
+            .add_documents(documents!([
+                // the two last words are swapped, in the same position bucket
+                { "id": "0", "text": "alpha bravo charlie delta echo foxtrot golf hotel india juliett kilo lima mike november oscar papa romeo quebec sierra" },
+                // attribute starts with the exact query
+                { "id": "1", "text": format!("{query} sierra") },
+            ]))
+            .unwrap();
+
+        let rtxn = index.read_txn().unwrap();
+
+        let SearchResult { documents_ids, .. } =
+            index.search(&rtxn).query(query).words_limit(18).execute().unwrap();
+        insta::assert_snapshot!(format!("{documents_ids:?}"), @"[1, 0]");
+    }
+
+    #[test]
+    fn test_exact_words_in_exact_attributes() {
+        let index = TempIndex::new();
+
+        index
+            .update_settings(|settings| {
+                settings.set_primary_key(S("id"));
+                settings.set_criteria(vec![Criterion::Exactness]);
+                settings.set_exact_attributes(hashset! { S("text") });
+            })
+            .unwrap();
+
+        index
+            .add_documents(documents!([
+                // 1 exact word
+                { "id": "0", "text": "the cats" },
+                // 2 exact words
+                { "id": "1", "text": "the cats and dogs" },
+                // 3 exact words
+                { "id": "2", "text": "the cats and dogs and birds" },
+            ]))
+            .unwrap();
+
+        let rtxn = index.read_txn().unwrap();
+
+        let SearchResult { documents_ids, .. } =
+            index.search(&rtxn).query("cats dogs birds").execute().unwrap();
+        insta::assert_snapshot!(format!("{documents_ids:?}"), @"[2, 1, 0]");
+    }
+
     fn print_combinations(rbs: &[RoaringBitmap]) -> String {
         let mut s = String::new();
         for rb in rbs {
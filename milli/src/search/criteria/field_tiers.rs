@@ -0,0 +1,218 @@
+use std::collections::VecDeque;
+
+use log::debug;
+use roaring::RoaringBitmap;
+
+use super::{
+    resolve_phrase, resolve_query_tree, Context, Criterion, CriterionParameters, CriterionResult,
+};
+use crate::search::criteria::InitialCandidates;
+use crate::search::query_tree::{Operation, Query, QueryKind};
+use crate::search::{word_derivations, WordDerivationsCache};
+use crate::{relative_from_absolute_position, FieldId, Result};
+
+/// Splits the buckets of its parent into tiers of fields, see
+/// [`Search::field_tiers`](crate::search::Search::field_tiers).
+///
+/// The first tier contains the candidates matching the query in the first field,
+/// the second tier the remaining candidates matching the query in the second field
+/// and so on. The candidates that don't match the query in any of the fields are
+/// returned in a last tier. Every tier is returned as a separate bucket with the query tree
+/// of the parent bucket, which makes the following criteria rank the candidates of a tier
+/// independently of the other tiers.
+pub struct FieldTiers<'t> {
+    ctx: &'t dyn Context<'t>,
+    fields_ids: Vec<FieldId>,
+    /// The tiers of the current bucket that are still to be returned.
+    tiers: VecDeque<CriterionResult>,
+    parent: Box<dyn Criterion + 't>,
+}
+
+impl<'t> FieldTiers<'t> {
+    pub fn new(
+        ctx: &'t dyn Context<'t>,
+        parent: Box<dyn Criterion + 't>,
+        fields_ids: Vec<FieldId>,
+    ) -> Self {
+        FieldTiers { ctx, fields_ids, tiers: VecDeque::new(), parent }
+    }
+}
+
+impl<'t> Criterion for FieldTiers<'t> {
+    #[logging_timer::time("FieldTiers::{}")]
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            level = "trace",
+            name = "FieldTiers::next",
+            skip_all,
+            fields(excluded_candidates = params.excluded_candidates.len())
+        )
+    )]
+    fn next(&mut self, params: &mut CriterionParameters) -> Result<Option<CriterionResult>> {
+        while let Some(mut tier) = self.tiers.pop_front() {
+            if let Some(candidates) = tier.candidates.as_mut() {
+                *candidates -= params.excluded_candidates;
+                if !candidates.is_empty() {
+                    return Ok(Some(tier));
+                }
+            }
+        }
+
+        match self.parent.next(params)? {
+            Some(CriterionResult {
+                query_tree: Some(query_tree),
+                candidates,
+                filtered_candidates,
+                initial_candidates,
+            }) => {
+                let mut candidates = match candidates {
+                    Some(candidates) => candidates,
+                    None => {
+                        resolve_query_tree(self.ctx, &query_tree, params.wdcache)?
+                            - params.excluded_candidates
+                    }
+                };
+
+                if let Some(filtered_candidates) = filtered_candidates {
+                    candidates &= filtered_candidates;
+                }
+
+                let mut initial_candidates = Some(
+                    initial_candidates
+                        .unwrap_or_else(|| InitialCandidates::Estimated(candidates.clone())),
+                );
+
+                for &field_id in &self.fields_ids {
+                    if candidates.is_empty() {
+                        break;
+                    }
+                    let mut field_candidates = resolve_query_tree_in_field(
+                        self.ctx,
+                        &query_tree,
+                        field_id,
+                        params.wdcache,
+                    )?;
+                    field_candidates &= &candidates;
+                    candidates -= &field_candidates;
+                    debug!(
+                        "FieldTiers field {} matches {} candidates",
+                        field_id,
+                        field_candidates.len()
+                    );
+                    self.tiers.push_back(CriterionResult {
+                        query_tree: Some(query_tree.clone()),
+                        candidates: Some(field_candidates),
+                        filtered_candidates: None,
+                        initial_candidates: initial_candidates.take(),
+                    });
+                }
+
+                self.tiers.push_back(CriterionResult {
+                    query_tree: Some(query_tree),
+                    candidates: Some(candidates),
+                    filtered_candidates: None,
+                    initial_candidates: initial_candidates.take(),
+                });
+
+                // the first tier carries the initial candidates, even if it is empty.
+                let mut first = self.tiers.pop_front().unwrap();
+                if let Some(candidates) = first.candidates.as_mut() {
+                    *candidates -= params.excluded_candidates;
+                }
+                Ok(Some(first))
+            }
+            Some(result) => Ok(Some(result)),
+            None => Ok(None),
+        }
+    }
+}
+
+/// Returns the documents matching the query tree with the words of its leaves
+/// all located in the given field.
+///
+/// The phrases are matched using the word pair proximities of the whole documents and are
+/// only restricted to the documents containing all the words of the phrase in the field.
+fn resolve_query_tree_in_field(
+    ctx: &dyn Context,
+    query_tree: &Operation,
+    field_id: FieldId,
+    wdcache: &mut WordDerivationsCache,
+) -> Result<RoaringBitmap> {
+    use Operation::{And, Or, Phrase, Query};
+
+    match query_tree {
+        And(ops) => {
+            let mut candidates: Option<RoaringBitmap> = None;
+            for op in ops {
+                let docids = resolve_query_tree_in_field(ctx, op, field_id, wdcache)?;
+                match candidates.as_mut() {
+                    Some(candidates) => *candidates &= docids,
+                    None => candidates = Some(docids),
+                }
+                if candidates.as_ref().map_or(false, RoaringBitmap::is_empty) {
+                    break;
+                }
+            }
+            Ok(candidates.unwrap_or_default())
+        }
+        Phrase(words, slop) => {
+            let mut candidates = resolve_phrase(ctx, words, *slop)?;
+            for word in words.iter().flatten() {
+                if candidates.is_empty() {
+                    break;
+                }
+                candidates &= word_docids_in_field(ctx, word, false, field_id)?;
+            }
+            Ok(candidates)
+        }
+        Or(_, ops) => {
+            let mut candidates = RoaringBitmap::new();
+            for op in ops {
+                candidates |= resolve_query_tree_in_field(ctx, op, field_id, wdcache)?;
+            }
+            Ok(candidates)
+        }
+        Query(query) => query_docids_in_field(ctx, query, field_id, wdcache),
+    }
+}
+
+fn query_docids_in_field(
+    ctx: &dyn Context,
+    query: &Query,
+    field_id: FieldId,
+    wdcache: &mut WordDerivationsCache,
+) -> Result<RoaringBitmap> {
+    let (word, typo) = match &query.kind {
+        QueryKind::Exact { word, .. } => (word, 0),
+        QueryKind::Tolerant { word, typo } => (word, *typo),
+    };
+
+    if query.prefix && typo == 0 && ctx.in_prefix_cache(word) {
+        return word_docids_in_field(ctx, word, true, field_id);
+    }
+
+    let mut docids = RoaringBitmap::new();
+    for (word, _typo) in word_derivations(word, query.prefix, typo, ctx.words_fst(), wdcache)? {
+        docids |= word_docids_in_field(ctx, word, false, field_id)?;
+    }
+    Ok(docids)
+}
+
+/// Returns the documents containing the word, or a word starting with the prefix
+/// when `in_prefix_cache` is `true`, in the given field.
+fn word_docids_in_field(
+    ctx: &dyn Context,
+    word: &str,
+    in_prefix_cache: bool,
+    field_id: FieldId,
+) -> Result<RoaringBitmap> {
+    let mut docids = RoaringBitmap::new();
+    for result in ctx.word_position_iterator(word, in_prefix_cache)? {
+        let ((_, position), positions_docids) = result?;
+        if relative_from_absolute_position(position).0 == field_id {
+            docids |= positions_docids;
+        }
+    }
+    Ok(docids)
+}
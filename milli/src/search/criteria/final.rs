@@ -36,6 +36,18 @@ impl<'t> Final<'t> {
     }
 
     #[logging_timer::time("Final::{}")]
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            level = "trace",
+            name = "Final::next",
+            skip_all,
+            fields(
+                excluded_candidates = excluded_candidates.len(),
+                candidates = tracing::field::Empty,
+            )
+        )
+    )]
     pub fn next(&mut self, excluded_candidates: &RoaringBitmap) -> Result<Option<FinalResult>> {
         debug!("Final iteration");
         let excluded_candidates = &self.returned_candidates | excluded_candidates;
@@ -69,6 +81,9 @@ impl<'t> Final<'t> {
 
                 self.returned_candidates |= &candidates;
 
+                #[cfg(feature = "tracing")]
+                tracing::Span::current().record("candidates", candidates.len());
+
                 Ok(Some(FinalResult { query_tree, candidates, initial_candidates }))
             }
             None => Ok(None),
@@ -65,6 +65,15 @@ impl<'t> Geo<'t> {
 }
 
 impl Criterion for Geo<'_> {
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            level = "trace",
+            name = "Geo::next",
+            skip_all,
+            fields(excluded_candidates = params.excluded_candidates.len())
+        )
+    )]
     fn next(&mut self, params: &mut CriterionParameters) -> Result<Option<CriterionResult>> {
         let rtree = self.rtree.as_ref();
 
@@ -0,0 +1,263 @@
+use std::collections::{BTreeSet, HashMap, VecDeque};
+
+use log::debug;
+use roaring::RoaringBitmap;
+
+use super::{resolve_query_tree, Context, Criterion, CriterionParameters, CriterionResult};
+use crate::search::criteria::InitialCandidates;
+use crate::search::query_tree::{Operation, QueryKind};
+use crate::search::{word_derivations, WordDerivationsCache};
+use crate::{relative_from_absolute_position, FieldId, Result};
+
+/// Demotes the candidates matching the query in short fields only, see
+/// [`Criterion::Length`](crate::Criterion::Length).
+///
+/// The length of the match of a candidate is the word count of the longest searchable field
+/// containing a word of the query, as stored in the `field_id_word_count_docids` database.
+/// The candidates matching in a field of at least `min_words` words, or in a field longer than
+/// the counted word counts, are returned in the first bucket. The other candidates follow in
+/// one bucket per match length, by decreasing length, so that a document whose only match is
+/// a one-word title comes after the documents matching the query in context.
+pub struct Length<'t> {
+    ctx: &'t dyn Context<'t>,
+    min_words: u8,
+    /// The buckets of the current parent bucket that are still to be returned.
+    buckets: VecDeque<CriterionResult>,
+    parent: Box<dyn Criterion + 't>,
+}
+
+impl<'t> Length<'t> {
+    pub fn new(ctx: &'t dyn Context<'t>, parent: Box<dyn Criterion + 't>, min_words: u8) -> Self {
+        Length { ctx, min_words, buckets: VecDeque::new(), parent }
+    }
+}
+
+impl<'t> Criterion for Length<'t> {
+    #[logging_timer::time("Length::{}")]
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            level = "trace",
+            name = "Length::next",
+            skip_all,
+            fields(excluded_candidates = params.excluded_candidates.len())
+        )
+    )]
+    fn next(&mut self, params: &mut CriterionParameters) -> Result<Option<CriterionResult>> {
+        while let Some(mut bucket) = self.buckets.pop_front() {
+            if let Some(candidates) = bucket.candidates.as_mut() {
+                *candidates -= params.excluded_candidates;
+                if !candidates.is_empty() {
+                    return Ok(Some(bucket));
+                }
+            }
+        }
+
+        match self.parent.next(params)? {
+            Some(CriterionResult {
+                query_tree: Some(query_tree),
+                candidates,
+                filtered_candidates,
+                initial_candidates,
+            }) => {
+                let mut candidates = match candidates {
+                    Some(candidates) => candidates,
+                    None => {
+                        resolve_query_tree(self.ctx, &query_tree, params.wdcache)?
+                            - params.excluded_candidates
+                    }
+                };
+
+                if let Some(filtered_candidates) = filtered_candidates {
+                    candidates &= filtered_candidates;
+                }
+
+                let mut initial_candidates = Some(
+                    initial_candidates
+                        .unwrap_or_else(|| InitialCandidates::Estimated(candidates.clone())),
+                );
+
+                for candidates in length_buckets(
+                    self.ctx,
+                    &query_tree,
+                    candidates,
+                    self.min_words,
+                    params.wdcache,
+                )? {
+                    self.buckets.push_back(CriterionResult {
+                        query_tree: Some(query_tree.clone()),
+                        candidates: Some(candidates),
+                        filtered_candidates: None,
+                        initial_candidates: initial_candidates.take(),
+                    });
+                }
+
+                // the first bucket carries the initial candidates, even if it is empty.
+                let mut first = self.buckets.pop_front().unwrap();
+                if let Some(candidates) = first.candidates.as_mut() {
+                    *candidates -= params.excluded_candidates;
+                }
+                Ok(Some(first))
+            }
+            Some(result) => Ok(Some(result)),
+            None => Ok(None),
+        }
+    }
+}
+
+/// Splits the candidates into the buckets of decreasing match length, the first bucket
+/// containing the candidates whose match is at least `min_words` long.
+///
+/// The candidates that don't contain any word of the query in a searchable field,
+/// e.g. when matching synonyms only, are kept in the first bucket.
+fn length_buckets(
+    ctx: &dyn Context,
+    query_tree: &Operation,
+    candidates: RoaringBitmap,
+    min_words: u8,
+    wdcache: &mut WordDerivationsCache,
+) -> Result<Vec<RoaringBitmap>> {
+    let matched_fields = matched_fields_docids(ctx, query_tree, wdcache)?;
+
+    let mut short_matches = RoaringBitmap::new();
+    let mut short_fields_docids = HashMap::new();
+    for (&field_id, matched) in &matched_fields {
+        let mut short_docids = RoaringBitmap::new();
+        for word_count in 1..min_words {
+            if let Some(docids) = ctx.field_id_word_count_docids(field_id, word_count)? {
+                short_docids |= docids;
+            }
+        }
+        short_matches |= matched & &short_docids;
+        short_fields_docids.insert(field_id, short_docids);
+    }
+    // a match in a field that is not short is enough for the candidate to be kept first.
+    let mut long_matches = RoaringBitmap::new();
+    for (field_id, matched) in &matched_fields {
+        long_matches |= matched - &short_fields_docids[field_id];
+    }
+
+    let mut remaining = (short_matches - long_matches) & &candidates;
+    let mut buckets = vec![candidates - &remaining];
+    for word_count in (1..min_words).rev() {
+        if remaining.is_empty() {
+            break;
+        }
+        let mut bucket = RoaringBitmap::new();
+        for (&field_id, matched) in &matched_fields {
+            if let Some(docids) = ctx.field_id_word_count_docids(field_id, word_count)? {
+                bucket |= docids & matched & &remaining;
+            }
+        }
+        remaining -= &bucket;
+        debug!("Length match of {} words for {} candidates", word_count, bucket.len());
+        buckets.push(bucket);
+    }
+
+    Ok(buckets)
+}
+
+/// Returns, for each field, the documents containing a word of the query tree in this field.
+fn matched_fields_docids(
+    ctx: &dyn Context,
+    query_tree: &Operation,
+    wdcache: &mut WordDerivationsCache,
+) -> Result<HashMap<FieldId, RoaringBitmap>> {
+    let mut words = BTreeSet::new();
+    query_tree_words(ctx, query_tree, wdcache, &mut words)?;
+
+    let mut matched_fields: HashMap<FieldId, RoaringBitmap> = HashMap::new();
+    for (word, in_prefix_cache) in words {
+        for result in ctx.word_position_iterator(&word, in_prefix_cache)? {
+            let ((_, position), docids) = result?;
+            let (field_id, _) = relative_from_absolute_position(position);
+            *matched_fields.entry(field_id).or_default() |= docids;
+        }
+    }
+
+    Ok(matched_fields)
+}
+
+/// Collects the words matched by the leaves of the query tree, along with whether the word
+/// must be looked up as a prefix in the prefix databases.
+fn query_tree_words(
+    ctx: &dyn Context,
+    query_tree: &Operation,
+    wdcache: &mut WordDerivationsCache,
+    words: &mut BTreeSet<(String, bool)>,
+) -> Result<()> {
+    use Operation::{And, Or, Phrase, Query};
+
+    match query_tree {
+        And(ops) | Or(_, ops) => {
+            for op in ops {
+                query_tree_words(ctx, op, wdcache, words)?;
+            }
+        }
+        Phrase(phrase, _) => {
+            words.extend(phrase.iter().flatten().map(|word| (word.clone(), false)));
+        }
+        Query(query) => {
+            let (word, typo) = match &query.kind {
+                QueryKind::Exact { word, .. } => (word, 0),
+                QueryKind::Tolerant { word, typo } => (word, *typo),
+            };
+
+            if query.prefix && typo == 0 && ctx.in_prefix_cache(word) {
+                words.insert((word.clone(), true));
+            } else {
+                let derivations =
+                    word_derivations(word, query.prefix, typo, ctx.words_fst(), wdcache)?;
+                words.extend(derivations.iter().map(|(word, _typo)| (word.clone(), false)));
+            }
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use big_s::S;
+
+    use crate::index::tests::TempIndex;
+    use crate::{Criterion, Search, SearchResult, TermsMatchingStrategy};
+
+    #[test]
+    fn short_title_match_is_demoted() {
+        let index = TempIndex::new();
+        index
+            .update_settings(|settings| {
+                settings.set_searchable_fields(vec![S("title"), S("body")]);
+                settings.set_criteria(vec![
+                    Criterion::Words,
+                    Criterion::Typo,
+                    Criterion::Length(3),
+                    Criterion::Exactness,
+                ]);
+            })
+            .unwrap();
+        index
+            .add_documents(documents!([
+                { "id": 0, "title": "headphones", "body": "buy now cheap" },
+                { "id": 1, "title": "wireless headphones review", "body": "a long test" },
+                { "id": 2, "title": "review", "body": "the best headphones of the year" },
+                { "id": 3, "title": "headphones case", "body": "" },
+            ]))
+            .unwrap();
+
+        let rtxn = index.read_txn().unwrap();
+        let mut search = Search::new(&rtxn, &index);
+        search.query("headphones");
+        search.terms_matching_strategy(TermsMatchingStrategy::All);
+
+        // the contextual matches come first, ranked by exactness, then the two-word
+        // title and finally the one-word title, even though it is an exact match.
+        let SearchResult { documents_ids, .. } = search.execute().unwrap();
+        assert_eq!(documents_ids, vec![1, 2, 3, 0]);
+
+        // the lazy search ranks the buckets the same way
+        let lazy: Vec<_> = search.execute_iter().unwrap().flat_map(Result::unwrap).collect();
+        assert_eq!(lazy, documents_ids);
+    }
+}
@@ -0,0 +1,108 @@
+use log::debug;
+use roaring::RoaringBitmap;
+
+use super::{resolve_query_tree, Context, Criterion, CriterionParameters, CriterionResult};
+use crate::search::criteria::InitialCandidates;
+use crate::Result;
+
+/// Bounds the number of candidates ranked by the following criteria.
+///
+/// When a bucket of its parent contains more than `max_candidates` documents,
+/// only its first `max_candidates` documents by internal id are forwarded with the query tree,
+/// and thus ranked by the following criteria. The other documents of the bucket are returned
+/// afterward, in a single bucket without query tree, which makes the following criteria
+/// only sort them by the criteria that do not depend on the query (e.g. `asc`, `desc`).
+pub struct MaxCandidates<'t> {
+    ctx: &'t dyn Context<'t>,
+    max_candidates: u64,
+    /// The candidates of the current bucket that were not forwarded to be ranked.
+    remaining_candidates: Option<RoaringBitmap>,
+    parent: Box<dyn Criterion + 't>,
+}
+
+impl<'t> MaxCandidates<'t> {
+    pub fn new(
+        ctx: &'t dyn Context<'t>,
+        parent: Box<dyn Criterion + 't>,
+        max_candidates: usize,
+    ) -> Self {
+        MaxCandidates {
+            ctx,
+            max_candidates: max_candidates as u64,
+            remaining_candidates: None,
+            parent,
+        }
+    }
+}
+
+impl<'t> Criterion for MaxCandidates<'t> {
+    #[logging_timer::time("MaxCandidates::{}")]
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            level = "trace",
+            name = "MaxCandidates::next",
+            skip_all,
+            fields(excluded_candidates = params.excluded_candidates.len())
+        )
+    )]
+    fn next(&mut self, params: &mut CriterionParameters) -> Result<Option<CriterionResult>> {
+        if let Some(mut remaining_candidates) = self.remaining_candidates.take() {
+            remaining_candidates -= params.excluded_candidates;
+            if !remaining_candidates.is_empty() {
+                debug!("MaxCandidates returns {} unranked candidates", remaining_candidates.len());
+                return Ok(Some(CriterionResult {
+                    query_tree: None,
+                    candidates: Some(remaining_candidates),
+                    filtered_candidates: None,
+                    initial_candidates: None,
+                }));
+            }
+        }
+
+        match self.parent.next(params)? {
+            Some(CriterionResult {
+                query_tree,
+                candidates,
+                filtered_candidates,
+                initial_candidates,
+            }) => {
+                let mut candidates = match (candidates, query_tree.as_ref()) {
+                    (Some(candidates), _) => candidates,
+                    (None, Some(qt)) => {
+                        resolve_query_tree(self.ctx, qt, params.wdcache)?
+                            - params.excluded_candidates
+                    }
+                    (None, None) => self.ctx.documents_ids()? - params.excluded_candidates,
+                };
+
+                if let Some(filtered_candidates) = filtered_candidates {
+                    candidates &= filtered_candidates;
+                }
+
+                let initial_candidates = initial_candidates
+                    .unwrap_or_else(|| InitialCandidates::Estimated(candidates.clone()));
+
+                if candidates.len() > self.max_candidates {
+                    let ranked: RoaringBitmap =
+                        candidates.iter().take(self.max_candidates as usize).collect();
+                    debug!(
+                        "MaxCandidates ranks the first {} of the {} candidates",
+                        ranked.len(),
+                        candidates.len()
+                    );
+                    self.remaining_candidates = Some(candidates - &ranked);
+                    candidates = ranked;
+                }
+
+                Ok(Some(CriterionResult {
+                    query_tree,
+                    candidates: Some(candidates),
+                    filtered_candidates: None,
+                    initial_candidates: Some(initial_candidates),
+                }))
+            }
+            None => Ok(None),
+        }
+    }
+}
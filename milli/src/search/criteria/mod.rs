@@ -6,27 +6,40 @@ use std::ops::{BitOr, BitOrAssign};
 use roaring::RoaringBitmap;
 
 use self::asc_desc::AscDesc;
+pub(crate) use self::attribute::attribute_rank;
 use self::attribute::Attribute;
 use self::exactness::Exactness;
+use self::field_tiers::FieldTiers;
 use self::initial::Initial;
+use self::length::Length;
+use self::max_candidates::MaxCandidates;
 use self::proximity::Proximity;
 use self::r#final::Final;
+pub use self::timed::CriteriaTimings;
 use self::typo::Typo;
 use self::words::Words;
-use super::query_tree::{Operation, PrimitiveQueryPart, Query, QueryKind};
-use super::CriterionImplementationStrategy;
+use super::query_tree::{Operation, PhraseSlop, PrimitiveQueryPart, Query, QueryKind};
+use super::{CriterionImplementationStrategy, ExactnessConfig, MissingValues, PhraseOrder};
+use crate::proximity::MAX_DISTANCE;
 use crate::search::criteria::geo::Geo;
 use crate::search::{word_derivations, Distinct, WordDerivationsCache};
 use crate::update::{MAX_LENGTH_FOR_PREFIX_PROXIMITY_DB, MAX_PROXIMITY_FOR_PREFIX_PROXIMITY_DB};
 use crate::{AscDesc as AscDescName, DocumentId, FieldId, Index, Member, Result};
 
+/// The scale used to convert the weight of a field into an integer rank.
+const FIELD_RANK_SCALE: f32 = 1000.0;
+
 mod asc_desc;
 mod attribute;
 mod exactness;
+mod field_tiers;
 pub mod r#final;
 mod geo;
 mod initial;
+mod length;
+mod max_candidates;
 mod proximity;
+mod timed;
 mod typo;
 mod words;
 
@@ -179,6 +192,8 @@ pub trait Context<'c> {
         word_count: u8,
     ) -> heed::Result<Option<RoaringBitmap>>;
     fn word_position_docids(&self, word: &str, pos: u32) -> heed::Result<Option<RoaringBitmap>>;
+    fn proximity_databases(&self) -> heed::Result<bool>;
+    fn bucketed_positions(&self) -> heed::Result<bool>;
 }
 
 pub struct CriteriaBuilder<'t> {
@@ -362,6 +377,14 @@ impl<'c> Context<'c> for CriteriaBuilder<'c> {
         let key = (word, pos);
         self.index.word_position_docids.get(self.rtxn, &key)
     }
+
+    fn proximity_databases(&self) -> heed::Result<bool> {
+        self.index.proximity_databases(self.rtxn)
+    }
+
+    fn bucketed_positions(&self) -> heed::Result<bool> {
+        self.index.bucketed_positions(self.rtxn)
+    }
 }
 
 impl<'t> CriteriaBuilder<'t> {
@@ -371,6 +394,36 @@ impl<'t> CriteriaBuilder<'t> {
         Ok(Self { rtxn, index, words_fst, words_prefixes_fst })
     }
 
+    /// Returns the rank of each searchable field according to its position in the searchable
+    /// attributes and its boost, a lower rank being better, or `None` if all the searchable
+    /// fields have the neutral boost of `1.0`.
+    fn field_ranks(&self) -> Result<Option<HashMap<FieldId, u64>>> {
+        let field_boosts = self.index.field_boosts(self.rtxn)?;
+        let fields_ids_map = self.index.fields_ids_map(self.rtxn)?;
+        let searchable_fields_ids = self.searchable_fields_ids()?;
+        let boost = |fid| {
+            fields_ids_map.name(fid).and_then(|name| field_boosts.get(name)).copied().unwrap_or(1.0)
+        };
+        if searchable_fields_ids.iter().all(|fid| boost(*fid) == 1.0) {
+            return Ok(None);
+        }
+
+        let field_ranks = searchable_fields_ids
+            .into_iter()
+            .enumerate()
+            .map(|(i, fid)| {
+                let boost = boost(fid);
+                // the weight of a field decreases with its position in the searchable
+                // attributes and is multiplied by its boost.
+                let weight = boost.max(0.0) / (i + 1) as f32;
+                let rank = if weight > 0.0 { (FIELD_RANK_SCALE / weight) as u64 } else { u64::MAX };
+                (fid, rank)
+            })
+            .collect();
+
+        Ok(Some(field_ranks))
+    }
+
     #[allow(clippy::too_many_arguments)]
     pub fn build<D: 't + Distinct>(
         &'t self,
@@ -378,9 +431,15 @@ impl<'t> CriteriaBuilder<'t> {
         primitive_query: Option<Vec<PrimitiveQueryPart>>,
         filtered_candidates: Option<RoaringBitmap>,
         sort_criteria: Option<Vec<AscDescName>>,
+        sort_missing_values: MissingValues,
+        disabled_criteria: &[crate::Criterion],
         exhaustive_number_hits: bool,
         distinct: Option<D>,
         implementation_strategy: CriterionImplementationStrategy,
+        exactness: ExactnessConfig,
+        max_candidates: Option<usize>,
+        field_tiers: &[String],
+        timings: Option<&CriteriaTimings>,
     ) -> Result<Final<'t>> {
         use crate::criterion::Criterion as Name;
 
@@ -393,7 +452,30 @@ impl<'t> CriteriaBuilder<'t> {
             exhaustive_number_hits,
             distinct,
         )) as Box<dyn Criterion>;
+        if !field_tiers.is_empty() {
+            let fields_ids_map = self.index.fields_ids_map(self.rtxn)?;
+            let fields_ids =
+                field_tiers.iter().filter_map(|name| fields_ids_map.id(name)).collect();
+            criterion = Box::new(FieldTiers::new(self, criterion, fields_ids));
+        }
+        let mut max_candidates = max_candidates;
+        let proximity_databases = self.index.proximity_databases(self.rtxn)?;
         for name in self.index.criteria(self.rtxn)? {
+            if disabled_criteria.contains(&name) {
+                continue;
+            }
+            // the proximity criterion can't rank the documents without the proximity databases
+            if name == Name::Proximity && !proximity_databases {
+                continue;
+            }
+            // The candidates are bounded right after the cheapest criteria,
+            // before the first criterion that is costly to compute on large buckets.
+            if !matches!(name, Name::Words | Name::Typo) {
+                if let Some(max_candidates) = max_candidates.take() {
+                    criterion = Box::new(MaxCandidates::new(self, criterion, max_candidates));
+                }
+            }
+            let timed_name = timings.map(|_| name.to_string());
             criterion = match name {
                 Name::Words => Box::new(Words::new(self, criterion)),
                 Name::Typo => Box::new(Typo::new(self, criterion)),
@@ -406,6 +488,7 @@ impl<'t> CriteriaBuilder<'t> {
                                     self.rtxn,
                                     criterion,
                                     field.to_string(),
+                                    sort_missing_values,
                                     implementation_strategy,
                                 )?),
                                 AscDescName::Desc(Member::Field(field)) => Box::new(AscDesc::desc(
@@ -413,6 +496,7 @@ impl<'t> CriteriaBuilder<'t> {
                                     self.rtxn,
                                     criterion,
                                     field.to_string(),
+                                    sort_missing_values,
                                     implementation_strategy,
                                 )?),
                                 AscDescName::Asc(Member::Geo(point)) => {
@@ -430,15 +514,21 @@ impl<'t> CriteriaBuilder<'t> {
                 Name::Proximity => {
                     Box::new(Proximity::new(self, criterion, implementation_strategy))
                 }
-                Name::Attribute => {
-                    Box::new(Attribute::new(self, criterion, implementation_strategy))
+                Name::Attribute => Box::new(Attribute::new(
+                    self,
+                    criterion,
+                    implementation_strategy,
+                    self.field_ranks()?,
+                )),
+                Name::Exactness => {
+                    Box::new(Exactness::new(self, criterion, &primitive_query, exactness)?)
                 }
-                Name::Exactness => Box::new(Exactness::new(self, criterion, &primitive_query)?),
                 Name::Asc(field) => Box::new(AscDesc::asc(
                     self.index,
                     self.rtxn,
                     criterion,
                     field,
+                    MissingValues::Last,
                     implementation_strategy,
                 )?),
                 Name::Desc(field) => Box::new(AscDesc::desc(
@@ -446,9 +536,21 @@ impl<'t> CriteriaBuilder<'t> {
                     self.rtxn,
                     criterion,
                     field,
+                    MissingValues::Last,
                     implementation_strategy,
                 )?),
+                Name::Boost(field) => Box::new(AscDesc::boost(
+                    self.index,
+                    self.rtxn,
+                    criterion,
+                    field,
+                    implementation_strategy,
+                )?),
+                Name::Length(min_words) => Box::new(Length::new(self, criterion, min_words)),
             };
+            if let (Some(timings), Some(timed_name)) = (timings, timed_name) {
+                criterion = timings.wrap(timed_name, criterion);
+            }
         }
 
         Ok(Final::new(self, criterion))
@@ -488,7 +590,7 @@ pub fn resolve_query_tree(
                 }
                 Ok(candidates)
             }
-            Phrase(words) => resolve_phrase(ctx, words),
+            Phrase(words, slop) => resolve_phrase(ctx, words, *slop),
             Or(_, ops) => {
                 let mut candidates = RoaringBitmap::new();
                 for op in ops {
@@ -504,7 +606,13 @@ pub fn resolve_query_tree(
     resolve_operation(ctx, query_tree, wdcache)
 }
 
-pub fn resolve_phrase(ctx: &dyn Context, phrase: &[Option<String>]) -> Result<RoaringBitmap> {
+/// Returns the documents containing the words of the phrase close to each other, the
+/// consecutive words being at most at the proximity of the slop, in the order of the slop.
+pub fn resolve_phrase(
+    ctx: &dyn Context,
+    phrase: &[Option<String>],
+    slop: PhraseSlop,
+) -> Result<RoaringBitmap> {
     let mut candidates = RoaringBitmap::new();
     let mut first_iter = true;
     let winsize = phrase.len().min(3);
@@ -513,6 +621,21 @@ pub fn resolve_phrase(ctx: &dyn Context, phrase: &[Option<String>]) -> Result<Ro
         return Ok(candidates);
     }
 
+    // without the proximity databases, the phrase matches the documents containing all its words
+    if !ctx.proximity_databases()? {
+        for word in phrase.iter().flatten() {
+            let docids = ctx.word_docids(word)?.unwrap_or_default()
+                | ctx.exact_word_docids(word)?.unwrap_or_default();
+            if first_iter {
+                candidates = docids;
+                first_iter = false;
+            } else {
+                candidates &= docids;
+            }
+        }
+        return Ok(candidates);
+    }
+
     for win in phrase.windows(winsize) {
         // Get all the documents with the matching distance for each word pairs.
         let mut bitmaps = Vec::with_capacity(winsize.pow(2));
@@ -527,25 +650,27 @@ pub fn resolve_phrase(ctx: &dyn Context, phrase: &[Option<String>]) -> Result<Ro
                 .enumerate()
                 .filter_map(|(index, word)| word.as_ref().map(|word| (index, word)))
             {
-                if dist == 0 {
-                    match ctx.word_pair_proximity_docids(s1, s2, 1)? {
-                        Some(m) => bitmaps.push(m),
-                        // If there are no document for this pair, there will be no
-                        // results for the phrase query.
-                        None => return Ok(RoaringBitmap::new()),
+                // the words separated by `dist` words in the phrase can be `dist + 1` times
+                // the slop apart, e.g. the consecutive words of an exact phrase are adjacent.
+                let max_proximity =
+                    ((dist + 1) * slop.proximity as usize).min(MAX_DISTANCE as usize - 1) as u8;
+                let mut bitmap = RoaringBitmap::new();
+                for proximity in 1..=max_proximity {
+                    if let Some(m) = ctx.word_pair_proximity_docids(s1, s2, proximity)? {
+                        bitmap |= m
                     }
-                } else {
-                    let mut bitmap = RoaringBitmap::new();
-                    for dist in 0..=dist {
-                        if let Some(m) = ctx.word_pair_proximity_docids(s1, s2, dist as u8 + 1)? {
+                    if slop.order == PhraseOrder::AnyOrder {
+                        if let Some(m) = ctx.word_pair_proximity_docids(s2, s1, proximity)? {
                             bitmap |= m
                         }
                     }
-                    if bitmap.is_empty() {
-                        return Ok(bitmap);
-                    } else {
-                        bitmaps.push(bitmap);
-                    }
+                }
+                // If there are no document for this pair, there will be no
+                // results for the phrase query.
+                if bitmap.is_empty() {
+                    return Ok(bitmap);
+                } else {
+                    bitmaps.push(bitmap);
                 }
             }
         }
@@ -566,6 +691,37 @@ pub fn resolve_phrase(ctx: &dyn Context, phrase: &[Option<String>]) -> Result<Ro
             }
         }
     }
+
+    // The pairs of words are stored at their proximity in order and at this proximity
+    // minus one in the reverse order, the words of the phrases that must be in order but
+    // aren't necessarily adjacent are checked against their positions.
+    if slop.order == PhraseOrder::InOrder && slop.proximity > 1 && !candidates.is_empty() {
+        let words: Vec<_> = phrase
+            .iter()
+            .enumerate()
+            .filter_map(|(index, word)| word.as_ref().map(|word| (index, word.as_str())))
+            .collect();
+        let mut in_order = RoaringBitmap::new();
+        for docid in candidates.iter() {
+            let positions = ctx.docid_words_positions(docid)?;
+            let is_in_order = words.windows(2).all(|pair| {
+                let ((left_index, left), (right_index, right)) = (pair[0], pair[1]);
+                let max_proximity = ((right_index - left_index) * slop.proximity as usize)
+                    .min(MAX_DISTANCE as usize - 1) as u32;
+                match (positions.get(left), positions.get(right)) {
+                    (Some(left), Some(right)) => left.iter().any(|position| {
+                        right.rank(position.saturating_add(max_proximity)) > right.rank(position)
+                    }),
+                    _ => false,
+                }
+            });
+            if is_in_order {
+                in_order.insert(docid);
+            }
+        }
+        candidates = in_order;
+    }
+
     Ok(candidates)
 }
 
@@ -919,6 +1075,14 @@ pub mod test {
             todo!()
         }
 
+        fn proximity_databases(&self) -> heed::Result<bool> {
+            Ok(true)
+        }
+
+        fn bucketed_positions(&self) -> heed::Result<bool> {
+            Ok(true)
+        }
+
         fn field_id_word_count_docids(
             &self,
             _field_id: FieldId,
@@ -57,6 +57,15 @@ impl<'t> Proximity<'t> {
 
 impl<'t> Criterion for Proximity<'t> {
     #[logging_timer::time("Proximity::{}")]
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            level = "trace",
+            name = "Proximity::next",
+            skip_all,
+            fields(excluded_candidates = params.excluded_candidates.len())
+        )
+    )]
     fn next(&mut self, params: &mut CriterionParameters) -> Result<Option<CriterionResult>> {
         // remove excluded candidates when next is called, instead of doing it in the loop.
         if let Some((_, _, allowed_candidates)) = self.state.as_mut() {
@@ -200,7 +209,7 @@ fn resolve_candidates(
 
         let result = match query_tree {
             And(ops) => mdfs(ctx, ops, proximity, cache, wdcache)?,
-            Phrase(words) => {
+            Phrase(words, slop) => {
                 if proximity == 0 {
                     let most_left = words
                         .iter()
@@ -215,7 +224,7 @@ fn resolve_candidates(
                         .map(|w| Query { prefix: false, kind: QueryKind::exact(w.clone()) });
 
                     match (most_left, most_right) {
-                        (Some(l), Some(r)) => vec![(l, r, resolve_phrase(ctx, words)?)],
+                        (Some(l), Some(r)) => vec![(l, r, resolve_phrase(ctx, words, *slop)?)],
                         _otherwise => Default::default(),
                     }
                 } else {
@@ -492,7 +501,7 @@ fn resolve_plane_sweep_candidates(
                 }
                 plane_sweep(groups_positions, false)?
             }
-            Phrase(words) => {
+            Phrase(words, slop) => {
                 let mut groups_positions = Vec::with_capacity(words.len());
 
                 // group stop_words together.
@@ -501,7 +510,8 @@ fn resolve_plane_sweep_candidates(
                     if matches!(words.first(), None | Some(None)) {
                         continue;
                     }
-                    // make a consecutive plane-sweep on the subgroup of words.
+                    // make a consecutive plane-sweep on the subgroup of words,
+                    // unless the words of the phrase can be apart.
                     let mut subgroup = Vec::with_capacity(words.len());
                     for word in words.iter().map(|w| w.as_deref().unwrap()) {
                         match words_positions.get(word) {
@@ -514,7 +524,7 @@ fn resolve_plane_sweep_candidates(
                     match subgroup.len() {
                         0 => {}
                         1 => groups_positions.push(subgroup.pop().unwrap()),
-                        _ => groups_positions.push(plane_sweep(subgroup, true)?),
+                        _ => groups_positions.push(plane_sweep(subgroup, slop.is_exact())?),
                     }
                 }
                 match groups_positions.len() {
@@ -663,7 +673,7 @@ mod tests {
 
         let rtxn = index.read_txn().unwrap();
 
-        let SearchResult { matching_words: _, candidates: _, documents_ids } = index
+        let SearchResult { matching_words: _, candidates: _, documents_ids, .. } = index
             .search(&rtxn)
             .query("zero c")
             .criterion_implementation_strategy(CriterionImplementationStrategy::OnlySetBased)
@@ -671,7 +681,7 @@ mod tests {
             .unwrap();
         insta::assert_snapshot!(format!("{documents_ids:?}"), @"[2, 3, 4, 1, 5, 0]");
 
-        let SearchResult { matching_words: _, candidates: _, documents_ids } = index
+        let SearchResult { matching_words: _, candidates: _, documents_ids, .. } = index
             .search(&rtxn)
             .query("zero co")
             .criterion_implementation_strategy(CriterionImplementationStrategy::OnlySetBased)
@@ -679,7 +689,7 @@ mod tests {
             .unwrap();
         insta::assert_snapshot!(format!("{documents_ids:?}"), @"[2, 3, 4, 1, 5, 0]");
 
-        let SearchResult { matching_words: _, candidates: _, documents_ids } = index
+        let SearchResult { matching_words: _, candidates: _, documents_ids, .. } = index
             .search(&rtxn)
             .query("zero con")
             .criterion_implementation_strategy(CriterionImplementationStrategy::OnlySetBased)
@@ -690,7 +700,7 @@ mod tests {
         // all of its word derivations
         insta::assert_snapshot!(format!("{documents_ids:?}"), @"[0, 1, 2, 3, 4, 5]");
 
-        let SearchResult { matching_words: _, candidates: _, documents_ids } = index
+        let SearchResult { matching_words: _, candidates: _, documents_ids, .. } = index
             .search(&rtxn)
             .criterion_implementation_strategy(CriterionImplementationStrategy::OnlySetBased)
             .query("zero conf")
@@ -700,7 +710,7 @@ mod tests {
         // that contain `conf` exactly, and not as a prefix.
         insta::assert_snapshot!(format!("{documents_ids:?}"), @"[4, 5, 0, 1, 2, 3]");
 
-        let SearchResult { matching_words: _, candidates: _, documents_ids } = index
+        let SearchResult { matching_words: _, candidates: _, documents_ids, .. } = index
             .search(&rtxn)
             .criterion_implementation_strategy(CriterionImplementationStrategy::OnlySetBased)
             .query("zero config")
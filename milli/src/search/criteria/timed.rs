@@ -0,0 +1,67 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+
+use super::{Criterion, CriterionParameters, CriterionResult};
+use crate::Result;
+
+/// The time spent in the criteria wrapped by [`CriteriaTimings::wrap`], shared by them.
+///
+/// Only the criteria of a search executed with
+/// [`Search::execute_with_timings`](crate::Search::execute_with_timings) are wrapped,
+/// the others are not slowed down by the measures.
+#[derive(Debug, Default, Clone)]
+pub struct CriteriaTimings {
+    /// The names of the wrapped criteria, from the first one to the last one, and the time
+    /// spent in their `next` method, including the time spent in their parents.
+    timings: Rc<RefCell<Vec<(String, Duration)>>>,
+}
+
+impl CriteriaTimings {
+    /// Wraps the criterion to measure the time spent in it and in its parents
+    /// that are not wrapped themselves.
+    pub fn wrap<'t>(
+        &self,
+        name: String,
+        criterion: Box<dyn Criterion + 't>,
+    ) -> Box<dyn Criterion + 't> {
+        let mut timings = self.timings.borrow_mut();
+        let index = timings.len();
+        timings.push((name, Duration::ZERO));
+        Box::new(Timed { index, timings: self.timings.clone(), criterion })
+    }
+
+    /// Returns the time spent in each of the wrapped criteria, without the time spent in
+    /// the wrapped criteria they are the children of.
+    pub fn criteria_timings(&self) -> Vec<(String, Duration)> {
+        let timings = self.timings.borrow();
+        let mut parent_time = Duration::ZERO;
+        timings
+            .iter()
+            .map(|(name, time)| {
+                let own_time = time.saturating_sub(parent_time);
+                parent_time = *time;
+                (name.clone(), own_time)
+            })
+            .collect()
+    }
+}
+
+/// A criterion adding the time spent in its `next` method to the [`CriteriaTimings`].
+///
+/// A criterion is the only one calling its parent, thus the time spent in the parent
+/// is always included in the time spent in its child.
+struct Timed<'t> {
+    index: usize,
+    timings: Rc<RefCell<Vec<(String, Duration)>>>,
+    criterion: Box<dyn Criterion + 't>,
+}
+
+impl<'t> Criterion for Timed<'t> {
+    fn next(&mut self, params: &mut CriterionParameters) -> Result<Option<CriterionResult>> {
+        let before = Instant::now();
+        let result = self.criterion.next(params);
+        self.timings.borrow_mut()[self.index].1 += before.elapsed();
+        result
+    }
+}
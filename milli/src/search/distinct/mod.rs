@@ -2,6 +2,7 @@ mod facet_distinct;
 mod noop_distinct;
 
 pub use facet_distinct::FacetDistinct;
+pub(crate) use facet_distinct::{facet_number_values, facet_string_values};
 pub use noop_distinct::NoopDistinct;
 use roaring::RoaringBitmap;
 
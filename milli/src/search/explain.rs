@@ -0,0 +1,539 @@
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+
+use filter_parser::FilterCondition;
+use levenshtein_automata::Distance;
+use roaring::RoaringBitmap;
+use serde::Serialize;
+use serde_json::Value;
+
+use super::distinct::{facet_number_values, facet_string_values};
+use super::query_tree::{Operation, Query, QueryKind};
+use super::{build_dfa, PhraseOrder, Search};
+use crate::{
+    distance_between_two_points, relative_from_absolute_position, AscDesc, Criterion, DocumentId,
+    FieldId, FieldsIdsMap, Member, Result,
+};
+
+/// The explanation of why a document matches, or doesn't match, a search,
+/// see [`Search::explain_document`].
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DocumentExplanation {
+    pub document_id: DocumentId,
+    /// Whether the document matches the query tree, `true` if there is no query.
+    pub matches_query: bool,
+    /// Whether the document passes every filter clause, `true` if there is no filter.
+    pub matches_filter: bool,
+    pub query_words: Vec<QueryWordExplanation>,
+    pub filter_clauses: Vec<FilterClauseExplanation>,
+    pub sort_values: Vec<SortValueExplanation>,
+}
+
+/// A word of the query tree and the words of the document it matched.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QueryWordExplanation {
+    pub word: String,
+    pub is_prefix: bool,
+    pub max_typo: u8,
+    pub matches: Vec<WordMatchExplanation>,
+}
+
+/// A word of the document matched by a query word, with its positions in each field.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WordMatchExplanation {
+    pub word: String,
+    pub typo: u8,
+    pub positions: BTreeMap<String, Vec<u16>>,
+}
+
+/// A top-level clause of the filter, evaluated independently of the other ones.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FilterClauseExplanation {
+    pub clause: String,
+    pub passed: bool,
+}
+
+/// The values of the document used by a sort criterion.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SortValueExplanation {
+    pub criterion: String,
+    pub values: Vec<Value>,
+}
+
+/// A query word of the tree along with the absolute positions it matched in the document.
+pub(super) struct MatchedQueryWord {
+    pub(super) explanation: QueryWordExplanation,
+    pub(super) positions: RoaringBitmap,
+}
+
+impl<'a> Search<'a> {
+    /// Explains how the given document is matched by the search: which document words are
+    /// matched by the words of the query, with which typos and where, which top-level filter
+    /// clauses pass or fail and which values are used by the sort criteria.
+    ///
+    /// The query tree is resolved against this single document only, the normal search is
+    /// never executed.
+    pub fn explain_document(&self, docid: DocumentId) -> Result<DocumentExplanation> {
+        // ensures that the document exists and isn't soft deleted.
+        self.index.documents(self.rtxn, Some(docid))?;
+
+        let fields_ids_map = self.index.fields_ids_map(self.rtxn)?;
+        let exact_attributes = self.index.exact_attributes_ids(self.rtxn)?;
+
+        let document_words = self.document_words(docid)?;
+
+        let (matches_query, query_words) = match self.build_query_tree()? {
+            Some((query_tree, _, _)) => {
+                let mut matched_words = Vec::new();
+                let matches = explain_operation(
+                    &query_tree,
+                    &document_words,
+                    &exact_attributes,
+                    &fields_ids_map,
+                    &mut matched_words,
+                );
+                (matches, matched_words.into_iter().map(|m| m.explanation).collect())
+            }
+            None => (true, Vec::new()),
+        };
+
+        let mut filter_clauses = Vec::new();
+        if let Some(filter) = &self.filter {
+            let clauses = match FilterCondition::from(filter.clone()) {
+                FilterCondition::And(clauses) => clauses,
+                condition => vec![condition],
+            };
+            for clause in clauses {
+                let description = clause.to_string();
+                let passed =
+                    filter.sub_filter(clause).evaluate(self.rtxn, self.index)?.contains(docid);
+                filter_clauses.push(FilterClauseExplanation { clause: description, passed });
+            }
+        }
+        let matches_filter = filter_clauses.iter().all(|clause| clause.passed);
+
+        let mut asc_descs: Vec<_> = self.sort_criteria.iter().flatten().cloned().collect();
+        for criterion in self.index.criteria(self.rtxn)? {
+            match criterion {
+                Criterion::Asc(field) => asc_descs.push(AscDesc::Asc(Member::Field(field))),
+                Criterion::Desc(field) => asc_descs.push(AscDesc::Desc(Member::Field(field))),
+                _ => (),
+            }
+        }
+
+        let mut sort_values = Vec::new();
+        for asc_desc in asc_descs {
+            let criterion = match &asc_desc {
+                AscDesc::Asc(member) => format!("{}:asc", member),
+                AscDesc::Desc(member) => format!("{}:desc", member),
+            };
+            let values = match asc_desc.member() {
+                Member::Field(field) => match fields_ids_map.id(field) {
+                    Some(fid) => self.facet_values(docid, fid)?,
+                    None => Vec::new(),
+                },
+                Member::Geo(point) => {
+                    let lat = fields_ids_map.id("_geo.lat");
+                    let lng = fields_ids_map.id("_geo.lng");
+                    match (lat, lng) {
+                        (Some(lat), Some(lng)) => {
+                            let lat = self.facet_number_value(docid, lat)?;
+                            let lng = self.facet_number_value(docid, lng)?;
+                            match (lat, lng) {
+                                (Some(lat), Some(lng)) => {
+                                    let distance = distance_between_two_points(point, &[lat, lng]);
+                                    serde_json::Number::from_f64(distance)
+                                        .map(Value::Number)
+                                        .into_iter()
+                                        .collect()
+                                }
+                                _ => Vec::new(),
+                            }
+                        }
+                        _ => Vec::new(),
+                    }
+                }
+            };
+            sort_values.push(SortValueExplanation { criterion, values });
+        }
+
+        Ok(DocumentExplanation {
+            document_id: docid,
+            matches_query,
+            matches_filter,
+            query_words,
+            filter_clauses,
+            sort_values,
+        })
+    }
+
+    /// Returns, for each of the given documents, the names of the fields in which at least one
+    /// word of the query has been matched, in the order of the searchable fields if defined.
+    pub fn matched_fields(&self, documents_ids: &[DocumentId]) -> Result<Vec<Vec<String>>> {
+        let query_tree = match self.build_query_tree()? {
+            Some((query_tree, _, _)) => query_tree,
+            None => return Ok(vec![Vec::new(); documents_ids.len()]),
+        };
+
+        let fields_ids_map = self.index.fields_ids_map(self.rtxn)?;
+        let exact_attributes = self.index.exact_attributes_ids(self.rtxn)?;
+        let searchable_fields_ids = self.index.searchable_fields_ids(self.rtxn)?;
+        documents_ids
+            .iter()
+            .map(|&docid| {
+                self.document_matched_fields(
+                    &query_tree,
+                    docid,
+                    &fields_ids_map,
+                    &exact_attributes,
+                    searchable_fields_ids.as_deref(),
+                )
+            })
+            .collect()
+    }
+
+    /// Returns the names of the fields of the document in which at least one word of the
+    /// query tree has been matched, in the order of the searchable fields if defined.
+    fn document_matched_fields(
+        &self,
+        query_tree: &Operation,
+        docid: DocumentId,
+        fields_ids_map: &FieldsIdsMap,
+        exact_attributes: &HashSet<FieldId>,
+        searchable_fields_ids: Option<&[FieldId]>,
+    ) -> Result<Vec<String>> {
+        let document_words = self.document_words(docid)?;
+        let mut matched_words = Vec::new();
+        explain_operation(
+            query_tree,
+            &document_words,
+            exact_attributes,
+            fields_ids_map,
+            &mut matched_words,
+        );
+
+        let mut matched_fields = BTreeSet::new();
+        for matched in matched_words {
+            matched_fields
+                .extend(matched.positions.iter().map(|pos| relative_from_absolute_position(pos).0));
+        }
+
+        let matched_fields: Vec<FieldId> = match searchable_fields_ids {
+            Some(searchable) => {
+                searchable.iter().copied().filter(|fid| matched_fields.contains(fid)).collect()
+            }
+            None => matched_fields.into_iter().collect(),
+        };
+
+        Ok(matched_fields
+            .into_iter()
+            .filter_map(|fid| fields_ids_map.name(fid).map(ToString::to_string))
+            .collect())
+    }
+
+    /// Returns the words of the document along with their absolute positions.
+    pub(super) fn document_words(&self, docid: DocumentId) -> Result<Vec<(String, RoaringBitmap)>> {
+        let mut document_words = Vec::new();
+        for result in self.index.docid_word_positions.prefix_iter(self.rtxn, &(docid, ""))? {
+            let ((_, word), positions) = result?;
+            document_words.push((word.to_string(), positions));
+        }
+        Ok(document_words)
+    }
+
+    /// Returns the number and then the string facet values of the document for this field.
+    fn facet_values(&self, docid: DocumentId, fid: FieldId) -> Result<Vec<Value>> {
+        let mut values = Vec::new();
+        for result in facet_number_values(docid, fid, self.index, self.rtxn)? {
+            let ((_, _, number), ()) = result?;
+            values.extend(serde_json::Number::from_f64(number).map(Value::Number));
+        }
+        for result in facet_string_values(docid, fid, self.index, self.rtxn)? {
+            let (_, original) = result?;
+            values.push(Value::String(original.to_string()));
+        }
+        Ok(values)
+    }
+
+    fn facet_number_value(&self, docid: DocumentId, fid: FieldId) -> Result<Option<f64>> {
+        match facet_number_values(docid, fid, self.index, self.rtxn)?.next() {
+            Some(result) => {
+                let ((_, _, number), ()) = result?;
+                Ok(Some(number))
+            }
+            None => Ok(None),
+        }
+    }
+}
+
+/// Resolves the operation against the words of a single document, registers the query
+/// words it contains in `matched_words` and returns whether the document matches it.
+pub(super) fn explain_operation(
+    operation: &Operation,
+    document_words: &[(String, RoaringBitmap)],
+    exact_attributes: &HashSet<FieldId>,
+    fields_ids_map: &FieldsIdsMap,
+    matched_words: &mut Vec<MatchedQueryWord>,
+) -> bool {
+    match operation {
+        // we don't short-circuit to explain every query word of the tree.
+        Operation::And(ops) => ops.iter().fold(true, |acc, op| {
+            explain_operation(op, document_words, exact_attributes, fields_ids_map, matched_words)
+                && acc
+        }),
+        Operation::Or(_, ops) => ops.iter().fold(false, |acc, op| {
+            explain_operation(op, document_words, exact_attributes, fields_ids_map, matched_words)
+                || acc
+        }),
+        Operation::Query(query) => {
+            let matched = explain_query(query, document_words, exact_attributes, fields_ids_map);
+            let matches = !matched.positions.is_empty();
+            register(matched_words, matched);
+            matches
+        }
+        Operation::Phrase(words, slop) if !slop.is_exact() => {
+            // the positions of the last word of the phrase matched after the previous ones.
+            let mut last_positions: Option<RoaringBitmap> = None;
+            let mut gap = 0;
+            for word in words {
+                gap += 1;
+                let word = match word {
+                    Some(word) => word,
+                    // stop words are not indexed but still take a position.
+                    None => continue,
+                };
+                let query = Query { prefix: false, kind: QueryKind::exact(word.to_string()) };
+                let matched =
+                    explain_query(&query, document_words, exact_attributes, fields_ids_map);
+                let max_distance = gap * slop.proximity as u32;
+                let in_reach = |previous: u32, position: u32| match slop.order {
+                    PhraseOrder::InOrder => {
+                        position > previous && position - previous <= max_distance
+                    }
+                    PhraseOrder::AnyOrder => {
+                        (1..=max_distance).contains(&position.abs_diff(previous))
+                    }
+                };
+                last_positions = Some(match last_positions {
+                    Some(previous) => matched
+                        .positions
+                        .iter()
+                        .filter(|&position| previous.iter().any(|p| in_reach(p, position)))
+                        .collect(),
+                    None => matched.positions.clone(),
+                });
+                gap = 0;
+                register(matched_words, matched);
+            }
+            last_positions.map_or(false, |positions| !positions.is_empty())
+        }
+        Operation::Phrase(words, _) => {
+            let mut phrase_positions: Option<RoaringBitmap> = None;
+            for (offset, word) in words.iter().enumerate() {
+                let word = match word {
+                    Some(word) => word,
+                    // stop words are not indexed but still take a position.
+                    None => continue,
+                };
+                let query = Query { prefix: false, kind: QueryKind::exact(word.to_string()) };
+                let matched =
+                    explain_query(&query, document_words, exact_attributes, fields_ids_map);
+                // we only keep the positions at which the phrase could start.
+                let starts: RoaringBitmap = matched
+                    .positions
+                    .iter()
+                    .filter_map(|position| position.checked_sub(offset as u32))
+                    .collect();
+                phrase_positions = Some(match phrase_positions {
+                    Some(positions) => positions & starts,
+                    None => starts,
+                });
+                register(matched_words, matched);
+            }
+            phrase_positions.map_or(false, |positions| !positions.is_empty())
+        }
+    }
+}
+
+/// Finds the words of the document matched by the query word and their positions.
+pub(super) fn explain_query(
+    query: &Query,
+    document_words: &[(String, RoaringBitmap)],
+    exact_attributes: &HashSet<FieldId>,
+    fields_ids_map: &FieldsIdsMap,
+) -> MatchedQueryWord {
+    let (word, max_typo) = match &query.kind {
+        QueryKind::Tolerant { typo, word } => (word, *typo),
+        QueryKind::Exact { word, .. } => (word, 0),
+    };
+
+    let dfa = build_dfa(word, max_typo, query.prefix);
+    let mut all_positions = RoaringBitmap::new();
+    let mut matches = Vec::new();
+    for (document_word, positions) in document_words {
+        let typo = match dfa.eval(document_word) {
+            Distance::Exact(typo) if typo <= max_typo => typo,
+            _ => continue,
+        };
+
+        let mut matched_positions = RoaringBitmap::new();
+        let mut fields_positions: BTreeMap<String, Vec<u16>> = BTreeMap::new();
+        for position in positions {
+            let (fid, relative) = relative_from_absolute_position(position);
+            // the words of the exact attributes can't be matched with typos.
+            if typo > 0 && exact_attributes.contains(&fid) {
+                continue;
+            }
+            matched_positions.insert(position);
+            let name = fields_ids_map.name(fid).unwrap_or_default().to_string();
+            fields_positions.entry(name).or_default().push(relative);
+        }
+
+        if !matched_positions.is_empty() {
+            all_positions |= matched_positions;
+            matches.push(WordMatchExplanation {
+                word: document_word.clone(),
+                typo,
+                positions: fields_positions,
+            });
+        }
+    }
+
+    MatchedQueryWord {
+        explanation: QueryWordExplanation {
+            word: word.clone(),
+            is_prefix: query.prefix,
+            max_typo,
+            matches,
+        },
+        positions: all_positions,
+    }
+}
+
+/// Registers a matched query word, once, in the order in which it appears in the tree.
+fn register(matched_words: &mut Vec<MatchedQueryWord>, matched: MatchedQueryWord) {
+    let already_registered = matched_words.iter().any(|m| {
+        m.explanation.word == matched.explanation.word
+            && m.explanation.is_prefix == matched.explanation.is_prefix
+            && m.explanation.max_typo == matched.explanation.max_typo
+    });
+    if !already_registered {
+        matched_words.push(matched);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashSet;
+
+    use big_s::S;
+    use maplit::hashset;
+
+    use super::*;
+    use crate::index::tests::TempIndex;
+    use crate::{Filter, TermsMatchingStrategy};
+
+    fn create_index() -> TempIndex {
+        let index = TempIndex::new();
+        index
+            .update_settings(|settings| {
+                settings.set_searchable_fields(vec![S("title"), S("description")]);
+                settings.set_filterable_fields(hashset! { S("price"), S("color") });
+                settings.set_sortable_fields(hashset! { S("price") });
+            })
+            .unwrap();
+        index
+            .add_documents(documents!([
+                { "id": 0, "title": "the red kitten", "description": "a kitten sleeping", "price": 10, "color": "Red" },
+                { "id": 1, "title": "the blue dog", "description": "a dog playing", "price": 20, "color": "Blue" },
+            ]))
+            .unwrap();
+        index
+    }
+
+    #[test]
+    fn explain_query_words() {
+        let index = create_index();
+        let rtxn = index.read_txn().unwrap();
+
+        let mut search = Search::new(&rtxn, &index);
+        search.query("kiten sleeping");
+        search.terms_matching_strategy(TermsMatchingStrategy::All);
+        let explanation = search.explain_document(0).unwrap();
+        assert!(explanation.matches_query);
+
+        let kitten = explanation.query_words.iter().find(|w| w.word == "kiten").unwrap();
+        assert!(!kitten.is_prefix);
+        assert_eq!(
+            kitten.matches,
+            vec![WordMatchExplanation {
+                word: S("kitten"),
+                typo: 1,
+                positions: BTreeMap::from([(S("description"), vec![1]), (S("title"), vec![2])]),
+            }]
+        );
+
+        let sleeping = explanation.query_words.iter().find(|w| w.word == "sleeping").unwrap();
+        assert!(sleeping.is_prefix);
+        assert_eq!(sleeping.matches.len(), 1);
+        assert_eq!(sleeping.matches[0].typo, 0);
+
+        let explanation = search.explain_document(1).unwrap();
+        assert!(!explanation.matches_query);
+        assert!(explanation.query_words.iter().all(|w| w.matches.is_empty()));
+    }
+
+    #[test]
+    fn explain_phrase() {
+        let index = create_index();
+        let rtxn = index.read_txn().unwrap();
+
+        let mut search = Search::new(&rtxn, &index);
+        search.query("\"red kitten\"");
+        assert!(search.explain_document(0).unwrap().matches_query);
+
+        search.query("\"kitten red\"");
+        assert!(!search.explain_document(0).unwrap().matches_query);
+    }
+
+    #[test]
+    fn explain_filter_and_sort() {
+        let index = create_index();
+        let rtxn = index.read_txn().unwrap();
+
+        let mut search = Search::new(&rtxn, &index);
+        search.filter(Filter::from_str("price < 15 AND color = blue").unwrap().unwrap());
+        search.sort_criteria(vec![AscDesc::Desc(Member::Field(S("price")))]);
+
+        let explanation = search.explain_document(0).unwrap();
+        assert!(explanation.matches_query);
+        assert!(!explanation.matches_filter);
+        let passed: HashSet<_> =
+            explanation.filter_clauses.iter().map(|c| (c.clause.as_str(), c.passed)).collect();
+        assert_eq!(passed, HashSet::from([("{price} < {15}", true), ("{color} = {blue}", false)]));
+        assert_eq!(
+            explanation.sort_values,
+            vec![SortValueExplanation {
+                criterion: S("price:desc"),
+                values: vec![Value::from(10.0)]
+            }]
+        );
+
+        let explanation = search.explain_document(1).unwrap();
+        assert!(!explanation.matches_filter);
+        assert_eq!(explanation.sort_values[0].values, vec![Value::from(20.0)]);
+    }
+
+    #[test]
+    fn explain_unknown_document() {
+        let index = create_index();
+        let rtxn = index.read_txn().unwrap();
+
+        let search = Search::new(&rtxn, &index);
+        assert!(search.explain_document(42).is_err());
+    }
+}
@@ -1,4 +1,4 @@
-use std::collections::{BTreeMap, HashSet};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::ops::ControlFlow;
 use std::{fmt, mem};
 
@@ -9,12 +9,12 @@ use roaring::RoaringBitmap;
 use crate::error::UserError;
 use crate::facet::FacetType;
 use crate::heed_codec::facet::{
-    FacetGroupKeyCodec, FacetGroupValueCodec, FieldDocIdFacetF64Codec, FieldDocIdFacetStringCodec,
-    OrderedF64Codec,
+    FacetGroupKey, FacetGroupKeyCodec, FacetGroupValueCodec, FieldDocIdFacetF64Codec,
+    FieldDocIdFacetStringCodec, OrderedF64Codec,
 };
 use crate::heed_codec::{ByteSliceRefCodec, StrRefCodec};
 use crate::search::facet::facet_distribution_iter;
-use crate::{FieldId, Index, Result};
+use crate::{DocumentId, FieldId, Index, Result, Search, SearchResult};
 
 /// The default number of values by facets that will
 /// be fetched from the key-value store.
@@ -28,6 +28,8 @@ pub struct FacetDistribution<'a> {
     facets: Option<HashSet<String>>,
     candidates: Option<RoaringBitmap>,
     max_values_per_facet: usize,
+    exhaustive: bool,
+    always_include: HashMap<String, Vec<String>>,
     rtxn: &'a heed::RoTxn<'a>,
     index: &'a Index,
 }
@@ -38,6 +40,8 @@ impl<'a> FacetDistribution<'a> {
             facets: None,
             candidates: None,
             max_values_per_facet: DEFAULT_VALUES_PER_FACET,
+            exhaustive: false,
+            always_include: HashMap::new(),
             rtxn,
             index,
         }
@@ -58,6 +62,30 @@ impl<'a> FacetDistribution<'a> {
         self
     }
 
+    /// Forces [`FacetDistribution::execute_for_search`] to exhaustively compute the candidates
+    /// of the search before counting, like [`Search::exhaustive_number_hits`] does. By default
+    /// the counts are computed against the candidates estimated by the search, which is faster.
+    pub fn exhaustive(&mut self, exhaustive: bool) -> &mut Self {
+        self.exhaustive = exhaustive;
+        self
+    }
+
+    /// Always returns the given values of the facet, with a count of 0 when no candidate
+    /// has them, so that the values of a facet do not vary with the candidates.
+    ///
+    /// The values are compared to the facet values once normalized like them, i.e.
+    /// case-insensitively, trimmed and with the numbers in their canonical form, and are
+    /// returned in addition to the `max_values_per_facet` values.
+    pub fn always_include<I: IntoIterator<Item = A>, A: AsRef<str>>(
+        &mut self,
+        field: &str,
+        values: I,
+    ) -> &mut Self {
+        let values = values.into_iter().map(|s| s.as_ref().to_string()).collect();
+        self.always_include.insert(field.to_string(), values);
+        self
+    }
+
     /// There is a small amount of candidates OR we ask for facet string values so we
     /// decide to iterate over the facet values of each one of them, one by one.
     fn facet_distribution_from_documents(
@@ -118,10 +146,12 @@ impl<'a> FacetDistribution<'a> {
                     }
                 }
 
-                let iter = normalized_distribution
-                    .into_iter()
-                    .map(|(_normalized, (original, count))| (original.to_string(), count));
-                distribution.extend(iter);
+                for (normalized, (original, count)) in normalized_distribution {
+                    let original =
+                        counted_original_string(self.rtxn, self.index, field_id, normalized)?
+                            .unwrap_or_else(|| original.to_string());
+                    distribution.insert(original, count);
+                }
             }
         }
 
@@ -170,14 +200,8 @@ impl<'a> FacetDistribution<'a> {
             candidates,
             |facet_key, nbr_docids, any_docid| {
                 let facet_key = StrRefCodec::bytes_decode(facet_key).unwrap();
-
-                let key: (FieldId, _, &str) = (field_id, any_docid, facet_key);
-                let original_string = self
-                    .index
-                    .field_id_docid_facet_strings
-                    .get(self.rtxn, &key)?
-                    .unwrap()
-                    .to_owned();
+                let original_string =
+                    original_string(self.rtxn, self.index, field_id, facet_key, any_docid)?;
 
                 distribution.insert(original_string, nbr_docids);
                 if distribution.len() == self.max_values_per_facet {
@@ -225,10 +249,9 @@ impl<'a> FacetDistribution<'a> {
         for result in iter {
             let (key, value) = result?;
 
-            let docid = value.bitmap.iter().next().unwrap();
-            let key: (FieldId, _, &'a str) = (field_id, docid, key.left_bound);
+            let any_docid = value.bitmap.min().unwrap();
             let original_string =
-                self.index.field_id_docid_facet_strings.get(self.rtxn, &key)?.unwrap().to_owned();
+                original_string(self.rtxn, self.index, field_id, key.left_bound, any_docid)?;
 
             distribution.insert(original_string, value.bitmap.len());
             if distribution.len() == self.max_values_per_facet {
@@ -239,11 +262,15 @@ impl<'a> FacetDistribution<'a> {
         Ok(distribution)
     }
 
-    fn facet_values(&self, field_id: FieldId) -> heed::Result<BTreeMap<String, u64>> {
+    fn facet_values(
+        &self,
+        field_id: FieldId,
+        candidates: Option<&RoaringBitmap>,
+    ) -> heed::Result<BTreeMap<String, u64>> {
         use FacetType::{Number, String};
 
-        match self.candidates {
-            Some(ref candidates) => {
+        match candidates {
+            Some(candidates) => {
                 // Classic search, candidates were specified, we must return facet values only related
                 // to those candidates. We also enter here for facet strings for performance reasons.
                 let mut distribution = BTreeMap::new();
@@ -278,8 +305,86 @@ impl<'a> FacetDistribution<'a> {
         }
     }
 
+    /// Returns the number of candidates of every facet value, by facet.
+    ///
+    /// The facet string values are returned as written in the documents, when the documents
+    /// spell a value differently the spelling of the most documents is returned.
     pub fn execute(&self) -> Result<BTreeMap<String, BTreeMap<String, u64>>> {
+        self.distribution(self.candidates.as_ref())
+    }
+
+    /// Executes the search and returns its result along with the number of its candidates of
+    /// every facet value, by facet, and whether these counts are exhaustive. The search is only
+    /// executed once and the candidates set with [`FacetDistribution::candidates`] are ignored.
+    ///
+    /// Unless the search or the facet distribution is exhaustive, the candidates of the search
+    /// are estimated and the counts can thus be lower than the real ones.
+    pub fn execute_for_search(
+        &self,
+        search: &Search,
+    ) -> Result<(SearchResult, FacetDistributionResult)> {
+        let result = search.execute_with(search.exhaustive_number_hits || self.exhaustive)?;
+        let distribution = self.distribution(Some(&result.candidates))?;
+        let exhaustive = result.exhaustive_candidates;
+        Ok((result, FacetDistributionResult { distribution, exhaustive }))
+    }
+
+    /// Returns the number of candidates of every boolean value, by facet.
+    ///
+    /// The booleans are counted apart from the other facet values returned by
+    /// [`FacetDistribution::execute`], the facets without any boolean are not returned.
+    pub fn execute_booleans(&self) -> Result<BTreeMap<String, BTreeMap<bool, u64>>> {
         let fields_ids_map = self.index.fields_ids_map(self.rtxn)?;
+        let fields = self.fields()?;
+        let candidates = self.index.live_candidates(self.rtxn, self.candidates.as_ref())?;
+
+        let mut distribution = BTreeMap::new();
+        for (fid, name) in fields_ids_map.iter() {
+            if crate::is_faceted(name, &fields) {
+                let mut values = BTreeMap::new();
+                for value in [false, true] {
+                    let mut docids = self.index.facet_bool_docids(self.rtxn, fid, value)?;
+                    if let Some(candidates) = &candidates {
+                        docids &= candidates;
+                    }
+                    if !docids.is_empty() {
+                        values.insert(value, docids.len());
+                    }
+                }
+                if !values.is_empty() {
+                    distribution.insert(name.to_string(), values);
+                }
+            }
+        }
+
+        Ok(distribution)
+    }
+
+    fn distribution(
+        &self,
+        candidates: Option<&RoaringBitmap>,
+    ) -> Result<BTreeMap<String, BTreeMap<String, u64>>> {
+        let fields_ids_map = self.index.fields_ids_map(self.rtxn)?;
+        let fields = self.fields()?;
+        // the facet databases still contain the soft-deleted documents.
+        let candidates = self.index.live_candidates(self.rtxn, candidates)?;
+
+        let mut distribution = BTreeMap::new();
+        for (fid, name) in fields_ids_map.iter() {
+            if crate::is_faceted(name, &fields) {
+                let mut values = self.facet_values(fid, candidates.as_ref())?;
+                if let Some(always_include) = self.always_include.get(name) {
+                    merge_always_included_values(&mut values, always_include);
+                }
+                distribution.insert(name.to_string(), values);
+            }
+        }
+
+        Ok(distribution)
+    }
+
+    /// Returns the facets to compute the distribution of, checking that they are filterable.
+    fn fields(&self) -> Result<HashSet<String>> {
         let filterable_fields = self.index.filterable_fields(self.rtxn)?;
 
         let fields = match self.facets {
@@ -301,39 +406,188 @@ impl<'a> FacetDistribution<'a> {
             None => filterable_fields,
         };
 
-        let mut distribution = BTreeMap::new();
-        for (fid, name) in fields_ids_map.iter() {
-            if crate::is_faceted(name, &fields) {
-                let values = self.facet_values(fid)?;
-                distribution.insert(name.to_string(), values);
-            }
-        }
-
-        Ok(distribution)
+        Ok(fields)
     }
 }
 
 impl fmt::Debug for FacetDistribution<'_> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        let FacetDistribution { facets, candidates, max_values_per_facet, rtxn: _, index: _ } =
-            self;
+        let FacetDistribution {
+            facets,
+            candidates,
+            max_values_per_facet,
+            exhaustive,
+            always_include,
+            rtxn: _,
+            index: _,
+        } = self;
 
         f.debug_struct("FacetDistribution")
             .field("facets", facets)
             .field("candidates", candidates)
             .field("max_values_per_facet", max_values_per_facet)
+            .field("exhaustive", exhaustive)
+            .field("always_include", always_include)
             .finish()
     }
 }
 
+/// The facet distribution of the candidates of a search, see
+/// [`FacetDistribution::execute_for_search`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct FacetDistributionResult {
+    /// The number of candidates of every facet value, by facet.
+    pub distribution: BTreeMap<String, BTreeMap<String, u64>>,
+    /// Whether the counts are computed against all the documents matching the search,
+    /// rather than against an estimation of them.
+    pub exhaustive: bool,
+}
+
+/// Adds the values missing from the distribution with a count of 0, the values
+/// already counted under another spelling are not added again.
+fn merge_always_included_values(distribution: &mut BTreeMap<String, u64>, values: &[String]) {
+    let counted: HashSet<_> =
+        distribution.keys().map(|value| crate::normalize_facet_string(value)).collect();
+    for value in values {
+        if !counted.contains(&crate::normalize_facet_string(value)) {
+            distribution.entry(value.clone()).or_insert(0);
+        }
+    }
+}
+
+/// Returns the number of candidates of every number, string and boolean value of the field,
+/// the values none of the candidates have are not returned.
+///
+/// The whole level 0 of the field is read and only the cardinality of the intersection
+/// of each value with the candidates is computed. The values written the same way, e.g. the
+/// number `40` and the string `"40"`, are counted together.
+pub(crate) fn facet_counts_in(
+    rtxn: &heed::RoTxn,
+    index: &Index,
+    field_id: FieldId,
+    candidates: &RoaringBitmap,
+) -> Result<BTreeMap<String, u64>> {
+    // the facet databases still contain the soft-deleted documents.
+    let candidates = index.live_candidates(rtxn, Some(candidates))?.unwrap_or_default();
+    let mut counts = BTreeMap::new();
+
+    let mut prefix = field_id.to_be_bytes().to_vec();
+    prefix.push(0); // read values from level 0 only
+
+    let iter = index
+        .facet_id_string_docids
+        .as_polymorph()
+        .prefix_iter::<_, ByteSlice, ByteSlice>(rtxn, prefix.as_slice())?
+        .remap_types::<FacetGroupKeyCodec<StrRefCodec>, FacetGroupValueCodec>();
+
+    for result in iter {
+        let (key, value) = result?;
+        let count = value.bitmap.intersection_len(&candidates);
+        if count != 0 {
+            let any_docid = value.bitmap.min().unwrap();
+            let original_string =
+                original_string(rtxn, index, field_id, key.left_bound, any_docid)?;
+            counts.insert(original_string, count);
+        }
+    }
+
+    // A number or a boolean can be written like a string of the field, a document containing
+    // both of them must only be counted once, the documents of the string are thus read again.
+    let mut insert_count = |value: String, docids: RoaringBitmap| -> Result<()> {
+        if counts.contains_key(&value) {
+            let normalized = crate::normalize_facet_string(&value);
+            let key = FacetGroupKey { field_id, level: 0, left_bound: normalized.as_str() };
+            if let Some(strings) = index.facet_id_string_docids.get(rtxn, &key)? {
+                let count = (docids | strings.bitmap).intersection_len(&candidates);
+                counts.insert(value, count);
+            }
+        } else {
+            let count = docids.intersection_len(&candidates);
+            if count != 0 {
+                counts.insert(value, count);
+            }
+        }
+        Ok(())
+    };
+
+    let iter = index
+        .facet_id_f64_docids
+        .as_polymorph()
+        .prefix_iter::<_, ByteSlice, ByteSlice>(rtxn, prefix.as_slice())?
+        .remap_types::<FacetGroupKeyCodec<OrderedF64Codec>, FacetGroupValueCodec>();
+
+    for result in iter {
+        let (key, value) = result?;
+        insert_count(key.left_bound.to_string(), value.bitmap)?;
+    }
+
+    for value in [false, true] {
+        insert_count(value.to_string(), index.facet_bool_docids(rtxn, field_id, value)?)?;
+    }
+
+    Ok(counts)
+}
+
+/// Returns the original string of the normalized facet value, see [`counted_original_string`].
+///
+/// The spellings of the facet strings of the indexes created by older versions are not
+/// counted, the spelling of the given document, which contains the value, is returned instead.
+fn original_string(
+    rtxn: &heed::RoTxn,
+    index: &Index,
+    field_id: FieldId,
+    normalized: &str,
+    any_docid: DocumentId,
+) -> heed::Result<String> {
+    match counted_original_string(rtxn, index, field_id, normalized)? {
+        Some(original) => Ok(original),
+        None => {
+            let key: (FieldId, _, &str) = (field_id, any_docid, normalized);
+            let original = index.field_id_docid_facet_strings.get(rtxn, &key)?.unwrap();
+            Ok(original.to_owned())
+        }
+    }
+}
+
+/// The documents can spell a facet value differently (e.g. `Blue` and `  blue`), the original
+/// string returned for a facet value is the spelling of the most documents of the index, then
+/// the smallest once trimmed, and then the smallest as written. This way the original string
+/// does not depend on the order in which the documents were indexed.
+///
+/// Returns `None` when the spellings of the facet strings of the index are not counted.
+fn counted_original_string(
+    rtxn: &heed::RoTxn,
+    index: &Index,
+    field_id: FieldId,
+    normalized: &str,
+) -> heed::Result<Option<String>> {
+    if !index.facet_string_originals_indexed(rtxn)? {
+        return Ok(None);
+    }
+
+    let spellings = index.facet_id_string_originals.get(rtxn, &(field_id, normalized))?;
+    let original = spellings.and_then(|spellings| {
+        spellings
+            .into_iter()
+            .min_by(|(a, a_count), (b, b_count)| {
+                b_count.cmp(a_count).then_with(|| (a.trim(), a).cmp(&(b.trim(), b)))
+            })
+            .map(|(original, _)| original)
+    });
+
+    Ok(original)
+}
+
 #[cfg(test)]
 mod tests {
     use big_s::S;
     use maplit::hashset;
+    use roaring::RoaringBitmap;
 
     use crate::documents::documents_batch_reader_from_objects;
     use crate::index::tests::TempIndex;
-    use crate::{milli_snap, FacetDistribution};
+    use crate::update::DeletionStrategy;
+    use crate::{milli_snap, FacetDistribution, Filter, Search};
 
     #[test]
     fn few_candidates_few_facet_values() {
@@ -378,10 +632,9 @@ mod tests {
             .execute()
             .unwrap();
 
-        // I think it would be fine if "  blue" was "Blue" instead.
-        // We just need to get any non-normalised string I think, even if it's not in
-        // the candidates
-        milli_snap!(format!("{map:?}"), @r###"{"colour": {"  blue": 1, "RED": 1}}"###);
+        // The spelling of the value is the same whatever the candidates, even if the
+        // candidates spell it differently.
+        milli_snap!(format!("{map:?}"), @r###"{"colour": {"Blue": 1, "RED": 1}}"###);
 
         let map = FacetDistribution::new(&txn, &index)
             .facets(std::iter::once("colour"))
@@ -434,7 +687,7 @@ mod tests {
             .execute()
             .unwrap();
 
-        milli_snap!(format!("{map:?}"), @r###"{"colour": {"Blue": 4000, "Red": 6000}}"###);
+        milli_snap!(format!("{map:?}"), @r###"{"colour": {"BLUE": 4000, "RED": 6000}}"###);
 
         let map = FacetDistribution::new(&txn, &index)
             .facets(std::iter::once("colour"))
@@ -442,7 +695,7 @@ mod tests {
             .execute()
             .unwrap();
 
-        milli_snap!(format!("{map:?}"), @r###"{"colour": {"Blue": 4000}}"###);
+        milli_snap!(format!("{map:?}"), @r###"{"colour": {"BLUE": 4000}}"###);
 
         let map = FacetDistribution::new(&txn, &index)
             .facets(std::iter::once("colour"))
@@ -450,7 +703,7 @@ mod tests {
             .execute()
             .unwrap();
 
-        milli_snap!(format!("{map:?}"), @r###"{"colour": {"Blue": 4000, "Red": 6000}}"###);
+        milli_snap!(format!("{map:?}"), @r###"{"colour": {"BLUE": 4000, "RED": 6000}}"###);
 
         let map = FacetDistribution::new(&txn, &index)
             .facets(std::iter::once("colour"))
@@ -458,7 +711,7 @@ mod tests {
             .execute()
             .unwrap();
 
-        milli_snap!(format!("{map:?}"), @r###"{"colour": {"Blue": 2000, "Red": 3000}}"###);
+        milli_snap!(format!("{map:?}"), @r###"{"colour": {"BLUE": 2000, "RED": 3000}}"###);
 
         let map = FacetDistribution::new(&txn, &index)
             .facets(std::iter::once("colour"))
@@ -466,7 +719,7 @@ mod tests {
             .execute()
             .unwrap();
 
-        milli_snap!(format!("{map:?}"), @r###"{"colour": {"Blue": 2000, "Red": 3000}}"###);
+        milli_snap!(format!("{map:?}"), @r###"{"colour": {"BLUE": 2000, "RED": 3000}}"###);
 
         let map = FacetDistribution::new(&txn, &index)
             .facets(std::iter::once("colour"))
@@ -475,7 +728,7 @@ mod tests {
             .execute()
             .unwrap();
 
-        milli_snap!(format!("{map:?}"), @r###"{"colour": {"Blue": 2000}}"###);
+        milli_snap!(format!("{map:?}"), @r###"{"colour": {"BLUE": 2000}}"###);
     }
 
     #[test]
@@ -537,4 +790,253 @@ mod tests {
 
         milli_snap!(format!("{map:?}"), "candidates_0_5_000", @"825f23a4090d05756f46176987b7d992");
     }
+
+    #[test]
+    fn original_string_does_not_depend_on_the_indexing_order() {
+        let documents = [
+            serde_json::json!({ "id": 0, "genre": "drama" }),
+            serde_json::json!({ "id": 1, "genre": "Drama" }),
+            serde_json::json!({ "id": 2, "genre": " DRAMA" }),
+            serde_json::json!({ "id": 3, "genre": "Comedy" }),
+        ];
+
+        for permutation in [[0, 1, 2, 3], [3, 2, 1, 0], [2, 0, 3, 1]] {
+            let index = TempIndex::new();
+            index
+                .update_settings(|settings| settings.set_filterable_fields(hashset! { S("genre") }))
+                .unwrap();
+
+            // every document is indexed in its own batch
+            for i in permutation {
+                let document = documents[i].as_object().unwrap().clone();
+                index.add_documents(documents_batch_reader_from_objects(vec![document])).unwrap();
+            }
+
+            let txn = index.read_txn().unwrap();
+
+            let map = FacetDistribution::new(&txn, &index)
+                .facets(std::iter::once("genre"))
+                .execute()
+                .unwrap();
+            assert_eq!(format!("{map:?}"), r###"{"genre": {" DRAMA": 3, "Comedy": 1}}"###);
+
+            let map = FacetDistribution::new(&txn, &index)
+                .facets(std::iter::once("genre"))
+                .candidates(index.documents_ids(&txn).unwrap())
+                .execute()
+                .unwrap();
+            assert_eq!(format!("{map:?}"), r###"{"genre": {" DRAMA": 3, "Comedy": 1}}"###);
+        }
+    }
+
+    #[test]
+    fn original_string_is_the_spelling_of_the_most_documents() {
+        let mut index = TempIndex::new();
+        index.index_documents_config.deletion_strategy = DeletionStrategy::AlwaysHard;
+        index
+            .update_settings(|settings| settings.set_filterable_fields(hashset! { S("genre") }))
+            .unwrap();
+        index.add_json_documents(serde_json::json!([
+            { "id": 0, "genre": "drama" },
+            { "id": 1, "genre": "Drama" },
+            { "id": 2, "genre": "Drama" },
+            { "id": 3, "genre": "DRAMA" },
+        ]));
+
+        let txn = index.read_txn().unwrap();
+        let map = FacetDistribution::new(&txn, &index)
+            .facets(std::iter::once("genre"))
+            .execute()
+            .unwrap();
+        assert_eq!(format!("{map:?}"), r###"{"genre": {"Drama": 4}}"###);
+        drop(txn);
+
+        // the spellings of the deleted documents are not counted anymore
+        index.delete_document("1");
+        index.delete_document("2");
+
+        let txn = index.read_txn().unwrap();
+        let map = FacetDistribution::new(&txn, &index)
+            .facets(std::iter::once("genre"))
+            .execute()
+            .unwrap();
+        assert_eq!(format!("{map:?}"), r###"{"genre": {"DRAMA": 2}}"###);
+    }
+
+    #[test]
+    fn exhaustive_counts_of_a_search() {
+        let index = TempIndex::new();
+        index
+            .update_settings(|settings| settings.set_filterable_fields(hashset! { S("colour") }))
+            .unwrap();
+        index
+            .add_documents(documents!([
+                { "id": 0, "text": "hello world", "colour": "blue" },
+                { "id": 1, "text": "hello world", "colour": "red" },
+                { "id": 2, "text": "hello", "colour": "red" },
+                { "id": 3, "text": "hello", "colour": "red" },
+            ]))
+            .unwrap();
+
+        let txn = index.read_txn().unwrap();
+        let mut search = Search::new(&txn, &index);
+        search.query("hello world");
+        search.limit(1);
+
+        // the search stops at the bucket of the documents matching all the words
+        let (search_result, result) = FacetDistribution::new(&txn, &index)
+            .facets(std::iter::once("colour"))
+            .execute_for_search(&search)
+            .unwrap();
+        assert_eq!(search_result.documents_ids, vec![0]);
+        assert_eq!(search_result.candidates, RoaringBitmap::from_iter([0, 1]));
+        assert!(!result.exhaustive);
+        milli_snap!(format!("{:?}", result.distribution), @r###"{"colour": {"blue": 1, "red": 1}}"###);
+
+        let result = FacetDistribution::new(&txn, &index)
+            .facets(std::iter::once("colour"))
+            .exhaustive(true)
+            .execute_for_search(&search)
+            .unwrap()
+            .1;
+        assert!(result.exhaustive);
+        milli_snap!(format!("{:?}", result.distribution), @r###"{"colour": {"blue": 1, "red": 3}}"###);
+
+        // an exhaustive search makes the counts exhaustive
+        search.exhaustive_number_hits(true);
+        let result = FacetDistribution::new(&txn, &index)
+            .facets(std::iter::once("colour"))
+            .execute_for_search(&search)
+            .unwrap()
+            .1;
+        assert!(result.exhaustive);
+        milli_snap!(format!("{:?}", result.distribution), @r###"{"colour": {"blue": 1, "red": 3}}"###);
+    }
+
+    #[test]
+    fn always_included_values() {
+        let index = TempIndex::new();
+        index
+            .update_settings(|settings| {
+                settings.set_filterable_fields(hashset! { S("colour"), S("size") })
+            })
+            .unwrap();
+        index
+            .add_documents(documents!([
+                { "id": 0, "colour": "Blue", "size": 38 },
+                { "id": 1, "colour": "red", "size": 40 },
+                { "id": 2, "colour": "red", "size": 42 },
+            ]))
+            .unwrap();
+
+        let txn = index.read_txn().unwrap();
+        let candidates = Filter::from_str("colour = red").unwrap().unwrap().evaluate(&txn, &index);
+
+        // the filtered out values are returned with a count of 0.
+        let map = FacetDistribution::new(&txn, &index)
+            .candidates(candidates.unwrap())
+            .always_include("colour", ["blue", "Red", "green"])
+            .always_include("size", ["38"])
+            .execute()
+            .unwrap();
+        milli_snap!(format!("{map:?}"), @r###"{"colour": {"blue": 0, "green": 0, "red": 2}, "size": {"38": 0, "40": 1, "42": 1}}"###);
+
+        // the counted values are not duplicated.
+        let map = FacetDistribution::new(&txn, &index)
+            .facets(std::iter::once("colour"))
+            .always_include("colour", ["blue", "green"])
+            .execute()
+            .unwrap();
+        milli_snap!(format!("{map:?}"), @r###"{"colour": {"Blue": 1, "green": 0, "red": 2}}"###);
+
+        // the numbers are compared in their canonical form.
+        let map = FacetDistribution::new(&txn, &index)
+            .facets(std::iter::once("size"))
+            .always_include("size", ["38.0", " 40", "44"])
+            .execute()
+            .unwrap();
+        milli_snap!(format!("{map:?}"), @r###"{"size": {"38": 1, "40": 1, "42": 1, "44": 0}}"###);
+    }
+
+    #[test]
+    fn boolean_values() {
+        let index = TempIndex::new();
+        index
+            .update_settings(|settings| {
+                settings.set_filterable_fields(hashset! { S("in_stock"), S("colour") })
+            })
+            .unwrap();
+        index
+            .add_documents(documents!([
+                { "id": 0, "in_stock": true, "colour": "blue" },
+                { "id": 1, "in_stock": false, "colour": "red" },
+                { "id": 2, "in_stock": "true", "colour": "red" },
+                { "id": 3, "in_stock": [true, "false"], "colour": "red" },
+            ]))
+            .unwrap();
+
+        let txn = index.read_txn().unwrap();
+
+        let map = FacetDistribution::new(&txn, &index).execute_booleans().unwrap();
+        milli_snap!(format!("{map:?}"), @r###"{"in_stock": {false: 1, true: 2}}"###);
+
+        // the booleans are not returned as strings anymore
+        let map = FacetDistribution::new(&txn, &index)
+            .facets(std::iter::once("in_stock"))
+            .execute()
+            .unwrap();
+        milli_snap!(format!("{map:?}"), @r###"{"in_stock": {"false": 1, "true": 1}}"###);
+
+        let map = FacetDistribution::new(&txn, &index)
+            .candidates((1..4).collect())
+            .execute_booleans()
+            .unwrap();
+        milli_snap!(format!("{map:?}"), @r###"{"in_stock": {false: 1, true: 1}}"###);
+    }
+
+    #[test]
+    fn facet_counts_in_candidates() {
+        let index = TempIndex::new();
+        index
+            .update_settings(|settings| {
+                settings.set_filterable_fields(hashset! { S("colour"), S("size") })
+            })
+            .unwrap();
+        index
+            .add_documents(documents!([
+                { "id": 0, "colour": "Blue", "size": 38 },
+                { "id": 1, "colour": "  blue", "size": 40 },
+                { "id": 2, "colour": "RED", "size": 40 },
+                { "id": 3, "colour": "red", "size": 42 },
+                { "id": 4, "colour": "green", "size": [38, "large"] },
+                { "id": 5, "colour": true, "size": "40" },
+                { "id": 6, "colour": [false, "true"], "size": "42" },
+            ]))
+            .unwrap();
+
+        let txn = index.read_txn().unwrap();
+        let fields_ids_map = index.fields_ids_map(&txn).unwrap();
+        let colour = fields_ids_map.id("colour").unwrap();
+        let size = fields_ids_map.id("size").unwrap();
+
+        let candidates = (1..4).collect();
+        let map = index.facet_counts_in(&txn, colour, &candidates).unwrap();
+        milli_snap!(format!("{map:?}"), @r###"{"Blue": 1, "RED": 2}"###);
+        let map = index.facet_counts_in(&txn, size, &candidates).unwrap();
+        milli_snap!(format!("{map:?}"), @r###"{"40": 2, "42": 1}"###);
+
+        let candidates = [0, 4].into_iter().collect();
+        let map = index.facet_counts_in(&txn, size, &candidates).unwrap();
+        milli_snap!(format!("{map:?}"), @r###"{"38": 2, "large": 1}"###);
+
+        // the booleans are counted, along with the strings written the same way
+        let candidates = (0..7).collect();
+        let map = index.facet_counts_in(&txn, colour, &candidates).unwrap();
+        milli_snap!(format!("{map:?}"), @r###"{"Blue": 2, "RED": 2, "false": 1, "green": 1, "true": 2}"###);
+        let map = index.facet_counts_in(&txn, size, &candidates).unwrap();
+        milli_snap!(format!("{map:?}"), @r###"{"38": 2, "40": 3, "42": 2, "large": 1}"###);
+
+        let map = index.facet_counts_in(&txn, size, &RoaringBitmap::new()).unwrap();
+        assert!(map.is_empty());
+    }
 }
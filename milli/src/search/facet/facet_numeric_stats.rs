@@ -0,0 +1,202 @@
+use heed::types::ByteSlice;
+use heed::RoTxn;
+use roaring::RoaringBitmap;
+use serde::Serialize;
+
+use super::{get_first_facet_value, get_last_facet_value};
+use crate::heed_codec::facet::{FacetGroupKeyCodec, FacetGroupValueCodec, OrderedF64Codec};
+use crate::heed_codec::ByteSliceRefCodec;
+use crate::{FieldId, Index, Result};
+
+/// The statistics of the number values of a facet, see [`Index::facet_numeric_stats`].
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct NumericFacetStats {
+    pub min: f64,
+    pub max: f64,
+    /// The average of the values, a value shared by several documents is counted once
+    /// for each of them.
+    pub avg: f64,
+    /// The number of documents having at least one number value.
+    pub count: u64,
+}
+
+/// Returns the smallest and the biggest number values of the field among the candidates,
+/// or among all the documents when there are no candidates.
+pub(crate) fn facet_numeric_range(
+    rtxn: &RoTxn,
+    index: &Index,
+    field_id: FieldId,
+    candidates: Option<&RoaringBitmap>,
+) -> Result<Option<(f64, f64)>> {
+    let candidates = match index.live_candidates(rtxn, candidates)? {
+        Some(candidates) => candidates,
+        None => {
+            // the level 0 is sorted, its first and last entries are the bounds of the field.
+            let db =
+                index.facet_id_f64_docids.remap_key_type::<FacetGroupKeyCodec<ByteSliceRefCodec>>();
+            let min = get_first_facet_value::<OrderedF64Codec>(rtxn, db, field_id)?;
+            let max = get_last_facet_value::<OrderedF64Codec>(rtxn, db, field_id)?;
+            return Ok(min.zip(max));
+        }
+    };
+
+    let prefix = level0_prefix(field_id);
+    let db = index.facet_id_f64_docids.as_polymorph();
+
+    let mut min = None;
+    for result in db
+        .prefix_iter::<_, ByteSlice, ByteSlice>(rtxn, &prefix)?
+        .remap_types::<FacetGroupKeyCodec<OrderedF64Codec>, FacetGroupValueCodec>()
+    {
+        let (key, value) = result?;
+        if !value.bitmap.is_disjoint(&candidates) {
+            min = Some(key.left_bound);
+            break;
+        }
+    }
+
+    let mut max = None;
+    for result in db
+        .rev_prefix_iter::<_, ByteSlice, ByteSlice>(rtxn, &prefix)?
+        .remap_types::<FacetGroupKeyCodec<OrderedF64Codec>, FacetGroupValueCodec>()
+    {
+        let (key, value) = result?;
+        if !value.bitmap.is_disjoint(&candidates) {
+            max = Some(key.left_bound);
+            break;
+        }
+    }
+
+    Ok(min.zip(max))
+}
+
+/// Computes the statistics of the number values of the field among the candidates,
+/// or among all the documents when there are no candidates.
+pub(crate) fn facet_numeric_stats(
+    rtxn: &RoTxn,
+    index: &Index,
+    field_id: FieldId,
+    candidates: Option<&RoaringBitmap>,
+) -> Result<Option<NumericFacetStats>> {
+    let candidates = index.live_candidates(rtxn, candidates)?;
+
+    let mut min = None;
+    let mut max = f64::NAN;
+    let mut sum = 0.0;
+    let mut values_count = 0;
+    let mut documents = RoaringBitmap::new();
+
+    let prefix = level0_prefix(field_id);
+    for result in index
+        .facet_id_f64_docids
+        .as_polymorph()
+        .prefix_iter::<_, ByteSlice, ByteSlice>(rtxn, &prefix)?
+        .remap_types::<FacetGroupKeyCodec<OrderedF64Codec>, FacetGroupValueCodec>()
+    {
+        let (key, value) = result?;
+        let docids = match &candidates {
+            Some(candidates) => value.bitmap & candidates,
+            None => value.bitmap,
+        };
+        if docids.is_empty() {
+            continue;
+        }
+
+        // the values are iterated in ascending order.
+        min.get_or_insert(key.left_bound);
+        max = key.left_bound;
+        sum += key.left_bound * docids.len() as f64;
+        values_count += docids.len();
+        documents |= docids;
+    }
+
+    Ok(min.map(|min| NumericFacetStats {
+        min,
+        max,
+        avg: sum / values_count as f64,
+        count: documents.len(),
+    }))
+}
+
+fn level0_prefix(field_id: FieldId) -> Vec<u8> {
+    let mut prefix = field_id.to_be_bytes().to_vec();
+    prefix.push(0); // the level 0 contains the values one by one
+    prefix
+}
+
+#[cfg(test)]
+mod tests {
+    use std::iter::FromIterator;
+
+    use big_s::S;
+    use maplit::hashset;
+    use roaring::RoaringBitmap;
+
+    use super::NumericFacetStats;
+    use crate::index::tests::TempIndex;
+    use crate::update::{DeleteDocuments, DeletionStrategy};
+
+    #[test]
+    fn numeric_stats() {
+        let index = TempIndex::new();
+        index
+            .update_settings(|settings| {
+                settings.set_primary_key(S("id"));
+                settings.set_filterable_fields(hashset! { S("price") });
+            })
+            .unwrap();
+        index
+            .add_documents(documents!([
+                { "id": 0, "price": 10 },
+                { "id": 1, "price": 2.5 },
+                { "id": 2, "price": [5, 40] },
+                { "id": 3, "price": "cheap" },
+                { "id": 4 },
+                { "id": 5, "price": 10 },
+            ]))
+            .unwrap();
+
+        let rtxn = index.read_txn().unwrap();
+        let price = index.fields_ids_map(&rtxn).unwrap().id("price").unwrap();
+
+        let stats = index.facet_numeric_stats(&rtxn, price, None).unwrap();
+        assert_eq!(
+            stats,
+            Some(NumericFacetStats { min: 2.5, max: 40.0, avg: 67.5 / 5.0, count: 4 })
+        );
+        assert_eq!(index.facet_numeric_range(&rtxn, price, None).unwrap(), Some((2.5, 40.0)));
+
+        let candidates = RoaringBitmap::from_iter([0, 2, 3, 4]);
+        let stats = index.facet_numeric_stats(&rtxn, price, Some(&candidates)).unwrap();
+        assert_eq!(
+            stats,
+            Some(NumericFacetStats { min: 5.0, max: 40.0, avg: 55.0 / 3.0, count: 2 })
+        );
+        assert_eq!(
+            index.facet_numeric_range(&rtxn, price, Some(&candidates)).unwrap(),
+            Some((5.0, 40.0))
+        );
+
+        // the candidates without any number value
+        let candidates = RoaringBitmap::from_iter([3, 4]);
+        assert_eq!(index.facet_numeric_stats(&rtxn, price, Some(&candidates)).unwrap(), None);
+        assert_eq!(index.facet_numeric_range(&rtxn, price, Some(&candidates)).unwrap(), None);
+        drop(rtxn);
+
+        // the soft-deleted documents are ignored
+        let mut wtxn = index.write_txn().unwrap();
+        let mut builder = DeleteDocuments::new(&mut wtxn, &index).unwrap();
+        builder.strategy(DeletionStrategy::AlwaysSoft);
+        builder.delete_external_id("2");
+        builder.execute().unwrap();
+        wtxn.commit().unwrap();
+
+        let rtxn = index.read_txn().unwrap();
+        let stats = index.facet_numeric_stats(&rtxn, price, None).unwrap();
+        assert_eq!(
+            stats,
+            Some(NumericFacetStats { min: 2.5, max: 10.0, avg: 22.5 / 3.0, count: 3 })
+        );
+        assert_eq!(index.facet_numeric_range(&rtxn, price, None).unwrap(), Some((2.5, 10.0)));
+    }
+}
@@ -5,25 +5,51 @@ use std::ops::Bound::{self, Excluded, Included};
 use either::Either;
 pub use filter_parser::{Condition, Error as FPError, FilterCondition, Span, Token};
 use roaring::RoaringBitmap;
+use rstar::AABB;
 
 use super::facet_range_search;
-use crate::error::{Error, UserError};
+use crate::error::{did_you_mean, Error, UserError};
+use crate::facet::FacetType;
 use crate::heed_codec::facet::{
     FacetGroupKey, FacetGroupKeyCodec, FacetGroupValueCodec, OrderedF64Codec,
 };
-use crate::{distance_between_two_points, lat_lng_to_xyz, FieldId, Index, Result};
+use crate::{
+    distance_between_point_and_polygon, distance_between_two_points, lat_lng_to_xyz,
+    normalize_facet_string, FieldId, FieldsIdsMap, Index, Result,
+};
 
 /// The maximum number of filters the filter AST can process.
 const MAX_FILTER_DEPTH: usize = 2000;
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+/// Returns the boolean written by an unquoted `true` or `false` value, a quoted value is a string.
+fn parse_boolean(value: &Token) -> Option<bool> {
+    match value.value() {
+        "true" if !value.is_quoted() => Some(true),
+        "false" if !value.is_quoted() => Some(false),
+        _ => None,
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
 pub struct Filter<'a> {
     condition: FilterCondition<'a>,
+    number_tolerance: Option<f64>,
+}
+
+/// A condition whose constant parts have been folded, see [`Filter::fold_constants`].
+enum FoldedCondition<'a> {
+    /// The condition is always `true` or always `false`, whatever the documents.
+    Constant(bool),
+    /// The condition depends on the documents, it doesn't contain any constant condition.
+    Condition(FilterCondition<'a>),
 }
 
 #[derive(Debug)]
 enum FilterError<'a> {
     AttributeNotFilterable { attribute: &'a str, filterable_fields: HashSet<String> },
+    AttributeNotFound(&'a str),
+    NotNumeric { attribute: &'a str, operator: &'static str },
+    NotString { attribute: &'a str, value: &'a str },
     BadGeo(&'a str),
     BadGeoLat(f64),
     BadGeoLng(f64),
@@ -47,12 +73,28 @@ impl<'a> Display for FilterError<'a> {
 
                     write!(
                         f,
-                        "Attribute `{}` is not filterable. Available filterable attributes are: `{}`.",
+                        "Attribute `{}` is not filterable. Available filterable attributes are: `{}`.{}",
                         attribute,
                         filterables_list,
+                        did_you_mean(attribute, filterable_fields.iter().map(AsRef::as_ref)),
                     )
                 }
             },
+            Self::AttributeNotFound(attribute) => write!(
+                f,
+                "Attribute `{}` does not exist in this index.",
+                attribute
+            ),
+            Self::NotNumeric { attribute, operator } => write!(
+                f,
+                "Attribute `{}` only contains strings and thus can't be used with the `{}` operator.",
+                attribute, operator
+            ),
+            Self::NotString { attribute, value } => write!(
+                f,
+                "Attribute `{}` only contains numbers and thus can't be compared to `{}`.",
+                attribute, value
+            ),
             Self::TooDeep => write!(f,
                 "Too many filter conditions, can't process more than {} filters.",
                 MAX_FILTER_DEPTH
@@ -124,7 +166,7 @@ impl<'a> Filter<'a> {
             return Err(token.as_external_error(FilterError::TooDeep).into());
         }
 
-        Ok(Some(Self { condition: and }))
+        Ok(Some(Self { condition: and, number_tolerance: None }))
     }
 
     #[allow(clippy::should_implement_trait)]
@@ -139,19 +181,242 @@ impl<'a> Filter<'a> {
             return Err(token.as_external_error(FilterError::TooDeep).into());
         }
 
-        Ok(Some(Self { condition }))
+        Ok(Some(Self { condition, number_tolerance: None }))
     }
 }
 
 impl<'a> Filter<'a> {
+    /// Makes the equalities on the numbers match the values within `tolerance` of the
+    /// filtered number, i.e. `rating = 4.35` matches the ratings in `[4.35 - tolerance,
+    /// 4.35 + tolerance]` and `rating != 4.35` the other documents.
+    ///
+    /// It applies to the `=`, `!=` and `IN` operators but not to the strings, which are still
+    /// matched exactly.
+    pub fn with_number_tolerance(mut self, tolerance: f64) -> Self {
+        self.number_tolerance = Some(tolerance.abs());
+        self
+    }
+
+    /// Creates a filter from a part of this filter, with the same options.
+    pub(crate) fn sub_filter(&self, condition: FilterCondition<'a>) -> Filter<'a> {
+        Filter { condition, number_tolerance: self.number_tolerance }
+    }
+
+    /// Estimates the fraction of the documents of the index matching this filter,
+    /// see [`Index::estimate_selectivity`].
+    ///
+    /// Only the leaves of the filter are evaluated, the `AND`, `OR` and `NOT` operators
+    /// combine their fractions as if they were independent.
+    pub(crate) fn estimate_selectivity(&self, rtxn: &heed::RoTxn, index: &Index) -> Result<f64> {
+        let documents_count = index.number_of_documents(rtxn)?;
+        if documents_count == 0 {
+            return Ok(0.0);
+        }
+        self.condition_selectivity(&self.condition, rtxn, index, documents_count as f64)
+    }
+
+    fn condition_selectivity(
+        &self,
+        condition: &FilterCondition<'a>,
+        rtxn: &heed::RoTxn,
+        index: &Index,
+        documents_count: f64,
+    ) -> Result<f64> {
+        match condition {
+            FilterCondition::Not(f) => {
+                Ok(1.0 - self.condition_selectivity(f, rtxn, index, documents_count)?)
+            }
+            FilterCondition::And(subfilters) => {
+                let mut selectivity = 1.0;
+                for f in subfilters {
+                    selectivity *= self.condition_selectivity(f, rtxn, index, documents_count)?;
+                }
+                Ok(selectivity)
+            }
+            FilterCondition::Or(subfilters) => {
+                let mut unmatched = 1.0;
+                for f in subfilters {
+                    unmatched *=
+                        1.0 - self.condition_selectivity(f, rtxn, index, documents_count)?;
+                }
+                Ok(1.0 - unmatched)
+            }
+            leaf => {
+                let docids = self.sub_filter(leaf.clone()).evaluate(rtxn, index)?;
+                Ok((docids.len() as f64 / documents_count).min(1.0))
+            }
+        }
+    }
+
+    /// Parses the filter and checks it against the current schema of the index without
+    /// evaluating it, see [`Filter::validate`].
+    pub fn parse_and_validate(
+        expression: &'a str,
+        rtxn: &heed::RoTxn,
+        index: &Index,
+    ) -> Result<Option<Self>> {
+        match Self::from_str(expression)? {
+            Some(filter) => {
+                filter.validate(rtxn, index)?;
+                Ok(Some(filter))
+            }
+            None => Ok(None),
+        }
+    }
+
+    /// Checks that the attributes used by the filter exist and are filterable, and that
+    /// their values are of the type expected by the operators, e.g. that a `<` isn't used
+    /// on an attribute that only contains strings.
+    pub fn validate(&self, rtxn: &heed::RoTxn, index: &Index) -> Result<()> {
+        let filterable_fields = index.filterable_fields(rtxn)?;
+        let fields_ids_map = index.fields_ids_map(rtxn)?;
+        Self::validate_condition(&self.condition, rtxn, index, &filterable_fields, &fields_ids_map)
+    }
+
+    fn validate_condition(
+        condition: &FilterCondition<'a>,
+        rtxn: &heed::RoTxn,
+        index: &Index,
+        filterable_fields: &HashSet<String>,
+        fields_ids_map: &FieldsIdsMap,
+    ) -> Result<()> {
+        match condition {
+            FilterCondition::Not(f) => {
+                Self::validate_condition(f, rtxn, index, filterable_fields, fields_ids_map)
+            }
+            FilterCondition::Or(subfilters) | FilterCondition::And(subfilters) => {
+                for f in subfilters {
+                    Self::validate_condition(f, rtxn, index, filterable_fields, fields_ids_map)?;
+                }
+                Ok(())
+            }
+            FilterCondition::In { fid, els } => {
+                for el in els {
+                    let op = Condition::Equal(el.clone());
+                    Self::validate_operator(
+                        rtxn,
+                        index,
+                        filterable_fields,
+                        fields_ids_map,
+                        fid,
+                        &op,
+                    )?;
+                }
+                Ok(())
+            }
+            FilterCondition::Condition { fid, op } => {
+                Self::validate_operator(rtxn, index, filterable_fields, fields_ids_map, fid, op)
+            }
+            FilterCondition::GeoLowerThan { point, radius } => {
+                if !filterable_fields.contains("_geo") {
+                    return Err(point[0].as_external_error(FilterError::AttributeNotFilterable {
+                        attribute: "_geo",
+                        filterable_fields: filterable_fields.clone(),
+                    }))?;
+                }
+                let lat = point[0].parse_finite_float()?;
+                if !(-90.0..=90.0).contains(&lat) {
+                    return Err(point[0].as_external_error(FilterError::BadGeoLat(lat)))?;
+                }
+                let lng = point[1].parse_finite_float()?;
+                if !(-180.0..=180.0).contains(&lng) {
+                    return Err(point[1].as_external_error(FilterError::BadGeoLng(lng)))?;
+                }
+                radius.parse_finite_float()?;
+                Ok(())
+            }
+        }
+    }
+
+    fn validate_operator(
+        rtxn: &heed::RoTxn,
+        index: &Index,
+        filterable_fields: &HashSet<String>,
+        fields_ids_map: &FieldsIdsMap,
+        fid: &Token<'a>,
+        op: &Condition<'a>,
+    ) -> Result<()> {
+        let attribute = fid.value();
+        if !crate::is_faceted(attribute, filterable_fields) {
+            // a number on the left of the operator is a constant condition.
+            if fid.parse_finite_float().is_ok() {
+                return Ok(());
+            }
+            let error = match fid.lexeme() {
+                attribute @ "_geo" => FilterError::BadGeo(attribute),
+                attribute if attribute.starts_with("_geoPoint(") => {
+                    FilterError::BadGeo("_geoPoint")
+                }
+                attribute @ "_geoDistance" => FilterError::Reserved(attribute),
+                attribute if fields_ids_map.id(attribute).is_none() => {
+                    FilterError::AttributeNotFound(attribute)
+                }
+                attribute => FilterError::AttributeNotFilterable {
+                    attribute,
+                    filterable_fields: filterable_fields.clone(),
+                },
+            };
+            return Err(fid.as_external_error(error))?;
+        }
+
+        // the operators comparing numbers require numbers.
+        let operator = match op {
+            Condition::GreaterThan(val) => Some((">", val)),
+            Condition::GreaterThanOrEqual(val) => Some((">=", val)),
+            Condition::LowerThan(val) => Some(("<", val)),
+            Condition::LowerThanOrEqual(val) => Some(("<=", val)),
+            Condition::Between { from, to } => {
+                from.parse_finite_float()?;
+                Some(("TO", to))
+            }
+            Condition::Equal(_) | Condition::NotEqual(_) | Condition::Exists => None,
+        };
+        if let Some((_, val)) = operator {
+            val.parse_finite_float()?;
+        }
+
+        // the type of the attribute is inferred from the facet values of the documents.
+        let field_id = match fields_ids_map.id(attribute) {
+            Some(field_id) => field_id,
+            None => return Ok(()),
+        };
+        let has_numbers =
+            !index.faceted_documents_ids(rtxn, field_id, FacetType::Number)?.is_empty();
+        let has_strings =
+            !index.faceted_documents_ids(rtxn, field_id, FacetType::String)?.is_empty();
+
+        match (operator, op) {
+            (Some((operator, _)), _) if has_strings && !has_numbers => {
+                Err(fid.as_external_error(FilterError::NotNumeric { attribute, operator }))?
+            }
+            (None, Condition::Equal(val) | Condition::NotEqual(val))
+                if has_numbers
+                    && !has_strings
+                    && val.parse_finite_float().is_err()
+                    && parse_boolean(val).is_none() =>
+            {
+                Err(val.as_external_error(FilterError::NotString { attribute, value: val.value() }))?
+            }
+            _ => Ok(()),
+        }
+    }
+
     pub fn evaluate(&self, rtxn: &heed::RoTxn, index: &Index) -> Result<RoaringBitmap> {
         // to avoid doing this for each recursive call we're going to do it ONCE ahead of time
         let soft_deleted_documents = index.soft_deleted_documents_ids(rtxn)?;
         let filterable_fields = index.filterable_fields(rtxn)?;
 
+        // the constant conditions are folded once for the whole filter too
+        let result = match Self::fold_constants(&self.condition, &filterable_fields) {
+            FoldedCondition::Constant(true) => index.documents_ids(rtxn)?,
+            FoldedCondition::Constant(false) => RoaringBitmap::new(),
+            FoldedCondition::Condition(condition) => {
+                self.sub_filter(condition).inner_evaluate(rtxn, index, &filterable_fields)?
+            }
+        };
+
         // and finally we delete all the soft_deleted_documents, again, only once at the very end
-        self.inner_evaluate(rtxn, index, &filterable_fields)
-            .map(|result| result - soft_deleted_documents)
+        Ok(result - soft_deleted_documents)
     }
 
     fn evaluate_operator(
@@ -159,6 +424,7 @@ impl<'a> Filter<'a> {
         index: &Index,
         field_id: FieldId,
         operator: &Condition<'a>,
+        number_tolerance: Option<f64>,
     ) -> Result<RoaringBitmap> {
         let numbers_db = index.facet_id_f64_docids;
         let strings_db = index.facet_id_string_docids;
@@ -186,30 +452,54 @@ impl<'a> Filter<'a> {
                 return Ok(exist);
             }
             Condition::Equal(val) => {
+                // the unquoted `true` and `false` only match the boolean values, but the indexes
+                // that aren't migrated yet still store the booleans of their older documents
+                // as strings, these are matched the old way too.
+                let mut bool_docids = RoaringBitmap::new();
+                if let Some(boolean) = parse_boolean(val) {
+                    bool_docids = index.facet_bool_docids(rtxn, field_id, boolean)?;
+                    if index.boolean_facets_indexed(rtxn)? {
+                        return Ok(bool_docids);
+                    }
+                }
+
                 let string_docids = strings_db
                     .get(
                         rtxn,
                         &FacetGroupKey {
                             field_id,
                             level: 0,
-                            left_bound: &val.value().to_lowercase(),
+                            left_bound: &normalize_facet_string(val.value()),
                         },
                     )?
                     .map(|v| v.bitmap)
                     .unwrap_or_default();
                 let number = val.parse_finite_float().ok();
-                let number_docids = match number {
-                    Some(n) => numbers_db
+                let number_docids = match (number, number_tolerance) {
+                    (Some(n), Some(tolerance)) => {
+                        let mut docids = RoaringBitmap::new();
+                        Self::explore_facet_number_levels(
+                            rtxn,
+                            numbers_db,
+                            field_id,
+                            Included(n - tolerance),
+                            Included(n + tolerance),
+                            &mut docids,
+                        )?;
+                        docids
+                    }
+                    (Some(n), None) => numbers_db
                         .get(rtxn, &FacetGroupKey { field_id, level: 0, left_bound: n })?
                         .map(|v| v.bitmap)
                         .unwrap_or_default(),
-                    None => RoaringBitmap::new(),
+                    (None, _) => RoaringBitmap::new(),
                 };
-                return Ok(string_docids | number_docids);
+                return Ok(string_docids | number_docids | bool_docids);
             }
             Condition::NotEqual(val) => {
                 let operator = Condition::Equal(val.clone());
-                let docids = Self::evaluate_operator(rtxn, index, field_id, &operator)?;
+                let docids =
+                    Self::evaluate_operator(rtxn, index, field_id, &operator, number_tolerance)?;
                 let all_ids = index.documents_ids(rtxn)?;
                 return Ok(all_ids - docids);
             }
@@ -222,7 +512,7 @@ impl<'a> Filter<'a> {
 
     /// Aggregates the documents ids that are part of the specified range automatically
     /// going deeper through the levels.
-    fn explore_facet_number_levels(
+    pub(crate) fn explore_facet_number_levels(
         rtxn: &heed::RoTxn,
         db: heed::Database<FacetGroupKeyCodec<OrderedF64Codec>, FacetGroupValueCodec>,
         field_id: FieldId,
@@ -245,6 +535,82 @@ impl<'a> Filter<'a> {
         Ok(())
     }
 
+    /// Folds the conditions of the filter that don't depend on the documents, i.e. that
+    /// are always `true` or always `false`, and removes them from the returned condition.
+    ///
+    /// The following conditions are considered constant:
+    /// - a range on a filterable attribute whose lower bound is greater than
+    ///   its upper bound, e.g. `price 10 TO 1`, which is always `false`,
+    /// - an empty `IN` on a filterable attribute, e.g. `price IN []`, which is always `false`,
+    /// - the negation of a constant condition,
+    /// - an `AND` containing an always `false` condition or only always `true` ones,
+    /// - an `OR` containing an always `true` condition or only always `false` ones.
+    ///
+    /// The conditions on an attribute that isn't filterable are never constant, their
+    /// evaluation returns an error. The filter is folded once before being evaluated,
+    /// this allows to skip the evaluation of the other conditions and avoid useless
+    /// database scans.
+    fn fold_constants(
+        condition: &FilterCondition<'a>,
+        filterable_fields: &HashSet<String>,
+    ) -> FoldedCondition<'a> {
+        match condition {
+            FilterCondition::Not(f) => match Self::fold_constants(f, filterable_fields) {
+                FoldedCondition::Constant(value) => FoldedCondition::Constant(!value),
+                FoldedCondition::Condition(f) => {
+                    FoldedCondition::Condition(FilterCondition::Not(Box::new(f)))
+                }
+            },
+            FilterCondition::Condition { fid, op: Condition::Between { from, to } }
+                if crate::is_faceted(fid.value(), filterable_fields) =>
+            {
+                match (from.parse_finite_float(), to.parse_finite_float()) {
+                    (Ok(from), Ok(to)) if from > to => FoldedCondition::Constant(false),
+                    _ => FoldedCondition::Condition(condition.clone()),
+                }
+            }
+            FilterCondition::In { fid, els }
+                if els.is_empty() && crate::is_faceted(fid.value(), filterable_fields) =>
+            {
+                FoldedCondition::Constant(false)
+            }
+            FilterCondition::Or(subfilters) => {
+                let mut remaining = Vec::new();
+                for f in subfilters {
+                    match Self::fold_constants(f, filterable_fields) {
+                        FoldedCondition::Constant(true) => return FoldedCondition::Constant(true),
+                        FoldedCondition::Constant(false) => (),
+                        FoldedCondition::Condition(f) => remaining.push(f),
+                    }
+                }
+                if remaining.is_empty() {
+                    FoldedCondition::Constant(false)
+                } else {
+                    FoldedCondition::Condition(FilterCondition::Or(remaining))
+                }
+            }
+            FilterCondition::And(subfilters) => {
+                let mut remaining = Vec::new();
+                for f in subfilters {
+                    match Self::fold_constants(f, filterable_fields) {
+                        FoldedCondition::Constant(false) => {
+                            return FoldedCondition::Constant(false)
+                        }
+                        FoldedCondition::Constant(true) => (),
+                        FoldedCondition::Condition(f) => remaining.push(f),
+                    }
+                }
+                if remaining.is_empty() {
+                    // an empty AND is evaluated to no documents, we keep it that way.
+                    FoldedCondition::Constant(!subfilters.is_empty())
+                } else {
+                    FoldedCondition::Condition(FilterCondition::And(remaining))
+                }
+            }
+            condition => FoldedCondition::Condition(condition.clone()),
+        }
+    }
+
     fn inner_evaluate(
         &self,
         rtxn: &heed::RoTxn,
@@ -255,7 +621,7 @@ impl<'a> Filter<'a> {
             FilterCondition::Not(f) => {
                 let all_ids = index.documents_ids(rtxn)?;
                 let selected = Self::inner_evaluate(
-                    &(f.as_ref().clone()).into(),
+                    &self.sub_filter(f.as_ref().clone()),
                     rtxn,
                     index,
                     filterable_fields,
@@ -271,7 +637,13 @@ impl<'a> Filter<'a> {
 
                         for el in els {
                             let op = Condition::Equal(el.clone());
-                            let el_bitmap = Self::evaluate_operator(rtxn, index, fid, &op)?;
+                            let el_bitmap = Self::evaluate_operator(
+                                rtxn,
+                                index,
+                                fid,
+                                &op,
+                                self.number_tolerance,
+                            )?;
                             bitmap |= el_bitmap;
                         }
                         Ok(bitmap)
@@ -289,7 +661,7 @@ impl<'a> Filter<'a> {
                 if crate::is_faceted(fid.value(), filterable_fields) {
                     let field_ids_map = index.fields_ids_map(rtxn)?;
                     if let Some(fid) = field_ids_map.id(fid.value()) {
-                        Self::evaluate_operator(rtxn, index, fid, op)
+                        Self::evaluate_operator(rtxn, index, fid, op, self.number_tolerance)
                     } else {
                         Ok(RoaringBitmap::new())
                     }
@@ -316,8 +688,12 @@ impl<'a> Filter<'a> {
             FilterCondition::Or(subfilters) => {
                 let mut bitmap = RoaringBitmap::new();
                 for f in subfilters {
-                    bitmap |=
-                        Self::inner_evaluate(&(f.clone()).into(), rtxn, index, filterable_fields)?;
+                    bitmap |= Self::inner_evaluate(
+                        &self.sub_filter(f.clone()),
+                        rtxn,
+                        index,
+                        filterable_fields,
+                    )?;
                 }
                 Ok(bitmap)
             }
@@ -325,7 +701,7 @@ impl<'a> Filter<'a> {
                 let mut subfilters_iter = subfilters.iter();
                 if let Some(first_subfilter) = subfilters_iter.next() {
                     let mut bitmap = Self::inner_evaluate(
-                        &(first_subfilter.clone()).into(),
+                        &self.sub_filter(first_subfilter.clone()),
                         rtxn,
                         index,
                         filterable_fields,
@@ -335,7 +711,7 @@ impl<'a> Filter<'a> {
                             return Ok(bitmap);
                         }
                         bitmap &= Self::inner_evaluate(
-                            &(f.clone()).into(),
+                            &self.sub_filter(f.clone()),
                             rtxn,
                             index,
                             filterable_fields,
@@ -361,21 +737,35 @@ impl<'a> Filter<'a> {
                         )?;
                     }
                     let radius = radius.parse_finite_float()?;
-                    let rtree = match index.geo_rtree(rtxn)? {
-                        Some(rtree) => rtree,
-                        None => return Ok(RoaringBitmap::new()),
-                    };
 
-                    let xyz_base_point = lat_lng_to_xyz(&base_point);
+                    let mut result = match index.geo_rtree(rtxn)? {
+                        Some(rtree) => {
+                            let xyz_base_point = lat_lng_to_xyz(&base_point);
+                            rtree
+                                .nearest_neighbor_iter(&xyz_base_point)
+                                .take_while(|point| {
+                                    distance_between_two_points(&base_point, &point.data.1)
+                                        <= radius + f64::EPSILON
+                                })
+                                .map(|point| point.data.0)
+                                .collect()
+                        }
+                        None => RoaringBitmap::new(),
+                    };
 
-                    let result = rtree
-                        .nearest_neighbor_iter(&xyz_base_point)
-                        .take_while(|point| {
-                            distance_between_two_points(&base_point, &point.data.1)
-                                <= radius + f64::EPSILON
-                        })
-                        .map(|point| point.data.0)
-                        .collect();
+                    // the documents describing a region match when it intersects the circle.
+                    if let Some(rtree) = index.geo_shapes_rtree(rtxn)? {
+                        let envelope = geo_radius_envelope(&base_point, radius);
+                        result.extend(
+                            rtree
+                                .locate_in_envelope_intersecting(&envelope)
+                                .filter(|shape| {
+                                    distance_between_point_and_polygon(&base_point, &shape.data.1)
+                                        <= radius + f64::EPSILON
+                                })
+                                .map(|shape| shape.data.0),
+                        );
+                    }
 
                     Ok(result)
                 } else {
@@ -389,9 +779,33 @@ impl<'a> Filter<'a> {
     }
 }
 
+/// Returns the smallest bounding box, in terms of latitude and longitude, containing all
+/// the points at less than `radius` meters from the center.
+fn geo_radius_envelope(center: &[f64; 2], radius: f64) -> AABB<[f64; 2]> {
+    // the polar radius is the smallest radius of the earth, the envelope is thus large enough.
+    const EARTH_POLAR_RADIUS: f64 = 6_356_752.0;
+
+    let angle = radius.max(0.0) / EARTH_POLAR_RADIUS;
+    let lat_delta = angle.to_degrees();
+    let (south, north) = (center[0] - lat_delta, center[0] + lat_delta);
+    if south <= -90.0 || north >= 90.0 {
+        // the circle contains a pole and thus all the longitudes.
+        return AABB::from_corners([south.max(-90.0), -180.0], [north.min(90.0), 180.0]);
+    }
+
+    let lng_delta = (angle.sin() / center[0].to_radians().cos()).asin().to_degrees();
+    let (west, east) = (center[1] - lng_delta, center[1] + lng_delta);
+    if west < -180.0 || east > 180.0 {
+        // the circle crosses the antimeridian.
+        return AABB::from_corners([south, -180.0], [north, 180.0]);
+    }
+
+    AABB::from_corners([south, west], [north, east])
+}
+
 impl<'a> From<FilterCondition<'a>> for Filter<'a> {
     fn from(fc: FilterCondition<'a>) -> Self {
-        Self { condition: fc }
+        Self { condition: fc, number_tolerance: None }
     }
 }
 
@@ -529,6 +943,59 @@ mod tests {
         assert!(error.to_string().starts_with(
             "Attribute `name` is not filterable. Available filterable attributes are: `title`."
         ));
+
+        let filter = Filter::from_str("tilte = 12").unwrap().unwrap();
+        let error = filter.evaluate(&rtxn, &index).unwrap_err();
+        insta::assert_display_snapshot!(error, @r###"
+        Attribute `tilte` is not filterable. Available filterable attributes are: `title`. Did you mean `title`?
+        1:6 tilte = 12
+        "###);
+    }
+
+    #[test]
+    fn parse_and_validate() {
+        let index = TempIndex::new();
+
+        index
+            .update_settings(|settings| {
+                settings.set_filterable_fields(hashset! { S("price"), S("color") });
+            })
+            .unwrap();
+        index
+            .add_documents(documents!([
+                { "id": 0, "price": 10, "color": "red", "title": "hello" },
+                { "id": 1, "price": 20, "color": "blue", "title": "world" },
+            ]))
+            .unwrap();
+
+        let rtxn = index.read_txn().unwrap();
+
+        let filter = Filter::parse_and_validate("price > 10 AND color = red", &rtxn, &index);
+        assert!(filter.unwrap().is_some());
+        let filter = Filter::parse_and_validate("   ", &rtxn, &index);
+        assert!(filter.unwrap().is_none());
+
+        let error = Filter::parse_and_validate("size = 10", &rtxn, &index).unwrap_err();
+        assert!(error.to_string().starts_with("Attribute `size` does not exist in this index."));
+
+        let error = Filter::parse_and_validate("title = hello", &rtxn, &index).unwrap_err();
+        assert!(error.to_string().starts_with(
+            "Attribute `title` is not filterable. Available filterable attributes are:"
+        ));
+
+        let error = Filter::parse_and_validate("color > 10", &rtxn, &index).unwrap_err();
+        assert!(error.to_string().starts_with(
+            "Attribute `color` only contains strings and thus can't be used with the `>` operator."
+        ));
+
+        let error = Filter::parse_and_validate("price IN [10, cheap]", &rtxn, &index).unwrap_err();
+        assert!(error.to_string().starts_with(
+            "Attribute `price` only contains numbers and thus can't be compared to `cheap`."
+        ));
+
+        // the validation doesn't evaluate the filter.
+        let filter = Filter::parse_and_validate("price 10 TO 20", &rtxn, &index).unwrap().unwrap();
+        assert_eq!(filter.evaluate(&rtxn, &index).unwrap(), RoaringBitmap::from_iter([0, 1]));
     }
 
     #[test]
@@ -625,6 +1092,59 @@ mod tests {
         assert_eq!(documents_ids, vec![0]);
     }
 
+    #[test]
+    fn geo_radius_intersects_regions() {
+        let index = TempIndex::new();
+
+        index
+            .update_settings(|settings| {
+                settings.set_filterable_fields(hashset! { S("_geo") });
+            })
+            .unwrap();
+
+        index
+            .add_documents(documents!([
+              { "id": 0, "_geo": { "lat": 45.5, "lng": 9.5 } },
+              { "id": 1, "_geo": { "boundingBox": [[45.0, 9.0], [46.0, 10.0]] } },
+              { "id": 2, "_geo": { "polygon": [[0.0, 0.0], [0.0, 1.0], [1.0, 0.0]] } },
+            ]))
+            .unwrap();
+
+        let rtxn = index.read_txn().unwrap();
+        let evaluate = |filter: &str| {
+            let filter = Filter::from_str(filter).unwrap().unwrap();
+            filter.evaluate(&rtxn, &index).unwrap().into_iter().collect::<Vec<_>>()
+        };
+
+        // the circle overlaps the east edge of the bounding box, ~8km away from its center.
+        assert_eq!(evaluate("_geoRadius(45.5, 10.1, 20000)"), vec![1]);
+        assert_eq!(evaluate("_geoRadius(45.5, 10.5, 20000)"), Vec::<u32>::new());
+        assert_eq!(evaluate("_geoRadius(45.5, 9.5, 0)"), vec![0, 1]);
+
+        // the circle overlaps the hypotenuse of the triangle, ~16km away from its center.
+        assert_eq!(evaluate("_geoRadius(0.6, 0.6, 20000)"), vec![2]);
+        assert_eq!(evaluate("_geoRadius(0.6, 0.6, 10000)"), Vec::<u32>::new());
+        assert_eq!(evaluate("_geoRadius(0.2, 0.2, 0)"), vec![2]);
+        drop(rtxn);
+
+        // the regions are removed with their documents.
+        index.delete_document("1");
+        let rtxn = index.read_txn().unwrap();
+        let mut search = crate::Search::new(&rtxn, &index);
+        search.filter(Filter::from_str("_geoRadius(45.5, 10.1, 20000)").unwrap().unwrap());
+        let crate::SearchResult { documents_ids, .. } = search.execute().unwrap();
+        assert!(documents_ids.is_empty());
+        drop(rtxn);
+
+        // a polygon needs at least three vertices.
+        let error = index
+            .add_documents(documents!([
+              { "id": 3, "_geo": { "polygon": [[0.0, 0.0], [1.0, 1.0]] } },
+            ]))
+            .unwrap_err();
+        assert!(error.to_string().contains("Was expecting a `boundingBox`"));
+    }
+
     #[test]
     fn geo_radius_error() {
         let index = TempIndex::new();
@@ -835,4 +1355,184 @@ mod tests {
         let result = filter.evaluate(&rtxn, &index).unwrap();
         assert_eq!(result, RoaringBitmap::from_iter((0..100).filter(|x| x % 10 != 0)));
     }
+
+    #[test]
+    fn filter_boolean() {
+        let index = TempIndex::new();
+
+        index
+            .update_settings(|settings| {
+                settings.set_primary_key(S("id"));
+                settings.set_filterable_fields(hashset! { S("in_stock") });
+            })
+            .unwrap();
+
+        index
+            .add_documents(documents!([
+                { "id": 0, "in_stock": true },
+                { "id": 1, "in_stock": false },
+                { "id": 2, "in_stock": "true" },
+                { "id": 3, "in_stock": [true, false] },
+                { "id": 4, "in_stock": 1 },
+            ]))
+            .unwrap();
+
+        let rtxn = index.read_txn().unwrap();
+        let evaluate = |filter: &str| {
+            Filter::from_str(filter).unwrap().unwrap().evaluate(&rtxn, &index).unwrap()
+        };
+
+        assert_eq!(evaluate("in_stock = true"), RoaringBitmap::from_iter([0, 3]));
+        assert_eq!(evaluate("in_stock = false"), RoaringBitmap::from_iter([1, 3]));
+        assert_eq!(evaluate("in_stock = \"true\""), RoaringBitmap::from_iter([2]));
+        assert_eq!(evaluate("in_stock = 'true'"), RoaringBitmap::from_iter([2]));
+        assert_eq!(evaluate("in_stock != true"), RoaringBitmap::from_iter([1, 2, 4]));
+        assert_eq!(evaluate("in_stock IN [true, \"true\"]"), RoaringBitmap::from_iter([0, 2, 3]));
+        assert_eq!(evaluate("in_stock = 1"), RoaringBitmap::from_iter([4]));
+        assert_eq!(evaluate("in_stock EXISTS"), RoaringBitmap::from_iter(0..5));
+    }
+
+    #[test]
+    fn filter_string_case_insensitive() {
+        let index = TempIndex::new();
+
+        index
+            .update_settings(|settings| {
+                settings.set_primary_key(S("id"));
+                settings.set_filterable_fields(hashset! { S("brand") });
+            })
+            .unwrap();
+
+        // longer than the facet keys, the stored value is truncated
+        let long = "Apple".repeat(200);
+        index
+            .add_documents(documents!([
+                { "id": 0, "brand": "Apple" },
+                { "id": 1, "brand": "apple" },
+                { "id": 2, "brand": "  APPLE " },
+                { "id": 3, "brand": "Apples" },
+                { "id": 4, "brand": long },
+            ]))
+            .unwrap();
+
+        let rtxn = index.read_txn().unwrap();
+        let evaluate = |filter: &str| {
+            Filter::from_str(filter).unwrap().unwrap().evaluate(&rtxn, &index).unwrap()
+        };
+
+        let apple = RoaringBitmap::from_iter([0, 1, 2]);
+        assert_eq!(evaluate("brand = Apple"), apple);
+        assert_eq!(evaluate("brand = apple"), apple);
+        assert_eq!(evaluate("brand = APPLE"), apple);
+        assert_eq!(evaluate("brand = ' aPPle  '"), apple);
+        assert_eq!(evaluate("brand IN [APPLE, apples]"), RoaringBitmap::from_iter([0, 1, 2, 3]));
+        assert_eq!(evaluate("brand != apple"), RoaringBitmap::from_iter([3, 4]));
+
+        let long_docids = RoaringBitmap::from_iter([4]);
+        assert_eq!(evaluate(&format!("brand = '{long}'")), long_docids);
+        assert_eq!(evaluate(&format!("brand = '{}'", long.to_uppercase())), long_docids);
+    }
+
+    #[test]
+    fn filter_number_tolerance() {
+        let index = TempIndex::new();
+
+        index
+            .update_settings(|settings| {
+                settings.set_primary_key(S("id"));
+                settings.set_filterable_fields(hashset! { S("rating") });
+            })
+            .unwrap();
+
+        index
+            .add_documents(documents!([
+                { "id": 0, "rating": 4.35 },
+                { "id": 1, "rating": 4.35000000001 },
+                { "id": 2, "rating": 4.35009 },
+                { "id": 3, "rating": 4.34991 },
+                { "id": 4, "rating": 4.35011 },
+                { "id": 5, "rating": 4.34989 },
+                { "id": 6, "rating": "4.35001" },
+                { "id": 7, "rating": "4.35" },
+            ]))
+            .unwrap();
+
+        let rtxn = index.read_txn().unwrap();
+        let evaluate = |filter: &str, tolerance: Option<f64>| {
+            let mut filter = Filter::from_str(filter).unwrap().unwrap();
+            if let Some(tolerance) = tolerance {
+                filter = filter.with_number_tolerance(tolerance);
+            }
+            filter.evaluate(&rtxn, &index).unwrap()
+        };
+
+        // without tolerance the numbers must be equal
+        assert_eq!(evaluate("rating = 4.35", None), RoaringBitmap::from_iter([0, 7]));
+
+        // the numbers within the tolerance match, but not the strings
+        let tolerance = Some(0.0001);
+        assert_eq!(evaluate("rating = 4.35", tolerance), RoaringBitmap::from_iter([0, 1, 2, 3, 7]));
+        assert_eq!(evaluate("rating != 4.35", tolerance), RoaringBitmap::from_iter([4, 5, 6]));
+        assert_eq!(
+            evaluate("rating IN [4.35, 4.3501]", tolerance),
+            RoaringBitmap::from_iter([0, 1, 2, 3, 4, 7])
+        );
+        assert_eq!(
+            evaluate("NOT rating = 4.35 AND rating EXISTS", tolerance),
+            RoaringBitmap::from_iter([4, 5, 6])
+        );
+        assert_eq!(evaluate("rating = 4.35002", tolerance), RoaringBitmap::from_iter([0, 1, 2, 4]));
+    }
+
+    #[test]
+    fn constant_filters() {
+        let index = TempIndex::new();
+
+        index
+            .update_settings(|settings| {
+                settings.set_primary_key(S("id"));
+                settings.set_filterable_fields(hashset! { S("price") });
+            })
+            .unwrap();
+
+        index
+            .add_documents(documents!([
+                { "id": 0, "price": 10 },
+                { "id": 1, "price": 20 },
+                { "id": 2, "price": 30 },
+            ]))
+            .unwrap();
+
+        let rtxn = index.read_txn().unwrap();
+        let evaluate =
+            |filter: &str| Filter::from_str(filter).unwrap().unwrap().evaluate(&rtxn, &index);
+        let all = RoaringBitmap::from_iter([0, 1, 2]);
+
+        // tautologies match all the documents
+        assert_eq!(evaluate("NOT price 30 TO 10").unwrap(), all);
+        assert_eq!(evaluate("price NOT IN [] OR price = 10").unwrap(), all);
+        assert_eq!(
+            evaluate("NOT price 30 TO 10 AND price < 25").unwrap(),
+            RoaringBitmap::from_iter([0, 1])
+        );
+
+        // contradictions match no documents
+        assert!(evaluate("price IN []").unwrap().is_empty());
+        assert!(evaluate("price IN [] OR price 30 TO 10").unwrap().is_empty());
+
+        // a contradiction makes the whole AND empty without evaluating the other
+        // conditions, the non-filterable attribute is never looked at
+        assert!(evaluate("price = 10 AND price IN [] AND unknown = 3").unwrap().is_empty());
+        assert!(evaluate("unknown = 3 AND price 30 TO 10").unwrap().is_empty());
+        // but it is still reported when the other conditions must be evaluated
+        assert!(evaluate("NOT price IN [] AND unknown = 3").is_err());
+
+        // the comparisons between numbers aren't constant, the left number must be filterable
+        for filter in ["1 = 1", "1 IN [0, 1] OR price = 10"] {
+            let error = evaluate(filter).unwrap_err();
+            assert!(error.to_string().starts_with(
+                "Attribute `1` is not filterable. Available filterable attributes are: `price`."
+            ));
+        }
+    }
 }
@@ -3,12 +3,18 @@ pub use facet_sort_descending::descending_facet_sort;
 use heed::types::{ByteSlice, DecodeIgnore};
 use heed::{BytesDecode, RoTxn};
 
-pub use self::facet_distribution::{FacetDistribution, DEFAULT_VALUES_PER_FACET};
+pub(crate) use self::facet_distribution::facet_counts_in;
+pub use self::facet_distribution::{
+    FacetDistribution, FacetDistributionResult, DEFAULT_VALUES_PER_FACET,
+};
+pub use self::facet_numeric_stats::NumericFacetStats;
+pub(crate) use self::facet_numeric_stats::{facet_numeric_range, facet_numeric_stats};
 pub use self::filter::Filter;
 use crate::heed_codec::facet::{FacetGroupKeyCodec, FacetGroupValueCodec};
 use crate::heed_codec::ByteSliceRefCodec;
 mod facet_distribution;
 mod facet_distribution_iter;
+mod facet_numeric_stats;
 mod facet_range_search;
 mod facet_sort_ascending;
 mod facet_sort_descending;
@@ -41,7 +41,17 @@ impl MatchingWords {
 
     /// Returns an iterator over terms that match or partially match the given token.
     pub fn match_token<'a, 'b>(&'a self, token: &'b Token<'b>) -> MatchesIter<'a, 'b> {
-        MatchesIter { inner: Box::new(self.inner.iter()), token }
+        self.match_normalized_token(token, token.lemma())
+    }
+
+    /// Returns an iterator over terms that match or partially match the given token,
+    /// compared by its `lemma` normalized by the normalization rules of the index.
+    pub(crate) fn match_normalized_token<'a, 'b>(
+        &'a self,
+        token: &'b Token<'b>,
+        lemma: &'b str,
+    ) -> MatchesIter<'a, 'b> {
+        MatchesIter { inner: Box::new(self.inner.iter()), token, lemma }
     }
 }
 
@@ -51,6 +61,7 @@ pub struct MatchesIter<'a, 'b> {
     #[allow(clippy::type_complexity)]
     inner: Box<dyn Iterator<Item = &'a (Vec<Rc<MatchingWord>>, Vec<PrimitiveWordId>)> + 'a>,
     token: &'b Token<'b>,
+    lemma: &'b str,
 }
 
 impl<'a> Iterator for MatchesIter<'a, '_> {
@@ -58,20 +69,22 @@ impl<'a> Iterator for MatchesIter<'a, '_> {
 
     fn next(&mut self) -> Option<Self::Item> {
         match self.inner.next() {
-            Some((matching_words, ids)) => match matching_words[0].match_token(self.token) {
-                Some(char_len) => {
-                    if matching_words.len() > 1 {
-                        Some(MatchType::Partial(PartialMatch {
-                            matching_words: &matching_words[1..],
-                            ids,
-                            char_len,
-                        }))
-                    } else {
-                        Some(MatchType::Full { char_len, ids })
+            Some((matching_words, ids)) => {
+                match matching_words[0].match_normalized_token(self.token, self.lemma) {
+                    Some(char_len) => {
+                        if matching_words.len() > 1 {
+                            Some(MatchType::Partial(PartialMatch {
+                                matching_words: &matching_words[1..],
+                                ids,
+                                char_len,
+                            }))
+                        } else {
+                            Some(MatchType::Full { char_len, ids })
+                        }
                     }
+                    None => self.next(),
                 }
-                None => self.next(),
-            },
+            }
             None => None,
         }
     }
@@ -116,9 +129,16 @@ impl MatchingWord {
 
     /// Returns the lenght in chars of the match in case of the token matches the term.
     pub fn match_token(&self, token: &Token) -> Option<usize> {
-        match self.dfa.eval(token.lemma()) {
+        self.match_normalized_token(token, token.lemma())
+    }
+
+    /// Returns the lenght in chars of the match in case of the `lemma` of the token, normalized
+    /// by the normalization rules of the index, matches the term. The whole token is highlighted
+    /// when the rules changed its lemma, the prefix can't be mapped back to the original text.
+    pub(crate) fn match_normalized_token(&self, token: &Token, lemma: &str) -> Option<usize> {
+        match self.dfa.eval(lemma) {
             Distance::Exact(t) if t <= self.typo => {
-                if self.prefix {
+                if self.prefix && lemma == token.lemma() {
                     let len = bytes_to_highlight(token.lemma(), &self.word);
                     Some(token.original_lengths(len).0)
                 } else {
@@ -154,7 +174,17 @@ impl<'a> PartialMatch<'a> {
     /// - Partial if the given token matches the partial match but doesn't complete it
     /// - Full if the given token completes the partial match
     pub fn match_token(self, token: &Token) -> Option<MatchType<'a>> {
-        self.matching_words[0].match_token(token).map(|char_len| {
+        self.match_normalized_token(token, token.lemma())
+    }
+
+    /// Like [`PartialMatch::match_token`], comparing the `lemma` of the token normalized
+    /// by the normalization rules of the index.
+    pub(crate) fn match_normalized_token(
+        self,
+        token: &Token,
+        lemma: &str,
+    ) -> Option<MatchType<'a>> {
+        self.matching_words[0].match_normalized_token(token, lemma).map(|char_len| {
             if self.matching_words.len() > 1 {
                 MatchType::Partial(PartialMatch {
                     matching_words: &self.matching_words[1..],
@@ -1,10 +1,13 @@
 use std::borrow::Cow;
+use std::collections::BTreeMap;
 
 use charabia::{SeparatorKind, Token, Tokenizer};
 use matching_words::{MatchType, PartialMatch, PrimitiveWordId};
 pub use matching_words::{MatchingWord, MatchingWords};
 use serde::Serialize;
 
+use crate::apply_normalization_rules;
+
 pub mod matching_words;
 
 const DEFAULT_CROP_MARKER: &str = "…";
@@ -18,6 +21,8 @@ pub struct MatcherBuilder<'a, A> {
     crop_marker: Option<String>,
     highlight_prefix: Option<String>,
     highlight_suffix: Option<String>,
+    highlight_merging: HighlightMerging,
+    normalization_rules: BTreeMap<String, String>,
 }
 
 impl<'a, A> MatcherBuilder<'a, A> {
@@ -28,6 +33,8 @@ impl<'a, A> MatcherBuilder<'a, A> {
             crop_marker: None,
             highlight_prefix: None,
             highlight_suffix: None,
+            highlight_merging: HighlightMerging::Never,
+            normalization_rules: BTreeMap::new(),
         }
     }
 
@@ -46,6 +53,22 @@ impl<'a, A> MatcherBuilder<'a, A> {
         self
     }
 
+    /// Sets whether the highlights of consecutive matched words are merged into a single
+    /// highlight, e.g. `<em>new york</em>` instead of `<em>new</em> <em>york</em>`.
+    pub fn highlight_merging(&mut self, merging: HighlightMerging) -> &Self {
+        self.highlight_merging = merging;
+        self
+    }
+
+    /// Sets the normalization rules of the index, applied to the words of the text before
+    /// they are compared to the matching words, see [`Index::normalization_rules`].
+    ///
+    /// [`Index::normalization_rules`]: crate::Index::normalization_rules
+    pub fn normalization_rules(&mut self, rules: BTreeMap<String, String>) -> &Self {
+        self.normalization_rules = rules;
+        self
+    }
+
     pub fn build<'t, 'm>(&'m self, text: &'t str) -> Matcher<'t, 'm, A> {
         let crop_marker = match &self.crop_marker {
             Some(marker) => marker.as_str(),
@@ -67,11 +90,25 @@ impl<'a, A> MatcherBuilder<'a, A> {
             crop_marker,
             highlight_prefix,
             highlight_suffix,
+            highlight_merging: self.highlight_merging,
+            normalization_rules: &self.normalization_rules,
             matches: None,
         }
     }
 }
 
+/// Defines which matched words are highlighted together, see [`MatcherBuilder::highlight_merging`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum HighlightMerging {
+    /// Every matched word is highlighted separately.
+    Never,
+    /// The consecutive matched words only separated by whitespaces are highlighted together.
+    Whitespace,
+    /// The consecutive matched words separated by any separator, e.g. `new-york` or
+    /// `new, york`, are highlighted together.
+    Separators,
+}
+
 #[derive(Copy, Clone, Default)]
 pub struct FormatOptions {
     pub highlight: bool,
@@ -101,6 +138,15 @@ pub struct MatchBounds {
     pub length: usize,
 }
 
+/// A highlighted part of the text, made of one or several consecutive matches.
+struct Highlight {
+    byte_start: usize,
+    byte_end: usize,
+    char_len: usize,
+    // byte end of the last matched token, after the highlight when it is a prefix.
+    token_byte_end: usize,
+}
+
 /// Structure used to analize a string, compute words that match,
 /// and format the source string, returning a highlighted and cropped sub-string.
 pub struct Matcher<'t, 'm, A> {
@@ -110,6 +156,8 @@ pub struct Matcher<'t, 'm, A> {
     crop_marker: &'m str,
     highlight_prefix: &'m str,
     highlight_suffix: &'m str,
+    highlight_merging: HighlightMerging,
+    normalization_rules: &'m BTreeMap<String, String>,
     matches: Option<(Vec<Token<'t>>, Vec<Match>)>,
 }
 
@@ -122,13 +170,13 @@ impl<'t, A: AsRef<[u8]>> Matcher<'t, '_, A> {
             mut partial: PartialMatch,
             token_position: usize,
             word_position: usize,
-            words_positions: &mut impl Iterator<Item = (usize, usize, &'a Token<'a>)>,
+            words_positions: &mut impl Iterator<Item = (usize, usize, &'a Token<'a>, &'a str)>,
             matches: &mut Vec<Match>,
         ) -> bool {
             let mut potential_matches = vec![(token_position, word_position, partial.char_len())];
 
-            for (token_position, word_position, word) in words_positions {
-                partial = match partial.match_token(word) {
+            for (token_position, word_position, word, lemma) in words_positions {
+                partial = match partial.match_normalized_token(word, lemma) {
                     // token matches the partial match, but the match is not full,
                     // we temporarly save the current token then we try to match the next one.
                     Some(MatchType::Partial(partial)) => {
@@ -169,11 +217,17 @@ impl<'t, A: AsRef<[u8]>> Matcher<'t, '_, A> {
         }
 
         let tokens: Vec<_> = self.tokenizer.tokenize(self.text).collect();
+        // the matching words are normalized by the rules like the words of the index.
+        let lemmas: Vec<_> = tokens
+            .iter()
+            .map(|token| apply_normalization_rules(token.lemma(), self.normalization_rules))
+            .collect();
         let mut matches = Vec::new();
 
         let mut words_positions = tokens
             .iter()
-            .scan((0, 0), |(token_position, word_position), token| {
+            .zip(&lemmas)
+            .scan((0, 0), |(token_position, word_position), (token, lemma)| {
                 let current_token_position = *token_position;
                 let current_word_position = *word_position;
                 *token_position += 1;
@@ -181,12 +235,12 @@ impl<'t, A: AsRef<[u8]>> Matcher<'t, '_, A> {
                     *word_position += 1;
                 }
 
-                Some((current_token_position, current_word_position, token))
+                Some((current_token_position, current_word_position, token, lemma.as_ref()))
             })
-            .filter(|(_, _, token)| !token.is_separator());
+            .filter(|(_, _, token, _)| !token.is_separator());
 
-        while let Some((token_position, word_position, word)) = words_positions.next() {
-            for match_type in self.matching_words.match_token(word) {
+        while let Some((token_position, word_position, word, lemma)) = words_positions.next() {
+            for match_type in self.matching_words.match_normalized_token(word, lemma) {
                 match match_type {
                     // we match, we save the current token as a match,
                     // then we continue the rest of the tokens.
@@ -227,16 +281,73 @@ impl<'t, A: AsRef<[u8]>> Matcher<'t, '_, A> {
     pub fn matches(&mut self) -> Vec<MatchBounds> {
         match &self.matches {
             None => self.compute_matches().matches(),
-            Some((tokens, matches)) => matches
-                .iter()
-                .map(|m| MatchBounds {
-                    start: tokens[m.token_position].byte_start,
-                    length: m.match_len,
-                })
+            Some((tokens, matches)) => self
+                .highlights(tokens, matches)
+                .into_iter()
+                .map(|h| MatchBounds { start: h.byte_start, length: h.char_len })
                 .collect(),
         }
     }
 
+    /// Returns the parts of the text to highlight for the matches, the consecutive matches
+    /// are merged into a single highlight according to the highlight merging of the matcher.
+    fn highlights(&self, tokens: &[Token], matches: &[Match]) -> Vec<Highlight> {
+        let mut highlights: Vec<Highlight> = Vec::with_capacity(matches.len());
+        let mut previous_token_position = None;
+
+        for m in matches {
+            let token = &tokens[m.token_position];
+            let highlight_byte_index = self.text[token.byte_start..]
+                .char_indices()
+                .enumerate()
+                .find(|(i, _)| *i == m.match_len)
+                .map_or(token.byte_end, |(_, (i, _))| i + token.byte_start);
+
+            let mergeable = match (highlights.last(), previous_token_position) {
+                (Some(previous), Some(previous_token_position)) => {
+                    // a prefix highlight ends in the middle of its word.
+                    previous.byte_end == previous.token_byte_end
+                        && previous_token_position < m.token_position
+                        && self.are_mergeable_separators(
+                            &tokens[previous_token_position + 1..m.token_position],
+                        )
+                }
+                _ => false,
+            };
+
+            match highlights.last_mut() {
+                Some(previous) if mergeable => {
+                    previous.char_len +=
+                        self.text[previous.byte_end..highlight_byte_index].chars().count();
+                    previous.byte_end = highlight_byte_index;
+                    previous.token_byte_end = token.byte_end;
+                }
+                _ => highlights.push(Highlight {
+                    byte_start: token.byte_start,
+                    byte_end: highlight_byte_index,
+                    char_len: m.match_len,
+                    token_byte_end: token.byte_end,
+                }),
+            }
+            previous_token_position = Some(m.token_position);
+        }
+
+        highlights
+    }
+
+    /// Returns `true` if the highlights of the matches surrounding these tokens can be merged.
+    fn are_mergeable_separators(&self, tokens: &[Token]) -> bool {
+        let is_mergeable = |token: &Token| match self.highlight_merging {
+            HighlightMerging::Never => false,
+            HighlightMerging::Whitespace => {
+                token.is_separator()
+                    && self.text[token.byte_start..token.byte_end].chars().all(char::is_whitespace)
+            }
+            HighlightMerging::Separators => token.is_separator(),
+        };
+        !tokens.is_empty() && tokens.iter().all(is_mergeable)
+    }
+
     /// Returns the bounds in byte index of the crop window.
     fn crop_bounds(&self, tokens: &[Token], matches: &[Match], crop_size: usize) -> (usize, usize) {
         // if there is no match, we start from the beginning of the string by default.
@@ -445,27 +556,21 @@ impl<'t, A: AsRef<[u8]>> Matcher<'t, '_, A> {
 
                     if format_options.highlight {
                         // insert highlight markers around matches.
-                        for m in matches {
-                            let token = &tokens[m.token_position];
-
-                            if byte_index < token.byte_start {
-                                formatted.push(&self.text[byte_index..token.byte_start]);
+                        for highlight in self.highlights(tokens, matches) {
+                            if byte_index < highlight.byte_start {
+                                formatted.push(&self.text[byte_index..highlight.byte_start]);
                             }
 
-                            let highlight_byte_index = self.text[token.byte_start..]
-                                .char_indices()
-                                .enumerate()
-                                .find(|(i, _)| *i == m.match_len)
-                                .map_or(token.byte_end, |(_, (i, _))| i + token.byte_start);
                             formatted.push(self.highlight_prefix);
-                            formatted.push(&self.text[token.byte_start..highlight_byte_index]);
+                            formatted.push(&self.text[highlight.byte_start..highlight.byte_end]);
                             formatted.push(self.highlight_suffix);
                             // if it's a prefix highlight, we put the end of the word after the highlight marker.
-                            if highlight_byte_index < token.byte_end {
-                                formatted.push(&self.text[highlight_byte_index..token.byte_end]);
+                            if highlight.byte_end < highlight.token_byte_end {
+                                formatted
+                                    .push(&self.text[highlight.byte_end..highlight.token_byte_end]);
                             }
 
-                            byte_index = token.byte_end;
+                            byte_index = highlight.token_byte_end;
                         }
                     }
 
@@ -496,6 +601,7 @@ impl<'t, A: AsRef<[u8]>> Matcher<'t, '_, A> {
 mod tests {
     use std::rc::Rc;
 
+    use big_s::S;
     use charabia::TokenizerBuilder;
 
     use super::*;
@@ -862,4 +968,82 @@ mod tests {
             @"_the_ _do_ _or_ die can't be he _do_ and or isn'_t_ _he_"
         );
     }
+
+    #[test]
+    fn merge_adjacent_highlights() {
+        let all = vec![
+            Rc::new(MatchingWord::new("new".to_string(), 0, false).unwrap()),
+            Rc::new(MatchingWord::new("york".to_string(), 0, false).unwrap()),
+        ];
+        // the "new york" phrase.
+        let matching_words = vec![(vec![all[0].clone(), all[1].clone()], vec![0, 1])];
+        let matching_words = MatchingWords::new(matching_words);
+
+        let mut builder = MatcherBuilder::from_matching_words(matching_words);
+        let format_options = FormatOptions { highlight: true, crop: None };
+        let text = "I moved from New York to New-York.";
+
+        let mut matcher = builder.build(text);
+        insta::assert_snapshot!(
+            matcher.format(format_options),
+            @"I moved from <em>New</em> <em>York</em> to <em>New</em>-<em>York</em>."
+        );
+
+        builder.highlight_merging(HighlightMerging::Whitespace);
+        let mut matcher = builder.build(text);
+        insta::assert_snapshot!(
+            matcher.format(format_options),
+            @"I moved from <em>New York</em> to <em>New</em>-<em>York</em>."
+        );
+        assert_eq!(
+            matcher.matches(),
+            vec![
+                MatchBounds { start: 13, length: 8 },
+                MatchBounds { start: 25, length: 3 },
+                MatchBounds { start: 29, length: 4 },
+            ]
+        );
+
+        builder.highlight_merging(HighlightMerging::Separators);
+        let mut matcher = builder.build(text);
+        insta::assert_snapshot!(
+            matcher.format(format_options),
+            @"I moved from <em>New York</em> to <em>New-York</em>."
+        );
+    }
+
+    #[test]
+    fn highlight_normalization_rules() {
+        let all = vec![
+            Rc::new(MatchingWord::new("strasse".to_string(), 0, false).unwrap()),
+            Rc::new(MatchingWord::new("stras".to_string(), 0, true).unwrap()),
+        ];
+        let format_options = FormatOptions { highlight: true, crop: None };
+        let text = "Die Straße und die Strasse.";
+
+        let matching_words = MatchingWords::new(vec![(vec![all[0].clone()], vec![0])]);
+        let mut builder = MatcherBuilder::from_matching_words(matching_words);
+        let mut matcher = builder.build(text);
+        insta::assert_snapshot!(
+            matcher.format(format_options),
+            @"Die Straße und die <em>Strasse</em>."
+        );
+
+        builder.normalization_rules(maplit::btreemap! { S("ß") => S("ss") });
+        let mut matcher = builder.build(text);
+        insta::assert_snapshot!(
+            matcher.format(format_options),
+            @"Die <em>Straße</em> und die <em>Strasse</em>."
+        );
+
+        // the prefix of a word changed by the rules can't be mapped back to the text.
+        let matching_words = MatchingWords::new(vec![(vec![all[1].clone()], vec![0])]);
+        let mut builder = MatcherBuilder::from_matching_words(matching_words);
+        builder.normalization_rules(maplit::btreemap! { S("ß") => S("ss") });
+        let mut matcher = builder.build(text);
+        insta::assert_snapshot!(
+            matcher.format(format_options),
+            @"Die <em>Straße</em> und die <em>Stras</em>se."
+        );
+    }
 }
@@ -4,52 +4,95 @@ use std::fmt;
 use std::mem::take;
 use std::result::Result as StdResult;
 use std::str::Utf8Error;
-use std::time::Instant;
+use std::time::{Duration, Instant};
 
-use charabia::TokenizerBuilder;
+use charabia::{Tokenizer, TokenizerBuilder};
 use distinct::{Distinct, DocIter, FacetDistinct, NoopDistinct};
 use fst::automaton::Str;
 use fst::{Automaton, IntoStreamer, Streamer};
 use levenshtein_automata::{LevenshteinAutomatonBuilder as LevBuilder, DFA};
-use log::debug;
-use once_cell::sync::Lazy;
+use log::{debug, warn};
+use once_cell::sync::{Lazy, OnceCell};
 use roaring::bitmap::RoaringBitmap;
 
-pub use self::facet::{FacetDistribution, Filter, DEFAULT_VALUES_PER_FACET};
+pub use self::candidate_metrics::CandidateMetrics;
+pub use self::correction::QueryCorrection;
+pub use self::explain::{
+    DocumentExplanation, FilterClauseExplanation, QueryWordExplanation, SortValueExplanation,
+    WordMatchExplanation,
+};
+pub use self::facet::{
+    FacetDistribution, FacetDistributionResult, Filter, NumericFacetStats, DEFAULT_VALUES_PER_FACET,
+};
 use self::fst_utils::{Complement, Intersection, StartsWith, Union};
 pub use self::matches::{
-    FormatOptions, MatchBounds, Matcher, MatcherBuilder, MatchingWord, MatchingWords,
+    FormatOptions, HighlightMerging, MatchBounds, Matcher, MatcherBuilder, MatchingWord,
+    MatchingWords,
 };
-use self::query_tree::QueryTreeBuilder;
+use self::query_tree::{Operation, PrimitiveQuery, PrimitiveQueryPart, QueryTreeBuilder};
+pub use self::query_tree_debug::QueryTreeNode;
+use self::scoped_terms::{extract_scoped_terms, scoped_terms_candidates, ScopedTerm};
+pub(crate) use self::selectivity::estimate_selectivity;
+pub use self::tie_breaker::TieBreaker;
+use self::tie_breaker::TieBreaking;
 use crate::error::UserError;
 use crate::search::criteria::r#final::{Final, FinalResult};
-use crate::search::criteria::InitialCandidates;
-use crate::{AscDesc, Criterion, DocumentId, Index, Member, Result};
+use crate::search::criteria::{CriteriaTimings, InitialCandidates};
+use crate::update::TokenizerConfig;
+use crate::{
+    preserved_form_prefix, AscDesc, Condition, Criterion, DocumentId, FilterCondition, Index,
+    Member, Result, Token,
+};
 
 // Building these factories is not free.
 static LEVDIST0: Lazy<LevBuilder> = Lazy::new(|| LevBuilder::new(0, true));
 static LEVDIST1: Lazy<LevBuilder> = Lazy::new(|| LevBuilder::new(1, true));
 static LEVDIST2: Lazy<LevBuilder> = Lazy::new(|| LevBuilder::new(2, true));
 
+mod candidate_metrics;
+mod correction;
 mod criteria;
 mod distinct;
+mod explain;
 pub mod facet;
 mod fst_utils;
 mod matches;
 mod query_tree;
+mod query_tree_debug;
+mod scoped_terms;
+mod selectivity;
+mod tie_breaker;
 
 pub struct Search<'a> {
     query: Option<String>,
     // this should be linked to the String in the query
     filter: Option<Filter<'a>>,
+    or_filter: Option<Filter<'a>>,
+    facet_value: Option<(&'a str, &'a str)>,
     offset: usize,
     limit: usize,
     sort_criteria: Option<Vec<AscDesc>>,
+    sort_missing_values: MissingValues,
+    disabled_criteria: Vec<Criterion>,
     terms_matching_strategy: TermsMatchingStrategy,
     authorize_typos: bool,
+    synonyms_in_phrases: bool,
+    phrase_order: PhraseOrder,
+    case_sensitive: bool,
     words_limit: usize,
     exhaustive_number_hits: bool,
     criterion_implementation_strategy: CriterionImplementationStrategy,
+    exactness: ExactnessConfig,
+    max_candidates: Option<usize>,
+    tie_breaker: TieBreaker,
+    field_tiers: Vec<String>,
+    ignore_unsearchable_scoped_terms: bool,
+    empty_query: EmptyQuery,
+    min_matched_words: Option<usize>,
+    tokenizer_config: Option<TokenizerConfig>,
+    /// The context of the criteria, kept here for the iterators returned
+    /// by [`Search::execute_iter`] to borrow it.
+    criteria_builder: OnceCell<criteria::CriteriaBuilder<'a>>,
     rtxn: &'a heed::RoTxn<'a>,
     index: &'a Index,
 }
@@ -59,19 +102,70 @@ impl<'a> Search<'a> {
         Search {
             query: None,
             filter: None,
+            or_filter: None,
+            facet_value: None,
             offset: 0,
             limit: 20,
             sort_criteria: None,
+            sort_missing_values: MissingValues::default(),
+            disabled_criteria: Vec::new(),
             terms_matching_strategy: TermsMatchingStrategy::default(),
             authorize_typos: true,
+            synonyms_in_phrases: false,
+            phrase_order: PhraseOrder::default(),
+            case_sensitive: false,
             exhaustive_number_hits: false,
             words_limit: 10,
             criterion_implementation_strategy: CriterionImplementationStrategy::default(),
+            exactness: ExactnessConfig::default(),
+            max_candidates: None,
+            tie_breaker: TieBreaker::default(),
+            field_tiers: Vec::new(),
+            ignore_unsearchable_scoped_terms: false,
+            empty_query: EmptyQuery::default(),
+            min_matched_words: None,
+            tokenizer_config: None,
+            criteria_builder: OnceCell::new(),
             rtxn,
             index,
         }
     }
 
+    /// Returns a copy of the parameters of this search, for the searches derived from it
+    /// that override some of them. The criteria context is not copied.
+    pub(crate) fn derive(&self) -> Search<'a> {
+        Search {
+            query: self.query.clone(),
+            filter: self.filter.clone(),
+            or_filter: self.or_filter.clone(),
+            facet_value: self.facet_value,
+            offset: self.offset,
+            limit: self.limit,
+            sort_criteria: self.sort_criteria.clone(),
+            sort_missing_values: self.sort_missing_values,
+            disabled_criteria: self.disabled_criteria.clone(),
+            terms_matching_strategy: self.terms_matching_strategy,
+            authorize_typos: self.authorize_typos,
+            synonyms_in_phrases: self.synonyms_in_phrases,
+            phrase_order: self.phrase_order,
+            case_sensitive: self.case_sensitive,
+            words_limit: self.words_limit,
+            exhaustive_number_hits: self.exhaustive_number_hits,
+            criterion_implementation_strategy: self.criterion_implementation_strategy,
+            exactness: self.exactness,
+            max_candidates: self.max_candidates,
+            tie_breaker: self.tie_breaker,
+            field_tiers: self.field_tiers.clone(),
+            ignore_unsearchable_scoped_terms: self.ignore_unsearchable_scoped_terms,
+            empty_query: self.empty_query,
+            min_matched_words: self.min_matched_words,
+            tokenizer_config: self.tokenizer_config.clone(),
+            criteria_builder: OnceCell::new(),
+            rtxn: self.rtxn,
+            index: self.index,
+        }
+    }
+
     pub fn query(&mut self, query: impl Into<String>) -> &mut Search<'a> {
         self.query = Some(query.into());
         self
@@ -92,6 +186,24 @@ impl<'a> Search<'a> {
         self
     }
 
+    /// Whether the documents without a value for a field of the sort criteria are returned
+    /// before or after the sorted documents, by default they are returned after them,
+    /// regardless of the sort order.
+    pub fn sort_missing_values(&mut self, missing_values: MissingValues) -> &mut Search<'a> {
+        self.sort_missing_values = missing_values;
+        self
+    }
+
+    /// Removes these ranking rules from the ranking rules of the index for this search only,
+    /// the ranking rules that are not part of the ranking rules of the index are ignored.
+    ///
+    /// The `words` ranking rule can only be disabled when all the words of the query are
+    /// mandatory, and the `attribute` ranking rule when no field boost is configured.
+    pub fn disable_criteria(&mut self, criteria: &[Criterion]) -> &mut Search<'a> {
+        self.disabled_criteria = criteria.to_vec();
+        self
+    }
+
     pub fn terms_matching_strategy(&mut self, value: TermsMatchingStrategy) -> &mut Search<'a> {
         self.terms_matching_strategy = value;
         self
@@ -102,6 +214,30 @@ impl<'a> Search<'a> {
         self
     }
 
+    /// Whether the words of the quoted phrases of the query are also replaced by their synonyms,
+    /// by default the phrases are matched literally.
+    pub fn synonyms_in_phrases(&mut self, value: bool) -> &mut Search<'a> {
+        self.synonyms_in_phrases = value;
+        self
+    }
+
+    /// Whether the words of the phrases with a slop must appear in the order of the phrase,
+    /// see [`PhraseOrder`], by default they must.
+    pub fn phrase_order(&mut self, value: PhraseOrder) -> &mut Search<'a> {
+        self.phrase_order = value;
+        self
+    }
+
+    /// Whether the words of the query are matched with their case, by default they are not.
+    ///
+    /// The case-sensitive searches only match the words of the case-sensitive attributes
+    /// of the index, and count the case differences as typos, the accent differences too
+    /// whatever the [`TypoOnDiacritics`](crate::TypoOnDiacritics) of the index.
+    pub fn case_sensitive(&mut self, value: bool) -> &mut Search<'a> {
+        self.case_sensitive = value;
+        self
+    }
+
     pub fn words_limit(&mut self, value: usize) -> &mut Search<'a> {
         self.words_limit = value;
         self
@@ -112,6 +248,31 @@ impl<'a> Search<'a> {
         self
     }
 
+    /// Also returns the documents matching this filter but not the query, they are ranked
+    /// after all the documents matching the query, whatever their relevancy.
+    ///
+    /// These filter-only documents are ranked among themselves like a search without query,
+    /// by the criteria that don't depend on the query, e.g. `sort`, `asc` and `desc`. The
+    /// [`Search::filter`] restricts both kinds of documents, and the distinct attribute is
+    /// applied to each kind separately. The documents matching the query are exhaustively
+    /// computed to know which documents only match the filter, and this filter is only
+    /// applied by [`Search::execute`].
+    pub fn or_filter(&mut self, condition: Filter<'a>) -> &mut Search<'a> {
+        self.or_filter = Some(condition);
+        self
+    }
+
+    /// Restricts the candidates to the documents having exactly this value for this filterable
+    /// field, like an equality filter but without having to escape the field nor the value.
+    ///
+    /// Without query, the candidates are only ranked by the criteria that don't depend on the
+    /// query, e.g. the `sort`, `asc`, `desc` and `boost` ranking rules, which is useful to
+    /// retrieve the top documents of a category.
+    pub fn facet_value(&mut self, field: &'a str, value: &'a str) -> &mut Search<'a> {
+        self.facet_value = Some((field, value));
+        self
+    }
+
     /// Force the search to exhastivelly compute the number of candidates,
     /// this will increase the search time but allows finite pagination.
     pub fn exhaustive_number_hits(&mut self, exhaustive_number_hits: bool) -> &mut Search<'a> {
@@ -127,41 +288,232 @@ impl<'a> Search<'a> {
         self
     }
 
+    /// Configures how the `exactness` ranking rule ranks the candidates, by default the
+    /// documents with an attribute equal to or starting with the query are ranked before
+    /// the documents containing the most exact words.
+    pub fn exactness(&mut self, config: ExactnessConfig) -> &mut Search<'a> {
+        self.exactness = config;
+        self
+    }
+
+    /// Bounds the number of candidates ranked by the costly criteria to `max_candidates`.
+    ///
+    /// When a bucket of the cheapest criteria (i.e. `words` and `typo`) contains more than
+    /// `max_candidates` documents, only its first `max_candidates` documents by internal id are
+    /// ranked by the following criteria. The other documents of the bucket are returned after
+    /// the ranked ones and are only sorted by the criteria that don't depend on the query.
+    /// The results are thus approximate beyond the cutoff, but the number of candidates stays
+    /// the same.
+    pub fn set_max_candidates(&mut self, max_candidates: usize) -> &mut Search<'a> {
+        self.max_candidates = Some(max_candidates);
+        self
+    }
+
+    /// How the documents ranked equally by all the ranking rules are ordered,
+    /// by ascending internal document id by default.
+    ///
+    /// The internal ids change when the documents are indexed again, use
+    /// [`TieBreaker::ExternalIdAsc`] to keep the same order, e.g. to paginate across
+    /// reindexations. The distinct attribute still keeps the document with the smallest
+    /// internal id of each group.
+    pub fn tie_breaker(&mut self, tie_breaker: TieBreaker) -> &mut Search<'a> {
+        self.tie_breaker = tie_breaker;
+        self
+    }
+
+    /// Matches the query in the given fields in order: the documents matching the query
+    /// in the first field are ranked first, then the other documents matching it in the second
+    /// field and so on. The documents that don't match the query in any of these fields, but
+    /// in another searchable field, are ranked last.
+    ///
+    /// The tiers take precedence over all the ranking rules, which only rank the documents
+    /// of a tier between themselves: a document matching only in the second field is always
+    /// ranked below a document matching in the first field, whatever its number of matched
+    /// words, typos or proximity. A document belongs to the first field it matches the whole
+    /// query in, according to the terms matching strategy. The unknown fields are ignored.
+    pub fn field_tiers(&mut self, fields: Vec<String>) -> &mut Search<'a> {
+        self.field_tiers = fields;
+        self
+    }
+
+    /// Whether the `field:term` terms of the query scoped to a field that isn't searchable
+    /// are searched in all the fields instead of returning an error, `false` by default.
+    ///
+    /// The terms scoped to a searchable field only match the documents containing all their
+    /// words, exactly, in this field. The prefixes that aren't the name of a field of the
+    /// index, as in `12:30`, and the terms of the phrases are never scoped.
+    pub fn ignore_unsearchable_scoped_terms(&mut self, value: bool) -> &mut Search<'a> {
+        self.ignore_unsearchable_scoped_terms = value;
+        self
+    }
+
+    /// Chooses the documents returned when the query is empty, that is when there is no query
+    /// or when it doesn't contain any word, e.g. only stop words or punctuation.
+    ///
+    /// By default, all the documents matching the filters are returned, see [`EmptyQuery`].
+    pub fn empty_query(&mut self, behavior: EmptyQuery) -> &mut Search<'a> {
+        self.empty_query = behavior;
+        self
+    }
+
+    /// Excludes the documents containing fewer than `min_matched_words` distinct words
+    /// of the query, whatever the terms matching strategy.
+    ///
+    /// A word is matched with the same typos, prefix, splits and synonyms as in the query tree,
+    /// a phrase counts as a single word. When the query contains fewer words, all of them
+    /// are required.
+    pub fn set_min_matched_words(&mut self, min_matched_words: usize) -> &mut Search<'a> {
+        self.min_matched_words = Some(min_matched_words);
+        self
+    }
+
+    /// Tokenizes the query with the given tokenizer configuration, which must be the
+    /// configuration the documents were indexed with, see [`TokenizerConfig`].
+    pub fn tokenizer_config(&mut self, config: TokenizerConfig) -> &mut Search<'a> {
+        self.tokenizer_config = Some(config);
+        self
+    }
+
+    /// Logs a warning if the documents of the index were tokenized with another
+    /// tokenizer configuration than the one of the search.
+    fn check_tokenizer_fingerprint(&self) -> Result<()> {
+        let fingerprint = self.tokenizer_config.as_ref().map(TokenizerConfig::fingerprint);
+        if self.index.tokenizer_fingerprint(self.rtxn)? != fingerprint {
+            warn!(
+                "The query is tokenized with another tokenizer configuration than the \
+                 documents of the index, some of its words may not be found"
+            );
+        }
+        Ok(())
+    }
+
+    /// Returns the query without the `field:` prefixes of its scoped terms, and these terms.
+    fn scoped_query(&self) -> Result<Option<(Cow<str>, Vec<ScopedTerm>)>> {
+        match self.query.as_deref() {
+            Some(query) => {
+                let fields_ids_map = self.index.fields_ids_map(self.rtxn)?;
+                let searchable_fields_ids = self.index.searchable_fields_ids(self.rtxn)?;
+                extract_scoped_terms(
+                    query,
+                    &fields_ids_map,
+                    searchable_fields_ids.as_deref(),
+                    self.ignore_unsearchable_scoped_terms,
+                )
+                .map(Some)
+            }
+            None => Ok(None),
+        }
+    }
+
     fn is_typo_authorized(&self) -> Result<bool> {
         let index_authorizes_typos = self.index.authorize_typos(self.rtxn)?;
         // only authorize typos if both the index and the query allow it.
         Ok(self.authorize_typos && index_authorizes_typos)
     }
 
-    pub fn execute(&self) -> Result<SearchResult> {
-        // We create the query tree by spliting the query into tokens.
-        let before = Instant::now();
-        let (query_tree, primitive_query, matching_words) = match self.query.as_ref() {
-            Some(query) => {
-                let mut builder = QueryTreeBuilder::new(self.rtxn, self.index)?;
-                builder.terms_matching_strategy(self.terms_matching_strategy);
-
-                builder.authorize_typos(self.is_typo_authorized()?);
-
-                builder.words_limit(self.words_limit);
-                // We make sure that the analyzer is aware of the stop words
-                // this ensures that the query builder is able to properly remove them.
-                let mut tokbuilder = TokenizerBuilder::new();
-                let stop_words = self.index.stop_words(self.rtxn)?;
-                if let Some(ref stop_words) = stop_words {
-                    tokbuilder.stop_words(stop_words);
-                }
+    fn query_tree_builder(&self) -> Result<QueryTreeBuilder<'a>> {
+        let mut builder = QueryTreeBuilder::new(self.rtxn, self.index)?;
+        builder.terms_matching_strategy(self.terms_matching_strategy);
+
+        builder.authorize_typos(self.is_typo_authorized()?);
+        builder.synonyms_in_phrases(self.synonyms_in_phrases);
+        builder.phrase_order(self.phrase_order);
+        builder.case_sensitive(self.case_sensitive);
+
+        builder.words_limit(self.words_limit);
+        Ok(builder)
+    }
+
+    /// Builds the query tree of the search, along with its primitive query and matching words.
+    ///
+    /// Returns `None` if there is no query or if the query doesn't contain any word.
+    fn build_query_tree(&self) -> Result<Option<(Operation, PrimitiveQuery, MatchingWords)>> {
+        let query = match self.scoped_query()? {
+            Some((query, _)) => query,
+            None => return Ok(None),
+        };
+
+        let mut builder = self.query_tree_builder()?;
+        builder.original_query(&query);
+        self.check_tokenizer_fingerprint()?;
+        self.with_query_tokenizer(|tokenizer| builder.build(tokenizer.tokenize(&query)))
+    }
+
+    /// Calls `f` with the tokenizer of the query, aware of the stop words and of the
+    /// languages of all the fields.
+    fn with_query_tokenizer<T>(&self, f: impl FnOnce(&Tokenizer<&[u8]>) -> Result<T>) -> Result<T> {
+        let mut tokbuilder = TokenizerBuilder::new();
+        if let Some(tokenizer_config) = &self.tokenizer_config {
+            tokenizer_config.configure(&mut tokbuilder);
+        }
+        // We make sure that the analyzer is aware of the stop words
+        // this ensures that the query builder is able to properly remove them.
+        let stop_words = self.index.stop_words(self.rtxn)?;
+        if let Some(ref stop_words) = stop_words {
+            tokbuilder.stop_words(stop_words);
+        }
+        // The query isn't scoped to a field, so it is tokenized
+        // with the union of the languages of all the fields.
+        let allow_list = self.index.query_allow_list(self.rtxn)?;
+        if !allow_list.is_empty() {
+            tokbuilder.allow_list(&allow_list);
+        }
+
+        let tokenizer = tokbuilder.build();
+        f(&tokenizer)
+    }
 
-                let tokenizer = tokbuilder.build();
-                let tokens = tokenizer.tokenize(query);
-                builder
-                    .build(tokens)?
-                    .map_or((None, None, None), |(qt, pq, mw)| (Some(qt), Some(pq), Some(mw)))
+    /// Returns the documents containing at least `min_matched_words` distinct parts of the
+    /// primitive query, or all of them when there are fewer parts, `None` if no part is required.
+    fn min_matched_words_candidates(
+        &self,
+        primitive_query: &[PrimitiveQueryPart],
+        min_matched_words: usize,
+    ) -> Result<Option<RoaringBitmap>> {
+        let mut parts: Vec<PrimitiveQueryPart> = Vec::new();
+        for part in primitive_query {
+            if !parts.contains(part) {
+                parts.push(part.clone());
             }
+        }
+
+        let operations = self.query_tree_builder()?.primitive_parts_operations(&parts)?;
+        let ctx = self
+            .criteria_builder
+            .get_or_try_init(|| criteria::CriteriaBuilder::new(self.rtxn, self.index))?;
+        let mut wdcache = WordDerivationsCache::new();
+
+        // `at_least[i]` contains the documents matching at least `i + 1` of the words seen so far.
+        let min_matched_words = min_matched_words.min(operations.len());
+        if min_matched_words == 0 {
+            return Ok(None);
+        }
+
+        let mut at_least = vec![RoaringBitmap::new(); min_matched_words];
+        for operation in &operations {
+            let docids = criteria::resolve_query_tree(ctx, operation, &mut wdcache)?;
+            for i in (1..min_matched_words).rev() {
+                let matched_one_more = &at_least[i - 1] & &docids;
+                at_least[i] |= matched_one_more;
+            }
+            at_least[0] |= docids;
+        }
+
+        Ok(at_least.pop())
+    }
+
+    /// Builds the query tree and evaluates the filters of the search,
+    /// after having checked that the search can sort by its sort criteria.
+    fn prepare(&self) -> Result<PreparedSearch> {
+        // We create the query tree by spliting the query into tokens.
+        let before = Instant::now();
+        let (query_tree, primitive_query, matching_words) = match self.build_query_tree()? {
+            Some((qt, pq, mw)) => (Some(qt), Some(pq), Some(mw)),
             None => (None, None, None),
         };
 
-        debug!("query tree: {:?} took {:.02?}", query_tree, before.elapsed());
+        let query_parsing = before.elapsed();
+        debug!("query tree: {:?} took {:.02?}", query_tree, query_parsing);
 
         // We create the original candidates with the facet conditions results.
         let before = Instant::now();
@@ -169,6 +521,34 @@ impl<'a> Search<'a> {
             Some(condition) => Some(condition.evaluate(self.rtxn, self.index)?),
             None => None,
         };
+        let filtered_candidates = match self.facet_value {
+            Some((field, value)) => {
+                let condition = FilterCondition::Condition {
+                    fid: Token::from(field),
+                    op: Condition::Equal(Token::from(value)),
+                };
+                let facet_value_candidates =
+                    Filter::from(condition).evaluate(self.rtxn, self.index)?;
+                match filtered_candidates {
+                    Some(filtered_candidates) => Some(filtered_candidates & facet_value_candidates),
+                    None => Some(facet_value_candidates),
+                }
+            }
+            None => filtered_candidates,
+        };
+        // The scoped terms restrict the candidates to the documents containing them in their field.
+        let filtered_candidates = match self.scoped_query()? {
+            Some((_, scoped_terms)) if !scoped_terms.is_empty() => {
+                match scoped_terms_candidates(self, &scoped_terms)? {
+                    Some(scoped_candidates) => match filtered_candidates {
+                        Some(filtered_candidates) => Some(filtered_candidates & scoped_candidates),
+                        None => Some(scoped_candidates),
+                    },
+                    None => filtered_candidates,
+                }
+            }
+            _ => filtered_candidates,
+        };
 
         debug!("facet candidates: {:?} took {:.02?}", filtered_candidates, before.elapsed());
 
@@ -195,62 +575,374 @@ impl<'a> Search<'a> {
             }
         }
 
+        // We check that the disabled ranking rules can be removed without breaking the ranking.
+        for criterion in &self.disabled_criteria {
+            let reason = match criterion {
+                Criterion::Words if self.terms_matching_strategy != TermsMatchingStrategy::All => {
+                    "it ranks the documents matching only some of the query words"
+                }
+                Criterion::Attribute if !self.index.field_boosts(self.rtxn)?.is_empty() => {
+                    "the field boosts of the index are applied by it"
+                }
+                _ => continue,
+            };
+            return Err(UserError::CriterionCannotBeDisabled {
+                criterion: criterion.to_string(),
+                reason,
+            }
+            .into());
+        }
+
         // We check that the sort ranking rule exists and throw an
         // error if we try to use it and that it doesn't.
-        let sort_ranking_rule_missing = !self.index.criteria(self.rtxn)?.contains(&Criterion::Sort);
+        let sort_ranking_rule_missing = !self.index.criteria(self.rtxn)?.contains(&Criterion::Sort)
+            || self.disabled_criteria.contains(&Criterion::Sort);
         let empty_sort_criteria = self.sort_criteria.as_ref().map_or(true, |s| s.is_empty());
         if sort_ranking_rule_missing && !empty_sort_criteria {
             return Err(UserError::SortRankingRuleMissing.into());
         }
 
-        let criteria_builder = criteria::CriteriaBuilder::new(self.rtxn, self.index)?;
+        // The documents matching too few words of the query are excluded.
+        let min_matched_words_candidates = match (self.min_matched_words, &primitive_query) {
+            (Some(min_matched_words), Some(primitive_query)) => {
+                self.min_matched_words_candidates(primitive_query, min_matched_words)?
+            }
+            _ => None,
+        };
+        let filtered_candidates = match (filtered_candidates, min_matched_words_candidates) {
+            (Some(filtered_candidates), Some(candidates)) => Some(filtered_candidates & candidates),
+            (filtered_candidates, candidates) => filtered_candidates.or(candidates),
+        };
 
-        match self.index.distinct_field(self.rtxn)? {
+        // Without query, the documents are only returned if the search is configured to.
+        let filtered_candidates = match self.empty_query {
+            EmptyQuery::NoDocuments if query_tree.is_none() => Some(RoaringBitmap::new()),
+            _ => filtered_candidates,
+        };
+
+        Ok(PreparedSearch {
+            query_tree,
+            primitive_query,
+            matching_words,
+            filtered_candidates,
+            query_parsing,
+        })
+    }
+
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "trace", name = "Search::execute", skip_all)
+    )]
+    pub fn execute(&self) -> Result<SearchResult> {
+        self.execute_with(self.exhaustive_number_hits)
+    }
+
+    /// Executes the search and measures the time spent in each of its phases,
+    /// see [`SearchTimings`].
+    ///
+    /// The ranking rules are only measured by this method, [`Search::execute`] doesn't.
+    pub fn execute_with_timings(&self) -> Result<(SearchResult, SearchTimings)> {
+        let mut timings = SearchTimings::default();
+        let result = self.execute_timed(self.exhaustive_number_hits, Some(&mut timings))?;
+        Ok((result, timings))
+    }
+
+    /// Executes the search, exhaustively computing its candidates when `exhaustive_number_hits`
+    /// is `true`, whatever the option of the search is.
+    pub(crate) fn execute_with(&self, exhaustive_number_hits: bool) -> Result<SearchResult> {
+        self.execute_timed(exhaustive_number_hits, None)
+    }
+
+    /// Executes the search, measuring the time spent in each of its phases in the
+    /// given `timings` if any.
+    fn execute_timed(
+        &self,
+        exhaustive_number_hits: bool,
+        mut timings: Option<&mut SearchTimings>,
+    ) -> Result<SearchResult> {
+        let started = Instant::now();
+        let PreparedSearch {
+            query_tree,
+            primitive_query,
+            matching_words,
+            filtered_candidates,
+            query_parsing,
+        } = self.prepare()?;
+        let before = Instant::now();
+
+        // The documents only matching the `or_filter` are ranked after all the documents
+        // matching the query, which must therefore be exhaustively known.
+        let or_filter_candidates = match &self.or_filter {
+            Some(condition) => {
+                let mut candidates = condition.evaluate(self.rtxn, self.index)?;
+                if let Some(filtered_candidates) = &filtered_candidates {
+                    candidates &= filtered_candidates;
+                }
+                Some(candidates)
+            }
+            None => None,
+        };
+        let exhaustive_number_hits = exhaustive_number_hits || or_filter_candidates.is_some();
+        let candidates_resolution =
+            before.duration_since(started).saturating_sub(query_parsing) + before.elapsed();
+
+        let mut result = self.rank(
+            query_tree,
+            primitive_query,
+            filtered_candidates,
+            matching_words.unwrap_or_default(),
+            exhaustive_number_hits,
+            self.offset,
+            self.limit,
+            timings.as_deref_mut(),
+        )?;
+
+        if let Some(or_filter_candidates) = or_filter_candidates {
+            let filter_only_candidates = or_filter_candidates - &result.candidates;
+            if !filter_only_candidates.is_empty() {
+                let query_hits = result.candidates.len() as usize;
+                let filter_only = self.rank(
+                    None,
+                    None,
+                    Some(filter_only_candidates),
+                    MatchingWords::default(),
+                    true,
+                    self.offset.saturating_sub(query_hits),
+                    self.limit - result.documents_ids.len(),
+                    timings.as_deref_mut(),
+                )?;
+                result.documents_ids.extend(filter_only.documents_ids);
+                result.candidates |= filter_only.candidates;
+            }
+        }
+
+        if let Some(timings) = timings {
+            timings.query_parsing = query_parsing;
+            timings.candidates_resolution = candidates_resolution;
+            timings.total = started.elapsed();
+        }
+
+        Ok(result)
+    }
+
+    /// Executes the search lazily, returning an iterator over the ranked documents.
+    ///
+    /// The iterator yields the documents one bucket of the last criterion at a time, the
+    /// candidates are only ranked when the iterator is pulled, so stopping early avoids
+    /// ranking the remaining candidates. The offset of the search is applied but not its
+    /// limit, the caller stops pulling the iterator instead.
+    pub fn execute_iter(&self) -> Result<SearchIter> {
+        let PreparedSearch {
+            query_tree,
+            primitive_query,
+            matching_words,
+            filtered_candidates,
+            query_parsing: _,
+        } = self.prepare()?;
+
+        let criteria_builder = self
+            .criteria_builder
+            .get_or_try_init(|| criteria::CriteriaBuilder::new(self.rtxn, self.index))?;
+
+        let (criteria, distinct) = match self.index.distinct_field(self.rtxn)? {
             None => {
                 let criteria = criteria_builder.build::<NoopDistinct>(
                     query_tree,
                     primitive_query,
                     filtered_candidates,
                     self.sort_criteria.clone(),
+                    self.sort_missing_values,
+                    &self.disabled_criteria,
                     self.exhaustive_number_hits,
                     None,
                     self.criterion_implementation_strategy,
+                    self.exactness,
+                    self.max_candidates,
+                    &self.field_tiers,
+                    None,
                 )?;
-                self.perform_sort(NoopDistinct, matching_words.unwrap_or_default(), criteria)
+                (Some(criteria), None)
             }
             Some(name) => {
                 let field_ids_map = self.index.fields_ids_map(self.rtxn)?;
                 match field_ids_map.id(name) {
                     Some(fid) => {
                         let distinct = FacetDistinct::new(fid, self.index, self.rtxn);
-
                         let criteria = criteria_builder.build(
                             query_tree,
                             primitive_query,
                             filtered_candidates,
                             self.sort_criteria.clone(),
+                            self.sort_missing_values,
+                            &self.disabled_criteria,
                             self.exhaustive_number_hits,
                             Some(distinct.clone()),
                             self.criterion_implementation_strategy,
+                            self.exactness,
+                            self.max_candidates,
+                            &self.field_tiers,
+                            None,
                         )?;
-                        self.perform_sort(distinct, matching_words.unwrap_or_default(), criteria)
+                        (Some(criteria), Some(distinct))
                     }
-                    None => Ok(SearchResult::default()),
+                    None => (None, None),
+                }
+            }
+        };
+
+        Ok(SearchIter {
+            criteria,
+            distinct,
+            matching_words: matching_words.unwrap_or_default(),
+            excluded_candidates: self.index.soft_deleted_documents_ids(self.rtxn)?,
+            tie_breaking: TieBreaking::new(self.tie_breaker, self.rtxn, self.index)?,
+            offset: self.offset,
+            ranked_candidates: 0,
+        })
+    }
+
+    /// Returns all the documents matching the query and the filters, without ranking them.
+    ///
+    /// The query tree is resolved according to the terms matching strategy and the distinct
+    /// attribute is applied, the documents returned are the exhaustive candidates of the search.
+    /// A placeholder search only evaluates the filters.
+    pub fn execute_candidates(&self) -> Result<RoaringBitmap> {
+        let PreparedSearch { query_tree, filtered_candidates, .. } = self.prepare()?;
+
+        let or_filter_candidates = match &self.or_filter {
+            Some(condition) => {
+                let mut candidates = condition.evaluate(self.rtxn, self.index)?;
+                if let Some(filtered_candidates) = &filtered_candidates {
+                    candidates &= filtered_candidates;
+                }
+                Some(candidates)
+            }
+            None => None,
+        };
+
+        let mut candidates = match (query_tree, filtered_candidates) {
+            (Some(query_tree), filtered_candidates) => {
+                let ctx = self
+                    .criteria_builder
+                    .get_or_try_init(|| criteria::CriteriaBuilder::new(self.rtxn, self.index))?;
+                let mut wdcache = WordDerivationsCache::new();
+                let candidates = criteria::resolve_query_tree(ctx, &query_tree, &mut wdcache)?;
+                match filtered_candidates {
+                    Some(filtered_candidates) => candidates & filtered_candidates,
+                    None => candidates,
                 }
             }
+            (None, Some(filtered_candidates)) => filtered_candidates,
+            (None, None) => self.index.documents_ids(self.rtxn)?,
+        };
+
+        if let Some(or_filter_candidates) = or_filter_candidates {
+            candidates |= or_filter_candidates;
+        }
+
+        candidates -= self.index.soft_deleted_documents_ids(self.rtxn)?;
+
+        match self.index.distinct_field(self.rtxn)? {
+            None => Ok(candidates),
+            Some(name) => match self.index.fields_ids_map(self.rtxn)?.id(name) {
+                Some(fid) => {
+                    let mut distinct = FacetDistinct::new(fid, self.index, self.rtxn);
+                    distinct.distinct(candidates, RoaringBitmap::new()).collect()
+                }
+                None => Ok(RoaringBitmap::new()),
+            },
         }
     }
 
+    /// Ranks the candidates matching the query tree and the filtered candidates,
+    /// returning the documents between `offset` and `offset + limit`.
+    ///
+    /// The time spent in the ranking rules is added to the given `timings` if any.
+    #[allow(clippy::too_many_arguments)]
+    fn rank(
+        &self,
+        query_tree: Option<Operation>,
+        primitive_query: Option<PrimitiveQuery>,
+        filtered_candidates: Option<RoaringBitmap>,
+        matching_words: MatchingWords,
+        exhaustive_number_hits: bool,
+        offset: usize,
+        limit: usize,
+        timings: Option<&mut SearchTimings>,
+    ) -> Result<SearchResult> {
+        let started = Instant::now();
+        let criteria_timings = timings.as_ref().map(|_| CriteriaTimings::default());
+        let criteria_builder = criteria::CriteriaBuilder::new(self.rtxn, self.index)?;
+
+        let result = match self.index.distinct_field(self.rtxn)? {
+            None => {
+                let criteria = criteria_builder.build::<NoopDistinct>(
+                    query_tree,
+                    primitive_query,
+                    filtered_candidates,
+                    self.sort_criteria.clone(),
+                    self.sort_missing_values,
+                    &self.disabled_criteria,
+                    exhaustive_number_hits,
+                    None,
+                    self.criterion_implementation_strategy,
+                    self.exactness,
+                    self.max_candidates,
+                    &self.field_tiers,
+                    criteria_timings.as_ref(),
+                )?;
+                self.perform_sort(NoopDistinct, matching_words, criteria, offset, limit)?
+            }
+            Some(name) => {
+                let field_ids_map = self.index.fields_ids_map(self.rtxn)?;
+                match field_ids_map.id(name) {
+                    Some(fid) => {
+                        let distinct = FacetDistinct::new(fid, self.index, self.rtxn);
+
+                        let criteria = criteria_builder.build(
+                            query_tree,
+                            primitive_query,
+                            filtered_candidates,
+                            self.sort_criteria.clone(),
+                            self.sort_missing_values,
+                            &self.disabled_criteria,
+                            exhaustive_number_hits,
+                            Some(distinct.clone()),
+                            self.criterion_implementation_strategy,
+                            self.exactness,
+                            self.max_candidates,
+                            &self.field_tiers,
+                            criteria_timings.as_ref(),
+                        )?;
+                        self.perform_sort(distinct, matching_words, criteria, offset, limit)?
+                    }
+                    None => SearchResult::default(),
+                }
+            }
+        };
+
+        if let (Some(timings), Some(criteria_timings)) = (timings, criteria_timings) {
+            let ranking = criteria_timings.criteria_timings();
+            let ranking_time: Duration = ranking.iter().map(|(_, time)| *time).sum();
+            timings.documents_fetching += started.elapsed().saturating_sub(ranking_time);
+            timings.add_ranking(ranking);
+        }
+
+        Ok(result)
+    }
+
     fn perform_sort<D: Distinct>(
         &self,
         mut distinct: D,
         matching_words: MatchingWords,
         mut criteria: Final,
+        mut offset: usize,
+        limit: usize,
     ) -> Result<SearchResult> {
-        let mut offset = self.offset;
         let mut initial_candidates = InitialCandidates::Estimated(RoaringBitmap::new());
         let mut excluded_candidates = self.index.soft_deleted_documents_ids(self.rtxn)?;
         let mut documents_ids = Vec::new();
+        let mut tie_breaking = TieBreaking::new(self.tie_breaker, self.rtxn, self.index)?;
 
         while let Some(FinalResult { candidates, initial_candidates: ic, .. }) =
             criteria.next(&excluded_candidates)?
@@ -258,7 +950,8 @@ impl<'a> Search<'a> {
             debug!("Number of candidates found {}", candidates.len());
 
             let excluded = take(&mut excluded_candidates);
-            let mut candidates = distinct.distinct(candidates, excluded);
+            let mut candidates =
+                tie_breaking.break_ties(distinct.distinct(candidates, excluded))?;
 
             initial_candidates |= ic;
 
@@ -267,22 +960,25 @@ impl<'a> Search<'a> {
                 offset = offset.saturating_sub(discarded);
             }
 
-            for candidate in candidates.by_ref().take(self.limit - documents_ids.len()) {
+            for candidate in candidates.by_ref().take(limit - documents_ids.len()) {
                 documents_ids.push(candidate?);
             }
 
             excluded_candidates |= candidates.into_excluded();
 
-            if documents_ids.len() == self.limit {
+            if documents_ids.len() == limit {
                 break;
             }
         }
 
         initial_candidates.map_inplace(|c| c - excluded_candidates);
 
+        let exhaustive_candidates = matches!(initial_candidates, InitialCandidates::Exhaustive(_));
+
         Ok(SearchResult {
             matching_words,
             candidates: initial_candidates.into_inner(),
+            exhaustive_candidates,
             documents_ids,
         })
     }
@@ -293,40 +989,250 @@ impl fmt::Debug for Search<'_> {
         let Search {
             query,
             filter,
+            or_filter,
+            facet_value,
             offset,
             limit,
             sort_criteria,
+            sort_missing_values,
+            disabled_criteria,
             terms_matching_strategy,
             authorize_typos,
+            synonyms_in_phrases,
+            phrase_order,
+            case_sensitive,
             words_limit,
             exhaustive_number_hits,
             criterion_implementation_strategy,
+            exactness,
+            max_candidates,
+            tie_breaker,
+            field_tiers,
+            ignore_unsearchable_scoped_terms,
+            empty_query,
+            min_matched_words,
+            tokenizer_config,
+            criteria_builder: _,
             rtxn: _,
             index: _,
         } = self;
         f.debug_struct("Search")
             .field("query", query)
             .field("filter", filter)
+            .field("or_filter", or_filter)
+            .field("facet_value", facet_value)
             .field("offset", offset)
             .field("limit", limit)
             .field("sort_criteria", sort_criteria)
+            .field("sort_missing_values", sort_missing_values)
+            .field("disabled_criteria", disabled_criteria)
             .field("terms_matching_strategy", terms_matching_strategy)
             .field("authorize_typos", authorize_typos)
+            .field("synonyms_in_phrases", synonyms_in_phrases)
+            .field("phrase_order", phrase_order)
+            .field("case_sensitive", case_sensitive)
             .field("exhaustive_number_hits", exhaustive_number_hits)
             .field("criterion_implementation_strategy", criterion_implementation_strategy)
+            .field("exactness", exactness)
             .field("words_limit", words_limit)
+            .field("max_candidates", max_candidates)
+            .field("tie_breaker", tie_breaker)
+            .field("field_tiers", field_tiers)
+            .field("ignore_unsearchable_scoped_terms", ignore_unsearchable_scoped_terms)
+            .field("empty_query", empty_query)
+            .field("min_matched_words", min_matched_words)
+            .field("tokenizer_config", tokenizer_config)
             .finish()
     }
 }
 
+/// The query tree and the filtered candidates of a search, see [`Search::prepare`].
+struct PreparedSearch {
+    query_tree: Option<Operation>,
+    primitive_query: Option<PrimitiveQuery>,
+    matching_words: Option<MatchingWords>,
+    filtered_candidates: Option<RoaringBitmap>,
+    /// The time spent building the query tree.
+    query_parsing: Duration,
+}
+
+/// An iterator over the documents of a search, ranked lazily, see [`Search::execute_iter`].
+///
+/// Every item is a non-empty batch of documents ids, in the ranking order.
+pub struct SearchIter<'s> {
+    criteria: Option<Final<'s>>,
+    distinct: Option<FacetDistinct<'s>>,
+    matching_words: MatchingWords,
+    excluded_candidates: RoaringBitmap,
+    tie_breaking: TieBreaking<'s>,
+    offset: usize,
+    ranked_candidates: u64,
+}
+
+impl SearchIter<'_> {
+    pub fn matching_words(&self) -> &MatchingWords {
+        &self.matching_words
+    }
+
+    /// The number of candidates the criteria have ranked so far.
+    pub fn ranked_candidates(&self) -> u64 {
+        self.ranked_candidates
+    }
+
+    fn next_batch(&mut self) -> Result<Option<Vec<DocumentId>>> {
+        let criteria = match self.criteria.as_mut() {
+            Some(criteria) => criteria,
+            None => return Ok(None),
+        };
+
+        while let Some(FinalResult { candidates, .. }) = criteria.next(&self.excluded_candidates)? {
+            debug!("Number of candidates found {}", candidates.len());
+            self.ranked_candidates += candidates.len();
+
+            let excluded = take(&mut self.excluded_candidates);
+            let documents_ids = match self.distinct.as_mut() {
+                Some(distinct) => drain_candidates(
+                    self.tie_breaking.break_ties(distinct.distinct(candidates, excluded))?,
+                    &mut self.offset,
+                    &mut self.excluded_candidates,
+                )?,
+                None => drain_candidates(
+                    self.tie_breaking.break_ties(NoopDistinct.distinct(candidates, excluded))?,
+                    &mut self.offset,
+                    &mut self.excluded_candidates,
+                )?,
+            };
+
+            if !documents_ids.is_empty() {
+                return Ok(Some(documents_ids));
+            }
+        }
+
+        Ok(None)
+    }
+}
+
+impl Iterator for SearchIter<'_> {
+    type Item = Result<Vec<DocumentId>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_batch().transpose()
+    }
+}
+
+/// Collects the documents of the candidates left after skipping the `offset` first ones,
+/// and adds the candidates excluded by the distinct to the `excluded` ones.
+fn drain_candidates(
+    mut candidates: impl DocIter,
+    offset: &mut usize,
+    excluded: &mut RoaringBitmap,
+) -> Result<Vec<DocumentId>> {
+    if *offset != 0 {
+        let discarded = candidates.by_ref().take(*offset).count();
+        *offset = offset.saturating_sub(discarded);
+    }
+
+    let documents_ids = candidates.by_ref().collect::<Result<Vec<_>>>()?;
+    *excluded |= candidates.into_excluded();
+
+    Ok(documents_ids)
+}
+
 #[derive(Default)]
 pub struct SearchResult {
     pub matching_words: MatchingWords,
     pub candidates: RoaringBitmap,
+    /// Whether the `candidates` are all the documents matching the search, they can be
+    /// an estimation when the search is not exhaustive, see [`Search::exhaustive_number_hits`].
+    pub exhaustive_candidates: bool,
     // TODO those documents ids should be associated with their criteria scores.
     pub documents_ids: Vec<DocumentId>,
 }
 
+/// The time spent in each phase of a search, see [`Search::execute_with_timings`].
+///
+/// The phases are measured one after the other, their sum is slightly lower than the
+/// `total` time of the search.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct SearchTimings {
+    /// The time spent tokenizing the query and building its query tree.
+    pub query_parsing: Duration,
+    /// The time spent evaluating the filters, the scoped terms and the minimum number
+    /// of matched words restricting the candidates.
+    pub candidates_resolution: Duration,
+    /// The time spent in each ranking rule, in the order they are applied. A ranking rule
+    /// resolving the query tree of a bucket for the first time, usually the `words` one,
+    /// also includes the time spent computing the candidates matching the query.
+    pub ranking: Vec<(String, Duration)>,
+    /// The time spent building the ranking rules and taking the documents out of the
+    /// ranked buckets, applying the distinct attribute, the tie breaker and the offset.
+    pub documents_fetching: Duration,
+    /// The time spent executing the whole search.
+    pub total: Duration,
+}
+
+impl SearchTimings {
+    /// Adds the time spent in the ranking rules of a ranking of the search.
+    fn add_ranking(&mut self, ranking: Vec<(String, Duration)>) {
+        for (name, time) in ranking {
+            match self.ranking.iter_mut().find(|(n, _)| *n == name) {
+                Some((_, total)) => *total += time,
+                None => self.ranking.push((name, time)),
+            }
+        }
+    }
+}
+
+/// How the `exactness` ranking rule ranks the candidates, see [`Search::exactness`].
+///
+/// The exactness ranking rule ranks the candidates in these levels:
+/// 1. the documents with an attribute equal to the query,
+/// 2. the documents with an attribute starting with the query,
+/// 3. the documents containing the words of the query consecutively, in an attribute,
+/// 4. the documents containing the most exact words of the query, i.e. without typo nor prefix,
+/// 5. the other documents.
+///
+/// The levels 1 and 2 are the exact attribute levels, the level 3 the exact query level
+/// and the level 4 the exact words level. The exact query level is disabled by default.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ExactnessConfig {
+    /// Whether the documents with an attribute equal to or starting with the query are ranked first.
+    pub exact_attribute: bool,
+    /// Whether the documents containing the words of the query consecutively are ranked
+    /// before the documents only containing exact words of the query.
+    pub exact_query: bool,
+    /// Whether the documents are ranked by their number of exact words of the query.
+    pub exact_words: bool,
+}
+
+impl Default for ExactnessConfig {
+    fn default() -> Self {
+        ExactnessConfig { exact_attribute: true, exact_query: false, exact_words: true }
+    }
+}
+
+/// The documents returned by a search without query, see [`Search::empty_query`].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum EmptyQuery {
+    /// All the documents matching the filters, ranked by the criteria that don't depend on
+    /// the query, e.g. the `sort`, `asc` and `desc` ranking rules.
+    #[default]
+    AllDocuments,
+    /// No document, e.g. for a search bar that must stay empty until something is typed.
+    NoDocuments,
+}
+
+/// Where the documents without a value for the sorted field are placed,
+/// see [`Search::sort_missing_values`].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum MissingValues {
+    /// Before the documents having a value.
+    First,
+    /// After the documents having a value.
+    #[default]
+    Last,
+}
+
 #[derive(Debug, Default, Clone, Copy)]
 pub enum CriterionImplementationStrategy {
     OnlyIterative,
@@ -357,6 +1263,17 @@ impl Default for TermsMatchingStrategy {
     }
 }
 
+/// Whether the words of the phrases with a slop, e.g. `"quick fox"~2`, must appear in the
+/// order of the phrase. The words of the phrases without a slop are always matched in order.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PhraseOrder {
+    /// `"quick fox"~2` matches `quick brown fox` but not `fox and quick`.
+    #[default]
+    InOrder,
+    /// `"quick fox"~2` matches both `quick brown fox` and `fox and quick`.
+    AnyOrder,
+}
+
 pub type WordDerivationsCache = HashMap<(String, bool, u8), Vec<(String, u8)>>;
 
 pub fn word_derivations<'c>(
@@ -403,6 +1320,10 @@ pub fn word_derivations<'c>(
 
                 while let Some((found_word, state)) = stream.next() {
                     let found_word = std::str::from_utf8(found_word)?;
+                    // the preserved forms of the words only derive from one another.
+                    if preserved_form_prefix(found_word) != preserved_form_prefix(word) {
+                        continue;
+                    }
                     // in the case the typo is on the first letter, we know the number of typo
                     // is two
                     if get_first(found_word) != get_first(word) {
@@ -443,8 +1364,15 @@ pub fn build_dfa(word: &str, typos: u8, is_prefix: bool) -> DFA {
 
 #[cfg(test)]
 mod test {
+    use std::collections::HashSet;
+    use std::str::FromStr;
+
+    use big_s::S;
+
     use super::*;
+    use crate::documents::documents_batch_reader_from_objects;
     use crate::index::tests::TempIndex;
+    use crate::TypoOnDiacritics;
 
     #[test]
     fn test_is_authorized_typos() {
@@ -533,4 +1461,796 @@ mod test {
 
         assert_eq!(found, &[("zealand".to_string(), 1)]);
     }
+
+    #[test]
+    fn test_matched_fields() {
+        let index = TempIndex::new();
+        index
+            .update_settings(|settings| {
+                settings.set_searchable_fields(vec![S("title"), S("description")]);
+            })
+            .unwrap();
+        index
+            .add_documents(documents!([
+                { "id": 0, "description": "a kitten sleeping", "title": "kitten" },
+                { "id": 1, "description": "a dog with a kitten", "title": "dog" },
+                { "id": 2, "description": "sleeping", "title": "dog" },
+            ]))
+            .unwrap();
+
+        let rtxn = index.read_txn().unwrap();
+        let mut search = Search::new(&rtxn, &index);
+        search.query("kitten");
+        search.terms_matching_strategy(TermsMatchingStrategy::All);
+
+        let SearchResult { documents_ids, .. } = search.execute().unwrap();
+        let matched_fields = search.matched_fields(&documents_ids).unwrap();
+        let matched_fields: HashMap<_, _> = documents_ids.into_iter().zip(matched_fields).collect();
+        assert_eq!(
+            matched_fields,
+            HashMap::from([(0, vec![S("title"), S("description")]), (1, vec![S("description")])])
+        );
+    }
+
+    #[test]
+    fn test_max_candidates() {
+        let index = TempIndex::new();
+        // the even documents have a better proximity than the odd ones.
+        let documents = (0..1000).map(|id| {
+            let text = if id % 2 == 0 { "hello world" } else { "hello big big big world" };
+            let document = serde_json::json!({ "id": id, "text": text });
+            document.as_object().unwrap().clone()
+        });
+        index
+            .add_documents(crate::documents::documents_batch_reader_from_objects(documents))
+            .unwrap();
+
+        let rtxn = index.read_txn().unwrap();
+        let mut search = Search::new(&rtxn, &index);
+        search.query("hello world");
+        search.limit(60);
+
+        let exact = search.execute().unwrap();
+        assert_eq!(exact.candidates.len(), 1000);
+        assert_eq!(exact.documents_ids, (0..120).step_by(2).collect::<Vec<_>>());
+
+        // only the 100 first candidates are ranked by the proximity criterion,
+        // the other ones are returned afterward without being ranked.
+        search.set_max_candidates(100);
+        let approximate = search.execute().unwrap();
+        assert_eq!(approximate.candidates.len(), 1000);
+        assert_eq!(approximate.documents_ids[..50], exact.documents_ids[..50]);
+        assert_eq!(approximate.documents_ids[50..], (1..20).step_by(2).collect::<Vec<_>>()[..]);
+
+        // the results are exact when the cutoff is not reached.
+        search.set_max_candidates(1000);
+        let result = search.execute().unwrap();
+        assert_eq!(result.documents_ids, exact.documents_ids);
+    }
+
+    #[test]
+    fn test_field_tiers() {
+        let index = TempIndex::new();
+        index
+            .add_documents(documents!([
+                { "id": 0, "title": "the fox jumps over the quick dog", "body": "nothing" },
+                { "id": 1, "title": "a cat", "body": "quick fox" },
+                { "id": 2, "title": "quick fox", "body": "quick fox" },
+                { "id": 3, "title": "qvick fox", "body": "" },
+                { "id": 4, "title": "hello", "body": "the quick fox is there" },
+                { "id": 5, "title": "a quick brown fox", "body": "" },
+            ]))
+            .unwrap();
+
+        let rtxn = index.read_txn().unwrap();
+        let mut search = Search::new(&rtxn, &index);
+        search.query("quick fox");
+        search.terms_matching_strategy(TermsMatchingStrategy::All);
+
+        // the body-only matches have a better proximity than some title matches.
+        let SearchResult { documents_ids, .. } = search.execute().unwrap();
+        let position = |docid| documents_ids.iter().position(|&id| id == docid).unwrap();
+        assert!(position(1) < position(0));
+
+        search.field_tiers(vec![S("title"), S("body")]);
+        let SearchResult { documents_ids, candidates, .. } = search.execute().unwrap();
+        assert_eq!(candidates.len(), 6);
+        let (title_matches, body_matches) = documents_ids.split_at(4);
+        assert_eq!(
+            title_matches.iter().copied().collect::<HashSet<_>>(),
+            HashSet::from([0, 2, 3, 5])
+        );
+        assert_eq!(body_matches.iter().copied().collect::<HashSet<_>>(), HashSet::from([1, 4]));
+        // the following criteria still rank the documents of each tier.
+        assert_eq!(title_matches[0], 2);
+        assert_eq!(body_matches, &[1, 4]);
+
+        // the lazy search ranks the tiers the same way
+        let lazy: Vec<_> = search.execute_iter().unwrap().flat_map(Result::unwrap).collect();
+        assert_eq!(lazy, documents_ids);
+    }
+
+    #[test]
+    fn test_facet_value() {
+        let index = TempIndex::new();
+        index
+            .update_settings(|settings| {
+                settings.set_filterable_fields(maplit::hashset! { S("category"), S("popularity") });
+                let mut criteria = crate::default_criteria();
+                criteria.push(Criterion::Desc(S("popularity")));
+                settings.set_criteria(criteria);
+            })
+            .unwrap();
+        index
+            .add_documents(documents!([
+                { "id": 0, "title": "red sneakers", "category": "Shoes", "popularity": 12 },
+                { "id": 1, "title": "blue hat", "category": "Hats", "popularity": 80 },
+                { "id": 2, "title": "hiking boots", "category": "Shoes", "popularity": 45 },
+                { "id": 3, "title": "sandals", "category": "Shoes", "popularity": 3 },
+                { "id": 4, "title": "running shoes", "category": ["Shoes", "Sport"], "popularity": 60 },
+            ]))
+            .unwrap();
+
+        let rtxn = index.read_txn().unwrap();
+        let mut search = Search::new(&rtxn, &index);
+        search.facet_value("category", "shoes");
+        let SearchResult { documents_ids, candidates, .. } = search.execute().unwrap();
+        // the documents of the category are ranked by the popularity criterion.
+        assert_eq!(documents_ids, vec![4, 2, 0, 3]);
+        assert_eq!(candidates.len(), 4);
+
+        // the facet value is combined with the filter and the limit.
+        search.filter(Filter::from_str("popularity > 10").unwrap().unwrap());
+        search.limit(2);
+        let SearchResult { documents_ids, .. } = search.execute().unwrap();
+        assert_eq!(documents_ids, vec![4, 2]);
+
+        let mut search = Search::new(&rtxn, &index);
+        search.facet_value("title", "sandals");
+        assert!(search.execute().is_err());
+    }
+
+    #[test]
+    fn test_or_filter() {
+        let index = TempIndex::new();
+        index
+            .update_settings(|settings| {
+                settings.set_filterable_fields(maplit::hashset! { S("category"), S("popularity") });
+                let mut criteria = crate::default_criteria();
+                criteria.push(Criterion::Desc(S("popularity")));
+                settings.set_criteria(criteria);
+            })
+            .unwrap();
+        index
+            .add_documents(documents!([
+                { "id": 0, "title": "red shoes", "category": "Shoes", "popularity": 12 },
+                { "id": 1, "title": "blue hat", "category": "Hats", "popularity": 80 },
+                { "id": 2, "title": "hiking boots", "category": "Shoes", "popularity": 45 },
+                { "id": 3, "title": "sandals", "category": "Shoes", "popularity": 3 },
+                { "id": 4, "title": "running shoes", "category": "Shoes", "popularity": 60 },
+                { "id": 5, "title": "winter hat", "category": "Hats", "popularity": 20 },
+            ]))
+            .unwrap();
+
+        let rtxn = index.read_txn().unwrap();
+        let mut search = Search::new(&rtxn, &index);
+        search.query("shoes");
+        search.or_filter(Filter::from_str("category = Shoes").unwrap().unwrap());
+        let SearchResult { documents_ids, candidates, .. } = search.execute().unwrap();
+        // the filter-only documents come after the query matches, ranked by popularity.
+        assert_eq!(documents_ids, vec![4, 0, 2, 3]);
+        assert_eq!(candidates.len(), 4);
+
+        // the pagination goes through the query matches then the filter-only documents.
+        search.offset(1).limit(2);
+        let SearchResult { documents_ids, .. } = search.execute().unwrap();
+        assert_eq!(documents_ids, vec![0, 2]);
+
+        // the filter restricts both the query matches and the filter-only documents.
+        search.offset(0).limit(20);
+        search.filter(Filter::from_str("popularity > 10").unwrap().unwrap());
+        let SearchResult { documents_ids, .. } = search.execute().unwrap();
+        assert_eq!(documents_ids, vec![4, 0, 2]);
+    }
+
+    #[test]
+    fn test_empty_query() {
+        let index = TempIndex::new();
+        index
+            .update_settings(|settings| {
+                settings.set_filterable_fields(maplit::hashset! { S("popularity") });
+                let mut criteria = crate::default_criteria();
+                criteria.push(Criterion::Desc(S("popularity")));
+                settings.set_criteria(criteria);
+            })
+            .unwrap();
+        index
+            .add_documents(documents!([
+                { "id": 0, "title": "red hat", "popularity": 12 },
+                { "id": 1, "title": "blue hat", "popularity": 80 },
+                { "id": 2, "title": "hiking boots", "popularity": 45 },
+            ]))
+            .unwrap();
+
+        let rtxn = index.read_txn().unwrap();
+        let search = |query: Option<&str>, behavior: EmptyQuery| {
+            let mut search = Search::new(&rtxn, &index);
+            if let Some(query) = query {
+                search.query(query);
+            }
+            let SearchResult { documents_ids, candidates, .. } =
+                search.empty_query(behavior).execute().unwrap();
+            (documents_ids, candidates.len())
+        };
+
+        // by default all the documents are ranked by the criteria not depending on the query.
+        assert_eq!(search(None, EmptyQuery::default()), (vec![1, 2, 0], 3));
+        assert_eq!(search(Some("?!"), EmptyQuery::AllDocuments), (vec![1, 2, 0], 3));
+
+        // or no document is returned, until the query contains a word.
+        assert_eq!(search(None, EmptyQuery::NoDocuments), (vec![], 0));
+        assert_eq!(search(Some(""), EmptyQuery::NoDocuments), (vec![], 0));
+        assert_eq!(search(Some("?!"), EmptyQuery::NoDocuments), (vec![], 0));
+        assert_eq!(search(Some("hat"), EmptyQuery::NoDocuments), (vec![1, 0], 2));
+    }
+
+    #[test]
+    fn test_min_matched_words() {
+        let index = TempIndex::new();
+        index
+            .add_documents(documents!([
+                { "id": 0, "text": "quick brown fox jumps" },
+                { "id": 1, "text": "quick brown fox" },
+                { "id": 2, "text": "quick brown" },
+                { "id": 3, "text": "fox jumps" },
+                { "id": 4, "text": "quick brown dog jumps" },
+                { "id": 5, "text": "lazy dog" },
+            ]))
+            .unwrap();
+
+        let rtxn = index.read_txn().unwrap();
+        let search = |min_matched_words: Option<usize>| {
+            let mut search = Search::new(&rtxn, &index);
+            search.query("quick brown fox jumps");
+            search.terms_matching_strategy(TermsMatchingStrategy::Any);
+            if let Some(min_matched_words) = min_matched_words {
+                search.set_min_matched_words(min_matched_words);
+            }
+            let SearchResult { candidates, .. } = search.execute().unwrap();
+            candidates.into_iter().collect::<Vec<_>>()
+        };
+
+        assert_eq!(search(None), vec![0, 1, 2, 3, 4]);
+        // the documents matching only 2 of the 4 words are excluded.
+        assert_eq!(search(Some(3)), vec![0, 1, 4]);
+        // more words than in the query requires all of them.
+        assert_eq!(search(Some(10)), vec![0]);
+    }
+
+    #[test]
+    fn test_execute_candidates() {
+        let index = TempIndex::new();
+        index
+            .update_settings(|settings| {
+                settings.set_filterable_fields(maplit::hashset! { S("group") });
+            })
+            .unwrap();
+
+        let documents: Vec<_> = (0..50)
+            .map(|i| {
+                let title = if i % 3 == 0 { "quick brown fox" } else { "lazy dog" };
+                serde_json::json!({ "id": i, "title": title, "group": i % 4 })
+                    .as_object()
+                    .unwrap()
+                    .clone()
+            })
+            .collect();
+        index.add_documents(documents_batch_reader_from_objects(documents)).unwrap();
+
+        let rtxn = index.read_txn().unwrap();
+        let exhaustive_candidates = |search: &mut Search| {
+            let SearchResult { candidates, .. } =
+                search.exhaustive_number_hits(true).execute().unwrap();
+            candidates
+        };
+
+        let mut search = Search::new(&rtxn, &index);
+        let candidates = search.execute_candidates().unwrap();
+        assert_eq!(candidates.len(), 50);
+        assert_eq!(candidates, exhaustive_candidates(&mut search));
+
+        search.filter(Filter::from_str("group = 1").unwrap().unwrap());
+        let candidates = search.execute_candidates().unwrap();
+        assert_eq!(candidates.len(), 13);
+        assert_eq!(candidates, exhaustive_candidates(&mut search));
+
+        search.query("quick fox");
+        let candidates = search.execute_candidates().unwrap();
+        assert_eq!(candidates.len(), 4);
+        assert_eq!(candidates, exhaustive_candidates(&mut search));
+
+        // the documents matching only some of the words are candidates too.
+        let mut search = Search::new(&rtxn, &index);
+        search.query("quick lazy").terms_matching_strategy(TermsMatchingStrategy::Any);
+        let candidates = search.execute_candidates().unwrap();
+        assert_eq!(candidates.len(), 50);
+        assert_eq!(candidates, exhaustive_candidates(&mut search));
+        drop(search);
+        drop(rtxn);
+
+        // a single document is kept for each distinct value.
+        index.update_settings(|settings| settings.set_distinct_field(S("group"))).unwrap();
+        let rtxn = index.read_txn().unwrap();
+        let mut search = Search::new(&rtxn, &index);
+        search.query("dog");
+        let candidates = search.execute_candidates().unwrap();
+        assert_eq!(candidates.len(), 4);
+        assert_eq!(candidates.len(), exhaustive_candidates(&mut search).len());
+    }
+
+    #[test]
+    fn test_synonyms_in_phrases() {
+        let index = TempIndex::new();
+        index
+            .update_settings(|settings| {
+                settings.set_synonyms(maplit::hashmap! { S("nyc") => vec![S("new york")] });
+            })
+            .unwrap();
+        index
+            .add_documents(documents!([
+                { "id": 0, "text": "the new york marathon" },
+                { "id": 1, "text": "the nyc marathon" },
+                { "id": 2, "text": "the boston marathon" },
+            ]))
+            .unwrap();
+
+        let rtxn = index.read_txn().unwrap();
+        let mut search = Search::new(&rtxn, &index);
+        search.query("\"nyc marathon\"");
+        let SearchResult { mut documents_ids, .. } = search.execute().unwrap();
+        documents_ids.sort_unstable();
+        assert_eq!(documents_ids, vec![1]);
+
+        search.synonyms_in_phrases(true);
+        let SearchResult { mut documents_ids, .. } = search.execute().unwrap();
+        documents_ids.sort_unstable();
+        assert_eq!(documents_ids, vec![0, 1]);
+
+        // the words outside of the quotes are replaced by their synonyms in both modes
+        let mut search = Search::new(&rtxn, &index);
+        search.query("nyc \"marathon\"");
+        search.terms_matching_strategy(TermsMatchingStrategy::All);
+        let SearchResult { mut documents_ids, .. } = search.execute().unwrap();
+        documents_ids.sort_unstable();
+        assert_eq!(documents_ids, vec![0, 1]);
+    }
+
+    #[test]
+    fn test_case_sensitive() {
+        let index = TempIndex::new();
+        index
+            .update_settings(|settings| {
+                settings.set_case_sensitive_attributes(maplit::hashset! { S("ticker") });
+            })
+            .unwrap();
+        index
+            .add_documents(documents!([
+                { "id": 0, "ticker": "Apple", "text": "apple" },
+                { "id": 1, "ticker": "apple" },
+                { "id": 2, "ticker": "IT" },
+                { "id": 3, "ticker": "it" },
+                { "id": 4, "text": "Apple IT" },
+            ]))
+            .unwrap();
+
+        let rtxn = index.read_txn().unwrap();
+        let search = |query: &str, case_sensitive: bool, authorize_typos: bool| {
+            let mut search = Search::new(&rtxn, &index);
+            search.query(query);
+            search.case_sensitive(case_sensitive);
+            search.authorize_typos(authorize_typos);
+            search.execute().unwrap().documents_ids
+        };
+
+        // the case differences are typos
+        assert_eq!(search("Apple", true, true), vec![0, 1]);
+        assert_eq!(search("apple", true, true), vec![1, 0]);
+        assert_eq!(search("Apple", true, false), vec![0]);
+        assert_eq!(search("apple", true, false), vec![1]);
+        // the short words can't have typos
+        assert_eq!(search("IT", true, true), vec![2]);
+        assert_eq!(search("it", true, true), vec![3]);
+
+        // the case is still ignored by default, in all the attributes
+        let mut documents_ids = search("Apple", false, true);
+        documents_ids.sort_unstable();
+        assert_eq!(documents_ids, vec![0, 1, 4]);
+        let mut documents_ids = search("it", false, true);
+        documents_ids.sort_unstable();
+        assert_eq!(documents_ids, vec![2, 3, 4]);
+        drop(rtxn);
+
+        // the case-preserved words are removed with the setting
+        index.update_settings(|settings| settings.reset_case_sensitive_attributes()).unwrap();
+
+        let rtxn = index.read_txn().unwrap();
+        assert!(index.word_docids.get(&rtxn, "\u{1}Apple").unwrap().is_none());
+        let mut search = Search::new(&rtxn, &index);
+        search.query("Apple").case_sensitive(true);
+        assert!(search.execute().unwrap().documents_ids.is_empty());
+    }
+
+    #[test]
+    fn test_typo_on_diacritics() {
+        let index = TempIndex::new();
+        index
+            .add_documents(documents!([
+                { "id": 0, "text": "marché" },
+                { "id": 1, "text": "marche" },
+                { "id": 2, "text": "élève" },
+                { "id": 3, "text": "eleve" },
+            ]))
+            .unwrap();
+
+        let search = |query: &str, authorize_typos: bool| {
+            let rtxn = index.read_txn().unwrap();
+            let mut search = Search::new(&rtxn, &index);
+            search.query(query);
+            search.authorize_typos(authorize_typos);
+            search.execute().unwrap().documents_ids
+        };
+
+        // the accents are ignored by default
+        assert_eq!(search("marche", false), vec![0, 1]);
+        assert_eq!(search("marché", false), vec![0, 1]);
+        assert_eq!(search("eleve", false), vec![2, 3]);
+
+        index
+            .update_settings(|settings| {
+                settings.set_typo_on_diacritics(TypoOnDiacritics::CountsAsTypo)
+            })
+            .unwrap();
+
+        // an accent difference is a typo, ranked after the exact matches. The trailing
+        // spaces keep the words from being prefixes, matching more words with typos.
+        assert_eq!(search("marche ", true), vec![1, 0]);
+        assert_eq!(search("marché ", true), vec![0, 1]);
+        assert_eq!(search("marche ", false), vec![1]);
+        // two accent differences are more typos than a five chars word is allowed
+        assert_eq!(search("eleve ", true), vec![3]);
+        assert_eq!(search("élève ", true), vec![2]);
+
+        index
+            .update_settings(|settings| settings.set_typo_on_diacritics(TypoOnDiacritics::NoMatch))
+            .unwrap();
+
+        // an accent difference never matches, while the other typos still do
+        assert_eq!(search("marche ", true), vec![1]);
+        assert_eq!(search("marché ", true), vec![0]);
+        assert_eq!(search("marchhe ", true), vec![1]);
+        assert_eq!(search("marchés ", true), vec![0]);
+        assert_eq!(search("eleve ", true), vec![3]);
+        assert_eq!(search("élèvee ", true), vec![2]);
+
+        // the words normalized with another number of chars never match with their accents
+        index.add_documents(documents!([{ "id": 4, "text": "cœur" }])).unwrap();
+        assert_eq!(search("cœur ", true), vec![4]);
+        assert_eq!(search("coeur ", true), Vec::<u32>::new());
+
+        // the synonyms are those of the normalized words
+        index
+            .update_settings(|settings| {
+                settings.set_synonyms(maplit::hashmap! { S("eleve") => vec![S("student")] })
+            })
+            .unwrap();
+        index.add_documents(documents!([{ "id": 5, "text": "student" }])).unwrap();
+        assert_eq!(search("élève ", true), vec![2, 5]);
+
+        // the diacritics-preserved words are removed with the setting
+        index.update_settings(|settings| settings.reset_typo_on_diacritics()).unwrap();
+
+        let rtxn = index.read_txn().unwrap();
+        assert!(index.word_docids.get(&rtxn, "\u{2}marche").unwrap().is_none());
+        drop(rtxn);
+        assert_eq!(search("marche", false), vec![0, 1]);
+    }
+
+    #[test]
+    fn test_phrase_slop() {
+        let index = TempIndex::new();
+        index
+            .add_documents(documents!([
+                { "id": 0, "text": "the quick brown fox" },
+                { "id": 1, "text": "the quick fox" },
+                { "id": 2, "text": "the fox is quick" },
+                { "id": 3, "text": "the fox quick" },
+            ]))
+            .unwrap();
+
+        let rtxn = index.read_txn().unwrap();
+        let search_phrase = |query: &str, order: PhraseOrder| {
+            let mut search = Search::new(&rtxn, &index);
+            search.query(query);
+            search.phrase_order(order);
+            let SearchResult { mut documents_ids, .. } = search.execute().unwrap();
+            documents_ids.sort_unstable();
+            documents_ids
+        };
+
+        // a slop of 0 or 1 is an exact phrase
+        assert_eq!(search_phrase("\"quick fox\"", PhraseOrder::InOrder), vec![1]);
+        assert_eq!(search_phrase("\"quick fox\"~0", PhraseOrder::InOrder), vec![1]);
+        assert_eq!(search_phrase("\"quick fox\"~1", PhraseOrder::InOrder), vec![1]);
+        // a slop of 2 allows one word between quick and fox
+        assert_eq!(search_phrase("\"quick fox\"~2", PhraseOrder::InOrder), vec![0, 1]);
+
+        // the words of the last documents are in the reverse order
+        assert_eq!(search_phrase("\"quick fox\"~3", PhraseOrder::InOrder), vec![0, 1]);
+        assert_eq!(search_phrase("\"quick fox\"~1", PhraseOrder::AnyOrder), vec![1, 3]);
+        assert_eq!(search_phrase("\"quick fox\"~2", PhraseOrder::AnyOrder), vec![0, 1, 2, 3]);
+    }
+
+    #[test]
+    fn test_timings() {
+        let index = TempIndex::new();
+        index
+            .add_documents(documents!([
+                { "id": 0, "text": "the quick brown fox" },
+                { "id": 1, "text": "the quick fox" },
+                { "id": 2, "text": "the fox is quick" },
+            ]))
+            .unwrap();
+
+        let rtxn = index.read_txn().unwrap();
+        let mut search = Search::new(&rtxn, &index);
+        search.query("quick fox");
+        let (SearchResult { documents_ids, .. }, timings) = search.execute_with_timings().unwrap();
+        assert_eq!(documents_ids, search.execute().unwrap().documents_ids);
+        assert_eq!(documents_ids.len(), 3);
+
+        let criteria: Vec<_> = timings.ranking.iter().map(|(name, _)| name.as_str()).collect();
+        assert_eq!(criteria, ["words", "typo", "proximity", "attribute", "sort", "exactness"]);
+
+        let sum = timings.query_parsing
+            + timings.candidates_resolution
+            + timings.ranking.iter().map(|(_, time)| *time).sum::<Duration>()
+            + timings.documents_fetching;
+        assert!(sum <= timings.total, "{sum:?} > {:?}", timings.total);
+    }
+
+    #[test]
+    fn test_disable_criteria() {
+        let index = TempIndex::new();
+        index
+            .update_settings(|settings| {
+                settings.set_sortable_fields(maplit::hashset! { S("id") });
+            })
+            .unwrap();
+        index
+            .add_documents(documents!([
+                { "id": 0, "text": "quick is a very slow and lazy fox" },
+                { "id": 1, "text": "quack fox" },
+            ]))
+            .unwrap();
+
+        let rtxn = index.read_txn().unwrap();
+        let mut search = Search::new(&rtxn, &index);
+        search.query("quick fox");
+        let SearchResult { documents_ids, .. } = search.execute().unwrap();
+        assert_eq!(documents_ids, vec![0, 1]);
+
+        // without the typo ranking rule, the proximity ranks the document with a typo first
+        search.disable_criteria(&[Criterion::Typo]);
+        let SearchResult { documents_ids, .. } = search.execute().unwrap();
+        assert_eq!(documents_ids, vec![1, 0]);
+
+        // the words ranking rule is needed to rank the documents matching some of the words
+        search.disable_criteria(&[Criterion::Words]);
+        let error = search.execute().unwrap_err();
+        assert!(matches!(
+            error,
+            crate::Error::UserError(UserError::CriterionCannotBeDisabled { .. })
+        ));
+        search.terms_matching_strategy(TermsMatchingStrategy::All);
+        let SearchResult { documents_ids, .. } = search.execute().unwrap();
+        assert_eq!(documents_ids, vec![0, 1]);
+
+        // the sort ranking rule can't be disabled when sorting
+        search.disable_criteria(&[Criterion::Sort]);
+        search.sort_criteria(vec![AscDesc::from_str("id:asc").unwrap()]);
+        let error = search.execute().unwrap_err();
+        assert!(matches!(error, crate::Error::UserError(UserError::SortRankingRuleMissing)));
+        drop(rtxn);
+
+        // the attribute ranking rule applies the field boosts
+        index
+            .update_settings(|settings| {
+                settings.set_field_boosts(maplit::btreemap! { S("text") => 2.0 });
+            })
+            .unwrap();
+        let rtxn = index.read_txn().unwrap();
+        let mut search = Search::new(&rtxn, &index);
+        search.query("quick fox");
+        search.disable_criteria(&[Criterion::Attribute]);
+        let error = search.execute().unwrap_err();
+        assert!(matches!(
+            error,
+            crate::Error::UserError(UserError::CriterionCannotBeDisabled { .. })
+        ));
+    }
+
+    #[test]
+    fn test_execute_iter() {
+        let index = TempIndex::new();
+        index
+            .update_settings(|settings| {
+                settings.set_sortable_fields(maplit::hashset! { S("rank") });
+                settings.set_filterable_fields(maplit::hashset! { S("group") });
+            })
+            .unwrap();
+
+        let documents: Vec<_> = (0..100)
+            .map(|i| {
+                serde_json::json!({ "id": i, "rank": (i * 37) % 100, "group": i % 10 })
+                    .as_object()
+                    .unwrap()
+                    .clone()
+            })
+            .collect();
+        index.add_documents(documents_batch_reader_from_objects(documents)).unwrap();
+
+        let rtxn = index.read_txn().unwrap();
+        let mut search = Search::new(&rtxn, &index);
+        search.sort_criteria(vec![AscDesc::from_str("rank:asc").unwrap()]);
+
+        // the documents are ranked lazily, stopping early leaves candidates unranked
+        {
+            let mut iter = search.execute_iter().unwrap();
+            let mut documents_ids = Vec::new();
+            for batch in iter.by_ref() {
+                documents_ids.extend(batch.unwrap());
+                if documents_ids.len() >= 5 {
+                    break;
+                }
+            }
+            assert_eq!(documents_ids, vec![0, 73, 46, 19, 92]);
+            assert!(iter.ranked_candidates() < 100);
+        }
+
+        // the iterator yields the same documents as the search, in the same order
+        search.limit(100);
+        let SearchResult { documents_ids: expected, .. } = search.execute().unwrap();
+        let documents_ids: Vec<_> =
+            search.execute_iter().unwrap().flat_map(Result::unwrap).collect();
+        assert_eq!(documents_ids, expected);
+
+        search.offset(3);
+        let documents_ids: Vec<_> =
+            search.execute_iter().unwrap().flat_map(Result::unwrap).collect();
+        assert_eq!(documents_ids, expected[3..]);
+        drop(search);
+        drop(rtxn);
+
+        // the distinct is applied across the batches
+        index.update_settings(|settings| settings.set_distinct_field(S("group"))).unwrap();
+        let rtxn = index.read_txn().unwrap();
+        let mut search = Search::new(&rtxn, &index);
+        search.sort_criteria(vec![AscDesc::from_str("rank:asc").unwrap()]);
+        let documents_ids: Vec<_> =
+            search.execute_iter().unwrap().flat_map(Result::unwrap).collect();
+        assert_eq!(documents_ids.len(), 10);
+        let groups: HashSet<_> = documents_ids.iter().map(|id| id % 10).collect();
+        assert_eq!(groups.len(), 10);
+        assert_eq!(&documents_ids[..3], &[0, 73, 46]);
+    }
+
+    #[test]
+    fn test_tie_breaker() {
+        let external_ids = ["c", "a", "10", "e", "b", "2", "d"];
+
+        // the same documents indexed in two different orders
+        let mut pages = Vec::new();
+        for order in [external_ids.to_vec(), external_ids.iter().rev().copied().collect()] {
+            let index = TempIndex::new();
+            let documents: Vec<_> = order
+                .iter()
+                .map(|id| {
+                    serde_json::json!({ "id": id, "text": "hello world" })
+                        .as_object()
+                        .unwrap()
+                        .clone()
+                })
+                .collect();
+            index.add_documents(documents_batch_reader_from_objects(documents)).unwrap();
+
+            let rtxn = index.read_txn().unwrap();
+            let docids_to_external: HashMap<_, _> = index
+                .external_documents_ids(&rtxn)
+                .unwrap()
+                .to_hash_map()
+                .into_iter()
+                .map(|(external_id, docid)| (docid, external_id))
+                .collect();
+
+            let mut search = Search::new(&rtxn, &index);
+            search.query("hello");
+            search.tie_breaker(TieBreaker::ExternalIdAsc);
+            search.limit(3);
+
+            let mut index_pages = Vec::new();
+            for offset in [0, 3, 6] {
+                search.offset(offset);
+                let SearchResult { documents_ids, .. } = search.execute().unwrap();
+                let page: Vec<_> =
+                    documents_ids.iter().map(|docid| docids_to_external[docid].clone()).collect();
+                index_pages.push(page);
+            }
+
+            // the lazy search breaks the ties the same way
+            search.offset(0);
+            let documents_ids: Vec<_> =
+                search.execute_iter().unwrap().flat_map(Result::unwrap).collect();
+            let all: Vec<_> =
+                documents_ids.iter().map(|docid| docids_to_external[docid].clone()).collect();
+            assert_eq!(all, index_pages.concat());
+
+            pages.push(index_pages);
+        }
+
+        assert_eq!(pages[0], pages[1]);
+        assert_eq!(pages[0], vec![vec!["10", "2", "a"], vec!["b", "c", "d"], vec!["e"]]);
+    }
+
+    #[test]
+    fn test_canonical_numbers() {
+        let index = TempIndex::new();
+        index
+            .update_settings(|settings| {
+                settings.set_filterable_fields(maplit::hashset! { S("price"), S("size") });
+            })
+            .unwrap();
+        index
+            .add_documents(documents!([
+                { "id": 0, "price": 1, "size": "1.0" },
+                { "id": 1, "price": 1.0, "size": "1" },
+                { "id": 2, "price": 2.5, "size": "2.50" },
+                { "id": 3, "price": -0.0, "size": "-0.0" },
+                { "id": 4, "price": 0, "size": "007" },
+                { "id": 5, "text": "it costs 1e0 dollar" },
+            ]))
+            .unwrap();
+
+        let rtxn = index.read_txn().unwrap();
+        let search_ids = |query: &str| {
+            let mut search = Search::new(&rtxn, &index);
+            search.query(query);
+            let SearchResult { documents_ids, .. } = search.execute().unwrap();
+            let mut documents_ids: Vec<_> = documents_ids.into_iter().collect();
+            documents_ids.sort_unstable();
+            documents_ids
+        };
+        let filter_ids = |filter: &str| {
+            let filter = Filter::from_str(filter).unwrap().unwrap();
+            filter.evaluate(&rtxn, &index).unwrap().into_iter().collect::<Vec<_>>()
+        };
+
+        // the integers, floats and exponent notations are the same words
+        assert_eq!(search_ids("1"), vec![0, 1, 5]);
+        assert_eq!(search_ids("1.0"), vec![0, 1, 5]);
+        assert_eq!(search_ids("1e0"), vec![0, 1, 5]);
+        assert_eq!(search_ids("2.50"), vec![2]);
+
+        // and the same facet values, for the numbers and the numeric strings
+        assert_eq!(filter_ids("price = 1"), vec![0, 1]);
+        assert_eq!(filter_ids("price = 1.0"), vec![0, 1]);
+        assert_eq!(filter_ids("size = 1"), vec![0, 1]);
+        assert_eq!(filter_ids("size = 1e0"), vec![0, 1]);
+        assert_eq!(filter_ids("size = 2.5"), vec![2]);
+
+        // `-0.0` is `0`, but the leading zeros are kept
+        assert_eq!(filter_ids("price = 0"), vec![3, 4]);
+        assert_eq!(filter_ids("price = -0.0"), vec![3, 4]);
+        assert_eq!(filter_ids("size = 0"), vec![3]);
+        assert_eq!(filter_ids("size = 007"), vec![4]);
+    }
 }
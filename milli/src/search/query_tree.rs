@@ -1,33 +1,72 @@
 use std::borrow::Cow;
+use std::cell::RefCell;
 use std::cmp::max;
 use std::collections::hash_map::Entry;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::hash::Hash;
 use std::rc::Rc;
-use std::{fmt, mem};
+use std::{fmt, iter, mem};
 
 use charabia::normalizer::NormalizedTokenIter;
 use charabia::{SeparatorKind, TokenKind};
 use roaring::RoaringBitmap;
 use slice_group_by::GroupBy;
 
+use crate::proximity::MAX_DISTANCE;
 use crate::search::matches::matching_words::{MatchingWord, PrimitiveWordId};
-use crate::search::TermsMatchingStrategy;
-use crate::{CboRoaringBitmapLenCodec, Index, MatchingWords, Result};
+use crate::search::{PhraseOrder, TermsMatchingStrategy};
+use crate::stemming::Stemmers;
+use crate::{
+    apply_normalization_rules, canonicalize_numeric_word, case_sensitive_word, diacritics_word,
+    normalize_stop_words, preserved_form_prefix, stem_word, unprefixed_word,
+    CboRoaringBitmapLenCodec, Index, LongWordsPolicy, MatchingWords, Result, StopWordsMode,
+    TypoOnDiacritics,
+};
 
 type IsOptionalWord = bool;
 type IsPrefix = bool;
+/// Whether a word follows a word of the query that is too long to be searched.
+type AfterDroppedWord = bool;
 
 #[derive(Clone, PartialEq, Eq, Hash)]
 pub enum Operation {
     And(Vec<Operation>),
     // series of consecutive non prefix and exact words
     // `None` means a stop word.
-    Phrase(Vec<Option<String>>),
+    Phrase(Vec<Option<String>>, PhraseSlop),
     Or(IsOptionalWord, Vec<Operation>),
     Query(Query),
 }
 
+/// How far apart the consecutive words of a phrase can be, written `"quick fox"~2` in the query.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct PhraseSlop {
+    /// The maximum proximity between two consecutive words of the phrase, `1` meaning that
+    /// the words are adjacent and `2` that there can be one word between them.
+    pub proximity: u8,
+    pub order: PhraseOrder,
+}
+
+impl PhraseSlop {
+    /// The slop of the phrases written without `~`, whose words are adjacent and in order.
+    pub const EXACT: PhraseSlop = PhraseSlop { proximity: 1, order: PhraseOrder::InOrder };
+
+    /// Creates the slop written `~slop` after a phrase, `~0` and `~1` both mean that the words
+    /// are adjacent and the slop is capped to the maximum proximity stored in the index.
+    pub fn new(slop: u8, order: PhraseOrder) -> PhraseSlop {
+        let proximity = slop.clamp(1, MAX_DISTANCE as u8 - 1);
+        if proximity == 1 {
+            PhraseSlop::EXACT
+        } else {
+            PhraseSlop { proximity, order }
+        }
+    }
+
+    pub fn is_exact(&self) -> bool {
+        *self == PhraseSlop::EXACT
+    }
+}
+
 impl fmt::Debug for Operation {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         fn pprint_tree(f: &mut fmt::Formatter<'_>, op: &Operation, depth: usize) -> fmt::Result {
@@ -36,9 +75,20 @@ impl fmt::Debug for Operation {
                     writeln!(f, "{:1$}AND", "", depth * 2)?;
                     children.iter().try_for_each(|c| pprint_tree(f, c, depth + 1))
                 }
-                Operation::Phrase(children) => {
+                Operation::Phrase(children, slop) if slop.is_exact() => {
                     writeln!(f, "{:2$}PHRASE {:?}", "", children, depth * 2)
                 }
+                Operation::Phrase(children, slop) => {
+                    writeln!(
+                        f,
+                        "{:4$}PHRASE {:?}~{} {:?}",
+                        "",
+                        children,
+                        slop.proximity,
+                        slop.order,
+                        depth * 2
+                    )
+                }
                 Operation::Or(true, children) => {
                     writeln!(f, "{:1$}OR(WORD)", "", depth * 2)?;
                     children.iter().try_for_each(|c| pprint_tree(f, c, depth + 1))
@@ -79,15 +129,15 @@ impl Operation {
         }
     }
 
-    fn phrase(mut words: Vec<Option<String>>) -> Self {
+    fn phrase(mut words: Vec<Option<String>>, slop: PhraseSlop) -> Self {
         if words.len() == 1 {
             if let Some(word) = words.pop().unwrap() {
                 Self::Query(Query { prefix: false, kind: QueryKind::exact(word) })
             } else {
-                Self::Phrase(words)
+                Self::Phrase(words, slop)
             }
         } else {
-            Self::Phrase(words)
+            Self::Phrase(words, slop)
         }
     }
 
@@ -170,6 +220,18 @@ trait Context {
         right_word: &str,
         proximity: u8,
     ) -> heed::Result<Option<u64>>;
+    /// Returns the stem of a word of the query, matched along with the word.
+    fn word_stem(&self, _word: &str) -> Option<String> {
+        None
+    }
+    /// Returns the normalized word a word of the query is the preserved form of,
+    /// the synonyms being defined for the normalized words.
+    fn normalized_word<'w>(&self, word: &'w str) -> Cow<'w, str> {
+        match preserved_form_prefix(word) {
+            Some(_) => Cow::Owned(unprefixed_word(word).to_lowercase()),
+            None => Cow::Borrowed(word),
+        }
+    }
 }
 
 /// The query tree builder is the interface to build a query tree.
@@ -178,8 +240,25 @@ pub struct QueryTreeBuilder<'a> {
     index: &'a Index,
     terms_matching_strategy: TermsMatchingStrategy,
     authorize_typos: bool,
+    synonyms_in_phrases: bool,
+    phrase_order: PhraseOrder,
+    case_sensitive: bool,
+    original_query: Option<String>,
     words_limit: Option<usize>,
     exact_words: Option<fst::Set<Cow<'a, [u8]>>>,
+    max_word_length: usize,
+    long_words_policy: LongWordsPolicy,
+    min_word_length: usize,
+    stop_words_mode: StopWordsMode,
+    typo_on_diacritics: TypoOnDiacritics,
+    normalization_rules: BTreeMap<String, String>,
+    /// The stop words normalized by the rules, see [`normalize_stop_words`].
+    normalized_stop_words: Option<fst::Set<Vec<u8>>>,
+    stemmers: Stemmers,
+    /// The stems of the words of the last built query.
+    stems: RefCell<HashMap<String, String>>,
+    /// The normalized words of the preserved forms of the words of the last built query.
+    normalized_words: RefCell<HashMap<String, String>>,
 }
 
 impl<'a> Context for QueryTreeBuilder<'a> {
@@ -188,7 +267,32 @@ impl<'a> Context for QueryTreeBuilder<'a> {
     }
 
     fn synonyms<S: AsRef<str>>(&self, words: &[S]) -> heed::Result<Option<Vec<Vec<String>>>> {
-        self.index.words_synonyms(self.rtxn, words)
+        if self.normalization_rules.is_empty() {
+            return self.index.words_synonyms(self.rtxn, words);
+        }
+
+        // the synonyms are stored as normalized by the tokenizer, the rules are applied
+        // to them like they are applied to the words of the query.
+        let normalize = |words: &[String]| -> Vec<String> {
+            words
+                .iter()
+                .map(|word| apply_normalization_rules(word, &self.normalization_rules).into_owned())
+                .collect()
+        };
+        let words: Vec<_> = words.iter().map(|word| word.as_ref().to_string()).collect();
+        let mut found: Option<Vec<Vec<String>>> = None;
+        for (key, synonyms) in self.index.synonyms(self.rtxn)? {
+            // several words can be normalized into the same words, their synonyms are merged.
+            if normalize(&key) == words {
+                let found = found.get_or_insert_with(Vec::new);
+                found.extend(synonyms.iter().map(|synonym| normalize(synonym)));
+            }
+        }
+        if let Some(found) = &mut found {
+            found.sort_unstable();
+            found.dedup();
+        }
+        Ok(found)
     }
 
     fn word_documents_count(&self, word: &str) -> heed::Result<Option<u64>> {
@@ -217,19 +321,51 @@ impl<'a> Context for QueryTreeBuilder<'a> {
             .remap_data_type::<CboRoaringBitmapLenCodec>()
             .get(self.rtxn, &key)
     }
+
+    fn word_stem(&self, word: &str) -> Option<String> {
+        self.stems.borrow().get(word).cloned()
+    }
+
+    fn normalized_word<'w>(&self, word: &'w str) -> Cow<'w, str> {
+        match self.normalized_words.borrow().get(word) {
+            Some(normalized) => Cow::Owned(normalized.clone()),
+            None if preserved_form_prefix(word).is_some() => {
+                Cow::Owned(unprefixed_word(word).to_lowercase())
+            }
+            None => Cow::Borrowed(word),
+        }
+    }
 }
 
 impl<'a> QueryTreeBuilder<'a> {
     /// Create a `QueryTreeBuilder` from a heed ReadOnly transaction `rtxn`
     /// and an Index `index`.
     pub fn new(rtxn: &'a heed::RoTxn<'a>, index: &'a Index) -> Result<Self> {
+        let normalization_rules = index.normalization_rules(rtxn)?;
         Ok(Self {
             rtxn,
             index,
             terms_matching_strategy: TermsMatchingStrategy::default(),
             authorize_typos: true,
+            synonyms_in_phrases: false,
+            phrase_order: PhraseOrder::default(),
+            case_sensitive: false,
+            original_query: None,
             words_limit: None,
             exact_words: index.exact_words(rtxn)?,
+            max_word_length: index.max_word_length(rtxn)?,
+            long_words_policy: index.long_words_policy(rtxn)?,
+            min_word_length: index.min_indexed_word_length(rtxn)?,
+            stop_words_mode: index.stop_words_mode(rtxn)?,
+            typo_on_diacritics: index.typo_on_diacritics(rtxn)?,
+            normalized_stop_words: normalize_stop_words(
+                index.stop_words(rtxn)?.as_ref(),
+                &normalization_rules,
+            )?,
+            normalization_rules,
+            stemmers: index.stemmers(rtxn)?,
+            stems: RefCell::default(),
+            normalized_words: RefCell::default(),
         })
     }
 
@@ -254,6 +390,37 @@ impl<'a> QueryTreeBuilder<'a> {
         self
     }
 
+    /// if `synonyms_in_phrases` is set to `true` the words of the phrases are also
+    /// replaced by their synonyms, otherwise the phrases are matched literally.
+    /// default value if not called: `false`
+    pub fn synonyms_in_phrases(&mut self, synonyms_in_phrases: bool) -> &mut Self {
+        self.synonyms_in_phrases = synonyms_in_phrases;
+        self
+    }
+
+    /// whether the words of the phrases with a slop, e.g. `"quick fox"~2`,
+    /// must appear in the order of the phrase.
+    /// default value if not called: `PhraseOrder::InOrder`
+    pub fn phrase_order(&mut self, phrase_order: PhraseOrder) -> &mut Self {
+        self.phrase_order = phrase_order;
+        self
+    }
+
+    /// if `case_sensitive` is set to `true` the words of the query are matched with their case,
+    /// against the case-sensitive attributes only, the case and accent differences are typos.
+    /// default value if not called: `false`
+    pub fn case_sensitive(&mut self, case_sensitive: bool) -> &mut Self {
+        self.case_sensitive = case_sensitive;
+        self
+    }
+
+    /// The text the tokens given to [`build`](Self::build) come from. The words are only
+    /// matched with their case or their accents when it is given.
+    pub fn original_query(&mut self, query: &str) -> &mut Self {
+        self.original_query = Some(query.to_string());
+        self
+    }
+
     /// Limit words and phrases that will be taken for query building.
     /// Any beyond `words_limit` will be ignored.
     pub fn words_limit(&mut self, words_limit: usize) -> &mut Self {
@@ -272,28 +439,88 @@ impl<'a> QueryTreeBuilder<'a> {
         &self,
         query: NormalizedTokenIter<A>,
     ) -> Result<Option<(Operation, PrimitiveQuery, MatchingWords)>> {
-        let primitive_query = create_primitive_query(query, self.words_limit);
+        let mut stems = HashMap::new();
+        let mut normalized_words = HashMap::new();
+        let primitive_query = create_primitive_query(
+            query,
+            self.original_query.as_deref(),
+            self.case_sensitive,
+            self.typo_on_diacritics,
+            self.words_limit,
+            self.max_word_length,
+            self.long_words_policy,
+            self.min_word_length,
+            self.stop_words_mode,
+            self.normalized_stop_words.as_ref(),
+            &self.normalization_rules,
+            self.phrase_order,
+            &self.stemmers,
+            &mut stems,
+            &mut normalized_words,
+        );
+        self.stems.replace(stems);
+        self.normalized_words.replace(normalized_words.clone());
         if !primitive_query.is_empty() {
             let qt = create_query_tree(
                 self,
                 self.terms_matching_strategy,
                 self.authorize_typos,
+                self.synonyms_in_phrases,
                 &primitive_query,
             )?;
+            // the words are highlighted as normalized, whatever their searched forms.
+            let normalized = |word: &String| normalized_words.get(word).unwrap_or(word).clone();
+            let normalized_query: Vec<_> = primitive_query
+                .iter()
+                .map(|part| match part {
+                    PrimitiveQueryPart::Word(word, prefix, after_dropped_word) => {
+                        PrimitiveQueryPart::Word(normalized(word), *prefix, *after_dropped_word)
+                    }
+                    PrimitiveQueryPart::Phrase(words, slop) => {
+                        let words = words.iter().map(|word| word.as_ref().map(normalized));
+                        PrimitiveQueryPart::Phrase(words.collect(), *slop)
+                    }
+                })
+                .collect();
             let matching_words =
-                create_matching_words(self, self.authorize_typos, &primitive_query)?;
+                create_matching_words(self, self.authorize_typos, &normalized_query)?;
             Ok(Some((qt, primitive_query, matching_words)))
         } else {
             Ok(None)
         }
     }
+
+    /// Creates an operation for each part of the primitive query, matching
+    /// the documents containing this part regardless of the other ones.
+    pub fn primitive_parts_operations(
+        &self,
+        query: &[PrimitiveQueryPart],
+    ) -> Result<Vec<Operation>> {
+        query
+            .iter()
+            .map(|part| {
+                resolve_primitive_part(
+                    self,
+                    self.authorize_typos,
+                    self.synonyms_in_phrases,
+                    part.clone(),
+                )
+            })
+            .collect()
+    }
 }
 
 /// Split the word depending on the frequency of pairs near together in the database documents.
+///
+/// The case-preserved and diacritics-preserved forms of the words are never split.
 fn split_best_frequency<'a>(
     ctx: &impl Context,
     word: &'a str,
 ) -> heed::Result<Option<(&'a str, &'a str)>> {
+    if preserved_form_prefix(word).is_some() {
+        return Ok(None);
+    }
+
     let chars = word.char_indices().skip(1);
     let mut best = None;
 
@@ -320,9 +547,11 @@ pub struct TypoConfig<'a> {
 
 /// Return the `QueryKind` of a word depending on `authorize_typos`
 /// and the provided word length.
-fn typos(word: String, authorize_typos: bool, config: TypoConfig) -> QueryKind {
-    if authorize_typos && !config.exact_words.map_or(false, |s| s.contains(&word)) {
-        let count = word.chars().count().min(u8::MAX as usize) as u8;
+pub(crate) fn typos(word: String, authorize_typos: bool, config: TypoConfig) -> QueryKind {
+    // the prefix and the padding of the preserved forms of the words are not part of the word.
+    let unprefixed = unprefixed_word(&word);
+    if authorize_typos && !config.exact_words.map_or(false, |s| s.contains(unprefixed.as_ref())) {
+        let count = unprefixed.chars().count().min(u8::MAX as usize) as u8;
         if count < config.word_len_one_typo {
             QueryKind::exact(word)
         } else if count < config.word_len_two_typo {
@@ -338,7 +567,8 @@ fn typos(word: String, authorize_typos: bool, config: TypoConfig) -> QueryKind {
 /// Fetch synonyms from the `Context` for the provided word
 /// and create the list of operations for the query tree
 fn synonyms(ctx: &impl Context, word: &[&str]) -> heed::Result<Option<Vec<Operation>>> {
-    let synonyms = ctx.synonyms(word)?;
+    let words: Vec<_> = word.iter().map(|word| ctx.normalized_word(word)).collect();
+    let synonyms = ctx.synonyms(&words)?;
     Ok(synonyms.map(|synonyms| {
         synonyms
             .into_iter()
@@ -349,65 +579,125 @@ fn synonyms(ctx: &impl Context, word: &[&str]) -> heed::Result<Option<Vec<Operat
                         kind: QueryKind::exact(synonym[0].clone()),
                     })
                 } else {
-                    Operation::Phrase(synonym.into_iter().map(Some).collect())
+                    Operation::Phrase(synonym.into_iter().map(Some).collect(), PhraseSlop::EXACT)
                 }
             })
             .collect()
     }))
 }
 
-/// Main function that creates the final query tree from the primitive query.
-fn create_query_tree(
+/// The maximum number of phrases a phrase can be expanded to by replacing its words by
+/// their synonyms, see [`phrase_with_synonyms`].
+const MAX_PHRASE_SYNONYMS: usize = 10;
+
+/// Create an operation matching the phrase or one of the phrases obtained
+/// by replacing some of its words by their synonyms.
+fn phrase_with_synonyms(
+    ctx: &impl Context,
+    words: Vec<Option<String>>,
+    slop: PhraseSlop,
+) -> heed::Result<Operation> {
+    let mut phrases: Vec<Vec<Option<String>>> = vec![Vec::new()];
+    for word in words {
+        let mut alternatives = vec![vec![word.clone()]];
+        if let Some(word) = word {
+            if let Some(synonyms) = ctx.synonyms(&[ctx.normalized_word(&word)])? {
+                let synonyms = synonyms.into_iter().map(|s| s.into_iter().map(Some).collect());
+                alternatives.extend(synonyms);
+            }
+        }
+
+        phrases = phrases
+            .iter()
+            .flat_map(|phrase| {
+                alternatives.iter().map(move |alternative| {
+                    let mut phrase = phrase.clone();
+                    phrase.extend(alternative.iter().cloned());
+                    phrase
+                })
+            })
+            .take(MAX_PHRASE_SYNONYMS)
+            .collect();
+    }
+
+    let phrases = phrases.into_iter().map(|phrase| Operation::phrase(phrase, slop)).collect();
+    Ok(Operation::or(false, phrases))
+}
+
+/// Matches on the `PrimitiveQueryPart` and create an operation from it.
+fn resolve_primitive_part(
     ctx: &impl Context,
-    terms_matching_strategy: TermsMatchingStrategy,
     authorize_typos: bool,
-    query: &[PrimitiveQueryPart],
+    synonyms_in_phrases: bool,
+    part: PrimitiveQueryPart,
 ) -> Result<Operation> {
-    /// Matches on the `PrimitiveQueryPart` and create an operation from it.
-    fn resolve_primitive_part(
-        ctx: &impl Context,
-        authorize_typos: bool,
-        part: PrimitiveQueryPart,
-    ) -> Result<Operation> {
-        match part {
-            // 1. try to split word in 2
-            // 2. try to fetch synonyms
-            // 3. create an operation containing the word
-            // 4. wrap all in an OR operation
-            PrimitiveQueryPart::Word(word, prefix) => {
-                let mut children = synonyms(ctx, &[&word])?.unwrap_or_default();
-                if let Some((left, right)) = split_best_frequency(ctx, &word)? {
-                    children.push(Operation::Phrase(vec![
-                        Some(left.to_string()),
-                        Some(right.to_string()),
-                    ]));
-                }
-                let (word_len_one_typo, word_len_two_typo) = ctx.min_word_len_for_typo()?;
-                let exact_words = ctx.exact_words();
-                let config =
-                    TypoConfig { max_typos: 2, word_len_one_typo, word_len_two_typo, exact_words };
-                children.push(Operation::Query(Query {
-                    prefix,
-                    kind: typos(word, authorize_typos, config),
-                }));
-                Ok(Operation::or(false, children))
+    match part {
+        // 1. try to split word in 2
+        // 2. try to fetch synonyms
+        // 3. create an operation containing the word
+        // 4. wrap all in an OR operation
+        PrimitiveQueryPart::Word(word, prefix, _) => {
+            let mut children = synonyms(ctx, &[&word])?.unwrap_or_default();
+            if let Some((left, right)) = split_best_frequency(ctx, &word)? {
+                let words = vec![Some(left.to_string()), Some(right.to_string())];
+                children.push(Operation::Phrase(words, PhraseSlop::EXACT));
+            }
+            let (word_len_one_typo, word_len_two_typo) = ctx.min_word_len_for_typo()?;
+            let exact_words = ctx.exact_words();
+            let config =
+                TypoConfig { max_typos: 2, word_len_one_typo, word_len_two_typo, exact_words };
+            // the stem is matched exactly, only the word itself is an exact match.
+            if let Some(stem) = ctx.word_stem(&word) {
+                let stem = Query { prefix: false, kind: QueryKind::exact(stem) };
+                children.push(Operation::Query(stem));
             }
-            // create a CONSECUTIVE operation wrapping all word in the phrase
-            PrimitiveQueryPart::Phrase(words) => Ok(Operation::phrase(words)),
+            children.push(Operation::Query(Query {
+                prefix,
+                kind: typos(word, authorize_typos, config),
+            }));
+            Ok(Operation::or(false, children))
+        }
+        // create a CONSECUTIVE operation wrapping all word in the phrase,
+        // and the ones of the phrases made of their synonyms if requested
+        PrimitiveQueryPart::Phrase(words, slop) if synonyms_in_phrases => {
+            Ok(phrase_with_synonyms(ctx, words, slop)?)
+        }
+        PrimitiveQueryPart::Phrase(words, slop) => Ok(Operation::phrase(words, slop)),
+    }
+}
+
+/// Concatenates the words of an ngram, the preserved forms of
+/// the words are concatenated into a preserved form.
+fn concat_words(words: &[&str]) -> String {
+    match words.first().and_then(|first| preserved_form_prefix(first)) {
+        Some(prefix) => {
+            let words = words.iter().map(|w| w.strip_prefix(prefix).unwrap_or(w));
+            iter::once(prefix).chain(words.flat_map(str::chars)).collect()
         }
+        None => words.concat(),
     }
+}
 
+/// Main function that creates the final query tree from the primitive query.
+fn create_query_tree(
+    ctx: &impl Context,
+    terms_matching_strategy: TermsMatchingStrategy,
+    authorize_typos: bool,
+    synonyms_in_phrases: bool,
+    query: &[PrimitiveQueryPart],
+) -> Result<Operation> {
     /// Create all ngrams 1..=3 generating query tree branches.
     fn ngrams(
         ctx: &impl Context,
         authorize_typos: bool,
+        synonyms_in_phrases: bool,
         query: &[PrimitiveQueryPart],
         any_words: bool,
     ) -> Result<Operation> {
         const MAX_NGRAM: usize = 3;
         let mut op_children = Vec::new();
 
-        for sub_query in query.linear_group_by(|a, b| !(a.is_phrase() || b.is_phrase())) {
+        for sub_query in query.linear_group_by(|a, b| a.ngram_with(b)) {
             let mut or_op_children = Vec::new();
 
             for ngram in 1..=MAX_NGRAM.min(sub_query.len()) {
@@ -418,8 +708,12 @@ fn create_query_tree(
 
                     match group {
                         [part] => {
-                            let operation =
-                                resolve_primitive_part(ctx, authorize_typos, part.clone())?;
+                            let operation = resolve_primitive_part(
+                                ctx,
+                                authorize_typos,
+                                synonyms_in_phrases,
+                                part.clone(),
+                            )?;
                             and_op_children.push(operation);
                         }
                         words => {
@@ -427,7 +721,7 @@ fn create_query_tree(
                             let words: Vec<_> = words
                                 .iter()
                                 .filter_map(|part| {
-                                    if let PrimitiveQueryPart::Word(word, _) = part {
+                                    if let PrimitiveQueryPart::Word(word, ..) = part {
                                         Some(word.as_str())
                                     } else {
                                         None
@@ -435,7 +729,7 @@ fn create_query_tree(
                                 })
                                 .collect();
                             let mut operations = synonyms(ctx, &words)?.unwrap_or_default();
-                            let concat = words.concat();
+                            let concat = concat_words(&words);
                             let (word_len_one_typo, word_len_two_typo) =
                                 ctx.min_word_len_for_typo()?;
                             let exact_words = ctx.exact_words();
@@ -455,7 +749,8 @@ fn create_query_tree(
                     }
 
                     if !is_last {
-                        let ngrams = ngrams(ctx, authorize_typos, tail, any_words)?;
+                        let ngrams =
+                            ngrams(ctx, authorize_typos, synonyms_in_phrases, tail, any_words)?;
                         and_op_children.push(ngrams);
                     }
 
@@ -479,23 +774,25 @@ fn create_query_tree(
     let number_phrases = query.iter().filter(|p| p.is_phrase()).count();
     let remove_count = query.len() - max(number_phrases, 1);
     if remove_count == 0 {
-        return ngrams(ctx, authorize_typos, query, false);
+        return ngrams(ctx, authorize_typos, synonyms_in_phrases, query, false);
     }
 
     let mut operation_children = Vec::new();
     let mut query = query.to_vec();
     for _ in 0..=remove_count {
         let pos = match terms_matching_strategy {
-            TermsMatchingStrategy::All => return ngrams(ctx, authorize_typos, &query, false),
+            TermsMatchingStrategy::All => {
+                return ngrams(ctx, authorize_typos, synonyms_in_phrases, &query, false)
+            }
             TermsMatchingStrategy::Any => {
                 let operation = Operation::Or(
                     true,
                     vec![
                         // branch allowing matching documents to contains any query word.
-                        ngrams(ctx, authorize_typos, &query, true)?,
+                        ngrams(ctx, authorize_typos, synonyms_in_phrases, &query, true)?,
                         // branch forcing matching documents to contains all the query words,
                         // keeping this documents of the top of the resulted list.
-                        ngrams(ctx, authorize_typos, &query, false)?,
+                        ngrams(ctx, authorize_typos, synonyms_in_phrases, &query, false)?,
                     ],
                 );
 
@@ -515,7 +812,7 @@ fn create_query_tree(
                 .enumerate()
                 .filter(|(_, part)| !part.is_phrase())
                 .min_by_key(|(_, part)| match part {
-                    PrimitiveQueryPart::Word(s, _) => s.len(),
+                    PrimitiveQueryPart::Word(s, ..) => s.len(),
                     _ => unreachable!(),
                 })
                 .map(|(pos, _)| pos),
@@ -524,7 +821,7 @@ fn create_query_tree(
                 .enumerate()
                 .filter(|(_, part)| !part.is_phrase())
                 .max_by_key(|(_, part)| match part {
-                    PrimitiveQueryPart::Word(s, _) => {
+                    PrimitiveQueryPart::Word(s, ..) => {
                         ctx.word_documents_count(s).unwrap_or_default().unwrap_or(u64::max_value())
                     }
                     _ => unreachable!(),
@@ -533,7 +830,8 @@ fn create_query_tree(
         };
 
         // compute and push the current branch on the front
-        operation_children.insert(0, ngrams(ctx, authorize_typos, &query, false)?);
+        operation_children
+            .insert(0, ngrams(ctx, authorize_typos, synonyms_in_phrases, &query, false)?);
         // remove word from query before creating an new branch
         match pos {
             Some(pos) => query.remove(pos),
@@ -585,7 +883,7 @@ fn create_matching_words(
         match part {
             // 1. try to split word in 2
             // 2. try to fetch synonyms
-            PrimitiveQueryPart::Word(word, prefix) => {
+            PrimitiveQueryPart::Word(word, prefix, _) => {
                 if let Some(synonyms) = ctx.synonyms(&[word.as_str()])? {
                     for synonym in synonyms {
                         // Require that all words of the synonym have a corresponding MatchingWord
@@ -627,7 +925,7 @@ fn create_matching_words(
                 }
             }
             // create a CONSECUTIVE matchings words wrapping all word in the phrase
-            PrimitiveQueryPart::Phrase(words) => {
+            PrimitiveQueryPart::Phrase(words, _) => {
                 let ids: Vec<_> =
                     (0..words.len()).into_iter().map(|i| id + i as PrimitiveWordId).collect();
                 // Require that all words of the phrase have a corresponding MatchingWord
@@ -657,7 +955,7 @@ fn create_matching_words(
     ) -> Result<()> {
         const MAX_NGRAM: usize = 3;
 
-        for sub_query in query.linear_group_by(|a, b| !(a.is_phrase() || b.is_phrase())) {
+        for sub_query in query.linear_group_by(|a, b| a.ngram_with(b)) {
             for ngram in 1..=MAX_NGRAM.min(sub_query.len()) {
                 if let Some(group) = sub_query.get(..ngram) {
                     let tail = &sub_query[ngram..];
@@ -679,7 +977,7 @@ fn create_matching_words(
                             let words: Vec<_> = words
                                 .iter()
                                 .filter_map(|part| {
-                                    if let PrimitiveQueryPart::Word(word, _) = part {
+                                    if let PrimitiveQueryPart::Word(word, ..) = part {
                                         Some(word.as_str())
                                     } else {
                                         None
@@ -702,7 +1000,7 @@ fn create_matching_words(
                                     }
                                 }
                             }
-                            let word = words.concat();
+                            let word = concat_words(&words);
                             let (word_len_one_typo, word_len_two_typo) =
                                 ctx.min_word_len_for_typo()?;
                             let exact_words = ctx.exact_words();
@@ -752,34 +1050,64 @@ fn create_matching_words(
 
 pub type PrimitiveQuery = Vec<PrimitiveQueryPart>;
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum PrimitiveQueryPart {
-    Phrase(Vec<Option<String>>),
-    Word(String, IsPrefix),
+    Phrase(Vec<Option<String>>, PhraseSlop),
+    Word(String, IsPrefix, AfterDroppedWord),
 }
 
 impl PrimitiveQueryPart {
     fn is_phrase(&self) -> bool {
-        matches!(self, Self::Phrase(_))
+        matches!(self, Self::Phrase(..))
     }
 
     fn is_prefix(&self) -> bool {
-        matches!(self, Self::Word(_, is_prefix) if *is_prefix)
+        matches!(self, Self::Word(_, is_prefix, _) if *is_prefix)
+    }
+
+    /// Whether the part can be part of the same n-grams as the next one, the phrases and the
+    /// words of each side of a dropped word are never concatenated.
+    fn ngram_with(&self, next: &Self) -> bool {
+        !(self.is_phrase() || next.is_phrase() || matches!(next, Self::Word(_, _, true)))
     }
 
     fn len(&self) -> usize {
         match self {
-            Self::Phrase(words) => words.len(),
-            Self::Word(_, _) => 1,
+            Self::Phrase(words, _) => words.len(),
+            Self::Word(..) => 1,
         }
     }
 }
 
 /// Create primitive query from tokenized query string,
 /// the primitive query is an intermediate state to build the query tree.
+///
+/// A phrase closed by a quote directly followed by `~` and a number, e.g. `"quick fox"~2`,
+/// is given this slop, its words are matched in the `phrase_order`.
+///
+/// The stems of the words, but not of the words of the phrases, are added to `stems`.
+///
+/// When the `original_query` the tokens come from is given, the words are replaced by their
+/// [case-preserved forms](case_sensitive_word) if `case_sensitive`, or by their
+/// [diacritics-preserved forms](diacritics_word) unless the accents are free. These forms are
+/// never stemmed and the normalized words they replace are added to `normalized_words`.
+#[allow(clippy::too_many_arguments)]
 fn create_primitive_query<A>(
     query: NormalizedTokenIter<A>,
+    original_query: Option<&str>,
+    case_sensitive: bool,
+    typo_on_diacritics: TypoOnDiacritics,
     words_limit: Option<usize>,
+    max_word_length: usize,
+    long_words_policy: LongWordsPolicy,
+    min_word_length: usize,
+    stop_words_mode: StopWordsMode,
+    normalized_stop_words: Option<&fst::Set<Vec<u8>>>,
+    normalization_rules: &BTreeMap<String, String>,
+    phrase_order: PhraseOrder,
+    stemmers: &Stemmers,
+    stems: &mut HashMap<String, String>,
+    normalized_words: &mut HashMap<String, String>,
 ) -> PrimitiveQuery
 where
     A: AsRef<[u8]>,
@@ -787,6 +1115,10 @@ where
     let mut primitive_query = Vec::new();
     let mut phrase = Vec::new();
     let mut quoted = false;
+    // whether the last token closed a phrase, that the next `~` gives a slop to.
+    let mut phrase_closed = false;
+    // whether a word was dropped since the last part, the n-grams don't span over it.
+    let mut after_dropped_word = false;
 
     let parts_limit = words_limit.unwrap_or(usize::MAX);
 
@@ -800,47 +1132,126 @@ where
         match token.kind {
             TokenKind::Word | TokenKind::StopWord => {
                 // 1. if the word is quoted we push it in a phrase-buffer waiting for the ending quote,
-                // 2. if the word is not the last token of the query and is not a stop_word we push it as a non-prefix word,
+                // 2. if the word is not the last token of the query and is neither a stop_word nor too short
+                //    we push it as a non-prefix word,
                 // 3. if the word is the last token of the query we push it as a prefix word.
-                if quoted {
-                    if let TokenKind::StopWord = token.kind {
-                        phrase.push(None)
+                // The words are normalized, the numbers canonicalized and the words that
+                // are too long are handled like they are during indexing.
+                let word = canonicalize_numeric_word(token.lemma());
+                let word = apply_normalization_rules(&word, normalization_rules);
+                // the words normalized by the rules into a stop word are stop words too.
+                let stop_word = token.kind == TokenKind::StopWord
+                    || normalized_stop_words
+                        .map_or(false, |stop_words| stop_words.contains(word.as_ref()));
+                let word = long_words_policy.apply(&word, max_word_length);
+                // the words that are too short are never indexed, they are ignored like the stop words.
+                let too_short = word.map_or(false, |word| word.chars().count() < min_word_length);
+                let original = original_query.map(|query| &query[token.byte_start..token.byte_end]);
+                let preserved = original.and_then(|original| {
+                    if case_sensitive {
+                        Some(case_sensitive_word(original))
                     } else {
-                        phrase.push(Some(token.lemma().to_string()));
+                        diacritics_word(
+                            original,
+                            token.lemma(),
+                            typo_on_diacritics,
+                            normalization_rules,
+                        )
                     }
-                } else if peekable.peek().is_some() {
-                    if let TokenKind::StopWord = token.kind {
-                    } else {
-                        primitive_query
-                            .push(PrimitiveQueryPart::Word(token.lemma().to_string(), false));
+                });
+                let word = match &preserved {
+                    Some(preserved) => {
+                        let preserved =
+                            word.and(long_words_policy.apply(preserved, max_word_length));
+                        if let (Some(preserved), Some(word)) = (preserved, word) {
+                            normalized_words.insert(preserved.to_string(), word.to_string());
+                        }
+                        preserved
+                    }
+                    None => word,
+                };
+                if quoted {
+                    // the stop words are only indexed in the `SearchOnly` mode.
+                    let stop_word = stop_word && stop_words_mode == StopWordsMode::IndexAndSearch;
+                    match (stop_word, word) {
+                        (true, _) | (_, None) => phrase.push(None),
+                        (_, Some(_)) if too_short => phrase.push(None),
+                        (_, Some(word)) => phrase.push(Some(word.to_string())),
+                    }
+                } else if let Some(word) = word {
+                    let is_last = peekable.peek().is_none();
+                    if is_last || (!stop_word && !too_short) {
+                        let stem = match preserved {
+                            Some(_) => None,
+                            None => stemmers
+                                .stem(word, token.script, token.language)
+                                .filter(|stem| stem.chars().count() >= min_word_length),
+                        };
+                        if let Some(stem) = stem {
+                            stems.insert(word.to_string(), stem_word(&stem));
+                        }
+                        let part = PrimitiveQueryPart::Word(
+                            word.to_string(),
+                            is_last,
+                            mem::take(&mut after_dropped_word),
+                        );
+                        primitive_query.push(part);
                     }
                 } else {
-                    primitive_query.push(PrimitiveQueryPart::Word(token.lemma().to_string(), true));
+                    after_dropped_word = true;
                 }
             }
             TokenKind::Separator(separator_kind) => {
-                let quote_count = token.lemma().chars().filter(|&s| s == '"').count();
+                let lemma = token.lemma();
+                let quote_count = lemma.chars().filter(|&s| s == '"').count();
+                let closing_quote = quoted && quote_count % 2 != 0;
                 // swap quoted state if we encounter a double quote
                 if quote_count % 2 != 0 {
                     quoted = !quoted;
                 }
                 // if there is a quote or a hard separator we close the phrase.
+                let mut closed = false;
                 if quote_count > 0 || separator_kind == SeparatorKind::Hard {
                     let phrase = mem::take(&mut phrase);
 
                     // if the phrase only contains stop words, we don't keep it in the query.
                     if phrase.iter().any(|w| w.is_some()) {
-                        primitive_query.push(PrimitiveQueryPart::Phrase(phrase));
+                        let part = PrimitiveQueryPart::Phrase(phrase, PhraseSlop::EXACT);
+                        primitive_query.push(part);
+                        closed = closing_quote;
+                    }
+                }
+
+                // the `~` is either part of the separators following the closing quote
+                // or the next separator, and the slop the number that follows it.
+                let after_quote = lemma.rsplit('"').next().unwrap_or_default();
+                let tilde = if closed { after_quote.starts_with('~') } else { lemma == "~" };
+                if (closed || phrase_closed) && tilde {
+                    let slop = peekable.next_if(|token| {
+                        token.kind == TokenKind::Word && token.lemma().parse::<u8>().is_ok()
+                    });
+                    if let Some(slop) = slop.and_then(|token| token.lemma().parse().ok()) {
+                        if let Some(PrimitiveQueryPart::Phrase(_, phrase_slop)) =
+                            primitive_query.last_mut()
+                        {
+                            *phrase_slop = PhraseSlop::new(slop, phrase_order);
+                        }
                     }
+                    phrase_closed = false;
+                } else {
+                    phrase_closed = closed;
                 }
+                continue;
             }
             _ => (),
         }
+        phrase_closed = false;
     }
 
     // If a quote is never closed, we consider all of the end of the query as a phrase.
     if phrase.iter().any(|w| w.is_some()) {
-        primitive_query.push(PrimitiveQueryPart::Phrase(mem::take(&mut phrase)));
+        let part = PrimitiveQueryPart::Phrase(mem::take(&mut phrase), PhraseSlop::EXACT);
+        primitive_query.push(part);
     }
 
     primitive_query
@@ -854,7 +1265,7 @@ pub fn maximum_typo(operation: &Operation) -> usize {
         And(ops) => ops.iter().map(maximum_typo).sum::<usize>(),
         Query(q) => q.kind.typo() as usize,
         // no typo allowed in phrases
-        Phrase(_) => 0,
+        Phrase(..) => 0,
     }
 }
 
@@ -866,7 +1277,7 @@ pub fn maximum_proximity(operation: &Operation) -> usize {
         And(ops) => {
             ops.iter().map(maximum_proximity).sum::<usize>() + ops.len().saturating_sub(1) * 7
         }
-        Query(_) | Phrase(_) => 0,
+        Query(_) | Phrase(..) => 0,
     }
 }
 
@@ -882,12 +1293,14 @@ mod test {
     use super::*;
     use crate::index::tests::TempIndex;
     use crate::index::{DEFAULT_MIN_WORD_LEN_ONE_TYPO, DEFAULT_MIN_WORD_LEN_TWO_TYPOS};
+    use crate::MAX_WORD_LENGTH;
 
     #[derive(Debug)]
     struct TestContext {
         synonyms: HashMap<Vec<String>, Vec<Vec<String>>>,
         postings: HashMap<String, RoaringBitmap>,
         exact_words: Option<fst::Set<Cow<'static, [u8]>>>,
+        synonyms_in_phrases: bool,
     }
 
     impl TestContext {
@@ -898,12 +1311,29 @@ mod test {
             words_limit: Option<usize>,
             query: NormalizedTokenIter<A>,
         ) -> Result<Option<(Operation, PrimitiveQuery)>> {
-            let primitive_query = create_primitive_query(query, words_limit);
+            let primitive_query = create_primitive_query(
+                query,
+                None,
+                false,
+                TypoOnDiacritics::default(),
+                words_limit,
+                MAX_WORD_LENGTH,
+                LongWordsPolicy::Drop,
+                0,
+                StopWordsMode::default(),
+                None,
+                &BTreeMap::new(),
+                PhraseOrder::default(),
+                &Stemmers::default(),
+                &mut HashMap::new(),
+                &mut HashMap::new(),
+            );
             if !primitive_query.is_empty() {
                 let qt = create_query_tree(
                     self,
                     terms_matching_strategy,
                     authorize_typos,
+                    self.synonyms_in_phrases,
                     &primitive_query,
                 )?;
                 Ok(Some((qt, primitive_query)))
@@ -1005,6 +1435,7 @@ mod test {
                     String::from("quickbrown fox")  => random_postings(rng,   8000),
                 },
                 exact_words,
+                synonyms_in_phrases: false,
             }
         }
     }
@@ -1090,6 +1521,48 @@ mod test {
         "###);
     }
 
+    #[test]
+    fn synonyms_in_phrases() {
+        let query = "\"nyc marathon\"";
+
+        let (query_tree, _) = TestContext::default()
+            .build(TermsMatchingStrategy::Last, true, None, query.tokenize())
+            .unwrap()
+            .unwrap();
+
+        insta::assert_debug_snapshot!(query_tree, @r###"
+        PHRASE [Some("nyc"), Some("marathon")]
+        "###);
+
+        let context = TestContext { synonyms_in_phrases: true, ..TestContext::default() };
+        let (query_tree, _) = context
+            .build(TermsMatchingStrategy::Last, true, None, query.tokenize())
+            .unwrap()
+            .unwrap();
+
+        insta::assert_debug_snapshot!(query_tree, @r###"
+        OR
+          PHRASE [Some("nyc"), Some("marathon")]
+          PHRASE [Some("new"), Some("york"), Some("marathon")]
+          PHRASE [Some("new"), Some("york"), Some("city"), Some("marathon")]
+        "###);
+
+        // the words outside of the quotes are always replaced by their synonyms
+        let (query_tree, _) = TestContext::default()
+            .build(TermsMatchingStrategy::All, true, None, "nyc \"marathon\"".tokenize())
+            .unwrap()
+            .unwrap();
+
+        insta::assert_debug_snapshot!(query_tree, @r###"
+        AND
+          OR
+            PHRASE [Some("new"), Some("york")]
+            PHRASE [Some("new"), Some("york"), Some("city")]
+            Exact { word: "nyc" }
+          Exact { word: "marathon" }
+        "###);
+    }
+
     #[test]
     fn complex_synonyms() {
         let query = "new york city ";
@@ -1232,6 +1705,23 @@ mod test {
         "###);
     }
 
+    #[test]
+    fn phrase_with_slop() {
+        let query = "\"quick fox\"~2 \"lazy dog\"~0";
+        let tokens = query.tokenize();
+
+        let (query_tree, _) = TestContext::default()
+            .build(TermsMatchingStrategy::All, true, None, tokens)
+            .unwrap()
+            .unwrap();
+
+        insta::assert_debug_snapshot!(query_tree, @r###"
+        AND
+          PHRASE [Some("quick"), Some("fox")]~2 InOrder
+          PHRASE [Some("lazy"), Some("dog")]
+        "###);
+    }
+
     #[test]
     fn optional_word() {
         let query = "hey my friend ";
@@ -1379,14 +1869,13 @@ mod test {
         let query = "what a supercalifragilisticexpialidocioussupercalifragilisticexpialidocioussupercalifragilisticexpialidocioussupercalifragilisticexpialidocioussupercalifragilisticexpialidocioussupercalifragilisticexpialidocioussupercalifragilisticexpialidocioussupercalifragilisticexpialidocious house";
         let mut builder = QueryTreeBuilder::new(&rtxn, &index).unwrap();
         builder.words_limit(10);
+        // the long word is dropped from the query, like it is when indexing documents,
+        // the words around it are not concatenated
         let (_, _, matching_words) = builder.build(query.tokenize()).unwrap().unwrap();
         insta::assert_snapshot!(format!("{matching_words:?}"), @r###"
         [
-        ([MatchingWord { word: "house", typo: 1, prefix: true }], [3])
-        ([MatchingWord { word: "house", typo: 1, prefix: true }], [2])
         ([MatchingWord { word: "whata", typo: 1, prefix: false }], [0, 1])
         ([MatchingWord { word: "house", typo: 1, prefix: true }], [2])
-        ([MatchingWord { word: "house", typo: 1, prefix: true }], [1])
         ([MatchingWord { word: "what", typo: 0, prefix: false }], [0])
         ([MatchingWord { word: "a", typo: 0, prefix: false }], [1])
         ]
@@ -0,0 +1,230 @@
+use std::fmt::Write;
+
+use roaring::RoaringBitmap;
+use serde::Serialize;
+
+use super::criteria::{resolve_query_tree, CriteriaBuilder};
+use super::query_tree::{Operation, QueryKind};
+use super::{Search, WordDerivationsCache};
+use crate::Result;
+
+/// A node of the query tree built for a search, annotated with the number of documents
+/// it matches, see [`Search::build_query_tree_debug`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum QueryTreeNode {
+    And {
+        candidates: u64,
+        children: Vec<QueryTreeNode>,
+    },
+    #[serde(rename_all = "camelCase")]
+    Or {
+        is_optional_word: bool,
+        candidates: u64,
+        children: Vec<QueryTreeNode>,
+    },
+    /// A series of consecutive words, `None` being a stop word.
+    Phrase {
+        words: Vec<Option<String>>,
+        candidates: u64,
+    },
+    #[serde(rename_all = "camelCase")]
+    Word {
+        word: String,
+        is_prefix: bool,
+        /// Whether the word can only be matched exactly.
+        exact: bool,
+        max_typo: u8,
+        candidates: u64,
+    },
+}
+
+impl QueryTreeNode {
+    /// The number of documents matched by this node.
+    pub fn candidates(&self) -> u64 {
+        match self {
+            QueryTreeNode::And { candidates, .. }
+            | QueryTreeNode::Or { candidates, .. }
+            | QueryTreeNode::Phrase { candidates, .. }
+            | QueryTreeNode::Word { candidates, .. } => *candidates,
+        }
+    }
+
+    /// Returns a Graphviz dot representation of the tree, each node
+    /// being labelled with its operation and its number of candidates.
+    pub fn to_dot(&self) -> String {
+        fn write_node(dot: &mut String, node: &QueryTreeNode, next_id: &mut usize) -> usize {
+            let id = *next_id;
+            *next_id += 1;
+
+            let label = match node {
+                QueryTreeNode::And { .. } => String::from("AND"),
+                QueryTreeNode::Or { is_optional_word: true, .. } => String::from("OR(WORD)"),
+                QueryTreeNode::Or { is_optional_word: false, .. } => String::from("OR"),
+                QueryTreeNode::Phrase { words, .. } => {
+                    let words: Vec<_> = words.iter().map(|w| w.as_deref().unwrap_or("_")).collect();
+                    format!("PHRASE {}", words.join(" "))
+                }
+                QueryTreeNode::Word { word, is_prefix, exact, max_typo, .. } => {
+                    let prefix = if *is_prefix { "Prefix" } else { "" };
+                    if *exact {
+                        format!("{}Exact {}", prefix, word)
+                    } else {
+                        format!("{}Tolerant {} (max typo: {})", prefix, word, max_typo)
+                    }
+                }
+            };
+            let label = label.replace('\\', "\\\\").replace('"', "\\\"");
+            let _ =
+                writeln!(dot, "  {} [label=\"{}\\n{} candidates\"];", id, label, node.candidates());
+
+            if let QueryTreeNode::And { children, .. } | QueryTreeNode::Or { children, .. } = node {
+                for child in children {
+                    let child_id = write_node(dot, child, next_id);
+                    let _ = writeln!(dot, "  {} -> {};", id, child_id);
+                }
+            }
+
+            id
+        }
+
+        let mut dot = String::from("digraph query_tree {\n");
+        write_node(&mut dot, self, &mut 0);
+        dot.push('}');
+        dot
+    }
+}
+
+impl<'a> Search<'a> {
+    /// Builds the query tree exactly like the search does, after the stop words removal, the
+    /// ngrams concatenation, the synonyms expansion and the typo derivations, and annotates
+    /// each node with the number of documents it matches.
+    ///
+    /// Returns `None` if there is no query or if the query doesn't contain any word.
+    pub fn build_query_tree_debug(&self) -> Result<Option<QueryTreeNode>> {
+        let query_tree = match self.build_query_tree()? {
+            Some((query_tree, _, _)) => query_tree,
+            None => return Ok(None),
+        };
+
+        let ctx = CriteriaBuilder::new(self.rtxn, self.index)?;
+        let soft_deleted_documents = self.index.soft_deleted_documents_ids(self.rtxn)?;
+        let mut wdcache = WordDerivationsCache::new();
+        let (node, _) = debug_operation(&ctx, &query_tree, &soft_deleted_documents, &mut wdcache)?;
+
+        Ok(Some(node))
+    }
+}
+
+/// Converts the operation into a node, resolving the leaves like the search does
+/// and combining their candidates to compute the candidates of the branches.
+fn debug_operation(
+    ctx: &CriteriaBuilder,
+    operation: &Operation,
+    soft_deleted_documents: &RoaringBitmap,
+    wdcache: &mut WordDerivationsCache,
+) -> Result<(QueryTreeNode, RoaringBitmap)> {
+    match operation {
+        Operation::And(ops) | Operation::Or(_, ops) => {
+            let mut children = Vec::with_capacity(ops.len());
+            let mut candidates: Option<RoaringBitmap> = None;
+            for op in ops {
+                let (child, docids) = debug_operation(ctx, op, soft_deleted_documents, wdcache)?;
+                children.push(child);
+                candidates = Some(match (candidates, operation) {
+                    (Some(candidates), Operation::And(_)) => candidates & docids,
+                    (Some(candidates), _) => candidates | docids,
+                    (None, _) => docids,
+                });
+            }
+
+            let candidates = candidates.unwrap_or_default();
+            let node = match operation {
+                Operation::Or(is_optional_word, _) => QueryTreeNode::Or {
+                    is_optional_word: *is_optional_word,
+                    candidates: candidates.len(),
+                    children,
+                },
+                _ => QueryTreeNode::And { candidates: candidates.len(), children },
+            };
+            Ok((node, candidates))
+        }
+        Operation::Phrase(words, _) => {
+            let candidates = resolve_query_tree(ctx, operation, wdcache)? - soft_deleted_documents;
+            let node = QueryTreeNode::Phrase { words: words.clone(), candidates: candidates.len() };
+            Ok((node, candidates))
+        }
+        Operation::Query(query) => {
+            let candidates = resolve_query_tree(ctx, operation, wdcache)? - soft_deleted_documents;
+            let (word, exact, max_typo) = match &query.kind {
+                QueryKind::Exact { word, .. } => (word.clone(), true, 0),
+                QueryKind::Tolerant { typo, word } => (word.clone(), false, *typo),
+            };
+            let node = QueryTreeNode::Word {
+                word,
+                is_prefix: query.prefix,
+                exact,
+                max_typo,
+                candidates: candidates.len(),
+            };
+            Ok((node, candidates))
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashMap;
+
+    use big_s::S;
+
+    use super::*;
+    use crate::index::tests::TempIndex;
+    use crate::TermsMatchingStrategy;
+
+    #[test]
+    fn query_tree_debug_dot() {
+        let index = TempIndex::new();
+        index
+            .update_settings(|settings| {
+                settings.set_synonyms(HashMap::from([(S("nyc"), vec![S("new york")])]));
+            })
+            .unwrap();
+        index
+            .add_documents(documents!([
+                { "id": 0, "text": "the big apple is in new york" },
+                { "id": 1, "text": "nyc never sleeps" },
+                { "id": 2, "text": "new york and the big apple" },
+            ]))
+            .unwrap();
+
+        let rtxn = index.read_txn().unwrap();
+        let mut search = Search::new(&rtxn, &index);
+        search.query("\"big apple\" nyc");
+        search.terms_matching_strategy(TermsMatchingStrategy::All);
+        let tree = search.build_query_tree_debug().unwrap().unwrap();
+
+        assert_eq!(tree.candidates(), 2);
+        insta::assert_snapshot!(tree.to_dot(), @r###"
+        digraph query_tree {
+          0 [label="AND\n2 candidates"];
+          1 [label="PHRASE big apple\n2 candidates"];
+          0 -> 1;
+          2 [label="OR\n3 candidates"];
+          3 [label="PHRASE new york\n2 candidates"];
+          2 -> 3;
+          4 [label="PrefixExact nyc\n1 candidates"];
+          2 -> 4;
+          0 -> 2;
+        }
+        "###);
+    }
+
+    #[test]
+    fn no_query_tree_without_query() {
+        let index = TempIndex::new();
+        let rtxn = index.read_txn().unwrap();
+        let search = Search::new(&rtxn, &index);
+        assert_eq!(search.build_query_tree_debug().unwrap(), None);
+    }
+}
@@ -0,0 +1,305 @@
+use std::borrow::Cow;
+
+use heed::{Database, RoTxn};
+use roaring::RoaringBitmap;
+
+use super::query_tree::{Operation, Query, QueryKind};
+use super::{word_derivations, Search, TermsMatchingStrategy, WordDerivationsCache};
+use crate::error::UserError;
+use crate::{
+    absolute_from_relative_position, CboRoaringBitmapCodec, FieldId, FieldsIdsMap, Index, Result,
+    StrBEU32Codec,
+};
+
+/// A term of the query constrained to a field, written `field:term`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct ScopedTerm {
+    pub field_id: FieldId,
+    pub term: String,
+}
+
+/// Extracts the `field:term` terms of the query, outside of the phrases, and returns
+/// the query without their `field:` prefixes along with them.
+///
+/// Only the prefixes naming a field of the index are recognized, the other ones, e.g.
+/// in `12:30`, are left in the query. A prefix naming a field that isn't searchable is an
+/// error, unless `ignore_unsearchable` is `true`, in which case the term isn't scoped.
+pub(crate) fn extract_scoped_terms<'q>(
+    query: &'q str,
+    fields_ids_map: &FieldsIdsMap,
+    searchable_fields_ids: Option<&[FieldId]>,
+    ignore_unsearchable: bool,
+) -> Result<(Cow<'q, str>, Vec<ScopedTerm>)> {
+    let mut unscoped_query = String::with_capacity(query.len());
+    let mut scoped_terms = Vec::new();
+    let mut in_phrase = false;
+
+    for segment in query.split_inclusive(char::is_whitespace) {
+        let word = segment.trim_end();
+        if !in_phrase {
+            let scoped = word
+                .split_once(':')
+                .filter(|(_, term)| !term.is_empty() && !term.contains('"'))
+                .and_then(|(field, term)| fields_ids_map.id(field).map(|fid| (field, fid, term)));
+            if let Some((field, field_id, term)) = scoped {
+                let searchable =
+                    searchable_fields_ids.map_or(true, |fields_ids| fields_ids.contains(&field_id));
+                if searchable {
+                    scoped_terms.push(ScopedTerm { field_id, term: term.to_string() });
+                } else if !ignore_unsearchable {
+                    return Err(UserError::UnsearchableScopedTerm {
+                        field: field.to_string(),
+                        term: term.to_string(),
+                    }
+                    .into());
+                }
+                unscoped_query.push_str(&segment[field.len() + 1..]);
+                continue;
+            }
+        }
+        if word.matches('"').count() % 2 == 1 {
+            in_phrase = !in_phrase;
+        }
+        unscoped_query.push_str(segment);
+    }
+
+    if scoped_terms.is_empty() && unscoped_query == query {
+        Ok((Cow::Borrowed(query), scoped_terms))
+    } else {
+        Ok((Cow::Owned(unscoped_query), scoped_terms))
+    }
+}
+
+/// Returns the documents containing all the words of every scoped term in its field,
+/// `None` if the scoped terms don't contain any word.
+///
+/// The words of the scoped terms are matched like the words of the query: normalized, along
+/// with their synonyms and stems, with typos and the last word of each term as a prefix.
+/// The words of their phrases must all be in the field, but in any order.
+pub(crate) fn scoped_terms_candidates(
+    search: &Search,
+    scoped_terms: &[ScopedTerm],
+) -> Result<Option<RoaringBitmap>> {
+    let (rtxn, index) = (search.rtxn, search.index);
+    let words_fst = index.words_fst(rtxn)?;
+    let words_prefixes_fst = index.words_prefixes_fst(rtxn)?;
+    let mut wdcache = WordDerivationsCache::new();
+
+    let mut candidates: Option<RoaringBitmap> = None;
+    for ScopedTerm { field_id, term } in scoped_terms {
+        let mut builder = search.query_tree_builder()?;
+        builder.terms_matching_strategy(TermsMatchingStrategy::All);
+        builder.original_query(term);
+        let query_tree =
+            search.with_query_tokenizer(|tokenizer| builder.build(tokenizer.tokenize(term)))?;
+        let operation = match query_tree {
+            Some((operation, _, _)) => operation,
+            None => continue,
+        };
+
+        let context = FieldContext {
+            rtxn,
+            index,
+            field_id: *field_id,
+            words_fst: &words_fst,
+            words_prefixes_fst: &words_prefixes_fst,
+        };
+        let docids = context.operation_docids(&operation, &mut wdcache)?;
+        match candidates.as_mut() {
+            Some(candidates) => *candidates &= docids,
+            None => candidates = Some(docids),
+        }
+    }
+    Ok(candidates)
+}
+
+/// Resolves the operations of a query tree to the documents containing their words in a field.
+struct FieldContext<'t> {
+    rtxn: &'t RoTxn<'t>,
+    index: &'t Index,
+    field_id: FieldId,
+    words_fst: &'t fst::Set<Cow<'t, [u8]>>,
+    words_prefixes_fst: &'t fst::Set<Cow<'t, [u8]>>,
+}
+
+impl FieldContext<'_> {
+    fn operation_docids(
+        &self,
+        operation: &Operation,
+        wdcache: &mut WordDerivationsCache,
+    ) -> Result<RoaringBitmap> {
+        match operation {
+            Operation::And(operations) => {
+                let mut docids: Option<RoaringBitmap> = None;
+                for operation in operations {
+                    let operation_docids = self.operation_docids(operation, wdcache)?;
+                    match docids.as_mut() {
+                        Some(docids) => *docids &= operation_docids,
+                        None => docids = Some(operation_docids),
+                    }
+                }
+                Ok(docids.unwrap_or_default())
+            }
+            Operation::Or(_, operations) => {
+                let mut docids = RoaringBitmap::new();
+                for operation in operations {
+                    docids |= self.operation_docids(operation, wdcache)?;
+                }
+                Ok(docids)
+            }
+            Operation::Phrase(words, _) => {
+                let mut docids: Option<RoaringBitmap> = None;
+                for word in words.iter().flatten() {
+                    let word_docids = self.word_docids(self.index.word_position_docids, word)?;
+                    match docids.as_mut() {
+                        Some(docids) => *docids &= word_docids,
+                        None => docids = Some(word_docids),
+                    }
+                }
+                Ok(docids.unwrap_or_default())
+            }
+            Operation::Query(Query { prefix, kind }) => match kind {
+                QueryKind::Exact { word, .. } if !prefix => {
+                    self.word_docids(self.index.word_position_docids, word)
+                }
+                QueryKind::Exact { word, .. } if self.words_prefixes_fst.contains(word) => {
+                    self.word_docids(self.index.word_prefix_position_docids, word)
+                }
+                QueryKind::Exact { word, .. } => self.derivations_docids(word, *prefix, 0, wdcache),
+                QueryKind::Tolerant { typo, word } => {
+                    self.derivations_docids(word, *prefix, *typo, wdcache)
+                }
+            },
+        }
+    }
+
+    fn derivations_docids(
+        &self,
+        word: &str,
+        is_prefix: bool,
+        max_typo: u8,
+        wdcache: &mut WordDerivationsCache,
+    ) -> Result<RoaringBitmap> {
+        let mut docids = RoaringBitmap::new();
+        for (word, _typo) in word_derivations(word, is_prefix, max_typo, self.words_fst, wdcache)? {
+            docids |= self.word_docids(self.index.word_position_docids, word)?;
+        }
+        Ok(docids)
+    }
+
+    /// Returns the documents containing the word, or the prefix, in the field.
+    fn word_docids(
+        &self,
+        db: Database<StrBEU32Codec, CboRoaringBitmapCodec>,
+        word: &str,
+    ) -> Result<RoaringBitmap> {
+        let left = (word, absolute_from_relative_position(self.field_id, 0));
+        let right = (word, absolute_from_relative_position(self.field_id, u16::MAX));
+
+        let mut docids = RoaringBitmap::new();
+        for result in db.range(self.rtxn, &(left..=right))? {
+            let (_, positions_docids) = result?;
+            docids |= positions_docids;
+        }
+        Ok(docids)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use big_s::S;
+    use maplit::hashset;
+
+    use super::{extract_scoped_terms, ScopedTerm};
+    use crate::index::tests::TempIndex;
+    use crate::{Error, FieldsIdsMap, SearchResult, UserError};
+
+    #[test]
+    fn extract_field_scoped_terms() {
+        let mut fields_ids_map = FieldsIdsMap::new();
+        let title = fields_ids_map.insert("title").unwrap();
+        let body = fields_ids_map.insert("body").unwrap();
+        fields_ids_map.insert("brand").unwrap();
+        let searchable = [title, body];
+
+        let (query, terms) = extract_scoped_terms(
+            "title:phone at 12:30 \"body:case and\" body:waterproof",
+            &fields_ids_map,
+            Some(&searchable),
+            false,
+        )
+        .unwrap();
+        assert_eq!(query, "phone at 12:30 \"body:case and\" waterproof");
+        assert_eq!(
+            terms,
+            vec![
+                ScopedTerm { field_id: title, term: S("phone") },
+                ScopedTerm { field_id: body, term: S("waterproof") },
+            ]
+        );
+
+        assert!(
+            extract_scoped_terms("brand:acme", &fields_ids_map, Some(&searchable), false).is_err()
+        );
+        let (query, terms) =
+            extract_scoped_terms("brand:acme", &fields_ids_map, Some(&searchable), true).unwrap();
+        assert_eq!(query, "acme");
+        assert!(terms.is_empty());
+    }
+
+    #[test]
+    fn field_scoped_terms() {
+        let index = TempIndex::new();
+        index
+            .update_settings(|settings| {
+                settings.set_primary_key(S("id"));
+                settings.set_searchable_fields(vec![S("title"), S("body")]);
+                settings.set_filterable_fields(hashset! { S("brand") });
+            })
+            .unwrap();
+        index
+            .add_documents(documents!([
+                { "id": 0, "title": "waterproof phone", "body": "a great phone", "brand": "acme" },
+                { "id": 1, "title": "phone case", "body": "not waterproof", "brand": "acme" },
+                { "id": 2, "title": "case", "body": "for your waterproof phone", "brand": "acme" },
+                { "id": 3, "title": "12:30 phone", "body": "waterproof", "brand": "acme" },
+            ]))
+            .unwrap();
+
+        let rtxn = index.read_txn().unwrap();
+        let search = |query: &str| {
+            let SearchResult { mut documents_ids, .. } =
+                index.search(&rtxn).query(query).execute().unwrap();
+            documents_ids.sort_unstable();
+            documents_ids
+        };
+
+        // the document 2 only contains phone in its body
+        assert_eq!(search("phone"), vec![0, 1, 2, 3]);
+        assert_eq!(search("title:phone"), vec![0, 1, 3]);
+        assert_eq!(search("title:phone body:waterproof"), vec![1, 3]);
+        assert_eq!(search("title:phone waterproof"), vec![0, 1, 3]);
+        // the words of the terms are matched like the words of the query
+        assert_eq!(search("title:PHONE"), vec![0, 1, 3]);
+        assert_eq!(search("title:pho"), vec![0, 1, 3]);
+        assert_eq!(search("title:waterprof"), vec![0]);
+        // the phrases are left as is
+        assert_eq!(search("\"title:phone\""), vec![]);
+
+        // the fields that aren't searchable are refused, or ignored
+        let error = index.search(&rtxn).query("brand:phone").execute().unwrap_err();
+        assert!(matches!(
+            error,
+            Error::UserError(UserError::UnsearchableScopedTerm { ref field, ref term })
+                if field == "brand" && term == "phone"
+        ));
+        let SearchResult { mut documents_ids, .. } = index
+            .search(&rtxn)
+            .query("brand:phone")
+            .ignore_unsearchable_scoped_terms(true)
+            .execute()
+            .unwrap();
+        documents_ids.sort_unstable();
+        assert_eq!(documents_ids, vec![0, 1, 2, 3]);
+    }
+}
@@ -0,0 +1,175 @@
+use std::borrow::Cow;
+
+use super::query_tree::{Operation, Query, QueryKind};
+use super::{word_derivations, Filter, Search, TermsMatchingStrategy, WordDerivationsCache};
+use crate::{Index, Result, RoaringBitmapLenCodec};
+
+/// Estimates the fraction of the documents matching the query and the filter,
+/// see [`Index::estimate_selectivity`].
+pub(crate) fn estimate_selectivity(
+    rtxn: &heed::RoTxn,
+    index: &Index,
+    query: &str,
+    filter: Option<&Filter>,
+) -> Result<f64> {
+    let documents_count = index.number_of_documents(rtxn)?;
+    if documents_count == 0 {
+        return Ok(0.0);
+    }
+
+    let mut selectivity = 1.0;
+
+    let mut search = Search::new(rtxn, index);
+    search.query(query);
+    search.terms_matching_strategy(TermsMatchingStrategy::All);
+    if let Some((query_tree, _, _)) = search.build_query_tree()? {
+        let mut estimator = QueryEstimator {
+            rtxn,
+            index,
+            words_fst: index.words_fst(rtxn)?,
+            words_prefixes_fst: index.words_prefixes_fst(rtxn)?,
+            documents_count: documents_count as f64,
+            wdcache: WordDerivationsCache::new(),
+        };
+        selectivity *= estimator.operation_selectivity(&query_tree)?;
+    }
+
+    if let Some(filter) = filter {
+        selectivity *= filter.estimate_selectivity(rtxn, index)?;
+    }
+
+    Ok(selectivity.clamp(0.0, 1.0))
+}
+
+struct QueryEstimator<'t> {
+    rtxn: &'t heed::RoTxn<'t>,
+    index: &'t Index,
+    words_fst: fst::Set<Cow<'t, [u8]>>,
+    words_prefixes_fst: fst::Set<Cow<'t, [u8]>>,
+    documents_count: f64,
+    wdcache: WordDerivationsCache,
+}
+
+impl QueryEstimator<'_> {
+    fn operation_selectivity(&mut self, operation: &Operation) -> Result<f64> {
+        match operation {
+            Operation::And(ops) => {
+                let mut selectivity = 1.0;
+                for op in ops {
+                    selectivity *= self.operation_selectivity(op)?;
+                }
+                Ok(selectivity)
+            }
+            Operation::Or(_, ops) => {
+                let mut unmatched = 1.0;
+                for op in ops {
+                    unmatched *= 1.0 - self.operation_selectivity(op)?;
+                }
+                Ok(1.0 - unmatched)
+            }
+            // the proximity of the words of a phrase is ignored, it can only be
+            // more selective than the words themselves.
+            Operation::Phrase(words, _) => {
+                let mut selectivity = 1.0;
+                for word in words.iter().flatten() {
+                    let count = self.index.word_documents_count_upper_bound(self.rtxn, word)?;
+                    selectivity *= self.fraction(count);
+                }
+                Ok(selectivity)
+            }
+            Operation::Query(query) => self.query_selectivity(query),
+        }
+    }
+
+    /// Sums the number of documents of every derivation of the query word,
+    /// the documents containing several derivations are therefore counted several times.
+    fn query_selectivity(&mut self, query: &Query) -> Result<f64> {
+        let (word, typo) = match &query.kind {
+            QueryKind::Exact { word, .. } => (word, 0),
+            QueryKind::Tolerant { word, typo } => (word, *typo),
+        };
+
+        if query.prefix && typo == 0 && self.words_prefixes_fst.contains(word) {
+            let count = self.prefix_documents_count(word)?;
+            return Ok(self.fraction(count));
+        }
+
+        let mut count = 0;
+        let derivations =
+            word_derivations(word, query.prefix, typo, &self.words_fst, &mut self.wdcache)?;
+        for (word, _typo) in derivations {
+            count += self.index.word_documents_count_upper_bound(self.rtxn, word)?;
+        }
+        Ok(self.fraction(count))
+    }
+
+    fn prefix_documents_count(&self, prefix: &str) -> Result<u64> {
+        let word_prefix_docids =
+            self.index.word_prefix_docids.remap_data_type::<RoaringBitmapLenCodec>();
+        let exact_word_prefix_docids =
+            self.index.exact_word_prefix_docids.remap_data_type::<RoaringBitmapLenCodec>();
+        Ok(word_prefix_docids.get(self.rtxn, prefix)?.unwrap_or_default()
+            + exact_word_prefix_docids.get(self.rtxn, prefix)?.unwrap_or_default())
+    }
+
+    fn fraction(&self, count: u64) -> f64 {
+        (count as f64 / self.documents_count).min(1.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use big_s::S;
+    use maplit::hashset;
+
+    use crate::index::tests::TempIndex;
+    use crate::Filter;
+
+    #[test]
+    fn estimate_selectivity() {
+        let index = TempIndex::new();
+        index
+            .update_settings(|settings| {
+                settings.set_primary_key(S("id"));
+                settings.set_filterable_fields(hashset! { S("color") });
+            })
+            .unwrap();
+
+        let mut docs = vec![];
+        for i in 0..100 {
+            let mut title = vec!["text"];
+            if i % 2 == 0 {
+                title.push("apple");
+            }
+            if i % 5 == 0 {
+                title.push("zebra");
+            }
+            let color = if i % 3 == 0 { "red" } else { "blue" };
+            docs.push(serde_json::json!({ "id": i, "title": title.join(" "), "color": color }));
+        }
+        index.add_documents(documents!(docs)).unwrap();
+
+        let rtxn = index.read_txn().unwrap();
+        let estimate = |query: &str, filter: Option<&str>| {
+            let filter = filter.map(|filter| Filter::from_str(filter).unwrap().unwrap());
+            index.estimate_selectivity(&rtxn, query, filter.as_ref()).unwrap()
+        };
+        let assert_close = |estimate: f64, expected: f64| {
+            assert!(
+                (estimate - expected).abs() < 0.01,
+                "estimated {estimate}, expected {expected}"
+            );
+        };
+
+        assert_eq!(estimate("", None), 1.0);
+        assert_eq!(estimate("missing", None), 0.0);
+        // 50 documents contain apple
+        assert_close(estimate("apple", None), 0.5);
+        // 10 documents contain both apple and zebra
+        assert_close(estimate("apple zebra", None), 0.1);
+        // 7 documents contain zebra and are red
+        assert_close(estimate("zebra", Some("color = red")), 0.07);
+        // 66 documents are not red
+        assert_close(estimate("", Some("NOT color = red")), 0.66);
+    }
+}
@@ -0,0 +1,141 @@
+use std::collections::HashMap;
+
+use roaring::RoaringBitmap;
+use serde_json::Value;
+
+use super::distinct::DocIter;
+use crate::error::{InternalError, UserError};
+use crate::{DocumentId, FieldId, Index, Result, BEU32};
+
+/// How the documents ranked equally by all the ranking rules are ordered,
+/// see [`Search::tie_breaker`](super::Search::tie_breaker).
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum TieBreaker {
+    /// By ascending internal document id, which is free but changes when
+    /// the documents are deleted and added again.
+    #[default]
+    InternalDocidAsc,
+    /// By ascending external document id, in lexicographic order, which doesn't depend
+    /// on the order the documents were indexed in but requires to read the external id
+    /// of every tied document.
+    ExternalIdAsc,
+}
+
+/// Orders the buckets of tied documents returned by the ranking rules.
+pub(crate) enum TieBreaking<'t> {
+    InternalDocidAsc,
+    ExternalIdAsc(ExternalIds<'t>),
+}
+
+impl<'t> TieBreaking<'t> {
+    pub fn new(
+        tie_breaker: TieBreaker,
+        rtxn: &'t heed::RoTxn<'t>,
+        index: &'t Index,
+    ) -> Result<Self> {
+        match tie_breaker {
+            TieBreaker::InternalDocidAsc => Ok(TieBreaking::InternalDocidAsc),
+            TieBreaker::ExternalIdAsc => {
+                Ok(TieBreaking::ExternalIdAsc(ExternalIds::new(rtxn, index)?))
+            }
+        }
+    }
+
+    /// Orders the documents of a bucket, the documents are iterated in the order
+    /// of their internal ids, which is already the expected one for the default tie breaker.
+    pub fn break_ties<I: DocIter>(&mut self, documents: I) -> Result<TieBrokenIter<I>> {
+        match self {
+            TieBreaking::InternalDocidAsc => Ok(TieBrokenIter::Unchanged(documents)),
+            TieBreaking::ExternalIdAsc(external_ids) => {
+                let mut documents = documents;
+                let mut sorted = Vec::new();
+                for docid in documents.by_ref() {
+                    let docid = docid?;
+                    sorted.push((external_ids.get(docid)?, docid));
+                }
+                sorted.sort_unstable();
+
+                let documents_ids: Vec<_> = sorted.into_iter().map(|(_, docid)| docid).collect();
+                Ok(TieBrokenIter::Sorted {
+                    documents_ids: documents_ids.into_iter(),
+                    excluded: documents.into_excluded(),
+                })
+            }
+        }
+    }
+}
+
+/// The documents of a bucket in the order of the tie breaker.
+pub(crate) enum TieBrokenIter<I> {
+    Unchanged(I),
+    Sorted { documents_ids: std::vec::IntoIter<DocumentId>, excluded: RoaringBitmap },
+}
+
+impl<I: DocIter> Iterator for TieBrokenIter<I> {
+    type Item = Result<DocumentId>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self {
+            TieBrokenIter::Unchanged(documents) => documents.next(),
+            TieBrokenIter::Sorted { documents_ids, .. } => documents_ids.next().map(Ok),
+        }
+    }
+}
+
+impl<I: DocIter> DocIter for TieBrokenIter<I> {
+    fn into_excluded(self) -> RoaringBitmap {
+        match self {
+            TieBrokenIter::Unchanged(documents) => documents.into_excluded(),
+            TieBrokenIter::Sorted { excluded, .. } => excluded,
+        }
+    }
+}
+
+/// Retrieves the external ids of the documents.
+pub(crate) struct ExternalIds<'t> {
+    rtxn: &'t heed::RoTxn<'t>,
+    index: &'t Index,
+    /// The field id of the primary key when it is a top-level field of the documents.
+    primary_key_id: Option<FieldId>,
+    /// The external ids of all the documents, only computed for the nested primary keys.
+    inverted: Option<HashMap<DocumentId, String>>,
+}
+
+impl<'t> ExternalIds<'t> {
+    fn new(rtxn: &'t heed::RoTxn<'t>, index: &'t Index) -> Result<Self> {
+        let primary_key_id = match index.primary_key(rtxn)? {
+            Some(primary_key) => index.fields_ids_map(rtxn)?.id(primary_key),
+            None => None,
+        };
+        Ok(ExternalIds { rtxn, index, primary_key_id, inverted: None })
+    }
+
+    fn get(&mut self, docid: DocumentId) -> Result<String> {
+        if let Some(primary_key_id) = self.primary_key_id {
+            let document = self
+                .index
+                .documents
+                .get(self.rtxn, &BEU32::new(docid))?
+                .ok_or(UserError::UnknownInternalDocumentId { document_id: docid })?;
+            if let Some(value) = document.get(primary_key_id) {
+                return match serde_json::from_slice(value).map_err(InternalError::SerdeJson)? {
+                    Value::String(external_id) => Ok(external_id),
+                    value => Ok(value.to_string()),
+                };
+            }
+        }
+
+        // the nested primary keys can't be read directly from the documents.
+        if self.inverted.is_none() {
+            let external_ids = self.index.external_documents_ids(self.rtxn)?;
+            let inverted = external_ids
+                .to_hash_map()
+                .into_iter()
+                .map(|(external_id, docid)| (docid, external_id))
+                .collect();
+            self.inverted = Some(inverted);
+        }
+        let inverted = self.inverted.as_ref().unwrap();
+        Ok(inverted.get(&docid).cloned().unwrap_or_default())
+    }
+}
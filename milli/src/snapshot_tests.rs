@@ -1,12 +1,22 @@
 use std::borrow::Cow;
+use std::collections::BTreeMap;
 use std::fmt::Write;
 use std::path::Path;
 
+use heed::types::ByteSlice;
+use heed::BytesDecode;
+pub use regex::Regex;
 use roaring::RoaringBitmap;
 
 use crate::facet::FacetType;
-use crate::heed_codec::facet::{FacetGroupKey, FacetGroupValue};
-use crate::{make_db_snap_from_iter, ExternalDocumentsIds, Index};
+use crate::heed_codec::facet::{decode_key, FacetGroupValue, FacetGroupValueCodec, FacetKey};
+use crate::{
+    make_db_snap_from_iter, DocumentId, ExternalDocumentsIds, FieldId, FieldsIdsMap, Index, BEU16,
+    BEU32, BEU64,
+};
+
+/// The maximum number of characters of a document value displayed in [`snap_documents`].
+const MAX_DISPLAYED_VALUE_LEN: usize = 32;
 
 #[track_caller]
 pub fn default_db_snapshot_settings_for_test(name: Option<&str>) -> (insta::Settings, String) {
@@ -97,6 +107,13 @@ Create a snapshot test of the given database.
     - `word_prefix_position_docids`
     - `facet_id_f64_docids`
     - `facet_id_string_docids`
+    - `facet_id_exists_docids`
+    - `facet_id_bool_docids`
+    - `facet_id_string_originals`
+    - `field_id_docid_facet_f64s`
+    - `field_id_docid_facet_strings`
+    - `documents`
+    - `documents_indexed_at`
     - `documents_ids`
     - `stop_words`
     - `soft_deleted_documents_ids`
@@ -108,6 +125,7 @@ Create a snapshot test of the given database.
     - `string_faceted_documents_ids`
     - `words_fst`
     - `words_prefixes_fst`
+    - `main`
 
 3. The identifier for the snapshot test (optional)
 4. `@""` to write the snapshot inline (optional)
@@ -198,116 +216,153 @@ macro_rules! db_snap {
 }
 
 pub fn snap_word_docids(index: &Index) -> String {
-    let snap = make_db_snap_from_iter!(index, word_docids, |(s, b)| {
-        &format!("{s:<16} {}", display_bitmap(&b))
-    });
+    let snap =
+        make_db_snap_from_iter!(index, word_docids, |entry| { &display_word_docids_entry(entry) });
     snap
 }
 pub fn snap_exact_word_docids(index: &Index) -> String {
-    let snap = make_db_snap_from_iter!(index, exact_word_docids, |(s, b)| {
-        &format!("{s:<16} {}", display_bitmap(&b))
+    let snap = make_db_snap_from_iter!(index, exact_word_docids, |entry| {
+        &display_word_docids_entry(entry)
     });
     snap
 }
 pub fn snap_word_prefix_docids(index: &Index) -> String {
-    let snap = make_db_snap_from_iter!(index, word_prefix_docids, |(s, b)| {
-        &format!("{s:<16} {}", display_bitmap(&b))
+    let snap = make_db_snap_from_iter!(index, word_prefix_docids, |entry| {
+        &display_word_docids_entry(entry)
     });
     snap
 }
 pub fn snap_exact_word_prefix_docids(index: &Index) -> String {
-    let snap = make_db_snap_from_iter!(index, exact_word_prefix_docids, |(s, b)| {
-        &format!("{s:<16} {}", display_bitmap(&b))
+    let snap = make_db_snap_from_iter!(index, exact_word_prefix_docids, |entry| {
+        &display_word_docids_entry(entry)
     });
     snap
 }
 pub fn snap_docid_word_positions(index: &Index) -> String {
-    let snap = make_db_snap_from_iter!(index, docid_word_positions, |((idx, s), b)| {
-        &format!("{idx:<6} {s:<16} {}", display_bitmap(&b))
+    let snap = make_db_snap_from_iter!(index, docid_word_positions, |entry| {
+        &display_docid_word_positions_entry(entry)
     });
     snap
 }
 pub fn snap_word_pair_proximity_docids(index: &Index) -> String {
-    let snap = make_db_snap_from_iter!(index, word_pair_proximity_docids, |(
-        (proximity, word1, word2),
-        b,
-    )| {
-        &format!("{proximity:<2} {word1:<16} {word2:<16} {}", display_bitmap(&b))
+    let snap = make_db_snap_from_iter!(index, word_pair_proximity_docids, |entry| {
+        &display_word_pair_proximity_docids_entry(entry)
     });
     snap
 }
 pub fn snap_word_prefix_pair_proximity_docids(index: &Index) -> String {
-    let snap = make_db_snap_from_iter!(index, word_prefix_pair_proximity_docids, |(
-        (proximity, word1, prefix),
-        b,
-    )| {
-        &format!("{proximity:<2} {word1:<16} {prefix:<4} {}", display_bitmap(&b))
+    let snap = make_db_snap_from_iter!(index, word_prefix_pair_proximity_docids, |entry| {
+        &display_word_prefix_pair_proximity_docids_entry(entry)
     });
     snap
 }
 pub fn snap_prefix_word_pair_proximity_docids(index: &Index) -> String {
-    let snap = make_db_snap_from_iter!(index, prefix_word_pair_proximity_docids, |(
-        (proximity, prefix, word2),
-        b,
-    )| {
-        &format!("{proximity:<2} {prefix:<4} {word2:<16} {}", display_bitmap(&b))
+    let snap = make_db_snap_from_iter!(index, prefix_word_pair_proximity_docids, |entry| {
+        &display_prefix_word_pair_proximity_docids_entry(entry)
     });
     snap
 }
 pub fn snap_word_position_docids(index: &Index) -> String {
-    let snap = make_db_snap_from_iter!(index, word_position_docids, |((word, position), b)| {
-        &format!("{word:<16} {position:<6} {}", display_bitmap(&b))
+    let snap = make_db_snap_from_iter!(index, word_position_docids, |entry| {
+        &display_word_position_docids_entry(entry)
     });
     snap
 }
 pub fn snap_field_id_word_count_docids(index: &Index) -> String {
-    let snap = make_db_snap_from_iter!(index, field_id_word_count_docids, |(
-        (field_id, word_count),
-        b,
-    )| {
-        &format!("{field_id:<3} {word_count:<6} {}", display_bitmap(&b))
+    let snap = make_db_snap_from_iter!(index, field_id_word_count_docids, |entry| {
+        &display_field_id_word_count_docids_entry(entry)
     });
     snap
 }
 pub fn snap_word_prefix_position_docids(index: &Index) -> String {
-    let snap = make_db_snap_from_iter!(index, word_prefix_position_docids, |(
-        (word_prefix, position),
-        b,
-    )| {
-        &format!("{word_prefix:<4} {position:<6} {}", display_bitmap(&b))
+    let snap = make_db_snap_from_iter!(index, word_prefix_position_docids, |entry| {
+        &display_word_prefix_position_docids_entry(entry)
     });
     snap
 }
 pub fn snap_facet_id_f64_docids(index: &Index) -> String {
-    let snap = make_db_snap_from_iter!(index, facet_id_f64_docids, |(
-        FacetGroupKey { field_id, level, left_bound },
-        FacetGroupValue { size, bitmap },
-    )| {
-        &format!("{field_id:<3} {level:<2} {left_bound:<6} {size:<2} {}", display_bitmap(&bitmap))
+    snap_facet_db(index, FacetType::Number)
+}
+pub fn snap_facet_id_exists_docids(index: &Index) -> String {
+    let snap = make_db_snap_from_iter!(index, facet_id_exists_docids, |entry| {
+        &display_facet_id_exists_docids_entry(entry)
     });
     snap
 }
-pub fn snap_facet_id_exists_docids(index: &Index) -> String {
-    let snap = make_db_snap_from_iter!(index, facet_id_exists_docids, |(facet_id, docids)| {
-        &format!("{facet_id:<3} {}", display_bitmap(&docids))
+pub fn snap_facet_id_bool_docids(index: &Index) -> String {
+    let snap = make_db_snap_from_iter!(index, facet_id_bool_docids, |entry| {
+        &display_facet_id_bool_docids_entry(entry)
+    });
+    snap
+}
+pub fn snap_facet_id_string_originals(index: &Index) -> String {
+    let snap = make_db_snap_from_iter!(index, facet_id_string_originals, |entry| {
+        &display_facet_id_string_originals_entry(entry)
     });
     snap
 }
 pub fn snap_facet_id_string_docids(index: &Index) -> String {
-    let snap = make_db_snap_from_iter!(index, facet_id_string_docids, |(
-        FacetGroupKey { field_id, level, left_bound },
-        FacetGroupValue { size, bitmap },
-    )| {
-        &format!("{field_id:<3} {level:<2} {left_bound:<12} {size:<2} {}", display_bitmap(&bitmap))
+    snap_facet_db(index, FacetType::String)
+}
+/// Snapshots a facet database, the entries that cannot be decoded are displayed with their raw
+/// bytes instead of panicking, so that the snapshot localizes the problem.
+fn snap_facet_db(index: &Index, facet_type: FacetType) -> String {
+    let rtxn = index.read_txn().unwrap();
+    let db = match facet_type {
+        FacetType::String => index.facet_id_string_docids.remap_types::<ByteSlice, ByteSlice>(),
+        FacetType::Number => index.facet_id_f64_docids.remap_types::<ByteSlice, ByteSlice>(),
+    };
+
+    let mut snap = String::new();
+    for result in db.iter(&rtxn).unwrap() {
+        let (key_bytes, value_bytes) = result.unwrap();
+        snap.push_str(&display_facet_entry(facet_type, key_bytes, value_bytes));
+        snap.push('\n');
+    }
+    snap
+}
+pub fn snap_field_id_docid_facet_f64s(index: &Index) -> String {
+    let snap = make_db_snap_from_iter!(index, field_id_docid_facet_f64s, |entry| {
+        &display_field_id_docid_facet_f64s_entry(entry)
     });
     snap
 }
 pub fn snap_field_id_docid_facet_strings(index: &Index) -> String {
-    let snap = make_db_snap_from_iter!(index, field_id_docid_facet_strings, |(
-        (field_id, doc_id, string),
-        other_string,
-    )| {
-        &format!("{field_id:<3} {doc_id:<4} {string:<12} {other_string}")
+    let snap = make_db_snap_from_iter!(index, field_id_docid_facet_strings, |entry| {
+        &display_field_id_docid_facet_strings_entry(entry)
+    });
+    snap
+}
+/// Snapshots the stored documents, ordered by external id, as their sorted `field:value`
+/// pairs. The values are displayed as JSON and truncated when they are too long.
+pub fn snap_documents(index: &Index) -> String {
+    let rtxn = index.read_txn().unwrap();
+    let fields_ids_map = index.fields_ids_map(&rtxn).unwrap();
+    let external_ids: BTreeMap<_, _> = index
+        .external_documents_ids(&rtxn)
+        .unwrap()
+        .to_hash_map()
+        .into_iter()
+        .map(|(external_id, docid)| (docid, external_id))
+        .collect();
+
+    let mut documents = BTreeMap::new();
+    for result in index.all_documents(&rtxn).unwrap() {
+        let (docid, obkv) = result.unwrap();
+        let external_id =
+            external_ids.get(&docid).cloned().unwrap_or_else(|| format!("<docid {docid}>"));
+        documents.insert(external_id, display_document(obkv, &fields_ids_map));
+    }
+
+    let mut snap = String::new();
+    for (external_id, fields) in documents {
+        writeln!(&mut snap, "{external_id:<16} {fields}").unwrap();
+    }
+    snap
+}
+pub fn snap_documents_indexed_at(index: &Index) -> String {
+    let snap = make_db_snap_from_iter!(index, documents_indexed_at, |entry| {
+        &display_documents_indexed_at_entry(entry)
     });
     snap
 }
@@ -419,7 +474,21 @@ pub fn snap_words_prefixes_fst(index: &Index) -> String {
     }
     snap
 }
+/// Snapshots the keys of the main database, e.g. to check that the primary key or
+/// the geo rtree are stored. The bytes following the name of a key are displayed in hex.
+pub fn snap_main(index: &Index) -> String {
+    let rtxn = index.read_txn().unwrap();
+    let mut snap = String::new();
+    for result in index.main.iter::<_, ByteSlice, ByteSlice>(&rtxn).unwrap() {
+        let (key, _) = result.unwrap();
+        snap.push_str(&display_main_key(key));
+        snap.push('\n');
+    }
+    snap
+}
 
+/// Snapshots the settings of the index, the unordered collections are sorted
+/// so that the snapshot doesn't depend on the order of insertion.
 pub fn snap_settings(index: &Index) -> String {
     let mut snap = String::new();
     let rtxn = index.read_txn().unwrap();
@@ -431,20 +500,53 @@ pub fn snap_settings(index: &Index) -> String {
         };
     }
 
+    macro_rules! write_sorted_setting_to_snap {
+        ($name:ident) => {
+            let mut $name: Vec<_> = index.$name(&rtxn).unwrap().into_iter().collect();
+            $name.sort_unstable();
+            writeln!(&mut snap, "{}: {:?}", stringify!($name), $name).unwrap();
+        };
+    }
+
     write_setting_to_snap!(primary_key);
     write_setting_to_snap!(criteria);
     write_setting_to_snap!(displayed_fields);
     write_setting_to_snap!(distinct_field);
-    write_setting_to_snap!(filterable_fields);
-    write_setting_to_snap!(sortable_fields);
-    write_setting_to_snap!(synonyms);
+    write_setting_to_snap!(stop_words_mode);
+    write_sorted_setting_to_snap!(filterable_fields);
+    write_sorted_setting_to_snap!(sortable_fields);
+
+    let mut synonyms: Vec<_> = index.synonyms(&rtxn).unwrap().into_iter().collect();
+    synonyms.sort_unstable();
+    writeln!(&mut snap, "synonyms:").unwrap();
+    for (words, mut expansions) in synonyms {
+        expansions.sort_unstable();
+        writeln!(&mut snap, "  {words:?}: {expansions:?}").unwrap();
+    }
+
     write_setting_to_snap!(authorize_typos);
     write_setting_to_snap!(min_word_len_one_typo);
     write_setting_to_snap!(min_word_len_two_typos);
     write_setting_to_snap!(exact_words);
-    write_setting_to_snap!(exact_attributes);
+    write_sorted_setting_to_snap!(exact_attributes);
+    write_sorted_setting_to_snap!(case_sensitive_attributes);
     write_setting_to_snap!(max_values_per_facet);
     write_setting_to_snap!(pagination_max_total_hits);
+    write_setting_to_snap!(max_word_length);
+    write_setting_to_snap!(long_words_policy);
+    write_setting_to_snap!(min_indexed_word_length);
+    write_setting_to_snap!(max_positions_per_word);
+    write_setting_to_snap!(max_fields);
+    write_setting_to_snap!(max_fields_policy);
+    write_setting_to_snap!(field_boosts);
+    write_setting_to_snap!(fields_languages);
+    write_setting_to_snap!(detected_languages_allow_list);
+    write_setting_to_snap!(stemming);
+    write_setting_to_snap!(normalization_rules);
+    write_setting_to_snap!(mixed_facet_types);
+    write_setting_to_snap!(proximity_databases);
+    write_setting_to_snap!(cross_field_proximity);
+    write_setting_to_snap!(typo_on_diacritics);
     write_setting_to_snap!(searchable_fields);
     write_setting_to_snap!(user_defined_searchable_fields);
 
@@ -501,6 +603,21 @@ macro_rules! full_snap_of_db {
     ($index:ident, facet_id_exists_docids) => {{
         $crate::snapshot_tests::snap_facet_id_exists_docids(&$index)
     }};
+    ($index:ident, facet_id_bool_docids) => {{
+        $crate::snapshot_tests::snap_facet_id_bool_docids(&$index)
+    }};
+    ($index:ident, facet_id_string_originals) => {{
+        $crate::snapshot_tests::snap_facet_id_string_originals(&$index)
+    }};
+    ($index:ident, field_id_docid_facet_f64s) => {{
+        $crate::snapshot_tests::snap_field_id_docid_facet_f64s(&$index)
+    }};
+    ($index:ident, documents) => {{
+        $crate::snapshot_tests::snap_documents(&$index)
+    }};
+    ($index:ident, documents_indexed_at) => {{
+        $crate::snapshot_tests::snap_documents_indexed_at(&$index)
+    }};
     ($index:ident, documents_ids) => {{
         $crate::snapshot_tests::snap_documents_ids(&$index)
     }};
@@ -534,6 +651,128 @@ macro_rules! full_snap_of_db {
     ($index:ident, words_prefixes_fst) => {{
         $crate::snapshot_tests::snap_words_prefixes_fst(&$index)
     }};
+    ($index:ident, main) => {{
+        $crate::snapshot_tests::snap_main(&$index)
+    }};
+}
+
+/// The snapshot functions of every database, named like the arguments of [`full_snap_of_db!`].
+const DATABASE_SNAPSHOTS: &[(&str, fn(&Index) -> String)] = &[
+    ("settings", snap_settings),
+    ("word_docids", snap_word_docids),
+    ("exact_word_docids", snap_exact_word_docids),
+    ("word_prefix_docids", snap_word_prefix_docids),
+    ("exact_word_prefix_docids", snap_exact_word_prefix_docids),
+    ("docid_word_positions", snap_docid_word_positions),
+    ("word_pair_proximity_docids", snap_word_pair_proximity_docids),
+    ("word_prefix_pair_proximity_docids", snap_word_prefix_pair_proximity_docids),
+    ("prefix_word_pair_proximity_docids", snap_prefix_word_pair_proximity_docids),
+    ("word_position_docids", snap_word_position_docids),
+    ("field_id_word_count_docids", snap_field_id_word_count_docids),
+    ("word_prefix_position_docids", snap_word_prefix_position_docids),
+    ("facet_id_f64_docids", snap_facet_id_f64_docids),
+    ("facet_id_string_docids", snap_facet_id_string_docids),
+    ("field_id_docid_facet_f64s", snap_field_id_docid_facet_f64s),
+    ("field_id_docid_facet_strings", snap_field_id_docid_facet_strings),
+    ("facet_id_exists_docids", snap_facet_id_exists_docids),
+    ("facet_id_bool_docids", snap_facet_id_bool_docids),
+    ("facet_id_string_originals", snap_facet_id_string_originals),
+    ("documents", snap_documents),
+    ("documents_indexed_at", snap_documents_indexed_at),
+    ("documents_ids", snap_documents_ids),
+    ("stop_words", snap_stop_words),
+    ("soft_deleted_documents_ids", snap_soft_deleted_documents_ids),
+    ("field_distribution", snap_field_distributions),
+    ("fields_ids_map", snap_fields_ids_map),
+    ("geo_faceted_documents_ids", snap_geo_faceted_documents_ids),
+    ("external_documents_ids", snap_external_documents_ids),
+    ("number_faceted_documents_ids", snap_number_faceted_documents_ids),
+    ("string_faceted_documents_ids", snap_string_faceted_documents_ids),
+    ("words_fst", snap_words_fst),
+    ("words_prefixes_fst", snap_words_prefixes_fst),
+    ("main", snap_main),
+];
+
+/// Snapshot every database of the index whose name matches `include` and doesn't match `exclude`.
+///
+/// The snapshots are written to `test_directory/test_file.rs/test_name/name/db_name.snap`,
+/// next to the file calling this function, and are hashed like the ones of [`db_snap!`].
+#[track_caller]
+pub fn snapshot_index(index: &Index, name: &str, include: Option<Regex>, exclude: Option<Regex>) {
+    let (mut settings, test_name) = default_db_snapshot_settings_for_test(Some(name));
+
+    // insta resolves relative snapshot paths from the file containing the assertion, which
+    // is this one, so we anchor the path to the directory of the caller instead.
+    let caller = Path::new(std::panic::Location::caller().file());
+    let current_dir = std::env::current_dir().unwrap();
+    if let Some(root) = current_dir.ancestors().find(|dir| dir.join(caller).is_file()) {
+        let filename = caller.file_name().unwrap();
+        let path = root.join(caller.parent().unwrap()).join("snapshots");
+        settings.set_snapshot_path(path.join(filename).join(test_name).join(name));
+    }
+
+    settings.bind(|| {
+        for (db_name, snap_db) in DATABASE_SNAPSHOTS {
+            if include.as_ref().map_or(false, |include| !include.is_match(db_name))
+                || exclude.as_ref().map_or(false, |exclude| exclude.is_match(db_name))
+            {
+                continue;
+            }
+            let snap = snap_db(index);
+            let snaps = convert_snap_to_hash_if_needed(db_name, &snap, false);
+            for (name, snap) in snaps {
+                insta::assert_snapshot!(name, snap);
+            }
+        }
+    });
+}
+
+/**
+Snapshot all the databases of an index, optionally filtered by regexes on their names.
+
+## Arguments
+1. The identifier for the `Index`
+2. The identifier for the snapshot test
+3. `include: "regex"` to only snapshot the databases matching the regex (optional)
+4. `exclude: "regex"` to skip the databases matching the regex (optional)
+
+## Example
+```ignore
+// snapshot every database but the fsts
+snapshot_index!(index, "initial", exclude: "_fst$");
+// snapshot the word databases, except the prefix ones
+snapshot_index!(index, "initial", include: "^word_", exclude: "prefix");
+```
+*/
+#[macro_export]
+macro_rules! snapshot_index {
+    ($index:expr, $name:expr) => {
+        $crate::snapshot_tests::snapshot_index(&$index, &format!("{}", $name), None, None)
+    };
+    ($index:expr, $name:expr, include: $include:expr) => {
+        $crate::snapshot_tests::snapshot_index(
+            &$index,
+            &format!("{}", $name),
+            Some($crate::snapshot_tests::Regex::new($include).unwrap()),
+            None,
+        )
+    };
+    ($index:expr, $name:expr, exclude: $exclude:expr) => {
+        $crate::snapshot_tests::snapshot_index(
+            &$index,
+            &format!("{}", $name),
+            None,
+            Some($crate::snapshot_tests::Regex::new($exclude).unwrap()),
+        )
+    };
+    ($index:expr, $name:expr, include: $include:expr, exclude: $exclude:expr) => {
+        $crate::snapshot_tests::snapshot_index(
+            &$index,
+            &format!("{}", $name),
+            Some($crate::snapshot_tests::Regex::new($include).unwrap()),
+            Some($crate::snapshot_tests::Regex::new($exclude).unwrap()),
+        )
+    };
 }
 
 pub fn convert_snap_to_hash_if_needed<'snap>(
@@ -575,6 +814,136 @@ macro_rules! make_db_snap_from_iter {
     }};
 }
 
+// The formatting of the entries of the databases, shared by their snapshots
+// and by the reports of the `index_diff`.
+
+pub(crate) fn display_word_docids_entry((word, docids): (&str, RoaringBitmap)) -> String {
+    format!("{word:<16} {}", display_bitmap(&docids))
+}
+pub(crate) fn display_docid_word_positions_entry(
+    ((docid, word), positions): ((u32, &str), RoaringBitmap),
+) -> String {
+    format!("{docid:<6} {word:<16} {}", display_bitmap(&positions))
+}
+pub(crate) fn display_word_pair_proximity_docids_entry(
+    ((proximity, word1, word2), docids): ((u8, &str, &str), RoaringBitmap),
+) -> String {
+    format!("{proximity:<2} {word1:<16} {word2:<16} {}", display_bitmap(&docids))
+}
+pub(crate) fn display_word_prefix_pair_proximity_docids_entry(
+    ((proximity, word1, prefix), docids): ((u8, &str, &str), RoaringBitmap),
+) -> String {
+    format!("{proximity:<2} {word1:<16} {prefix:<4} {}", display_bitmap(&docids))
+}
+pub(crate) fn display_prefix_word_pair_proximity_docids_entry(
+    ((proximity, prefix, word2), docids): ((u8, &str, &str), RoaringBitmap),
+) -> String {
+    format!("{proximity:<2} {prefix:<4} {word2:<16} {}", display_bitmap(&docids))
+}
+pub(crate) fn display_word_position_docids_entry(
+    ((word, position), docids): ((&str, u32), RoaringBitmap),
+) -> String {
+    format!("{word:<16} {position:<6} {}", display_bitmap(&docids))
+}
+pub(crate) fn display_field_id_word_count_docids_entry(
+    ((field_id, word_count), docids): ((FieldId, u8), RoaringBitmap),
+) -> String {
+    format!("{field_id:<3} {word_count:<6} {}", display_bitmap(&docids))
+}
+pub(crate) fn display_word_prefix_position_docids_entry(
+    ((word_prefix, position), docids): ((&str, u32), RoaringBitmap),
+) -> String {
+    format!("{word_prefix:<4} {position:<6} {}", display_bitmap(&docids))
+}
+pub(crate) fn display_facet_id_exists_docids_entry(
+    (facet_id, docids): (BEU16, RoaringBitmap),
+) -> String {
+    format!("{facet_id:<3} {}", display_bitmap(&docids))
+}
+pub(crate) fn display_facet_id_bool_docids_entry(
+    ((facet_id, value), docids): ((FieldId, bool), RoaringBitmap),
+) -> String {
+    format!("{facet_id:<3} {value:<5} {}", display_bitmap(&docids))
+}
+pub(crate) fn display_facet_id_string_originals_entry(
+    ((facet_id, normalized), spellings): ((FieldId, &str), BTreeMap<String, u64>),
+) -> String {
+    format!("{facet_id:<3} {normalized:<12} {spellings:?}")
+}
+pub(crate) fn display_field_id_docid_facet_f64s_entry(
+    ((field_id, docid, value), _): ((FieldId, DocumentId, f64), ()),
+) -> String {
+    format!("{field_id:<3} {docid:<4} {value:<6}")
+}
+pub(crate) fn display_field_id_docid_facet_strings_entry(
+    ((field_id, docid, string), original): ((FieldId, DocumentId, &str), &str),
+) -> String {
+    format!("{field_id:<3} {docid:<4} {string:<12} {original}")
+}
+pub(crate) fn display_documents_indexed_at_entry((docid, sequence): (BEU32, BEU64)) -> String {
+    format!("{:<4} {}", docid.get(), sequence.get())
+}
+/// Displays an entry of a facet database, the entries that cannot be decoded are
+/// displayed with their raw bytes.
+pub(crate) fn display_facet_entry(facet_type: FacetType, key: &[u8], value: &[u8]) -> String {
+    match (decode_key(facet_type, key), FacetGroupValueCodec::bytes_decode(value)) {
+        (Some(facet_key), Some(FacetGroupValue { size, bitmap })) => {
+            let field_id = facet_key.field_id();
+            let level = facet_key.level();
+            let left_bound = match facet_key {
+                FacetKey::String(facet_key) => format!("{:<12}", facet_key.left_bound),
+                FacetKey::Number(facet_key) => format!("{:<6}", facet_key.left_bound),
+            };
+            format!("{field_id:<3} {level:<2} {left_bound} {size:<2} {}", display_bitmap(&bitmap))
+        }
+        (None, _) => format!("<<undecodable key: {}>>", display_bytes(key)),
+        (Some(_), None) => {
+            format!("<<undecodable value of key {}: {}>>", display_bytes(key), display_bytes(value))
+        }
+    }
+}
+/// Displays a stored document as its `field:value` pairs sorted by field name, the values
+/// are displayed as JSON and truncated when they are too long.
+pub(crate) fn display_document(obkv: obkv::KvReaderU16, fields_ids_map: &FieldsIdsMap) -> String {
+    let mut fields: Vec<_> = obkv
+        .iter()
+        .map(|(field_id, value)| {
+            let name = fields_ids_map.name(field_id).unwrap();
+            let value: serde_json::Value = serde_json::from_slice(value).unwrap();
+            let mut value = value.to_string();
+            if let Some((end, _)) = value.char_indices().nth(MAX_DISPLAYED_VALUE_LEN) {
+                value.truncate(end);
+                value.push_str("...");
+            }
+            (name, value)
+        })
+        .collect();
+    fields.sort_unstable();
+
+    let fields: Vec<_> =
+        fields.into_iter().map(|(name, value)| format!("{name}:{value}")).collect();
+    fields.join(" ")
+}
+/// Displays a key of the main database, the bytes following its name are displayed in hex.
+pub(crate) fn display_main_key(key: &[u8]) -> String {
+    let name_len = key.iter().position(|b| !b.is_ascii_graphic()).unwrap_or(key.len());
+    let (name, suffix) = key.split_at(name_len);
+    let name = String::from_utf8_lossy(name);
+    if suffix.is_empty() {
+        name.into_owned()
+    } else {
+        format!("{name} {}", display_bytes(suffix))
+    }
+}
+
+pub fn display_bytes(bytes: &[u8]) -> String {
+    let mut s = String::new();
+    for byte in bytes {
+        write!(&mut s, "{byte:02x}").unwrap();
+    }
+    s
+}
+
 pub fn display_bitmap(b: &RoaringBitmap) -> String {
     let mut s = String::new();
     s.push('[');
@@ -584,3 +953,108 @@ pub fn display_bitmap(b: &RoaringBitmap) -> String {
     s.push(']');
     s
 }
+
+#[cfg(test)]
+mod tests {
+    use big_s::S;
+    use heed::types::ByteSlice;
+    use maplit::{hashmap, hashset};
+    use serde_json::json;
+
+    use crate::test_utils::TempIndex;
+    use crate::{db_snap, snapshot_index};
+
+    #[test]
+    fn undecodable_facet_entries() {
+        let index = TempIndex::new();
+        index.apply_settings(|settings| settings.set_filterable_fields(hashset! { S("genre") }));
+        index.add_json_documents(json!([{ "id": 0, "genre": "drama" }]));
+
+        let mut wtxn = index.write_txn().unwrap();
+        let db = index.facet_id_string_docids.remap_types::<ByteSlice, ByteSlice>();
+        // a key too short to contain a level
+        db.put(&mut wtxn, &[0, 1], &[1, 0, 0, 0, 0]).unwrap();
+        // a left bound that is not valid UTF-8
+        db.put(&mut wtxn, &[0, 1, 0, 0xff], &[1, 0, 0, 0, 0]).unwrap();
+        // a value with an invalid bitmap
+        db.put(&mut wtxn, &[0, 1, 0, b'a'], &[1, 0xff]).unwrap();
+        wtxn.commit().unwrap();
+
+        db_snap!(index, facet_id_string_docids, @r###"
+        <<undecodable key: 0001>>
+        <<undecodable value of key 00010061: 01ff>>
+        1   0  drama        1  [0, ]
+        <<undecodable key: 000100ff>>
+        "###);
+    }
+
+    #[test]
+    fn snap_documents() {
+        let index = TempIndex::new();
+        index.add_json_documents(json!([
+            { "id": "b", "name": "bob", "tags": ["a", "b"] },
+            {
+                "id": "a",
+                "name": "kevin",
+                "bio": "a very long description that goes beyond the limit",
+            },
+        ]));
+
+        db_snap!(index, documents, @r###"
+        a                bio:"a very long description that go... id:"a" name:"kevin"
+        b                id:"b" name:"bob" tags:["a","b"]
+        "###);
+    }
+
+    #[test]
+    fn snap_settings_is_sorted() {
+        let first = TempIndex::new();
+        first
+            .update_settings(|settings| {
+                settings.set_filterable_fields(hashset! { S("a"), S("b"), S("c") });
+                settings.set_sortable_fields(hashset! { S("z"), S("y") });
+                settings.set_exact_attributes(hashset! { S("title"), S("body") });
+                settings.set_synonyms(hashmap! {
+                    S("nyc") => vec![S("new york"), S("big apple")],
+                    S("sf") => vec![S("san francisco")],
+                });
+            })
+            .unwrap();
+
+        let second = TempIndex::new();
+        second
+            .update_settings(|settings| {
+                settings.set_synonyms(hashmap! {
+                    S("sf") => vec![S("san francisco")],
+                    S("nyc") => vec![S("big apple"), S("new york")],
+                });
+                settings.set_exact_attributes(hashset! { S("body"), S("title") });
+                settings.set_sortable_fields(hashset! { S("y"), S("z") });
+                settings.set_filterable_fields(hashset! { S("c"), S("b"), S("a") });
+            })
+            .unwrap();
+
+        let snap = super::snap_settings(&first);
+        assert_eq!(snap, super::snap_settings(&second));
+        assert!(snap.contains(r#"filterable_fields: ["a", "b", "c"]"#));
+        assert!(snap.contains(
+            "synonyms:\n  [\"nyc\"]: [[\"big\", \"apple\"], [\"new\", \"york\"]]\n  [\"sf\"]"
+        ));
+    }
+
+    #[test]
+    fn snapshot_index_include_exclude() {
+        let index = TempIndex::new();
+        index
+            .add_documents(documents!([
+                { "id": 0, "name": "kevin" },
+                { "id": 1, "name": "bob" },
+            ]))
+            .unwrap();
+
+        // only documents_ids
+        snapshot_index!(index, "include", include: "^documents_ids$");
+        // field_distribution and fields_ids_map, but not the field_id_*_docid* databases
+        snapshot_index!(index, "include_exclude", include: "^field", exclude: "_docid");
+    }
+}
@@ -0,0 +1,78 @@
+use charabia::{Language, Script};
+use rust_stemmers::{Algorithm, Stemmer};
+
+use crate::script_language_from_code;
+
+/// Returns the stemming algorithm of the language, `None` if it can't be stemmed.
+fn stemming_algorithm(language: Language) -> Option<Algorithm> {
+    let algorithm = match language {
+        Language::Ara => Algorithm::Arabic,
+        Language::Dan => Algorithm::Danish,
+        Language::Deu => Algorithm::German,
+        Language::Ell => Algorithm::Greek,
+        Language::Eng => Algorithm::English,
+        Language::Fra => Algorithm::French,
+        Language::Ita => Algorithm::Italian,
+        Language::Nld => Algorithm::Dutch,
+        Language::Por => Algorithm::Portuguese,
+        Language::Rus => Algorithm::Russian,
+        Language::Spa => Algorithm::Spanish,
+        Language::Swe => Algorithm::Swedish,
+        Language::Tur => Algorithm::Turkish,
+        _ => return None,
+    };
+    Some(algorithm)
+}
+
+/// Returns whether the language of this ISO 639-3 code can be stemmed.
+pub(crate) fn is_stemmable_code(code: &str) -> bool {
+    script_language_from_code(code).map_or(false, |(_, l)| stemming_algorithm(l).is_some())
+}
+
+/// The stemmers of the languages the stemming is enabled for.
+///
+/// A word is stemmed in the language detected for it when it is enabled. The detection
+/// of the short texts being unreliable, a word of a script only one of the enabled
+/// languages is written in is otherwise stemmed in this language.
+#[derive(Default)]
+pub(crate) struct Stemmers {
+    stemmers: Vec<(Script, Language, Stemmer)>,
+}
+
+impl Stemmers {
+    /// Creates the stemmers of the given ISO 639-3 codes, ignoring the ones that can't be stemmed.
+    pub fn from_codes<'a>(codes: impl IntoIterator<Item = &'a str>) -> Stemmers {
+        let stemmers = codes
+            .into_iter()
+            .filter_map(script_language_from_code)
+            .filter_map(|(script, language)| {
+                let algorithm = stemming_algorithm(language)?;
+                Some((script, language, Stemmer::create(algorithm)))
+            })
+            .collect();
+        Stemmers { stemmers }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.stemmers.is_empty()
+    }
+
+    /// Returns the stem of the word, that can be the word itself, or `None`
+    /// when none of the stemmers applies to it.
+    pub fn stem(&self, word: &str, script: Script, language: Option<Language>) -> Option<String> {
+        let detected = self.stemmers.iter().find(|(_, l, _)| Some(*l) == language);
+        let stemmer = match detected {
+            Some((_, _, stemmer)) => stemmer,
+            None => {
+                let mut same_script = self.stemmers.iter().filter(|(s, _, _)| *s == script);
+                match (same_script.next(), same_script.next()) {
+                    (Some((_, _, stemmer)), None) => stemmer,
+                    _ => return None,
+                }
+            }
+        };
+
+        let stem = stemmer.stem(word);
+        (!stem.is_empty()).then(|| stem.into_owned())
+    }
+}
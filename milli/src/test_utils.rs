@@ -0,0 +1,157 @@
+//! Helpers to write the tests of the crates using milli, enabled by the `test-utils` feature.
+//!
+//! The [`TempIndex`] is the index the tests of milli itself are written with, it also enables
+//! the `snapshot-tests` feature, so that the databases can be snapshotted with the
+//! [`db_snap!`](crate::db_snap) and [`snapshot_index!`](crate::snapshot_index) macros.
+//!
+//! ```ignore
+//! use milli::test_utils::TempIndex;
+//! use serde_json::json;
+//!
+//! let index = TempIndex::new();
+//! index.apply_settings(|settings| settings.set_searchable_fields(vec!["title".to_string()]));
+//! index.add_json_documents(json!([{ "id": 1, "title": "hello" }]));
+//! milli::db_snap!(index, documents_ids, @"[0, ]");
+//! ```
+
+use std::ops::Deref;
+
+use heed::{EnvOpenOptions, RwTxn};
+use tempfile::TempDir;
+
+use crate::documents::{
+    documents_batch_reader_from_objects, objects_from_json_value, DocumentsBatchReader,
+};
+use crate::update::{
+    self, DeleteDocuments, IndexDocuments, IndexDocumentsConfig, IndexerConfig, Settings,
+};
+use crate::Index;
+
+/// An index stored in a temporary directory, removed when the index is dropped.
+///
+/// The documents are added and the settings are updated with the [`indexer_config`] and the
+/// [`index_documents_config`] of the index, which can be changed between the updates.
+///
+/// [`indexer_config`]: TempIndex::indexer_config
+/// [`index_documents_config`]: TempIndex::index_documents_config
+pub struct TempIndex {
+    pub inner: Index,
+    pub indexer_config: IndexerConfig,
+    pub index_documents_config: IndexDocumentsConfig,
+    _tempdir: TempDir,
+}
+
+impl Deref for TempIndex {
+    type Target = Index;
+
+    fn deref(&self) -> &Self::Target {
+        &self.inner
+    }
+}
+
+impl TempIndex {
+    /// Creates a temporary index
+    pub fn new_with_map_size(size: usize) -> Self {
+        let mut options = EnvOpenOptions::new();
+        options.map_size(size);
+        let _tempdir = TempDir::new().unwrap();
+        let inner = Index::new(options, _tempdir.path()).unwrap();
+        let indexer_config = IndexerConfig::default();
+        let index_documents_config = IndexDocumentsConfig::default();
+        Self { inner, indexer_config, index_documents_config, _tempdir }
+    }
+    /// Creates a temporary index, with a default `4096 * 1000` size. This should be enough for
+    /// most tests.
+    pub fn new() -> Self {
+        Self::new_with_map_size(4096 * 1000)
+    }
+    pub fn add_documents_using_wtxn<'t, R>(
+        &'t self,
+        wtxn: &mut RwTxn<'t, '_>,
+        documents: DocumentsBatchReader<R>,
+    ) -> Result<(), crate::error::Error>
+    where
+        R: std::io::Read + std::io::Seek,
+    {
+        let builder = IndexDocuments::new(
+            wtxn,
+            self,
+            &self.indexer_config,
+            self.index_documents_config.clone(),
+            |_| (),
+            || false,
+        )?;
+        let (builder, user_error) = builder.add_documents(documents)?;
+        user_error?;
+        builder.execute()?;
+        Ok(())
+    }
+    pub fn add_documents<R>(
+        &self,
+        documents: DocumentsBatchReader<R>,
+    ) -> Result<(), crate::error::Error>
+    where
+        R: std::io::Read + std::io::Seek,
+    {
+        let mut wtxn = self.write_txn().unwrap();
+        self.add_documents_using_wtxn(&mut wtxn, documents)?;
+        wtxn.commit().unwrap();
+        Ok(())
+    }
+
+    /// Adds the documents, given as a JSON object or an array of JSON objects, and panics
+    /// with the error if they can't be added.
+    #[track_caller]
+    pub fn add_json_documents(&self, documents: serde_json::Value) {
+        let documents = documents_batch_reader_from_objects(objects_from_json_value(documents));
+        if let Err(error) = self.add_documents(documents) {
+            panic!("the documents couldn't be added to the temporary index: {error}");
+        }
+    }
+
+    pub fn update_settings(
+        &self,
+        update: impl Fn(&mut Settings),
+    ) -> Result<(), crate::error::Error> {
+        let mut wtxn = self.write_txn().unwrap();
+        self.update_settings_using_wtxn(&mut wtxn, update)?;
+        wtxn.commit().unwrap();
+        Ok(())
+    }
+    pub fn update_settings_using_wtxn<'t>(
+        &'t self,
+        wtxn: &mut RwTxn<'t, '_>,
+        update: impl Fn(&mut Settings),
+    ) -> Result<(), crate::error::Error> {
+        let mut builder = update::Settings::new(wtxn, &self.inner, &self.indexer_config);
+        update(&mut builder);
+        builder.execute(drop, || false)?;
+        Ok(())
+    }
+
+    /// Updates the settings and panics with the error if they can't be applied.
+    #[track_caller]
+    pub fn apply_settings(&self, update: impl Fn(&mut Settings)) {
+        if let Err(error) = self.update_settings(update) {
+            panic!("the settings couldn't be applied to the temporary index: {error}");
+        }
+    }
+
+    pub fn delete_document(&self, external_document_id: &str) {
+        let mut wtxn = self.write_txn().unwrap();
+
+        let mut delete = DeleteDocuments::new(&mut wtxn, &self).unwrap();
+        delete.strategy(self.index_documents_config.deletion_strategy);
+        delete.document_id_rules(self.index_documents_config.document_id_rules.clone());
+
+        delete.delete_external_id(external_document_id);
+        delete.execute().unwrap();
+        wtxn.commit().unwrap();
+    }
+}
+
+impl Default for TempIndex {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -1,4 +1,4 @@
-use std::iter::{Chain, FromIterator};
+use std::iter::Chain;
 use std::ops::RangeInclusive;
 
 use roaring::bitmap::{IntoIter, RoaringBitmap};
@@ -16,7 +16,10 @@ impl AvailableDocumentsIds {
 
         match used_docids.max() {
             Some(last_id) => {
-                let mut available = RoaringBitmap::from_iter(0..last_id);
+                // The ids freed by the hard-deleted documents are given back first, the range is
+                // inserted at once to avoid iterating over the ids of the biggest indexes.
+                let mut available = RoaringBitmap::new();
+                available.insert_range(0..last_id);
                 available -= used_docids;
 
                 let iter = match last_id.checked_add(1) {
@@ -68,6 +71,17 @@ mod tests {
         left.zip(right).take(500).for_each(|(l, r)| assert_eq!(l, r));
     }
 
+    #[test]
+    fn last_id() {
+        let mut base = RoaringBitmap::new();
+        base.insert(5);
+        base.insert(u32::max_value());
+
+        let left = AvailableDocumentsIds::from_documents_ids(&base, &RoaringBitmap::new());
+        let right = (0..u32::max_value()).filter(|&n| n != 5);
+        left.zip(right).take(500).for_each(|(l, r)| assert_eq!(l, r));
+    }
+
     #[test]
     fn soft_deleted() {
         let mut base = RoaringBitmap::new();
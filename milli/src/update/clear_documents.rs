@@ -33,9 +33,12 @@ impl<'t, 'u, 'i> ClearDocuments<'t, 'u, 'i> {
             facet_id_f64_docids,
             facet_id_string_docids,
             facet_id_exists_docids,
+            facet_id_bool_docids,
+            facet_id_string_originals,
             field_id_docid_facet_f64s,
             field_id_docid_facet_strings,
             documents,
+            documents_indexed_at,
         } = self.index;
 
         let empty_roaring = RoaringBitmap::default();
@@ -52,6 +55,7 @@ impl<'t, 'u, 'i> ClearDocuments<'t, 'u, 'i> {
         self.index.put_soft_deleted_documents_ids(self.wtxn, &empty_roaring)?;
         self.index.put_field_distribution(self.wtxn, &FieldDistribution::default())?;
         self.index.delete_geo_rtree(self.wtxn)?;
+        self.index.delete_geo_shapes_rtree(self.wtxn)?;
         self.index.delete_geo_faceted_documents_ids(self.wtxn)?;
 
         // We clean all the faceted documents ids.
@@ -84,10 +88,13 @@ impl<'t, 'u, 'i> ClearDocuments<'t, 'u, 'i> {
         word_prefix_position_docids.clear(self.wtxn)?;
         facet_id_f64_docids.clear(self.wtxn)?;
         facet_id_exists_docids.clear(self.wtxn)?;
+        facet_id_bool_docids.clear(self.wtxn)?;
         facet_id_string_docids.clear(self.wtxn)?;
+        facet_id_string_originals.clear(self.wtxn)?;
         field_id_docid_facet_f64s.clear(self.wtxn)?;
         field_id_docid_facet_strings.clear(self.wtxn)?;
         documents.clear(self.wtxn)?;
+        documents_indexed_at.clear(self.wtxn)?;
 
         Ok(number_of_documents)
     }
@@ -137,6 +144,8 @@ mod tests {
         assert!(index.word_prefix_pair_proximity_docids.is_empty(&rtxn).unwrap());
         assert!(index.facet_id_f64_docids.is_empty(&rtxn).unwrap());
         assert!(index.facet_id_string_docids.is_empty(&rtxn).unwrap());
+        assert!(index.facet_id_bool_docids.is_empty(&rtxn).unwrap());
+        assert!(index.facet_id_string_originals.is_empty(&rtxn).unwrap());
         assert!(index.field_id_docid_facet_f64s.is_empty(&rtxn).unwrap());
         assert!(index.field_id_docid_facet_strings.is_empty(&rtxn).unwrap());
         assert!(index.documents.is_empty(&rtxn).unwrap());
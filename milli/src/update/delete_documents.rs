@@ -1,5 +1,5 @@
 use std::collections::btree_map::Entry;
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeSet, HashMap, HashSet};
 
 use fst::IntoStreamer;
 use heed::types::{ByteSlice, DecodeIgnore, Str};
@@ -9,11 +9,12 @@ use serde::{Deserialize, Serialize};
 use time::OffsetDateTime;
 
 use super::facet::delete::FacetsDelete;
-use super::ClearDocuments;
-use crate::error::InternalError;
+use super::index_documents::{is_counted_in_field_distribution, recount_field_distribution};
+use super::{ClearDocuments, DocumentIdRules};
+use crate::error::{InternalError, UserError};
 use crate::facet::FacetType;
-use crate::heed_codec::facet::FieldDocIdFacetCodec;
-use crate::heed_codec::CboRoaringBitmapCodec;
+use crate::heed_codec::facet::{FacetGroupKey, FacetGroupKeyCodec, FieldDocIdFacetCodec};
+use crate::heed_codec::{ByteSliceRefCodec, CboRoaringBitmapCodec};
 use crate::{
     ExternalDocumentsIds, FieldId, FieldIdMapMissingEntry, Index, Result, RoaringBitmapCodec,
     SmallString32, BEU32,
@@ -25,6 +26,9 @@ pub struct DeleteDocuments<'t, 'u, 'i> {
     external_documents_ids: ExternalDocumentsIds<'static>,
     to_delete_docids: RoaringBitmap,
     strategy: DeletionStrategy,
+    document_id_rules: DocumentIdRules,
+    /// The first external id given that doesn't follow the rules, returned by the execution.
+    invalid_external_id: Option<UserError>,
 }
 
 /// Result of a [`DeleteDocuments`] operation.
@@ -34,6 +38,36 @@ pub struct DocumentDeletionResult {
     pub remaining_documents: u64,
 }
 
+/// Detailed result of a [`DeleteDocuments`] operation,
+/// see [`DeleteDocuments::execute_with_statistics`].
+///
+/// The words and facet values are counted as removed once all the documents containing them
+/// are deleted, whether the documents are soft or hard deleted.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DeletionResult {
+    pub deleted_documents: u64,
+    pub remaining_documents: u64,
+    /// The number of words that are no more contained by any document.
+    pub removed_words: u64,
+    /// The number of facet values, of all the fields, that are no more contained by any document.
+    pub removed_facet_values: u64,
+    /// The size used by the index before the deletion, in bytes, see [`Index::used_size`].
+    pub used_size_before: u64,
+    /// The size used by the index after the deletion, in bytes. It is only known once the
+    /// transaction is committed, see [`DeletionResult::measure_used_size_after`].
+    pub used_size_after: Option<u64>,
+}
+
+impl DeletionResult {
+    /// Measures the size used by the index after the deletion and stores it in the
+    /// `used_size_after` field, must be called once the transaction of the deletion is committed.
+    pub fn measure_used_size_after(&mut self, index: &Index) -> Result<u64> {
+        let used_size_after = index.used_size()?;
+        self.used_size_after = Some(used_size_after);
+        Ok(used_size_after)
+    }
+}
+
 /// Strategy for deleting documents.
 ///
 /// - Soft-deleted documents are simply marked as deleted without being actually removed from DB.
@@ -80,6 +114,7 @@ impl<'t, 'u, 'i> DeleteDocuments<'t, 'u, 'i> {
         wtxn: &'t mut heed::RwTxn<'i, 'u>,
         index: &'i Index,
     ) -> Result<DeleteDocuments<'t, 'u, 'i>> {
+        recount_field_distribution(wtxn, index)?;
         let external_documents_ids = index.external_documents_ids(wtxn)?.into_static();
 
         Ok(DeleteDocuments {
@@ -88,6 +123,8 @@ impl<'t, 'u, 'i> DeleteDocuments<'t, 'u, 'i> {
             external_documents_ids,
             to_delete_docids: RoaringBitmap::new(),
             strategy: Default::default(),
+            document_id_rules: DocumentIdRules::default(),
+            invalid_external_id: None,
         })
     }
 
@@ -95,6 +132,12 @@ impl<'t, 'u, 'i> DeleteDocuments<'t, 'u, 'i> {
         self.strategy = strategy;
     }
 
+    /// Sets the rules the external ids given to [`Self::delete_external_id`] must follow,
+    /// they should be the ones used to add the documents.
+    pub fn document_id_rules(&mut self, rules: DocumentIdRules) {
+        self.document_id_rules = rules;
+    }
+
     pub fn delete_document(&mut self, docid: u32) {
         self.to_delete_docids.insert(docid);
     }
@@ -103,7 +146,23 @@ impl<'t, 'u, 'i> DeleteDocuments<'t, 'u, 'i> {
         self.to_delete_docids |= docids;
     }
 
+    /// Deletes the document with this external id, returns its internal id or `None` if there
+    /// is no such document.
+    ///
+    /// An external id that doesn't follow the rules is never found, the execution of the
+    /// deletion then returns an [`UserError::InvalidDocumentId`] error.
     pub fn delete_external_id(&mut self, external_id: &str) -> Option<u32> {
+        if let Err(reason) = self.document_id_rules.validate(external_id) {
+            if self.invalid_external_id.is_none() {
+                self.invalid_external_id = Some(UserError::InvalidDocumentId {
+                    document_id: external_id.into(),
+                    document_nth: None,
+                    reason,
+                });
+            }
+            return None;
+        }
+
         let docid = self.external_documents_ids.get(external_id)?;
         self.delete_document(docid);
         Some(docid)
@@ -117,7 +176,120 @@ impl<'t, 'u, 'i> DeleteDocuments<'t, 'u, 'i> {
 
         Ok(DocumentDeletionResult { deleted_documents, remaining_documents })
     }
+
+    /// Executes the deletion like [`Self::execute`] and returns more detailed statistics.
+    ///
+    /// Computing the removed words and facet values requires to read every word and facet value
+    /// of the deleted documents, which makes a soft deletion significantly slower.
+    pub fn execute_with_statistics(self) -> Result<DeletionResult> {
+        let used_size_before = self.index.used_size()?;
+        let (removed_words, removed_facet_values) = self.count_removed_words_and_facet_values()?;
+
+        let DetailedDocumentDeletionResult {
+            deleted_documents,
+            remaining_documents,
+            soft_deletion_used: _,
+        } = self.execute_inner()?;
+
+        Ok(DeletionResult {
+            deleted_documents,
+            remaining_documents,
+            removed_words,
+            removed_facet_values,
+            used_size_before,
+            used_size_after: None,
+        })
+    }
+
+    /// Counts the words and facet values of the documents to delete that are only
+    /// contained by deleted documents, including the previously soft-deleted ones.
+    fn count_removed_words_and_facet_values(&self) -> Result<(u64, u64)> {
+        let soft_deleted_docids = self.index.soft_deleted_documents_ids(self.wtxn)?;
+        let deleted_docids = self.index.documents_ids(self.wtxn)? & &self.to_delete_docids;
+        let all_deleted_docids = &deleted_docids | &soft_deleted_docids;
+
+        let mut words = BTreeSet::new();
+        for docid in &deleted_docids {
+            for result in self.index.docid_word_positions.prefix_iter(self.wtxn, &(docid, ""))? {
+                let ((_docid, word), _positions) = result?;
+                words.insert(word.to_string());
+            }
+        }
+
+        let mut removed_words = 0;
+        for word in &words {
+            let mut docids = self.index.word_docids.get(self.wtxn, word)?.unwrap_or_default();
+            docids |= self.index.exact_word_docids.get(self.wtxn, word)?.unwrap_or_default();
+            if docids.is_subset(&all_deleted_docids) {
+                removed_words += 1;
+            }
+        }
+
+        let faceted_fields_ids = self.index.faceted_fields_ids(self.wtxn)?;
+        let mut removed_facet_values = 0;
+        for facet_type in [FacetType::Number, FacetType::String] {
+            let (docid_values_db, values_docids_db) = match facet_type {
+                FacetType::Number => (
+                    self.index.field_id_docid_facet_f64s.remap_types::<ByteSlice, DecodeIgnore>(),
+                    self.index
+                        .facet_id_f64_docids
+                        .remap_key_type::<FacetGroupKeyCodec<ByteSliceRefCodec>>(),
+                ),
+                FacetType::String => (
+                    self.index
+                        .field_id_docid_facet_strings
+                        .remap_types::<ByteSlice, DecodeIgnore>(),
+                    self.index
+                        .facet_id_string_docids
+                        .remap_key_type::<FacetGroupKeyCodec<ByteSliceRefCodec>>(),
+                ),
+            };
+
+            for &field_id in &faceted_fields_ids {
+                let mut facet_values = BTreeSet::new();
+                for docid in &deleted_docids {
+                    let mut prefix = field_id.to_be_bytes().to_vec();
+                    prefix.extend_from_slice(&docid.to_be_bytes());
+                    let iter = docid_values_db
+                        .prefix_iter(self.wtxn, &prefix)?
+                        .remap_key_type::<FieldDocIdFacetCodec<ByteSlice>>();
+                    for result in iter {
+                        let ((_, _, facet_value), _) = result?;
+                        facet_values.insert(facet_value.to_vec());
+                    }
+                }
+
+                for facet_value in &facet_values {
+                    let key =
+                        FacetGroupKey { field_id, level: 0, left_bound: facet_value.as_slice() };
+                    let is_removed = match values_docids_db.get(self.wtxn, &key)? {
+                        Some(value) => value.bitmap.is_subset(&all_deleted_docids),
+                        None => true,
+                    };
+                    if is_removed {
+                        removed_facet_values += 1;
+                    }
+                }
+            }
+        }
+
+        // The booleans are not stored by document, only their documents ids are.
+        for &field_id in &faceted_fields_ids {
+            for value in [false, true] {
+                let docids = self.index.facet_bool_docids(self.wtxn, field_id, value)?;
+                if !docids.is_disjoint(&deleted_docids) && docids.is_subset(&all_deleted_docids) {
+                    removed_facet_values += 1;
+                }
+            }
+        }
+
+        Ok((removed_words, removed_facet_values))
+    }
     pub(crate) fn execute_inner(mut self) -> Result<DetailedDocumentDeletionResult> {
+        if let Some(error) = self.invalid_external_id.take() {
+            return Err(error.into());
+        }
+
         self.index.set_updated_at(self.wtxn, &OffsetDateTime::now_utc())?;
 
         // We retrieve the current documents ids that are in the database.
@@ -164,7 +336,10 @@ impl<'t, 'u, 'i> DeleteDocuments<'t, 'u, 'i> {
                 self.index.documents.get(self.wtxn, &key)?.ok_or(
                     InternalError::DatabaseMissingEntry { db_name: "documents", key: None },
                 )?;
-            for (fid, _value) in document.iter() {
+            for (fid, value) in document.iter() {
+                if !is_counted_in_field_distribution(value) {
+                    continue;
+                }
                 let field_name =
                     fields_ids_map.name(fid).ok_or(FieldIdMapMissingEntry::FieldId {
                         field_id: fid,
@@ -191,15 +366,19 @@ impl<'t, 'u, 'i> DeleteDocuments<'t, 'u, 'i> {
         // decide for a hard or soft deletion depending on the strategy
         let soft_deletion = match self.strategy {
             DeletionStrategy::Dynamic => {
-                // decide to keep the soft deleted in the DB for now if they meet 2 criteria:
+                // decide to keep the soft deleted in the DB for now if they meet 3 criteria:
                 // 1. There is less than a fixed rate of 50% of soft-deleted to actual documents, *and*
-                // 2. Soft-deleted occupy an average of less than a fixed size on disk
+                // 2. There are enough available ids to replace all the documents, the ids of the
+                //    soft-deleted are only given back to the new documents once hard-deleted, *and*
+                // 3. Soft-deleted occupy an average of less than a fixed size on disk
 
                 let size_used = self.index.used_size()?;
                 let nb_documents = self.index.number_of_documents(self.wtxn)?;
                 let nb_soft_deleted = soft_deleted_docids.len();
+                let enough_available_ids =
+                    self.index.available_ids_count(self.wtxn)? >= nb_documents;
 
-                (nb_soft_deleted < nb_documents) && {
+                (nb_soft_deleted < nb_documents) && enough_available_ids && {
                     const SOFT_DELETED_SIZE_BYTE_THRESHOLD: u64 = 1_073_741_824; // 1GiB
 
                     // nb_documents + nb_soft_deleted !=0 because if nb_documents is 0 we short-circuit earlier, and then we moved the documents to delete
@@ -244,13 +423,17 @@ impl<'t, 'u, 'i> DeleteDocuments<'t, 'u, 'i> {
             field_id_docid_facet_f64s: _,
             field_id_docid_facet_strings: _,
             facet_id_exists_docids,
+            facet_id_bool_docids,
+            facet_id_string_originals: _,
             documents,
+            documents_indexed_at,
         } = self.index;
 
         // Retrieve the words contained in the documents.
         let mut words = Vec::new();
         for docid in &self.to_delete_docids {
             documents.delete(self.wtxn, &BEU32::new(docid))?;
+            documents_indexed_at.delete(self.wtxn, &BEU32::new(docid))?;
 
             // We iterate through the words positions of the document id, retrieve the word and delete the positions.
             // We create an iterator to be able to get the content and delete the key-value itself.
@@ -470,6 +653,19 @@ impl<'t, 'u, 'i> DeleteDocuments<'t, 'u, 'i> {
             self.index.put_geo_faceted_documents_ids(self.wtxn, &geo_faceted_doc_ids)?;
         }
 
+        if let Some(mut rtree) = self.index.geo_shapes_rtree(self.wtxn)? {
+            let shapes_to_remove: Vec<_> = rtree
+                .iter()
+                .filter(|&shape| self.to_delete_docids.contains(shape.data.0))
+                .cloned()
+                .collect();
+            shapes_to_remove.iter().for_each(|shape| {
+                rtree.remove(shape);
+            });
+
+            self.index.put_geo_shapes_rtree(self.wtxn, &rtree)?;
+        }
+
         for facet_type in [FacetType::Number, FacetType::String] {
             let mut affected_facet_values = HashMap::new();
             for field_id in self.index.faceted_fields_ids(self.wtxn)? {
@@ -505,6 +701,11 @@ impl<'t, 'u, 'i> DeleteDocuments<'t, 'u, 'i> {
             facet_id_exists_docids,
             &self.to_delete_docids,
         )?;
+        remove_docids_from_facet_id_exists_docids(
+            self.wtxn,
+            facet_id_bool_docids,
+            &self.to_delete_docids,
+        )?;
 
         self.index.put_soft_deleted_documents_ids(self.wtxn, &RoaringBitmap::new())?;
 
@@ -583,27 +784,61 @@ fn remove_docids_from_field_id_docid_facet_value(
 ) -> heed::Result<HashSet<Vec<u8>>> {
     let db = match facet_type {
         FacetType::String => {
-            index.field_id_docid_facet_strings.remap_types::<ByteSlice, DecodeIgnore>()
-        }
-        FacetType::Number => {
-            index.field_id_docid_facet_f64s.remap_types::<ByteSlice, DecodeIgnore>()
+            index.field_id_docid_facet_strings.remap_types::<ByteSlice, ByteSlice>()
         }
+        FacetType::Number => index.field_id_docid_facet_f64s.remap_types::<ByteSlice, ByteSlice>(),
     };
     let mut all_affected_facet_values = HashSet::default();
+    // The number of removed documents of every original spelling of the facet strings.
+    let mut removed_spellings: HashMap<Vec<u8>, HashMap<Vec<u8>, u64>> = HashMap::new();
     let mut iter = db
         .prefix_iter_mut(wtxn, &field_id.to_be_bytes())?
         .remap_key_type::<FieldDocIdFacetCodec<ByteSlice>>();
 
     while let Some(result) = iter.next() {
-        let ((_, docid, facet_value), _) = result?;
+        let ((_, docid, facet_value), original) = result?;
         if to_remove.contains(docid) {
             if !all_affected_facet_values.contains(facet_value) {
                 all_affected_facet_values.insert(facet_value.to_owned());
             }
+            if facet_type == FacetType::String {
+                *removed_spellings
+                    .entry(facet_value.to_owned())
+                    .or_default()
+                    .entry(original.to_owned())
+                    .or_default() += 1;
+            }
             // safety: we don't keep references from inside the LMDB database.
             unsafe { iter.del_current()? };
         }
     }
+    drop(iter);
+
+    for (normalized, removed) in removed_spellings {
+        let normalized = std::str::from_utf8(&normalized).map_err(|_| heed::Error::Decoding)?;
+        let key = (field_id, normalized);
+        let mut spellings = match index.facet_id_string_originals.get(wtxn, &key)? {
+            Some(spellings) => spellings,
+            // the spellings of the indexes created by older versions are not counted
+            None => continue,
+        };
+        for (original, count) in removed {
+            let original = String::from_utf8(original).map_err(|_| heed::Error::Decoding)?;
+            if let Entry::Occupied(mut entry) = spellings.entry(original) {
+                let remaining = entry.get().saturating_sub(count);
+                if remaining == 0 {
+                    entry.remove();
+                } else {
+                    entry.insert(remaining);
+                }
+            }
+        }
+        if spellings.is_empty() {
+            index.facet_id_string_originals.delete(wtxn, &key)?;
+        } else {
+            index.facet_id_string_originals.put(wtxn, &key, &spellings)?;
+        }
+    }
 
     Ok(all_affected_facet_values)
 }
@@ -642,7 +877,7 @@ mod tests {
 
     use super::*;
     use crate::index::tests::TempIndex;
-    use crate::{db_snap, Filter};
+    use crate::{db_snap, milli_snap, Filter};
 
     fn delete_documents<'t>(
         wtxn: &mut RwTxn<'t, '_>,
@@ -1023,6 +1258,68 @@ mod tests {
         );
     }
 
+    fn facets_and_geo_should_not_return_deleted_documents_(deletion_strategy: DeletionStrategy) {
+        let index = TempIndex::new();
+
+        let mut wtxn = index.write_txn().unwrap();
+        index
+            .update_settings_using_wtxn(&mut wtxn, |settings| {
+                settings.set_primary_key(S("id"));
+                settings.set_filterable_fields(hashset!(S("color"), S("price"), S("_geo")));
+                settings.set_sortable_fields(hashset!(S("_geo")));
+            })
+            .unwrap();
+
+        index
+            .add_documents_using_wtxn(
+                &mut wtxn,
+                documents!([
+                    { "id": "0", "color": "red",   "price": 10, "_geo": { "lat": 50.0, "lng": 3.0 } },
+                    { "id": "1", "color": "Red",   "price": 20, "_geo": { "lat": 50.0, "lng": 3.1 } },
+                    { "id": "2", "color": "blue",  "price": 10, "_geo": { "lat": 50.0, "lng": 3.2 } },
+                    { "id": "3", "color": "blue",  "price": 30, "_geo": { "lat": 48.0, "lng": 2.0 } },
+                    { "id": "4", "color": "green", "price": 20, "_geo": { "lat": 48.0, "lng": 2.1 } },
+                    { "id": "5", "color": "green", "price": 30, "_geo": { "lat": 48.0, "lng": 2.2 } }
+                ]),
+            )
+            .unwrap();
+
+        // delete half of the documents
+        delete_documents(&mut wtxn, &index, &["1", "3", "5"], deletion_strategy);
+        wtxn.commit().unwrap();
+
+        // the snapshots are the same whatever the deletion strategy is
+        let rtxn = index.read_txn().unwrap();
+        let distribution =
+            index.facets_distribution(&rtxn).facets(["color", "price"]).execute().unwrap();
+        milli_snap!(format!("{distribution:?}"), @r###"{"color": {"blue": 1, "green": 1, "red": 1}, "price": {"10": 2, "20": 1}}"###);
+
+        let price = index.fields_ids_map(&rtxn).unwrap().id("price").unwrap();
+        let range = index.facet_numeric_range(&rtxn, price, None).unwrap();
+        milli_snap!(format!("{range:?}"), @"Some((10.0, 20.0))");
+
+        let filter = Filter::from_str("_geoRadius(50.0, 3.0, 20000)").unwrap().unwrap();
+        let results = index.search(&rtxn).filter(filter).execute().unwrap();
+        milli_snap!(format!("{:?}", results.documents_ids), @"[0, 2]");
+
+        let results = index
+            .search(&rtxn)
+            .sort_criteria(vec!["_geoPoint(48.0, 2.0):asc".parse().unwrap()])
+            .execute()
+            .unwrap();
+        milli_snap!(format!("{:?}", results.documents_ids), @"[4, 0, 2]");
+
+        let filter = Filter::from_str("color = red OR price = 30").unwrap().unwrap();
+        let results = index.search(&rtxn).filter(filter).execute().unwrap();
+        milli_snap!(format!("{:?}", results.documents_ids), @"[0]");
+    }
+
+    #[test]
+    fn facets_and_geo_should_not_return_deleted_documents() {
+        facets_and_geo_should_not_return_deleted_documents_(DeletionStrategy::AlwaysHard);
+        facets_and_geo_should_not_return_deleted_documents_(DeletionStrategy::AlwaysSoft);
+    }
+
     fn get_documents_should_not_return_deleted_documents_(deletion_strategy: DeletionStrategy) {
         let index = TempIndex::new();
 
@@ -1164,4 +1461,105 @@ mod tests {
         stats_should_not_return_deleted_documents_(DeletionStrategy::AlwaysHard);
         stats_should_not_return_deleted_documents_(DeletionStrategy::AlwaysSoft);
     }
+
+    fn facet_values_count(index: &Index, rtxn: &heed::RoTxn) -> u64 {
+        let f64s = index
+            .facet_id_f64_docids
+            .remap_types::<FacetGroupKeyCodec<ByteSliceRefCodec>, DecodeIgnore>();
+        let strings = index
+            .facet_id_string_docids
+            .remap_types::<FacetGroupKeyCodec<ByteSliceRefCodec>, DecodeIgnore>();
+
+        let mut count = 0;
+        for db in [f64s, strings] {
+            for result in db.iter(rtxn).unwrap() {
+                let (key, ()) = result.unwrap();
+                if key.level == 0 {
+                    count += 1;
+                }
+            }
+        }
+        count + index.facet_id_bool_docids.len(rtxn).unwrap() as u64
+    }
+
+    fn deletion_statistics_(deletion_strategy: DeletionStrategy) -> Vec<DeletionResult> {
+        let index = TempIndex::new();
+        index
+            .update_settings(|settings| {
+                settings.set_primary_key(S("id"));
+                settings.set_searchable_fields(vec![S("title")]);
+                settings.set_filterable_fields(hashset!(S("color"), S("price"), S("sold")));
+            })
+            .unwrap();
+        index
+            .add_documents(documents!([
+                { "id": "0", "title": "apple banana", "color": "red",   "price": 10, "sold": false },
+                { "id": "1", "title": "apple cherry", "color": "blue",  "price": 20, "sold": true },
+                { "id": "2", "title": "date",         "color": "blue",  "price": 30, "sold": true },
+                { "id": "3", "title": "apple",        "color": "green", "price": 10, "sold": false },
+            ]))
+            .unwrap();
+
+        let mut results = Vec::new();
+        for external_ids in [&["1", "2"][..], &["3"][..]] {
+            let mut wtxn = index.write_txn().unwrap();
+            let words_before = index.words_fst(&wtxn).unwrap().len() as u64;
+            let facet_values_before = facet_values_count(&index, &wtxn);
+
+            let mut builder = DeleteDocuments::new(&mut wtxn, &index).unwrap();
+            builder.strategy(deletion_strategy);
+            for external_id in external_ids {
+                assert!(builder.delete_external_id(external_id).is_some());
+            }
+            let mut result = builder.execute_with_statistics().unwrap();
+            assert_eq!(result.used_size_after, None);
+
+            // the hard deletion really removes the words and facet values from the databases.
+            if deletion_strategy == DeletionStrategy::AlwaysHard {
+                let words_after = index.words_fst(&wtxn).unwrap().len() as u64;
+                let facet_values_after = facet_values_count(&index, &wtxn);
+                assert_eq!(result.removed_words, words_before - words_after);
+                assert_eq!(result.removed_facet_values, facet_values_before - facet_values_after);
+            }
+            wtxn.commit().unwrap();
+            let used_size_after = result.measure_used_size_after(&index).unwrap();
+            assert_eq!(result.used_size_after, Some(used_size_after));
+            assert_eq!(used_size_after, index.used_size().unwrap());
+            results.push(result);
+        }
+        results
+    }
+
+    #[test]
+    fn deletion_statistics() {
+        let hard = deletion_statistics_(DeletionStrategy::AlwaysHard);
+        let soft = deletion_statistics_(DeletionStrategy::AlwaysSoft);
+
+        let counts: Vec<_> = hard
+            .iter()
+            .map(|r| {
+                (
+                    r.deleted_documents,
+                    r.remaining_documents,
+                    r.removed_words,
+                    r.removed_facet_values,
+                )
+            })
+            .collect();
+        // "cherry", "date", "blue", 20, 30 and true then only "green".
+        assert_eq!(counts, vec![(2, 2, 2, 4), (1, 1, 0, 1)]);
+
+        let soft_counts: Vec<_> = soft
+            .iter()
+            .map(|r| {
+                (
+                    r.deleted_documents,
+                    r.remaining_documents,
+                    r.removed_words,
+                    r.removed_facet_values,
+                )
+            })
+            .collect();
+        assert_eq!(soft_counts, counts);
+    }
 }
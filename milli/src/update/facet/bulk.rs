@@ -68,6 +68,10 @@ impl<'i> FacetsUpdateBulk<'i> {
     }
 
     #[logging_timer::time("FacetsUpdateBulk::{}")]
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "trace", name = "FacetsUpdateBulk::execute", skip_all)
+    )]
     pub fn execute(self, wtxn: &mut heed::RwTxn) -> Result<()> {
         let Self { index, field_ids, group_size, min_level_size, facet_type, new_data } = self;
 
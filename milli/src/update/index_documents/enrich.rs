@@ -1,3 +1,4 @@
+use std::collections::{BTreeSet, HashSet};
 use std::io::{Read, Seek};
 use std::result::Result as StdResult;
 use std::{fmt, iter};
@@ -5,10 +6,12 @@ use std::{fmt, iter};
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 
-use crate::documents::{DocumentsBatchIndex, DocumentsBatchReader, EnrichedDocumentsBatchReader};
-use crate::error::{GeoError, InternalError, UserError};
+use crate::documents::{
+    DocumentsBatchCursor, DocumentsBatchIndex, DocumentsBatchReader, EnrichedDocumentsBatchReader,
+};
+use crate::error::{GeoError, InternalError, InvalidDocumentIdReason, UserError};
 use crate::update::index_documents::{obkv_to_object, writer_into_reader};
-use crate::{FieldId, Index, Object, Result};
+use crate::{FieldId, FieldIdMapMissingEntry, Index, Object, Result, MAX_FIELDS};
 
 /// The symbol used to define levels in a nested primary key.
 const PRIMARY_KEY_SPLIT_SYMBOL: char = '.';
@@ -20,7 +23,8 @@ const DEFAULT_PRIMARY_KEY: &str = "id";
 ///  - we can infer a primary key,
 ///  - all the documents id exist and are extracted,
 ///  - the validity of them but also,
-///  - the validity of the `_geo` field depending on the settings.
+///  - the validity of the `_geo` field depending on the settings, unless `lenient` is set in
+///    which case the invalid `_geo` fields are dropped later by the [`Transform`](super::Transform).
 ///
 /// # Panics
 ///
@@ -29,6 +33,8 @@ pub fn enrich_documents_batch<R: Read + Seek>(
     rtxn: &heed::RoTxn,
     index: &Index,
     autogenerate_docids: bool,
+    document_id_rules: &DocumentIdRules,
+    lenient: bool,
     reader: DocumentsBatchReader<R>,
 ) -> Result<StdResult<EnrichedDocumentsBatchReader<R>, UserError>> {
     let (mut cursor, mut documents_batch_index) = reader.into_cursor_and_fields_index();
@@ -36,84 +42,31 @@ pub fn enrich_documents_batch<R: Read + Seek>(
     let mut external_ids = tempfile::tempfile().map(grenad::Writer::new)?;
     let mut uuid_buffer = [0; uuid::fmt::Hyphenated::LENGTH];
 
-    // The primary key *field id* that has already been set for this index or the one
-    // we will guess by searching for the first key that contains "id" as a substring.
-    let primary_key = match index.primary_key(rtxn)? {
-        Some(primary_key) if primary_key.contains(PRIMARY_KEY_SPLIT_SYMBOL) => {
-            PrimaryKey::nested(primary_key)
-        }
-        Some(primary_key) => match documents_batch_index.id(primary_key) {
-            Some(id) => PrimaryKey::flat(primary_key, id),
-            None if autogenerate_docids => {
-                PrimaryKey::flat(primary_key, documents_batch_index.insert(primary_key))
-            }
-            None => {
-                return match cursor.next_document()? {
-                    Some(first_document) => Ok(Err(UserError::MissingDocumentId {
-                        primary_key: primary_key.to_string(),
-                        document: obkv_to_object(&first_document, &documents_batch_index)?,
-                    })),
-                    None => unreachable!("Called with reader.is_empty()"),
-                };
-            }
-        },
-        None => {
-            let mut guesses: Vec<(u16, &str)> = documents_batch_index
-                .iter()
-                .filter(|(_, name)| name.to_lowercase().ends_with(DEFAULT_PRIMARY_KEY))
-                .map(|(field_id, name)| (*field_id, name.as_str()))
-                .collect();
-
-            // sort the keys in a deterministic, obvious way, so that fields are always in the same order.
-            guesses.sort_by(|(_, left_name), (_, right_name)| {
-                // shortest name first
-                left_name.len().cmp(&right_name.len()).then_with(
-                    // then alphabetical order
-                    || left_name.cmp(right_name),
-                )
-            });
-
-            match guesses.as_slice() {
-                [] if autogenerate_docids => PrimaryKey::flat(
-                    DEFAULT_PRIMARY_KEY,
-                    documents_batch_index.insert(DEFAULT_PRIMARY_KEY),
-                ),
-                [] => return Ok(Err(UserError::NoPrimaryKeyCandidateFound)),
-                [(field_id, name)] => {
-                    log::info!("Primary key was not specified in index. Inferred to '{name}'");
-                    PrimaryKey::flat(name, *field_id)
-                }
-                multiple => {
-                    return Ok(Err(UserError::MultiplePrimaryKeyCandidatesFound {
-                        candidates: multiple
-                            .iter()
-                            .map(|(_, candidate)| candidate.to_string())
-                            .collect(),
-                    }));
-                }
-            }
-        }
+    let primary_key = match retrieve_or_guess_primary_key(
+        rtxn,
+        index,
+        &mut cursor,
+        &mut documents_batch_index,
+        autogenerate_docids,
+    )? {
+        Ok(primary_key) => primary_key,
+        Err(user_error) => return Ok(Err(user_error)),
     };
 
     // If the settings specifies that a _geo field must be used therefore we must check the
     // validity of it in all the documents of this batch and this is when we return `Some`.
-    let geo_field_id = match documents_batch_index.id("_geo") {
-        Some(geo_field_id)
-            if index.sortable_fields(rtxn)?.contains("_geo")
-                || index.filterable_fields(rtxn)?.contains("_geo") =>
-        {
-            Some(geo_field_id)
-        }
-        _otherwise => None,
-    };
+    // In lenient mode the invalid `_geo` fields are dropped by the transform instead.
+    let geo_field_id =
+        if lenient { None } else { geo_field_id(rtxn, index, &documents_batch_index)? };
 
     let mut count = 0;
     while let Some(document) = cursor.next_document()? {
         let document_id = match fetch_or_generate_document_id(
             &document,
             &documents_batch_index,
-            primary_key,
+            &primary_key,
             autogenerate_docids,
+            document_id_rules,
             &mut uuid_buffer,
             count,
         )? {
@@ -144,23 +97,236 @@ pub fn enrich_documents_batch<R: Read + Seek>(
     Ok(Ok(reader))
 }
 
+/// The outcome of the validation of a batch of documents, see
+/// [`IndexDocumentsConfig::dry_run`](super::IndexDocumentsConfig::dry_run).
+#[derive(Debug, Default)]
+pub struct DryRunReport {
+    /// The number of documents in the batch.
+    pub documents_count: u32,
+    /// The number of valid documents whose external id is not in the index yet.
+    pub new_documents: u32,
+    /// The errors found in the batch, in the order of the documents.
+    pub errors: Vec<DryRunError>,
+}
+
+/// An error that would have been returned when indexing the documents.
+#[derive(Debug)]
+pub struct DryRunError {
+    /// The position of the document in the batch, `None` if the error concerns the whole batch.
+    pub document_nth: Option<u32>,
+    /// The external id of the document, when it could be retrieved.
+    pub external_id: Option<String>,
+    pub error: UserError,
+}
+
+/// Runs the same validations as [`enrich_documents_batch`] and resolves the document ids
+/// against the index, but collects the errors of all the documents instead of stopping at
+/// the first one. It also checks that the new fields fit in the fields ids map of the index.
+pub fn validate_documents_batch<R: Read + Seek>(
+    rtxn: &heed::RoTxn,
+    index: &Index,
+    autogenerate_docids: bool,
+    document_id_rules: &DocumentIdRules,
+    reader: DocumentsBatchReader<R>,
+) -> Result<DryRunReport> {
+    let mut report = DryRunReport::default();
+    if reader.is_empty() {
+        return Ok(report);
+    }
+
+    let (mut cursor, mut documents_batch_index) = reader.into_cursor_and_fields_index();
+    let mut uuid_buffer = [0; uuid::fmt::Hyphenated::LENGTH];
+
+    let primary_key = match retrieve_or_guess_primary_key(
+        rtxn,
+        index,
+        &mut cursor,
+        &mut documents_batch_index,
+        autogenerate_docids,
+    )? {
+        Ok(primary_key) => primary_key,
+        Err(error) => {
+            report.errors.push(DryRunError { document_nth: None, external_id: None, error });
+            return Ok(report);
+        }
+    };
+
+    let geo_field_id = geo_field_id(rtxn, index, &documents_batch_index)?;
+    let external_documents_ids = index.external_documents_ids(rtxn)?;
+    let mut fields_ids_map = index.fields_ids_map(rtxn)?;
+    let mut batch_external_ids = HashSet::new();
+
+    let mut count = 0;
+    while let Some(document) = cursor.next_document()? {
+        let document_nth = Some(count);
+        let document_id = fetch_or_generate_document_id(
+            &document,
+            &documents_batch_index,
+            &primary_key,
+            autogenerate_docids,
+            document_id_rules,
+            &mut uuid_buffer,
+            count,
+        )?;
+        count += 1;
+
+        let document_id = match document_id {
+            Ok(document_id) => document_id,
+            Err(error) => {
+                report.errors.push(DryRunError { document_nth, external_id: None, error });
+                continue;
+            }
+        };
+        let external_id = Some(document_id.value().to_string());
+
+        if let Some(geo_value) = geo_field_id.and_then(|fid| document.get(fid)) {
+            if let Err(error) = validate_geo_from_json(&document_id, geo_value)? {
+                let error = UserError::from(error);
+                report.errors.push(DryRunError { document_nth, external_id, error });
+                continue;
+            }
+        }
+
+        // The nested objects are flattened when indexed, their flattened keys get a field id too.
+        let mut fields_fit = true;
+        for (field_id, value) in document.iter() {
+            let name =
+                documents_batch_index.name(field_id).ok_or(FieldIdMapMissingEntry::FieldId {
+                    field_id,
+                    process: "validate_documents_batch",
+                })?;
+            fields_fit &= fields_ids_map.insert(name).is_some();
+            if fields_fit && json_depth_checker::should_flatten_from_unchecked_slice(value) {
+                let value: Value =
+                    serde_json::from_slice(value).map_err(InternalError::SerdeJson)?;
+                let object: Object = iter::once((name.to_string(), value)).collect();
+                for key in flatten_serde_json::flatten(&object).keys() {
+                    fields_fit &= fields_ids_map.insert(key).is_some();
+                }
+            }
+        }
+        if !fields_fit {
+            let error = UserError::AttributeLimitReached {
+                external_id: external_id.clone(),
+                limit: MAX_FIELDS,
+            };
+            report.errors.push(DryRunError { document_nth, external_id, error });
+            continue;
+        }
+
+        let value = document_id.value();
+        if external_documents_ids.get(value).is_none()
+            && batch_external_ids.insert(value.to_string())
+        {
+            report.new_documents += 1;
+        }
+    }
+    report.documents_count = count;
+
+    Ok(report)
+}
+
+/// Returns the primary key of the index or the one guessed from the fields of the batch,
+/// or generated when the document ids are autogenerated.
+fn retrieve_or_guess_primary_key<R: Read + Seek>(
+    rtxn: &heed::RoTxn,
+    index: &Index,
+    cursor: &mut DocumentsBatchCursor<R>,
+    documents_batch_index: &mut DocumentsBatchIndex,
+    autogenerate_docids: bool,
+) -> Result<StdResult<PrimaryKey, UserError>> {
+    // The primary key *field id* that has already been set for this index or the one
+    // we will guess by searching for the first key that contains "id" as a substring.
+    match index.primary_key(rtxn)? {
+        Some(primary_key) if primary_key.contains(PRIMARY_KEY_SPLIT_SYMBOL) => {
+            Ok(Ok(PrimaryKey::nested(primary_key)))
+        }
+        Some(primary_key) => match documents_batch_index.id(primary_key) {
+            Some(id) => Ok(Ok(PrimaryKey::flat(primary_key, id))),
+            None if autogenerate_docids => {
+                Ok(Ok(PrimaryKey::flat(primary_key, documents_batch_index.insert(primary_key))))
+            }
+            None => match cursor.next_document()? {
+                Some(first_document) => Ok(Err(UserError::MissingDocumentId {
+                    primary_key: primary_key.to_string(),
+                    document: obkv_to_object(&first_document, documents_batch_index)?,
+                })),
+                None => unreachable!("Called with reader.is_empty()"),
+            },
+        },
+        None => {
+            let mut guesses: Vec<(u16, &str)> = documents_batch_index
+                .iter()
+                .filter(|(_, name)| name.to_lowercase().ends_with(DEFAULT_PRIMARY_KEY))
+                .map(|(field_id, name)| (*field_id, name.as_str()))
+                .collect();
+
+            // sort the keys in a deterministic, obvious way, so that fields are always in the same order.
+            guesses.sort_by(|(_, left_name), (_, right_name)| {
+                // shortest name first
+                left_name.len().cmp(&right_name.len()).then_with(
+                    // then alphabetical order
+                    || left_name.cmp(right_name),
+                )
+            });
+
+            match guesses.as_slice() {
+                [] if autogenerate_docids => Ok(Ok(PrimaryKey::flat(
+                    DEFAULT_PRIMARY_KEY,
+                    documents_batch_index.insert(DEFAULT_PRIMARY_KEY),
+                ))),
+                [] => Ok(Err(UserError::NoPrimaryKeyCandidateFound)),
+                [(field_id, name)] => {
+                    log::info!("Primary key was not specified in index. Inferred to '{name}'");
+                    Ok(Ok(PrimaryKey::flat(name, *field_id)))
+                }
+                multiple => Ok(Err(UserError::MultiplePrimaryKeyCandidatesFound {
+                    candidates: multiple
+                        .iter()
+                        .map(|(_, candidate)| candidate.to_string())
+                        .collect(),
+                })),
+            }
+        }
+    }
+}
+
+/// Returns the field id of the `_geo` field of the batch if it must be validated, that is
+/// when it is sortable or filterable.
+pub(crate) fn geo_field_id(
+    rtxn: &heed::RoTxn,
+    index: &Index,
+    documents_batch_index: &DocumentsBatchIndex,
+) -> Result<Option<FieldId>> {
+    match documents_batch_index.id("_geo") {
+        Some(geo_field_id)
+            if index.sortable_fields(rtxn)?.contains("_geo")
+                || index.filterable_fields(rtxn)?.contains("_geo") =>
+        {
+            Ok(Some(geo_field_id))
+        }
+        _otherwise => Ok(None),
+    }
+}
+
 /// Retrieve the document id after validating it, returning a `UserError`
 /// if the id is invalid or can't be guessed.
 fn fetch_or_generate_document_id(
     document: &obkv::KvReader<FieldId>,
     documents_batch_index: &DocumentsBatchIndex,
-    primary_key: PrimaryKey,
+    primary_key: &PrimaryKey,
     autogenerate_docids: bool,
+    document_id_rules: &DocumentIdRules,
     uuid_buffer: &mut [u8; uuid::fmt::Hyphenated::LENGTH],
     count: u32,
 ) -> Result<StdResult<DocumentId, UserError>> {
     match primary_key {
         PrimaryKey::Flat { name: primary_key, field_id: primary_key_id } => {
-            match document.get(primary_key_id) {
+            match document.get(*primary_key_id) {
                 Some(document_id_bytes) => {
                     let document_id = serde_json::from_slice(document_id_bytes)
                         .map_err(InternalError::SerdeJson)?;
-                    match validate_document_id_value(document_id)? {
+                    match validate_document_id_value(document_id, document_id_rules, Some(count))? {
                         Ok(document_id) => Ok(Ok(DocumentId::retrieved(document_id))),
                         Err(user_error) => Ok(Err(user_error)),
                     }
@@ -195,10 +361,12 @@ fn fetch_or_generate_document_id(
             }
 
             match matching_documents_ids.pop() {
-                Some(document_id) => match validate_document_id_value(document_id)? {
-                    Ok(document_id) => Ok(Ok(DocumentId::retrieved(document_id))),
-                    Err(user_error) => Ok(Err(user_error)),
-                },
+                Some(document_id) => {
+                    match validate_document_id_value(document_id, document_id_rules, Some(count))? {
+                        Ok(document_id) => Ok(Ok(DocumentId::retrieved(document_id))),
+                        Err(user_error) => Ok(Err(user_error)),
+                    }
+                }
                 None => Ok(Err(UserError::MissingDocumentId {
                     primary_key: nested.name().to_string(),
                     document: obkv_to_object(document, documents_batch_index)?,
@@ -210,19 +378,19 @@ fn fetch_or_generate_document_id(
 
 /// A type that represent the type of primary key that has been set
 /// for this index, a classic flat one or a nested one.
-#[derive(Debug, Clone, Copy)]
-enum PrimaryKey<'a> {
-    Flat { name: &'a str, field_id: FieldId },
-    Nested { name: &'a str },
+#[derive(Debug, Clone)]
+enum PrimaryKey {
+    Flat { name: String, field_id: FieldId },
+    Nested { name: String },
 }
 
-impl PrimaryKey<'_> {
+impl PrimaryKey {
     fn flat(name: &str, field_id: FieldId) -> PrimaryKey {
-        PrimaryKey::Flat { name, field_id }
+        PrimaryKey::Flat { name: name.to_string(), field_id }
     }
 
     fn nested(name: &str) -> PrimaryKey {
-        PrimaryKey::Nested { name }
+        PrimaryKey::Nested { name: name.to_string() }
     }
 
     fn name(&self) -> &str {
@@ -325,26 +493,101 @@ pub fn fetch_matching_values_in_object(
     }
 }
 
-pub fn validate_document_id(document_id: &str) -> Option<&str> {
-    if !document_id.is_empty()
-        && document_id.chars().all(|c| matches!(c, 'a'..='z' | 'A'..='Z' | '0'..='9' | '-' | '_'))
-    {
-        Some(document_id)
-    } else {
-        None
+/// The rules the external document ids must follow, see
+/// [`IndexDocumentsConfig::document_id_rules`](super::IndexDocumentsConfig::document_id_rules).
+///
+/// By default, the document ids are only composed of alphanumeric characters (a-z A-Z 0-9),
+/// hyphens (-) and underscores (_), and their length is not bounded.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DocumentIdRules {
+    pub allowed_chars: AllowedDocumentIdChars,
+    /// The maximum length, in bytes, of the document ids.
+    pub max_length: Option<usize>,
+}
+
+impl Default for DocumentIdRules {
+    fn default() -> Self {
+        DocumentIdRules {
+            allowed_chars: AllowedDocumentIdChars::AlphanumericAnd(BTreeSet::from(['-', '_'])),
+            max_length: None,
+        }
+    }
+}
+
+/// The characters allowed in the document ids, the NUL character is never allowed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AllowedDocumentIdChars {
+    /// The ASCII alphanumeric characters and the given characters.
+    AlphanumericAnd(BTreeSet<char>),
+    /// Any character that is neither a whitespace nor a control character.
+    Printable,
+}
+
+impl AllowedDocumentIdChars {
+    fn allows(&self, c: char) -> bool {
+        match self {
+            _ if c == '\0' => false,
+            AllowedDocumentIdChars::AlphanumericAnd(chars) => {
+                c.is_ascii_alphanumeric() || chars.contains(&c)
+            }
+            AllowedDocumentIdChars::Printable => !c.is_whitespace() && !c.is_control(),
+        }
+    }
+}
+
+impl DocumentIdRules {
+    /// Checks that the document id follows the rules.
+    pub fn validate(&self, document_id: &str) -> StdResult<(), InvalidDocumentIdReason> {
+        if document_id.is_empty() {
+            return Err(InvalidDocumentIdReason::Empty);
+        }
+
+        let mut invalid_chars = Vec::new();
+        for c in document_id.chars() {
+            if !self.allowed_chars.allows(c) && !invalid_chars.contains(&c) {
+                invalid_chars.push(c);
+            }
+        }
+        if !invalid_chars.is_empty() {
+            return Err(InvalidDocumentIdReason::InvalidCharacters(invalid_chars));
+        }
+
+        match self.max_length {
+            Some(max_length) if document_id.len() > max_length => {
+                Err(InvalidDocumentIdReason::TooLong { length: document_id.len(), max_length })
+            }
+            _ => Ok(()),
+        }
     }
 }
 
 /// Parses a Json encoded document id and validate it, returning a user error when it is one.
-pub fn validate_document_id_value(document_id: Value) -> Result<StdResult<String, UserError>> {
-    match document_id {
-        Value::String(string) => match validate_document_id(&string) {
-            Some(s) if s.len() == string.len() => Ok(Ok(string)),
-            Some(s) => Ok(Ok(s.to_string())),
-            None => Ok(Err(UserError::InvalidDocumentId { document_id: Value::String(string) })),
-        },
-        Value::Number(number) if number.is_i64() => Ok(Ok(number.to_string())),
-        content => Ok(Err(UserError::InvalidDocumentId { document_id: content })),
+///
+/// The `document_nth` is the position of the document in the added documents.
+pub fn validate_document_id_value(
+    document_id: Value,
+    rules: &DocumentIdRules,
+    document_nth: Option<u32>,
+) -> Result<StdResult<String, UserError>> {
+    let string = match document_id {
+        Value::String(string) => string,
+        Value::Number(number) if number.is_i64() => number.to_string(),
+        content => {
+            return Ok(Err(UserError::InvalidDocumentId {
+                document_id: content,
+                document_nth,
+                reason: InvalidDocumentIdReason::InvalidType,
+            }))
+        }
+    };
+
+    match rules.validate(&string) {
+        Ok(()) => Ok(Ok(string)),
+        Err(reason) => Ok(Err(UserError::InvalidDocumentId {
+            document_id: Value::String(string),
+            document_nth,
+            reason,
+        })),
     }
 }
 
@@ -370,12 +613,73 @@ pub fn extract_finite_float_from_value(value: Value) -> StdResult<f64, Value> {
     }
 }
 
+/// Returns the vertices of the polygon delimiting a `_geo` region from the latitudes and
+/// longitudes of its points, one after the other, or `None` if they do not describe one.
+///
+/// The supported regions are:
+///  - a `boundingBox` described by its south-west and north-east corners,
+///    e.g. `{ "boundingBox": [[45.0, 9.0], [46.0, 10.0]] }`,
+///  - a `polygon` described by at least three vertices, the last one being implicitly
+///    connected to the first one, e.g. `{ "polygon": [[0.0, 0.0], [0.0, 1.0], [1.0, 0.0]] }`.
+pub fn geo_shape_vertices(kind: &str, coordinates: &[f64]) -> Option<Vec<[f64; 2]>> {
+    if coordinates.len() % 2 != 0 {
+        return None;
+    }
+    let vertices: Vec<_> = coordinates.chunks(2).map(|c| [c[0], c[1]]).collect();
+    if vertices
+        .iter()
+        .any(|[lat, lng]| !(-90.0..=90.0).contains(lat) || !(-180.0..=180.0).contains(lng))
+    {
+        return None;
+    }
+
+    match (kind, vertices.as_slice()) {
+        ("boundingBox", [[south, west], [north, east]]) if south <= north && west <= east => {
+            Some(vec![[*south, *west], [*south, *east], [*north, *east], [*north, *west]])
+        }
+        ("polygon", vertices) if vertices.len() >= 3 => Some(vertices.to_vec()),
+        _ => None,
+    }
+}
+
+/// Checks that the `_geo` object describes a region supported by [`geo_shape_vertices`].
+fn validate_geo_shape(object: serde_json::Map<String, Value>) -> StdResult<(), Value> {
+    let value = Value::Object(object.clone());
+    let mut shapes = object.into_iter();
+    let coordinates = match (shapes.next(), shapes.next()) {
+        (Some((kind, Value::Array(vertices))), None) => vertices
+            .into_iter()
+            .map(|vertex| match vertex {
+                Value::Array(coordinates) if coordinates.len() == 2 => coordinates
+                    .into_iter()
+                    .map(extract_finite_float_from_value)
+                    .collect::<StdResult<Vec<_>, _>>()
+                    .ok(),
+                _ => None,
+            })
+            .collect::<Option<Vec<_>>>()
+            .map(|coordinates| (kind, coordinates.concat())),
+        _ => None,
+    };
+
+    match coordinates {
+        Some((kind, coordinates)) if geo_shape_vertices(&kind, &coordinates).is_some() => Ok(()),
+        _ => Err(value),
+    }
+}
+
 pub fn validate_geo_from_json(id: &DocumentId, bytes: &[u8]) -> Result<StdResult<(), GeoError>> {
     use GeoError::*;
     let debug_id = || {
         serde_json::from_slice(id.value().as_bytes()).unwrap_or_else(|_| Value::from(id.debug()))
     };
     match serde_json::from_slice(bytes).map_err(InternalError::SerdeJson)? {
+        Value::Object(object)
+            if object.contains_key("boundingBox") || object.contains_key("polygon") =>
+        {
+            Ok(validate_geo_shape(object)
+                .map_err(|value| BadShape { document_id: debug_id(), value }))
+        }
         Value::Object(mut object) => match (object.remove("lat"), object.remove("lng")) {
             (Some(lat), Some(lng)) => {
                 match (extract_finite_float_from_value(lat), extract_finite_float_from_value(lng)) {
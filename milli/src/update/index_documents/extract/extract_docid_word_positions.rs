@@ -1,30 +1,52 @@
-use std::collections::HashSet;
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::convert::TryInto;
 use std::fs::File;
-use std::{io, mem, str};
+use std::{io, iter, mem, str};
 
-use charabia::{SeparatorKind, Token, TokenKind, TokenizerBuilder};
+use charabia::{Language, Script, SeparatorKind, Token, TokenKind, TokenizerBuilder};
 use roaring::RoaringBitmap;
 use serde_json::Value;
 
 use super::helpers::{concat_u32s_array, create_sorter, sorter_into_reader, GrenadParameters};
 use crate::error::{InternalError, SerializationError};
+use crate::stemming::Stemmers;
+use crate::update::TokenizerConfig;
 use crate::{
-    absolute_from_relative_position, FieldId, Result, MAX_POSITION_PER_ATTRIBUTE, MAX_WORD_LENGTH,
+    absolute_from_relative_position, apply_normalization_rules, canonical_json_number,
+    canonicalize_numeric_word, case_sensitive_word, diacritics_word, normalize_stop_words,
+    stem_word, FieldId, LongWordsPolicy, Result, TypoOnDiacritics, MAX_POSITION_PER_ATTRIBUTE,
 };
 
 /// Extracts the word and positions where this word appear and
 /// prefixes it by the document id.
 ///
+/// When `max_positions_per_word` is defined, only the first positions of each word
+/// of a document are extracted. The words shorter than `min_word_length` characters
+/// are not extracted. The stems of the words are extracted along with them, and so are
+/// the [case-preserved forms](case_sensitive_word) of the words of the case-sensitive attributes
+/// and, unless the accents are free, their [diacritics-preserved forms](diacritics_word).
+///
 /// Returns the generated internal documents ids and a grenad reader
 /// with the list of extracted words from the given chunk of documents.
 #[logging_timer::time]
+#[cfg_attr(feature = "tracing", tracing::instrument(level = "trace", skip_all))]
 pub fn extract_docid_word_positions<R: io::Read + io::Seek>(
     obkv_documents: grenad::Reader<R>,
     indexer: GrenadParameters,
     searchable_fields: &Option<HashSet<FieldId>>,
     stop_words: Option<&fst::Set<&[u8]>>,
     max_positions_per_attributes: Option<u32>,
+    max_word_length: usize,
+    long_words_policy: LongWordsPolicy,
+    min_word_length: usize,
+    max_positions_per_word: Option<usize>,
+    documents_allow_list: &HashMap<Script, Vec<Language>>,
+    fields_allow_lists: &HashMap<FieldId, HashMap<Script, Vec<Language>>>,
+    normalization_rules: &BTreeMap<String, String>,
+    stemmers: &Stemmers,
+    case_sensitive_attributes: &HashSet<FieldId>,
+    typo_on_diacritics: TypoOnDiacritics,
+    tokenizer_config: Option<&TokenizerConfig>,
 ) -> Result<(RoaringBitmap, grenad::Reader<File>)> {
     let max_positions_per_attributes = max_positions_per_attributes
         .map_or(MAX_POSITION_PER_ATTRIBUTE, |max| max.min(MAX_POSITION_PER_ATTRIBUTE));
@@ -42,11 +64,41 @@ pub fn extract_docid_word_positions<R: io::Read + io::Seek>(
 
     let mut key_buffer = Vec::new();
     let mut field_buffer = String::new();
+    // the number of positions extracted for each word of the current document.
+    let mut word_positions_count: HashMap<String, usize> = HashMap::new();
     let mut builder = TokenizerBuilder::new();
+    if let Some(tokenizer_config) = tokenizer_config {
+        tokenizer_config.configure(&mut builder);
+    }
     if let Some(stop_words) = stop_words {
         builder.stop_words(stop_words);
     }
+    if !documents_allow_list.is_empty() {
+        builder.allow_list(documents_allow_list);
+    }
     let tokenizer = builder.build();
+    // the words normalized by the rules into a stop word are not indexed either.
+    let normalized_stop_words = normalize_stop_words(stop_words, normalization_rules)?;
+
+    // the fields with a language hint are tokenized with their own tokenizer.
+    let mut fields_builders: HashMap<_, _> = fields_allow_lists
+        .iter()
+        .map(|(field_id, allow_list)| {
+            let mut builder = TokenizerBuilder::new();
+            if let Some(tokenizer_config) = tokenizer_config {
+                tokenizer_config.configure(&mut builder);
+            }
+            if let Some(stop_words) = stop_words {
+                builder.stop_words(stop_words);
+            }
+            builder.allow_list(allow_list);
+            (*field_id, builder)
+        })
+        .collect();
+    let fields_tokenizers: HashMap<_, _> = fields_builders
+        .iter_mut()
+        .map(|(field_id, builder)| (*field_id, builder.build()))
+        .collect();
 
     let mut cursor = obkv_documents.into_cursor()?;
     while let Some((key, value)) = cursor.move_on_next()? {
@@ -59,6 +111,7 @@ pub fn extract_docid_word_positions<R: io::Read + io::Seek>(
         documents_ids.push(document_id);
         key_buffer.clear();
         key_buffer.extend_from_slice(&document_id.to_be_bytes());
+        word_positions_count.clear();
 
         for (field_id, field_bytes) in obkv.iter() {
             if searchable_fields.as_ref().map_or(true, |sf| sf.contains(&field_id)) {
@@ -66,21 +119,71 @@ pub fn extract_docid_word_positions<R: io::Read + io::Seek>(
                     serde_json::from_slice(field_bytes).map_err(InternalError::SerdeJson)?;
                 field_buffer.clear();
                 if let Some(field) = json_to_string(&value, &mut field_buffer) {
+                    let tokenizer = fields_tokenizers.get(&field_id).unwrap_or(&tokenizer);
+                    let case_sensitive = case_sensitive_attributes.contains(&field_id);
                     let tokens = process_tokens(tokenizer.tokenize(field))
                         .take_while(|(p, _)| (*p as u32) < max_positions_per_attributes);
 
                     for (index, token) in tokens {
-                        let token = token.lemma().trim();
-                        if !token.is_empty() && token.len() <= MAX_WORD_LENGTH {
-                            key_buffer.truncate(mem::size_of::<u32>());
-                            key_buffer.extend_from_slice(token.as_bytes());
-
+                        let (script, language) = (token.script, token.language);
+                        let original = &field[token.byte_start..token.byte_end];
+                        let lemma = token.lemma();
+                        let token = canonicalize_numeric_word(lemma);
+                        let token = apply_normalization_rules(&token, normalization_rules);
+                        let token = long_words_policy.apply(token.trim(), max_word_length);
+                        // the words that are too short are not indexed but keep their position.
+                        let token = token.filter(|token| {
+                            !token.is_empty()
+                                && token.chars().count() >= min_word_length
+                                && !normalized_stop_words
+                                    .as_ref()
+                                    .map_or(false, |stop_words| stop_words.contains(token))
+                        });
+                        if let Some(token) = token {
                             let position: u16 = index
                                 .try_into()
                                 .map_err(|_| SerializationError::InvalidNumberSerialization)?;
                             let position = absolute_from_relative_position(field_id, position);
-                            docid_word_positions_sorter
-                                .insert(&key_buffer, position.to_ne_bytes())?;
+
+                            // the stem and the preserved forms of the word
+                            // are indexed at the same position.
+                            let stem = stemmers
+                                .stem(token, script, language)
+                                .filter(|stem| stem.chars().count() >= min_word_length)
+                                .map(|stem| stem_word(&stem));
+                            let cased_word = case_sensitive.then(|| case_sensitive_word(original));
+                            let cased_word = cased_word
+                                .as_deref()
+                                .and_then(|word| long_words_policy.apply(word, max_word_length));
+                            let diacritics = diacritics_word(
+                                original,
+                                lemma,
+                                typo_on_diacritics,
+                                normalization_rules,
+                            );
+                            let diacritics = diacritics
+                                .as_deref()
+                                .and_then(|word| long_words_policy.apply(word, max_word_length));
+                            let words = iter::once(token)
+                                .chain(stem.as_deref())
+                                .chain(cased_word)
+                                .chain(diacritics);
+                            for word in words {
+                                if let Some(max_positions) = max_positions_per_word {
+                                    match word_positions_count.get_mut(word) {
+                                        Some(count) if *count >= max_positions => continue,
+                                        Some(count) => *count += 1,
+                                        None => {
+                                            word_positions_count.insert(word.to_string(), 1);
+                                        }
+                                    }
+                                }
+
+                                key_buffer.truncate(mem::size_of::<u32>());
+                                key_buffer.extend_from_slice(word.as_bytes());
+                                docid_word_positions_sorter
+                                    .insert(&key_buffer, position.to_ne_bytes())?;
+                            }
                         }
                     }
                 }
@@ -98,7 +201,7 @@ fn json_to_string<'a>(value: &'a Value, buffer: &'a mut String) -> Option<&'a st
         match value {
             Value::Null => false,
             Value::Bool(boolean) => write!(output, "{}", boolean).is_ok(),
-            Value::Number(number) => write!(output, "{}", number).is_ok(),
+            Value::Number(number) => write!(output, "{}", canonical_json_number(number)).is_ok(),
             Value::String(string) => write!(output, "{}", string).is_ok(),
             Value::Array(array) => {
                 let mut count = 0;
@@ -1,4 +1,4 @@
-use std::collections::{BTreeMap, HashSet};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::convert::TryInto;
 use std::fs::File;
 use std::io;
@@ -10,21 +10,29 @@ use roaring::RoaringBitmap;
 use serde_json::Value;
 
 use super::helpers::{create_sorter, keep_first, sorter_into_reader, GrenadParameters};
-use crate::error::InternalError;
+use crate::error::{InternalError, UserError};
 use crate::facet::value_encoding::f64_into_bytes;
+use crate::facet::MixedFacetTypes;
 use crate::update::index_documents::{create_writer, writer_into_reader};
-use crate::{CboRoaringBitmapCodec, DocumentId, FieldId, Result, BEU32, MAX_FACET_VALUE_LENGTH};
+use crate::{normalize_facet_string, CboRoaringBitmapCodec, DocumentId, FieldId, Result, BEU32};
 
 /// Extracts the facet values of each faceted field of each document.
 ///
 /// Returns the generated grenad reader containing the docid the fid and the orginal value as key
-/// and the normalized value as value extracted from the given chunk of documents.
+/// and the normalized value as value extracted from the given chunk of documents, along with the
+/// docids of each field id and of each field id and boolean value.
+///
+/// The numbers and strings of the fields with a mixed facet types mode are
+/// coerced or duplicated according to it, see [`MixedFacetTypes`].
 #[logging_timer::time]
+#[cfg_attr(feature = "tracing", tracing::instrument(level = "trace", skip_all))]
 pub fn extract_fid_docid_facet_values<R: io::Read + io::Seek>(
     obkv_documents: grenad::Reader<R>,
     indexer: GrenadParameters,
     faceted_fields: &HashSet<FieldId>,
-) -> Result<(grenad::Reader<File>, grenad::Reader<File>, grenad::Reader<File>)> {
+    mixed_facet_types: &HashMap<FieldId, (MixedFacetTypes, String)>,
+) -> Result<(grenad::Reader<File>, grenad::Reader<File>, grenad::Reader<File>, grenad::Reader<File>)>
+{
     let max_memory = indexer.max_memory_by_thread();
 
     let mut fid_docid_facet_numbers_sorter = create_sorter(
@@ -46,6 +54,7 @@ pub fn extract_fid_docid_facet_values<R: io::Read + io::Seek>(
     );
 
     let mut facet_exists_docids = BTreeMap::<FieldId, RoaringBitmap>::new();
+    let mut facet_bool_docids = BTreeMap::<(FieldId, bool), RoaringBitmap>::new();
 
     let mut key_buffer = Vec::new();
     let mut cursor = obkv_documents.into_cursor()?;
@@ -72,7 +81,14 @@ pub fn extract_fid_docid_facet_values<R: io::Read + io::Seek>(
                 let value =
                     serde_json::from_slice(field_bytes).map_err(InternalError::SerdeJson)?;
 
-                let (numbers, strings) = extract_facet_values(&value);
+                let (mut numbers, mut strings, booleans) = extract_facet_values(&value);
+                if let Some((mode, field)) = mixed_facet_types.get(&field_id) {
+                    apply_mixed_facet_types(*mode, field, &mut numbers, &mut strings)?;
+                }
+
+                for boolean in booleans {
+                    facet_bool_docids.entry((field_id, boolean)).or_default().insert(document);
+                }
 
                 // insert facet numbers in sorter
                 for number in numbers {
@@ -87,14 +103,8 @@ pub fn extract_fid_docid_facet_values<R: io::Read + io::Seek>(
 
                 // insert normalized and original facet string in sorter
                 for (normalized, original) in strings.into_iter().filter(|(n, _)| !n.is_empty()) {
-                    let normalised_truncated_value: String = normalized
-                        .char_indices()
-                        .take_while(|(idx, _)| idx + 4 < MAX_FACET_VALUE_LENGTH)
-                        .map(|(_, c)| c)
-                        .collect();
-
                     key_buffer.truncate(size_of::<FieldId>() + size_of::<DocumentId>());
-                    key_buffer.extend_from_slice(normalised_truncated_value.as_bytes());
+                    key_buffer.extend_from_slice(normalized.as_bytes());
                     fid_docid_facet_strings_sorter.insert(&key_buffer, original.as_bytes())?;
                 }
             }
@@ -112,36 +122,104 @@ pub fn extract_fid_docid_facet_values<R: io::Read + io::Seek>(
     }
     let facet_exists_docids_reader = writer_into_reader(facet_exists_docids_writer)?;
 
+    let mut facet_bool_docids_writer = create_writer(
+        indexer.chunk_compression_type,
+        indexer.chunk_compression_level,
+        tempfile::tempfile()?,
+    );
+    let mut key_buffer = Vec::new();
+    for ((fid, boolean), bitmap) in facet_bool_docids.into_iter() {
+        // Note: this encoding is consistent with FieldIdBoolCodec
+        key_buffer.clear();
+        key_buffer.extend_from_slice(&fid.to_be_bytes());
+        key_buffer.push(boolean as u8);
+        let bitmap_bytes = CboRoaringBitmapCodec::bytes_encode(&bitmap).unwrap();
+        facet_bool_docids_writer.insert(&key_buffer, &bitmap_bytes)?;
+    }
+    let facet_bool_docids_reader = writer_into_reader(facet_bool_docids_writer)?;
+
     Ok((
         sorter_into_reader(fid_docid_facet_numbers_sorter, indexer)?,
         sorter_into_reader(fid_docid_facet_strings_sorter, indexer)?,
         facet_exists_docids_reader,
+        facet_bool_docids_reader,
     ))
 }
 
-fn extract_facet_values(value: &Value) -> (Vec<f64>, Vec<(String, String)>) {
+/// Rejects, moves or copies the strings and numbers of a field according to its mode.
+fn apply_mixed_facet_types(
+    mode: MixedFacetTypes,
+    field: &str,
+    numbers: &mut Vec<f64>,
+    strings: &mut Vec<(String, String)>,
+) -> Result<()> {
+    match mode {
+        MixedFacetTypes::StrictNumeric => {
+            if let Some((_, original)) = strings.iter().find(|(n, _)| !n.is_empty()) {
+                return Err(UserError::InvalidNumericFacetValue {
+                    field: field.to_string(),
+                    value: original.clone(),
+                }
+                .into());
+            }
+        }
+        MixedFacetTypes::Coerce => {
+            strings.retain(|(_, original)| match MixedFacetTypes::parse_number(original) {
+                Some(number) => {
+                    numbers.push(if number == 0.0 { 0.0 } else { number });
+                    false
+                }
+                None => true,
+            });
+        }
+        MixedFacetTypes::DualIndex => {
+            let parsed: Vec<f64> = strings
+                .iter()
+                .filter_map(|(_, original)| MixedFacetTypes::parse_number(original))
+                .map(|number| if number == 0.0 { 0.0 } else { number })
+                .collect();
+            for number in numbers.iter() {
+                let original = number.to_string();
+                strings.push((normalize_facet_string(&original), original));
+            }
+            numbers.extend(parsed);
+        }
+    }
+
+    Ok(())
+}
+
+pub(crate) fn extract_facet_values(value: &Value) -> (Vec<f64>, Vec<(String, String)>, Vec<bool>) {
     fn inner_extract_facet_values(
         value: &Value,
         can_recurse: bool,
         output_numbers: &mut Vec<f64>,
         output_strings: &mut Vec<(String, String)>,
+        output_booleans: &mut Vec<bool>,
     ) {
         match value {
             Value::Null => (),
-            Value::Bool(b) => output_strings.push((b.to_string(), b.to_string())),
+            Value::Bool(b) => output_booleans.push(*b),
             Value::Number(number) => {
                 if let Some(float) = number.as_f64() {
-                    output_numbers.push(float);
+                    // `-0.0` and `0.0` are the same facet value.
+                    output_numbers.push(if float == 0.0 { 0.0 } else { float });
                 }
             }
             Value::String(original) => {
-                let normalized = original.trim().to_lowercase();
-                output_strings.push((normalized, original.clone()));
+                // the numeric strings are canonicalized like the numbers, e.g. `"1.0"` is `1`.
+                output_strings.push((normalize_facet_string(original), original.clone()));
             }
             Value::Array(values) => {
                 if can_recurse {
                     for value in values {
-                        inner_extract_facet_values(value, false, output_numbers, output_strings);
+                        inner_extract_facet_values(
+                            value,
+                            false,
+                            output_numbers,
+                            output_strings,
+                            output_booleans,
+                        );
                     }
                 }
             }
@@ -151,7 +229,14 @@ fn extract_facet_values(value: &Value) -> (Vec<f64>, Vec<(String, String)>) {
 
     let mut facet_number_values = Vec::new();
     let mut facet_string_values = Vec::new();
-    inner_extract_facet_values(value, true, &mut facet_number_values, &mut facet_string_values);
+    let mut facet_bool_values = Vec::new();
+    inner_extract_facet_values(
+        value,
+        true,
+        &mut facet_number_values,
+        &mut facet_string_values,
+        &mut facet_bool_values,
+    );
 
-    (facet_number_values, facet_string_values)
+    (facet_number_values, facet_string_values, facet_bool_values)
 }
@@ -12,12 +12,17 @@ use crate::error::SerializationError;
 use crate::index::db_name::DOCID_WORD_POSITIONS;
 use crate::{relative_from_absolute_position, DocumentId, FieldId, Result};
 
+/// The maximum number of words of a field for the document to be stored
+/// under this word count in the `field_id_word_count_docids` database.
+pub const MAX_FIELD_ID_WORD_COUNT: u8 = 10;
+
 /// Extracts the field id word count and the documents ids where
 /// this field id with this amount of words appear.
 ///
 /// Returns a grenad reader with the list of extracted field id word counts
 /// and documents ids from the given chunk of docid word positions.
 #[logging_timer::time]
+#[cfg_attr(feature = "tracing", tracing::instrument(level = "trace", skip_all))]
 pub fn extract_fid_word_count_docids<R: io::Read + io::Seek>(
     docid_word_positions: grenad::Reader<R>,
     indexer: GrenadParameters,
@@ -83,7 +88,7 @@ fn drain_document_fid_wordcount_into_sorter(
     let mut key_buffer = Vec::new();
 
     for (fid, count) in document_fid_wordcount.drain() {
-        if count <= 10 {
+        if count <= MAX_FIELD_ID_WORD_COUNT as u32 {
             key_buffer.clear();
             key_buffer.extend_from_slice(&fid.to_be_bytes());
             key_buffer.push(count as u8);
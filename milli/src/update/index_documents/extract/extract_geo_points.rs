@@ -6,13 +6,25 @@ use serde_json::Value;
 
 use super::helpers::{create_writer, writer_into_reader, GrenadParameters};
 use crate::error::GeoError;
-use crate::update::index_documents::extract_finite_float_from_value;
+use crate::update::index_documents::{extract_finite_float_from_value, geo_shape_vertices};
 use crate::{FieldId, InternalError, Result};
 
+/// The ids of the fields generated by the flattening of the `_geo` field.
+#[derive(Debug, Clone, Copy)]
+pub struct GeoFieldsIds {
+    /// The ids of the `_geo.lat` and `_geo.lng` fields of the points.
+    pub lat_lng: (FieldId, FieldId),
+    /// The id of the `_geo.boundingBox` field of the regions, if any document has one.
+    pub bounding_box: Option<FieldId>,
+    /// The id of the `_geo.polygon` field of the regions, if any document has one.
+    pub polygon: Option<FieldId>,
+}
+
 /// Extracts the geographical coordinates contained in each document under the `_geo` field.
 ///
 /// Returns the generated grenad reader containing the docid as key associated to the (latitude, longitude)
 #[logging_timer::time]
+#[cfg_attr(feature = "tracing", tracing::instrument(level = "trace", skip_all))]
 pub fn extract_geo_points<R: io::Read + io::Seek>(
     obkv_documents: grenad::Reader<R>,
     indexer: GrenadParameters,
@@ -63,3 +75,66 @@ pub fn extract_geo_points<R: io::Read + io::Seek>(
 
     writer_into_reader(writer)
 }
+
+/// Extracts the geographical regions contained in each document under the `_geo` field,
+/// see [`geo_shape_vertices`] for the supported regions.
+///
+/// Returns the generated grenad reader containing the docid as key associated to the
+/// (latitude, longitude) of the vertices of the polygon delimiting the region.
+#[logging_timer::time]
+#[cfg_attr(feature = "tracing", tracing::instrument(level = "trace", skip_all))]
+pub fn extract_geo_shapes<R: io::Read + io::Seek>(
+    obkv_documents: grenad::Reader<R>,
+    indexer: GrenadParameters,
+    primary_key_id: FieldId,
+    geo_fields_ids: GeoFieldsIds,
+) -> Result<grenad::Reader<File>> {
+    let mut writer = create_writer(
+        indexer.chunk_compression_type,
+        indexer.chunk_compression_level,
+        tempfile::tempfile()?,
+    );
+
+    let shapes_fields =
+        [("boundingBox", geo_fields_ids.bounding_box), ("polygon", geo_fields_ids.polygon)];
+
+    let mut cursor = obkv_documents.into_cursor()?;
+    while let Some((docid_bytes, value)) = cursor.move_on_next()? {
+        let obkv = obkv::KvReader::new(value);
+        let document_id = || -> Value {
+            let document_id = obkv.get(primary_key_id).unwrap();
+            serde_json::from_slice(document_id).unwrap()
+        };
+
+        for (kind, fid) in shapes_fields.iter() {
+            let shape = match fid.and_then(|fid| obkv.get(fid)) {
+                Some(shape) => shape,
+                None => continue,
+            };
+
+            // the flattening concatenated the latitudes and longitudes of the vertices.
+            let value: Value = serde_json::from_slice(shape).map_err(InternalError::SerdeJson)?;
+            let coordinates = match &value {
+                Value::Array(values) => values
+                    .iter()
+                    .cloned()
+                    .map(extract_finite_float_from_value)
+                    .collect::<std::result::Result<Vec<_>, _>>()
+                    .ok(),
+                _ => None,
+            };
+            let vertices = coordinates
+                .and_then(|coordinates| geo_shape_vertices(kind, &coordinates))
+                .ok_or_else(|| GeoError::BadShape { document_id: document_id(), value })?;
+
+            let mut bytes = Vec::with_capacity(vertices.len() * 16);
+            for [lat, lng] in &vertices {
+                bytes.extend_from_slice(&lat.to_ne_bytes());
+                bytes.extend_from_slice(&lng.to_ne_bytes());
+            }
+            writer.insert(docid_bytes, bytes)?;
+        }
+    }
+
+    writer_into_reader(writer)
+}
@@ -22,6 +22,7 @@ use crate::{relative_from_absolute_position, FieldId, Result};
 /// The first returned reader is the one for normal word_docids, and the second one is for
 /// exact_word_docids
 #[logging_timer::time]
+#[cfg_attr(feature = "tracing", tracing::instrument(level = "trace", skip_all))]
 pub fn extract_word_docids<R: io::Read + io::Seek>(
     docid_word_positions: grenad::Reader<R>,
     indexer: GrenadParameters,
@@ -1,7 +1,7 @@
 use std::cmp::Ordering;
-use std::collections::{BinaryHeap, HashMap};
+use std::collections::{BTreeMap, BinaryHeap, HashMap};
 use std::fs::File;
-use std::{cmp, io, mem, str, vec};
+use std::{cmp, io, iter, mem, str, vec};
 
 use super::helpers::{
     create_sorter, merge_cbo_roaring_bitmaps, read_u32_ne_bytes, sorter_into_reader,
@@ -9,17 +9,22 @@ use super::helpers::{
 };
 use crate::error::SerializationError;
 use crate::index::db_name::DOCID_WORD_POSITIONS;
-use crate::proximity::{positions_proximity, MAX_DISTANCE};
-use crate::{DocumentId, Result};
+use crate::proximity::{index_proximity, positions_proximity, MAX_DISTANCE};
+use crate::{relative_from_absolute_position, DocumentId, FieldId, Result};
 
 /// Extracts the best proximity between pairs of words and the documents ids where this pair appear.
 ///
 /// Returns a grenad reader with the list of extracted word pairs proximities and
 /// documents ids from the given chunk of docid word positions.
+///
+/// The proximity between the words of different fields is only computed when
+/// `cross_field_proximity` is enabled, see [`flatten_fields_positions`].
 #[logging_timer::time]
+#[cfg_attr(feature = "tracing", tracing::instrument(level = "trace", skip_all))]
 pub fn extract_word_pair_proximity_docids<R: io::Read + io::Seek>(
     docid_word_positions: grenad::Reader<R>,
     indexer: GrenadParameters,
+    cross_field_proximity: bool,
 ) -> Result<grenad::Reader<File>> {
     let max_memory = indexer.max_memory_by_thread();
 
@@ -49,6 +54,7 @@ pub fn extract_word_pair_proximity_docids<R: io::Read + io::Seek>(
             document_word_positions_into_sorter(
                 curr_document_id,
                 document_word_positions_heap,
+                cross_field_proximity,
                 &mut word_pair_proximity_docids_sorter,
             )?;
             current_document_id = Some(document_id);
@@ -70,6 +76,7 @@ pub fn extract_word_pair_proximity_docids<R: io::Read + io::Seek>(
         document_word_positions_into_sorter(
             document_id,
             document_word_positions_heap,
+            cross_field_proximity,
             &mut word_pair_proximity_docids_sorter,
         )?;
     }
@@ -84,21 +91,37 @@ pub fn extract_word_pair_proximity_docids<R: io::Read + io::Seek>(
 fn document_word_positions_into_sorter(
     document_id: DocumentId,
     mut word_positions_heap: BinaryHeap<PeekedWordPosition<vec::IntoIter<u32>>>,
+    cross_field_proximity: bool,
     word_pair_proximity_docids_sorter: &mut grenad::Sorter<MergeFn>,
 ) -> Result<()> {
+    let proximity: fn(u32, u32) -> u32 = if cross_field_proximity {
+        word_positions_heap = flatten_fields_positions(word_positions_heap);
+        index_proximity
+    } else {
+        positions_proximity
+    };
+
     let mut word_pair_proximity = HashMap::new();
     let mut ordered_peeked_word_positions = Vec::new();
     while !word_positions_heap.is_empty() {
-        while let Some(peeked_word_position) = word_positions_heap.pop() {
-            ordered_peeked_word_positions.push(peeked_word_position);
-            if ordered_peeked_word_positions.len() == 7 {
-                break;
+        // The stems and the preserved forms of the words are at the same position as the
+        // words, the window spans 7 distinct positions whatever the number of words at each.
+        let mut window_positions = 0;
+        let mut last_position = None;
+        while let Some(position) = word_positions_heap.peek().map(|p| p.position) {
+            if last_position != Some(position) {
+                if window_positions == 7 {
+                    break;
+                }
+                window_positions += 1;
+                last_position = Some(position);
             }
+            ordered_peeked_word_positions.extend(word_positions_heap.pop());
         }
 
         if let Some((head, tail)) = ordered_peeked_word_positions.split_first() {
             for PeekedWordPosition { word, position, .. } in tail {
-                let prox = positions_proximity(head.position, *position);
+                let prox = proximity(head.position, *position);
                 if prox > 0 && prox < MAX_DISTANCE {
                     word_pair_proximity
                         .entry((head.word.clone(), word.clone()))
@@ -116,7 +139,7 @@ fn document_word_positions_into_sorter(
             // Advance the head and push it in the heap.
             if let Some(mut head) = ordered_peeked_word_positions.pop() {
                 if let Some(next_position) = head.iter.next() {
-                    let prox = positions_proximity(head.position, next_position);
+                    let prox = proximity(head.position, next_position);
 
                     if prox > 0 && prox < MAX_DISTANCE {
                         word_pair_proximity
@@ -151,6 +174,58 @@ fn document_word_positions_into_sorter(
     Ok(())
 }
 
+/// Replaces the positions of the words of a document by their indexes in the concatenation
+/// of its fields, ordered by id, so that the last word of a field is next to the first word
+/// of the following field.
+fn flatten_fields_positions(
+    word_positions_heap: BinaryHeap<PeekedWordPosition<vec::IntoIter<u32>>>,
+) -> BinaryHeap<PeekedWordPosition<vec::IntoIter<u32>>> {
+    let words_positions: Vec<_> = word_positions_heap
+        .into_iter()
+        .map(|PeekedWordPosition { word, position, iter: next_positions }| {
+            let positions: Vec<_> = iter::once(position).chain(next_positions).collect();
+            (word, positions)
+        })
+        .collect();
+
+    // the fields are as long as their last position.
+    let mut fields_lengths = BTreeMap::<FieldId, u32>::new();
+    for (_, positions) in &words_positions {
+        for &position in positions {
+            let (field_id, relative) = relative_from_absolute_position(position);
+            let length = fields_lengths.entry(field_id).or_default();
+            *length = cmp::max(*length, relative as u32 + 1);
+        }
+    }
+
+    let mut offset = 0;
+    let fields_offsets: HashMap<_, _> = fields_lengths
+        .into_iter()
+        .map(|(field_id, length)| {
+            let field_offset = offset;
+            offset += length;
+            (field_id, field_offset)
+        })
+        .collect();
+
+    words_positions
+        .into_iter()
+        .filter_map(|(word, positions)| {
+            let mut positions: Vec<_> = positions
+                .into_iter()
+                .map(|position| {
+                    let (field_id, relative) = relative_from_absolute_position(position);
+                    fields_offsets[&field_id] + relative as u32
+                })
+                .collect();
+            positions.sort_unstable();
+            let mut iter = positions.into_iter();
+            let position = iter.next()?;
+            Some(PeekedWordPosition { word, position, iter })
+        })
+        .collect()
+}
+
 struct PeekedWordPosition<I> {
     word: String,
     position: u32,
@@ -7,16 +7,19 @@ use super::helpers::{
 };
 use crate::error::SerializationError;
 use crate::index::db_name::DOCID_WORD_POSITIONS;
-use crate::{DocumentId, Result};
+use crate::{bucketed_absolute_position, DocumentId, Result};
 
 /// Extracts the word positions and the documents ids where this word appear.
 ///
 /// Returns a grenad reader with the list of extracted words at positions and
-/// documents ids from the given chunk of docid word positions.
+/// documents ids from the given chunk of docid word positions. The positions
+/// are bucketed when `bucketed_positions` is set, see [`bucketed_position`](crate::bucketed_position).
 #[logging_timer::time]
+#[cfg_attr(feature = "tracing", tracing::instrument(level = "trace", skip_all))]
 pub fn extract_word_position_docids<R: io::Read + io::Seek>(
     docid_word_positions: grenad::Reader<R>,
     indexer: GrenadParameters,
+    bucketed_positions: bool,
 ) -> Result<grenad::Reader<File>> {
     let max_memory = indexer.max_memory_by_thread();
 
@@ -37,6 +40,8 @@ pub fn extract_word_position_docids<R: io::Read + io::Seek>(
         let document_id = DocumentId::from_be_bytes(document_id_bytes);
 
         for position in read_u32_ne_bytes(value) {
+            let position =
+                if bucketed_positions { bucketed_absolute_position(position) } else { position };
             key_buffer.clear();
             key_buffer.extend_from_slice(word_bytes);
             key_buffer.extend_from_slice(&position.to_be_bytes());
@@ -8,9 +8,10 @@ mod extract_word_docids;
 mod extract_word_pair_proximity_docids;
 mod extract_word_position_docids;
 
-use std::collections::HashSet;
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::fs::File;
 
+use charabia::{Language, Script};
 use crossbeam_channel::Sender;
 use log::debug;
 use rayon::prelude::*;
@@ -18,18 +19,24 @@ use rayon::prelude::*;
 use self::extract_docid_word_positions::extract_docid_word_positions;
 use self::extract_facet_number_docids::extract_facet_number_docids;
 use self::extract_facet_string_docids::extract_facet_string_docids;
+pub(crate) use self::extract_fid_docid_facet_values::extract_facet_values;
 use self::extract_fid_docid_facet_values::extract_fid_docid_facet_values;
 use self::extract_fid_word_count_docids::extract_fid_word_count_docids;
-use self::extract_geo_points::extract_geo_points;
+pub(crate) use self::extract_fid_word_count_docids::MAX_FIELD_ID_WORD_COUNT;
+pub use self::extract_geo_points::GeoFieldsIds;
+use self::extract_geo_points::{extract_geo_points, extract_geo_shapes};
 use self::extract_word_docids::extract_word_docids;
 use self::extract_word_pair_proximity_docids::extract_word_pair_proximity_docids;
 use self::extract_word_position_docids::extract_word_position_docids;
 use super::helpers::{
     as_cloneable_grenad, merge_cbo_roaring_bitmaps, merge_roaring_bitmaps, CursorClonableMmap,
-    GrenadParameters, MergeFn, MergeableReader,
+    GrenadParameters, MergeFn, MergeableReader, ParentSpan,
 };
 use super::{helpers, TypedChunk};
-use crate::{FieldId, Result};
+use crate::facet::MixedFacetTypes;
+use crate::stemming::Stemmers;
+use crate::update::TokenizerConfig;
+use crate::{FieldId, LongWordsPolicy, Result, TypoOnDiacritics};
 
 /// Extract data for each databases from obkv documents in parallel.
 /// Send data in grenad file over provided Sender.
@@ -42,33 +49,65 @@ pub(crate) fn data_from_obkv_documents(
     searchable_fields: Option<HashSet<FieldId>>,
     faceted_fields: HashSet<FieldId>,
     primary_key_id: FieldId,
-    geo_fields_ids: Option<(FieldId, FieldId)>,
+    geo_fields_ids: Option<GeoFieldsIds>,
     stop_words: Option<fst::Set<&[u8]>>,
     max_positions_per_attributes: Option<u32>,
+    max_word_length: usize,
+    long_words_policy: LongWordsPolicy,
+    min_word_length: usize,
+    max_positions_per_word: Option<usize>,
+    documents_allow_list: HashMap<Script, Vec<Language>>,
+    fields_allow_lists: HashMap<FieldId, HashMap<Script, Vec<Language>>>,
+    normalization_rules: BTreeMap<String, String>,
+    stemmers: Stemmers,
+    case_sensitive_attributes: HashSet<FieldId>,
+    typo_on_diacritics: TypoOnDiacritics,
+    tokenizer_config: Option<&TokenizerConfig>,
     exact_attributes: HashSet<FieldId>,
+    mixed_facet_types: HashMap<FieldId, (MixedFacetTypes, String)>,
+    proximity_databases: bool,
+    cross_field_proximity: bool,
+    bucketed_positions: bool,
 ) -> Result<()> {
+    let parent_span = ParentSpan::current();
     original_obkv_chunks
         .par_bridge()
         .map(|original_documents_chunk| {
-            send_original_documents_data(original_documents_chunk, lmdb_writer_sx.clone())
+            parent_span.in_scope(|| {
+                send_original_documents_data(original_documents_chunk, lmdb_writer_sx.clone())
+            })
         })
         .collect::<Result<()>>()?;
 
     #[allow(clippy::type_complexity)]
-    let result: Result<(Vec<_>, (Vec<_>, (Vec<_>, Vec<_>)))> = flattened_obkv_chunks
+    let result: Result<(Vec<_>, (Vec<_>, (Vec<_>, (Vec<_>, Vec<_>))))> = flattened_obkv_chunks
         .par_bridge()
         .map(|flattened_obkv_chunks| {
-            send_and_extract_flattened_documents_data(
-                flattened_obkv_chunks,
-                indexer,
-                lmdb_writer_sx.clone(),
-                &searchable_fields,
-                &faceted_fields,
-                primary_key_id,
-                geo_fields_ids,
-                &stop_words,
-                max_positions_per_attributes,
-            )
+            parent_span.in_scope(|| {
+                send_and_extract_flattened_documents_data(
+                    flattened_obkv_chunks,
+                    indexer,
+                    lmdb_writer_sx.clone(),
+                    &searchable_fields,
+                    &faceted_fields,
+                    primary_key_id,
+                    geo_fields_ids,
+                    &stop_words,
+                    max_positions_per_attributes,
+                    max_word_length,
+                    long_words_policy,
+                    min_word_length,
+                    max_positions_per_word,
+                    &documents_allow_list,
+                    &fields_allow_lists,
+                    &normalization_rules,
+                    &stemmers,
+                    &case_sensitive_attributes,
+                    typo_on_diacritics,
+                    tokenizer_config,
+                    &mixed_facet_types,
+                )
+            })
         })
         .collect();
 
@@ -76,14 +115,17 @@ pub(crate) fn data_from_obkv_documents(
         docid_word_positions_chunks,
         (
             docid_fid_facet_numbers_chunks,
-            (docid_fid_facet_strings_chunks, facet_exists_docids_chunks),
+            (
+                docid_fid_facet_strings_chunks,
+                (facet_exists_docids_chunks, facet_bool_docids_chunks),
+            ),
         ),
     ) = result?;
 
     // merge facet_exists_docids and send them as a typed chunk
     {
         let lmdb_writer_sx = lmdb_writer_sx.clone();
-        rayon::spawn(move || {
+        spawn_in_current_span(move || {
             debug!("merge {} database", "facet-id-exists-docids");
             match facet_exists_docids_chunks.merge(merge_cbo_roaring_bitmaps, &indexer) {
                 Ok(reader) => {
@@ -96,15 +138,36 @@ pub(crate) fn data_from_obkv_documents(
         });
     }
 
-    spawn_extraction_task::<_, _, Vec<grenad::Reader<File>>>(
-        docid_word_positions_chunks.clone(),
-        indexer,
-        lmdb_writer_sx.clone(),
-        extract_word_pair_proximity_docids,
-        merge_cbo_roaring_bitmaps,
-        TypedChunk::WordPairProximityDocids,
-        "word-pair-proximity-docids",
-    );
+    // merge facet_bool_docids and send them as a typed chunk
+    {
+        let lmdb_writer_sx = lmdb_writer_sx.clone();
+        spawn_in_current_span(move || {
+            debug!("merge {} database", "facet-id-bool-docids");
+            match facet_bool_docids_chunks.merge(merge_cbo_roaring_bitmaps, &indexer) {
+                Ok(reader) => {
+                    let _ = lmdb_writer_sx.send(Ok(TypedChunk::FieldIdFacetBoolDocids(reader)));
+                }
+                Err(e) => {
+                    let _ = lmdb_writer_sx.send(Err(e));
+                }
+            }
+        });
+    }
+
+    // without word pair proximity docids, the prefix pair proximity databases aren't built either
+    if proximity_databases {
+        spawn_extraction_task::<_, _, Vec<grenad::Reader<File>>>(
+            docid_word_positions_chunks.clone(),
+            indexer,
+            lmdb_writer_sx.clone(),
+            move |doc_word_pos, indexer| {
+                extract_word_pair_proximity_docids(doc_word_pos, indexer, cross_field_proximity)
+            },
+            merge_cbo_roaring_bitmaps,
+            TypedChunk::WordPairProximityDocids,
+            "word-pair-proximity-docids",
+        );
+    }
 
     spawn_extraction_task::<_, _, Vec<grenad::Reader<File>>>(
         docid_word_positions_chunks.clone(),
@@ -133,7 +196,9 @@ pub(crate) fn data_from_obkv_documents(
         docid_word_positions_chunks,
         indexer,
         lmdb_writer_sx.clone(),
-        extract_word_position_docids,
+        move |doc_word_pos, indexer| {
+            extract_word_position_docids(doc_word_pos, indexer, bucketed_positions)
+        },
         merge_cbo_roaring_bitmaps,
         TypedChunk::WordPositionDocids,
         "word-position-docids",
@@ -162,6 +227,12 @@ pub(crate) fn data_from_obkv_documents(
     Ok(())
 }
 
+/// Spawns the operation on the thread pool, within the tracing span of the caller.
+fn spawn_in_current_span(op: impl FnOnce() + Send + 'static) {
+    let parent_span = ParentSpan::current();
+    rayon::spawn(move || parent_span.in_scope(op));
+}
+
 /// Spawn a new task to extract data for a specific DB using extract_fn.
 /// Generated grenad chunks are merged using the merge_fn.
 /// The result of merged chunks is serialized as TypedChunk using the serialize_fn
@@ -183,10 +254,13 @@ fn spawn_extraction_task<FE, FS, M>(
     M: MergeableReader + FromParallelIterator<M::Output> + Send + 'static,
     M::Output: Send,
 {
-    rayon::spawn(move || {
-        let chunks: Result<M> =
-            chunks.into_par_iter().map(|chunk| extract_fn(chunk, indexer)).collect();
-        rayon::spawn(move || match chunks {
+    spawn_in_current_span(move || {
+        let parent_span = ParentSpan::current();
+        let chunks: Result<M> = chunks
+            .into_par_iter()
+            .map(|chunk| parent_span.in_scope(|| extract_fn(chunk, indexer)))
+            .collect();
+        spawn_in_current_span(move || match chunks {
             Ok(chunks) => {
                 debug!("merge {} database", name);
                 let reader = chunks.merge(merge_fn, &indexer);
@@ -219,6 +293,7 @@ fn send_original_documents_data(
 /// - docid_fid_facet_numbers
 /// - docid_fid_facet_strings
 /// - docid_fid_facet_exists
+/// - docid_fid_facet_bools
 #[allow(clippy::too_many_arguments)]
 #[allow(clippy::type_complexity)]
 fn send_and_extract_flattened_documents_data(
@@ -228,85 +303,142 @@ fn send_and_extract_flattened_documents_data(
     searchable_fields: &Option<HashSet<FieldId>>,
     faceted_fields: &HashSet<FieldId>,
     primary_key_id: FieldId,
-    geo_fields_ids: Option<(FieldId, FieldId)>,
+    geo_fields_ids: Option<GeoFieldsIds>,
     stop_words: &Option<fst::Set<&[u8]>>,
     max_positions_per_attributes: Option<u32>,
+    max_word_length: usize,
+    long_words_policy: LongWordsPolicy,
+    min_word_length: usize,
+    max_positions_per_word: Option<usize>,
+    documents_allow_list: &HashMap<Script, Vec<Language>>,
+    fields_allow_lists: &HashMap<FieldId, HashMap<Script, Vec<Language>>>,
+    normalization_rules: &BTreeMap<String, String>,
+    stemmers: &Stemmers,
+    case_sensitive_attributes: &HashSet<FieldId>,
+    typo_on_diacritics: TypoOnDiacritics,
+    tokenizer_config: Option<&TokenizerConfig>,
+    mixed_facet_types: &HashMap<FieldId, (MixedFacetTypes, String)>,
 ) -> Result<(
     grenad::Reader<CursorClonableMmap>,
     (
         grenad::Reader<CursorClonableMmap>,
-        (grenad::Reader<CursorClonableMmap>, grenad::Reader<File>),
+        (grenad::Reader<CursorClonableMmap>, (grenad::Reader<File>, grenad::Reader<File>)),
     ),
 )> {
     let flattened_documents_chunk =
         flattened_documents_chunk.and_then(|c| unsafe { as_cloneable_grenad(&c) })?;
+    let parent_span = ParentSpan::current();
 
     if let Some(geo_fields_ids) = geo_fields_ids {
         let documents_chunk_cloned = flattened_documents_chunk.clone();
         let lmdb_writer_sx_cloned = lmdb_writer_sx.clone();
-        rayon::spawn(move || {
-            let result =
-                extract_geo_points(documents_chunk_cloned, indexer, primary_key_id, geo_fields_ids);
+        spawn_in_current_span(move || {
+            let result = extract_geo_points(
+                documents_chunk_cloned,
+                indexer,
+                primary_key_id,
+                geo_fields_ids.lat_lng,
+            );
             let _ = match result {
                 Ok(geo_points) => lmdb_writer_sx_cloned.send(Ok(TypedChunk::GeoPoints(geo_points))),
                 Err(error) => lmdb_writer_sx_cloned.send(Err(error)),
             };
         });
+
+        if geo_fields_ids.bounding_box.is_some() || geo_fields_ids.polygon.is_some() {
+            let documents_chunk_cloned = flattened_documents_chunk.clone();
+            let lmdb_writer_sx_cloned = lmdb_writer_sx.clone();
+            spawn_in_current_span(move || {
+                let result = extract_geo_shapes(
+                    documents_chunk_cloned,
+                    indexer,
+                    primary_key_id,
+                    geo_fields_ids,
+                );
+                let _ = match result {
+                    Ok(geo_shapes) => {
+                        lmdb_writer_sx_cloned.send(Ok(TypedChunk::GeoShapes(geo_shapes)))
+                    }
+                    Err(error) => lmdb_writer_sx_cloned.send(Err(error)),
+                };
+            });
+        }
     }
 
     let (docid_word_positions_chunk, docid_fid_facet_values_chunks): (Result<_>, Result<_>) =
         rayon::join(
             || {
-                let (documents_ids, docid_word_positions_chunk) = extract_docid_word_positions(
-                    flattened_documents_chunk.clone(),
-                    indexer,
-                    searchable_fields,
-                    stop_words.as_ref(),
-                    max_positions_per_attributes,
-                )?;
+                parent_span.in_scope(|| {
+                    let (documents_ids, docid_word_positions_chunk) = extract_docid_word_positions(
+                        flattened_documents_chunk.clone(),
+                        indexer,
+                        searchable_fields,
+                        stop_words.as_ref(),
+                        max_positions_per_attributes,
+                        max_word_length,
+                        long_words_policy,
+                        min_word_length,
+                        max_positions_per_word,
+                        documents_allow_list,
+                        fields_allow_lists,
+                        normalization_rules,
+                        stemmers,
+                        case_sensitive_attributes,
+                        typo_on_diacritics,
+                        tokenizer_config,
+                    )?;
 
-                // send documents_ids to DB writer
-                let _ = lmdb_writer_sx.send(Ok(TypedChunk::NewDocumentsIds(documents_ids)));
+                    // send documents_ids to DB writer
+                    let _ = lmdb_writer_sx.send(Ok(TypedChunk::NewDocumentsIds(documents_ids)));
 
-                // send docid_word_positions_chunk to DB writer
-                let docid_word_positions_chunk =
-                    unsafe { as_cloneable_grenad(&docid_word_positions_chunk)? };
-                let _ = lmdb_writer_sx
-                    .send(Ok(TypedChunk::DocidWordPositions(docid_word_positions_chunk.clone())));
+                    // send docid_word_positions_chunk to DB writer
+                    let docid_word_positions_chunk =
+                        unsafe { as_cloneable_grenad(&docid_word_positions_chunk)? };
+                    let _ = lmdb_writer_sx.send(Ok(TypedChunk::DocidWordPositions(
+                        docid_word_positions_chunk.clone(),
+                    )));
 
-                Ok(docid_word_positions_chunk)
+                    Ok(docid_word_positions_chunk)
+                })
             },
             || {
-                let (
-                    docid_fid_facet_numbers_chunk,
-                    docid_fid_facet_strings_chunk,
-                    fid_facet_exists_docids_chunk,
-                ) = extract_fid_docid_facet_values(
-                    flattened_documents_chunk.clone(),
-                    indexer,
-                    faceted_fields,
-                )?;
+                parent_span.in_scope(|| {
+                    let (
+                        docid_fid_facet_numbers_chunk,
+                        docid_fid_facet_strings_chunk,
+                        fid_facet_exists_docids_chunk,
+                        fid_facet_bool_docids_chunk,
+                    ) = extract_fid_docid_facet_values(
+                        flattened_documents_chunk.clone(),
+                        indexer,
+                        faceted_fields,
+                        mixed_facet_types,
+                    )?;
 
-                // send docid_fid_facet_numbers_chunk to DB writer
-                let docid_fid_facet_numbers_chunk =
-                    unsafe { as_cloneable_grenad(&docid_fid_facet_numbers_chunk)? };
+                    // send docid_fid_facet_numbers_chunk to DB writer
+                    let docid_fid_facet_numbers_chunk =
+                        unsafe { as_cloneable_grenad(&docid_fid_facet_numbers_chunk)? };
 
-                let _ = lmdb_writer_sx.send(Ok(TypedChunk::FieldIdDocidFacetNumbers(
-                    docid_fid_facet_numbers_chunk.clone(),
-                )));
+                    let _ = lmdb_writer_sx.send(Ok(TypedChunk::FieldIdDocidFacetNumbers(
+                        docid_fid_facet_numbers_chunk.clone(),
+                    )));
 
-                // send docid_fid_facet_strings_chunk to DB writer
-                let docid_fid_facet_strings_chunk =
-                    unsafe { as_cloneable_grenad(&docid_fid_facet_strings_chunk)? };
+                    // send docid_fid_facet_strings_chunk to DB writer
+                    let docid_fid_facet_strings_chunk =
+                        unsafe { as_cloneable_grenad(&docid_fid_facet_strings_chunk)? };
 
-                let _ = lmdb_writer_sx.send(Ok(TypedChunk::FieldIdDocidFacetStrings(
-                    docid_fid_facet_strings_chunk.clone(),
-                )));
+                    let _ = lmdb_writer_sx.send(Ok(TypedChunk::FieldIdDocidFacetStrings(
+                        docid_fid_facet_strings_chunk.clone(),
+                    )));
 
-                Ok((
-                    docid_fid_facet_numbers_chunk,
-                    (docid_fid_facet_strings_chunk, fid_facet_exists_docids_chunk),
-                ))
+                    Ok((
+                        docid_fid_facet_numbers_chunk,
+                        (
+                            docid_fid_facet_strings_chunk,
+                            (fid_facet_exists_docids_chunk, fid_facet_bool_docids_chunk),
+                        ),
+                    ))
+                })
             },
         );
 
@@ -208,21 +208,26 @@ pub fn grenad_obkv_into_chunks<R: io::Read + io::Seek>(
     Ok(std::iter::from_fn(move || transposer().transpose()))
 }
 
+/// Writes the entries of the sorter into the database, merging them with the existing ones.
+/// Returns the number of entries written and their size in bytes.
 pub fn sorter_into_lmdb_database(
     wtxn: &mut heed::RwTxn,
     database: heed::PolyDatabase,
     sorter: Sorter<MergeFn>,
     merge: MergeFn,
-) -> Result<()> {
+) -> Result<(u64, u64)> {
     debug!("Writing MTBL sorter...");
     let before = Instant::now();
 
+    let mut written = (0, 0);
     let mut merger_iter = sorter.into_stream_merger_iter()?;
     if database.is_empty(wtxn)? {
         let mut out_iter = database.iter_mut::<_, ByteSlice, ByteSlice>(wtxn)?;
         while let Some((k, v)) = merger_iter.next()? {
             // safety: we don't keep references from inside the LMDB database.
             unsafe { out_iter.append(k, v)? };
+            written.0 += 1;
+            written.1 += (k.len() + v.len()) as u64;
         }
     } else {
         while let Some((k, v)) = merger_iter.next()? {
@@ -236,17 +241,20 @@ pub fn sorter_into_lmdb_database(
                     })?;
                     // safety: we don't keep references from inside the LMDB database.
                     unsafe { iter.put_current(k, &val)? };
+                    written.1 += (k.len() + val.len()) as u64;
                 }
                 _ => {
                     drop(iter);
                     database.put::<_, ByteSlice, ByteSlice>(wtxn, k, v)?;
+                    written.1 += (k.len() + v.len()) as u64;
                 }
             }
+            written.0 += 1;
         }
     }
 
     debug!("MTBL sorter writen in {:.02?}!", before.elapsed());
-    Ok(())
+    Ok(written)
 }
 
 /// Used when trying to merge readers, but you don't actually care about the values.
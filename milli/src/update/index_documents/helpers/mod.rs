@@ -24,6 +24,48 @@ pub fn valid_lmdb_key(key: impl AsRef<[u8]>) -> bool {
     key.as_ref().len() <= MAX_WORD_LENGTH * 2 && !key.as_ref().is_empty()
 }
 
+/// Returns `true` if a field of a document, given its value as stored in the documents
+/// database, is counted in the field distribution.
+///
+/// A field is counted when the document contains its key with a non-null value, empty arrays
+/// and objects included. Only the top-level keys are stored, a nested object is therefore
+/// counted once under its parent key, whatever its children are.
+pub fn is_counted_in_field_distribution(value: &[u8]) -> bool {
+    value != b"null"
+}
+
+/// The tracing span of the caller, entered by the tasks it runs on the other threads of the
+/// thread pool for their spans to be children of it.
+#[cfg(feature = "tracing")]
+#[derive(Clone)]
+pub struct ParentSpan(tracing::Span);
+
+#[cfg(not(feature = "tracing"))]
+#[derive(Clone)]
+pub struct ParentSpan;
+
+#[cfg(feature = "tracing")]
+impl ParentSpan {
+    pub fn current() -> ParentSpan {
+        ParentSpan(tracing::Span::current())
+    }
+
+    pub fn in_scope<T>(&self, f: impl FnOnce() -> T) -> T {
+        self.0.in_scope(f)
+    }
+}
+
+#[cfg(not(feature = "tracing"))]
+impl ParentSpan {
+    pub fn current() -> ParentSpan {
+        ParentSpan
+    }
+
+    pub fn in_scope<T>(&self, f: impl FnOnce() -> T) -> T {
+        f()
+    }
+}
+
 /// Divides one slice into two at an index, returns `None` if mid is out of bounds.
 pub fn try_split_at<T>(slice: &[T], mid: usize) -> Option<(&[T], &[T])> {
     if mid <= slice.len() {
@@ -9,38 +9,49 @@ use std::io::{Cursor, Read, Seek};
 use std::iter::FromIterator;
 use std::num::NonZeroU32;
 use std::result::Result as StdResult;
+use std::sync::Arc;
+use std::time::Instant;
+use std::{fmt, mem};
 
 use crossbeam_channel::{Receiver, Sender};
 use heed::types::Str;
 use heed::Database;
-use log::debug;
+use log::{debug, warn};
+use obkv::KvReaderU16;
 use roaring::RoaringBitmap;
 use serde::{Deserialize, Serialize};
 use slice_group_by::GroupBy;
 use typed_chunk::{write_typed_chunk_into_index, TypedChunk};
 
-use self::enrich::enrich_documents_batch;
+use self::enrich::{enrich_documents_batch, validate_documents_batch};
 pub use self::enrich::{
-    extract_finite_float_from_value, validate_document_id, validate_document_id_value,
-    validate_geo_from_json, DocumentId,
+    extract_finite_float_from_value, geo_shape_vertices, validate_document_id_value,
+    validate_geo_from_json, AllowedDocumentIdChars, DocumentId, DocumentIdRules, DryRunError,
+    DryRunReport,
 };
+use self::extract::GeoFieldsIds;
+pub(crate) use self::extract::MAX_FIELD_ID_WORD_COUNT;
+pub(crate) use self::helpers::is_counted_in_field_distribution;
 pub use self::helpers::{
     as_cloneable_grenad, create_sorter, create_writer, fst_stream_into_hashset,
     fst_stream_into_vec, merge_cbo_roaring_bitmaps, merge_roaring_bitmaps,
     sorter_into_lmdb_database, valid_lmdb_key, writer_into_reader, ClonableMmap, MergeFn,
 };
-use self::helpers::{grenad_obkv_into_chunks, GrenadParameters};
+use self::helpers::{grenad_obkv_into_chunks, GrenadParameters, ParentSpan};
 pub use self::transform::{Transform, TransformOutput};
 use crate::documents::{obkv_to_object, DocumentsBatchReader};
-use crate::error::{Error, InternalError, UserError};
+use crate::error::{Error, FieldIdMapMissingEntry, InternalError, UserError};
+use crate::index::{db_name, main_key};
 pub use crate::update::index_documents::helpers::CursorClonableMmap;
 use crate::update::{
     self, DeletionStrategy, IndexerConfig, PrefixWordPairsProximityDocids, UpdateIndexingStep,
     WordPrefixDocids, WordPrefixPositionDocids, WordsPrefixesFst,
 };
-use crate::{Index, Result, RoaringBitmapCodec};
+use crate::{
+    FieldDistribution, FieldsIdsMap, Index, Result, RoaringBitmapCodec, BEU32, BEU64, MAX_FIELDS,
+};
 
-static MERGED_DATABASE_COUNT: usize = 7;
+static MERGED_DATABASE_COUNT: usize = 8;
 static PREFIX_DATABASE_COUNT: usize = 5;
 static TOTAL_POSTING_DATABASE_COUNT: usize = MERGED_DATABASE_COUNT + PREFIX_DATABASE_COUNT;
 
@@ -50,6 +61,27 @@ pub struct DocumentAdditionResult {
     pub indexed_documents: u64,
     /// The total number of documents in the index after the update
     pub number_of_documents: u64,
+    /// The external ids of the documents skipped because they exceeded the maximum
+    /// document size, see [`IndexerConfig::skip_oversized_documents`], or the maximum
+    /// number of fields, see [`MaxFieldsPolicy::Skip`](crate::MaxFieldsPolicy::Skip).
+    #[serde(default)]
+    pub skipped_documents: Vec<String>,
+    /// The fields dropped from the documents because their values couldn't be indexed,
+    /// see [`IndexDocumentsConfig::lenient`].
+    #[serde(default)]
+    pub field_errors: Vec<FieldError>,
+}
+
+/// A field dropped from a document because its value couldn't be indexed,
+/// see [`IndexDocumentsConfig::lenient`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FieldError {
+    /// The external id of the document.
+    pub external_id: String,
+    /// The name of the dropped field.
+    pub field: String,
+    /// The message of the error the field would have made the indexing fail with.
+    pub message: String,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
@@ -70,6 +102,49 @@ impl Default for IndexDocumentsMethod {
     }
 }
 
+/// Which of two documents with the same external id is kept, see [`DuplicatesReducer`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeptDocument {
+    /// The document seen first in the batch.
+    Existing,
+    /// The document seen last in the batch.
+    Incoming,
+}
+
+/// Chooses which of two documents with the same external id in a batch is kept,
+/// see [`IndexDocumentsConfig::duplicates_reducer`].
+///
+/// The function is called with the document kept so far, the incoming document and the
+/// fields ids map of the documents.
+#[derive(Clone)]
+pub struct DuplicatesReducer(
+    Arc<dyn Fn(KvReaderU16, KvReaderU16, &FieldsIdsMap) -> KeptDocument + Send + Sync>,
+);
+
+impl DuplicatesReducer {
+    pub fn new<F>(reducer: F) -> DuplicatesReducer
+    where
+        F: Fn(KvReaderU16, KvReaderU16, &FieldsIdsMap) -> KeptDocument + Send + Sync + 'static,
+    {
+        DuplicatesReducer(Arc::new(reducer))
+    }
+
+    pub(crate) fn reduce(
+        &self,
+        existing: KvReaderU16,
+        incoming: KvReaderU16,
+        fields_ids_map: &FieldsIdsMap,
+    ) -> KeptDocument {
+        (self.0)(existing, incoming, fields_ids_map)
+    }
+}
+
+impl fmt::Debug for DuplicatesReducer {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("DuplicatesReducer").finish_non_exhaustive()
+    }
+}
+
 pub struct IndexDocuments<'t, 'u, 'i, 'a, FP, FA> {
     wtxn: &'t mut heed::RwTxn<'i, 'u>,
     index: &'i Index,
@@ -90,6 +165,60 @@ pub struct IndexDocumentsConfig {
     pub update_method: IndexDocumentsMethod,
     pub deletion_strategy: DeletionStrategy,
     pub autogenerate_docids: bool,
+    /// Whether a replaced or updated document keeps the internal id it already had.
+    ///
+    /// When enabled, the internal id of a document is guaranteed to stay the same for as long
+    /// as its external id is not deleted from the index, it is therefore safe to cache the
+    /// association between them. It is only guaranteed if every document addition is made
+    /// with this option enabled.
+    ///
+    /// The replaced documents are always hard-deleted, regardless of the `deletion_strategy`,
+    /// because a soft-deleted internal id can't be used by another document.
+    pub preserve_documents_ids: bool,
+    /// Chooses which document is kept when the same external id appears several times in
+    /// the documents added by an [`IndexDocuments`], the last one is kept by default.
+    ///
+    /// With the [`IndexDocumentsMethod::UpdateDocuments`] method the kept incoming document is
+    /// merged into the existing one, as without reducer. The documents already in the index are
+    /// not given to the reducer, and the documents of the batch are kept in memory to be
+    /// given to it.
+    pub duplicates_reducer: Option<DuplicatesReducer>,
+    /// The rules the external ids of the added documents must follow, the documents
+    /// are rejected with an [`UserError::InvalidDocumentId`] otherwise.
+    ///
+    /// The same rules should be given to the [`DeleteDocuments`](crate::update::DeleteDocuments)
+    /// of the index, see [`DeleteDocuments::document_id_rules`](crate::update::DeleteDocuments::document_id_rules).
+    pub document_id_rules: DocumentIdRules,
+    /// Whether the fields whose values can't be indexed are dropped from their documents
+    /// instead of making the whole indexing fail, the rest of the documents is indexed.
+    ///
+    /// Only the invalid `_geo` fields and the strings given to the fields in the
+    /// [`MixedFacetTypes::StrictNumeric`](crate::facet::MixedFacetTypes::StrictNumeric) mode
+    /// are dropped, they are reported in the [`DocumentAdditionResult::field_errors`].
+    pub lenient: bool,
+}
+
+impl IndexDocumentsConfig {
+    /// Validates the documents as an [`IndexDocuments`] with this configuration would,
+    /// without writing anything to the index.
+    ///
+    /// The errors of all the documents are reported, not only the first one: the missing or
+    /// invalid document ids, the invalid `_geo` fields and the fields that don't fit in the
+    /// fields ids map. The external ids are resolved against the current state of the index.
+    pub fn dry_run<R: Read + Seek>(
+        &self,
+        rtxn: &heed::RoTxn,
+        index: &Index,
+        reader: DocumentsBatchReader<R>,
+    ) -> Result<DryRunReport> {
+        validate_documents_batch(
+            rtxn,
+            index,
+            self.autogenerate_docids,
+            &self.document_id_rules,
+            reader,
+        )
+    }
 }
 
 impl<'t, 'u, 'i, 'a, FP, FA> IndexDocuments<'t, 'u, 'i, 'a, FP, FA>
@@ -97,6 +226,10 @@ where
     FP: Fn(UpdateIndexingStep) + Sync,
     FA: Fn() -> bool + Sync,
 {
+    /// Creates a documents addition in the given write transaction.
+    ///
+    /// It must be created after the settings updates made in the same transaction,
+    /// the documents are then indexed under the new settings.
     pub fn new(
         wtxn: &'t mut heed::RwTxn<'i, 'u>,
         index: &'i Index,
@@ -105,16 +238,19 @@ where
         progress: FP,
         should_abort: FA,
     ) -> Result<IndexDocuments<'t, 'u, 'i, 'a, FP, FA>> {
-        let transform = Some(Transform::new(
+        let mut transform = Transform::new(
             wtxn,
             index,
             indexer_config,
             config.update_method,
             config.autogenerate_docids,
-        )?);
+            config.preserve_documents_ids,
+            config.duplicates_reducer.clone(),
+        )?;
+        transform.lenient = config.lenient;
 
         Ok(IndexDocuments {
-            transform,
+            transform: Some(transform),
             config,
             indexer_config,
             progress,
@@ -125,6 +261,77 @@ where
         })
     }
 
+    /// Marks an empty index as indexed by the current version before the documents are added
+    /// and warns about the documents indexed by older versions, which are only migrated by an
+    /// explicit step, e.g. [`migrate_boolean_facets`], or by reindexing all of them.
+    fn check_indexed_documents(&mut self) -> Result<()> {
+        let index_is_empty = self.index.number_of_documents(self.wtxn)? == 0;
+
+        // The booleans of the documents indexed by older versions are stored as the "true" and
+        // "false" strings, they are only moved to their own database by `migrate_boolean_facets`.
+        if !self.index.boolean_facets_indexed(self.wtxn)? {
+            if index_is_empty {
+                self.index.put_boolean_facets_indexed(self.wtxn)?;
+            } else {
+                warn!(
+                    "The booleans of the documents of this index are stored as strings, \
+                     the index must be migrated for them to be sorted as booleans"
+                );
+            }
+        }
+
+        // The numbers of the documents indexed by older versions are not canonicalized,
+        // they are only canonicalized when all the documents are reindexed.
+        if !self.index.canonical_numbers(self.wtxn)? {
+            if index_is_empty {
+                self.index.put_canonical_numbers(self.wtxn)?;
+            } else {
+                warn!(
+                    "The numbers of the documents of this index are not canonicalized, \
+                     the index must be reindexed for them to be found by their canonical form"
+                );
+            }
+        }
+
+        // The original spellings of the facet strings of the documents indexed by older versions
+        // are not counted, they are only counted when all the documents are reindexed.
+        if !self.index.facet_string_originals_indexed(self.wtxn)? {
+            if index_is_empty {
+                self.index.put_facet_string_originals_indexed(self.wtxn)?;
+            } else {
+                warn!(
+                    "The original spellings of the facet strings of this index are not counted, \
+                     the index must be reindexed for the facet distribution to be stable"
+                );
+            }
+        }
+
+        // The positions of the documents indexed by older versions are exact, the positions of
+        // the new documents are only bucketed when all the documents are reindexed.
+        if !self.index.bucketed_positions(self.wtxn)? && index_is_empty {
+            self.index.put_bucketed_positions(self.wtxn)?;
+        }
+
+        recount_field_distribution(self.wtxn, self.index)?;
+
+        // The tokenizer configuration of the indexed documents is only changed by reindexing
+        // all of them, the new documents are tokenized differently until then.
+        let tokenizer_fingerprint = self.indexer_config.tokenizer_fingerprint();
+        if self.index.tokenizer_fingerprint(self.wtxn)? != tokenizer_fingerprint {
+            if index_is_empty {
+                self.index.put_tokenizer_fingerprint(self.wtxn, tokenizer_fingerprint)?;
+            } else {
+                warn!(
+                    "The documents are tokenized with another tokenizer configuration than the \
+                     documents of the index, the settings must apply the new configuration for \
+                     all the documents to be tokenized the same way"
+                );
+            }
+        }
+
+        Ok(())
+    }
+
     /// Adds a batch of documents to the current builder.
     ///
     /// Since the documents are progressively added to the writer, a failure will cause only
@@ -140,6 +347,11 @@ where
             return Ok((self, Ok(0)));
         }
 
+        // The documents can't be given an internal id once the index uses all of them.
+        if self.index.available_ids_count(self.wtxn)? == 0 {
+            return Ok((self, Err(UserError::DocumentLimitReached)));
+        }
+
         // We check for user errors in this validator and if there is one, we can return
         // the `IndexDocument` struct as it is valid to send more documents into it.
         // However, if there is an internal error we throw it away!
@@ -147,6 +359,8 @@ where
             self.wtxn,
             self.index,
             self.config.autogenerate_docids,
+            &self.config.document_id_rules,
+            self.config.lenient,
             reader,
         )? {
             Ok(reader) => reader,
@@ -168,15 +382,22 @@ where
 
     #[logging_timer::time("IndexDocuments::{}")]
     pub fn execute(mut self) -> Result<DocumentAdditionResult> {
+        let mut transform = self.transform.take().expect("Invalid document addition state");
+        let skipped_documents = mem::take(&mut transform.skipped_documents);
+        let field_errors = mem::take(&mut transform.field_errors);
+
         if self.added_documents == 0 {
             let number_of_documents = self.index.number_of_documents(self.wtxn)?;
-            return Ok(DocumentAdditionResult { indexed_documents: 0, number_of_documents });
+            return Ok(DocumentAdditionResult {
+                indexed_documents: 0,
+                number_of_documents,
+                skipped_documents,
+                field_errors,
+            });
         }
-        let output = self
-            .transform
-            .take()
-            .expect("Invalid document addition state")
-            .output_from_sorter(self.wtxn, &self.progress)?;
+        // The field distribution is counted again, if needed, before the transform updates it.
+        self.check_indexed_documents()?;
+        let output = transform.output_from_sorter(self.wtxn, &self.progress)?;
 
         let new_facets = output.compute_real_facets(self.wtxn, self.index)?;
         self.index.put_faceted_fields(self.wtxn, &new_facets)?;
@@ -196,11 +417,20 @@ where
         let indexed_documents = output.documents_count as u64;
         let number_of_documents = self.execute_raw(output)?;
 
-        Ok(DocumentAdditionResult { indexed_documents, number_of_documents })
+        Ok(DocumentAdditionResult {
+            indexed_documents,
+            number_of_documents,
+            skipped_documents,
+            field_errors,
+        })
     }
 
     /// Returns the total number of documents in the index after the update.
     #[logging_timer::time("IndexDocuments::{}")]
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "trace", name = "IndexDocuments::execute_raw", skip_all)
+    )]
     pub fn execute_raw(self, output: TransformOutput) -> Result<u64>
     where
         FP: Fn(UpdateIndexingStep) + Sync,
@@ -244,6 +474,8 @@ where
         let original_documents = grenad::Reader::new(original_documents)?;
         let flattened_documents = grenad::Reader::new(flattened_documents)?;
 
+        let extract_and_write_start = Instant::now();
+
         // create LMDB writer channel
         let (lmdb_writer_sx, lmdb_writer_rx): (
             Sender<Result<TypedChunk>>,
@@ -270,8 +502,15 @@ where
                         .fields_ids_map(self.wtxn)?
                         .insert("_geo.lat")
                         .zip(self.index.fields_ids_map(self.wtxn)?.insert("_geo.lng"))
-                        .ok_or(UserError::AttributeLimitReached)?;
-                    Some(field_ids)
+                        .ok_or(UserError::AttributeLimitReached {
+                            external_id: None,
+                            limit: MAX_FIELDS,
+                        })?;
+                    Some(GeoFieldsIds {
+                        lat_lng: field_ids,
+                        bounding_box: fields_ids_map.id("_geo.boundingBox"),
+                        polygon: fields_ids_map.id("_geo.polygon"),
+                    })
                 } else {
                     None
                 }
@@ -279,8 +518,22 @@ where
             None => None,
         };
 
-        let stop_words = self.index.stop_words(self.wtxn)?;
+        let stop_words = self.index.indexed_stop_words(self.wtxn)?;
         let exact_attributes = self.index.exact_attributes_ids(self.wtxn)?;
+        let max_word_length = self.index.max_word_length(self.wtxn)?;
+        let long_words_policy = self.index.long_words_policy(self.wtxn)?;
+        let min_word_length = self.index.min_indexed_word_length(self.wtxn)?;
+        let max_positions_per_word = self.index.max_positions_per_word(self.wtxn)?;
+        let documents_allow_list = self.index.documents_allow_list(self.wtxn)?;
+        let fields_allow_lists = self.index.fields_ids_allow_lists(self.wtxn)?;
+        let normalization_rules = self.index.normalization_rules(self.wtxn)?;
+        let stemmers = self.index.stemmers(self.wtxn)?;
+        let case_sensitive_attributes = self.index.case_sensitive_attributes_ids(self.wtxn)?;
+        let typo_on_diacritics = self.index.typo_on_diacritics(self.wtxn)?;
+        let mixed_facet_types = self.index.fields_ids_mixed_facet_types(self.wtxn)?;
+        let proximity_databases = self.index.proximity_databases(self.wtxn)?;
+        let cross_field_proximity = self.index.cross_field_proximity(self.wtxn)?;
+        let bucketed_positions = self.index.bucketed_positions(self.wtxn)?;
 
         let pool_params = GrenadParameters {
             chunk_compression_type: self.indexer_config.chunk_compression_type,
@@ -292,47 +545,69 @@ where
             self.indexer_config.documents_chunk_size.unwrap_or(1024 * 1024 * 4); // 4MiB
         let max_positions_per_attributes = self.indexer_config.max_positions_per_attributes;
 
-        // Run extraction pipeline in parallel.
+        // Run extraction pipeline in parallel, the extraction spans are children of this one.
+        let parent_span = ParentSpan::current();
         pool.install(|| {
-            // split obkv file into several chunks
-            let original_chunk_iter =
-                grenad_obkv_into_chunks(original_documents, pool_params, documents_chunk_size);
-
-            // split obkv file into several chunks
-            let flattened_chunk_iter =
-                grenad_obkv_into_chunks(flattened_documents, pool_params, documents_chunk_size);
-
-            let result = original_chunk_iter.and_then(|original_chunk| {
-                let flattened_chunk = flattened_chunk_iter?;
-                // extract all databases from the chunked obkv douments
-                extract::data_from_obkv_documents(
-                    original_chunk,
-                    flattened_chunk,
-                    pool_params,
-                    lmdb_writer_sx.clone(),
-                    searchable_fields,
-                    faceted_fields,
-                    primary_key_id,
-                    geo_fields_ids,
-                    stop_words,
-                    max_positions_per_attributes,
-                    exact_attributes,
-                )
-            });
+            parent_span.in_scope(|| {
+                // split obkv file into several chunks
+                let original_chunk_iter =
+                    grenad_obkv_into_chunks(original_documents, pool_params, documents_chunk_size);
+
+                // split obkv file into several chunks
+                let flattened_chunk_iter =
+                    grenad_obkv_into_chunks(flattened_documents, pool_params, documents_chunk_size);
+
+                let result = original_chunk_iter.and_then(|original_chunk| {
+                    let flattened_chunk = flattened_chunk_iter?;
+                    // extract all databases from the chunked obkv douments
+                    extract::data_from_obkv_documents(
+                        original_chunk,
+                        flattened_chunk,
+                        pool_params,
+                        lmdb_writer_sx.clone(),
+                        searchable_fields,
+                        faceted_fields,
+                        primary_key_id,
+                        geo_fields_ids,
+                        stop_words,
+                        max_positions_per_attributes,
+                        max_word_length,
+                        long_words_policy,
+                        min_word_length,
+                        max_positions_per_word,
+                        documents_allow_list,
+                        fields_allow_lists,
+                        normalization_rules,
+                        stemmers,
+                        case_sensitive_attributes,
+                        typo_on_diacritics,
+                        self.indexer_config.tokenizer_config.as_ref(),
+                        exact_attributes,
+                        mixed_facet_types,
+                        proximity_databases,
+                        cross_field_proximity,
+                        bucketed_positions,
+                    )
+                });
 
-            if let Err(e) = result {
-                let _ = lmdb_writer_sx.send(Err(e));
-            }
+                if let Err(e) = result {
+                    let _ = lmdb_writer_sx.send(Err(e));
+                }
 
-            // needs to be droped to avoid channel waiting lock.
-            drop(lmdb_writer_sx)
+                // needs to be droped to avoid channel waiting lock.
+                drop(lmdb_writer_sx)
+            })
         });
 
         // We delete the documents that this document addition replaces. This way we are
         // able to simply insert all the documents even if they already exist in the database.
         if !replaced_documents_ids.is_empty() {
             let mut deletion_builder = update::DeleteDocuments::new(self.wtxn, self.index)?;
-            deletion_builder.strategy(self.config.deletion_strategy);
+            if self.config.preserve_documents_ids {
+                deletion_builder.strategy(DeletionStrategy::AlwaysHard);
+            } else {
+                deletion_builder.strategy(self.config.deletion_strategy);
+            }
             debug!("documents to delete {:?}", replaced_documents_ids);
             deletion_builder.delete_documents(&replaced_documents_ids);
             let deleted_documents_result = deletion_builder.execute_inner()?;
@@ -383,8 +658,13 @@ where
                 otherwise => otherwise,
             };
 
-            let (docids, is_merged_database) =
-                write_typed_chunk_into_index(typed_chunk, self.index, self.wtxn, index_is_empty)?;
+            let (docids, is_merged_database) = write_typed_chunk_into_index(
+                typed_chunk,
+                self.index,
+                self.wtxn,
+                index_is_empty,
+                self.indexer_config.metrics.as_deref(),
+            )?;
             if !docids.is_empty() {
                 final_documents_ids |= docids;
                 let documents_seen_count = final_documents_ids.len();
@@ -415,20 +695,49 @@ where
         // We write the external documents ids into the main database.
         self.index.put_external_documents_ids(self.wtxn, &external_documents_ids)?;
 
+        if self.indexer_config.track_indexing_sequence {
+            let sequence = BEU64::new(self.index.next_indexing_sequence(self.wtxn)?);
+            for docid in &new_documents_ids {
+                self.index.documents_indexed_at.put(self.wtxn, &BEU32::new(docid), &sequence)?;
+            }
+            if let Some(metrics) = &self.indexer_config.metrics {
+                // Every entry is made of a 4 bytes document id and an 8 bytes sequence.
+                let entries = new_documents_ids.len();
+                metrics.on_db_written(db_name::DOCUMENTS_INDEXED_AT, entries, entries * 12);
+            }
+        }
+
         let all_documents_ids = index_documents_ids | new_documents_ids;
         self.index.put_documents_ids(self.wtxn, &all_documents_ids)?;
 
+        if let Some(metrics) = &self.indexer_config.metrics {
+            metrics.on_phase_duration("extract_and_write", extract_and_write_start.elapsed());
+        }
+
+        let indexer_config = self.indexer_config;
+        let prefix_databases_start = Instant::now();
         self.execute_prefix_databases(
             word_docids,
             exact_word_docids,
             word_pair_proximity_docids,
             word_position_docids,
         )?;
+        if let Some(metrics) = &indexer_config.metrics {
+            metrics.on_phase_duration("prefix_databases", prefix_databases_start.elapsed());
+        }
 
         Ok(all_documents_ids.len())
     }
 
     #[logging_timer::time("IndexDocuments::{}")]
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            level = "trace",
+            name = "IndexDocuments::execute_prefix_databases",
+            skip_all
+        )
+    )]
     pub fn execute_prefix_databases(
         self,
         word_docids: Option<grenad::Reader<CursorClonableMmap>>,
@@ -475,6 +784,11 @@ where
         }
 
         let current_prefix_fst = self.index.words_prefixes_fst(self.wtxn)?;
+        if let Some(metrics) = &self.indexer_config.metrics {
+            let key_bytes = main_key::WORDS_PREFIXES_FST_KEY.len();
+            let fst_bytes = current_prefix_fst.as_fst().as_bytes().len();
+            metrics.on_db_written(db_name::MAIN, 1, (key_bytes + fst_bytes) as u64);
+        }
 
         // We retrieve the common words between the previous and new prefix word fst.
         let common_prefix_fst_words = fst_stream_into_vec(
@@ -512,6 +826,7 @@ where
                 self.index.word_docids,
                 self.index.word_prefix_docids,
                 self.indexer_config,
+                db_name::WORD_PREFIX_DOCIDS,
                 &new_prefix_fst_words,
                 &common_prefix_fst_words,
                 &del_prefix_fst_words,
@@ -525,6 +840,7 @@ where
                 self.index.exact_word_docids,
                 self.index.exact_word_prefix_docids,
                 self.indexer_config,
+                db_name::EXACT_WORD_PREFIX_DOCIDS,
                 &new_prefix_fst_words,
                 &common_prefix_fst_words,
                 &del_prefix_fst_words,
@@ -543,13 +859,16 @@ where
 
         if let Some(word_pair_proximity_docids) = word_pair_proximity_docids {
             // Run the word prefix pair proximity docids update operation.
-            PrefixWordPairsProximityDocids::new(
+            let mut builder = PrefixWordPairsProximityDocids::new(
                 self.wtxn,
                 self.index,
                 self.indexer_config.chunk_compression_type,
                 self.indexer_config.chunk_compression_level,
-            )
-            .execute(
+            );
+            if let Some(metrics) = &self.indexer_config.metrics {
+                builder.metrics(metrics.clone());
+            }
+            builder.execute(
                 word_pair_proximity_docids,
                 &new_prefix_fst_words,
                 &common_prefix_fst_words,
@@ -574,6 +893,7 @@ where
             builder.chunk_compression_level = self.indexer_config.chunk_compression_level;
             builder.max_nb_chunks = self.indexer_config.max_nb_chunks;
             builder.max_memory = self.indexer_config.max_memory;
+            builder.metrics = self.indexer_config.metrics.clone();
             if let Some(value) = self.config.words_positions_level_group_size {
                 builder.level_group_size(value);
             }
@@ -610,6 +930,7 @@ fn execute_word_prefix_docids(
     word_docids_db: Database<Str, RoaringBitmapCodec>,
     word_prefix_docids_db: Database<Str, RoaringBitmapCodec>,
     indexer_config: &IndexerConfig,
+    db_name: &'static str,
     new_prefix_fst_words: &[String],
     common_prefix_fst_words: &[&[String]],
     del_prefix_fst_words: &HashSet<Vec<u8>>,
@@ -620,21 +941,181 @@ fn execute_word_prefix_docids(
     builder.chunk_compression_level = indexer_config.chunk_compression_level;
     builder.max_nb_chunks = indexer_config.max_nb_chunks;
     builder.max_memory = indexer_config.max_memory;
+    builder.db_name = db_name;
+    builder.metrics = indexer_config.metrics.clone();
     builder.execute(cursor, new_prefix_fst_words, common_prefix_fst_words, del_prefix_fst_words)?;
     Ok(())
 }
 
+/// Reindexes all the documents of the index, their fields are remapped from the
+/// `old_fields_ids_map` to the `new_fields_ids_map`.
+pub(crate) fn reindex_all_documents<'i>(
+    wtxn: &mut heed::RwTxn<'i, '_>,
+    index: &'i Index,
+    indexer_config: &IndexerConfig,
+    old_fields_ids_map: FieldsIdsMap,
+    new_fields_ids_map: FieldsIdsMap,
+    progress: &(dyn Fn(UpdateIndexingStep) + Sync),
+    should_abort: &(dyn Fn() -> bool + Sync),
+) -> Result<()> {
+    let transform = Transform::new(
+        wtxn,
+        index,
+        indexer_config,
+        IndexDocumentsMethod::ReplaceDocuments,
+        false,
+        false,
+        None,
+    )?;
+
+    // We clear the databases and remap the documents fields based on the new `FieldsIdsMap`.
+    let output =
+        transform.prepare_for_documents_reindexing(wtxn, old_fields_ids_map, new_fields_ids_map)?;
+
+    // All the documents are extracted again, their numbers end up canonicalized, their booleans
+    // in their own database, the spellings of their facet strings counted, their positions
+    // bucketed and they are tokenized with the tokenizer configuration of the indexer.
+    index.put_canonical_numbers(wtxn)?;
+    index.put_boolean_facets_indexed(wtxn)?;
+    index.put_facet_string_originals_indexed(wtxn)?;
+    index.put_bucketed_positions(wtxn)?;
+    index.put_tokenizer_fingerprint(wtxn, indexer_config.tokenizer_fingerprint())?;
+
+    // We index the generated `TransformOutput` which must contain
+    // all the documents with fields in the new order.
+    let indexing_builder = IndexDocuments::new(
+        wtxn,
+        index,
+        indexer_config,
+        IndexDocumentsConfig::default(),
+        progress,
+        should_abort,
+    )?;
+    indexing_builder.execute_raw(output)?;
+
+    Ok(())
+}
+
+/// Moves the booleans of the documents indexed by older versions, stored as the `"true"` and
+/// `"false"` strings, to the [`facet_id_bool_docids`](Index::facet_id_bool_docids) database by
+/// reindexing all the documents of the index.
+///
+/// Returns whether the documents were reindexed, nothing is done when the booleans of the
+/// index are already in their own database.
+pub fn migrate_boolean_facets<'i>(
+    wtxn: &mut heed::RwTxn<'i, '_>,
+    index: &'i Index,
+    indexer_config: &IndexerConfig,
+    progress: &(dyn Fn(UpdateIndexingStep) + Sync),
+    should_abort: &(dyn Fn() -> bool + Sync),
+) -> Result<bool> {
+    if index.boolean_facets_indexed(wtxn)? {
+        return Ok(false);
+    }
+
+    if index.number_of_documents(wtxn)? == 0 {
+        index.put_boolean_facets_indexed(wtxn)?;
+        return Ok(false);
+    }
+
+    let fields_ids_map = index.fields_ids_map(wtxn)?;
+    reindex_all_documents(
+        wtxn,
+        index,
+        indexer_config,
+        fields_ids_map.clone(),
+        fields_ids_map,
+        progress,
+        should_abort,
+    )?;
+
+    Ok(true)
+}
+
+/// Counts the fields of the documents again when the field distribution of the index was
+/// computed by older versions, which counted the fields with a null value.
+///
+/// The deleted documents don't decrement the fields they contain with a null value anymore,
+/// these counts would otherwise never reach zero.
+pub(crate) fn recount_field_distribution(wtxn: &mut heed::RwTxn, index: &Index) -> Result<()> {
+    if index.null_values_uncounted(wtxn)? {
+        return Ok(());
+    }
+
+    let fields_ids_map = index.fields_ids_map(wtxn)?;
+    let mut field_distribution = FieldDistribution::new();
+    for document in index.all_documents(wtxn)? {
+        let (_, obkv) = document?;
+        for (field_id, value) in obkv.iter() {
+            if !is_counted_in_field_distribution(value) {
+                continue;
+            }
+            let name = fields_ids_map.name(field_id).ok_or(FieldIdMapMissingEntry::FieldId {
+                field_id,
+                process: "Counting the field distribution again.",
+            })?;
+            *field_distribution.entry(name.to_string()).or_default() += 1;
+        }
+    }
+
+    index.put_field_distribution(wtxn, &field_distribution)?;
+    index.put_null_values_uncounted(wtxn)?;
+    Ok(())
+}
+
+/// Extracts the words and facets of the given documents again from their stored content and
+/// replaces their previous postings, without touching the other documents of the index.
+///
+/// The documents keep their internal ids, the ids that are not the ids of documents of the
+/// index are ignored. Returns the number of reindexed documents.
+pub fn reindex_documents<'i>(
+    wtxn: &mut heed::RwTxn<'i, '_>,
+    index: &'i Index,
+    indexer_config: &IndexerConfig,
+    documents_ids: &RoaringBitmap,
+    progress: &(dyn Fn(UpdateIndexingStep) + Sync),
+    should_abort: &(dyn Fn() -> bool + Sync),
+) -> Result<u64> {
+    let transform = Transform::new(
+        wtxn,
+        index,
+        indexer_config,
+        IndexDocumentsMethod::ReplaceDocuments,
+        false,
+        true,
+        None,
+    )?;
+
+    let output = transform.prepare_for_partial_reindexing(wtxn, documents_ids)?;
+    let reindexed = output.new_documents_ids.len();
+
+    // The documents are hard-deleted before being indexed again, the words
+    // they were the only ones to contain are removed from the words FST.
+    let config = IndexDocumentsConfig { preserve_documents_ids: true, ..Default::default() };
+    let indexing_builder =
+        IndexDocuments::new(wtxn, index, indexer_config, config, progress, should_abort)?;
+    indexing_builder.execute_raw(output)?;
+
+    Ok(reindexed)
+}
+
 #[cfg(test)]
 mod tests {
+    use std::collections::BTreeSet;
+    use std::sync::Arc;
+
     use big_s::S;
     use maplit::hashset;
+    use once_cell::sync::Lazy;
 
     use super::*;
     use crate::documents::documents_batch_reader_from_objects;
+    use crate::error::InvalidDocumentIdReason;
     use crate::index::tests::TempIndex;
+    use crate::index::{db_name, main_key};
     use crate::search::TermsMatchingStrategy;
-    use crate::update::DeleteDocuments;
-    use crate::{db_snap, BEU16};
+    use crate::update::{AtomicMetrics, DeleteDocuments, TokenizerConfig};
+    use crate::{db_snap, MaxFieldsPolicy, Object, BEU16};
 
     #[test]
     fn simple_document_replacement() {
@@ -681,6 +1162,74 @@ mod tests {
         assert_eq!(count, 3);
 
         drop(rtxn);
+
+        // Only the last version of the documents is stored.
+        db_snap!(index, documents, @r###"
+        1                id:1 name:"updated second kevin"
+        2                id:2 name:"updated kevina"
+        3                id:3 name:"updated benoit"
+        "###);
+    }
+
+    #[test]
+    fn duplicates_reducer_keeps_the_highest_version() {
+        let mut index = TempIndex::new();
+        index.index_documents_config.duplicates_reducer =
+            Some(DuplicatesReducer::new(|existing, incoming, fields_ids_map| {
+                let version_id = fields_ids_map.id("version").unwrap();
+                let existing: u64 =
+                    serde_json::from_slice(existing.get(version_id).unwrap()).unwrap();
+                let incoming: u64 =
+                    serde_json::from_slice(incoming.get(version_id).unwrap()).unwrap();
+                if incoming > existing {
+                    KeptDocument::Incoming
+                } else {
+                    KeptDocument::Existing
+                }
+            }));
+
+        index
+            .add_documents(documents!([
+                { "id": 1, "version": 2, "name": "kevin v2" },
+                { "id": 2, "version": 1, "name": "kevina v1" },
+                { "id": 1, "version": 3, "name": "kevin v3" },
+                { "id": 2, "version": 2, "name": "kevina v2" },
+                { "id": 1, "version": 1, "name": "kevin v1" },
+            ]))
+            .unwrap();
+
+        let rtxn = index.read_txn().unwrap();
+        assert_eq!(index.number_of_documents(&rtxn).unwrap(), 2);
+
+        let fields_ids_map = index.fields_ids_map(&rtxn).unwrap();
+        let name_id = fields_ids_map.id("name").unwrap();
+        let external_documents_ids = index.external_documents_ids(&rtxn).unwrap();
+        let names: Vec<_> = ["1", "2"]
+            .iter()
+            .map(|id| {
+                let docid = external_documents_ids.get(id).unwrap();
+                let (_, document) = index.documents(&rtxn, Some(docid)).unwrap().pop().unwrap();
+                let name: String = serde_json::from_slice(document.get(name_id).unwrap()).unwrap();
+                name
+            })
+            .collect();
+        assert_eq!(names, vec!["kevin v3", "kevina v2"]);
+        drop(rtxn);
+
+        // without reducer the last document of the batch is kept
+        index.index_documents_config.duplicates_reducer = None;
+        index
+            .add_documents(documents!([
+                { "id": 1, "version": 5, "name": "kevin v5" },
+                { "id": 1, "version": 4, "name": "kevin v4" },
+            ]))
+            .unwrap();
+
+        let rtxn = index.read_txn().unwrap();
+        let docid = index.external_documents_ids(&rtxn).unwrap().get("1").unwrap();
+        let (_, document) = index.documents(&rtxn, Some(docid)).unwrap().pop().unwrap();
+        let name: String = serde_json::from_slice(document.get(name_id).unwrap()).unwrap();
+        assert_eq!(name, "kevin v4");
     }
 
     #[test]
@@ -866,6 +1415,76 @@ mod tests {
         drop(rtxn);
     }
 
+    #[test]
+    fn configured_documents_ids_rules() {
+        let mut index = TempIndex::new();
+
+        // the dots and colons are not allowed by default
+        let error = index
+            .add_documents(documents!([
+                { "id": "doc-1", "name": "kevin" },
+                { "id": "com.example:doc.2", "name": "kevina" },
+            ]))
+            .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Document identifier `\"com.example:doc.2\"` is invalid. A document identifier can be \
+             of type integer or string, only composed of alphanumeric characters (a-z A-Z 0-9), \
+             hyphens (-) and underscores (_). It contains the invalid characters `.`, `:`. \
+             The document is at position 1 of the payload."
+        );
+
+        index.index_documents_config.document_id_rules = DocumentIdRules {
+            allowed_chars: AllowedDocumentIdChars::AlphanumericAnd(BTreeSet::from([
+                '-', '_', '.', ':', '{', '}',
+            ])),
+            max_length: Some(511),
+        };
+        index
+            .add_documents(documents!([
+                { "id": "com.example:doc.2", "name": "kevina" },
+                { "id": "{67e55044-10b1-426f-9247-bb680e5fe0c8}", "name": "benoit" },
+            ]))
+            .unwrap();
+
+        let long_id = "a".repeat(600);
+        let error =
+            index.add_documents(documents!([{ "id": long_id, "name": "kevin" }])).unwrap_err();
+        assert!(matches!(
+            error,
+            Error::UserError(UserError::InvalidDocumentId {
+                document_nth: Some(0),
+                reason: InvalidDocumentIdReason::TooLong { length: 600, max_length: 511 },
+                ..
+            })
+        ));
+
+        // the deletions follow the same rules
+        index.delete_document("com.example:doc.2");
+        let mut wtxn = index.write_txn().unwrap();
+        let mut builder = DeleteDocuments::new(&mut wtxn, &index).unwrap();
+        assert_eq!(builder.delete_external_id("doc 3"), None);
+        assert_eq!(builder.delete_external_id("com.example:doc.2"), None);
+        match builder.execute().unwrap_err() {
+            Error::UserError(UserError::InvalidDocumentId {
+                document_id,
+                document_nth: None,
+                reason: InvalidDocumentIdReason::InvalidCharacters(chars),
+            }) => {
+                assert_eq!(document_id, "doc 3");
+                assert_eq!(chars, vec![' ']);
+            }
+            error => panic!("unexpected error: {error}"),
+        }
+        wtxn.abort().unwrap();
+
+        let rtxn = index.read_txn().unwrap();
+        let external_documents_ids = index.external_documents_ids(&rtxn).unwrap();
+        assert!(external_documents_ids.get("com.example:doc.2").is_none());
+        assert!(external_documents_ids.get("{67e55044-10b1-426f-9247-bb680e5fe0c8}").is_some());
+        assert_eq!(index.number_of_documents(&rtxn).unwrap(), 1);
+    }
+
     #[test]
     fn complex_documents() {
         let index = TempIndex::new();
@@ -1732,6 +2351,134 @@ mod tests {
         check_ok(&index);
     }
 
+    #[test]
+    fn reindex_the_booleans_of_older_indexes() {
+        let index = TempIndex::new();
+        index
+            .update_settings(|settings| {
+                settings.set_filterable_fields(hashset!(S("in_stock")));
+            })
+            .unwrap();
+        index
+            .add_documents(documents!([
+                { "id": 0, "in_stock": true },
+                { "id": 1, "in_stock": "true" },
+            ]))
+            .unwrap();
+
+        db_snap!(index, facet_id_bool_docids, @r###"
+        1   true  [0, ]
+        "###);
+
+        // we simulate an index created before the booleans had their own database
+        let mut wtxn = index.write_txn().unwrap();
+        index.main.delete::<_, Str>(&mut wtxn, crate::index::main_key::BOOLEAN_FACETS_KEY).unwrap();
+        index.facet_id_bool_docids.clear(&mut wtxn).unwrap();
+        wtxn.commit().unwrap();
+
+        // the next addition doesn't reindex the documents
+        index.add_documents(documents!([{ "id": 2, "in_stock": false }])).unwrap();
+
+        let rtxn = index.read_txn().unwrap();
+        assert!(!index.boolean_facets_indexed(&rtxn).unwrap());
+        drop(rtxn);
+
+        let mut wtxn = index.write_txn().unwrap();
+        let migrated =
+            migrate_boolean_facets(&mut wtxn, &index, &index.indexer_config, &|_| (), &|| false)
+                .unwrap();
+        assert!(migrated);
+        wtxn.commit().unwrap();
+
+        let rtxn = index.read_txn().unwrap();
+        assert!(index.boolean_facets_indexed(&rtxn).unwrap());
+        let in_stock = index.fields_ids_map(&rtxn).unwrap().id("in_stock").unwrap();
+        let true_docids = index.facet_bool_docids(&rtxn, in_stock, true).unwrap();
+        let false_docids = index.facet_bool_docids(&rtxn, in_stock, false).unwrap();
+        let external_ids = index.external_documents_ids(&rtxn).unwrap();
+        assert_eq!(true_docids, RoaringBitmap::from_iter([external_ids.get("0").unwrap()]));
+        assert_eq!(false_docids, RoaringBitmap::from_iter([external_ids.get("2").unwrap()]));
+    }
+
+    #[test]
+    fn report_the_numbers_of_older_indexes() {
+        let index = TempIndex::new();
+        index.add_documents(documents!([{ "id": 0, "price": 1.0 }])).unwrap();
+
+        let rtxn = index.read_txn().unwrap();
+        assert!(index.canonical_numbers(&rtxn).unwrap());
+        drop(rtxn);
+
+        // we simulate an index created before the numbers were canonicalized
+        let mut wtxn = index.write_txn().unwrap();
+        index
+            .main
+            .delete::<_, Str>(&mut wtxn, crate::index::main_key::CANONICAL_NUMBERS_KEY)
+            .unwrap();
+        wtxn.commit().unwrap();
+
+        // adding documents doesn't reindex the existing ones
+        index.add_documents(documents!([{ "id": 1, "price": 2.0 }])).unwrap();
+        let rtxn = index.read_txn().unwrap();
+        assert!(!index.canonical_numbers(&rtxn).unwrap());
+        drop(rtxn);
+
+        // but a settings update that reindexes all the documents does
+        index.update_settings(|settings| settings.set_searchable_fields(vec![S("price")])).unwrap();
+        let rtxn = index.read_txn().unwrap();
+        assert!(index.canonical_numbers(&rtxn).unwrap());
+    }
+
+    #[test]
+    fn custom_tokenizer_config() {
+        static QUICK: Lazy<fst::Set<&[u8]>> = Lazy::new(|| {
+            let bytes = fst::Set::from_iter(["quick"]).unwrap().into_fst().into_inner();
+            fst::Set::new(&*Box::leak(bytes.into_boxed_slice())).unwrap()
+        });
+        let config = TokenizerConfig::new("quick-stop-word", |builder| {
+            builder.stop_words(&QUICK);
+        });
+        assert_ne!(config.fingerprint(), TokenizerConfig::new("default", |_| ()).fingerprint());
+
+        let mut index = TempIndex::new();
+        index.add_documents(documents!([{ "id": 0, "text": "the quick fox" }])).unwrap();
+        let rtxn = index.read_txn().unwrap();
+        assert!(index.word_docids.get(&rtxn, "quick").unwrap().is_some());
+        assert_eq!(index.tokenizer_fingerprint(&rtxn).unwrap(), None);
+        drop(rtxn);
+
+        // the documents tokenized with another configuration are added with a warning, like
+        // the queries are searched, the configuration of the indexed documents is kept
+        index.indexer_config.tokenizer_config = Some(config.clone());
+        index.add_documents(documents!([{ "id": 1, "text": "a quick dog" }])).unwrap();
+        let rtxn = index.read_txn().unwrap();
+        assert_eq!(index.tokenizer_fingerprint(&rtxn).unwrap(), None);
+        drop(rtxn);
+
+        // the settings updates don't reindex the documents with the new configuration
+        index.update_settings(|_| ()).unwrap();
+        let rtxn = index.read_txn().unwrap();
+        assert_eq!(index.tokenizer_fingerprint(&rtxn).unwrap(), None);
+        assert!(index.word_docids.get(&rtxn, "quick").unwrap().is_some());
+        drop(rtxn);
+
+        // unless they are asked to apply it
+        index.update_settings(|settings| settings.apply_tokenizer_config()).unwrap();
+
+        let rtxn = index.read_txn().unwrap();
+        assert_eq!(index.tokenizer_fingerprint(&rtxn).unwrap(), Some(config.fingerprint()));
+        // the stop word of the configuration isn't indexed anymore
+        assert!(index.word_docids.get(&rtxn, "quick").unwrap().is_none());
+        assert!(index.word_docids.get(&rtxn, "fox").unwrap().is_some());
+        assert!(index.word_docids.get(&rtxn, "dog").unwrap().is_some());
+
+        // and the queries tokenized with the configuration ignore it
+        let mut search = index.search(&rtxn);
+        search.query("quick fox");
+        search.tokenizer_config(config);
+        assert_eq!(search.execute().unwrap().documents_ids, vec![0]);
+    }
+
     #[test]
     fn primary_key_must_not_contain_floats() {
         let index = TempIndex::new_with_map_size(4096 * 100);
@@ -1879,4 +2626,449 @@ mod tests {
 
         index.add_documents(doc1).unwrap();
     }
+
+    #[test]
+    fn indexing_metrics() {
+        let mut index = TempIndex::new();
+        let metrics = Arc::new(AtomicMetrics::new());
+        index.indexer_config.metrics = Some(metrics.clone());
+        index.index_documents_config.words_prefix_threshold = Some(1);
+
+        index
+            .update_settings(|settings| {
+                settings.set_filterable_fields(hashset! { S("age") });
+            })
+            .unwrap();
+        index
+            .add_documents(documents!([
+                { "id": 0, "text": "hello world", "age": 12 },
+                { "id": 1, "text": "hello kefir", "age": 4 },
+                { "id": 2, "text": "kefir the cat", "age": 7 },
+            ]))
+            .unwrap();
+
+        assert_eq!(metrics.documents_extracted(), 3);
+
+        let db_written = metrics.db_written();
+        let (entries, bytes) = db_written[db_name::DOCUMENTS];
+        assert_eq!(entries, 3);
+        assert!(bytes > 0);
+
+        let rtxn = index.read_txn().unwrap();
+        let words = index.words_fst(&rtxn).unwrap().len() as u64;
+        assert_eq!(db_written[db_name::WORD_DOCIDS].0, words);
+        let prefixes = index.words_prefixes_fst(&rtxn).unwrap().len() as u64;
+        assert_eq!(db_written[db_name::WORD_PREFIX_DOCIDS].0, prefixes);
+        assert!(db_written[db_name::WORD_PREFIX_POSITION_DOCIDS].0 > 0);
+        assert!(db_written[db_name::WORD_PREFIX_PAIR_PROXIMITY_DOCIDS].0 > 0);
+        assert_eq!(db_written[db_name::FACET_ID_F64_DOCIDS].0, 3);
+        assert!(db_written[db_name::MAIN].1 > 0);
+
+        let phases = metrics.phase_durations();
+        assert!(phases.contains_key("extract_and_write"));
+        assert!(phases.contains_key("prefix_databases"));
+        drop(rtxn);
+
+        // the counters accumulate over the indexing runs
+        index.add_documents(documents!([{ "id": 3, "text": "hello" }])).unwrap();
+        assert_eq!(metrics.documents_extracted(), 4);
+        assert_eq!(metrics.db_written()[db_name::DOCUMENTS].0, 4);
+    }
+
+    #[test]
+    fn dry_run_reports_all_the_errors() {
+        let index = TempIndex::new();
+        index
+            .update_settings(|settings| {
+                settings.set_primary_key(S("id"));
+                settings.set_filterable_fields(hashset! { S("_geo") });
+            })
+            .unwrap();
+        index.add_documents(documents!([{ "id": 0, "title": "kefir" }])).unwrap();
+
+        let documents = documents!([
+            { "id": 0, "title": "kefir the cat" },
+            { "title": "no id" },
+            { "id": "bad id!", "title": "invalid id" },
+            { "id": 3, "_geo": { "lat": "north", "lng": 12 } },
+            { "id": 4, "title": "new" },
+        ]);
+        let rtxn = index.read_txn().unwrap();
+        let report = index.index_documents_config.dry_run(&rtxn, &index, documents).unwrap();
+        assert_eq!(report.documents_count, 5);
+        assert_eq!(report.new_documents, 1);
+
+        let errors: Vec<_> = report
+            .errors
+            .iter()
+            .map(|DryRunError { document_nth, external_id, error }| {
+                (*document_nth, external_id.as_deref(), error)
+            })
+            .collect();
+        assert_eq!(errors.len(), 3);
+        assert!(matches!(errors[0], (Some(1), None, UserError::MissingDocumentId { .. })));
+        assert!(matches!(errors[1], (Some(2), None, UserError::InvalidDocumentId { .. })));
+        assert!(matches!(errors[2], (Some(3), Some("3"), UserError::InvalidGeoField(_))));
+
+        // nothing was written
+        assert_eq!(index.number_of_documents(&rtxn).unwrap(), 1);
+        drop(rtxn);
+
+        // the errors concerning the whole batch are reported without document
+        let index = TempIndex::new();
+        let rtxn = index.read_txn().unwrap();
+        let documents = documents!([{ "title": "kefir" }]);
+        let report = index.index_documents_config.dry_run(&rtxn, &index, documents).unwrap();
+        assert!(matches!(
+            report.errors.as_slice(),
+            [DryRunError {
+                document_nth: None,
+                external_id: None,
+                error: UserError::NoPrimaryKeyCandidateFound
+            }]
+        ));
+    }
+
+    #[test]
+    fn oversized_documents() {
+        let mut index = TempIndex::new();
+        index.indexer_config.max_document_size = Some(1024 * 1024);
+        let huge_text = "lorem ipsum ".repeat(200_000);
+        let documents = || {
+            documents!([
+                { "id": 0, "text": "small" },
+                { "id": 1, "text": huge_text },
+                { "id": 2, "text": "small too" },
+            ])
+        };
+
+        // the oversized document fails the whole addition
+        let mut wtxn = index.write_txn().unwrap();
+        let builder = IndexDocuments::new(
+            &mut wtxn,
+            &index,
+            &index.indexer_config,
+            IndexDocumentsConfig::default(),
+            |_| (),
+            || false,
+        )
+        .unwrap();
+        match builder.add_documents(documents()) {
+            Err(Error::UserError(UserError::DocumentTooLarge { external_id, size, limit })) => {
+                assert_eq!(external_id, "1");
+                assert!(size > huge_text.len());
+                assert_eq!(limit, 1024 * 1024);
+            }
+            Err(error) => panic!("unexpected error {}", error),
+            Ok(_) => panic!("the oversized document must be rejected"),
+        }
+        drop(wtxn);
+
+        // or is skipped and reported
+        index.indexer_config.skip_oversized_documents = true;
+        let mut wtxn = index.write_txn().unwrap();
+        let builder = IndexDocuments::new(
+            &mut wtxn,
+            &index,
+            &index.indexer_config,
+            IndexDocumentsConfig::default(),
+            |_| (),
+            || false,
+        )
+        .unwrap();
+        let (builder, user_error) = builder.add_documents(documents()).unwrap();
+        assert_eq!(user_error.unwrap(), 2);
+        let result = builder.execute().unwrap();
+        assert_eq!(result.indexed_documents, 2);
+        assert_eq!(result.number_of_documents, 2);
+        assert_eq!(result.skipped_documents, vec![S("1")]);
+        wtxn.commit().unwrap();
+
+        db_snap!(index, documents_ids, @"[0, 1, ]");
+        let rtxn = index.read_txn().unwrap();
+        let external_ids = index.external_documents_ids(&rtxn).unwrap();
+        assert!(external_ids.get("1").is_none());
+    }
+
+    #[test]
+    fn lenient_indexing_drops_invalid_fields() {
+        use maplit::btreemap;
+
+        use crate::facet::MixedFacetTypes;
+
+        let index = TempIndex::new();
+        index
+            .update_settings(|settings| {
+                settings.set_filterable_fields(hashset! { S("_geo"), S("price"), S("color") });
+                settings.set_mixed_facet_types(
+                    btreemap! { S("price") => MixedFacetTypes::StrictNumeric },
+                );
+            })
+            .unwrap();
+
+        let documents = || {
+            documents!([
+                { "id": 0, "price": "cheap", "color": "red", "_geo": { "lat": "north", "lng": 2 } },
+                { "id": 1, "price": 10, "color": "blue" },
+            ])
+        };
+
+        // the invalid fields fail the whole addition
+        assert!(index.add_documents(documents()).is_err());
+
+        // or are dropped and reported in lenient mode
+        let mut wtxn = index.write_txn().unwrap();
+        let config = IndexDocumentsConfig { lenient: true, ..Default::default() };
+        let builder =
+            IndexDocuments::new(&mut wtxn, &index, &index.indexer_config, config, |_| (), || false)
+                .unwrap();
+        let (builder, user_error) = builder.add_documents(documents()).unwrap();
+        assert_eq!(user_error.unwrap(), 2);
+        let result = builder.execute().unwrap();
+        wtxn.commit().unwrap();
+
+        assert_eq!(result.indexed_documents, 2);
+        let dropped: Vec<_> = result
+            .field_errors
+            .iter()
+            .map(|e| (e.external_id.as_str(), e.field.as_str()))
+            .collect();
+        assert_eq!(dropped, vec![("0", "price"), ("0", "_geo")]);
+        assert_eq!(
+            result.field_errors[0].message,
+            "Attribute `price` only accepts numbers as facet values but the string `cheap` was found."
+        );
+
+        // the other fields of the document are indexed normally
+        let rtxn = index.read_txn().unwrap();
+        let mut search = crate::Search::new(&rtxn, &index);
+        search.filter(crate::Filter::from_str("color = red").unwrap().unwrap());
+        let crate::SearchResult { documents_ids, .. } = search.execute().unwrap();
+        assert_eq!(documents_ids, vec![0]);
+
+        let fields_ids_map = index.fields_ids_map(&rtxn).unwrap();
+        let price_id = fields_ids_map.id("price").unwrap();
+        let (_, document) = index.documents(&rtxn, Some(0)).unwrap().pop().unwrap();
+        assert!(document.get(price_id).is_none());
+        assert!(document.get(fields_ids_map.id("color").unwrap()).is_some());
+    }
+
+    #[test]
+    fn too_many_fields() {
+        let index = TempIndex::new();
+        let fields = |range: std::ops::Range<usize>| -> Object {
+            range.map(|i| (format!("field{i}"), serde_json::Value::Null)).collect()
+        };
+        let mut document = fields(0..40_000);
+        document.insert(S("id"), serde_json::json!(0));
+        index.add_documents(documents!([document])).unwrap();
+
+        let add_documents = |documents| {
+            let mut wtxn = index.write_txn().unwrap();
+            let builder = IndexDocuments::new(
+                &mut wtxn,
+                &index,
+                &index.indexer_config,
+                IndexDocumentsConfig::default(),
+                |_| (),
+                || false,
+            )
+            .unwrap();
+            builder.add_documents(documents).map(|_| ())
+        };
+
+        // the new fields of the document don't fit with the ones of the index
+        let mut document = fields(40_000..70_000);
+        document.insert(S("id"), serde_json::json!(1));
+        match add_documents(documents!([{ "id": 2 }, document])) {
+            Err(Error::UserError(UserError::AttributeLimitReached { external_id, limit })) => {
+                assert_eq!(external_id.as_deref(), Some("1"));
+                assert_eq!(limit, MAX_FIELDS);
+            }
+            otherwise => panic!("unexpected result {:?}", otherwise.map_err(|e| e.to_string())),
+        }
+
+        // the flattened fields of the document don't fit either
+        let nested = fields(0..70_000);
+        match add_documents(documents!([{ "id": 3, "nested": nested }])) {
+            Err(Error::UserError(UserError::AttributeLimitReached { external_id, .. })) => {
+                assert_eq!(external_id.as_deref(), Some("3"));
+            }
+            otherwise => panic!("unexpected result {:?}", otherwise.map_err(|e| e.to_string())),
+        }
+
+        // the index is left untouched and is still usable
+        let rtxn = index.read_txn().unwrap();
+        assert_eq!(index.fields_count(&rtxn).unwrap(), 40_001);
+        drop(rtxn);
+        index.add_documents(documents!([{ "id": 4, "field0": "kefir" }])).unwrap();
+        let rtxn = index.read_txn().unwrap();
+        assert_eq!(index.number_of_documents(&rtxn).unwrap(), 2);
+        assert_eq!(index.fields_count(&rtxn).unwrap(), 40_001);
+    }
+
+    #[test]
+    fn max_fields() {
+        let index = TempIndex::new();
+        index.update_settings(|settings| settings.set_max_fields(60)).unwrap();
+
+        let fields = |range: std::ops::Range<usize>| -> Object {
+            range.map(|i| (format!("field{i}"), serde_json::json!(i))).collect()
+        };
+        let add_documents = |documents| {
+            let mut wtxn = index.write_txn().unwrap();
+            let builder = IndexDocuments::new(
+                &mut wtxn,
+                &index,
+                &index.indexer_config,
+                IndexDocumentsConfig::default(),
+                |_| (),
+                || false,
+            )
+            .unwrap();
+            let (builder, user_error) = builder.add_documents(documents)?;
+            user_error.unwrap();
+            let result = builder.execute()?;
+            wtxn.commit().unwrap();
+            Ok::<_, Error>(result)
+        };
+
+        // a document with many dynamic fields under the limit is fully indexed, the fields
+        // generated by the flattening are counted too.
+        let mut document = fields(0..50);
+        document.insert(S("id"), serde_json::json!(0));
+        document.insert(S("nested"), serde_json::json!({ "a": 1, "b": { "c": 2 } }));
+        add_documents(documents!([document])).unwrap();
+        let rtxn = index.read_txn().unwrap();
+        // the 50 fields, `id`, `nested`, `nested.a` and `nested.b.c`
+        assert_eq!(index.fields_count(&rtxn).unwrap(), 54);
+        drop(rtxn);
+
+        // by default the documents bringing too many new fields fail the addition
+        let mut document = fields(50..60);
+        document.insert(S("id"), serde_json::json!(1));
+        match add_documents(documents!([{ "id": 2, "field0": "kefir" }, document.clone()])) {
+            Err(Error::UserError(UserError::AttributeLimitReached { external_id, limit })) => {
+                assert_eq!(external_id.as_deref(), Some("1"));
+                assert_eq!(limit, 60);
+            }
+            otherwise => panic!("unexpected result {:?}", otherwise.map_err(|e| e.to_string())),
+        }
+
+        // they can be skipped, without adding any of their fields
+        index
+            .update_settings(|settings| settings.set_max_fields_policy(MaxFieldsPolicy::Skip))
+            .unwrap();
+        let result =
+            add_documents(documents!([{ "id": 2, "field0": "kefir" }, document.clone()])).unwrap();
+        assert_eq!(result.skipped_documents, vec![S("1")]);
+        let rtxn = index.read_txn().unwrap();
+        assert_eq!(index.number_of_documents(&rtxn).unwrap(), 2);
+        assert_eq!(index.fields_count(&rtxn).unwrap(), 54);
+        drop(rtxn);
+
+        // or truncated, keeping the first new fields that fit
+        index
+            .update_settings(|settings| settings.set_max_fields_policy(MaxFieldsPolicy::Truncate))
+            .unwrap();
+        let result = add_documents(documents!([document])).unwrap();
+        assert!(result.skipped_documents.is_empty());
+        let rtxn = index.read_txn().unwrap();
+        let fields_ids_map = index.fields_ids_map(&rtxn).unwrap();
+        assert_eq!(fields_ids_map.len(), 60);
+        assert!(fields_ids_map.id("field55").is_some());
+        assert!(fields_ids_map.id("field56").is_none());
+        let docid = index.external_documents_ids(&rtxn).unwrap().get("1").unwrap();
+        let (_, document) = index.documents(&rtxn, Some(docid)).unwrap().remove(0);
+        assert_eq!(document.iter().count(), 7);
+    }
+
+    #[test]
+    fn reindex_some_documents() {
+        let index = TempIndex::new();
+        index
+            .add_documents(documents!([
+                { "id": 0, "text": "the quick fox" },
+                { "id": 1, "text": "the lazy dog" },
+                { "id": 2, "text": "a brown cat" },
+            ]))
+            .unwrap();
+
+        // the stop words are changed without reindexing, like a fixed tokenizer would do
+        let mut wtxn = index.write_txn().unwrap();
+        let stop_words = fst::Set::from_iter(["quick", "the"]).unwrap();
+        index.put_stop_words(&mut wtxn, &stop_words).unwrap();
+
+        let reindexed = reindex_documents(
+            &mut wtxn,
+            &index,
+            &index.indexer_config,
+            &vec![0, 42].into_iter().collect(),
+            &|_| (),
+            &|| false,
+        )
+        .unwrap();
+        assert_eq!(reindexed, 1);
+        wtxn.commit().unwrap();
+
+        // only the document 0 lost its stop words, "quick" is not in any document anymore
+        db_snap!(index, word_docids, @r###"
+        a                [2, ]
+        brown            [2, ]
+        cat              [2, ]
+        dog              [1, ]
+        fox              [0, ]
+        lazy             [1, ]
+        the              [1, ]
+        "###);
+        db_snap!(index, documents_ids, @"[0, 1, 2, ]");
+
+        let rtxn = index.read_txn().unwrap();
+        let words_fst = index.words_fst(&rtxn).unwrap();
+        assert!(!words_fst.contains("quick"));
+        assert!(words_fst.contains("the"));
+        assert_eq!(index.external_documents_ids(&rtxn).unwrap().get("0"), Some(0));
+        let (_, document) = index.documents(&rtxn, Some(0)).unwrap().remove(0);
+        let fields_ids_map = index.fields_ids_map(&rtxn).unwrap();
+        let text = document.get(fields_ids_map.id("text").unwrap()).unwrap();
+        assert_eq!(text, br#""the quick fox""#);
+    }
+
+    #[test]
+    fn bucketed_positions_shrink_word_position_docids() {
+        let text = vec!["word"; 64].join(" ");
+        let word_positions = |index: &TempIndex| {
+            let rtxn = index.read_txn().unwrap();
+            index
+                .word_position_docids
+                .iter(&rtxn)
+                .unwrap()
+                .filter(|entry| entry.as_ref().unwrap().0 .0 == "word")
+                .count()
+        };
+
+        // an index created by an older version stores the exact positions
+        let exact_index = TempIndex::new();
+        exact_index.add_documents(documents!([{ "id": 0 }])).unwrap();
+        let mut wtxn = exact_index.write_txn().unwrap();
+        exact_index.main.delete::<_, Str>(&mut wtxn, main_key::BUCKETED_POSITIONS_KEY).unwrap();
+        wtxn.commit().unwrap();
+        exact_index.add_documents(documents!([{ "id": 1, "text": text }])).unwrap();
+
+        let bucketed_index = TempIndex::new();
+        bucketed_index.add_documents(documents!([{ "id": 0 }, { "id": 1, "text": text }])).unwrap();
+
+        // the 16 exact positions followed by the buckets 16 and 32
+        assert_eq!(word_positions(&exact_index), 64);
+        assert_eq!(word_positions(&bucketed_index), 18);
+
+        // reindexing all the documents buckets the positions of the older indexes
+        exact_index
+            .update_settings(|settings| settings.set_searchable_fields(vec![S("text")]))
+            .unwrap();
+        let rtxn = exact_index.read_txn().unwrap();
+        assert!(exact_index.bucketed_positions(&rtxn).unwrap());
+        drop(rtxn);
+        assert_eq!(word_positions(&exact_index), 18);
+    }
 }
@@ -2,7 +2,8 @@ use std::borrow::Cow;
 use std::collections::hash_map::Entry;
 use std::collections::{HashMap, HashSet};
 use std::fs::File;
-use std::io::{Read, Seek};
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::mem;
 
 use fxhash::FxHashMap;
 use heed::RoTxn;
@@ -12,17 +13,32 @@ use roaring::RoaringBitmap;
 use serde_json::Value;
 use smartstring::SmartString;
 
-use super::helpers::{create_sorter, create_writer, keep_latest_obkv, merge_obkvs, MergeFn};
-use super::{IndexDocumentsMethod, IndexerConfig};
-use crate::documents::{DocumentsBatchIndex, EnrichedDocument, EnrichedDocumentsBatchReader};
+use super::enrich::{geo_field_id, validate_geo_from_json};
+use super::extract::extract_facet_values;
+use super::helpers::{
+    create_sorter, create_writer, is_counted_in_field_distribution, keep_latest_obkv, merge_obkvs,
+    MergeFn,
+};
+use super::{
+    DocumentId, DuplicatesReducer, FieldError, IndexDocumentsMethod, IndexerConfig, KeptDocument,
+};
+use crate::documents::{
+    DocumentsBatchIndex, EnrichedDocument, EnrichedDocumentsBatchCursor,
+    EnrichedDocumentsBatchReader,
+};
 use crate::error::{Error, InternalError, UserError};
+use crate::facet::MixedFacetTypes;
 use crate::index::{db_name, main_key};
 use crate::update::{AvailableDocumentsIds, ClearDocuments, UpdateIndexingStep};
 use crate::{
     ExternalDocumentsIds, FieldDistribution, FieldId, FieldIdMapMissingEntry, FieldsIdsMap, Index,
-    Result, BEU32,
+    MaxFieldsPolicy, Result, BEU32, MAX_FIELDS,
 };
 
+/// The number of external ids of the documents batch resolved at once
+/// against the external documents ids of the index.
+const EXTERNAL_IDS_CHUNK_SIZE: usize = 100_000;
+
 pub struct TransformOutput {
     pub primary_key: String,
     pub fields_ids_map: FieldsIdsMap,
@@ -47,6 +63,7 @@ pub struct Transform<'a, 'i> {
 
     indexer_settings: &'a IndexerConfig,
     pub autogenerate_docids: bool,
+    pub preserve_documents_ids: bool,
     pub index_documents_method: IndexDocumentsMethod,
     available_documents_ids: AvailableDocumentsIds,
 
@@ -57,27 +74,158 @@ pub struct Transform<'a, 'i> {
     // To increase the cache locality and decrease the heap usage we use compact smartstring.
     new_external_documents_ids_builder: FxHashMap<SmartString<smartstring::Compact>, u64>,
     documents_count: usize,
+    duplicates_reducer: Option<DuplicatesReducer>,
+    /// The documents kept so far for each external id of the batch,
+    /// only created when there is a duplicates reducer.
+    batch_documents: Option<BatchDocuments>,
+    /// The external ids of the documents skipped because they were too large.
+    pub(crate) skipped_documents: Vec<String>,
+    /// Whether the fields whose values can't be indexed are dropped from the documents,
+    /// see [`IndexDocumentsConfig::lenient`](super::IndexDocumentsConfig::lenient).
+    pub lenient: bool,
+    /// The fields dropped from the documents in lenient mode.
+    pub(crate) field_errors: Vec<FieldError>,
+    /// The maximum number of fields of the index, see [`Index::max_fields`].
+    max_fields: Option<usize>,
+    max_fields_policy: MaxFieldsPolicy,
+}
+
+/// The maximum size of a document, the obkv and grenad entries can't exceed a `u32` length.
+const MAX_DOCUMENT_SIZE: usize = u32::MAX as usize;
+
+/// The checks run on the fields of the documents in lenient mode, they find the fields the
+/// extraction would otherwise fail on.
+struct LenientChecks {
+    /// The id of the `_geo` field in the batch, when it must be valid.
+    geo_field_id: Option<FieldId>,
+    /// The faceted fields that only accept numbers.
+    strict_numeric_fields: HashSet<String>,
+}
+
+impl LenientChecks {
+    fn new(
+        rtxn: &RoTxn,
+        index: &Index,
+        fields_index: &DocumentsBatchIndex,
+    ) -> Result<LenientChecks> {
+        let faceted_fields = index.user_defined_faceted_fields(rtxn)?;
+        let strict_numeric_fields = index
+            .mixed_facet_types(rtxn)?
+            .into_iter()
+            .filter(|(field, mode)| {
+                *mode == MixedFacetTypes::StrictNumeric && crate::is_faceted(field, &faceted_fields)
+            })
+            .map(|(field, _)| field)
+            .collect();
+
+        Ok(LenientChecks {
+            geo_field_id: geo_field_id(rtxn, index, fields_index)?,
+            strict_numeric_fields,
+        })
+    }
+
+    /// Returns the error the field would make the indexing fail with, if any.
+    fn field_error(
+        &self,
+        fields_index: &DocumentsBatchIndex,
+        field_id: FieldId,
+        value: &[u8],
+        document_id: &DocumentId,
+    ) -> Result<Option<UserError>> {
+        if self.geo_field_id == Some(field_id) {
+            return Ok(validate_geo_from_json(document_id, value)?.err().map(UserError::from));
+        }
+
+        let name = match fields_index.name(field_id) {
+            Some(name) => name,
+            None => return Ok(None),
+        };
+        if !self.strict_numeric_fields.iter().any(|field| crate::is_faceted_by(field, name)) {
+            return Ok(None);
+        }
+
+        // The nested fields are flattened to check the values of the strict numeric
+        // fields they contain, as the extraction would.
+        let value: Value = serde_json::from_slice(value).map_err(InternalError::SerdeJson)?;
+        let object = serde_json::Map::from_iter([(name.to_string(), value)]);
+        for (field, value) in flatten_serde_json::flatten(&object) {
+            if !self.strict_numeric_fields.contains(&field) {
+                continue;
+            }
+            let (_, strings, _) = extract_facet_values(&value);
+            if let Some((_, original)) = strings.into_iter().find(|(n, _)| !n.is_empty()) {
+                return Ok(Some(UserError::InvalidNumericFacetValue { field, value: original }));
+            }
+        }
+
+        Ok(None)
+    }
+}
+
+/// The documents kept so far for each external id of the batch, they are written one after
+/// the other in a temporary file and only their offsets are kept in memory.
+struct BatchDocuments {
+    file: File,
+    file_len: u64,
+    /// The offset and the length of the document kept for each external id.
+    offsets: HashMap<String, (u64, usize)>,
+}
+
+impl BatchDocuments {
+    fn new() -> Result<BatchDocuments> {
+        Ok(BatchDocuments { file: tempfile::tempfile()?, file_len: 0, offsets: HashMap::new() })
+    }
+
+    /// Reads the document kept for this external id into the buffer,
+    /// returns `false` if there is none.
+    fn get(&mut self, external_id: &str, buffer: &mut Vec<u8>) -> Result<bool> {
+        let (offset, len) = match self.offsets.get(external_id) {
+            Some(&entry) => entry,
+            None => return Ok(false),
+        };
+        buffer.resize(len, 0);
+        self.file.seek(SeekFrom::Start(offset))?;
+        self.file.read_exact(buffer)?;
+        Ok(true)
+    }
+
+    /// Keeps this document for this external id, in place of the previous one.
+    fn insert(&mut self, external_id: &str, document: &[u8]) -> Result<()> {
+        self.file.seek(SeekFrom::Start(self.file_len))?;
+        self.file.write_all(document)?;
+        self.offsets.insert(external_id.to_string(), (self.file_len, document.len()));
+        self.file_len += document.len() as u64;
+        Ok(())
+    }
 }
 
 /// Create a mapping between the field ids found in the document batch and the one that were
 /// already present in the index.
 ///
 /// If new fields are present in the addition, they are added to the index field ids map.
+/// The fields that don't fit in the fields ids map are not mapped, the first document
+/// containing one of them is rejected.
+///
+/// When the index has a maximum number of fields, the new fields are not inserted here but
+/// along with the first document containing them, as the documents that don't fit must not
+/// grow the fields ids map.
 fn create_fields_mapping(
     index_field_map: &mut FieldsIdsMap,
     batch_field_map: &DocumentsBatchIndex,
-) -> Result<HashMap<FieldId, FieldId>> {
+    insert_new_fields: bool,
+) -> HashMap<FieldId, FieldId> {
     batch_field_map
         .iter()
         // we sort by id here to ensure a deterministic mapping of the fields, that preserves
         // the original ordering.
         .sorted_by_key(|(&id, _)| id)
-        .map(|(field, name)| match index_field_map.id(name) {
-            Some(id) => Ok((*field, id)),
-            None => index_field_map
-                .insert(name)
-                .ok_or(Error::UserError(UserError::AttributeLimitReached))
-                .map(|id| (*field, id)),
+        .filter_map(|(field, name)| {
+            let id = if insert_new_fields {
+                index_field_map.insert(name)
+            } else {
+                index_field_map.id(name)
+            };
+            id.map(|id| (*field, id))
         })
         .collect()
 }
@@ -89,6 +237,8 @@ impl<'a, 'i> Transform<'a, 'i> {
         indexer_settings: &'a IndexerConfig,
         index_documents_method: IndexDocumentsMethod,
         autogenerate_docids: bool,
+        preserve_documents_ids: bool,
+        duplicates_reducer: Option<DuplicatesReducer>,
     ) -> Result<Self> {
         // We must choose the appropriate merge function for when two or more documents
         // with the same user id must be merged or fully replaced in the same batch.
@@ -124,6 +274,7 @@ impl<'a, 'i> Transform<'a, 'i> {
             fields_ids_map: index.fields_ids_map(wtxn)?,
             indexer_settings,
             autogenerate_docids,
+            preserve_documents_ids,
             available_documents_ids: AvailableDocumentsIds::from_documents_ids(
                 &documents_ids,
                 &soft_deleted_documents_ids,
@@ -135,6 +286,16 @@ impl<'a, 'i> Transform<'a, 'i> {
             new_documents_ids: RoaringBitmap::new(),
             new_external_documents_ids_builder: FxHashMap::default(),
             documents_count: 0,
+            batch_documents: duplicates_reducer
+                .as_ref()
+                .map(|_| BatchDocuments::new())
+                .transpose()?,
+            duplicates_reducer,
+            skipped_documents: Vec::new(),
+            lenient: false,
+            field_errors: Vec::new(),
+            max_fields: index.max_fields(wtxn)?,
+            max_fields_policy: index.max_fields_policy(wtxn)?,
         })
     }
 
@@ -153,14 +314,34 @@ impl<'a, 'i> Transform<'a, 'i> {
         let (mut cursor, fields_index) = reader.into_cursor_and_fields_index();
 
         let external_documents_ids = self.index.external_documents_ids(wtxn)?;
+        let existing_documents_ids = if external_documents_ids.is_empty() {
+            FxHashMap::default()
+        } else {
+            resolve_existing_documents_ids(&mut cursor, &external_documents_ids)?
+        };
 
-        let mapping = create_fields_mapping(&mut self.fields_ids_map, &fields_index)?;
+        let mut mapping = create_fields_mapping(
+            &mut self.fields_ids_map,
+            &fields_index,
+            self.max_fields.is_none(),
+        );
 
         let primary_key = cursor.primary_key().to_string();
-        let primary_key_id =
-            self.fields_ids_map.insert(&primary_key).ok_or(UserError::AttributeLimitReached)?;
+        let primary_key_id = self
+            .fields_ids_map
+            .insert(&primary_key)
+            .ok_or(UserError::AttributeLimitReached { external_id: None, limit: MAX_FIELDS })?;
+
+        // In lenient mode the fields that would make the indexing fail are checked here to be
+        // dropped from the documents, the others are indexed as usual.
+        let lenient_checks = if self.lenient {
+            Some(LenientChecks::new(wtxn, self.index, &fields_index)?)
+        } else {
+            None
+        };
 
         let mut obkv_buffer = Vec::new();
+        let mut existing_obkv_buffer = Vec::new();
         let mut documents_count = 0;
         let mut docid_buffer: Vec<u8> = Vec::new();
         let mut field_buffer: Vec<(u16, Cow<[u8]>)> = Vec::new();
@@ -189,9 +370,60 @@ impl<'a, 'i> Transform<'a, 'i> {
                 field_buffer_cache.push((primary_key_id, Cow::from(&docid_buffer)));
             }
 
+            // The documents bringing too many new fields are rejected or skipped before any
+            // of their fields is inserted, the truncated ones drop the fields that don't fit.
+            if let Some(max_fields) = self.max_fields {
+                if self.max_fields_policy != MaxFieldsPolicy::Truncate
+                    && self.fields_ids_map.len()
+                        + self.new_fields_count(&fields_index, &document)?
+                        > max_fields
+                {
+                    if self.max_fields_policy == MaxFieldsPolicy::Reject {
+                        return Err(UserError::AttributeLimitReached {
+                            external_id: Some(external_id.to_string()),
+                            limit: max_fields,
+                        }
+                        .into());
+                    }
+                    self.skipped_documents.push(external_id.to_string());
+                    field_buffer = drop_and_reuse(field_buffer_cache);
+                    docid_buffer.clear();
+                    continue;
+                }
+            }
+
             for (k, v) in document.iter() {
-                let mapped_id =
-                    *mapping.get(&k).ok_or(InternalError::FieldIdMappingMissingEntry { key: k })?;
+                if let Some(checks) = &lenient_checks {
+                    if let Some(error) = checks.field_error(&fields_index, k, v, &document_id)? {
+                        self.field_errors.push(FieldError {
+                            external_id: external_id.to_string(),
+                            field: fields_index.name(k).unwrap_or_default().to_string(),
+                            message: error.to_string(),
+                        });
+                        continue;
+                    }
+                }
+                let mapped_id = match mapping.get(&k).copied() {
+                    Some(id) => id,
+                    None if self.max_fields.is_some() => {
+                        let name = fields_index.name(k).ok_or(FieldIdMapMissingEntry::FieldId {
+                            field_id: k,
+                            process: "Mapping the fields of the documents batch.",
+                        })?;
+                        match self.insert_field(name, external_id)? {
+                            Some(id) => *mapping.entry(k).or_insert(id),
+                            // the field doesn't fit and is dropped from the document.
+                            None => continue,
+                        }
+                    }
+                    None => {
+                        return Err(UserError::AttributeLimitReached {
+                            external_id: Some(external_id.to_string()),
+                            limit: MAX_FIELDS,
+                        }
+                        .into())
+                    }
+                };
                 field_buffer_cache.push((mapped_id, Cow::from(v)));
             }
 
@@ -200,12 +432,61 @@ impl<'a, 'i> Transform<'a, 'i> {
             // fieldids map keys order.
             field_buffer_cache.sort_unstable_by(|(f1, _), (f2, _)| f1.cmp(f2));
 
+            // We check the size of the document before building it, every field is stored
+            // with its id and the length of its value, which can't exceed a u32.
+            let document_size: usize = field_buffer_cache
+                .iter()
+                .map(|(_, v)| mem::size_of::<FieldId>() + mem::size_of::<u32>() + v.len())
+                .sum();
+            let max_document_size = self
+                .indexer_settings
+                .max_document_size
+                .map_or(MAX_DOCUMENT_SIZE, |size| size.min(MAX_DOCUMENT_SIZE));
+            if document_size > max_document_size {
+                if !self.indexer_settings.skip_oversized_documents {
+                    return Err(UserError::DocumentTooLarge {
+                        external_id: external_id.to_string(),
+                        size: document_size,
+                        limit: max_document_size,
+                    }
+                    .into());
+                }
+                self.skipped_documents.push(external_id.to_string());
+                field_buffer = drop_and_reuse(field_buffer_cache);
+                docid_buffer.clear();
+                continue;
+            }
+
             // Build the new obkv document.
             let mut writer = obkv::KvWriter::new(&mut obkv_buffer);
             for (k, v) in field_buffer_cache.iter() {
                 writer.insert(*k, v)?;
             }
 
+            if let (Some(reducer), Some(batch_documents)) =
+                (&self.duplicates_reducer, &mut self.batch_documents)
+            {
+                let kept = if batch_documents.get(external_id, &mut existing_obkv_buffer)? {
+                    reducer.reduce(
+                        KvReader::new(&existing_obkv_buffer),
+                        KvReader::new(&obkv_buffer),
+                        &self.fields_ids_map,
+                    )
+                } else {
+                    KeptDocument::Incoming
+                };
+
+                // the incoming document is ignored, as if it wasn't part of the batch.
+                if kept == KeptDocument::Existing {
+                    documents_count += 1;
+                    field_buffer = drop_and_reuse(field_buffer_cache);
+                    docid_buffer.clear();
+                    obkv_buffer.clear();
+                    continue;
+                }
+                batch_documents.insert(external_id, &obkv_buffer)?;
+            }
+
             let mut original_docid = None;
 
             let docid = match self.new_external_documents_ids_builder.entry((*external_id).into()) {
@@ -213,14 +494,19 @@ impl<'a, 'i> Transform<'a, 'i> {
                 Entry::Vacant(entry) => {
                     // If the document was already in the db we mark it as a replaced document.
                     // It'll be deleted later. We keep its original docid to insert it in the grenad.
-                    if let Some(docid) = external_documents_ids.get(entry.key()) {
+                    if let Some(&docid) = existing_documents_ids.get(entry.key().as_str()) {
                         self.replaced_documents_ids.insert(docid);
                         original_docid = Some(docid);
                     }
-                    let docid = self
-                        .available_documents_ids
-                        .next()
-                        .ok_or(UserError::DocumentLimitReached)?;
+                    // The replaced document is reinserted under its original docid if we
+                    // must preserve it, otherwise we give it a new one.
+                    let docid = match original_docid {
+                        Some(docid) if self.preserve_documents_ids => docid,
+                        _ => self
+                            .available_documents_ids
+                            .next()
+                            .ok_or(UserError::NoMoreDocumentIds)?,
+                    };
                     entry.insert(docid as u64);
                     docid
                 }
@@ -249,7 +535,7 @@ impl<'a, 'i> Transform<'a, 'i> {
                 } else {
                     // we associate the base document with the new key, everything will get merged later.
                     self.original_sorter.insert(docid.to_be_bytes(), base_obkv)?;
-                    match self.flatten_from_fields_ids_map(KvReader::new(base_obkv))? {
+                    match self.flatten_from_fields_ids_map(KvReader::new(base_obkv), external_id)? {
                         Some(buffer) => {
                             self.flattened_sorter.insert(docid.to_be_bytes(), &buffer)?
                         }
@@ -263,7 +549,7 @@ impl<'a, 'i> Transform<'a, 'i> {
                 // We use the extracted/generated user id as the key for this document.
                 self.original_sorter.insert(docid.to_be_bytes(), obkv_buffer.clone())?;
 
-                match self.flatten_from_fields_ids_map(KvReader::new(&obkv_buffer))? {
+                match self.flatten_from_fields_ids_map(KvReader::new(&obkv_buffer), external_id)? {
                     Some(buffer) => self.flattened_sorter.insert(docid.to_be_bytes(), &buffer)?,
                     None => {
                         self.flattened_sorter.insert(docid.to_be_bytes(), obkv_buffer.clone())?
@@ -295,7 +581,11 @@ impl<'a, 'i> Transform<'a, 'i> {
 
     // Flatten a document from the fields ids map contained in self and insert the new
     // created fields. Returns `None` if the document doesn't need to be flattened.
-    fn flatten_from_fields_ids_map(&mut self, obkv: KvReader<FieldId>) -> Result<Option<Vec<u8>>> {
+    fn flatten_from_fields_ids_map(
+        &mut self,
+        obkv: KvReader<FieldId>,
+        external_id: &str,
+    ) -> Result<Option<Vec<u8>>> {
         if obkv
             .iter()
             .all(|(_, value)| !json_depth_checker::should_flatten_from_unchecked_slice(value))
@@ -334,7 +624,11 @@ impl<'a, 'i> Transform<'a, 'i> {
         // Once we have the flattened version we insert all the new generated fields_ids
         // (if any) in the fields ids map and serialize the value.
         for (key, value) in flattened.into_iter() {
-            let fid = self.fields_ids_map.insert(&key).ok_or(UserError::AttributeLimitReached)?;
+            let fid = match self.insert_field(&key, external_id)? {
+                Some(fid) => fid,
+                // the field doesn't fit and is not indexed.
+                None => continue,
+            };
             let value = serde_json::to_vec(&value).map_err(InternalError::SerdeJson)?;
             key_value.push((fid, value.into()));
         }
@@ -348,6 +642,73 @@ impl<'a, 'i> Transform<'a, 'i> {
         Ok(Some(buffer))
     }
 
+    /// Returns the id of the field, inserting it in the fields ids map if it is new.
+    ///
+    /// Returns `None` if the field doesn't fit in the maximum number of fields of the index
+    /// and the documents are truncated, an error if they are rejected.
+    fn insert_field(&mut self, name: &str, external_id: &str) -> Result<Option<FieldId>> {
+        if let Some(id) = self.fields_ids_map.id(name) {
+            return Ok(Some(id));
+        }
+
+        match self.max_fields {
+            Some(max_fields) if self.fields_ids_map.len() >= max_fields => {
+                match self.max_fields_policy {
+                    MaxFieldsPolicy::Truncate => Ok(None),
+                    // the documents that don't fit are rejected or skipped before
+                    // their fields are inserted, it is a safety net.
+                    MaxFieldsPolicy::Reject | MaxFieldsPolicy::Skip => {
+                        Err(UserError::AttributeLimitReached {
+                            external_id: Some(external_id.to_string()),
+                            limit: max_fields,
+                        }
+                        .into())
+                    }
+                }
+            }
+            _ => match self.fields_ids_map.insert(name) {
+                Some(id) => Ok(Some(id)),
+                None => Err(UserError::AttributeLimitReached {
+                    external_id: Some(external_id.to_string()),
+                    limit: MAX_FIELDS,
+                }
+                .into()),
+            },
+        }
+    }
+
+    /// Returns the number of fields of the document that are not in the fields ids map yet,
+    /// the fields generated by the flattening of its nested objects included.
+    fn new_fields_count(
+        &self,
+        fields_index: &DocumentsBatchIndex,
+        document: &KvReader<FieldId>,
+    ) -> Result<usize> {
+        let mut new_fields = HashSet::new();
+        let mut nested = serde_json::Map::new();
+        for (k, v) in document.iter() {
+            let name = fields_index.name(k).ok_or(FieldIdMapMissingEntry::FieldId {
+                field_id: k,
+                process: "Counting the new fields of a document.",
+            })?;
+            if self.fields_ids_map.id(name).is_none() {
+                new_fields.insert(name.to_string());
+            }
+            if json_depth_checker::should_flatten_from_unchecked_slice(v) {
+                let value = serde_json::from_slice(v).map_err(InternalError::SerdeJson)?;
+                nested.insert(name.to_string(), value);
+            }
+        }
+
+        for key in flatten_serde_json::flatten(&nested).keys() {
+            if self.fields_ids_map.id(key).is_none() {
+                new_fields.insert(key.clone());
+            }
+        }
+
+        Ok(new_fields.len())
+    }
+
     /// Generate an obkv from a slice of key / value sorted by key.
     fn create_obkv_from_key_value(
         key_value: &mut [(FieldId, Cow<[u8]>)],
@@ -422,23 +783,22 @@ impl<'a, 'i> Transform<'a, 'i> {
                 InternalError::DatabaseMissingEntry { db_name: db_name::DOCUMENTS, key: None },
             )?;
 
-            for (key, _) in obkv.iter() {
+            for (key, value) in obkv.iter() {
+                if !is_counted_in_field_distribution(value) {
+                    continue;
+                }
                 let name =
                     self.fields_ids_map.name(key).ok_or(FieldIdMapMissingEntry::FieldId {
                         field_id: key,
                         process: "Computing field distribution in transform.",
                     })?;
-                // We checked that the document was in the db earlier. If we can't find it it means
-                // there is an inconsistency between the field distribution and the field id map.
-                let field =
-                    field_distribution.get_mut(name).ok_or(FieldIdMapMissingEntry::FieldId {
-                        field_id: key,
-                        process: "Accessing field distribution in transform.",
-                    })?;
-                *field -= 1;
-                if *field == 0 {
-                    // since we were able to get the field right before it's safe to unwrap here
-                    field_distribution.remove(name).unwrap();
+                // An inconsistent field distribution must not make the indexing fail,
+                // the counters are therefore never decremented below zero.
+                if let Some(count) = field_distribution.get_mut(name) {
+                    *count = count.saturating_sub(1);
+                    if *count == 0 {
+                        field_distribution.remove(name);
+                    }
                 }
             }
         }
@@ -497,7 +857,10 @@ impl<'a, 'i> Transform<'a, 'i> {
             // We increment all the field of the current document in the field distribution.
             let obkv = KvReader::new(val);
 
-            for (key, _) in obkv.iter() {
+            for (key, value) in obkv.iter() {
+                if !is_counted_in_field_distribution(value) {
+                    continue;
+                }
                 let name =
                     self.fields_ids_map.name(key).ok_or(FieldIdMapMissingEntry::FieldId {
                         field_id: key,
@@ -613,37 +976,11 @@ impl<'a, 'i> Transform<'a, 'i> {
 
             // Once we have the document. We're going to flatten it
             // and insert it in the flattened sorter.
-            let mut doc = serde_json::Map::new();
-
-            let reader = obkv::KvReader::new(buffer);
-            for (k, v) in reader.iter() {
-                let key = new_fields_ids_map.name(k).ok_or(FieldIdMapMissingEntry::FieldId {
-                    field_id: k,
-                    process: "Accessing field distribution in transform.",
-                })?;
-                let value = serde_json::from_slice::<serde_json::Value>(v)
-                    .map_err(InternalError::SerdeJson)?;
-                doc.insert(key.to_string(), value);
-            }
-
-            let flattened = flatten_serde_json::flatten(&doc);
-
-            // Once we have the flattened version we can convert it back to obkv and
-            // insert all the new generated fields_ids (if any) in the fields ids map.
-            let mut buffer: Vec<u8> = Vec::new();
-            let mut writer = KvWriter::new(&mut buffer);
-            let mut flattened: Vec<_> = flattened.into_iter().collect();
-            // we reorder the field to get all the known field first
-            flattened.sort_unstable_by_key(|(key, _)| {
-                new_fields_ids_map.id(key).unwrap_or(FieldId::MAX)
-            });
-
-            for (key, value) in flattened {
-                let fid =
-                    new_fields_ids_map.insert(&key).ok_or(UserError::AttributeLimitReached)?;
-                let value = serde_json::to_vec(&value).map_err(InternalError::SerdeJson)?;
-                writer.insert(fid, &value)?;
-            }
+            let buffer = flatten_stored_document(
+                KvReader::new(buffer),
+                &mut new_fields_ids_map,
+                self.max_fields,
+            )?;
             flattened_writer.insert(docid.to_be_bytes(), &buffer)?;
         }
 
@@ -675,6 +1012,164 @@ impl<'a, 'i> Transform<'a, 'i> {
 
         Ok(output)
     }
+
+    /// Returns a `TransformOutput` with the stored content of the given documents, unchanged,
+    /// that replaces them under their own internal ids once indexed. The ids that are not
+    /// the ids of documents of the index are ignored.
+    pub fn prepare_for_partial_reindexing(
+        self,
+        wtxn: &mut heed::RwTxn<'i, '_>,
+        documents_ids: &RoaringBitmap,
+    ) -> Result<TransformOutput> {
+        // There already has been a document addition, the primary key should be set by now.
+        let primary_key = self
+            .index
+            .primary_key(wtxn)?
+            .ok_or(InternalError::DatabaseMissingEntry {
+                db_name: db_name::MAIN,
+                key: Some(main_key::PRIMARY_KEY_KEY),
+            })?
+            .to_string();
+        let mut fields_ids_map = self.index.fields_ids_map(wtxn)?;
+        let field_distribution = self.index.field_distribution(wtxn)?;
+        let external_documents_ids = self.index.external_documents_ids(wtxn)?;
+
+        let soft_deleted_documents_ids = self.index.soft_deleted_documents_ids(wtxn)?;
+        let documents_ids =
+            (self.index.documents_ids(wtxn)? - soft_deleted_documents_ids) & documents_ids;
+
+        let mut original_writer = create_writer(
+            self.indexer_settings.chunk_compression_type,
+            self.indexer_settings.chunk_compression_level,
+            tempfile::tempfile()?,
+        );
+        let mut flattened_writer = create_writer(
+            self.indexer_settings.chunk_compression_type,
+            self.indexer_settings.chunk_compression_level,
+            tempfile::tempfile()?,
+        );
+
+        let documents = self.index.documents.remap_data_type::<heed::types::ByteSlice>();
+        for docid in &documents_ids {
+            let bytes = documents.get(wtxn, &BEU32::new(docid))?.ok_or(
+                InternalError::DatabaseMissingEntry { db_name: db_name::DOCUMENTS, key: None },
+            )?;
+            original_writer.insert(docid.to_be_bytes(), bytes)?;
+
+            let buffer = flatten_stored_document(
+                KvReader::new(bytes),
+                &mut fields_ids_map,
+                self.max_fields,
+            )?;
+            flattened_writer.insert(docid.to_be_bytes(), &buffer)?;
+        }
+
+        let mut original_documents = original_writer.into_inner()?;
+        original_documents.rewind()?;
+
+        let mut flattened_documents = flattened_writer.into_inner()?;
+        flattened_documents.rewind()?;
+
+        Ok(TransformOutput {
+            primary_key,
+            fields_ids_map,
+            field_distribution,
+            external_documents_ids: external_documents_ids.into_static(),
+            new_documents_ids: documents_ids.clone(),
+            documents_count: documents_ids.len() as usize,
+            replaced_documents_ids: documents_ids,
+            original_documents,
+            flattened_documents,
+        })
+    }
+}
+
+/// Flattens a document stored in the index, the fields that are only generated
+/// by the flattening are inserted in the `FieldsIdsMap`.
+///
+/// The new fields that don't fit in the maximum number of fields are not indexed, they
+/// were dropped when the document was truncated.
+fn flatten_stored_document(
+    obkv: obkv::KvReaderU16,
+    fields_ids_map: &mut FieldsIdsMap,
+    max_fields: Option<usize>,
+) -> Result<Vec<u8>> {
+    let mut doc = serde_json::Map::new();
+    for (k, v) in obkv.iter() {
+        let key = fields_ids_map.name(k).ok_or(FieldIdMapMissingEntry::FieldId {
+            field_id: k,
+            process: "Accessing field distribution in transform.",
+        })?;
+        let value =
+            serde_json::from_slice::<serde_json::Value>(v).map_err(InternalError::SerdeJson)?;
+        doc.insert(key.to_string(), value);
+    }
+
+    let flattened = flatten_serde_json::flatten(&doc);
+
+    // Once we have the flattened version we can convert it back to obkv and
+    // insert all the new generated fields_ids (if any) in the fields ids map.
+    let mut buffer: Vec<u8> = Vec::new();
+    let mut writer = KvWriter::new(&mut buffer);
+    let mut flattened: Vec<_> = flattened.into_iter().collect();
+    // we reorder the field to get all the known field first
+    flattened.sort_unstable_by_key(|(key, _)| fields_ids_map.id(key).unwrap_or(FieldId::MAX));
+
+    for (key, value) in flattened {
+        let fid = match max_fields {
+            Some(max_fields)
+                if fields_ids_map.id(&key).is_none() && fields_ids_map.len() >= max_fields =>
+            {
+                continue
+            }
+            _ => fields_ids_map
+                .insert(&key)
+                .ok_or(UserError::AttributeLimitReached { external_id: None, limit: MAX_FIELDS })?,
+        };
+        let value = serde_json::to_vec(&value).map_err(InternalError::SerdeJson)?;
+        writer.insert(fid, &value)?;
+    }
+
+    Ok(buffer)
+}
+
+/// Returns the internal ids of the documents of the batch that already exist in the index.
+///
+/// The external ids are sorted and resolved by chunks, which is faster than searching
+/// the external documents ids of the index for every document. The cursor is reset afterward.
+fn resolve_existing_documents_ids<R: Read + Seek>(
+    cursor: &mut EnrichedDocumentsBatchCursor<R>,
+    external_documents_ids: &ExternalDocumentsIds,
+) -> Result<FxHashMap<String, u32>> {
+    let mut existing_documents_ids = FxHashMap::default();
+    let mut chunk = Vec::with_capacity(EXTERNAL_IDS_CHUNK_SIZE);
+
+    loop {
+        let document_id = cursor.next_document_id()?;
+        let is_last = document_id.is_none();
+        if let Some(document_id) = document_id {
+            // the generated ids are new, they can't be in the index.
+            if !document_id.is_generated() {
+                chunk.push(document_id.value().to_string());
+            }
+        }
+
+        if is_last || chunk.len() == EXTERNAL_IDS_CHUNK_SIZE {
+            chunk.sort_unstable();
+            chunk.dedup();
+            let docids = external_documents_ids.get_many(&chunk)?;
+            existing_documents_ids.extend(
+                chunk.drain(..).zip(docids).filter_map(|(id, docid)| docid.map(|d| (id, d))),
+            );
+        }
+
+        if is_last {
+            break;
+        }
+    }
+
+    cursor.reset();
+    Ok(existing_documents_ids)
 }
 
 /// Drops all the value of type `U` in vec, and reuses the allocation to create a `Vec<T>`.
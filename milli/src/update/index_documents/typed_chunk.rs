@@ -1,4 +1,5 @@
 use std::borrow::Cow;
+use std::collections::{BTreeMap, HashMap};
 use std::convert::TryInto;
 use std::fs::File;
 use std::io;
@@ -7,18 +8,23 @@ use grenad::MergerBuilder;
 use heed::types::ByteSlice;
 use heed::{BytesDecode, RwTxn};
 use roaring::RoaringBitmap;
+use rstar::primitives::Rectangle;
 
 use super::helpers::{
     self, merge_ignore_values, roaring_bitmap_from_u32s_array, serialize_roaring_bitmap,
     valid_lmdb_key, CursorClonableMmap,
 };
 use super::{ClonableMmap, MergeFn};
+use crate::error::SerializationError;
 use crate::facet::FacetType;
+use crate::heed_codec::facet::FieldDocIdFacetStringCodec;
+use crate::index::{db_name, main_key};
 use crate::update::facet::FacetsUpdate;
 use crate::update::index_documents::helpers::as_cloneable_grenad;
+use crate::update::IndexingMetrics;
 use crate::{
-    lat_lng_to_xyz, BoRoaringBitmapCodec, CboRoaringBitmapCodec, DocumentId, GeoPoint, Index,
-    Result,
+    lat_lng_to_xyz, BoRoaringBitmapCodec, CboRoaringBitmapCodec, DocumentId, FieldId, GeoPoint,
+    GeoShape, Index, Result,
 };
 
 pub(crate) enum TypedChunk {
@@ -37,21 +43,60 @@ pub(crate) enum TypedChunk {
     FieldIdFacetStringDocids(grenad::Reader<File>),
     FieldIdFacetNumberDocids(grenad::Reader<File>),
     FieldIdFacetExistsDocids(grenad::Reader<File>),
+    FieldIdFacetBoolDocids(grenad::Reader<File>),
     GeoPoints(grenad::Reader<File>),
+    GeoShapes(grenad::Reader<File>),
+}
+
+impl TypedChunk {
+    /// The name of the kind of chunk, used to identify the chunks in the tracing spans.
+    #[cfg(feature = "tracing")]
+    fn kind(&self) -> &'static str {
+        match self {
+            TypedChunk::DocidWordPositions(_) => "docid_word_positions",
+            TypedChunk::FieldIdDocidFacetStrings(_) => "field_id_docid_facet_strings",
+            TypedChunk::FieldIdDocidFacetNumbers(_) => "field_id_docid_facet_numbers",
+            TypedChunk::Documents(_) => "documents",
+            TypedChunk::FieldIdWordcountDocids(_) => "field_id_word_count_docids",
+            TypedChunk::NewDocumentsIds(_) => "new_documents_ids",
+            TypedChunk::WordDocids { .. } => "word_docids",
+            TypedChunk::WordPositionDocids(_) => "word_position_docids",
+            TypedChunk::WordPairProximityDocids(_) => "word_pair_proximity_docids",
+            TypedChunk::FieldIdFacetStringDocids(_) => "facet_id_string_docids",
+            TypedChunk::FieldIdFacetNumberDocids(_) => "facet_id_f64_docids",
+            TypedChunk::FieldIdFacetExistsDocids(_) => "facet_id_exists_docids",
+            TypedChunk::FieldIdFacetBoolDocids(_) => "facet_id_bool_docids",
+            TypedChunk::GeoPoints(_) => "geo_points",
+            TypedChunk::GeoShapes(_) => "geo_shapes",
+        }
+    }
 }
 
 /// Write typed chunk in the corresponding LMDB database of the provided index.
 /// Return new documents seen.
+///
+/// The number of entries written into each database is reported to the `metrics`.
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(level = "trace", skip_all, fields(typed_chunk = typed_chunk.kind()))
+)]
 pub(crate) fn write_typed_chunk_into_index(
     typed_chunk: TypedChunk,
     index: &Index,
     wtxn: &mut RwTxn,
     index_is_empty: bool,
+    metrics: Option<&dyn IndexingMetrics>,
 ) -> Result<(RoaringBitmap, bool)> {
+    let on_db_written = |db_name: &str, (entries, bytes): (u64, u64)| {
+        if let Some(metrics) = metrics {
+            metrics.on_db_written(db_name, entries, bytes);
+        }
+    };
+
     let mut is_merged_database = false;
     match typed_chunk {
         TypedChunk::DocidWordPositions(docid_word_positions_iter) => {
-            write_entries_into_database(
+            let written = write_entries_into_database(
                 docid_word_positions_iter,
                 &index.docid_word_positions,
                 wtxn,
@@ -72,15 +117,20 @@ pub(crate) fn write_typed_chunk_into_index(
                     Ok(())
                 },
             )?;
+            on_db_written(db_name::DOCID_WORD_POSITIONS, written);
         }
         TypedChunk::Documents(obkv_documents_iter) => {
+            let mut written = (0, 0);
             let mut cursor = obkv_documents_iter.into_cursor()?;
             while let Some((key, value)) = cursor.move_on_next()? {
                 index.documents.remap_types::<ByteSlice, ByteSlice>().put(wtxn, key, value)?;
+                written.0 += 1;
+                written.1 += (key.len() + value.len()) as u64;
             }
+            on_db_written(db_name::DOCUMENTS, written);
         }
         TypedChunk::FieldIdWordcountDocids(fid_word_count_docids_iter) => {
-            append_entries_into_database(
+            let written = append_entries_into_database(
                 fid_word_count_docids_iter,
                 &index.field_id_word_count_docids,
                 wtxn,
@@ -88,14 +138,18 @@ pub(crate) fn write_typed_chunk_into_index(
                 |value, _buffer| Ok(value),
                 merge_cbo_roaring_bitmaps,
             )?;
+            on_db_written(db_name::FIELD_ID_WORD_COUNT_DOCIDS, written);
             is_merged_database = true;
         }
         TypedChunk::NewDocumentsIds(documents_ids) => {
-            return Ok((documents_ids, is_merged_database))
+            if let Some(metrics) = metrics {
+                metrics.on_documents_extracted(documents_ids.len());
+            }
+            return Ok((documents_ids, is_merged_database));
         }
         TypedChunk::WordDocids { word_docids_reader, exact_word_docids_reader } => {
             let word_docids_iter = unsafe { as_cloneable_grenad(&word_docids_reader) }?;
-            append_entries_into_database(
+            let written = append_entries_into_database(
                 word_docids_iter.clone(),
                 &index.word_docids,
                 wtxn,
@@ -103,9 +157,10 @@ pub(crate) fn write_typed_chunk_into_index(
                 |value, _buffer| Ok(value),
                 merge_roaring_bitmaps,
             )?;
+            on_db_written(db_name::WORD_DOCIDS, written);
 
             let exact_word_docids_iter = unsafe { as_cloneable_grenad(&exact_word_docids_reader) }?;
-            append_entries_into_database(
+            let written = append_entries_into_database(
                 exact_word_docids_iter.clone(),
                 &index.exact_word_docids,
                 wtxn,
@@ -113,6 +168,7 @@ pub(crate) fn write_typed_chunk_into_index(
                 |value, _buffer| Ok(value),
                 merge_roaring_bitmaps,
             )?;
+            on_db_written(db_name::EXACT_WORD_DOCIDS, written);
 
             // create fst from word docids
             let fst = merge_word_docids_reader_into_fst(word_docids_iter, exact_word_docids_iter)?;
@@ -124,10 +180,12 @@ pub(crate) fn write_typed_chunk_into_index(
             builder.extend_stream(union_stream)?;
             let fst = builder.into_set();
             index.put_words_fst(wtxn, &fst)?;
+            let fst_bytes = fst.as_fst().as_bytes().len();
+            on_db_written(db_name::MAIN, (1, (main_key::WORDS_FST_KEY.len() + fst_bytes) as u64));
             is_merged_database = true;
         }
         TypedChunk::WordPositionDocids(word_position_docids_iter) => {
-            append_entries_into_database(
+            let written = append_entries_into_database(
                 word_position_docids_iter,
                 &index.word_position_docids,
                 wtxn,
@@ -135,20 +193,29 @@ pub(crate) fn write_typed_chunk_into_index(
                 |value, _buffer| Ok(value),
                 merge_cbo_roaring_bitmaps,
             )?;
+            on_db_written(db_name::WORD_POSITION_DOCIDS, written);
             is_merged_database = true;
         }
         TypedChunk::FieldIdFacetNumberDocids(facet_id_number_docids_iter) => {
+            if metrics.is_some() {
+                let written = grenad_entries_and_bytes(&facet_id_number_docids_iter)?;
+                on_db_written(db_name::FACET_ID_F64_DOCIDS, written);
+            }
             let indexer = FacetsUpdate::new(index, FacetType::Number, facet_id_number_docids_iter);
             indexer.execute(wtxn)?;
             is_merged_database = true;
         }
         TypedChunk::FieldIdFacetStringDocids(facet_id_string_docids_iter) => {
+            if metrics.is_some() {
+                let written = grenad_entries_and_bytes(&facet_id_string_docids_iter)?;
+                on_db_written(db_name::FACET_ID_STRING_DOCIDS, written);
+            }
             let indexer = FacetsUpdate::new(index, FacetType::String, facet_id_string_docids_iter);
             indexer.execute(wtxn)?;
             is_merged_database = true;
         }
         TypedChunk::FieldIdFacetExistsDocids(facet_id_exists_docids) => {
-            append_entries_into_database(
+            let written = append_entries_into_database(
                 facet_id_exists_docids,
                 &index.facet_id_exists_docids,
                 wtxn,
@@ -156,10 +223,23 @@ pub(crate) fn write_typed_chunk_into_index(
                 |value, _buffer| Ok(value),
                 merge_cbo_roaring_bitmaps,
             )?;
+            on_db_written(db_name::FACET_ID_EXISTS_DOCIDS, written);
+            is_merged_database = true;
+        }
+        TypedChunk::FieldIdFacetBoolDocids(facet_id_bool_docids) => {
+            let written = append_entries_into_database(
+                facet_id_bool_docids,
+                &index.facet_id_bool_docids,
+                wtxn,
+                index_is_empty,
+                |value, _buffer| Ok(value),
+                merge_cbo_roaring_bitmaps,
+            )?;
+            on_db_written(db_name::FACET_ID_BOOL_DOCIDS, written);
             is_merged_database = true;
         }
         TypedChunk::WordPairProximityDocids(word_pair_proximity_docids_iter) => {
-            append_entries_into_database(
+            let written = append_entries_into_database(
                 word_pair_proximity_docids_iter,
                 &index.word_pair_proximity_docids,
                 wtxn,
@@ -167,27 +247,72 @@ pub(crate) fn write_typed_chunk_into_index(
                 |value, _buffer| Ok(value),
                 merge_cbo_roaring_bitmaps,
             )?;
+            on_db_written(db_name::WORD_PAIR_PROXIMITY_DOCIDS, written);
             is_merged_database = true;
         }
         TypedChunk::FieldIdDocidFacetNumbers(fid_docid_facet_number) => {
             let index_fid_docid_facet_numbers =
                 index.field_id_docid_facet_f64s.remap_types::<ByteSlice, ByteSlice>();
+            let mut written = (0, 0);
             let mut cursor = fid_docid_facet_number.into_cursor()?;
             while let Some((key, value)) = cursor.move_on_next()? {
                 if valid_lmdb_key(key) {
                     index_fid_docid_facet_numbers.put(wtxn, key, value)?;
+                    written.0 += 1;
+                    written.1 += (key.len() + value.len()) as u64;
                 }
             }
+            on_db_written(db_name::FIELD_ID_DOCID_FACET_F64S, written);
         }
         TypedChunk::FieldIdDocidFacetStrings(fid_docid_facet_string) => {
             let index_fid_docid_facet_strings =
                 index.field_id_docid_facet_strings.remap_types::<ByteSlice, ByteSlice>();
+            let mut written = (0, 0);
+            let mut spellings: HashMap<(FieldId, String), BTreeMap<String, u64>> = HashMap::new();
             let mut cursor = fid_docid_facet_string.into_cursor()?;
             while let Some((key, value)) = cursor.move_on_next()? {
                 if valid_lmdb_key(key) {
                     index_fid_docid_facet_strings.put(wtxn, key, value)?;
+                    written.0 += 1;
+                    written.1 += (key.len() + value.len()) as u64;
+
+                    let decoding_error = || SerializationError::Decoding {
+                        db_name: Some(db_name::FIELD_ID_DOCID_FACET_STRINGS),
+                    };
+                    let (field_id, _docid, normalized) =
+                        FieldDocIdFacetStringCodec::bytes_decode(key).ok_or_else(decoding_error)?;
+                    let original = std::str::from_utf8(value).map_err(|_| decoding_error())?;
+                    *spellings
+                        .entry((field_id, normalized.to_owned()))
+                        .or_default()
+                        .entry(original.to_owned())
+                        .or_default() += 1;
                 }
             }
+            on_db_written(db_name::FIELD_ID_DOCID_FACET_STRINGS, written);
+
+            // We count the documents of every original spelling of the facet strings,
+            // this way the facet distribution can pick one of them without reading the documents.
+            let mut written = (0, 0);
+            for ((field_id, normalized), counts) in spellings {
+                let key = (field_id, normalized.as_str());
+                let mut stored =
+                    index.facet_id_string_originals.get(wtxn, &key)?.unwrap_or_default();
+                for (original, count) in counts {
+                    *stored.entry(original).or_default() += count;
+                }
+                let value =
+                    serde_json::to_vec(&stored).map_err(|_| SerializationError::Encoding {
+                        db_name: Some(db_name::FACET_ID_STRING_ORIGINALS),
+                    })?;
+                index
+                    .facet_id_string_originals
+                    .remap_data_type::<ByteSlice>()
+                    .put(wtxn, &key, &value)?;
+                written.0 += 1;
+                written.1 += (2 + normalized.len() + value.len()) as u64;
+            }
+            on_db_written(db_name::FACET_ID_STRING_ORIGINALS, written);
         }
         TypedChunk::GeoPoints(geo_points) => {
             let mut rtree = index.geo_rtree(wtxn)?.unwrap_or_default();
@@ -209,6 +334,46 @@ pub(crate) fn write_typed_chunk_into_index(
             }
             index.put_geo_rtree(wtxn, &rtree)?;
             index.put_geo_faceted_documents_ids(wtxn, &geo_faceted_docids)?;
+            if metrics.is_some() {
+                let rtree_bytes = serialized_rtree_size(&rtree)?;
+                let docids_bytes = geo_faceted_docids.serialized_size() as u64;
+                let keys_bytes =
+                    main_key::GEO_RTREE_KEY.len() + main_key::GEO_FACETED_DOCUMENTS_IDS_KEY.len();
+                on_db_written(db_name::MAIN, (2, keys_bytes as u64 + rtree_bytes + docids_bytes));
+            }
+        }
+        TypedChunk::GeoShapes(geo_shapes) => {
+            let mut rtree = index.geo_shapes_rtree(wtxn)?.unwrap_or_default();
+
+            let mut cursor = geo_shapes.into_cursor()?;
+            while let Some((key, value)) = cursor.move_on_next()? {
+                // convert the key back to a u32 (4 bytes)
+                let docid = key.try_into().map(DocumentId::from_be_bytes).unwrap();
+
+                // convert the latitudes and longitudes of the vertices back to f64 (8 bytes)
+                let vertices: Vec<[f64; 2]> = value
+                    .chunks_exact(16)
+                    .map(|vertex| {
+                        let (lat, lng) = helpers::try_split_array_at::<u8, 8>(vertex).unwrap();
+                        [f64::from_ne_bytes(lat), f64::from_ne_bytes(lng.try_into().unwrap())]
+                    })
+                    .collect();
+
+                let (mut lower, mut upper) = (vertices[0], vertices[0]);
+                for [lat, lng] in &vertices {
+                    lower = [lower[0].min(*lat), lower[1].min(*lng)];
+                    upper = [upper[0].max(*lat), upper[1].max(*lng)];
+                }
+                let bounding_box = Rectangle::from_corners(lower, upper);
+
+                rtree.insert(GeoShape::new(bounding_box, (docid, vertices)));
+            }
+            index.put_geo_shapes_rtree(wtxn, &rtree)?;
+            if metrics.is_some() {
+                let rtree_bytes = serialized_rtree_size(&rtree)?;
+                let key_bytes = main_key::GEO_SHAPES_RTREE_KEY.len() as u64;
+                on_db_written(db_name::MAIN, (1, key_bytes + rtree_bytes));
+            }
         }
     }
 
@@ -232,6 +397,24 @@ fn merge_word_docids_reader_into_fst(
     Ok(builder.into_set())
 }
 
+/// Returns the number of entries of the reader and their size in bytes, it is used to
+/// measure the level 0 of the facet databases that is written by the [`FacetsUpdate`].
+fn grenad_entries_and_bytes(reader: &grenad::Reader<File>) -> Result<(u64, u64)> {
+    let mut cursor = unsafe { as_cloneable_grenad(reader) }?.into_cursor()?;
+    let mut written = (0, 0);
+    while let Some((key, value)) = cursor.move_on_next()? {
+        written.0 += 1;
+        written.1 += (key.len() + value.len()) as u64;
+    }
+    Ok(written)
+}
+
+/// Returns the size in bytes of the rtree once serialized into the main database.
+fn serialized_rtree_size<T: serde::Serialize>(rtree: &T) -> Result<u64> {
+    bincode::serialized_size(rtree)
+        .map_err(|_| SerializationError::Encoding { db_name: Some(db_name::MAIN) }.into())
+}
+
 fn merge_roaring_bitmaps(new_value: &[u8], db_value: &[u8], buffer: &mut Vec<u8>) -> Result<()> {
     let new_value = RoaringBitmap::deserialize_from(new_value)?;
     let db_value = RoaringBitmap::deserialize_from(db_value)?;
@@ -252,6 +435,7 @@ fn merge_cbo_roaring_bitmaps(
 
 /// Write provided entries in database using serialize_value function.
 /// merge_values function is used if an entry already exist in the database.
+/// Return the number of entries written and their size in bytes.
 fn write_entries_into_database<R, K, V, FS, FM>(
     data: grenad::Reader<R>,
     database: &heed::Database<K, V>,
@@ -259,13 +443,14 @@ fn write_entries_into_database<R, K, V, FS, FM>(
     index_is_empty: bool,
     serialize_value: FS,
     merge_values: FM,
-) -> Result<()>
+) -> Result<(u64, u64)>
 where
     R: io::Read + io::Seek,
     FS: for<'a> Fn(&'a [u8], &'a mut Vec<u8>) -> Result<&'a [u8]>,
     FM: Fn(&[u8], &[u8], &mut Vec<u8>) -> Result<()>,
 {
     let mut buffer = Vec::new();
+    let mut written = (0, 0);
     let database = database.remap_types::<ByteSlice, ByteSlice>();
 
     let mut cursor = data.into_cursor()?;
@@ -284,16 +469,19 @@ where
                 }
             };
             database.put(wtxn, key, value)?;
+            written.0 += 1;
+            written.1 += (key.len() + value.len()) as u64;
         }
     }
 
-    Ok(())
+    Ok(written)
 }
 
 /// Write provided entries in database using serialize_value function.
 /// merge_values function is used if an entry already exist in the database.
 /// All provided entries must be ordered.
 /// If the index is not empty, write_entries_into_database is called instead.
+/// Return the number of entries written and their size in bytes.
 fn append_entries_into_database<R, K, V, FS, FM>(
     data: grenad::Reader<R>,
     database: &heed::Database<K, V>,
@@ -301,7 +489,7 @@ fn append_entries_into_database<R, K, V, FS, FM>(
     index_is_empty: bool,
     serialize_value: FS,
     merge_values: FM,
-) -> Result<()>
+) -> Result<(u64, u64)>
 where
     R: io::Read + io::Seek,
     FS: for<'a> Fn(&'a [u8], &'a mut Vec<u8>) -> Result<&'a [u8]>,
@@ -319,6 +507,7 @@ where
     }
 
     let mut buffer = Vec::new();
+    let mut written = (0, 0);
     let mut database = database.iter_mut(wtxn)?.remap_types::<ByteSlice, ByteSlice>();
 
     let mut cursor = data.into_cursor()?;
@@ -327,8 +516,10 @@ where
             buffer.clear();
             let value = serialize_value(value, &mut buffer)?;
             unsafe { database.append(key, value)? };
+            written.0 += 1;
+            written.1 += (key.len() + value.len()) as u64;
         }
     }
 
-    Ok(())
+    Ok(written)
 }
@@ -1,6 +1,14 @@
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::sync::Arc;
+
+use charabia::TokenizerBuilder;
+use fxhash::FxHasher64;
 use grenad::CompressionType;
 use rayon::ThreadPool;
 
+use super::IndexingMetrics;
+
 #[derive(Debug)]
 pub struct IndexerConfig {
     pub log_every_n: Option<usize>,
@@ -11,6 +19,29 @@ pub struct IndexerConfig {
     pub chunk_compression_level: Option<u32>,
     pub thread_pool: Option<ThreadPool>,
     pub max_positions_per_attributes: Option<u32>,
+    /// The maximum size, in bytes, of a document once stored, the documents are rejected with
+    /// an [`UserError::DocumentTooLarge`](crate::UserError::DocumentTooLarge) above it.
+    ///
+    /// A document can never exceed 4 GiB, whatever this limit is.
+    pub max_document_size: Option<usize>,
+    /// Whether the documents exceeding the maximum size are skipped instead of failing the
+    /// whole addition, they are listed in the [`DocumentAdditionResult`](super::DocumentAdditionResult).
+    pub skip_oversized_documents: bool,
+    /// Whether to record, for every inserted or updated document, the indexing
+    /// sequence number at which it was indexed, see [`crate::Index::document_indexed_at`].
+    pub track_indexing_sequence: bool,
+    /// The callbacks notified of the progress of the indexing, see [`IndexingMetrics`].
+    pub metrics: Option<Arc<dyn IndexingMetrics>>,
+    /// The configuration of the tokenizer of the documents, the default configuration of
+    /// charabia is used when `None`, see [`TokenizerConfig`].
+    pub tokenizer_config: Option<TokenizerConfig>,
+}
+
+impl IndexerConfig {
+    /// The fingerprint of the tokenizer configuration, `None` for the default configuration.
+    pub(crate) fn tokenizer_fingerprint(&self) -> Option<u64> {
+        self.tokenizer_config.as_ref().map(TokenizerConfig::fingerprint)
+    }
 }
 
 impl Default for IndexerConfig {
@@ -24,6 +55,66 @@ impl Default for IndexerConfig {
             chunk_compression_level: None,
             thread_pool: None,
             max_positions_per_attributes: None,
+            max_document_size: None,
+            skip_oversized_documents: false,
+            track_indexing_sequence: false,
+            metrics: None,
+            tokenizer_config: None,
         }
     }
 }
+
+/// A configuration of the tokenizer replacing the default configuration of charabia,
+/// e.g. to restrict the languages detected in a script.
+///
+/// The same configuration must be given to the [`IndexerConfig`] and to the
+/// [`Search`](crate::Search) for the queries to be tokenized like the documents. The settings
+/// of the index, i.e. its stop words and the languages of its fields, are applied on top of it.
+///
+/// The index stores the [fingerprint](Self::fingerprint) of the configuration its documents
+/// were tokenized with. The document additions and the searches using another configuration
+/// log a warning until all the documents are reindexed with it, which the settings do when
+/// asked to, see [`Settings::apply_tokenizer_config`](super::Settings::apply_tokenizer_config).
+#[derive(Clone)]
+pub struct TokenizerConfig {
+    id: String,
+    configure: Arc<dyn Fn(&mut TokenizerBuilder<&[u8]>) + Send + Sync>,
+}
+
+impl TokenizerConfig {
+    /// Creates a configuration calling `configure` on every new tokenizer builder,
+    /// before the settings of the index are applied to it.
+    ///
+    /// The `id` identifies the configuration, it must be changed whenever `configure`
+    /// changes the way the texts are tokenized, e.g. to `"cjk-v2"` after `"cjk-v1"`.
+    pub fn new(
+        id: impl Into<String>,
+        configure: impl Fn(&mut TokenizerBuilder<&[u8]>) + Send + Sync + 'static,
+    ) -> Self {
+        TokenizerConfig { id: id.into(), configure: Arc::new(configure) }
+    }
+
+    /// Applies the configuration to the tokenizer builder.
+    pub(crate) fn configure(&self, builder: &mut TokenizerBuilder<&[u8]>) {
+        (self.configure)(builder)
+    }
+
+    /// Returns the identifier of this configuration.
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    /// Returns the fingerprint of the [identifier](Self::id) of this configuration, two
+    /// configurations with different identifiers have different fingerprints.
+    pub fn fingerprint(&self) -> u64 {
+        let mut hasher = FxHasher64::default();
+        self.id.hash(&mut hasher);
+        hasher.finish()
+    }
+}
+
+impl fmt::Debug for TokenizerConfig {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("TokenizerConfig").field("id", &self.id).finish_non_exhaustive()
+    }
+}
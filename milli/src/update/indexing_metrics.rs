@@ -0,0 +1,69 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// A set of callbacks called during the indexing of documents, see [`IndexerConfig::metrics`].
+///
+/// Every method does nothing by default, implementors only override the ones they care about.
+///
+/// [`IndexerConfig::metrics`]: crate::update::IndexerConfig::metrics
+pub trait IndexingMetrics: fmt::Debug + Send + Sync {
+    /// Called when `count` documents have been extracted and are about to be written.
+    fn on_documents_extracted(&self, _count: u64) {}
+
+    /// Called when `entries` entries, for a total of `bytes` bytes of keys and values,
+    /// have been written into the database named `db_name`, see [`crate::index::db_name`].
+    fn on_db_written(&self, _db_name: &str, _entries: u64, _bytes: u64) {}
+
+    /// Called at the end of an indexing phase with the time spent in it.
+    fn on_phase_duration(&self, _phase: &str, _duration: Duration) {}
+}
+
+/// An [`IndexingMetrics`] that accumulates the reported values in counters.
+#[derive(Debug, Default)]
+pub struct AtomicMetrics {
+    documents_extracted: AtomicU64,
+    db_written: Mutex<BTreeMap<String, (u64, u64)>>,
+    phase_durations: Mutex<BTreeMap<String, Duration>>,
+}
+
+impl AtomicMetrics {
+    pub fn new() -> AtomicMetrics {
+        AtomicMetrics::default()
+    }
+
+    /// The number of documents extracted so far.
+    pub fn documents_extracted(&self) -> u64 {
+        self.documents_extracted.load(Ordering::Relaxed)
+    }
+
+    /// The number of entries and bytes written into each database so far.
+    pub fn db_written(&self) -> BTreeMap<String, (u64, u64)> {
+        self.db_written.lock().unwrap().clone()
+    }
+
+    /// The total time spent in each of the indexing phases so far.
+    pub fn phase_durations(&self) -> BTreeMap<String, Duration> {
+        self.phase_durations.lock().unwrap().clone()
+    }
+}
+
+impl IndexingMetrics for AtomicMetrics {
+    fn on_documents_extracted(&self, count: u64) {
+        self.documents_extracted.fetch_add(count, Ordering::Relaxed);
+    }
+
+    fn on_db_written(&self, db_name: &str, entries: u64, bytes: u64) {
+        let mut db_written = self.db_written.lock().unwrap();
+        let (total_entries, total_bytes) = db_written.entry(db_name.to_string()).or_default();
+        *total_entries += entries;
+        *total_bytes += bytes;
+    }
+
+    fn on_phase_duration(&self, phase: &str, duration: Duration) {
+        let mut phase_durations = self.phase_durations.lock().unwrap();
+        *phase_durations.entry(phase.to_string()).or_default() += duration;
+    }
+}
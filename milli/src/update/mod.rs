@@ -1,17 +1,24 @@
 pub use self::available_documents_ids::AvailableDocumentsIds;
 pub use self::clear_documents::ClearDocuments;
-pub use self::delete_documents::{DeleteDocuments, DeletionStrategy, DocumentDeletionResult};
+pub use self::delete_documents::{
+    DeleteDocuments, DeletionResult, DeletionStrategy, DocumentDeletionResult,
+};
 pub use self::facet::bulk::FacetsUpdateBulk;
 pub use self::facet::incremental::FacetsUpdateIncrementalInner;
+pub(crate) use self::index_documents::MAX_FIELD_ID_WORD_COUNT;
 pub use self::index_documents::{
-    DocumentAdditionResult, DocumentId, IndexDocuments, IndexDocumentsConfig, IndexDocumentsMethod,
+    migrate_boolean_facets, reindex_documents, AllowedDocumentIdChars, DocumentAdditionResult,
+    DocumentId, DocumentIdRules, DryRunError, DryRunReport, DuplicatesReducer, FieldError,
+    IndexDocuments, IndexDocumentsConfig, IndexDocumentsMethod, KeptDocument,
 };
-pub use self::indexer_config::IndexerConfig;
+pub use self::indexer_config::{IndexerConfig, TokenizerConfig};
+pub use self::indexing_metrics::{AtomicMetrics, IndexingMetrics};
 pub use self::prefix_word_pairs::{
     PrefixWordPairsProximityDocids, MAX_LENGTH_FOR_PREFIX_PROXIMITY_DB,
     MAX_PROXIMITY_FOR_PREFIX_PROXIMITY_DB,
 };
 pub use self::settings::{Setting, Settings};
+pub use self::update_builder::UpdateBuilder;
 pub use self::update_step::UpdateIndexingStep;
 pub use self::word_prefix_docids::WordPrefixDocids;
 pub use self::words_prefix_position_docids::WordPrefixPositionDocids;
@@ -23,8 +30,10 @@ mod delete_documents;
 pub(crate) mod facet;
 mod index_documents;
 mod indexer_config;
+mod indexing_metrics;
 mod prefix_word_pairs;
 mod settings;
+mod update_builder;
 mod update_step;
 mod word_prefix_docids;
 mod words_prefix_position_docids;
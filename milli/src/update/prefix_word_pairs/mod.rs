@@ -1,11 +1,14 @@
 use std::borrow::Cow;
 use std::collections::HashSet;
 use std::io::BufReader;
+use std::sync::Arc;
 
 use grenad::CompressionType;
 use heed::types::ByteSlice;
 
 use super::index_documents::{merge_cbo_roaring_bitmaps, CursorClonableMmap};
+use super::IndexingMetrics;
+use crate::index::db_name;
 use crate::{Index, Result};
 
 mod prefix_word;
@@ -24,6 +27,7 @@ pub struct PrefixWordPairsProximityDocids<'t, 'u, 'i> {
     max_prefix_length: usize,
     chunk_compression_type: CompressionType,
     chunk_compression_level: Option<u32>,
+    metrics: Option<Arc<dyn IndexingMetrics>>,
 }
 impl<'t, 'u, 'i> PrefixWordPairsProximityDocids<'t, 'u, 'i> {
     pub fn new(
@@ -39,10 +43,25 @@ impl<'t, 'u, 'i> PrefixWordPairsProximityDocids<'t, 'u, 'i> {
             max_prefix_length: MAX_LENGTH_FOR_PREFIX_PROXIMITY_DB,
             chunk_compression_type,
             chunk_compression_level,
+            metrics: None,
         }
     }
 
+    /// Reports the entries written into the prefix pair proximity databases to the `metrics`.
+    pub fn metrics(&mut self, metrics: Arc<dyn IndexingMetrics>) -> &mut Self {
+        self.metrics = Some(metrics);
+        self
+    }
+
     #[logging_timer::time("WordPrefixPairProximityDocids::{}")]
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            level = "trace",
+            name = "WordPrefixPairProximityDocids::execute",
+            skip_all
+        )
+    )]
     pub fn execute<'a>(
         self,
         new_word_pair_proximity_docids: grenad::Reader<CursorClonableMmap>,
@@ -50,7 +69,7 @@ impl<'t, 'u, 'i> PrefixWordPairsProximityDocids<'t, 'u, 'i> {
         common_prefix_fst_words: &[&'a [String]],
         del_prefix_fst_words: &HashSet<Vec<u8>>,
     ) -> Result<()> {
-        index_word_prefix_database(
+        let (entries, bytes) = index_word_prefix_database(
             self.wtxn,
             self.index.word_pair_proximity_docids,
             self.index.word_prefix_pair_proximity_docids,
@@ -63,8 +82,11 @@ impl<'t, 'u, 'i> PrefixWordPairsProximityDocids<'t, 'u, 'i> {
             self.chunk_compression_type,
             self.chunk_compression_level,
         )?;
+        if let Some(metrics) = &self.metrics {
+            metrics.on_db_written(db_name::WORD_PREFIX_PAIR_PROXIMITY_DOCIDS, entries, bytes);
+        }
 
-        index_prefix_word_database(
+        let (entries, bytes) = index_prefix_word_database(
             self.wtxn,
             self.index.word_pair_proximity_docids,
             self.index.prefix_word_pair_proximity_docids,
@@ -77,18 +99,22 @@ impl<'t, 'u, 'i> PrefixWordPairsProximityDocids<'t, 'u, 'i> {
             self.chunk_compression_type,
             self.chunk_compression_level,
         )?;
+        if let Some(metrics) = &self.metrics {
+            metrics.on_db_written(db_name::PREFIX_WORD_PAIR_PROXIMITY_DOCIDS, entries, bytes);
+        }
 
         Ok(())
     }
 }
 
-// This is adapted from `sorter_into_lmdb_database`
+// This is adapted from `sorter_into_lmdb_database`, it returns the size in bytes
+// of the entry written.
 pub fn insert_into_database(
     wtxn: &mut heed::RwTxn,
     database: heed::PolyDatabase,
     new_key: &[u8],
     new_value: &[u8],
-) -> Result<()> {
+) -> Result<u64> {
     let mut iter = database.prefix_iter_mut::<_, ByteSlice, ByteSlice>(wtxn, new_key)?;
     match iter.next().transpose()? {
         Some((key, old_val)) if new_key == key => {
@@ -102,39 +128,46 @@ pub fn insert_into_database(
                     })?;
             // safety: we use the new_key, not the one from the database iterator, to avoid undefined behaviour
             unsafe { iter.put_current(new_key, &val)? };
+            Ok((new_key.len() + val.len()) as u64)
         }
         _ => {
             drop(iter);
             database.put::<_, ByteSlice, ByteSlice>(wtxn, new_key, new_value)?;
+            Ok((new_key.len() + new_value.len()) as u64)
         }
     }
-    Ok(())
 }
 
 // This is adapted from `sorter_into_lmdb_database` and `write_into_lmdb_database`,
 // but it uses `append` if the database is empty, and it assumes that the values in the
-// writer don't conflict with values in the database.
+// writer don't conflict with values in the database. It returns the number of entries
+// written and their size in bytes.
 pub fn write_into_lmdb_database_without_merging(
     wtxn: &mut heed::RwTxn,
     database: heed::PolyDatabase,
     writer: grenad::Writer<std::fs::File>,
-) -> Result<()> {
+) -> Result<(u64, u64)> {
     let file = writer.into_inner()?;
     let reader = grenad::Reader::new(BufReader::new(file))?;
+    let mut written = (0, 0);
     if database.is_empty(wtxn)? {
         let mut out_iter = database.iter_mut::<_, ByteSlice, ByteSlice>(wtxn)?;
         let mut cursor = reader.into_cursor()?;
         while let Some((k, v)) = cursor.move_on_next()? {
             // safety: the key comes from the grenad reader, not the database
             unsafe { out_iter.append(k, v)? };
+            written.0 += 1;
+            written.1 += (k.len() + v.len()) as u64;
         }
     } else {
         let mut cursor = reader.into_cursor()?;
         while let Some((k, v)) = cursor.move_on_next()? {
             database.put::<_, ByteSlice, ByteSlice>(wtxn, k, v)?;
+            written.0 += 1;
+            written.1 += (k.len() + v.len()) as u64;
         }
     }
-    Ok(())
+    Ok(written)
 }
 
 #[cfg(test)]
@@ -14,6 +14,7 @@ use crate::{CboRoaringBitmapCodec, Result, U8StrStrCodec, UncheckedU8StrStrCodec
 
 #[allow(clippy::too_many_arguments)]
 #[logging_timer::time]
+#[cfg_attr(feature = "tracing", tracing::instrument(level = "trace", skip_all))]
 pub fn index_prefix_word_database(
     wtxn: &mut heed::RwTxn,
     word_pair_proximity_docids: heed::Database<U8StrStrCodec, CboRoaringBitmapCodec>,
@@ -26,7 +27,7 @@ pub fn index_prefix_word_database(
     del_prefix_fst_words: &HashSet<Vec<u8>>,
     chunk_compression_type: CompressionType,
     chunk_compression_level: Option<u32>,
-) -> Result<()> {
+) -> Result<(u64, u64)> {
     let max_proximity = max_proximity - 1;
     debug!("Computing and writing the word prefix pair proximity docids into LMDB on disk...");
 
@@ -37,6 +38,7 @@ pub fn index_prefix_word_database(
         .filter(|s| s.len() <= max_prefix_length)
         .collect();
 
+    let mut written = (0, 0);
     for proximity in 1..max_proximity {
         for prefix in common_prefixes.iter() {
             let mut prefix_key = vec![proximity];
@@ -59,12 +61,14 @@ pub fn index_prefix_word_database(
                 },
                 // and this argument tells what to do with each new key (proximity, prefix, word2) and value (roaring bitmap)
                 |key, value| {
-                    insert_into_database(
+                    written.1 += insert_into_database(
                         wtxn,
                         *prefix_word_pair_proximity_docids.as_polymorph(),
                         key,
                         value,
-                    )
+                    )?;
+                    written.0 += 1;
+                    Ok(())
                 },
             )?;
         }
@@ -111,11 +115,13 @@ pub fn index_prefix_word_database(
     // Since the grenad contains only new prefixes, we know in advance that none
     // of its elements already exist in the DB, thus there is no need to specify
     // how to merge conflicting elements
-    write_into_lmdb_database_without_merging(
+    let (entries, bytes) = write_into_lmdb_database_without_merging(
         wtxn,
         *prefix_word_pair_proximity_docids.as_polymorph(),
         writer,
     )?;
+    written.0 += entries;
+    written.1 += bytes;
 
     // All of the word prefix pairs in the database that have a w2
     // that is contained in the `suppr_pw` set must be removed as well.
@@ -130,7 +136,7 @@ pub fn index_prefix_word_database(
         }
     }
 
-    Ok(())
+    Ok(written)
 }
 
 /// This is the core of the algorithm to initialise the Prefix Word Pair Proximity Docids database.
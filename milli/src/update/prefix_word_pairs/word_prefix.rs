@@ -178,6 +178,7 @@ use crate::{CboRoaringBitmapCodec, Result, U8StrStrCodec, UncheckedU8StrStrCodec
 
 #[allow(clippy::too_many_arguments)]
 #[logging_timer::time]
+#[cfg_attr(feature = "tracing", tracing::instrument(level = "trace", skip_all))]
 pub fn index_word_prefix_database(
     wtxn: &mut heed::RwTxn,
     word_pair_proximity_docids: heed::Database<U8StrStrCodec, CboRoaringBitmapCodec>,
@@ -190,9 +191,11 @@ pub fn index_word_prefix_database(
     del_prefix_fst_words: &HashSet<Vec<u8>>,
     chunk_compression_type: CompressionType,
     chunk_compression_level: Option<u32>,
-) -> Result<()> {
+) -> Result<(u64, u64)> {
     debug!("Computing and writing the word prefix pair proximity docids into LMDB on disk...");
 
+    let mut written = (0, 0);
+
     // Make a prefix trie from the common prefixes that are shorter than self.max_prefix_length
     let prefixes = PrefixTrieNode::from_sorted_prefixes(
         common_prefix_fst_words
@@ -224,12 +227,14 @@ pub fn index_word_prefix_database(
             max_proximity,
             // and this argument tells what to do with each new key (proximity, word1, prefix) and value (roaring bitmap)
             |key, value| {
-                insert_into_database(
+                written.1 += insert_into_database(
                     wtxn,
                     *word_prefix_pair_proximity_docids.as_polymorph(),
                     key,
                     value,
-                )
+                )?;
+                written.0 += 1;
+                Ok(())
             },
         )?;
     }
@@ -264,11 +269,13 @@ pub fn index_word_prefix_database(
         // Since the grenad contains only new prefixes, we know in advance that none
         // of its elements already exist in the DB, thus there is no need to specify
         // how to merge conflicting elements
-        write_into_lmdb_database_without_merging(
+        let (entries, bytes) = write_into_lmdb_database_without_merging(
             wtxn,
             *word_prefix_pair_proximity_docids.as_polymorph(),
             writer,
         )?;
+        written.0 += entries;
+        written.1 += bytes;
     }
 
     // All of the word prefix pairs in the database that have a w2
@@ -284,7 +291,7 @@ pub fn index_word_prefix_database(
         }
     }
 
-    Ok(())
+    Ok(written)
 }
 
 /// This is the core of the algorithm to initialise the Word Prefix Pair Proximity Docids database.
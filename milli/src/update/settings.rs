@@ -1,20 +1,25 @@
-use std::collections::{BTreeSet, HashMap, HashSet};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
 use std::result::Result as StdResult;
 
 use charabia::{Tokenizer, TokenizerBuilder};
 use deserr::{DeserializeError, DeserializeFromValue};
+use heed::types::{ByteSlice, DecodeIgnore};
 use itertools::Itertools;
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use time::OffsetDateTime;
 
-use super::index_documents::{IndexDocumentsConfig, Transform};
+use super::index_documents::reindex_all_documents;
 use super::IndexerConfig;
 use crate::criterion::Criterion;
 use crate::error::UserError;
+use crate::facet::{FacetType, MixedFacetTypes};
 use crate::index::{DEFAULT_MIN_WORD_LEN_ONE_TYPO, DEFAULT_MIN_WORD_LEN_TWO_TYPOS};
-use crate::update::index_documents::IndexDocumentsMethod;
-use crate::update::{IndexDocuments, UpdateIndexingStep};
-use crate::{FieldsIdsMap, Index, Result};
+use crate::stemming::is_stemmable_code;
+use crate::update::UpdateIndexingStep;
+use crate::{
+    script_language_from_code, FieldsIdsMap, Index, LongWordsPolicy, MaxFieldsPolicy, Result,
+    StopWordsMode, TypoOnDiacritics, MAX_FIELDS,
+};
 
 #[derive(Debug, Clone, PartialEq, Eq, Copy)]
 pub enum Setting<T> {
@@ -100,6 +105,12 @@ impl<'de, T: Deserialize<'de>> Deserialize<'de> for Setting<T> {
     }
 }
 
+/// Updates the settings of an index.
+///
+/// Nothing is written outside of the given write transaction, the settings can therefore be
+/// updated atomically along with a documents addition: execute the `Settings` first and then
+/// create an [`IndexDocuments`](crate::update::IndexDocuments) with the same transaction, the documents will be indexed under
+/// the new settings and nothing is persisted if the transaction is aborted.
 pub struct Settings<'a, 't, 'u, 'i> {
     wtxn: &'t mut heed::RwTxn<'i, 'u>,
     index: &'i Index,
@@ -112,6 +123,7 @@ pub struct Settings<'a, 't, 'u, 'i> {
     sortable_fields: Setting<HashSet<String>>,
     criteria: Setting<Vec<Criterion>>,
     stop_words: Setting<BTreeSet<String>>,
+    stop_words_mode: Setting<StopWordsMode>,
     distinct_field: Setting<String>,
     synonyms: Setting<HashMap<String, Vec<String>>>,
     primary_key: Setting<String>,
@@ -119,10 +131,32 @@ pub struct Settings<'a, 't, 'u, 'i> {
     min_word_len_two_typos: Setting<u8>,
     min_word_len_one_typo: Setting<u8>,
     exact_words: Setting<BTreeSet<String>>,
+    /// Words merged into the exact words, after the `exact_words` setting is applied.
+    exact_words_additions: Vec<String>,
+    /// Words removed from the exact words, after the additions are merged.
+    exact_words_removals: Vec<String>,
     /// Attributes on which typo tolerance is disabled.
     exact_attributes: Setting<HashSet<String>>,
+    case_sensitive_attributes: Setting<HashSet<String>>,
     max_values_per_facet: Setting<usize>,
     pagination_max_total_hits: Setting<usize>,
+    max_word_length: Setting<usize>,
+    long_words_policy: Setting<LongWordsPolicy>,
+    min_indexed_word_length: Setting<usize>,
+    max_positions_per_word: Setting<usize>,
+    max_fields: Setting<usize>,
+    max_fields_policy: Setting<MaxFieldsPolicy>,
+    field_boosts: Setting<BTreeMap<String, f32>>,
+    fields_languages: Setting<BTreeMap<String, String>>,
+    detected_languages_allow_list: Setting<Vec<String>>,
+    stemming: Setting<BTreeMap<String, bool>>,
+    normalization_rules: Setting<BTreeMap<String, String>>,
+    mixed_facet_types: Setting<BTreeMap<String, MixedFacetTypes>>,
+    proximity_databases: Setting<bool>,
+    cross_field_proximity: Setting<bool>,
+    typo_on_diacritics: Setting<TypoOnDiacritics>,
+    /// Whether the documents tokenized with another tokenizer configuration are reindexed.
+    apply_tokenizer_config: bool,
 }
 
 impl<'a, 't, 'u, 'i> Settings<'a, 't, 'u, 'i> {
@@ -140,16 +174,36 @@ impl<'a, 't, 'u, 'i> Settings<'a, 't, 'u, 'i> {
             sortable_fields: Setting::NotSet,
             criteria: Setting::NotSet,
             stop_words: Setting::NotSet,
+            stop_words_mode: Setting::NotSet,
             distinct_field: Setting::NotSet,
             synonyms: Setting::NotSet,
             primary_key: Setting::NotSet,
             authorize_typos: Setting::NotSet,
             exact_words: Setting::NotSet,
+            exact_words_additions: Vec::new(),
+            exact_words_removals: Vec::new(),
             min_word_len_two_typos: Setting::NotSet,
             min_word_len_one_typo: Setting::NotSet,
             exact_attributes: Setting::NotSet,
+            case_sensitive_attributes: Setting::NotSet,
             max_values_per_facet: Setting::NotSet,
             pagination_max_total_hits: Setting::NotSet,
+            max_word_length: Setting::NotSet,
+            long_words_policy: Setting::NotSet,
+            min_indexed_word_length: Setting::NotSet,
+            max_positions_per_word: Setting::NotSet,
+            max_fields: Setting::NotSet,
+            max_fields_policy: Setting::NotSet,
+            field_boosts: Setting::NotSet,
+            fields_languages: Setting::NotSet,
+            detected_languages_allow_list: Setting::NotSet,
+            stemming: Setting::NotSet,
+            normalization_rules: Setting::NotSet,
+            mixed_facet_types: Setting::NotSet,
+            proximity_databases: Setting::NotSet,
+            cross_field_proximity: Setting::NotSet,
+            typo_on_diacritics: Setting::NotSet,
+            apply_tokenizer_config: false,
             indexer_config,
         }
     }
@@ -178,6 +232,9 @@ impl<'a, 't, 'u, 'i> Settings<'a, 't, 'u, 'i> {
         self.filterable_fields = Setting::Set(names);
     }
 
+    /// Sets the fields the documents can be sorted by, a field also makes its nested fields
+    /// sortable (e.g. `author` makes `author.age` sortable) and a `*` segment matches any
+    /// segment (e.g. `author.*` or `*.age`), see [`crate::is_faceted_by`].
     pub fn set_sortable_fields(&mut self, names: HashSet<String>) {
         self.sortable_fields = Setting::Set(names);
     }
@@ -190,6 +247,8 @@ impl<'a, 't, 'u, 'i> Settings<'a, 't, 'u, 'i> {
         self.criteria = Setting::Reset;
     }
 
+    /// Sets the ranking rules, the field of a boost ranking rule must be filterable.
+    /// The field of an asc/desc ranking rule is faceted, whether it is sortable or not.
     pub fn set_criteria(&mut self, criteria: Vec<Criterion>) {
         self.criteria = Setting::Set(criteria);
     }
@@ -203,6 +262,16 @@ impl<'a, 't, 'u, 'i> Settings<'a, 't, 'u, 'i> {
             if stop_words.is_empty() { Setting::Reset } else { Setting::Set(stop_words) }
     }
 
+    /// Sets whether the stop words are removed from the indexed words or only ignored in the
+    /// queries, where the phrases still match them.
+    pub fn set_stop_words_mode(&mut self, mode: StopWordsMode) {
+        self.stop_words_mode = Setting::Set(mode);
+    }
+
+    pub fn reset_stop_words_mode(&mut self) {
+        self.stop_words_mode = Setting::Reset;
+    }
+
     pub fn reset_distinct_field(&mut self) {
         self.distinct_field = Setting::Reset;
     }
@@ -259,6 +328,20 @@ impl<'a, 't, 'u, 'i> Settings<'a, 't, 'u, 'i> {
         self.exact_words = Setting::Reset;
     }
 
+    /// Adds the words to the exact words, without rebuilding them from scratch.
+    ///
+    /// Adding a word that is already an exact word does nothing.
+    pub fn add_exact_words<I: IntoIterator<Item = String>>(&mut self, words: I) {
+        self.exact_words_additions.extend(words);
+    }
+
+    /// Removes the words from the exact words, without rebuilding them from scratch.
+    ///
+    /// Removing a word that is not an exact word does nothing.
+    pub fn remove_exact_words<I: IntoIterator<Item = String>>(&mut self, words: I) {
+        self.exact_words_removals.extend(words);
+    }
+
     pub fn set_exact_attributes(&mut self, attrs: HashSet<String>) {
         self.exact_attributes = Setting::Set(attrs);
     }
@@ -267,6 +350,16 @@ impl<'a, 't, 'u, 'i> Settings<'a, 't, 'u, 'i> {
         self.exact_attributes = Setting::Reset;
     }
 
+    /// Sets the attributes whose words are also indexed with their case, to be matched by
+    /// the case-sensitive searches. Changing them reindexes all the documents.
+    pub fn set_case_sensitive_attributes(&mut self, attrs: HashSet<String>) {
+        self.case_sensitive_attributes = Setting::Set(attrs);
+    }
+
+    pub fn reset_case_sensitive_attributes(&mut self) {
+        self.case_sensitive_attributes = Setting::Reset;
+    }
+
     pub fn set_max_values_per_facet(&mut self, value: usize) {
         self.max_values_per_facet = Setting::Set(value);
     }
@@ -283,6 +376,190 @@ impl<'a, 't, 'u, 'i> Settings<'a, 't, 'u, 'i> {
         self.pagination_max_total_hits = Setting::Reset;
     }
 
+    /// Sets the maximum length, in bytes, of the indexed and searched words.
+    /// It is capped to [`MAX_WORD_LENGTH`](crate::MAX_WORD_LENGTH).
+    pub fn set_max_word_length(&mut self, value: usize) {
+        self.max_word_length = Setting::Set(value);
+    }
+
+    pub fn reset_max_word_length(&mut self) {
+        self.max_word_length = Setting::Reset;
+    }
+
+    /// Sets whether the words longer than the maximum word length are dropped or truncated.
+    pub fn set_long_words_policy(&mut self, policy: LongWordsPolicy) {
+        self.long_words_policy = Setting::Set(policy);
+    }
+
+    pub fn reset_long_words_policy(&mut self) {
+        self.long_words_policy = Setting::Reset;
+    }
+
+    /// Sets the minimum length, in characters, of the indexed and searched words.
+    /// The shorter words are ignored, unlike the stop words they are never indexed.
+    pub fn set_min_indexed_word_length(&mut self, value: usize) {
+        self.min_indexed_word_length = Setting::Set(value);
+    }
+
+    pub fn reset_min_indexed_word_length(&mut self) {
+        self.min_indexed_word_length = Setting::Reset;
+    }
+
+    /// Sets the maximum number of positions stored for each word of a document, only the
+    /// first positions of a word are kept, in the order of the fields of the document.
+    ///
+    /// It reduces the size of the index for very repetitive documents, at the cost of the
+    /// precision of the proximity, attribute and exactness criteria and of the phrases, which
+    /// ignore the occurrences beyond the limit. A value of `0` is treated as `1`.
+    pub fn set_max_positions_per_word(&mut self, value: usize) {
+        self.max_positions_per_word = Setting::Set(value);
+    }
+
+    pub fn reset_max_positions_per_word(&mut self) {
+        self.max_positions_per_word = Setting::Reset;
+    }
+
+    /// Sets the maximum number of fields of the index, the primary key included. It prevents
+    /// the documents with many dynamically-named fields from growing the fields ids map
+    /// without bound, see [`set_max_fields_policy`](Self::set_max_fields_policy) for how
+    /// the documents bringing too many new fields are handled.
+    ///
+    /// The limit only applies to the next documents additions, the fields already known
+    /// by the index are kept. It can't be greater than [`MAX_FIELDS`].
+    pub fn set_max_fields(&mut self, value: usize) {
+        self.max_fields = Setting::Set(value);
+    }
+
+    pub fn reset_max_fields(&mut self) {
+        self.max_fields = Setting::Reset;
+    }
+
+    /// Sets whether the documents bringing more new fields than the maximum number of fields
+    /// allows fail the documents addition, are skipped or are truncated.
+    pub fn set_max_fields_policy(&mut self, policy: MaxFieldsPolicy) {
+        self.max_fields_policy = Setting::Set(policy);
+    }
+
+    pub fn reset_max_fields_policy(&mut self) {
+        self.max_fields_policy = Setting::Reset;
+    }
+
+    /// Sets the boost factor applied by the attribute criterion to the matches
+    /// found in these fields, the other fields have a boost of `1.0`.
+    ///
+    /// The boosts must be finite numbers greater than zero.
+    pub fn set_field_boosts(&mut self, boosts: BTreeMap<String, f32>) {
+        self.field_boosts = if boosts.is_empty() { Setting::Reset } else { Setting::Set(boosts) };
+    }
+
+    pub fn reset_field_boosts(&mut self) {
+        self.field_boosts = Setting::Reset;
+    }
+
+    /// Sets the language of the fields, as ISO 639-3 codes, used by the tokenizer
+    /// instead of the automatic language detection, e.g. `jpn` for Japanese.
+    pub fn set_fields_languages(&mut self, languages: BTreeMap<String, String>) {
+        self.fields_languages =
+            if languages.is_empty() { Setting::Reset } else { Setting::Set(languages) };
+    }
+
+    pub fn reset_fields_languages(&mut self) {
+        self.fields_languages = Setting::Reset;
+    }
+
+    /// Restricts the languages detected by the tokenizer, at both indexing and search time,
+    /// to the given ISO 639-3 codes. The scripts none of these languages are written in are
+    /// segmented without any language detection, an empty list detects every language.
+    pub fn set_detected_languages_allow_list(&mut self, languages: Vec<String>) {
+        self.detected_languages_allow_list =
+            if languages.is_empty() { Setting::Reset } else { Setting::Set(languages) };
+    }
+
+    pub fn reset_detected_languages_allow_list(&mut self) {
+        self.detected_languages_allow_list = Setting::Reset;
+    }
+
+    /// Sets whether the words of each language, given as an ISO 639-3 code, are stemmed.
+    /// The stems are indexed apart from the words, the documents containing a literal
+    /// match of the query words are still preferred by the exactness criterion.
+    pub fn set_stemming(&mut self, stemming: BTreeMap<String, bool>) {
+        self.stemming = if stemming.is_empty() { Setting::Reset } else { Setting::Set(stemming) };
+    }
+
+    pub fn reset_stemming(&mut self) {
+        self.stemming = Setting::Reset;
+    }
+
+    /// Sets the replacements applied to the words, at both indexing and search time, after
+    /// they have been lowercased and their diacritics removed by the tokenizer, e.g. `ß` → `ss`.
+    /// The rules are applied in the lexicographic order of their patterns.
+    ///
+    /// The stop words and the synonyms are normalized by the rules too, and the rules must be
+    /// given to the [`MatcherBuilder`](crate::MatcherBuilder) highlighting the documents.
+    pub fn set_normalization_rules(&mut self, rules: BTreeMap<String, String>) {
+        self.normalization_rules =
+            if rules.is_empty() { Setting::Reset } else { Setting::Set(rules) };
+    }
+
+    pub fn reset_normalization_rules(&mut self) {
+        self.normalization_rules = Setting::Reset;
+    }
+
+    /// Sets how the string values of the faceted fields that also contain numbers are indexed,
+    /// see [`MixedFacetTypes`].
+    pub fn set_mixed_facet_types(&mut self, modes: BTreeMap<String, MixedFacetTypes>) {
+        self.mixed_facet_types =
+            if modes.is_empty() { Setting::Reset } else { Setting::Set(modes) };
+    }
+
+    pub fn reset_mixed_facet_types(&mut self) {
+        self.mixed_facet_types = Setting::Reset;
+    }
+
+    /// Sets whether the word pair proximity databases are built, they are by default.
+    ///
+    /// Without them the index is smaller and faster to build, but the proximity criterion
+    /// is ignored and the phrases match the documents containing all their words, wherever
+    /// they are. Changing it reindexes all the documents.
+    pub fn set_proximity_databases(&mut self, value: bool) {
+        self.proximity_databases = Setting::Set(value);
+    }
+
+    pub fn reset_proximity_databases(&mut self) {
+        self.proximity_databases = Setting::Reset;
+    }
+
+    /// Sets whether the proximity between the words of two different fields of a document
+    /// is computed, it is not by default.
+    ///
+    /// When it is, the last word of a field is next to the first word of the following field,
+    /// the fields being ordered by id. Changing it reindexes all the documents.
+    pub fn set_cross_field_proximity(&mut self, value: bool) {
+        self.cross_field_proximity = Setting::Set(value);
+    }
+
+    pub fn reset_cross_field_proximity(&mut self) {
+        self.cross_field_proximity = Setting::Reset;
+    }
+
+    /// Sets how the accents of the words are taken into account by the typo tolerance, they
+    /// are ignored by default. Changing it reindexes all the documents.
+    pub fn set_typo_on_diacritics(&mut self, mode: TypoOnDiacritics) {
+        self.typo_on_diacritics = Setting::Set(mode);
+    }
+
+    pub fn reset_typo_on_diacritics(&mut self) {
+        self.typo_on_diacritics = Setting::Reset;
+    }
+
+    /// Reindexes all the documents with the tokenizer configuration of the [`IndexerConfig`]
+    /// if they were tokenized with another one, see [`TokenizerConfig`](super::TokenizerConfig).
+    ///
+    /// The other settings only apply the new configuration when they reindex all the documents.
+    pub fn apply_tokenizer_config(&mut self) {
+        self.apply_tokenizer_config = true;
+    }
+
     fn reindex<FP, FA>(
         &mut self,
         progress_callback: &FP,
@@ -300,32 +577,49 @@ impl<'a, 't, 'u, 'i> Settings<'a, 't, 'u, 'i> {
             return Ok(());
         }
 
-        let transform = Transform::new(
+        // all the documents are reindexed, their booleans end up in their own database.
+        self.index.put_boolean_facets_indexed(self.wtxn)?;
+        reindex_all_documents(
             self.wtxn,
             self.index,
             self.indexer_config,
-            IndexDocumentsMethod::ReplaceDocuments,
-            false,
-        )?;
-
-        // We clear the databases and remap the documents fields based on the new `FieldsIdsMap`.
-        let output = transform.prepare_for_documents_reindexing(
-            self.wtxn,
             old_fields_ids_map,
             fields_ids_map,
-        )?;
+            progress_callback,
+            should_abort,
+        )
+    }
 
-        // We index the generated `TransformOutput` which must contain
-        // all the documents with fields in the newly defined searchable order.
-        let indexing_builder = IndexDocuments::new(
-            self.wtxn,
-            self.index,
-            self.indexer_config,
-            IndexDocumentsConfig::default(),
-            &progress_callback,
-            &should_abort,
-        )?;
-        indexing_builder.execute_raw(output)?;
+    /// Deletes the facet values and faceted documents of the fields that were faceted
+    /// by the old settings and are not anymore. The fields that are still faceted because
+    /// they are sortable, distinct or used by an Asc/Desc criterion are kept.
+    fn remove_unfaceted_fields(
+        &mut self,
+        old_faceted_fields: &HashSet<String>,
+        new_faceted_fields: &HashSet<String>,
+        fields_ids_map: &FieldsIdsMap,
+    ) -> Result<()> {
+        let removed_fields_ids: Vec<_> = fields_ids_map
+            .iter()
+            .filter(|(_, name)| {
+                crate::is_faceted(name, old_faceted_fields)
+                    && !crate::is_faceted(name, new_faceted_fields)
+            })
+            .map(|(field_id, _)| field_id)
+            .collect();
+
+        for field_id in removed_fields_ids {
+            let prefix = field_id.to_be_bytes();
+            delete_prefixed_entries(self.wtxn, &self.index.facet_id_f64_docids, &prefix)?;
+            delete_prefixed_entries(self.wtxn, &self.index.facet_id_string_docids, &prefix)?;
+            delete_prefixed_entries(self.wtxn, &self.index.facet_id_exists_docids, &prefix)?;
+            delete_prefixed_entries(self.wtxn, &self.index.facet_id_bool_docids, &prefix)?;
+            delete_prefixed_entries(self.wtxn, &self.index.facet_id_string_originals, &prefix)?;
+            delete_prefixed_entries(self.wtxn, &self.index.field_id_docid_facet_f64s, &prefix)?;
+            delete_prefixed_entries(self.wtxn, &self.index.field_id_docid_facet_strings, &prefix)?;
+            self.index.delete_faceted_documents_ids(self.wtxn, field_id, FacetType::Number)?;
+            self.index.delete_faceted_documents_ids(self.wtxn, field_id, FacetType::String)?;
+        }
 
         Ok(())
     }
@@ -389,11 +683,17 @@ impl<'a, 't, 'u, 'i> Settings<'a, 't, 'u, 'i> {
                 // Add all the searchable attributes to the field map, and then add the
                 // remaining fields from the old field map to the new one
                 for name in names.iter() {
-                    new_fields_ids_map.insert(name).ok_or(UserError::AttributeLimitReached)?;
+                    new_fields_ids_map.insert(name).ok_or(UserError::AttributeLimitReached {
+                        external_id: None,
+                        limit: MAX_FIELDS,
+                    })?;
                 }
 
                 for (_, name) in old_fields_ids_map.iter() {
-                    new_fields_ids_map.insert(name).ok_or(UserError::AttributeLimitReached)?;
+                    new_fields_ids_map.insert(name).ok_or(UserError::AttributeLimitReached {
+                        external_id: None,
+                        limit: MAX_FIELDS,
+                    })?;
                 }
 
                 self.index.put_all_searchable_fields_from_fields_ids_map(
@@ -433,6 +733,22 @@ impl<'a, 't, 'u, 'i> Settings<'a, 't, 'u, 'i> {
         }
     }
 
+    fn indexed_stop_words_bytes(&self) -> Result<Option<Vec<u8>>> {
+        let stop_words = self.index.indexed_stop_words(self.wtxn)?;
+        Ok(stop_words.map(|stop_words| stop_words.as_fst().as_bytes().to_vec()))
+    }
+
+    fn update_stop_words_mode(&mut self) -> Result<()> {
+        match self.stop_words_mode {
+            Setting::Set(mode) => self.index.put_stop_words_mode(self.wtxn, mode)?,
+            Setting::Reset => {
+                self.index.delete_stop_words_mode(self.wtxn)?;
+            }
+            Setting::NotSet => (),
+        }
+        Ok(())
+    }
+
     fn update_synonyms(&mut self) -> Result<bool> {
         match self.synonyms {
             Setting::Set(ref synonyms) => {
@@ -450,6 +766,9 @@ impl<'a, 't, 'u, 'i> Settings<'a, 't, 'u, 'i> {
                 }
 
                 let mut builder = TokenizerBuilder::new();
+                if let Some(tokenizer_config) = &self.indexer_config.tokenizer_config {
+                    tokenizer_config.configure(&mut builder);
+                }
                 let stop_words = self.index.stop_words(self.wtxn)?;
                 if let Some(ref stop_words) = stop_words {
                     builder.stop_words(stop_words);
@@ -508,6 +827,25 @@ impl<'a, 't, 'u, 'i> Settings<'a, 't, 'u, 'i> {
         }
     }
 
+    fn update_case_sensitive_attributes(&mut self) -> Result<bool> {
+        match self.case_sensitive_attributes {
+            Setting::Set(ref attrs) => {
+                let old_attrs = self.index.case_sensitive_attributes(self.wtxn)?;
+                let old_attrs = old_attrs.into_iter().map(String::from).collect::<HashSet<_>>();
+
+                if attrs != &old_attrs {
+                    let attrs = attrs.iter().map(String::as_str).collect::<Vec<_>>();
+                    self.index.put_case_sensitive_attributes(self.wtxn, &attrs)?;
+                    Ok(true)
+                } else {
+                    Ok(false)
+                }
+            }
+            Setting::Reset => Ok(self.index.delete_case_sensitive_attributes(self.wtxn)?),
+            Setting::NotSet => Ok(false),
+        }
+    }
+
     fn update_filterable(&mut self) -> Result<()> {
         match self.filterable_fields {
             Setting::Set(ref fields) => {
@@ -555,12 +893,36 @@ impl<'a, 't, 'u, 'i> Settings<'a, 't, 'u, 'i> {
         Ok(())
     }
 
+    /// Checks that the field of every boost ranking rule is filterable, the boost ranking rules
+    /// rely on the facet values of their field.
+    ///
+    /// It is checked once both the filterable fields and the ranking rules are updated,
+    /// a field can't stop being filterable while a boost ranking rule uses it.
+    fn validate_boost_criteria(&self) -> Result<()> {
+        let filterable_fields = self.index.filterable_fields(self.wtxn)?;
+        for criterion in self.index.criteria(self.wtxn)? {
+            if let Criterion::Boost(field) = criterion {
+                if !crate::is_faceted(&field, &filterable_fields) {
+                    return Err(UserError::InvalidBoostAttribute {
+                        field,
+                        valid_fields: filterable_fields.into_iter().collect(),
+                    }
+                    .into());
+                }
+            }
+        }
+        Ok(())
+    }
+
     fn update_primary_key(&mut self) -> Result<()> {
         match self.primary_key {
             Setting::Set(ref primary_key) => {
                 if self.index.number_of_documents(self.wtxn)? == 0 {
                     let mut fields_ids_map = self.index.fields_ids_map(self.wtxn)?;
-                    fields_ids_map.insert(primary_key).ok_or(UserError::AttributeLimitReached)?;
+                    fields_ids_map.insert(primary_key).ok_or(UserError::AttributeLimitReached {
+                        external_id: None,
+                        limit: MAX_FIELDS,
+                    })?;
                     self.index.put_fields_ids_map(self.wtxn, &fields_ids_map)?;
                     self.index.put_primary_key(self.wtxn, primary_key)?;
                     Ok(())
@@ -630,26 +992,38 @@ impl<'a, 't, 'u, 'i> Settings<'a, 't, 'u, 'i> {
         Ok(())
     }
 
-    fn update_exact_words(&mut self) -> Result<()> {
-        match self.exact_words {
-            Setting::Set(ref mut words) => {
-                fn normalize(tokenizer: &Tokenizer<&[u8]>, text: &str) -> String {
-                    tokenizer.tokenize(text).map(|token| token.lemma().to_string()).collect()
-                }
+    /// Normalizes the exact words like the words of the documents, and sorts them.
+    fn normalize_exact_words<'w>(
+        &self,
+        words: impl IntoIterator<Item = &'w String>,
+    ) -> Result<Vec<String>> {
+        fn normalize(tokenizer: &Tokenizer<&[u8]>, text: &str) -> String {
+            tokenizer.tokenize(text).map(|token| token.lemma().to_string()).collect()
+        }
 
-                let mut builder = TokenizerBuilder::new();
-                let stop_words = self.index.stop_words(self.wtxn)?;
-                if let Some(ref stop_words) = stop_words {
-                    builder.stop_words(stop_words);
-                }
-                let tokenizer = builder.build();
+        let mut builder = TokenizerBuilder::new();
+        if let Some(tokenizer_config) = &self.indexer_config.tokenizer_config {
+            tokenizer_config.configure(&mut builder);
+        }
+        let stop_words = self.index.stop_words(self.wtxn)?;
+        if let Some(ref stop_words) = stop_words {
+            builder.stop_words(stop_words);
+        }
+        let tokenizer = builder.build();
 
-                let mut words: Vec<_> =
-                    words.iter().map(|word| normalize(&tokenizer, word)).collect();
+        let mut words: Vec<_> = words.into_iter().map(|word| normalize(&tokenizer, word)).collect();
 
-                // normalization could reorder words
-                words.sort_unstable();
+        // normalization could reorder words
+        words.sort_unstable();
+        words.dedup();
+
+        Ok(words)
+    }
 
+    fn update_exact_words(&mut self) -> Result<()> {
+        match self.exact_words {
+            Setting::Set(ref words) => {
+                let words = self.normalize_exact_words(words)?;
                 let words = fst::Set::from_iter(words.iter())?;
                 self.index.put_exact_words(self.wtxn, &words)?;
             }
@@ -659,6 +1033,19 @@ impl<'a, 't, 'u, 'i> Settings<'a, 't, 'u, 'i> {
             Setting::NotSet => (),
         }
 
+        if !self.exact_words_additions.is_empty() || !self.exact_words_removals.is_empty() {
+            let additions = self.normalize_exact_words(&self.exact_words_additions)?;
+            let additions = fst::Set::from_iter(additions.iter())?;
+            let removals = self.normalize_exact_words(&self.exact_words_removals)?;
+            let removals = fst::Set::from_iter(removals.iter())?;
+
+            let words = match self.index.exact_words(self.wtxn)? {
+                Some(words) => merge_exact_words(&words, &additions, &removals)?,
+                None => merge_exact_words(&fst::Set::default(), &additions, &removals)?,
+            };
+            self.index.put_exact_words(self.wtxn, &words)?;
+        }
+
         Ok(())
     }
 
@@ -690,99 +1077,433 @@ impl<'a, 't, 'u, 'i> Settings<'a, 't, 'u, 'i> {
         Ok(())
     }
 
-    pub fn execute<FP, FA>(mut self, progress_callback: FP, should_abort: FA) -> Result<()>
-    where
-        FP: Fn(UpdateIndexingStep) + Sync,
-        FA: Fn() -> bool + Sync,
-    {
-        self.index.set_updated_at(self.wtxn, &OffsetDateTime::now_utc())?;
-
-        let old_faceted_fields = self.index.user_defined_faceted_fields(self.wtxn)?;
-        let old_fields_ids_map = self.index.fields_ids_map(self.wtxn)?;
-
-        self.update_displayed()?;
-        self.update_filterable()?;
-        self.update_sortable()?;
-        self.update_distinct_field()?;
-        self.update_criteria()?;
-        self.update_primary_key()?;
-        self.update_authorize_typos()?;
-        self.update_min_typo_word_len()?;
-        self.update_exact_words()?;
-        self.update_max_values_per_facet()?;
-        self.update_pagination_max_total_hits()?;
-
-        // If there is new faceted fields we indicate that we must reindex as we must
-        // index new fields as facets. It means that the distinct attribute,
-        // an Asc/Desc criterion or a filtered attribute as be added or removed.
-        let new_faceted_fields = self.index.user_defined_faceted_fields(self.wtxn)?;
-        let faceted_updated = old_faceted_fields != new_faceted_fields;
-
-        let stop_words_updated = self.update_stop_words()?;
-        let synonyms_updated = self.update_synonyms()?;
-        let searchable_updated = self.update_searchable()?;
-        let exact_attributes_updated = self.update_exact_attributes()?;
+    fn update_max_fields(&mut self) -> Result<()> {
+        match self.max_fields {
+            Setting::Set(max) => {
+                self.index.put_max_fields(self.wtxn, max)?;
+            }
+            Setting::Reset => {
+                self.index.delete_max_fields(self.wtxn)?;
+            }
+            Setting::NotSet => (),
+        }
 
-        if stop_words_updated
-            || faceted_updated
-            || synonyms_updated
-            || searchable_updated
-            || exact_attributes_updated
-        {
-            self.reindex(&progress_callback, &should_abort, old_fields_ids_map)?;
+        match self.max_fields_policy {
+            Setting::Set(policy) => {
+                self.index.put_max_fields_policy(self.wtxn, policy)?;
+            }
+            Setting::Reset => {
+                self.index.delete_max_fields_policy(self.wtxn)?;
+            }
+            Setting::NotSet => (),
         }
 
         Ok(())
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use big_s::S;
-    use heed::types::ByteSlice;
-    use maplit::{btreeset, hashmap, hashset};
+    fn update_fields_languages(&mut self) -> Result<bool> {
+        match self.fields_languages {
+            Setting::Set(ref languages) => {
+                for (field, language) in languages {
+                    if script_language_from_code(language).is_none() {
+                        return Err(UserError::InvalidFieldLanguage {
+                            field: field.clone(),
+                            language: language.clone(),
+                        }
+                        .into());
+                    }
+                }
 
-    use super::*;
-    use crate::error::Error;
-    use crate::index::tests::TempIndex;
-    use crate::update::{ClearDocuments, DeleteDocuments};
-    use crate::{Criterion, Filter, SearchResult};
+                if self.index.fields_languages(self.wtxn)? != *languages {
+                    self.index.put_fields_languages(self.wtxn, languages)?;
+                    Ok(true)
+                } else {
+                    Ok(false)
+                }
+            }
+            Setting::Reset => Ok(self.index.delete_fields_languages(self.wtxn)?),
+            Setting::NotSet => Ok(false),
+        }
+    }
 
-    #[test]
-    fn set_and_reset_searchable_fields() {
-        let index = TempIndex::new();
+    fn update_detected_languages_allow_list(&mut self) -> Result<bool> {
+        match self.detected_languages_allow_list {
+            Setting::Set(ref languages) => {
+                if let Some(language) =
+                    languages.iter().find(|l| script_language_from_code(l).is_none())
+                {
+                    return Err(
+                        UserError::InvalidDetectedLanguage { language: language.clone() }.into()
+                    );
+                }
 
-        // First we send 3 documents with ids from 1 to 3.
-        let mut wtxn = index.write_txn().unwrap();
+                if self.index.detected_languages_allow_list(self.wtxn)? != *languages {
+                    self.index.put_detected_languages_allow_list(self.wtxn, languages)?;
+                    Ok(true)
+                } else {
+                    Ok(false)
+                }
+            }
+            Setting::Reset => Ok(self.index.delete_detected_languages_allow_list(self.wtxn)?),
+            Setting::NotSet => Ok(false),
+        }
+    }
 
-        index
-            .add_documents_using_wtxn(
-                &mut wtxn,
-                documents!([
-                    { "id": 1, "name": "kevin", "age": 23 },
-                    { "id": 2, "name": "kevina", "age": 21},
-                    { "id": 3, "name": "benoit", "age": 34 }
-                ]),
-            )
-            .unwrap();
+    fn update_stemming(&mut self) -> Result<bool> {
+        match self.stemming {
+            Setting::Set(ref stemming) => {
+                if let Some(language) = stemming.keys().find(|code| !is_stemmable_code(code)) {
+                    return Err(
+                        UserError::InvalidStemmingLanguage { language: language.clone() }.into()
+                    );
+                }
 
-        // We change the searchable fields to be the "name" field only.
-        index
-            .update_settings_using_wtxn(&mut wtxn, |settings| {
-                settings.set_searchable_fields(vec!["name".into()]);
-            })
-            .unwrap();
+                if self.index.stemming(self.wtxn)? != *stemming {
+                    self.index.put_stemming(self.wtxn, stemming)?;
+                    Ok(true)
+                } else {
+                    Ok(false)
+                }
+            }
+            Setting::Reset => Ok(self.index.delete_stemming(self.wtxn)?),
+            Setting::NotSet => Ok(false),
+        }
+    }
 
-        wtxn.commit().unwrap();
+    fn update_normalization_rules(&mut self) -> Result<bool> {
+        match self.normalization_rules {
+            Setting::Set(ref rules) => {
+                if let Some((pattern, replacement)) =
+                    rules.iter().find(|(pattern, _)| pattern.is_empty())
+                {
+                    return Err(UserError::InvalidNormalizationRule {
+                        pattern: pattern.clone(),
+                        replacement: replacement.clone(),
+                    }
+                    .into());
+                }
 
-        // Check that the searchable field is correctly set to "name" only.
-        let rtxn = index.read_txn().unwrap();
-        // When we search for something that is not in
-        // the searchable fields it must not return any document.
-        let result = index.search(&rtxn).query("23").execute().unwrap();
-        assert!(result.documents_ids.is_empty());
+                if self.index.normalization_rules(self.wtxn)? != *rules {
+                    self.index.put_normalization_rules(self.wtxn, rules)?;
+                    Ok(true)
+                } else {
+                    Ok(false)
+                }
+            }
+            Setting::Reset => Ok(self.index.delete_normalization_rules(self.wtxn)?),
+            Setting::NotSet => Ok(false),
+        }
+    }
 
-        // When we search for something that is in the searchable fields
+    fn update_mixed_facet_types(&mut self) -> Result<bool> {
+        match self.mixed_facet_types {
+            Setting::Set(ref modes) => {
+                if self.index.mixed_facet_types(self.wtxn)? != *modes {
+                    self.index.put_mixed_facet_types(self.wtxn, modes)?;
+                    Ok(true)
+                } else {
+                    Ok(false)
+                }
+            }
+            Setting::Reset => Ok(self.index.delete_mixed_facet_types(self.wtxn)?),
+            Setting::NotSet => Ok(false),
+        }
+    }
+
+    fn update_field_boosts(&mut self) -> Result<()> {
+        match self.field_boosts {
+            Setting::Set(ref boosts) => {
+                if let Some((field, &boost)) =
+                    boosts.iter().find(|(_, boost)| !(boost.is_finite() && **boost > 0.0))
+                {
+                    return Err(UserError::InvalidFieldBoost { field: field.clone(), boost }.into());
+                }
+                self.index.put_field_boosts(self.wtxn, boosts)?;
+            }
+            Setting::Reset => {
+                self.index.delete_field_boosts(self.wtxn)?;
+            }
+            Setting::NotSet => (),
+        }
+
+        Ok(())
+    }
+
+    fn update_max_word_length(&mut self) -> Result<bool> {
+        let old_max_word_length = self.index.max_word_length(self.wtxn)?;
+        match self.max_word_length {
+            Setting::Set(max) => {
+                self.index.put_max_word_length(self.wtxn, max)?;
+            }
+            Setting::Reset => {
+                self.index.delete_max_word_length(self.wtxn)?;
+            }
+            Setting::NotSet => return Ok(false),
+        }
+
+        Ok(old_max_word_length != self.index.max_word_length(self.wtxn)?)
+    }
+
+    fn update_min_indexed_word_length(&mut self) -> Result<bool> {
+        let old_min_word_length = self.index.min_indexed_word_length(self.wtxn)?;
+        match self.min_indexed_word_length {
+            Setting::Set(min) => {
+                self.index.put_min_indexed_word_length(self.wtxn, min)?;
+            }
+            Setting::Reset => {
+                self.index.delete_min_indexed_word_length(self.wtxn)?;
+            }
+            Setting::NotSet => return Ok(false),
+        }
+
+        Ok(old_min_word_length != self.index.min_indexed_word_length(self.wtxn)?)
+    }
+
+    fn update_long_words_policy(&mut self) -> Result<bool> {
+        let old_policy = self.index.long_words_policy(self.wtxn)?;
+        match self.long_words_policy {
+            Setting::Set(policy) => {
+                self.index.put_long_words_policy(self.wtxn, policy)?;
+            }
+            Setting::Reset => {
+                self.index.delete_long_words_policy(self.wtxn)?;
+            }
+            Setting::NotSet => return Ok(false),
+        }
+
+        Ok(old_policy != self.index.long_words_policy(self.wtxn)?)
+    }
+
+    fn update_max_positions_per_word(&mut self) -> Result<bool> {
+        let old_max_positions = self.index.max_positions_per_word(self.wtxn)?;
+        match self.max_positions_per_word {
+            Setting::Set(max) => {
+                self.index.put_max_positions_per_word(self.wtxn, max)?;
+            }
+            Setting::Reset => {
+                self.index.delete_max_positions_per_word(self.wtxn)?;
+            }
+            Setting::NotSet => return Ok(false),
+        }
+
+        Ok(old_max_positions != self.index.max_positions_per_word(self.wtxn)?)
+    }
+
+    fn update_proximity_databases(&mut self) -> Result<bool> {
+        let old_proximity_databases = self.index.proximity_databases(self.wtxn)?;
+        match self.proximity_databases {
+            Setting::Set(value) => {
+                self.index.put_proximity_databases(self.wtxn, value)?;
+            }
+            Setting::Reset => {
+                self.index.delete_proximity_databases(self.wtxn)?;
+            }
+            Setting::NotSet => return Ok(false),
+        }
+
+        Ok(old_proximity_databases != self.index.proximity_databases(self.wtxn)?)
+    }
+
+    fn update_cross_field_proximity(&mut self) -> Result<bool> {
+        let old_cross_field_proximity = self.index.cross_field_proximity(self.wtxn)?;
+        match self.cross_field_proximity {
+            Setting::Set(value) => {
+                self.index.put_cross_field_proximity(self.wtxn, value)?;
+            }
+            Setting::Reset => {
+                self.index.delete_cross_field_proximity(self.wtxn)?;
+            }
+            Setting::NotSet => return Ok(false),
+        }
+
+        Ok(old_cross_field_proximity != self.index.cross_field_proximity(self.wtxn)?)
+    }
+
+    fn update_typo_on_diacritics(&mut self) -> Result<bool> {
+        let old_typo_on_diacritics = self.index.typo_on_diacritics(self.wtxn)?;
+        match self.typo_on_diacritics {
+            Setting::Set(mode) => {
+                self.index.put_typo_on_diacritics(self.wtxn, mode)?;
+            }
+            Setting::Reset => {
+                self.index.delete_typo_on_diacritics(self.wtxn)?;
+            }
+            Setting::NotSet => return Ok(false),
+        }
+
+        Ok(old_typo_on_diacritics != self.index.typo_on_diacritics(self.wtxn)?)
+    }
+
+    pub fn execute<FP, FA>(mut self, progress_callback: FP, should_abort: FA) -> Result<()>
+    where
+        FP: Fn(UpdateIndexingStep) + Sync,
+        FA: Fn() -> bool + Sync,
+    {
+        self.index.set_updated_at(self.wtxn, &OffsetDateTime::now_utc())?;
+
+        let old_faceted_fields = self.index.user_defined_faceted_fields(self.wtxn)?;
+        let old_fields_ids_map = self.index.fields_ids_map(self.wtxn)?;
+
+        self.update_displayed()?;
+        self.update_filterable()?;
+        self.update_sortable()?;
+        self.update_distinct_field()?;
+        self.update_criteria()?;
+        self.validate_boost_criteria()?;
+        self.update_primary_key()?;
+        self.update_authorize_typos()?;
+        self.update_min_typo_word_len()?;
+        self.update_exact_words()?;
+        self.update_max_values_per_facet()?;
+        self.update_pagination_max_total_hits()?;
+        self.update_max_fields()?;
+        self.update_field_boosts()?;
+
+        // If there is new faceted fields we indicate that we must reindex as we must
+        // index new fields as facets. It means that the distinct attribute,
+        // an Asc/Desc criterion or a filtered attribute as be added or removed.
+        let new_faceted_fields = self.index.user_defined_faceted_fields(self.wtxn)?;
+        let faceted_updated = old_faceted_fields != new_faceted_fields;
+        if faceted_updated {
+            self.remove_unfaceted_fields(
+                &old_faceted_fields,
+                &new_faceted_fields,
+                &old_fields_ids_map,
+            )?;
+        }
+
+        // The stop words are only removed from the documents in the `IndexAndSearch` mode,
+        // the documents are reindexed when the stop words removed from them change.
+        let old_indexed_stop_words = self.indexed_stop_words_bytes()?;
+        self.update_stop_words()?;
+        self.update_stop_words_mode()?;
+        let indexed_stop_words_updated =
+            old_indexed_stop_words != self.indexed_stop_words_bytes()?;
+        let synonyms_updated = self.update_synonyms()?;
+        let searchable_updated = self.update_searchable()?;
+        let exact_attributes_updated = self.update_exact_attributes()?;
+        let case_sensitive_attributes_updated = self.update_case_sensitive_attributes()?;
+        let max_word_length_updated = self.update_max_word_length()?;
+        let long_words_policy_updated = self.update_long_words_policy()?;
+        let min_indexed_word_length_updated = self.update_min_indexed_word_length()?;
+        let max_positions_per_word_updated = self.update_max_positions_per_word()?;
+        let fields_languages_updated = self.update_fields_languages()?;
+        let detected_languages_updated = self.update_detected_languages_allow_list()?;
+        let stemming_updated = self.update_stemming()?;
+        let normalization_rules_updated = self.update_normalization_rules()?;
+        let mixed_facet_types_updated = self.update_mixed_facet_types()?;
+        let proximity_databases_updated = self.update_proximity_databases()?;
+        let cross_field_proximity_updated = self.update_cross_field_proximity()?;
+        let typo_on_diacritics_updated = self.update_typo_on_diacritics()?;
+        // The documents are only tokenized again with a new tokenizer configuration on demand.
+        let tokenizer_updated = self.apply_tokenizer_config
+            && self.index.tokenizer_fingerprint(self.wtxn)?
+                != self.indexer_config.tokenizer_fingerprint();
+
+        if indexed_stop_words_updated
+            || faceted_updated
+            || synonyms_updated
+            || searchable_updated
+            || exact_attributes_updated
+            || case_sensitive_attributes_updated
+            || max_word_length_updated
+            || long_words_policy_updated
+            || min_indexed_word_length_updated
+            || max_positions_per_word_updated
+            || fields_languages_updated
+            || detected_languages_updated
+            || stemming_updated
+            || normalization_rules_updated
+            || mixed_facet_types_updated
+            || proximity_databases_updated
+            || cross_field_proximity_updated
+            || typo_on_diacritics_updated
+            || tokenizer_updated
+        {
+            self.reindex(&progress_callback, &should_abort, old_fields_ids_map)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Deletes all the entries of the database whose key starts with the given prefix.
+fn delete_prefixed_entries<K, V>(
+    wtxn: &mut heed::RwTxn,
+    db: &heed::Database<K, V>,
+    prefix: &[u8],
+) -> heed::Result<()> {
+    let mut iter = db.remap_types::<ByteSlice, DecodeIgnore>().prefix_iter_mut(wtxn, prefix)?;
+    while let Some(result) = iter.next() {
+        result?;
+        // safety: we don't keep references from inside the LMDB database.
+        unsafe { iter.del_current()? };
+    }
+    Ok(())
+}
+
+/// Streams the union of the exact words with the additions, minus the removals, into a new set.
+fn merge_exact_words<A: AsRef<[u8]>>(
+    words: &fst::Set<A>,
+    additions: &fst::Set<Vec<u8>>,
+    removals: &fst::Set<Vec<u8>>,
+) -> Result<fst::Set<Vec<u8>>> {
+    let mut builder = fst::SetBuilder::memory();
+    builder.extend_stream(words.op().add(additions.stream()).union())?;
+    let words = builder.into_set();
+
+    let mut builder = fst::SetBuilder::memory();
+    builder.extend_stream(words.op().add(removals.stream()).difference())?;
+    Ok(builder.into_set())
+}
+
+#[cfg(test)]
+mod tests {
+    use big_s::S;
+    use heed::types::ByteSlice;
+    use maplit::{btreemap, btreeset, hashmap, hashset};
+
+    use super::*;
+    use crate::error::Error;
+    use crate::index::tests::TempIndex;
+    use crate::update::{ClearDocuments, DeleteDocuments, DeletionStrategy};
+    use crate::{db_snap, Criterion, Filter, SearchResult, TermsMatchingStrategy};
+
+    #[test]
+    fn set_and_reset_searchable_fields() {
+        let index = TempIndex::new();
+
+        // First we send 3 documents with ids from 1 to 3.
+        let mut wtxn = index.write_txn().unwrap();
+
+        index
+            .add_documents_using_wtxn(
+                &mut wtxn,
+                documents!([
+                    { "id": 1, "name": "kevin", "age": 23 },
+                    { "id": 2, "name": "kevina", "age": 21},
+                    { "id": 3, "name": "benoit", "age": 34 }
+                ]),
+            )
+            .unwrap();
+
+        // We change the searchable fields to be the "name" field only.
+        index
+            .update_settings_using_wtxn(&mut wtxn, |settings| {
+                settings.set_searchable_fields(vec!["name".into()]);
+            })
+            .unwrap();
+
+        wtxn.commit().unwrap();
+
+        // Check that the searchable field is correctly set to "name" only.
+        let rtxn = index.read_txn().unwrap();
+        // When we search for something that is not in
+        // the searchable fields it must not return any document.
+        let result = index.search(&rtxn).query("23").execute().unwrap();
+        assert!(result.documents_ids.is_empty());
+
+        // When we search for something that is in the searchable fields
         // we must find the appropriate document.
         let result = index.search(&rtxn).query(r#""kevin""#).execute().unwrap();
         let documents = index.documents(&rtxn, result.documents_ids).unwrap();
@@ -980,6 +1701,52 @@ mod tests {
         assert_eq!(count, 4);
     }
 
+    #[test]
+    fn remove_filterable_field_deletes_its_facets() {
+        let index = TempIndex::new();
+
+        index
+            .update_settings(|settings| {
+                settings.set_primary_key(S("id"));
+                settings.set_filterable_fields(hashset! { S("name"), S("age") });
+                settings.set_sortable_fields(hashset! { S("age") });
+            })
+            .unwrap();
+
+        index
+            .add_documents(documents!([
+                { "id": 0, "name": "kevin", "age": 23 },
+                { "id": 1, "name": "kevina", "age": 21 },
+                { "id": 2, "name": "benoit", "age": 34 }
+            ]))
+            .unwrap();
+
+        db_snap!(index, facet_id_string_docids, 1, @r###"
+        1   0  benoit       1  [2, ]
+        1   0  kevin        1  [0, ]
+        1   0  kevina       1  [1, ]
+        "###);
+
+        // The age stays faceted because it is still sortable.
+        index.update_settings(|settings| settings.reset_filterable_fields()).unwrap();
+
+        db_snap!(index, facet_id_string_docids, 2, @"");
+        db_snap!(index, field_id_docid_facet_strings, 2, @"");
+        db_snap!(index, facet_id_f64_docids, 2, @r###"
+        2   0  21     1  [1, ]
+        2   0  23     1  [0, ]
+        2   0  34     1  [2, ]
+        "###);
+
+        let rtxn = index.read_txn().unwrap();
+        let fields_ids_map = index.fields_ids_map(&rtxn).unwrap();
+        let name_id = fields_ids_map.id("name").unwrap();
+        let age_id = fields_ids_map.id("age").unwrap();
+        assert!(index.exists_faceted_documents_ids(&rtxn, name_id).unwrap().is_empty());
+        assert!(index.faceted_documents_ids(&rtxn, name_id, FacetType::String).unwrap().is_empty());
+        assert_eq!(index.faceted_documents_ids(&rtxn, age_id, FacetType::Number).unwrap().len(), 3);
+    }
+
     #[test]
     fn set_asc_desc_field() {
         let mut index = TempIndex::new();
@@ -1018,6 +1785,31 @@ mod tests {
         assert_eq!(iter.collect::<Vec<_>>(), vec![21, 23, 34]);
     }
 
+    #[test]
+    fn asc_desc_field_is_faceted_implicitly() {
+        let index = TempIndex::new();
+
+        // the field of an asc/desc ranking rule doesn't need to be sortable or filterable.
+        index
+            .update_settings(|settings| {
+                settings.set_filterable_fields(hashset! { S("genre") });
+                settings.set_criteria(vec![Criterion::Desc(S("release_date"))]);
+            })
+            .unwrap();
+
+        let rtxn = index.read_txn().unwrap();
+        let faceted_fields = index.user_defined_faceted_fields(&rtxn).unwrap();
+        assert_eq!(faceted_fields, hashset! { S("genre"), S("release_date") });
+        assert!(index.sortable_fields(&rtxn).unwrap().is_empty());
+        drop(rtxn);
+
+        // the field stops being faceted with the ranking rule.
+        index.update_settings(|settings| settings.reset_criteria()).unwrap();
+        let rtxn = index.read_txn().unwrap();
+        let faceted_fields = index.user_defined_faceted_fields(&rtxn).unwrap();
+        assert_eq!(faceted_fields, hashset! { S("genre") });
+    }
+
     #[test]
     fn set_distinct_field() {
         let mut index = TempIndex::new();
@@ -1185,6 +1977,65 @@ mod tests {
         assert_eq!(result.documents_ids.len(), 1); // there is one benoit in our data
     }
 
+    #[test]
+    fn search_only_stop_words() {
+        let index = TempIndex::new();
+
+        index
+            .update_settings(|settings| {
+                settings.set_stop_words(btreeset! { S("the"), S("who") });
+                settings.set_stop_words_mode(StopWordsMode::SearchOnly);
+            })
+            .unwrap();
+        index
+            .add_documents(documents!([
+                { "id": 0, "band": "The Who" },
+                { "id": 1, "band": "Who framed Roger Rabbit" },
+                { "id": 2, "band": "The Doors" },
+            ]))
+            .unwrap();
+
+        let search = |index: &TempIndex, query: &str| {
+            let rtxn = index.read_txn().unwrap();
+            let SearchResult { mut documents_ids, .. } =
+                index.search(&rtxn).query(query).execute().unwrap();
+            documents_ids.sort_unstable();
+            documents_ids
+        };
+
+        // the stop words are indexed and only the phrases match them
+        let rtxn = index.read_txn().unwrap();
+        assert!(index.word_docids.get(&rtxn, "the").unwrap().is_some());
+        let settings = crate::snapshot_tests::snap_settings(&index);
+        assert!(settings.contains("stop_words_mode: SearchOnly"), "{}", settings);
+        drop(rtxn);
+        assert_eq!(search(&index, "\"the who\""), vec![0]);
+        assert_eq!(search(&index, "the doors"), vec![2]);
+        assert_eq!(search(&index, "the who roger"), vec![1]);
+
+        // the documents are not reindexed when the stop words change
+        index
+            .update_settings(|settings| {
+                settings.set_stop_words(btreeset! { S("the"), S("who"), S("doors") });
+            })
+            .unwrap();
+        let rtxn = index.read_txn().unwrap();
+        assert!(index.word_docids.get(&rtxn, "doors").unwrap().is_some());
+        drop(rtxn);
+        assert_eq!(search(&index, "\"the doors\""), vec![2]);
+
+        // but they are when the stop words are removed from them again
+        index
+            .update_settings(|settings| {
+                settings.reset_stop_words_mode();
+            })
+            .unwrap();
+        let rtxn = index.read_txn().unwrap();
+        assert_eq!(index.stop_words_mode(&rtxn).unwrap(), StopWordsMode::IndexAndSearch);
+        assert!(index.word_docids.get(&rtxn, "the").unwrap().is_none());
+        assert!(index.word_docids.get(&rtxn, "doors").unwrap().is_none());
+    }
+
     #[test]
     fn set_and_reset_synonyms() {
         let mut index = TempIndex::new();
@@ -1475,22 +2326,766 @@ mod tests {
     }
 
     #[test]
-    fn test_correct_settings_init() {
+    fn add_and_remove_exact_words() {
+        let exact_words_bytes = |index: &TempIndex| {
+            let rtxn = index.read_txn().unwrap();
+            let words = index.exact_words(&rtxn).unwrap().unwrap();
+            words.as_fst().as_bytes().to_vec()
+        };
+
         let index = TempIndex::new();
+        index
+            .update_settings(|settings| {
+                settings.add_exact_words(vec![S("Kefir"), S("intel")]);
+            })
+            .unwrap();
+        index
+            .update_settings(|settings| {
+                settings.set_exact_words(btreeset! { S("a"), S("c"), S("e") });
+            })
+            .unwrap();
+        index
+            .update_settings(|settings| {
+                settings.add_exact_words(vec![S("B"), S("c"), S("zz"), S("b")]);
+                settings.remove_exact_words(vec![S("e"), S("missing")]);
+            })
+            .unwrap();
+
+        let rebuilt = TempIndex::new();
+        rebuilt
+            .update_settings(|settings| {
+                settings.set_exact_words(btreeset! { S("a"), S("b"), S("c"), S("zz") });
+            })
+            .unwrap();
+        assert_eq!(exact_words_bytes(&index), exact_words_bytes(&rebuilt));
 
+        // duplicate additions and removals of absent words are no-ops
         index
             .update_settings(|settings| {
-                // we don't actually update the settings, just check their content
-                let Settings {
-                    wtxn: _,
-                    index: _,
-                    indexer_config: _,
+                settings.add_exact_words(vec![S("a"), S("zz")]);
+                settings.remove_exact_words(vec![S("e")]);
+            })
+            .unwrap();
+        assert_eq!(exact_words_bytes(&index), exact_words_bytes(&rebuilt));
+
+        // the additions are merged even when there are no exact words yet
+        let index = TempIndex::new();
+        index.update_settings(|settings| settings.add_exact_words(vec![S("Kefir")])).unwrap();
+        let rtxn = index.read_txn().unwrap();
+        let words = index.exact_words(&rtxn).unwrap().unwrap();
+        assert_eq!(words.stream().into_strs().unwrap(), vec![S("kefir")]);
+    }
+
+    #[test]
+    fn max_word_length() {
+        let index = TempIndex::new();
+
+        index
+            .update_settings(|settings| {
+                settings.set_max_word_length(10);
+            })
+            .unwrap();
+        index
+            .add_documents(documents!([
+                { "id": 0, "text": "hello abcdefghijklmnopqrstuvwxyz" },
+            ]))
+            .unwrap();
+
+        // the overlong word is dropped by default
+        let rtxn = index.read_txn().unwrap();
+        assert_eq!(index.max_word_length(&rtxn).unwrap(), 10);
+        assert!(index.word_docids.get(&rtxn, "hello").unwrap().is_some());
+        assert!(index.word_docids.get(&rtxn, "abcdefghijklmnopqrstuvwxyz").unwrap().is_none());
+        assert!(index.word_docids.get(&rtxn, "abcdefghij").unwrap().is_none());
+        // and ignored at query time
+        let SearchResult { documents_ids, .. } = index
+            .search(&rtxn)
+            .query("hello abcdefghijklmnopqrstuvwxyz")
+            .terms_matching_strategy(crate::TermsMatchingStrategy::All)
+            .execute()
+            .unwrap();
+        assert_eq!(documents_ids, vec![0]);
+        drop(rtxn);
+
+        index
+            .update_settings(|settings| {
+                settings.set_long_words_policy(LongWordsPolicy::Truncate);
+            })
+            .unwrap();
+
+        // the overlong word is now truncated, both when indexing and searching
+        let rtxn = index.read_txn().unwrap();
+        assert!(index.word_docids.get(&rtxn, "abcdefghijklmnopqrstuvwxyz").unwrap().is_none());
+        assert!(index.word_docids.get(&rtxn, "abcdefghij").unwrap().is_some());
+        let SearchResult { documents_ids, .. } =
+            index.search(&rtxn).query("abcdefghijklmnop ").execute().unwrap();
+        assert_eq!(documents_ids, vec![0]);
+        drop(rtxn);
+
+        index
+            .update_settings(|settings| {
+                settings.reset_max_word_length();
+                settings.reset_long_words_policy();
+            })
+            .unwrap();
+
+        let rtxn = index.read_txn().unwrap();
+        assert_eq!(index.max_word_length(&rtxn).unwrap(), crate::MAX_WORD_LENGTH);
+        assert!(index.word_docids.get(&rtxn, "abcdefghijklmnopqrstuvwxyz").unwrap().is_some());
+    }
+
+    #[test]
+    fn min_indexed_word_length() {
+        let index = TempIndex::new();
+
+        index
+            .update_settings(|settings| {
+                settings.set_min_indexed_word_length(3);
+                settings.set_exact_attributes(hashset! { S("title") });
+            })
+            .unwrap();
+        index
+            .add_documents(documents!([
+                { "id": 0, "title": "a cat of the house", "text": "an old cat" },
+                { "id": 1, "title": "sunflower", "text": "x y z" },
+            ]))
+            .unwrap();
+
+        // the short words are neither indexed nor in the exact attributes
+        let rtxn = index.read_txn().unwrap();
+        assert_eq!(index.min_indexed_word_length(&rtxn).unwrap(), 3);
+        for word in ["a", "of", "an", "x"] {
+            assert!(index.word_docids.get(&rtxn, word).unwrap().is_none(), "{}", word);
+            assert!(index.exact_word_docids.get(&rtxn, word).unwrap().is_none(), "{}", word);
+        }
+        assert!(index.word_docids.get(&rtxn, "old").unwrap().is_some());
+        assert!(index.exact_word_docids.get(&rtxn, "the").unwrap().is_some());
+
+        // they are ignored at query time but keep their position in the phrases
+        let search = |query: &str| {
+            let SearchResult { documents_ids, .. } = index
+                .search(&rtxn)
+                .query(query)
+                .terms_matching_strategy(crate::TermsMatchingStrategy::All)
+                .execute()
+                .unwrap();
+            documents_ids
+        };
+        assert_eq!(search("an old cat of the"), vec![0]);
+        assert_eq!(search("\"cat of the house\""), vec![0]);
+        assert_eq!(search("\"cat the house\""), Vec::<u32>::new());
+        // and the n-grams are made of the words long enough
+        assert_eq!(search("of sun flower"), vec![1]);
+        drop(rtxn);
+
+        index
+            .update_settings(|settings| {
+                settings.reset_min_indexed_word_length();
+            })
+            .unwrap();
+
+        let rtxn = index.read_txn().unwrap();
+        assert_eq!(index.min_indexed_word_length(&rtxn).unwrap(), 0);
+        assert!(index.word_docids.get(&rtxn, "of").unwrap().is_some());
+    }
+
+    #[test]
+    fn max_positions_per_word() {
+        let index = TempIndex::new();
+
+        index
+            .update_settings(|settings| {
+                settings.set_max_positions_per_word(2);
+            })
+            .unwrap();
+        index
+            .add_documents(documents!([
+                { "id": 0, "text": "hello hello hello world hello" },
+            ]))
+            .unwrap();
+
+        // only the two first positions of hello are stored
+        let rtxn = index.read_txn().unwrap();
+        let text_id = index.fields_ids_map(&rtxn).unwrap().id("text").unwrap();
+        let positions = index.docid_word_positions.get(&rtxn, &(0, "hello")).unwrap().unwrap();
+        let positions: Vec<_> =
+            positions.iter().map(crate::relative_from_absolute_position).collect();
+        assert_eq!(positions, vec![(text_id, 0), (text_id, 1)]);
+        let positions = index.docid_word_positions.get(&rtxn, &(0, "world")).unwrap().unwrap();
+        assert_eq!(positions.len(), 1);
+
+        // the proximity is computed on the stored positions only
+        let SearchResult { documents_ids, .. } =
+            index.search(&rtxn).query("hello world").execute().unwrap();
+        assert_eq!(documents_ids, vec![0]);
+        let SearchResult { documents_ids, .. } =
+            index.search(&rtxn).query("\"hello world\"").execute().unwrap();
+        assert!(documents_ids.is_empty());
+        drop(rtxn);
+
+        index.update_settings(|settings| settings.reset_max_positions_per_word()).unwrap();
+
+        let rtxn = index.read_txn().unwrap();
+        assert_eq!(index.max_positions_per_word(&rtxn).unwrap(), None);
+        let positions = index.docid_word_positions.get(&rtxn, &(0, "hello")).unwrap().unwrap();
+        assert_eq!(positions.len(), 5);
+        let SearchResult { documents_ids, .. } =
+            index.search(&rtxn).query("\"hello world\"").execute().unwrap();
+        assert_eq!(documents_ids, vec![0]);
+    }
+
+    #[test]
+    fn proximity_databases() {
+        let index = TempIndex::new();
+        index
+            .update_settings(|settings| {
+                settings.set_primary_key(S("id"));
+                settings.set_proximity_databases(false);
+            })
+            .unwrap();
+        index
+            .add_documents(documents!([
+                { "id": 0, "text": "hello world" },
+                { "id": 1, "text": "world and a very long text before saying hello" },
+            ]))
+            .unwrap();
+        // the setting is respected by the following batches
+        index
+            .add_documents(documents!([
+                { "id": 2, "text": "hello there world" },
+                { "id": 3, "text": "goodbye world" },
+            ]))
+            .unwrap();
+
+        let rtxn = index.read_txn().unwrap();
+        assert!(!index.proximity_databases(&rtxn).unwrap());
+        assert!(index.word_pair_proximity_docids.is_empty(&rtxn).unwrap());
+        assert!(index.word_prefix_pair_proximity_docids.is_empty(&rtxn).unwrap());
+        assert!(index.prefix_word_pair_proximity_docids.is_empty(&rtxn).unwrap());
+
+        // the proximity criterion is ignored, the documents are ranked by the next criteria
+        let SearchResult { mut documents_ids, .. } = index
+            .search(&rtxn)
+            .query("hello world")
+            .terms_matching_strategy(TermsMatchingStrategy::All)
+            .execute()
+            .unwrap();
+        documents_ids.sort_unstable();
+        assert_eq!(documents_ids, vec![0, 1, 2]);
+
+        // the phrases match the documents containing all their words
+        let SearchResult { mut documents_ids, .. } =
+            index.search(&rtxn).query("\"hello world\"").execute().unwrap();
+        documents_ids.sort_unstable();
+        assert_eq!(documents_ids, vec![0, 1, 2]);
+        drop(rtxn);
+
+        // enabling them again rebuilds them
+        index.update_settings(|settings| settings.reset_proximity_databases()).unwrap();
+
+        let rtxn = index.read_txn().unwrap();
+        assert!(index.proximity_databases(&rtxn).unwrap());
+        assert!(!index.word_pair_proximity_docids.is_empty(&rtxn).unwrap());
+
+        let SearchResult { documents_ids, .. } =
+            index.search(&rtxn).query("\"hello world\"").execute().unwrap();
+        assert_eq!(documents_ids, vec![0]);
+    }
+
+    #[test]
+    fn cross_field_proximity() {
+        let index = TempIndex::new();
+        index
+            .update_settings(|settings| {
+                settings.set_primary_key(S("id"));
+                settings.set_searchable_fields(vec![S("title"), S("body")]);
+            })
+            .unwrap();
+        index
+            .add_documents(documents!([
+                { "id": 0, "title": "the quick fox", "body": "jumps over the lazy dog" },
+            ]))
+            .unwrap();
+
+        // the last word of the title is not next to the first word of the body
+        let rtxn = index.read_txn().unwrap();
+        assert!(!index.cross_field_proximity(&rtxn).unwrap());
+        let db = index.word_pair_proximity_docids;
+        assert!(db.get(&rtxn, &(1, "quick", "fox")).unwrap().is_some());
+        for proximity in 1..8 {
+            assert!(db.get(&rtxn, &(proximity, "fox", "jumps")).unwrap().is_none());
+        }
+        drop(rtxn);
+
+        index.update_settings(|settings| settings.set_cross_field_proximity(true)).unwrap();
+
+        let rtxn = index.read_txn().unwrap();
+        assert!(index.cross_field_proximity(&rtxn).unwrap());
+        let docids = db.get(&rtxn, &(1, "fox", "jumps")).unwrap().unwrap();
+        assert_eq!(docids.into_iter().collect::<Vec<_>>(), vec![0]);
+        let docids = db.get(&rtxn, &(2, "quick", "jumps")).unwrap().unwrap();
+        assert_eq!(docids.into_iter().collect::<Vec<_>>(), vec![0]);
+        // the proximities within a field are unchanged
+        assert!(db.get(&rtxn, &(1, "quick", "fox")).unwrap().is_some());
+        drop(rtxn);
+
+        index.update_settings(|settings| settings.reset_cross_field_proximity()).unwrap();
+
+        let rtxn = index.read_txn().unwrap();
+        assert!(db.get(&rtxn, &(1, "fox", "jumps")).unwrap().is_none());
+    }
+
+    #[test]
+    fn mixed_facet_types() {
+        use crate::facet::FacetType;
+
+        let mut index = TempIndex::new();
+        index.index_documents_config.deletion_strategy = DeletionStrategy::AlwaysHard;
+        index
+            .update_settings(|settings| {
+                settings.set_primary_key(S("id"));
+                settings.set_filterable_fields(hashset! { S("price") });
+            })
+            .unwrap();
+        index
+            .add_documents(documents!([
+                { "id": 0, "price": "10" },
+                { "id": 1, "price": 10 },
+                { "id": 2, "price": "cheap" },
+            ]))
+            .unwrap();
+
+        // returns the documents with a number and with a string value, and the ones below 20.
+        let facets = |index: &TempIndex| {
+            let rtxn = index.read_txn().unwrap();
+            let price = index.fields_ids_map(&rtxn).unwrap().id("price").unwrap();
+            let numbers = index.faceted_documents_ids(&rtxn, price, FacetType::Number).unwrap();
+            let strings = index.faceted_documents_ids(&rtxn, price, FacetType::String).unwrap();
+            let filter = Filter::from_str("price < 20").unwrap().unwrap();
+            let below = filter.evaluate(&rtxn, index).unwrap();
+            let to_vec = |docids: roaring::RoaringBitmap| docids.into_iter().collect::<Vec<_>>();
+            (to_vec(numbers), to_vec(strings), to_vec(below))
+        };
+
+        // by default the numbers and strings are indexed separately
+        assert_eq!(facets(&index), (vec![1], vec![0, 2], vec![1]));
+
+        index
+            .update_settings(|settings| {
+                settings.set_mixed_facet_types(btreemap! { S("price") => MixedFacetTypes::Coerce });
+            })
+            .unwrap();
+        assert_eq!(facets(&index), (vec![0, 1], vec![2], vec![0, 1]));
+
+        index
+            .update_settings(|settings| {
+                settings
+                    .set_mixed_facet_types(btreemap! { S("price") => MixedFacetTypes::DualIndex });
+            })
+            .unwrap();
+        assert_eq!(facets(&index), (vec![0, 1], vec![0, 1, 2], vec![0, 1]));
+
+        // the strings are rejected, whether they are numeric or not
+        let error = index
+            .update_settings(|settings| {
+                settings.set_mixed_facet_types(
+                    btreemap! { S("price") => MixedFacetTypes::StrictNumeric },
+                );
+            })
+            .unwrap_err();
+        assert!(matches!(
+            error,
+            Error::UserError(UserError::InvalidNumericFacetValue { ref field, .. }) if field == "price"
+        ));
+
+        index.delete_document("0");
+        index.delete_document("2");
+        index
+            .update_settings(|settings| {
+                settings.set_mixed_facet_types(
+                    btreemap! { S("price") => MixedFacetTypes::StrictNumeric },
+                );
+            })
+            .unwrap();
+        assert_eq!(facets(&index), (vec![1], vec![], vec![1]));
+
+        let error = index.add_documents(documents!([{ "id": 3, "price": "10" }])).unwrap_err();
+        assert!(matches!(
+            error,
+            Error::UserError(UserError::InvalidNumericFacetValue { ref value, .. }) if value == "10"
+        ));
+        index.add_documents(documents!([{ "id": 3, "price": 5 }])).unwrap();
+        assert_eq!(facets(&index), (vec![1, 3], vec![], vec![1, 3]));
+    }
+
+    #[cfg(feature = "default")]
+    #[test]
+    fn fields_languages() {
+        let index = TempIndex::new();
+
+        index
+            .update_settings(|settings| {
+                settings.set_fields_languages(btreemap! { S("title_ja") => S("jpn") });
+            })
+            .unwrap();
+        index
+            .add_documents(documents!([
+                { "id": 0, "title_en": "I live in Tokyo", "title_ja": "東京都に住んでいます" },
+            ]))
+            .unwrap();
+
+        let rtxn = index.read_txn().unwrap();
+        assert_eq!(index.fields_languages(&rtxn).unwrap(), btreemap! { S("title_ja") => S("jpn") });
+
+        // the japanese field is segmented into several words
+        let title_ja = index.fields_ids_map(&rtxn).unwrap().id("title_ja").unwrap();
+        let mut words = Vec::new();
+        for result in index.docid_word_positions.prefix_iter(&rtxn, &(0, "")).unwrap() {
+            let ((_, word), positions) = result.unwrap();
+            if positions.iter().any(|pos| crate::relative_from_absolute_position(pos).0 == title_ja)
+            {
+                words.push(word.to_string());
+            }
+        }
+        assert!(words.len() > 1, "{:?}", words);
+        assert!(!words.contains(&S("東京都に住んでいます")));
+    }
+
+    #[test]
+    fn invalid_fields_languages() {
+        let index = TempIndex::new();
+
+        let error = index
+            .update_settings(|settings| {
+                settings.set_fields_languages(btreemap! { S("title") => S("klingon") });
+            })
+            .unwrap_err();
+        assert!(matches!(
+            error,
+            Error::UserError(UserError::InvalidFieldLanguage { ref field, ref language })
+                if field == "title" && language == "klingon"
+        ));
+    }
+
+    #[cfg(feature = "default")]
+    #[test]
+    fn detected_languages_allow_list() {
+        let index = TempIndex::new();
+
+        index
+            .update_settings(|settings| {
+                settings.set_detected_languages_allow_list(vec![S("eng"), S("fra")]);
+            })
+            .unwrap();
+        index.add_documents(documents!([{ "id": 0, "name": "北京大学" }])).unwrap();
+
+        let rtxn = index.read_txn().unwrap();
+        assert_eq!(index.detected_languages_allow_list(&rtxn).unwrap(), vec![S("eng"), S("fra")]);
+
+        // without the chinese detection, the ideographs are segmented one by one
+        let words: Vec<_> = index
+            .docid_word_positions
+            .prefix_iter(&rtxn, &(0, ""))
+            .unwrap()
+            .map(|result| result.unwrap().0 .1.to_string())
+            .collect();
+        assert_eq!(words.len(), 4, "{:?}", words);
+        for ideograph in ["北", "京", "大", "学"] {
+            assert!(index.word_docids.get(&rtxn, ideograph).unwrap().is_some());
+        }
+        drop(rtxn);
+
+        // the chinese detection segments the same string into words
+        index
+            .update_settings(|settings| {
+                settings.reset_detected_languages_allow_list();
+            })
+            .unwrap();
+        let rtxn = index.read_txn().unwrap();
+        assert!(index.detected_languages_allow_list(&rtxn).unwrap().is_empty());
+        assert!(index.word_docids.get(&rtxn, "京").unwrap().is_none());
+    }
+
+    #[test]
+    fn invalid_detected_languages_allow_list() {
+        let index = TempIndex::new();
+
+        let error = index
+            .update_settings(|settings| {
+                settings.set_detected_languages_allow_list(vec![S("eng"), S("klingon")]);
+            })
+            .unwrap_err();
+        assert!(matches!(
+            error,
+            Error::UserError(UserError::InvalidDetectedLanguage { ref language })
+                if language == "klingon"
+        ));
+    }
+
+    #[test]
+    fn stemming() {
+        let index = TempIndex::new();
+
+        index
+            .update_settings(|settings| {
+                settings.set_criteria(vec![Criterion::Words, Criterion::Exactness]);
+            })
+            .unwrap();
+        index
+            .add_documents(documents!([
+                { "id": 0, "text": "the cat runs" },
+                { "id": 1, "text": "two cats running" },
+                { "id": 2, "text": "a sleeping dog" },
+            ]))
+            .unwrap();
+
+        let search = |index: &TempIndex, query: &str| {
+            let rtxn = index.read_txn().unwrap();
+            let SearchResult { documents_ids, .. } = index
+                .search(&rtxn)
+                .query(query)
+                .terms_matching_strategy(TermsMatchingStrategy::All)
+                .execute()
+                .unwrap();
+            documents_ids
+        };
+        // only the literal words match without stemming
+        assert_eq!(search(&index, "running"), vec![1]);
+        assert_eq!(search(&index, "dogs"), Vec::<u32>::new());
+
+        index
+            .update_settings(|settings| {
+                settings.set_stemming(btreemap! { S("eng") => true });
+            })
+            .unwrap();
+
+        let rtxn = index.read_txn().unwrap();
+        assert_eq!(index.stemming(&rtxn).unwrap(), btreemap! { S("eng") => true });
+        assert!(index.word_docids.get(&rtxn, &crate::stem_word("run")).unwrap().is_some());
+        assert!(index.word_docids.get(&rtxn, "run").unwrap().is_none());
+        assert!(index.word_docids.get(&rtxn, "runs").unwrap().is_some());
+        drop(rtxn);
+
+        // the verb forms and the plurals match, the literal matches first
+        assert_eq!(search(&index, "running"), vec![1, 0]);
+        assert_eq!(search(&index, "runs"), vec![0, 1]);
+        assert_eq!(search(&index, "dogs"), vec![2]);
+        assert_eq!(search(&index, "sleep dogs"), vec![2]);
+
+        // disabling the stemming reindexes the documents without the stems
+        index
+            .update_settings(|settings| {
+                settings.set_stemming(btreemap! { S("eng") => false });
+            })
+            .unwrap();
+        let rtxn = index.read_txn().unwrap();
+        assert!(index.word_docids.get(&rtxn, &crate::stem_word("run")).unwrap().is_none());
+        drop(rtxn);
+        assert_eq!(search(&index, "running"), vec![1]);
+    }
+
+    #[test]
+    fn stemming_keeps_the_distant_word_pairs() {
+        let index = TempIndex::new();
+
+        index
+            .update_settings(|settings| {
+                settings.set_stemming(btreemap! { S("eng") => true });
+            })
+            .unwrap();
+        index.add_documents(documents!([{ "id": 0, "text": "cats a b c d dogs" }])).unwrap();
+
+        // the stems are at the same positions as the words, they don't shrink the proximity window
+        let rtxn = index.read_txn().unwrap();
+        let docids = index.word_pair_proximity_docids.get(&rtxn, &(5, "cats", "dogs")).unwrap();
+        assert_eq!(docids, Some(roaring::RoaringBitmap::from_iter([0])));
+    }
+
+    #[test]
+    fn stemming_prefers_the_literal_words() {
+        let index = TempIndex::new();
+
+        index
+            .update_settings(|settings| {
+                settings.set_criteria(vec![Criterion::Words, Criterion::Exactness]);
+                settings.set_stemming(btreemap! { S("eng") => true });
+            })
+            .unwrap();
+        index
+            .add_documents(documents!([
+                { "id": 0, "text": "running fast" },
+                { "id": 1, "text": "run fast" },
+            ]))
+            .unwrap();
+
+        // the stem of `running` is not an exact match of `run`
+        let rtxn = index.read_txn().unwrap();
+        let SearchResult { documents_ids, .. } =
+            index.search(&rtxn).query("run").execute().unwrap();
+        assert_eq!(documents_ids, vec![1, 0]);
+    }
+
+    #[test]
+    fn invalid_stemming() {
+        let index = TempIndex::new();
+
+        let error = index
+            .update_settings(|settings| {
+                settings.set_stemming(btreemap! { S("eng") => true, S("jpn") => true });
+            })
+            .unwrap_err();
+        assert!(matches!(
+            error,
+            Error::UserError(UserError::InvalidStemmingLanguage { ref language })
+                if language == "jpn"
+        ));
+    }
+
+    #[test]
+    fn normalization_rules() {
+        let index = TempIndex::new();
+
+        index
+            .add_documents(documents!([
+                { "id": 0, "street": "Hauptstraße" },
+                { "id": 1, "street": "Bahnhofstrasse" },
+                { "id": 2, "street": "Straße der Pariser Kommune" },
+                { "id": 3, "street": "Strasse des 17. Juni" },
+            ]))
+            .unwrap();
+
+        let search = |query: &str| {
+            let rtxn = index.read_txn().unwrap();
+            let mut search = index.search(&rtxn);
+            search.query(query).authorize_typos(false);
+            let SearchResult { documents_ids, .. } = search.execute().unwrap();
+            documents_ids
+        };
+
+        // without the rule, the words are considered different.
+        assert_eq!(search("straße"), vec![2]);
+        assert_eq!(search("strasse"), vec![3]);
+
+        index
+            .update_settings(|settings| {
+                settings.set_normalization_rules(btreemap! { S("ß") => S("ss") });
+            })
+            .unwrap();
+
+        let rtxn = index.read_txn().unwrap();
+        assert_eq!(index.normalization_rules(&rtxn).unwrap(), btreemap! { S("ß") => S("ss") });
+        drop(rtxn);
+
+        // the documents are reindexed and both spellings match each other.
+        assert_eq!(search("straße"), vec![2, 3]);
+        assert_eq!(search("strasse"), vec![2, 3]);
+        assert_eq!(search("hauptstrasse"), vec![0]);
+
+        let error = index
+            .update_settings(|settings| {
+                settings.set_normalization_rules(btreemap! { S("") => S("ss") });
+            })
+            .unwrap_err();
+        assert!(matches!(
+            error,
+            Error::UserError(UserError::InvalidNormalizationRule { ref pattern, .. })
+                if pattern.is_empty()
+        ));
+    }
+
+    #[test]
+    fn invalid_field_boosts() {
+        let index = TempIndex::new();
+
+        for boost in [f32::NAN, f32::INFINITY, f32::NEG_INFINITY, -2.0, 0.0] {
+            let error = index
+                .update_settings(|settings| {
+                    settings.set_field_boosts(btreemap! { S("title") => 2.0, S("text") => boost });
+                })
+                .unwrap_err();
+            assert!(matches!(
+                error,
+                Error::UserError(UserError::InvalidFieldBoost { ref field, .. }) if field == "text"
+            ));
+        }
+        let error = index
+            .update_settings(|settings| settings.set_field_boosts(btreemap! { S("text") => -2.0 }))
+            .unwrap_err();
+        insta::assert_display_snapshot!(error, @"The boost `-2` of the `text` field is invalid. A field boost must be a finite number greater than zero.");
+
+        // the boosts greater than zero are accepted, even lower than one.
+        index
+            .update_settings(|settings| {
+                settings.set_field_boosts(btreemap! { S("title") => 2.0, S("text") => 0.5 });
+            })
+            .unwrap();
+        let rtxn = index.read_txn().unwrap();
+        assert_eq!(
+            index.field_boosts(&rtxn).unwrap(),
+            btreemap! { S("text") => 0.5, S("title") => 2.0 }
+        );
+    }
+
+    #[test]
+    fn normalization_rules_stop_words_and_synonyms() {
+        let index = TempIndex::new();
+
+        index
+            .update_settings(|settings| {
+                settings.set_normalization_rules(btreemap! { S("ß") => S("ss") });
+                settings.set_stop_words(btreeset! { S("grosse") });
+                settings.set_synonyms(hashmap! {
+                    S("main") => vec![S("hauptstraße")],
+                    S("straße") => vec![S("allee")],
+                });
+            })
+            .unwrap();
+        index
+            .add_documents(documents!([
+                { "id": 0, "street": "Hauptstraße" },
+                { "id": 1, "street": "Große Allee" },
+            ]))
+            .unwrap();
+
+        // the words normalized into a stop word are not indexed.
+        let rtxn = index.read_txn().unwrap();
+        let words = index.words_fst(&rtxn).unwrap().stream().into_strs().unwrap();
+        assert_eq!(words, vec![S("allee"), S("hauptstrasse")]);
+        drop(rtxn);
+
+        let search = |query: &str| {
+            let rtxn = index.read_txn().unwrap();
+            let mut search = index.search(&rtxn);
+            search.query(query).authorize_typos(false);
+            let SearchResult { documents_ids, .. } = search.execute().unwrap();
+            documents_ids
+        };
+
+        // the synonyms are normalized by the rules like the words of the query.
+        assert_eq!(search("main"), vec![0]);
+        assert_eq!(search("strasse"), vec![1]);
+        // the stop words of the query are ignored whatever their spelling.
+        assert_eq!(search("große allee"), vec![1]);
+    }
+
+    #[test]
+    fn test_correct_settings_init() {
+        let index = TempIndex::new();
+
+        index
+            .update_settings(|settings| {
+                // we don't actually update the settings, just check their content
+                let Settings {
+                    wtxn: _,
+                    index: _,
+                    indexer_config: _,
                     searchable_fields,
                     displayed_fields,
                     filterable_fields,
                     sortable_fields,
                     criteria,
                     stop_words,
+                    stop_words_mode,
                     distinct_field,
                     synonyms,
                     primary_key,
@@ -1498,9 +3093,28 @@ mod tests {
                     min_word_len_two_typos,
                     min_word_len_one_typo,
                     exact_words,
+                    exact_words_additions,
+                    exact_words_removals,
                     exact_attributes,
+                    case_sensitive_attributes,
                     max_values_per_facet,
                     pagination_max_total_hits,
+                    max_word_length,
+                    long_words_policy,
+                    min_indexed_word_length,
+                    max_positions_per_word,
+                    max_fields,
+                    max_fields_policy,
+                    field_boosts,
+                    fields_languages,
+                    detected_languages_allow_list,
+                    stemming,
+                    normalization_rules,
+                    mixed_facet_types,
+                    proximity_databases,
+                    cross_field_proximity,
+                    typo_on_diacritics,
+                    apply_tokenizer_config,
                 } = settings;
                 assert!(matches!(searchable_fields, Setting::NotSet));
                 assert!(matches!(displayed_fields, Setting::NotSet));
@@ -1508,6 +3122,7 @@ mod tests {
                 assert!(matches!(sortable_fields, Setting::NotSet));
                 assert!(matches!(criteria, Setting::NotSet));
                 assert!(matches!(stop_words, Setting::NotSet));
+                assert!(matches!(stop_words_mode, Setting::NotSet));
                 assert!(matches!(distinct_field, Setting::NotSet));
                 assert!(matches!(synonyms, Setting::NotSet));
                 assert!(matches!(primary_key, Setting::NotSet));
@@ -1515,9 +3130,28 @@ mod tests {
                 assert!(matches!(min_word_len_two_typos, Setting::NotSet));
                 assert!(matches!(min_word_len_one_typo, Setting::NotSet));
                 assert!(matches!(exact_words, Setting::NotSet));
+                assert!(exact_words_additions.is_empty());
+                assert!(exact_words_removals.is_empty());
                 assert!(matches!(exact_attributes, Setting::NotSet));
+                assert!(matches!(case_sensitive_attributes, Setting::NotSet));
                 assert!(matches!(max_values_per_facet, Setting::NotSet));
                 assert!(matches!(pagination_max_total_hits, Setting::NotSet));
+                assert!(matches!(max_word_length, Setting::NotSet));
+                assert!(matches!(long_words_policy, Setting::NotSet));
+                assert!(matches!(min_indexed_word_length, Setting::NotSet));
+                assert!(matches!(max_positions_per_word, Setting::NotSet));
+                assert!(matches!(max_fields, Setting::NotSet));
+                assert!(matches!(max_fields_policy, Setting::NotSet));
+                assert!(matches!(field_boosts, Setting::NotSet));
+                assert!(matches!(fields_languages, Setting::NotSet));
+                assert!(matches!(detected_languages_allow_list, Setting::NotSet));
+                assert!(matches!(stemming, Setting::NotSet));
+                assert!(matches!(normalization_rules, Setting::NotSet));
+                assert!(matches!(mixed_facet_types, Setting::NotSet));
+                assert!(matches!(proximity_databases, Setting::NotSet));
+                assert!(matches!(cross_field_proximity, Setting::NotSet));
+                assert!(matches!(typo_on_diacritics, Setting::NotSet));
+                assert!(!apply_tokenizer_config);
             })
             .unwrap();
     }
@@ -1536,7 +3170,9 @@ mod tests {
 
         let mut wtxn = index.write_txn().unwrap();
         let mut builder = DeleteDocuments::new(&mut wtxn, &index).unwrap();
-        (0..5).for_each(|id| drop(builder.delete_external_id(&id.to_string())));
+        for id in 0..5 {
+            assert!(builder.delete_external_id(&id.to_string()).is_some());
+        }
         builder.execute().unwrap();
 
         index
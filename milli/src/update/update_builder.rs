@@ -0,0 +1,133 @@
+use std::io::{Read, Seek};
+
+use super::{
+    DocumentAdditionResult, IndexDocuments, IndexDocumentsConfig, IndexerConfig, Settings,
+    UpdateIndexingStep,
+};
+use crate::documents::DocumentsBatchReader;
+use crate::{Index, Result};
+
+/// Builds the updates made of several operations applied in a single write transaction.
+///
+/// The operations are not committed, an error leaves the transaction in an intermediate
+/// state and it must then be aborted.
+pub struct UpdateBuilder<'a> {
+    indexer_config: &'a IndexerConfig,
+}
+
+impl<'a> UpdateBuilder<'a> {
+    pub fn new(indexer_config: &'a IndexerConfig) -> UpdateBuilder<'a> {
+        UpdateBuilder { indexer_config }
+    }
+
+    /// Applies the settings changes made by `update_settings` then adds the batch of documents,
+    /// the documents are extracted under the new settings.
+    ///
+    /// The existing documents are reindexed first if the settings changes require it.
+    /// A user error of the batch is returned like the other errors.
+    #[allow(clippy::too_many_arguments)]
+    pub fn settings_and_documents<'t, R, S, FP, FA>(
+        &self,
+        wtxn: &mut heed::RwTxn<'t, '_>,
+        index: &'t Index,
+        update_settings: S,
+        config: IndexDocumentsConfig,
+        documents: DocumentsBatchReader<R>,
+        progress: FP,
+        should_abort: FA,
+    ) -> Result<DocumentAdditionResult>
+    where
+        R: Read + Seek,
+        S: FnOnce(&mut Settings),
+        FP: Fn(UpdateIndexingStep) + Sync,
+        FA: Fn() -> bool + Sync,
+    {
+        let mut settings = Settings::new(wtxn, index, self.indexer_config);
+        update_settings(&mut settings);
+        settings.execute(&progress, &should_abort)?;
+
+        let builder = IndexDocuments::new(
+            wtxn,
+            index,
+            self.indexer_config,
+            config,
+            &progress,
+            &should_abort,
+        )?;
+        let (builder, user_result) = builder.add_documents(documents)?;
+        user_result?;
+        builder.execute()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use big_s::S;
+
+    use super::*;
+    use crate::documents::documents_batch_reader_from_objects;
+    use crate::index::tests::TempIndex;
+
+    #[test]
+    fn settings_and_documents_in_one_transaction() {
+        let index = TempIndex::new();
+        index
+            .update_settings(|settings| {
+                settings.set_searchable_fields(vec![S("title")]);
+            })
+            .unwrap();
+        index.add_documents(documents!([{ "id": 0, "title": "hello", "text": "world" }])).unwrap();
+
+        let mut wtxn = index.write_txn().unwrap();
+        let result = UpdateBuilder::new(&index.indexer_config)
+            .settings_and_documents(
+                &mut wtxn,
+                &index,
+                |settings| settings.set_searchable_fields(vec![S("text")]),
+                IndexDocumentsConfig::default(),
+                documents!([{ "id": 1, "title": "bonjour", "text": "monde" }]),
+                |_| (),
+                || false,
+            )
+            .unwrap();
+        assert_eq!(result.indexed_documents, 1);
+        assert_eq!(result.number_of_documents, 2);
+        wtxn.commit().unwrap();
+
+        // both the new document and the existing one are indexed under the new settings.
+        let rtxn = index.read_txn().unwrap();
+        assert_eq!(index.searchable_fields(&rtxn).unwrap(), Some(vec!["text"]));
+        for word in ["world", "monde"] {
+            assert!(index.word_docids.get(&rtxn, word).unwrap().is_some(), "{}", word);
+        }
+        for word in ["hello", "bonjour"] {
+            assert!(index.word_docids.get(&rtxn, word).unwrap().is_none(), "{}", word);
+        }
+    }
+
+    #[test]
+    fn aborted_settings_and_documents() {
+        let index = TempIndex::new();
+        index.add_documents(documents!([{ "id": 0, "title": "hello" }])).unwrap();
+
+        // the batch is invalid, the settings changes are aborted with it.
+        let documents = documents_batch_reader_from_objects(vec![serde_json::Map::new()]);
+        let mut wtxn = index.write_txn().unwrap();
+        UpdateBuilder::new(&index.indexer_config)
+            .settings_and_documents(
+                &mut wtxn,
+                &index,
+                |settings| settings.set_searchable_fields(vec![S("text")]),
+                IndexDocumentsConfig::default(),
+                documents,
+                |_| (),
+                || false,
+            )
+            .unwrap_err();
+        wtxn.abort().unwrap();
+
+        let rtxn = index.read_txn().unwrap();
+        assert_eq!(index.searchable_fields(&rtxn).unwrap(), None);
+        assert!(index.word_docids.get(&rtxn, "hello").unwrap().is_some());
+    }
+}
@@ -1,13 +1,16 @@
 use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
 
 use grenad::CompressionType;
 use heed::types::{ByteSlice, Str};
 use heed::Database;
 
+use crate::index::db_name;
 use crate::update::index_documents::{
     create_sorter, merge_roaring_bitmaps, sorter_into_lmdb_database, valid_lmdb_key,
     CursorClonableMmap, MergeFn,
 };
+use crate::update::IndexingMetrics;
 use crate::{Result, RoaringBitmapCodec};
 
 pub struct WordPrefixDocids<'t, 'u, 'i> {
@@ -18,6 +21,9 @@ pub struct WordPrefixDocids<'t, 'u, 'i> {
     pub(crate) chunk_compression_level: Option<u32>,
     pub(crate) max_nb_chunks: Option<usize>,
     pub(crate) max_memory: Option<usize>,
+    /// The name under which the writes are reported to the `metrics`, see [`db_name`].
+    pub(crate) db_name: &'static str,
+    pub(crate) metrics: Option<Arc<dyn IndexingMetrics>>,
 }
 
 impl<'t, 'u, 'i> WordPrefixDocids<'t, 'u, 'i> {
@@ -34,10 +40,16 @@ impl<'t, 'u, 'i> WordPrefixDocids<'t, 'u, 'i> {
             chunk_compression_level: None,
             max_nb_chunks: None,
             max_memory: None,
+            db_name: db_name::WORD_PREFIX_DOCIDS,
+            metrics: None,
         }
     }
 
     #[logging_timer::time("WordPrefixDocids::{}")]
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "trace", name = "WordPrefixDocids::execute", skip_all)
+    )]
     pub fn execute(
         self,
         mut new_word_docids_iter: grenad::ReaderCursor<CursorClonableMmap>,
@@ -109,12 +121,15 @@ impl<'t, 'u, 'i> WordPrefixDocids<'t, 'u, 'i> {
         drop(iter);
 
         // We finally write the word prefix docids into the LMDB database.
-        sorter_into_lmdb_database(
+        let (entries, bytes) = sorter_into_lmdb_database(
             self.wtxn,
             *self.word_prefix_docids.as_polymorph(),
             prefix_docids_sorter,
             merge_roaring_bitmaps,
         )?;
+        if let Some(metrics) = &self.metrics {
+            metrics.on_db_written(self.db_name, entries, bytes);
+        }
 
         Ok(())
     }
@@ -1,5 +1,6 @@
 use std::collections::{HashMap, HashSet};
 use std::num::NonZeroU32;
+use std::sync::Arc;
 use std::{cmp, str};
 
 use grenad::CompressionType;
@@ -9,11 +10,13 @@ use log::debug;
 
 use crate::error::SerializationError;
 use crate::heed_codec::StrBEU32Codec;
+use crate::index::db_name;
 use crate::index::main_key::WORDS_PREFIXES_FST_KEY;
 use crate::update::index_documents::{
     create_sorter, merge_cbo_roaring_bitmaps, sorter_into_lmdb_database, valid_lmdb_key,
     CursorClonableMmap, MergeFn,
 };
+use crate::update::IndexingMetrics;
 use crate::{Index, Result};
 
 pub struct WordPrefixPositionDocids<'t, 'u, 'i> {
@@ -23,6 +26,7 @@ pub struct WordPrefixPositionDocids<'t, 'u, 'i> {
     pub(crate) chunk_compression_level: Option<u32>,
     pub(crate) max_nb_chunks: Option<usize>,
     pub(crate) max_memory: Option<usize>,
+    pub(crate) metrics: Option<Arc<dyn IndexingMetrics>>,
     level_group_size: NonZeroU32,
     min_level_size: NonZeroU32,
 }
@@ -39,6 +43,7 @@ impl<'t, 'u, 'i> WordPrefixPositionDocids<'t, 'u, 'i> {
             chunk_compression_level: None,
             max_nb_chunks: None,
             max_memory: None,
+            metrics: None,
             level_group_size: NonZeroU32::new(4).unwrap(),
             min_level_size: NonZeroU32::new(5).unwrap(),
         }
@@ -55,6 +60,10 @@ impl<'t, 'u, 'i> WordPrefixPositionDocids<'t, 'u, 'i> {
     }
 
     #[logging_timer::time("WordPrefixPositionDocids::{}")]
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "trace", name = "WordPrefixPositionDocids::execute", skip_all)
+    )]
     pub fn execute(
         self,
         new_word_position_docids: grenad::Reader<CursorClonableMmap>,
@@ -155,12 +164,15 @@ impl<'t, 'u, 'i> WordPrefixPositionDocids<'t, 'u, 'i> {
         }
 
         // We finally write all the word prefix position docids into the LMDB database.
-        sorter_into_lmdb_database(
+        let (entries, bytes) = sorter_into_lmdb_database(
             self.wtxn,
             *self.index.word_prefix_position_docids.as_polymorph(),
             prefix_position_docids_sorter,
             merge_cbo_roaring_bitmaps,
         )?;
+        if let Some(metrics) = &self.metrics {
+            metrics.on_db_written(db_name::WORD_PREFIX_POSITION_DOCIDS, entries, bytes);
+        }
 
         Ok(())
     }
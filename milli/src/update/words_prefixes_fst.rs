@@ -41,6 +41,10 @@ impl<'t, 'u, 'i> WordsPrefixesFst<'t, 'u, 'i> {
     }
 
     #[logging_timer::time("WordsPrefixesFst::{}")]
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "trace", name = "WordsPrefixesFst::execute", skip_all)
+    )]
     pub fn execute(self) -> Result<()> {
         let words_fst = self.index.words_fst(self.wtxn)?;
 
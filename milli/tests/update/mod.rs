@@ -0,0 +1 @@
+mod settings_and_documents;
@@ -0,0 +1,97 @@
+use std::collections::HashSet;
+use std::io::Cursor;
+
+use big_s::S;
+use heed::EnvOpenOptions;
+use maplit::hashset;
+use milli::documents::{DocumentsBatchBuilder, DocumentsBatchReader};
+use milli::update::{IndexDocuments, IndexDocumentsConfig, IndexerConfig, Settings};
+use milli::{Filter, Index, Object};
+use serde_json::{json, Value};
+
+fn documents_batch(documents: Value) -> DocumentsBatchReader<Cursor<Vec<u8>>> {
+    let mut builder = DocumentsBatchBuilder::new(Vec::new());
+    for document in documents.as_array().unwrap() {
+        let object: Object = serde_json::from_value(document.clone()).unwrap();
+        builder.append_json_object(&object).unwrap();
+    }
+    let vector = builder.into_inner().unwrap();
+    DocumentsBatchReader::from_reader(Cursor::new(vector)).unwrap()
+}
+
+/// Updates the settings and adds the documents in the same write transaction,
+/// the transaction is only committed if the documents are valid.
+fn update_settings_and_add_documents(
+    index: &Index,
+    update_settings: impl Fn(&mut Settings),
+    documents: Value,
+) -> bool {
+    let config = IndexerConfig::default();
+    let mut wtxn = index.write_txn().unwrap();
+
+    let mut settings = Settings::new(&mut wtxn, index, &config);
+    update_settings(&mut settings);
+    settings.execute(|_| (), || false).unwrap();
+
+    let indexing_config = IndexDocumentsConfig::default();
+    let builder =
+        IndexDocuments::new(&mut wtxn, index, &config, indexing_config, |_| (), || false).unwrap();
+    let (builder, user_error) = builder.add_documents(documents_batch(documents)).unwrap();
+    if user_error.is_err() {
+        // dropping the transaction aborts it
+        return false;
+    }
+    builder.execute().unwrap();
+    wtxn.commit().unwrap();
+
+    true
+}
+
+#[test]
+fn settings_and_documents_are_committed_atomically() {
+    let path = tempfile::tempdir().unwrap();
+    let mut options = EnvOpenOptions::new();
+    options.map_size(10 * 1024 * 1024); // 10 MB
+    let index = Index::new(options, &path).unwrap();
+
+    let committed = update_settings_and_add_documents(
+        &index,
+        |settings| {
+            settings.set_primary_key(S("id"));
+            settings.set_filterable_fields(hashset! { S("kind") });
+        },
+        json!([
+            { "id": 0, "kind": "cat", "price": 10 },
+            { "id": 1, "kind": "dog", "price": 20 },
+        ]),
+    );
+    assert!(committed);
+
+    // the documents have been indexed under the new settings
+    let rtxn = index.read_txn().unwrap();
+    assert_eq!(index.primary_key(&rtxn).unwrap(), Some("id"));
+    let filter = Filter::from_str("kind = cat").unwrap().unwrap();
+    assert_eq!(filter.evaluate(&rtxn, &index).unwrap().len(), 1);
+    drop(rtxn);
+
+    // the second document has an invalid id, the whole batch is rejected
+    let committed = update_settings_and_add_documents(
+        &index,
+        |settings| {
+            settings.set_filterable_fields(hashset! { S("kind"), S("price") });
+        },
+        json!([
+            { "id": 2, "kind": "bird", "price": 30 },
+            { "id": "invalid id!", "kind": "fish", "price": 40 },
+        ]),
+    );
+    assert!(!committed);
+
+    // and the previous settings are left intact
+    let rtxn = index.read_txn().unwrap();
+    let filterable_fields = index.filterable_fields(&rtxn).unwrap();
+    assert_eq!(filterable_fields, HashSet::from([S("kind")]));
+    assert_eq!(index.number_of_documents(&rtxn).unwrap(), 2);
+    let filter = Filter::from_str("price > 5").unwrap().unwrap();
+    assert!(filter.evaluate(&rtxn, &index).is_err());
+}